@@ -7,11 +7,12 @@ extern crate proc_macro;
 use proc_macro::TokenStream;
 
 use proc_macro2::TokenStream as TokenStream2;
-use quote::{TokenStreamExt, quote, quote_spanned};
+use quote::{ToTokens, TokenStreamExt, format_ident, quote, quote_spanned};
+use syn::punctuated::Punctuated;
 use syn::spanned::Spanned;
 use syn::{
-    Error, Expr, ExprLit, ExprPath, ItemFn, ItemStruct, Lit, Visibility, parse_macro_input,
-    parse_quote, parse_quote_spanned,
+    Error, Expr, ExprLit, ExprPath, FnArg, Ident, ItemFn, ItemStruct, ItemTrait, Lit, Meta, Pat,
+    Receiver, ReturnType, Token, TraitItem, parse_macro_input,
 };
 
 macro_rules! err {
@@ -61,8 +62,29 @@ macro_rules! err {
 #[proc_macro_attribute]
 pub fn unsafe_protocol(args: TokenStream, input: TokenStream) -> TokenStream {
     let expr = parse_macro_input!(args as Expr);
+    let guid_val = guid_value(&expr);
 
-    let guid_val = match expr {
+    let item_struct = parse_macro_input!(input as ItemStruct);
+
+    let ident = &item_struct.ident;
+    let (impl_generics, ty_generics, where_clause) = item_struct.generics.split_for_impl();
+
+    quote! {
+        #item_struct
+
+        unsafe impl #impl_generics ::uefi::Identify for #ident #ty_generics #where_clause {
+            const GUID: ::uefi::Guid = #guid_val;
+        }
+
+        impl #impl_generics ::uefi::proto::Protocol for #ident #ty_generics #where_clause {}
+    }
+    .into()
+}
+
+/// Converts the GUID argument of a protocol attribute to an expression of type
+/// `Guid`.
+fn guid_value(expr: &Expr) -> TokenStream2 {
+    match expr {
         Expr::Lit(ExprLit {
             lit: Lit::Str(lit), ..
         }) => {
@@ -73,36 +95,339 @@ pub fn unsafe_protocol(args: TokenStream, input: TokenStream) -> TokenStream {
             expr,
             "macro input must be either a string literal or path to a constant"
         ),
-    };
+    }
+}
 
-    let item_struct = parse_macro_input!(input as ItemStruct);
+/// Attribute macro for defining UEFI protocols with a trait.
+///
+/// The macro is applied to a trait and takes one argument, either a GUID
+/// string or the path to a `Guid` constant, like [`unsafe_protocol`]. For a
+/// trait `Foo`, it generates:
+/// * `FooTable`: the `#[repr(C)]` table of function pointers that is
+///   installed as the protocol interface. Each function receives a pointer to
+///   the table, followed by the arguments of the method.
+/// * `FooProtocol`: the [`Protocol`] for consumers, which is opened with the
+///   functions in `uefi::boot`. It has a method for each method of the trait
+///   that calls the function in the table.
+/// * `FooInterface<T>`: the producer of the protocol, which contains the
+///   table and an implementation `T` of the trait. Its `install` and
+///   `uninstall` methods install and uninstall it on a handle.
+///
+/// The methods of the trait must take `&self` or `&mut self`, must not be
+/// generic, and their arguments and return types must be FFI-safe, since
+/// they are passed through `efiapi` functions. They may be `unsafe`.
+///
+/// # Safety
+///
+/// The GUID must not be used by other protocols with a different function
+/// table. Consumers that open a protocol installed by other drivers rely on
+/// the table matching the trait.
+///
+/// # Example
+///
+/// ```
+/// use uefi::proto::uefi_protocol;
+/// use uefi::Status;
+///
+/// #[uefi_protocol("0d2e6ab0-5bdb-4dd3-a1d4-6b5b7e1b3f27")]
+/// pub trait Counter {
+///     /// Increments the counter and returns its new value.
+///     fn increment(&mut self) -> u32;
+///
+///     /// Resets the counter.
+///     fn reset(&mut self) -> Status;
+/// }
+///
+/// struct Simple(u32);
+///
+/// impl Counter for Simple {
+///     fn increment(&mut self) -> u32 {
+///         self.0 += 1;
+///         self.0
+///     }
+///
+///     fn reset(&mut self) -> Status {
+///         self.0 = 0;
+///         Status::SUCCESS
+///     }
+/// }
+///
+/// # fn install() -> uefi::Result {
+/// // The interface must stay in place while it's installed.
+/// let interface = Box::leak(Box::new(CounterInterface::new(Simple(0))));
+/// let handle = unsafe { interface.install(None) }?;
+///
+/// let mut counter = uefi::boot::open_protocol_exclusive::<CounterProtocol>(handle)?;
+/// assert_eq!(counter.increment(), 1);
+/// # Ok(())
+/// # }
+/// ```
+///
+/// [`Protocol`]: https://docs.rs/uefi/latest/uefi/proto/trait.Protocol.html
+#[proc_macro_attribute]
+pub fn uefi_protocol(args: TokenStream, input: TokenStream) -> TokenStream {
+    let expr = parse_macro_input!(args as Expr);
 
-    let ident = &item_struct.ident;
-    let (impl_generics, ty_generics, where_clause) = item_struct.generics.split_for_impl();
+    let item_trait = parse_macro_input!(input as ItemTrait);
+
+    let mut errors = TokenStream2::new();
+
+    if !item_trait.generics.params.is_empty() {
+        errors.append_all(err!(
+            item_trait.generics.params,
+            "Protocol trait should not be generic"
+        ));
+    }
+
+    let mut methods = Vec::new();
+    for item in &item_trait.items {
+        let TraitItem::Fn(method) = item else {
+            errors.append_all(err!(item, "Protocol trait may only contain methods"));
+            continue;
+        };
+        let sig = &method.sig;
+        if let Some(constness) = sig.constness {
+            errors.append_all(err!(constness, "Protocol method should not be const"));
+        }
+        if let Some(asyncness) = sig.asyncness {
+            errors.append_all(err!(asyncness, "Protocol method should not be async"));
+        }
+        if let Some(ref abi) = sig.abi {
+            errors.append_all(err!(abi, "Protocol method must have no ABI modifier"));
+        }
+        if !sig.generics.params.is_empty() {
+            errors.append_all(err!(
+                sig.generics.params,
+                "Protocol method should not be generic"
+            ));
+        }
+        if let Some(ref variadic) = sig.variadic {
+            errors.append_all(err!(variadic, "Protocol method should not be variadic"));
+        }
+        let mutable = match sig.receiver() {
+            Some(Receiver {
+                reference: Some(_),
+                mutability,
+                colon_token: None,
+                ..
+            }) => mutability.is_some(),
+            _ => {
+                errors.append_all(err!(
+                    sig,
+                    "Protocol method must take `&self` or `&mut self`"
+                ));
+                continue;
+            }
+        };
+        let args: Vec<_> = sig
+            .inputs
+            .iter()
+            .skip(1)
+            .enumerate()
+            .filter_map(|(i, arg)| match arg {
+                FnArg::Typed(arg) => {
+                    let name = match &*arg.pat {
+                        Pat::Ident(pat) => pat.ident.clone(),
+                        _ => format_ident!("arg{}", i),
+                    };
+                    Some((name, &*arg.ty))
+                }
+                FnArg::Receiver(_) => None,
+            })
+            .collect();
+        let docs: Vec<_> = method
+            .attrs
+            .iter()
+            .filter(|attr| attr.path().is_ident("doc"))
+            .collect();
+        methods.push((sig, mutable, args, docs));
+    }
+
+    // Show most errors all at once instead of one by one.
+    if !errors.is_empty() {
+        return errors.into();
+    }
+
+    let vis = &item_trait.vis;
+    let trait_ident = &item_trait.ident;
+    let table_ident = format_ident!("{}Table", trait_ident);
+    let protocol_ident = format_ident!("{}Protocol", trait_ident);
+    let interface_ident = format_ident!("{}Interface", trait_ident);
+
+    let mut table_fields = TokenStream2::new();
+    let mut table_init = TokenStream2::new();
+    let mut protocol_methods = TokenStream2::new();
+    let mut thunks = TokenStream2::new();
+    for (sig, mutable, args, docs) in methods {
+        let name = &sig.ident;
+        let thunk_name = format_ident!("__{}", name);
+        let unsafety = &sig.unsafety;
+        let output = &sig.output;
+        let arg_names: Vec<_> = args.iter().map(|(name, _)| name).collect();
+        let arg_types: Vec<_> = args.iter().map(|(_, ty)| ty).collect();
+        let (this_ty, receiver, this_arg, implementation) = if mutable {
+            (
+                quote!(*mut #table_ident),
+                quote!(&mut self),
+                quote!(&mut self.0),
+                quote!(&mut (*__this.cast::<Self>()).implementation),
+            )
+        } else {
+            (
+                quote!(*const #table_ident),
+                quote!(&self),
+                quote!(&self.0),
+                quote!(&(*__this.cast::<Self>()).implementation),
+            )
+        };
+
+        table_fields.append_all(quote! {
+            #(#docs)*
+            #vis #name: unsafe extern "efiapi" fn(
+                this: #this_ty,
+                #(#arg_names: #arg_types),*
+            ) #output,
+        });
+        table_init.append_all(quote!(#name: Self::#thunk_name,));
+        protocol_methods.append_all(quote! {
+            #(#docs)*
+            #vis #unsafety fn #name(#receiver, #(#arg_names: #arg_types),*) #output {
+                unsafe { (self.0.#name)(#this_arg, #(#arg_names),*) }
+            }
+        });
+        thunks.append_all(quote! {
+            unsafe extern "efiapi" fn #thunk_name(
+                __this: #this_ty,
+                #(#arg_names: #arg_types),*
+            ) #output {
+                // The table is the first field of the interface, see `new`.
+                let __implementation = unsafe { #implementation };
+                unsafe { <T as #trait_ident>::#name(__implementation, #(#arg_names),*) }
+            }
+        });
+    }
+
+    let table_doc = format!("Function table of the [`{trait_ident}`] protocol.");
+    let protocol_doc = format!(
+        "Consumer of the [`{trait_ident}`] protocol, which calls the functions of the \
+         [`{table_ident}`] installed by the producer."
+    );
+    let interface_doc =
+        format!("Producer of the [`{trait_ident}`] protocol, which implements it with `T`.");
 
     quote! {
-        #item_struct
+        #item_trait
 
-        unsafe impl #impl_generics ::uefi::Identify for #ident #ty_generics #where_clause {
-            const GUID: ::uefi::Guid = #guid_val;
+        #[doc = #table_doc]
+        #[derive(Clone, Copy, Debug)]
+        #[repr(C)]
+        #vis struct #table_ident {
+            #table_fields
         }
 
-        impl #impl_generics ::uefi::proto::Protocol for #ident #ty_generics #where_clause {}
+        #[doc = #protocol_doc]
+        #[derive(Debug)]
+        #[repr(transparent)]
+        #[::uefi::proto::unsafe_protocol(#expr)]
+        #vis struct #protocol_ident(#table_ident);
+
+        impl #protocol_ident {
+            #protocol_methods
+        }
+
+        #[doc = #interface_doc]
+        #[derive(Debug)]
+        #[repr(C)]
+        #vis struct #interface_ident<T> {
+            table: #table_ident,
+            implementation: T,
+        }
+
+        impl<T: #trait_ident> #interface_ident<T> {
+            /// Creates the protocol interface of `implementation`.
+            #vis const fn new(implementation: T) -> Self {
+                Self {
+                    table: #table_ident { #table_init },
+                    implementation,
+                }
+            }
+
+            /// Returns the implementation of the protocol.
+            #vis const fn implementation(&self) -> &T {
+                &self.implementation
+            }
+
+            /// Installs the interface on `handle`, or on a new handle if
+            /// `handle` is `None`, and returns the handle.
+            ///
+            /// # Safety
+            ///
+            /// Until the interface is uninstalled, it must not be moved or
+            /// dropped, and must only be accessed through the protocol.
+            ///
+            /// # Errors
+            ///
+            /// See `uefi::boot::install_protocol_interface`.
+            #vis unsafe fn install(
+                &mut self,
+                handle: ::core::option::Option<::uefi::Handle>,
+            ) -> ::uefi::Result<::uefi::Handle> {
+                unsafe {
+                    ::uefi::boot::install_protocol_interface(
+                        handle,
+                        &<#protocol_ident as ::uefi::Identify>::GUID,
+                        ::core::ptr::from_mut(self).cast::<::core::ffi::c_void>(),
+                    )
+                }
+            }
+
+            /// Uninstalls the interface from `handle`.
+            ///
+            /// # Safety
+            ///
+            /// There must be no references to the protocol left, see
+            /// `uefi::boot::uninstall_protocol_interface`.
+            ///
+            /// # Errors
+            ///
+            /// See `uefi::boot::uninstall_protocol_interface`.
+            #vis unsafe fn uninstall(&mut self, handle: ::uefi::Handle) -> ::uefi::Result {
+                unsafe {
+                    ::uefi::boot::uninstall_protocol_interface(
+                        handle,
+                        &<#protocol_ident as ::uefi::Identify>::GUID,
+                        ::core::ptr::from_mut(self).cast::<::core::ffi::c_void>(),
+                    )
+                }
+            }
+
+            #thunks
+        }
     }
     .into()
 }
 
 /// Custom attribute for a UEFI executable entry point.
 ///
-/// This attribute modifies a function to mark it as the entry point for
-/// a UEFI executable. The function:
-/// * Must return [`Status`].
-/// * Must have zero parameters.
+/// This attribute marks a function as the entry point for a UEFI
+/// executable. The function:
+/// * Must return [`Status`] or [`Result<()>`]. If it returns an error, the
+///   error is logged and its status is returned to the firmware.
+/// * Must have either zero parameters, or two parameters that receive the
+///   image handle and a pointer to the system table. The pointer can be of
+///   any raw pointer type, e.g. `*const c_void` or `*mut SystemTable`. This
+///   helps migrating code that used the handle and table passed to the
+///   entry point; new code should use [`boot::image_handle`] and the
+///   functions in `uefi::system` instead.
 /// * Can optionally be `unsafe`.
 ///
 /// The global system table pointer and global image handle will be set
 /// automatically.
 ///
+/// The attribute accepts these options:
+/// * `init`: Calls [`helpers::init`] before the function. If that fails, the
+///   entry point returns its error status.
+///
 /// # Examples
 ///
 /// ```no_run
@@ -116,22 +441,98 @@ pub fn unsafe_protocol(args: TokenStream, input: TokenStream) -> TokenStream {
 /// }
 /// ```
 ///
+/// Set up the helpers and return a [`Result<()>`]:
+///
+/// ```no_run
+/// #![no_main]
+///
+/// use uefi::prelude::*;
+///
+/// #[entry(init)]
+/// fn main() -> uefi::Result {
+///     boot::stall(core::time::Duration::from_secs(1));
+///     Ok(())
+/// }
+/// ```
+///
 /// [`Status`]: https://docs.rs/uefi/latest/uefi/struct.Status.html
+/// [`Result<()>`]: https://docs.rs/uefi/latest/uefi/type.Result.html
+/// [`boot::image_handle`]: https://docs.rs/uefi/latest/uefi/boot/fn.image_handle.html
+/// [`helpers::init`]: https://docs.rs/uefi/latest/uefi/helpers/fn.init.html
 #[proc_macro_attribute]
 pub fn entry(args: TokenStream, input: TokenStream) -> TokenStream {
+    entry_impl(args, input, false)
+}
+
+/// Custom attribute for the entry point of a resident UEFI driver.
+///
+/// The function must have the same signature as for [`entry`]. The driver
+/// stays loaded if the function succeeds, see the [`uefi::driver`] module
+/// for details.
+///
+/// The attribute accepts these options:
+/// * `init`: Calls [`driver::init_helpers`] before the function. The
+///   helpers are torn down if the function or the unload function succeeds.
+/// * `unload = path`: Registers the function at `path` as the unload
+///   function of the image. It must have no parameters and return
+///   [`Status`] or [`Result<()>`].
+///
+/// # Example
+///
+/// ```no_run
+/// #![no_main]
+///
+/// use uefi::prelude::*;
+/// use uefi::driver_entry;
+///
+/// #[driver_entry(init, unload = unload)]
+/// fn main() -> uefi::Result {
+///     Ok(())
+/// }
+///
+/// fn unload() -> Status {
+///     Status::SUCCESS
+/// }
+/// ```
+///
+/// [`entry`]: macro@entry
+/// [`Status`]: https://docs.rs/uefi/latest/uefi/struct.Status.html
+/// [`Result<()>`]: https://docs.rs/uefi/latest/uefi/type.Result.html
+/// [`uefi::driver`]: https://docs.rs/uefi/latest/uefi/driver/index.html
+/// [`driver::init_helpers`]: https://docs.rs/uefi/latest/uefi/driver/fn.init_helpers.html
+#[proc_macro_attribute]
+pub fn driver_entry(args: TokenStream, input: TokenStream) -> TokenStream {
+    entry_impl(args, input, true)
+}
+
+fn entry_impl(args: TokenStream, input: TokenStream, driver: bool) -> TokenStream {
     // This code is inspired by the approach in this embedded Rust crate:
     // https://github.com/rust-embedded/cortex-m-rt/blob/965bf1e3291571e7e3b34834864117dc020fb391/macros/src/lib.rs#L85
 
     let mut errors = TokenStream2::new();
 
-    if !args.is_empty() {
-        errors.append_all(err!(
-            TokenStream2::from(args),
-            "Entry attribute accepts no arguments"
-        ));
+    let options =
+        match syn::parse::Parser::parse(Punctuated::<Meta, Token![,]>::parse_terminated, args) {
+            Ok(options) => options,
+            Err(err) => return err.to_compile_error().into(),
+        };
+    let mut init = false;
+    let mut unload = None;
+    for option in &options {
+        match option {
+            Meta::Path(path) if path.is_ident("init") => init = true,
+            Meta::NameValue(option) if driver && option.path.is_ident("unload") => {
+                unload = Some(&option.value);
+            }
+            _ => errors.append_all(err!(
+                option,
+                "Unknown entry option `{}`",
+                option.path().to_token_stream()
+            )),
+        }
     }
 
-    let mut f = parse_macro_input!(input as ItemFn);
+    let f = parse_macro_input!(input as ItemFn);
 
     if let Some(ref abi) = f.sig.abi {
         errors.append_all(err!(abi, "Entry function must have no ABI modifier"));
@@ -148,8 +549,11 @@ pub fn entry(args: TokenStream, input: TokenStream) -> TokenStream {
             "Entry function should not be generic"
         ));
     }
-    if !f.sig.inputs.is_empty() {
-        errors.append_all(err!(f.sig.inputs, "Entry function must have no arguments"));
+    if !matches!(f.sig.inputs.len(), 0 | 2) {
+        errors.append_all(err!(
+            f.sig.inputs,
+            "Entry function must have no arguments, or the image handle and system table"
+        ));
     }
 
     // Show most errors all at once instead of one by one.
@@ -157,64 +561,185 @@ pub fn entry(args: TokenStream, input: TokenStream) -> TokenStream {
         return errors.into();
     }
 
-    let signature_span = f.sig.span();
+    let fn_ident = &f.sig.ident;
 
-    // Fill in the image handle and system table arguments automatically.
+    // Pass the image handle and system table pointer through if requested.
     let image_handle_ident = quote!(internal_image_handle);
     let system_table_ident = quote!(internal_system_table);
-    f.sig.inputs = parse_quote_spanned!(
-        signature_span=>
-            #image_handle_ident: ::uefi::Handle,
-            #system_table_ident: *const ::core::ffi::c_void,
-    );
+    let call_args = if f.sig.inputs.is_empty() {
+        quote!()
+    } else {
+        quote!(#image_handle_ident, #system_table_ident as _)
+    };
+    let mut call = quote_spanned!(f.sig.span()=> #fn_ident(#call_args));
+    if f.sig.unsafety.is_some() {
+        call = quote!(unsafe { #call });
+    }
 
-    // Insert code at the beginning of the entry function to set the global
-    // image handle and system table pointer.
-    f.block.stmts.insert(
-        0,
-        parse_quote! {
-            unsafe {
-                ::uefi::boot::set_image_handle(#image_handle_ident);
-                ::uefi::table::set_system_table(#system_table_ident.cast());
+    let init_helpers = match (init, driver) {
+        (true, false) => quote! {
+            if let ::core::result::Result::Err(err) = ::uefi::helpers::init() {
+                return err.status();
             }
         },
-    );
-
-    // Set the required ABI.
-    f.sig.abi = Some(parse_quote_spanned!(signature_span=> extern "efiapi"));
+        (true, true) => quote! {
+            if let ::core::result::Result::Err(err) = ::uefi::driver::init_helpers() {
+                return err.status();
+            }
+        },
+        (false, _) => quote!(),
+    };
 
-    // Strip any visibility modifiers.
-    f.vis = Visibility::Inherited;
+    // Register the unload function of a driver. It tears down the helpers
+    // if the driver can be unloaded.
+    let (unload_fn, set_unload) = match unload {
+        Some(unload) => (
+            quote! {
+                extern "efiapi" fn __uefi_unload(_image_handle: ::uefi::Handle) -> ::uefi::Status {
+                    let status = ::uefi::Termination::report(#unload());
+                    if !status.is_error() {
+                        ::uefi::driver::teardown();
+                    }
+                    status
+                }
+            },
+            quote! {
+                if let ::core::result::Result::Err(err) =
+                    unsafe { ::uefi::driver::set_unload(__uefi_unload) }
+                {
+                    return err.status();
+                }
+            },
+        ),
+        None => (quote!(), quote!()),
+    };
 
-    let unsafety = &f.sig.unsafety;
-    let fn_ident = &f.sig.ident;
-    let fn_output = &f.sig.output;
-
-    // Get the expected argument types for the main function.
-    let expected_args = quote!(::uefi::Handle, *const core::ffi::c_void);
-
-    let fn_type_check = quote_spanned! {signature_span=>
-        // Cast from the function type to a function pointer with the same
-        // signature first, then try to assign that to an unnamed constant with
-        // the desired function pointer type.
-        //
-        // The cast is used to avoid an "expected fn pointer, found fn item"
-        // error if the signature is wrong, since that's not what we are
-        // interested in here. Instead we want to tell the user what
-        // specifically in the function signature is incorrect.
-        const _:
-            // The expected fn pointer type.
-            #unsafety extern "efiapi" fn(#expected_args) -> ::uefi::Status =
-            // Cast from a fn item to a function pointer.
-            #fn_ident as #unsafety extern "efiapi" fn(#expected_args) #fn_output;
+    // Convert the return value to a `Status`, pointing at the return type if
+    // that's not possible.
+    let output_span = match &f.sig.output {
+        ReturnType::Default => f.sig.span(),
+        ReturnType::Type(_, ty) => ty.span(),
     };
+    let mut report = quote_spanned!(output_span=> ::uefi::Termination::report(result));
+    if driver {
+        // The firmware unloads a driver whose entry point fails.
+        report = quote! {
+            let status = #report;
+            if status.is_error() {
+                ::uefi::driver::teardown();
+            }
+            status
+        };
+    }
 
     let result = quote! {
-        #fn_type_check
-
-        #[unsafe(export_name = "efi_main")]
         #f
 
+        // The wrapper is placed in an unnamed constant so that its name can't
+        // conflict with other items.
+        const _: () = {
+            #[unsafe(export_name = "efi_main")]
+            extern "efiapi" fn __uefi_entry(
+                #image_handle_ident: ::uefi::Handle,
+                #system_table_ident: *const ::core::ffi::c_void,
+            ) -> ::uefi::Status {
+                // Set the global image handle and system table pointer.
+                unsafe {
+                    ::uefi::boot::set_image_handle(#image_handle_ident);
+                    ::uefi::table::set_system_table(#system_table_ident.cast());
+                }
+
+                #set_unload
+
+                #init_helpers
+
+                let result = #call;
+                #report
+            }
+
+            #unload_fn
+        };
     };
     result.into()
 }
+
+/// Attribute macro for registering a test function with the test harness.
+///
+/// The function must have no parameters and return `()`. It's registered
+/// as a `TestCase` named after its path, e.g. `my_app::tests::test_foo`, in
+/// a linker section of the image, from which `uefi::test_harness::test_cases`
+/// returns it.
+///
+/// The attribute accepts these options:
+/// * `should_panic`: The test passes if it panics, and fails otherwise.
+///
+/// # Example
+///
+/// ```
+/// use uefi::test_harness::uefi_test;
+///
+/// #[uefi_test]
+/// fn test_addition() {
+///     assert_eq!(1 + 1, 2);
+/// }
+/// ```
+#[proc_macro_attribute]
+pub fn uefi_test(args: TokenStream, input: TokenStream) -> TokenStream {
+    let mut errors = TokenStream2::new();
+
+    let options =
+        match syn::parse::Parser::parse(Punctuated::<Ident, Token![,]>::parse_terminated, args) {
+            Ok(options) => options,
+            Err(err) => return err.to_compile_error().into(),
+        };
+    let mut should_panic = false;
+    for option in &options {
+        if option == "should_panic" {
+            should_panic = true;
+        } else {
+            errors.append_all(err!(option, "Unknown test option `{}`", option));
+        }
+    }
+
+    let f = parse_macro_input!(input as ItemFn);
+
+    if let Some(asyncness) = f.sig.asyncness {
+        errors.append_all(err!(asyncness, "Test function should not be async"));
+    }
+    if !f.sig.generics.params.is_empty() {
+        errors.append_all(err!(
+            f.sig.generics.params,
+            "Test function should not be generic"
+        ));
+    }
+    if !f.sig.inputs.is_empty() {
+        errors.append_all(err!(f.sig.inputs, "Test function must have no arguments"));
+    }
+
+    // Show most errors all at once instead of one by one.
+    if !errors.is_empty() {
+        return errors.into();
+    }
+
+    let fn_ident = &f.sig.ident;
+    let test_case = quote_spanned! {f.sig.span()=>
+        ::uefi::test_harness::TestCase::new(
+            ::core::concat!(::core::module_path!(), "::", ::core::stringify!(#fn_ident)),
+            #fn_ident,
+            #should_panic,
+        )
+    };
+
+    quote! {
+        #f
+
+        // The test case is placed in an unnamed constant so that its name
+        // can't conflict with other items.
+        const _: () = {
+            #[used]
+            #[unsafe(link_section = ".uefitst$b")]
+            static TEST_CASE: ::uefi::test_harness::TestCase = #test_case;
+        };
+    }
+    .into()
+}
@@ -0,0 +1,13 @@
+#![no_main]
+
+use uefi::prelude::*;
+
+// Only drivers can be unloaded.
+#[entry(unload = unload)]
+fn main() -> Status {
+    Status::SUCCESS
+}
+
+fn unload() -> Status {
+    Status::SUCCESS
+}
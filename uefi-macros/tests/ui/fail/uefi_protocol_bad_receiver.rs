@@ -0,0 +1,10 @@
+use uefi::proto::uefi_protocol;
+
+#[uefi_protocol("0d2e6ab0-5bdb-4dd3-a1d4-6b5b7e1b3f27")]
+trait Counter {
+    fn get(self) -> u32;
+
+    fn new() -> Self;
+}
+
+fn main() {}
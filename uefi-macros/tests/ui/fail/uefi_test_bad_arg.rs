@@ -0,0 +1,6 @@
+use uefi::test_harness::uefi_test;
+
+#[uefi_test(ignore)]
+fn test_addition(_x: usize) {}
+
+fn main() {}
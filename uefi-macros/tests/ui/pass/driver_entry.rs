@@ -0,0 +1,13 @@
+use uefi::{Status, driver_entry};
+
+#[driver_entry(init, unload = unload)]
+fn efi_main() -> uefi::Result {
+    Ok(())
+}
+
+fn unload() -> Status {
+    Status::SUCCESS
+}
+
+// trybuild requires a `main` function.
+fn main() {}
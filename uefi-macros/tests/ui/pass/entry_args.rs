@@ -0,0 +1,10 @@
+use core::ffi::c_void;
+use uefi::{entry, Handle, Status};
+
+#[entry]
+fn efi_main(_image: Handle, _system_table: *const c_void) -> Status {
+    Status::SUCCESS
+}
+
+// trybuild requires a `main` function.
+fn main() {}
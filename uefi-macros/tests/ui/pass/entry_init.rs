@@ -0,0 +1,9 @@
+use uefi::{entry, Status};
+
+#[entry(init)]
+fn efi_main() -> Status {
+    Status::SUCCESS
+}
+
+// trybuild requires a `main` function.
+fn main() {}
@@ -0,0 +1,9 @@
+use uefi::{entry, Status};
+
+#[entry]
+fn efi_main() -> uefi::Result {
+    Err(Status::ABORTED.into())
+}
+
+// trybuild requires a `main` function.
+fn main() {}
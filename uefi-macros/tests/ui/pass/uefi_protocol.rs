@@ -0,0 +1,50 @@
+use core::ptr;
+use uefi::proto::uefi_protocol;
+use uefi::{Identify, Status, guid};
+
+#[uefi_protocol("0d2e6ab0-5bdb-4dd3-a1d4-6b5b7e1b3f27")]
+pub trait Counter {
+    /// Returns the value of the counter.
+    fn get(&self) -> u32;
+
+    /// Adds `value` to the counter.
+    fn add(&mut self, value: u32) -> Status;
+
+    /// Reads the counter through `out`.
+    unsafe fn read(&self, out: *mut u32);
+}
+
+struct Simple(u32);
+
+impl Counter for Simple {
+    fn get(&self) -> u32 {
+        self.0
+    }
+
+    fn add(&mut self, value: u32) -> Status {
+        self.0 += value;
+        Status::SUCCESS
+    }
+
+    unsafe fn read(&self, out: *mut u32) {
+        unsafe { out.write(self.0) }
+    }
+}
+
+fn main() {
+    assert_eq!(
+        CounterProtocol::GUID,
+        guid!("0d2e6ab0-5bdb-4dd3-a1d4-6b5b7e1b3f27")
+    );
+
+    // Call the implementation through the function table, like a consumer
+    // that opened the installed interface.
+    let mut interface = CounterInterface::new(Simple(1));
+    let protocol = unsafe { &mut *ptr::from_mut(&mut interface).cast::<CounterProtocol>() };
+    assert_eq!(protocol.add(2), Status::SUCCESS);
+    assert_eq!(protocol.get(), 3);
+    let mut value = 0;
+    unsafe { protocol.read(&mut value) };
+    assert_eq!(value, 3);
+    assert_eq!(interface.implementation().0, 3);
+}
@@ -0,0 +1,14 @@
+use uefi::test_harness::uefi_test;
+
+#[uefi_test]
+fn test_addition() {
+    assert_eq!(1 + 1, 2);
+}
+
+#[uefi_test(should_panic)]
+fn test_panic() {
+    panic!("expected");
+}
+
+// trybuild requires a `main` function.
+fn main() {}
@@ -0,0 +1,213 @@
+// SPDX-License-Identifier: MIT OR Apache-2.0
+
+//! Key Management Service (KMS) protocol.
+
+use crate::{Boolean, Char16, Guid, Status, guid};
+use core::ffi::c_void;
+
+newtype_enum! {
+    /// Format of a key managed by a [`KmsProtocol`].
+    pub enum KmsKeyFormat: Guid => {
+        /// Generic 128-bit key.
+        GENERIC_128 = guid!("ec8a3d69-6ddf-4108-9476-7337fc522136"),
+        /// Generic 160-bit key.
+        GENERIC_160 = guid!("a3b3e6f8-efca-4bc1-88fb-cb87339b2579"),
+        /// Generic 256-bit key.
+        GENERIC_256 = guid!("70f64793-c323-4261-ac2c-d876f27c5345"),
+        /// Generic 512-bit key.
+        GENERIC_512 = guid!("978fe043-d7af-422e-8a92-2b48e463bde6"),
+        /// Generic 1024-bit key.
+        GENERIC_1024 = guid!("43be0b44-874b-4ead-b09c-241a4fbd7eb3"),
+        /// Generic 2048-bit key.
+        GENERIC_2048 = guid!("40093f23-630c-4626-9c48-40373b19cbbe"),
+        /// Generic 3072-bit key.
+        GENERIC_3072 = guid!("b9237513-6c44-4411-a990-21e556e05ade"),
+    }
+}
+
+newtype_enum! {
+    /// Type of the data of a client name or attribute identifier. The
+    /// supported types are reported as a bitmask.
+    pub enum KmsDataType: u8 => {
+        NONE = 0,
+        BINARY = 1,
+        ASCII = 2,
+        UNICODE = 4,
+        UTF8 = 8,
+    }
+}
+
+newtype_enum! {
+    /// Type of the value of a [`KmsKeyAttribute`].
+    pub enum KmsAttributeType: u16 => {
+        NONE = 0x00,
+        INTEGER = 0x01,
+        LONG_INTEGER = 0x02,
+        BIG_INTEGER = 0x03,
+        ENUMERATION = 0x04,
+        BOOLEAN = 0x05,
+        BYTE_STRING = 0x06,
+        TEXT_STRING = 0x07,
+        DATE_TIME = 0x08,
+        INTERVAL = 0x09,
+        STRUCTURE = 0x0a,
+        DYNAMIC = 0x0b,
+    }
+}
+
+/// Identity of a client of a [`KmsProtocol`].
+#[derive(Debug)]
+#[repr(C)]
+pub struct KmsClientInfo {
+    pub client_id_size: u16,
+    pub client_id: *mut c_void,
+    pub client_name_type: KmsDataType,
+    pub client_name_count: u8,
+    pub client_name: *mut c_void,
+}
+
+/// Description of a key.
+#[derive(Debug)]
+#[repr(C)]
+pub struct KmsKeyDescriptor {
+    pub key_identifier_size: u8,
+    pub key_identifier: *mut c_void,
+    pub key_format: KmsKeyFormat,
+    pub key_value: *mut c_void,
+    pub key_status: Status,
+}
+
+/// Attribute of a key.
+#[derive(Debug)]
+#[repr(C)]
+pub struct KmsKeyAttribute {
+    pub key_attribute_identifier_type: KmsDataType,
+    pub key_attribute_identifier_count: u16,
+    pub key_attribute_identifier: *mut c_void,
+    pub key_attribute_instance: u16,
+    pub key_attribute_type: KmsAttributeType,
+    pub key_attribute_value_size: u16,
+    pub key_attribute_value: *mut c_void,
+    pub key_attribute_status: Status,
+}
+
+/// Key Management Service protocol.
+#[derive(Debug)]
+#[repr(C)]
+pub struct KmsProtocol {
+    pub get_service_status: unsafe extern "efiapi" fn(this: *mut Self) -> Status,
+
+    pub register_client: unsafe extern "efiapi" fn(
+        this: *mut Self,
+        client: *mut KmsClientInfo,
+        client_data_size: *mut usize,
+        client_data: *mut *mut c_void,
+    ) -> Status,
+
+    pub create_key: unsafe extern "efiapi" fn(
+        this: *mut Self,
+        client: *mut KmsClientInfo,
+        key_descriptor_count: *mut u16,
+        key_descriptors: *mut KmsKeyDescriptor,
+        client_data_size: *mut usize,
+        client_data: *mut *mut c_void,
+    ) -> Status,
+
+    pub get_key: unsafe extern "efiapi" fn(
+        this: *mut Self,
+        client: *mut KmsClientInfo,
+        key_descriptor_count: *mut u16,
+        key_descriptors: *mut KmsKeyDescriptor,
+        client_data_size: *mut usize,
+        client_data: *mut *mut c_void,
+    ) -> Status,
+
+    pub add_key: unsafe extern "efiapi" fn(
+        this: *mut Self,
+        client: *mut KmsClientInfo,
+        key_descriptor_count: *mut u16,
+        key_descriptors: *mut KmsKeyDescriptor,
+        client_data_size: *mut usize,
+        client_data: *mut *mut c_void,
+    ) -> Status,
+
+    pub delete_key: unsafe extern "efiapi" fn(
+        this: *mut Self,
+        client: *mut KmsClientInfo,
+        key_descriptor_count: *mut u16,
+        key_descriptors: *mut KmsKeyDescriptor,
+        client_data_size: *mut usize,
+        client_data: *mut *mut c_void,
+    ) -> Status,
+
+    pub get_key_attributes: unsafe extern "efiapi" fn(
+        this: *mut Self,
+        client: *mut KmsClientInfo,
+        key_identifier_size: *mut u8,
+        key_identifier: *const c_void,
+        key_attributes_count: *mut u16,
+        key_attributes: *mut KmsKeyAttribute,
+        client_data_size: *mut usize,
+        client_data: *mut *mut c_void,
+    ) -> Status,
+
+    pub add_key_attributes: unsafe extern "efiapi" fn(
+        this: *mut Self,
+        client: *mut KmsClientInfo,
+        key_identifier_size: *mut u8,
+        key_identifier: *const c_void,
+        key_attributes_count: *mut u16,
+        key_attributes: *mut KmsKeyAttribute,
+        client_data_size: *mut usize,
+        client_data: *mut *mut c_void,
+    ) -> Status,
+
+    pub delete_key_attributes: unsafe extern "efiapi" fn(
+        this: *mut Self,
+        client: *mut KmsClientInfo,
+        key_identifier_size: *mut u8,
+        key_identifier: *const c_void,
+        key_attributes_count: *mut u16,
+        key_attributes: *mut KmsKeyAttribute,
+        client_data_size: *mut usize,
+        client_data: *mut *mut c_void,
+    ) -> Status,
+
+    pub get_key_by_attributes: unsafe extern "efiapi" fn(
+        this: *mut Self,
+        client: *mut KmsClientInfo,
+        key_attribute_count: *mut usize,
+        key_attributes: *mut KmsKeyAttribute,
+        key_descriptor_count: *mut usize,
+        key_descriptors: *mut KmsKeyDescriptor,
+        client_data_size: *mut usize,
+        client_data: *mut *mut c_void,
+    ) -> Status,
+
+    pub protocol_version: u32,
+    pub service_id: Guid,
+    pub service_name: *mut Char16,
+    pub service_version: u32,
+    pub service_available: Boolean,
+    pub client_id_supported: Boolean,
+    pub client_id_required: Boolean,
+    pub client_id_max_size: u16,
+    pub client_name_string_types: u8,
+    pub client_name_required: Boolean,
+    pub client_name_max_count: u16,
+    pub client_data_supported: Boolean,
+    pub client_data_max_size: usize,
+    pub key_id_variable_len_supported: Boolean,
+    pub key_id_max_size: usize,
+    pub key_formats_count: usize,
+    pub key_formats: *mut KmsKeyFormat,
+    pub key_attributes_supported: Boolean,
+    pub key_attribute_id_string_types: u8,
+    pub key_attribute_id_max_count: u16,
+    pub key_attributes_count: usize,
+    pub key_attributes: *mut KmsKeyAttribute,
+}
+
+impl KmsProtocol {
+    pub const GUID: Guid = guid!("ec3a978d-7c4e-48fa-9abe-6ad91cc8f811");
+}
@@ -0,0 +1,43 @@
+// SPDX-License-Identifier: MIT OR Apache-2.0
+
+use crate::{Boolean, Guid, PhysicalAddress, Status, guid};
+
+newtype_enum! {
+    /// How thoroughly the [`GenericMemoryTestProtocol`] tests memory.
+    #[derive(Default)]
+    pub enum ExtendedMemoryCoverageLevel: u32 => {
+        IGNORE = 0,
+        QUICK = 1,
+        SPARSE = 2,
+        EXTENSIVE = 3,
+        MAX_LEVEL = 4,
+    }
+}
+
+/// Protocol of the platform memory test, as provided by EDK2-based firmware.
+#[derive(Debug)]
+#[repr(C)]
+pub struct GenericMemoryTestProtocol {
+    pub memory_test_init: unsafe extern "efiapi" fn(
+        this: *mut Self,
+        level: ExtendedMemoryCoverageLevel,
+        require_soft_ecc_init: *mut Boolean,
+    ) -> Status,
+    pub perform_memory_test: unsafe extern "efiapi" fn(
+        this: *mut Self,
+        tested_memory_size: *mut u64,
+        total_memory_size: *mut u64,
+        error_out: *mut Boolean,
+        if_test_abort: Boolean,
+    ) -> Status,
+    pub finished: unsafe extern "efiapi" fn(this: *mut Self) -> Status,
+    pub compatible_range_test: unsafe extern "efiapi" fn(
+        this: *mut Self,
+        start_address: PhysicalAddress,
+        length: u64,
+    ) -> Status,
+}
+
+impl GenericMemoryTestProtocol {
+    pub const GUID: Guid = guid!("309de7f1-7f5e-4ace-b49c-531be5aa95ef");
+}
@@ -15,9 +15,11 @@ pub mod driver;
 pub mod file_system;
 pub mod firmware_volume;
 pub mod hii;
+pub mod kms;
 pub mod loaded_image;
 pub mod media;
 pub mod memory_protection;
+pub mod memory_test;
 pub mod misc;
 pub mod network;
 pub mod nvme;
@@ -26,6 +28,7 @@ pub mod rng;
 pub mod scsi;
 pub mod shell;
 pub mod shell_params;
+pub mod status_code;
 pub mod string;
 pub mod tcg;
 pub mod usb;
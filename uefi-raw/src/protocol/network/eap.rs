@@ -0,0 +1,78 @@
+// SPDX-License-Identifier: MIT OR Apache-2.0
+
+//! EAP configuration protocol.
+
+use crate::{Guid, Status, guid};
+use core::ffi::c_void;
+
+newtype_enum! {
+    /// EAP method type, as assigned by IANA.
+    pub enum EapType: u8 => {
+        /// Used for the data types that are not specific to a method.
+        ATTRIBUTE = 0,
+        IDENTITY = 1,
+        NOTIFICATION = 2,
+        NAK = 3,
+        MD5_CHALLENGE = 4,
+        OTP = 5,
+        GTC = 6,
+        EAP_TLS = 13,
+        EAP_SIM = 18,
+        TTLS = 21,
+        PEAP = 25,
+        MSCHAPV2 = 26,
+        EAP_EXTENSION = 33,
+    }
+}
+
+newtype_enum! {
+    /// Type of the data of an [`EapConfigurationProtocol`].
+    pub enum EapConfigDataType: i32 => {
+        /// [`EapType`] to use for authentication.
+        EAP_AUTH_METHOD = 0,
+        /// Array of the supported [`EapType`]s. Read-only.
+        EAP_SUPPORTED_AUTH_METHOD = 1,
+        /// Identity, as an ASCII string.
+        IDENTITY_STRING = 2,
+        /// CA certificate.
+        EAP_TLS_CA_CERT = 3,
+        /// Client certificate.
+        EAP_TLS_CLIENT_CERT = 4,
+        /// Private key of the client certificate.
+        EAP_TLS_CLIENT_PRIVATE_KEY_FILE = 5,
+        /// Password of the private key, as an ASCII string.
+        EAP_TLS_CLIENT_PRIVATE_KEY_FILE_PASSWORD = 6,
+        /// TLS cipher suites to use.
+        EAP_TLS_CIPHER_SUITE = 7,
+        /// Supported TLS cipher suites. Read-only.
+        EAP_TLS_SUPPORTED_CIPHER_SUITE = 8,
+        /// MSCHAPv2 password, as a null-terminated UCS-2 string.
+        EAP_MSCHAPV2_PASSWORD = 9,
+        /// [`EapType`] of the inner authentication of PEAP or TTLS.
+        EAP_2ND_AUTH_METHOD = 10,
+    }
+}
+
+#[derive(Debug)]
+#[repr(C)]
+pub struct EapConfigurationProtocol {
+    pub set_data: unsafe extern "efiapi" fn(
+        this: *mut Self,
+        eap_type: EapType,
+        data_type: EapConfigDataType,
+        data: *const c_void,
+        data_size: usize,
+    ) -> Status,
+
+    pub get_data: unsafe extern "efiapi" fn(
+        this: *mut Self,
+        eap_type: EapType,
+        data_type: EapConfigDataType,
+        data: *mut c_void,
+        data_size: *mut usize,
+    ) -> Status,
+}
+
+impl EapConfigurationProtocol {
+    pub const GUID: Guid = guid!("e5b58dbb-7688-44b4-97bf-5f1d4b7cc8db");
+}
@@ -240,3 +240,30 @@ impl HttpProtocol {
     pub const GUID: Guid = guid!("7a59b29b-910b-4171-8242-a85a0df25b5b");
     pub const SERVICE_BINDING_GUID: Guid = guid!("bdc8e6af-d9bc-4379-a72a-e0c4e75dae1c");
 }
+
+#[derive(Debug)]
+#[repr(C)]
+pub struct HttpUtilitiesProtocol {
+    pub build: unsafe extern "efiapi" fn(
+        this: *const Self,
+        seed_message_size: usize,
+        seed_message: *const c_void,
+        delete_count: usize,
+        delete_list: *const *const Char8,
+        append_count: usize,
+        append_list: *const *const HttpHeader,
+        new_message_size: *mut usize,
+        new_message: *mut *mut c_void,
+    ) -> Status,
+    pub parse: unsafe extern "efiapi" fn(
+        this: *const Self,
+        http_message: *const Char8,
+        http_message_size: usize,
+        header_fields: *mut *mut HttpHeader,
+        field_count: *mut usize,
+    ) -> Status,
+}
+
+impl HttpUtilitiesProtocol {
+    pub const GUID: Guid = guid!("3e35c163-4074-45dd-431e-23989dd86b32");
+}
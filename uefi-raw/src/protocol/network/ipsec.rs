@@ -0,0 +1,343 @@
+// SPDX-License-Identifier: MIT OR Apache-2.0
+
+//! IPsec configuration protocol.
+
+use crate::{Boolean, Event, Guid, IpAddress, Status, guid};
+use core::ffi::c_void;
+use core::fmt::{self, Debug, Formatter};
+
+/// Maximum length of an [`IpsecSpdData`] name or an [`IpsecPadId`] peer ID.
+pub const MAX_PEERID_LEN: usize = 128;
+
+newtype_enum! {
+    /// Type of the data of an [`IpsecConfigProtocol`].
+    pub enum IpsecConfigDataType: i32 => {
+        /// Security Policy Database.
+        SPD = 0,
+        /// Security Association Database.
+        SAD = 1,
+        /// Peer Authorization Database.
+        PAD = 2,
+    }
+}
+
+newtype_enum! {
+    /// Direction of the traffic a security policy applies to.
+    pub enum IpsecTrafficDirection: i32 => {
+        OUTBOUND = 0,
+        INBOUND = 1,
+    }
+}
+
+newtype_enum! {
+    /// Action for the traffic that matches a security policy.
+    pub enum IpsecAction: i32 => {
+        DISCARD = 0,
+        BYPASS = 1,
+        PROTECT = 2,
+    }
+}
+
+newtype_enum! {
+    /// IPsec mode.
+    pub enum IpsecMode: i32 => {
+        TRANSPORT = 0,
+        TUNNEL = 1,
+    }
+}
+
+newtype_enum! {
+    /// Handling of the Don't Fragment bit of the outer header in tunnel
+    /// mode.
+    pub enum IpsecTunnelDfOption: i32 => {
+        CLEAR_DF = 0,
+        SET_DF = 1,
+        COPY_DF = 2,
+    }
+}
+
+newtype_enum! {
+    /// IPsec security protocol.
+    pub enum IpsecProtocolType: i32 => {
+        /// Authentication Header.
+        AH = 0,
+        /// Encapsulating Security Payload.
+        ESP = 1,
+    }
+}
+
+newtype_enum! {
+    /// Authentication algorithm of a security association.
+    pub enum IpsecAuthAlgorithm: u8 => {
+        NONE = 0,
+        MD5_HMAC = 2,
+        SHA1_HMAC = 3,
+        SHA2_256_HMAC = 5,
+        SHA2_384_HMAC = 6,
+        SHA2_512_HMAC = 7,
+    }
+}
+
+newtype_enum! {
+    /// Encryption algorithm of a security association.
+    pub enum IpsecEncryptAlgorithm: u8 => {
+        NONE = 0,
+        DES_CBC = 2,
+        TRIPLE_DES_CBC = 3,
+        CAST_CBC = 6,
+        BLOWFISH_CBC = 7,
+        NULL = 11,
+        AES_CBC = 12,
+        AES_CTR = 13,
+    }
+}
+
+newtype_enum! {
+    /// Protocol used to authenticate a peer.
+    pub enum IpsecAuthProtocolType: i32 => {
+        IKEV1 = 0,
+        IKEV2 = 1,
+    }
+}
+
+newtype_enum! {
+    /// Method used to authenticate a peer.
+    pub enum IpsecAuthMethod: i32 => {
+        PRE_SHARED_SECRET = 0,
+        CERTIFICATES = 1,
+    }
+}
+
+/// IP address with a prefix length.
+#[derive(Clone, Copy, Debug)]
+#[repr(C)]
+pub struct IpAddressInfo {
+    pub address: IpAddress,
+    pub prefix_length: u8,
+}
+
+/// Selector of an SPD entry.
+#[derive(Debug)]
+#[repr(C)]
+pub struct IpsecSpdSelector {
+    pub local_address_count: u32,
+    pub local_address: *mut IpAddressInfo,
+    pub remote_address_count: u32,
+    pub remote_address: *mut IpAddressInfo,
+    pub next_layer_protocol: u16,
+    pub local_port: u16,
+    pub local_port_range: u16,
+    pub remote_port: u16,
+    pub remote_port_range: u16,
+}
+
+/// Lifetime of a security association.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+#[repr(C)]
+pub struct IpsecSaLifetime {
+    pub byte_count: u64,
+    pub soft_lifetime: u64,
+    pub hard_lifetime: u64,
+}
+
+/// Tunnel endpoints of a security policy in tunnel mode.
+#[derive(Clone, Copy, Debug)]
+#[repr(C)]
+pub struct IpsecTunnelOption {
+    pub local_tunnel_address: IpAddress,
+    pub remote_tunnel_address: IpAddress,
+    pub df: IpsecTunnelDfOption,
+}
+
+/// Processing policy of an SPD entry with the [`IpsecAction::PROTECT`]
+/// action.
+#[derive(Debug)]
+#[repr(C)]
+pub struct IpsecProcessPolicy {
+    pub ext_seq_num: Boolean,
+    pub seq_overflow: Boolean,
+    pub frag_check: Boolean,
+    pub mode: IpsecMode,
+    pub tunnel_option: *mut IpsecTunnelOption,
+    pub proto: IpsecProtocolType,
+    pub auth_algo_id: IpsecAuthAlgorithm,
+    pub enc_algo_id: IpsecEncryptAlgorithm,
+}
+
+/// Identifier of a security association, which is the selector of an SAD
+/// entry.
+#[derive(Clone, Copy, Debug)]
+#[repr(C)]
+pub struct IpsecSaId {
+    pub spi: u32,
+    pub proto: IpsecProtocolType,
+    pub dest_address: IpAddress,
+}
+
+/// Data of an SPD entry.
+#[derive(Debug)]
+#[repr(C)]
+pub struct IpsecSpdData {
+    pub name: [u8; MAX_PEERID_LEN],
+    pub package_flag: u32,
+    pub traffic_direction: IpsecTrafficDirection,
+    pub action: IpsecAction,
+    pub processing_policy: *mut IpsecProcessPolicy,
+    pub sa_id_count: usize,
+    /// First element of an array of `sa_id_count` elements.
+    pub sa_id: [IpsecSaId; 1],
+}
+
+/// Algorithm and key of the Authentication Header.
+#[derive(Clone, Copy, Debug)]
+#[repr(C)]
+pub struct IpsecAhAlgoInfo {
+    pub auth_algo_id: IpsecAuthAlgorithm,
+    pub auth_key_length: usize,
+    pub auth_key: *mut c_void,
+}
+
+/// Algorithms and keys of the Encapsulating Security Payload.
+#[derive(Clone, Copy, Debug)]
+#[repr(C)]
+pub struct IpsecEspAlgoInfo {
+    pub enc_algo_id: IpsecEncryptAlgorithm,
+    pub enc_key_length: usize,
+    pub enc_key: *mut c_void,
+    pub auth_algo_id: IpsecAuthAlgorithm,
+    pub auth_key_length: usize,
+    pub auth_key: *mut c_void,
+}
+
+/// Algorithms and keys of a security association, depending on its
+/// [`IpsecProtocolType`].
+#[derive(Clone, Copy)]
+#[repr(C)]
+pub union IpsecAlgoInfo {
+    pub ah_algo_info: IpsecAhAlgoInfo,
+    pub esp_algo_info: IpsecEspAlgoInfo,
+}
+
+impl Debug for IpsecAlgoInfo {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        // The active member depends on the protocol of the SA.
+        f.debug_struct("IpsecAlgoInfo").finish()
+    }
+}
+
+/// Data of an SAD entry.
+#[derive(Debug)]
+#[repr(C)]
+pub struct IpsecSaData {
+    pub mode: IpsecMode,
+    pub sn_count: u64,
+    pub anti_replay_window: u8,
+    pub algo_info: IpsecAlgoInfo,
+    pub sa_lifetime: IpsecSaLifetime,
+    pub path_mtu: u32,
+    pub spd_selector: *mut IpsecSpdSelector,
+    pub manual_set: Boolean,
+}
+
+/// Data of an SAD entry, with the tunnel endpoints. This is the type used by
+/// current implementations.
+#[derive(Debug)]
+#[repr(C)]
+pub struct IpsecSaData2 {
+    pub mode: IpsecMode,
+    pub sn_count: u64,
+    pub anti_replay_window: u8,
+    pub algo_info: IpsecAlgoInfo,
+    pub sa_lifetime: IpsecSaLifetime,
+    pub path_mtu: u32,
+    pub spd_selector: *mut IpsecSpdSelector,
+    pub manual_set: Boolean,
+    pub tunnel_source_address: IpAddress,
+    pub tunnel_destination_address: IpAddress,
+}
+
+/// Identifier of a peer, by address or by peer ID.
+#[derive(Clone, Copy)]
+#[repr(C)]
+pub union IpsecPadIdValue {
+    pub ip_address: IpAddressInfo,
+    pub peer_id: [u8; MAX_PEERID_LEN],
+}
+
+impl Debug for IpsecPadIdValue {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        // The active member is given by `IpsecPadId::peer_id_valid`.
+        f.debug_struct("IpsecPadIdValue").finish()
+    }
+}
+
+/// Identifier of a peer, which is the selector of a PAD entry.
+#[derive(Clone, Copy, Debug)]
+#[repr(C)]
+pub struct IpsecPadId {
+    /// Whether `id` is a peer ID rather than an address.
+    pub peer_id_valid: Boolean,
+    pub id: IpsecPadIdValue,
+}
+
+/// Data of a PAD entry.
+#[derive(Debug)]
+#[repr(C)]
+pub struct IpsecPadData {
+    pub auth_protocol: IpsecAuthProtocolType,
+    pub auth_method: IpsecAuthMethod,
+    pub ike_id_flag: Boolean,
+    pub auth_data_size: usize,
+    pub auth_data: *mut c_void,
+    pub revocation_data_size: usize,
+    pub revocation_data: *mut c_void,
+}
+
+/// IPsec configuration protocol.
+///
+/// The selector and the data passed to the functions are an
+/// [`IpsecSpdSelector`] and an [`IpsecSpdData`], an [`IpsecSaId`] and an
+/// [`IpsecSaData2`], or an [`IpsecPadId`] and an [`IpsecPadData`],
+/// depending on the [`IpsecConfigDataType`].
+#[derive(Debug)]
+#[repr(C)]
+pub struct IpsecConfigProtocol {
+    pub set_data: unsafe extern "efiapi" fn(
+        this: *const Self,
+        data_type: IpsecConfigDataType,
+        selector: *const c_void,
+        data: *const c_void,
+        insert_before: *const c_void,
+    ) -> Status,
+
+    pub get_data: unsafe extern "efiapi" fn(
+        this: *const Self,
+        data_type: IpsecConfigDataType,
+        selector: *const c_void,
+        data_size: *mut usize,
+        data: *mut c_void,
+    ) -> Status,
+
+    pub get_next_selector: unsafe extern "efiapi" fn(
+        this: *const Self,
+        data_type: IpsecConfigDataType,
+        selector_size: *mut usize,
+        selector: *mut c_void,
+    ) -> Status,
+
+    pub register_data_notify: unsafe extern "efiapi" fn(
+        this: *const Self,
+        data_type: IpsecConfigDataType,
+        event: Event,
+    ) -> Status,
+
+    pub unregister_data_notify: unsafe extern "efiapi" fn(
+        this: *const Self,
+        data_type: IpsecConfigDataType,
+        event: Event,
+    ) -> Status,
+}
+
+impl IpsecConfigProtocol {
+    pub const GUID: Guid = guid!("ce5e5929-c7a3-4602-ad9e-c9daf94ebfcf");
+}
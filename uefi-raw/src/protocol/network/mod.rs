@@ -1,9 +1,15 @@
 // SPDX-License-Identifier: MIT OR Apache-2.0
 
 pub mod dhcp4;
+pub mod eap;
 pub mod http;
 pub mod ip4;
 pub mod ip4_config2;
+pub mod ipsec;
 pub mod pxe;
 pub mod snp;
+pub mod tcp4;
+pub mod tcp6;
 pub mod tls;
+pub mod udp4;
+pub mod udp6;
@@ -0,0 +1,173 @@
+// SPDX-License-Identifier: MIT OR Apache-2.0
+
+use crate::{Boolean, Event, Guid, Handle, Ipv6Address, Status, guid};
+use core::ffi::c_void;
+use core::fmt::{self, Debug, Formatter};
+
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq, Ord, PartialOrd, Hash)]
+#[repr(C)]
+pub struct Tcp6AccessPoint {
+    pub station_address: Ipv6Address,
+    pub station_port: u16,
+    pub remote_address: Ipv6Address,
+    pub remote_port: u16,
+    pub active_flag: Boolean,
+}
+
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq, Ord, PartialOrd, Hash)]
+#[repr(C)]
+pub struct Tcp6Option {
+    pub receive_buffer_size: u32,
+    pub send_buffer_size: u32,
+    pub max_syn_back_log: u32,
+    pub connection_timeout: u32,
+    pub data_retries: u32,
+    pub fin_timeout: u32,
+    pub time_wait_timeout: u32,
+    pub keep_alive_probes: u32,
+    pub keep_alive_time: u32,
+    pub keep_alive_interval: u32,
+    pub enable_nagle: Boolean,
+    pub enable_time_stamp: Boolean,
+    pub enable_window_scaling: Boolean,
+    pub enable_selective_ack: Boolean,
+    pub enable_path_mtu_discovery: Boolean,
+}
+
+#[derive(Debug)]
+#[repr(C)]
+pub struct Tcp6ConfigData {
+    pub traffic_class: u8,
+    pub hop_limit: u8,
+    pub access_point: Tcp6AccessPoint,
+    pub control_option: *mut Tcp6Option,
+}
+
+newtype_enum! {
+    pub enum Tcp6ConnectionState: i32 => {
+        CLOSED = 0,
+        LISTEN = 1,
+        SYN_SENT = 2,
+        SYN_RECEIVED = 3,
+        ESTABLISHED = 4,
+        FIN_WAIT1 = 5,
+        FIN_WAIT2 = 6,
+        CLOSING = 7,
+        TIME_WAIT = 8,
+        CLOSE_WAIT = 9,
+        LAST_ACK = 10,
+    }
+}
+
+#[derive(Debug)]
+#[repr(C)]
+pub struct Tcp6CompletionToken {
+    pub event: Event,
+    pub status: Status,
+}
+
+#[derive(Debug)]
+#[repr(C)]
+pub struct Tcp6ConnectionToken {
+    pub completion_token: Tcp6CompletionToken,
+}
+
+#[derive(Debug)]
+#[repr(C)]
+pub struct Tcp6ListenToken {
+    pub completion_token: Tcp6CompletionToken,
+    pub new_child_handle: Handle,
+}
+
+#[derive(Debug)]
+#[repr(C)]
+pub struct Tcp6IoToken {
+    pub completion_token: Tcp6CompletionToken,
+    pub packet: Tcp6Packet,
+}
+
+#[derive(Clone, Copy)]
+#[repr(C)]
+pub union Tcp6Packet {
+    pub rx_data: *mut Tcp6ReceiveData,
+    pub tx_data: *mut Tcp6TransmitData,
+}
+
+impl Debug for Tcp6Packet {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        // This is a union type, so we can't access the internal data.
+        f.debug_struct("Tcp6Packet").finish()
+    }
+}
+
+#[derive(Debug)]
+#[repr(C)]
+pub struct Tcp6CloseToken {
+    pub completion_token: Tcp6CompletionToken,
+    pub abort_on_close: Boolean,
+}
+
+#[derive(Clone, Copy, Debug)]
+#[repr(C)]
+pub struct Tcp6FragmentData {
+    pub fragment_length: u32,
+    pub fragment_buffer: *mut c_void,
+}
+
+/// Data of a receive request. The fragment table has `fragment_count`
+/// entries.
+#[derive(Debug)]
+#[repr(C)]
+pub struct Tcp6ReceiveData {
+    pub urgent_flag: Boolean,
+    pub data_length: u32,
+    pub fragment_count: u32,
+    pub fragment_table: [Tcp6FragmentData; 1],
+}
+
+/// Data of a transmit request. The fragment table has `fragment_count`
+/// entries.
+#[derive(Debug)]
+#[repr(C)]
+pub struct Tcp6TransmitData {
+    pub push: Boolean,
+    pub urgent: Boolean,
+    pub data_length: u32,
+    pub fragment_count: u32,
+    pub fragment_table: [Tcp6FragmentData; 1],
+}
+
+#[derive(Debug)]
+#[repr(C)]
+pub struct Tcp6Protocol {
+    pub get_mode_data: unsafe extern "efiapi" fn(
+        this: *const Self,
+        tcp6_state: *mut Tcp6ConnectionState,
+        tcp6_config_data: *mut Tcp6ConfigData,
+        ip6_mode_data: *mut c_void,
+        mnp_config_data: *mut c_void,
+        snp_mode_data: *mut c_void,
+    ) -> Status,
+    pub configure: unsafe extern "efiapi" fn(
+        this: *mut Self,
+        tcp6_config_data: *const Tcp6ConfigData,
+    ) -> Status,
+    pub connect: unsafe extern "efiapi" fn(
+        this: *mut Self,
+        connection_token: *mut Tcp6ConnectionToken,
+    ) -> Status,
+    pub accept:
+        unsafe extern "efiapi" fn(this: *mut Self, listen_token: *mut Tcp6ListenToken) -> Status,
+    pub transmit: unsafe extern "efiapi" fn(this: *mut Self, token: *mut Tcp6IoToken) -> Status,
+    pub receive: unsafe extern "efiapi" fn(this: *mut Self, token: *mut Tcp6IoToken) -> Status,
+    pub close:
+        unsafe extern "efiapi" fn(this: *mut Self, close_token: *mut Tcp6CloseToken) -> Status,
+    pub cancel:
+        unsafe extern "efiapi" fn(this: *mut Self, token: *mut Tcp6CompletionToken) -> Status,
+    pub poll: unsafe extern "efiapi" fn(this: *mut Self) -> Status,
+}
+
+impl Tcp6Protocol {
+    pub const GUID: Guid = guid!("46e44855-bd60-4ab7-ab0d-a679b9447d77");
+    pub const SERVICE_BINDING_GUID: Guid = guid!("ec20eb79-6c1a-4664-9a0d-d2e4cc16d664");
+}
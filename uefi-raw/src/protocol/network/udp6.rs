@@ -0,0 +1,118 @@
+// SPDX-License-Identifier: MIT OR Apache-2.0
+
+use crate::time::Time;
+use crate::{Boolean, Event, Guid, Ipv6Address, Status, guid};
+use core::ffi::c_void;
+use core::fmt::{self, Debug, Formatter};
+
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq, Ord, PartialOrd, Hash)]
+#[repr(C)]
+pub struct Udp6ConfigData {
+    pub accept_promiscuous: Boolean,
+    pub accept_any_port: Boolean,
+    pub allow_duplicate_port: Boolean,
+    pub traffic_class: u8,
+    pub hop_limit: u8,
+    pub receive_timeout: u32,
+    pub transmit_timeout: u32,
+    pub station_address: Ipv6Address,
+    pub station_port: u16,
+    pub remote_address: Ipv6Address,
+    pub remote_port: u16,
+}
+
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq, Ord, PartialOrd, Hash)]
+#[repr(C)]
+pub struct Udp6SessionData {
+    pub source_address: Ipv6Address,
+    pub source_port: u16,
+    pub destination_address: Ipv6Address,
+    pub destination_port: u16,
+}
+
+#[derive(Clone, Copy, Debug)]
+#[repr(C)]
+pub struct Udp6FragmentData {
+    pub fragment_length: u32,
+    pub fragment_buffer: *mut c_void,
+}
+
+/// Data of a received datagram, allocated by the firmware. The fragment
+/// table has `fragment_count` entries. Signal `recycle_signal` to free the
+/// data.
+#[derive(Debug)]
+#[repr(C)]
+pub struct Udp6ReceiveData {
+    pub time_stamp: Time,
+    pub recycle_signal: Event,
+    pub udp_session: Udp6SessionData,
+    pub data_length: u32,
+    pub fragment_count: u32,
+    pub fragment_table: [Udp6FragmentData; 1],
+}
+
+/// Data of a transmit request. The fragment table has `fragment_count`
+/// entries.
+#[derive(Debug)]
+#[repr(C)]
+pub struct Udp6TransmitData {
+    pub udp_session_data: *mut Udp6SessionData,
+    pub data_length: u32,
+    pub fragment_count: u32,
+    pub fragment_table: [Udp6FragmentData; 1],
+}
+
+#[derive(Debug)]
+#[repr(C)]
+pub struct Udp6CompletionToken {
+    pub event: Event,
+    pub status: Status,
+    pub packet: Udp6Packet,
+}
+
+#[derive(Clone, Copy)]
+#[repr(C)]
+pub union Udp6Packet {
+    pub rx_data: *mut Udp6ReceiveData,
+    pub tx_data: *mut Udp6TransmitData,
+}
+
+impl Debug for Udp6Packet {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        // This is a union type, so we can't access the internal data.
+        f.debug_struct("Udp6Packet").finish()
+    }
+}
+
+#[derive(Debug)]
+#[repr(C)]
+pub struct Udp6Protocol {
+    pub get_mode_data: unsafe extern "efiapi" fn(
+        this: *const Self,
+        udp6_config_data: *mut Udp6ConfigData,
+        ip6_mode_data: *mut c_void,
+        mnp_config_data: *mut c_void,
+        snp_mode_data: *mut c_void,
+    ) -> Status,
+    pub configure: unsafe extern "efiapi" fn(
+        this: *mut Self,
+        udp_config_data: *const Udp6ConfigData,
+    ) -> Status,
+    pub groups: unsafe extern "efiapi" fn(
+        this: *mut Self,
+        join_flag: Boolean,
+        multicast_address: *const Ipv6Address,
+    ) -> Status,
+    pub transmit:
+        unsafe extern "efiapi" fn(this: *mut Self, token: *mut Udp6CompletionToken) -> Status,
+    pub receive:
+        unsafe extern "efiapi" fn(this: *mut Self, token: *mut Udp6CompletionToken) -> Status,
+    pub cancel:
+        unsafe extern "efiapi" fn(this: *mut Self, token: *mut Udp6CompletionToken) -> Status,
+    pub poll: unsafe extern "efiapi" fn(this: *mut Self) -> Status,
+}
+
+impl Udp6Protocol {
+    pub const GUID: Guid = guid!("4f948815-b4b9-43cb-8a33-90e060b34955");
+    pub const SERVICE_BINDING_GUID: Guid = guid!("66ed4721-3c98-4d3e-81e3-d03dd39a7254");
+}
@@ -0,0 +1,91 @@
+// SPDX-License-Identifier: MIT OR Apache-2.0
+
+//! Status Code protocol.
+//!
+//! This protocol is defined in the Platform Initialization (PI)
+//! Specification.
+
+use crate::{Guid, Status, guid};
+use core::ffi::c_void;
+
+/// `EFI_STATUS_CODE_TYPE`: the type of a status code in the low byte, and
+/// the severity of error codes in the high byte.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[repr(transparent)]
+pub struct StatusCodeType(pub u32);
+
+impl StatusCodeType {
+    /// Mask of the type of the status code.
+    pub const TYPE_MASK: u32 = 0x0000_00ff;
+    /// Mask of the severity of error codes.
+    pub const SEVERITY_MASK: u32 = 0xff00_0000;
+}
+
+/// `EFI_STATUS_CODE_VALUE`: the class, subclass and operation of a status
+/// code.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[repr(transparent)]
+pub struct StatusCodeValue(pub u32);
+
+impl StatusCodeValue {
+    /// Mask of the class.
+    pub const CLASS_MASK: u32 = 0xff00_0000;
+    /// Mask of the subclass.
+    pub const SUBCLASS_MASK: u32 = 0x00ff_0000;
+    /// Mask of the operation.
+    pub const OPERATION_MASK: u32 = 0x0000_ffff;
+}
+
+/// `EFI_STATUS_CODE_DATA`: header of the extended data of a status code.
+/// The data of `size` bytes follows the header.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[repr(C)]
+pub struct StatusCodeData {
+    pub header_size: u16,
+    pub size: u16,
+    pub data_type: Guid,
+}
+
+impl StatusCodeData {
+    /// Type of [`StatusCodeStringData`].
+    pub const STRING_GUID: Guid = guid!("92d11080-496f-4d95-be7e-037488382b0a");
+    /// Type of debug data with an error level and a format string.
+    pub const DEBUG_GUID: Guid = guid!("9a4e9246-d553-11d5-8794-00a0c91eb7e4");
+}
+
+newtype_enum! {
+    /// `EFI_STRING_TYPE`: encoding of the string of [`StatusCodeStringData`].
+    pub enum StringType: u32 => {
+        ASCII = 0,
+        UNICODE = 1,
+        TOKEN = 2,
+    }
+}
+
+/// `EFI_STATUS_CODE_STRING_DATA`: extended data with a string that
+/// describes a status code.
+#[derive(Debug)]
+#[repr(C)]
+pub struct StatusCodeStringData {
+    pub header: StatusCodeData,
+    pub string_type: StringType,
+    /// Pointer to the null-terminated string, in the encoding of
+    /// `string_type`.
+    pub string: *const c_void,
+}
+
+#[derive(Debug)]
+#[repr(C)]
+pub struct StatusCodeProtocol {
+    pub report_status_code: unsafe extern "efiapi" fn(
+        code_type: StatusCodeType,
+        value: StatusCodeValue,
+        instance: u32,
+        caller_id: *const Guid,
+        data: *const StatusCodeData,
+    ) -> Status,
+}
+
+impl StatusCodeProtocol {
+    pub const GUID: Guid = guid!("d2b2b828-0826-48a7-b3df-983c006024f0");
+}
@@ -96,6 +96,12 @@ pub enum Status: usize => {
     IP_ADDRESS_CONFLICT     = Self::ERROR_BIT | 34,
     /// A HTTP error occurred during the network operation.
     HTTP_ERROR              = Self::ERROR_BIT | 35,
+    /// The remote peer closed the connection (TCP protocols).
+    CONNECTION_FIN          = Self::ERROR_BIT | 104,
+    /// The remote peer reset the connection (TCP protocols).
+    CONNECTION_RESET        = Self::ERROR_BIT | 105,
+    /// The remote peer refused the connection (TCP protocols).
+    CONNECTION_REFUSED      = Self::ERROR_BIT | 106,
 }}
 
 impl Status {
@@ -169,5 +169,9 @@ newtype_enum! {
 
         /// Used to access EFI signature database variables.
         IMAGE_SECURITY_DATABASE = guid!("d719b2cb-3d3a-4596-a3bc-dad00e67656f"),
+
+        /// Used to access the variables of the shim boot loader, such as
+        /// its Machine Owner Key (MOK) state.
+        SHIM_LOCK = guid!("605dab50-e046-4300-abb6-3dd810dd8b23"),
     }
 }
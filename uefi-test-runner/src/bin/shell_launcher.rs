@@ -20,7 +20,15 @@ use uefi::prelude::*;
 use uefi::proto::BootPolicy;
 use uefi::proto::device_path::build::{self, DevicePathBuilder};
 use uefi::proto::device_path::{DevicePath, DeviceSubType, DeviceType, LoadedImageDevicePath};
-use uefi::proto::loaded_image::LoadedImage;
+use uefi::proto::loaded_image::{LoadOptions, LoadedImage};
+
+/// The code instrumented for `cargo xtask run --coverage` references the
+/// profiler runtime. This image doesn't write coverage data, so the symbol is
+/// all it needs, see the `coverage` module of the test runner.
+#[cfg(feature = "coverage")]
+#[unsafe(no_mangle)]
+#[used]
+static __llvm_profile_runtime: i32 = 0;
 
 /// Get the device path of the shell app. This is the same as the
 /// currently-loaded image's device path, but with the file path part changed.
@@ -65,13 +73,13 @@ fn efi_main() -> Status {
     // test-runner app. This automatically turns off the five-second delay.
     let mut shell_loaded_image = boot::open_protocol_exclusive::<LoadedImage>(shell_image_handle)
         .expect("failed to open LoadedImage protocol");
-    let load_options = cstr16!(r"shell.efi test_runner.efi arg1 arg2");
-    unsafe {
-        shell_loaded_image.set_load_options(
-            load_options.as_ptr().cast(),
-            load_options.num_bytes() as u32,
-        );
-    }
+    let load_options = LoadOptions::new().args([
+        cstr16!("shell.efi"),
+        cstr16!("test_runner.efi"),
+        cstr16!("arg1"),
+        cstr16!("arg2"),
+    ]);
+    let _load_options = shell_loaded_image.set_owned_load_options(load_options);
 
     info!("launching the shell app");
     boot::start_image(shell_image_handle).expect("failed to launch the shell app");
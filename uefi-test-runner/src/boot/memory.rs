@@ -1,14 +1,31 @@
 // SPDX-License-Identifier: MIT OR Apache-2.0
 
+use uefi::mem;
+
 pub fn test() {
     info!("Testing memory functions");
 
-    bootservices::allocate_pages();
-    bootservices::allocate_pool();
-    bootservices::memory_map();
+    let before = mem::alloc_stats();
+    mem::with_alloc_tag("memory tests", || {
+        bootservices::allocate_pages();
+        bootservices::allocate_pool();
+        bootservices::memory_map();
+
+        global::alloc_vec();
+        global::alloc_alignment();
+        typed::alloc_runtime_data();
+
+        pages::page_box();
+        pages::page_vec();
+        pages::arena();
+    });
 
-    global::alloc_vec();
-    global::alloc_alignment();
+    // All memory allocated by the tests must have been freed.
+    let after = mem::alloc_stats();
+    mem::report();
+    assert_eq!(after.current_bytes, before.current_bytes);
+    assert_eq!(after.live_allocations, before.live_allocations);
+    assert!(after.total_allocations > before.total_allocations);
 }
 
 /// Tests that directly use UEFI boot services to allocate memory.
@@ -16,7 +33,7 @@ mod bootservices {
     use alloc::vec::Vec;
     use uefi::boot;
     use uefi::boot::AllocateType;
-    use uefi::mem::memory_map::{MemoryMap, MemoryMapMut};
+    use uefi::mem::memory_map::{MemoryMap, MemoryMapBuffer, MemoryMapMut};
     use uefi_raw::table::boot::MemoryType;
 
     /// Tests the `allocate_pages` boot service.
@@ -86,6 +103,26 @@ mod bootservices {
         }
         let page_count = first_desc.page_count;
         assert!(page_count != 0, "Memory map entry has size zero");
+
+        // Refreshing the map reuses its buffer.
+        let buffer = memory_map.buffer().as_ptr();
+        memory_map
+            .refresh()
+            .expect("Failed to refresh UEFI memory map");
+        assert!(!memory_map.is_empty(), "Memory map is empty");
+        assert_eq!(memory_map.buffer().as_ptr(), buffer);
+        drop(memory_map);
+
+        // The caller-owned buffer is allocated on the first use and reused.
+        let mut buffer = MemoryMapBuffer::new(MemoryType::LOADER_DATA);
+        let first = boot::memory_map_into(&mut buffer)
+            .expect("Failed to retrieve UEFI memory map")
+            .buffer()
+            .as_ptr();
+        let memory_map =
+            boot::memory_map_into(&mut buffer).expect("Failed to retrieve UEFI memory map");
+        assert!(!memory_map.is_empty(), "Memory map is empty");
+        assert_eq!(memory_map.buffer().as_ptr(), first);
     }
 }
 
@@ -131,3 +168,96 @@ mod global {
         }
     }
 }
+
+/// Tests of [`uefi::allocator::TypedAllocator`].
+mod typed {
+    use core::alloc::{GlobalAlloc, Layout};
+    use uefi::allocator::TypedAllocator;
+    use uefi::boot::{self, MemoryType};
+    use uefi::mem::memory_map::MemoryMap;
+
+    /// Allocates memory of a chosen type and finds it in the memory map.
+    pub fn alloc_runtime_data() {
+        info!("Allocating runtime services data with a typed allocator");
+        let allocator = TypedAllocator::new(MemoryType::RUNTIME_SERVICES_DATA);
+        let layout = Layout::from_size_align(100, 16).unwrap();
+        let ptr = unsafe { allocator.alloc(layout) };
+        assert!(!ptr.is_null());
+
+        let addr = ptr as u64;
+        let memory_map = boot::memory_map(MemoryType::LOADER_DATA).unwrap();
+        let desc = memory_map
+            .entries()
+            .find(|desc| {
+                let end = desc.phys_start + desc.page_count * boot::PAGE_SIZE as u64;
+                (desc.phys_start..end).contains(&addr)
+            })
+            .expect("Allocation is not in the memory map");
+        assert_eq!(desc.ty, MemoryType::RUNTIME_SERVICES_DATA);
+
+        unsafe { allocator.dealloc(ptr, layout) };
+    }
+}
+
+/// Tests of [`uefi::mem::PageBox`], [`uefi::mem::PageVec`] and
+/// [`uefi::mem::Arena`].
+mod pages {
+    use uefi::boot::{AllocateType, MemoryType, PAGE_SIZE};
+    use uefi::mem::{Arena, PageBox, PageVec};
+
+    pub fn page_box() {
+        info!("Allocating a page box below 4 GiB");
+        let mut value = PageBox::new_in(
+            [7u32; 2000],
+            AllocateType::MaxAddress(0xffff_ffff),
+            MemoryType::LOADER_DATA,
+        )
+        .unwrap();
+        assert_eq!(value.page_count(), 2);
+        assert_eq!(value.physical_address() % PAGE_SIZE as u64, 0);
+        assert!(value.physical_address() < 0xffff_ffff);
+        value[1999] = 8;
+        assert_eq!(value[..3], [7, 7, 7]);
+        assert_eq!(value[1999], 8);
+    }
+
+    pub fn page_vec() {
+        info!("Filling a page vector");
+        let mut vec = PageVec::<u64>::with_capacity(10).unwrap();
+        assert_eq!(vec.page_count(), 1);
+        assert_eq!(vec.capacity(), PAGE_SIZE / 8);
+        vec.extend_from_slice(&[1, 2, 3]).unwrap();
+        vec.push(4).unwrap();
+        assert_eq!(*vec, [1, 2, 3, 4]);
+        assert_eq!(vec.pop(), Some(4));
+        vec.resize(vec.capacity(), 0).unwrap();
+        assert_eq!(vec.push(5), Err(5));
+        assert!(vec.extend_from_slice(&[5]).is_err());
+        vec.truncate(2);
+        assert_eq!(*vec, [1, 2]);
+    }
+
+    pub fn arena() {
+        info!("Allocating from an arena");
+        let mut arena = Arena::new(100).unwrap();
+        assert_eq!(arena.capacity(), PAGE_SIZE);
+
+        let byte = arena.alloc(1u8).unwrap();
+        *byte += 1;
+        let value = arena.alloc(0x1234_5678u32).unwrap();
+        assert_eq!(*value, 0x1234_5678);
+        assert_eq!(value as *mut u32 as usize % 4, 0);
+        assert_eq!(arena.alloc_str("arena").unwrap(), "arena");
+        assert_eq!(arena.used(), 8 + 5);
+
+        assert!(arena.alloc_slice_copy(&[0u8; PAGE_SIZE]).is_err());
+        arena.reset();
+        assert_eq!(arena.used(), 0);
+        assert_eq!(
+            arena.alloc_slice_copy(&[7u8; PAGE_SIZE]).unwrap().len(),
+            PAGE_SIZE
+        );
+        assert_eq!(arena.remaining(), 0);
+        assert_eq!(arena.alloc(1u8), Err(1));
+    }
+}
@@ -7,8 +7,9 @@ use uefi::boot::{
     EventType, OpenProtocolAttributes, OpenProtocolParams, SearchType, TimerTrigger, Tpl,
 };
 use uefi::mem::memory_map::MemoryType;
-use uefi::proto::unsafe_protocol;
-use uefi::{Event, Guid, Identify, boot, guid, system};
+use uefi::proto::{uefi_protocol, unsafe_protocol};
+use uefi::test_harness::uefi_test;
+use uefi::{Event, Guid, Identify, Status, boot, guid, system};
 
 pub fn test() {
     test_tpl();
@@ -219,6 +220,50 @@ fn test_uninstall_protocol_interface() {
     }
 }
 
+/// Dummy protocol defined with a trait for tests
+#[uefi_protocol("6a5bd9f0-2f86-4b8e-9b53-6f0b3a1c02d4")]
+trait Accumulator {
+    fn add(&mut self, value: u32) -> Status;
+
+    fn total(&self) -> u32;
+}
+
+struct TestAccumulator(u32);
+
+impl Accumulator for TestAccumulator {
+    fn add(&mut self, value: u32) -> Status {
+        match self.0.checked_add(value) {
+            Some(total) => {
+                self.0 = total;
+                Status::SUCCESS
+            }
+            None => Status::INVALID_PARAMETER,
+        }
+    }
+
+    fn total(&self) -> u32 {
+        self.0
+    }
+}
+
+#[uefi_test]
+fn test_uefi_protocol() {
+    info!("Installing a protocol defined with a trait");
+
+    let mut interface = AccumulatorInterface::new(TestAccumulator(1));
+    let handle = unsafe { interface.install(None) }.unwrap();
+
+    {
+        let mut accumulator = boot::open_protocol_exclusive::<AccumulatorProtocol>(handle).unwrap();
+        assert_eq!(accumulator.add(2), Status::SUCCESS);
+        assert_eq!(accumulator.add(u32::MAX), Status::INVALID_PARAMETER);
+        assert_eq!(accumulator.total(), 3);
+    }
+
+    unsafe { interface.uninstall(handle) }.unwrap();
+    assert_eq!(interface.implementation().0, 3);
+}
+
 fn test_install_configuration_table() {
     // Get the current number of entries.
     let initial_table_count = system::with_config_table(|t| t.len());
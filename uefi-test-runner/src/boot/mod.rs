@@ -3,21 +3,29 @@
 use alloc::string::ToString;
 use uefi::boot::{LoadImageSource, SearchType};
 use uefi::fs::FileSystem;
+use uefi::loader::linux::{INITRD_MEDIA_GUID, Initrd, LinuxBoot};
+use uefi::pe::{Machine, PeImage, Subsystem};
 use uefi::proto::BootPolicy;
 use uefi::proto::console::text::Output;
 use uefi::proto::device_path::media::FilePath;
 use uefi::proto::device_path::{DevicePath, LoadedImageDevicePath};
-use uefi::{CString16, Identify, boot};
+use uefi::proto::loaded_image::LoadOptions;
+use uefi::proto::media::load_file::LoadFile2;
+use uefi::test_harness::uefi_test;
+use uefi::{CString16, Identify, Status, boot, cstr16, loader};
 
 mod memory;
 mod misc;
+mod task;
 
 pub fn test() {
     info!("Testing boot services");
     memory::test();
     misc::test();
+    task::test();
     test_locate_handles();
     test_load_image();
+    test_linux_loader();
 }
 
 fn test_locate_handles() {
@@ -87,6 +95,7 @@ fn test_load_image() {
         let image_data = FileSystem::new(fs)
             .read(&*path)
             .expect("should read file content");
+        test_pe_load(&image_data);
         let load_source = LoadImageSource::FromBuffer {
             buffer: image_data.as_slice(),
             file_path: None,
@@ -102,6 +111,24 @@ fn test_load_image() {
             boot::open_protocol_exclusive::<LoadedImageDevicePath>(loaded_image)
                 .expect("should open LoadedImageDevicePath protocol");
         assert!(loaded_image_device_path.get().is_none());
+
+        // With Secure Boot, a modified image fails verification.
+        #[cfg(feature = "secure_boot")]
+        {
+            let mut tampered = image_data.clone();
+            let mid = tampered.len() / 2;
+            tampered[mid] ^= 0xff;
+            let load_source = LoadImageSource::FromBuffer {
+                buffer: tampered.as_slice(),
+                file_path: None,
+            };
+            let err = boot::load_image(boot::image_handle(), load_source)
+                .expect_err("should reject tampered image");
+            assert!(matches!(
+                err.status(),
+                Status::SECURITY_VIOLATION | Status::ACCESS_DENIED
+            ));
+        }
     }
     // Variant B: FromDevicePath
     {
@@ -114,3 +141,81 @@ fn test_load_image() {
         log::debug!("load_image with FromFilePath strategy works");
     }
 }
+
+/// Parses and maps the image of this test runner with the `pe` module. The
+/// image is not started.
+fn test_pe_load(image_data: &[u8]) {
+    info!("Testing the `pe` module");
+
+    let image = PeImage::parse(image_data).expect("should parse image");
+    assert_eq!(image.machine(), Machine::NATIVE);
+    assert_eq!(image.subsystem(), Subsystem::EFI_APPLICATION);
+    assert!(image.sections().any(|section| section.name() == b".text"));
+
+    let mapped = image.load().expect("should load image");
+    let memory = mapped.as_slice();
+    assert_eq!(memory.len(), image.size_of_image() as usize);
+    assert!(memory.as_ptr_range().contains(&mapped.entry_point()));
+    // The headers are copied unchanged.
+    let headers = image.size_of_headers() as usize;
+    assert_eq!(memory[..headers], image_data[..headers]);
+}
+
+/// Chainloads an invalid image, which must fail without exit data.
+#[uefi_test]
+fn test_chainload() {
+    info!("Testing `loader::chainload`");
+
+    let options = LoadOptions::new().arg(cstr16!("arg"));
+    let err = loader::chainload(
+        LoadImageSource::FromBuffer {
+            buffer: &[0; 64],
+            file_path: None,
+        },
+        Some(&options),
+    )
+    .expect_err("should fail to chainload invalid image");
+    assert_ne!(err.status(), Status::SUCCESS);
+    assert!(err.data().is_none());
+}
+
+/// Installs an initial ramdisk for Linux and loads it like the EFI stub of
+/// the kernel does.
+fn test_linux_loader() {
+    info!("Testing `loader::linux`");
+
+    /// Header of a vendor media device path node.
+    const INITRD_MEDIA_PATH_NODE: [u8; 4] = [4, 3, 20, 0];
+
+    let data = [1, 2, 3, 4, 5];
+    let initrd = Initrd::install(data).expect("should install initrd");
+    let err = Initrd::install([0]).expect_err("should not install a second initrd");
+    assert_eq!(err.status(), Status::ALREADY_STARTED);
+
+    // Locate the initrd the way Linux does.
+    let initrd_path = [
+        INITRD_MEDIA_PATH_NODE.as_slice(),
+        &INITRD_MEDIA_GUID.to_bytes(),
+        &[0x7f, 0xff, 4, 0],
+    ]
+    .concat();
+    let mut remaining_path = <&DevicePath>::try_from(initrd_path.as_slice()).unwrap();
+    let handle =
+        boot::locate_device_path::<LoadFile2>(&mut remaining_path).expect("should locate initrd");
+    assert_eq!(handle, initrd.handle());
+
+    let mut load_file2 =
+        boot::open_protocol_exclusive::<LoadFile2>(handle).expect("should open LoadFile2");
+    let loaded = load_file2
+        .load_file(remaining_path)
+        .expect("should load initrd");
+    assert_eq!(*loaded, data);
+    drop(load_file2);
+    drop(initrd);
+
+    let err = LinuxBoot::new(&[0; 64])
+        .cmdline(cstr16!("quiet"))
+        .boot()
+        .expect_err("should not boot invalid kernel");
+    assert_eq!(err.status(), Status::LOAD_ERROR);
+}
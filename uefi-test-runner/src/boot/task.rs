@@ -0,0 +1,93 @@
+// SPDX-License-Identifier: MIT OR Apache-2.0
+
+use alloc::rc::Rc;
+use alloc::vec::Vec;
+use core::cell::RefCell;
+use core::time::Duration;
+use uefi::Status;
+use uefi::boot::{self, EventType, TimerTrigger, Tpl};
+use uefi::task::{self, Completion, Either};
+
+pub fn test() {
+    info!("Testing async tasks...");
+    test_block_on();
+    test_spawn();
+    test_timeout();
+    test_select();
+    test_wait_for_event();
+    test_completion();
+}
+
+fn test_block_on() {
+    assert_eq!(task::block_on(async { 42 }), 42);
+    task::block_on(task::yield_now());
+}
+
+fn test_spawn() {
+    let order = Rc::new(RefCell::new(Vec::new()));
+    let sleeper = |id, ms| {
+        let order = order.clone();
+        async move {
+            task::sleep(Duration::from_millis(ms)).await.unwrap();
+            order.borrow_mut().push(id);
+            id
+        }
+    };
+
+    let (slow, fast) = task::block_on(async {
+        let slow = task::spawn(sleeper(1, 50));
+        let fast = task::spawn(sleeper(2, 10));
+        (slow.await, fast.await)
+    });
+    assert_eq!((slow, fast), (1, 2));
+    assert_eq!(*order.borrow(), [2, 1]);
+}
+
+fn test_timeout() {
+    let result = task::block_on(task::timeout(
+        Duration::from_millis(10),
+        task::sleep(Duration::from_secs(10)),
+    ));
+    assert_eq!(result.unwrap_err().status(), Status::TIMEOUT);
+
+    let result = task::block_on(task::timeout(Duration::from_secs(10), async { 7 }));
+    assert_eq!(result.unwrap(), 7);
+}
+
+fn test_select() {
+    let winner = task::block_on(task::select(
+        task::sleep(Duration::from_secs(10)),
+        task::sleep(Duration::from_millis(10)),
+    ));
+    assert!(matches!(winner, Either::Right(Ok(()))));
+
+    let winner = task::block_on(task::select(async { 1 }, async { 2 }));
+    assert_eq!(winner, Either::Left(1));
+}
+
+fn test_wait_for_event() {
+    let timer =
+        unsafe { boot::create_event(EventType::TIMER, Tpl::APPLICATION, None, None) }.unwrap();
+    boot::set_timer(&timer, TimerTrigger::Relative(100_000 /* 10ms */)).unwrap();
+    task::block_on(task::wait_for_event(&timer)).unwrap();
+    boot::close_event(timer).unwrap();
+}
+
+fn test_completion() {
+    let mut completion = Completion::new().unwrap();
+    assert!(!completion.is_signaled());
+
+    let event = unsafe { completion.event().unsafe_clone() };
+    task::block_on(async {
+        let signal = task::spawn(async move {
+            task::yield_now().await;
+            boot::signal_event(&event).unwrap();
+        });
+        (&mut completion).await;
+        signal.await;
+    });
+    assert!(completion.is_signaled());
+
+    completion.reset();
+    assert!(!completion.is_signaled());
+}
@@ -0,0 +1,178 @@
+// SPDX-License-Identifier: MIT OR Apache-2.0
+
+//! Writing the coverage data of the image.
+//!
+//! With the `coverage` feature, `cargo xtask run --coverage` builds the
+//! image with `-Cinstrument-coverage -Zno-profiler-runtime`. LLVM then
+//! counts the executed code regions in the `.lprfc` sections, and describes
+//! the instrumented functions in the `.lprfd` and `.lprfn` sections. The
+//! profiler runtime of LLVM isn't available for UEFI, so [`write`] creates
+//! the raw profile from the sections itself, in the same format as the
+//! runtime. The host merges the files with `llvm-profdata`.
+//!
+//! The sections are found like the runtime does on Windows: the linker sorts
+//! the sections `.lprfc$A`, `.lprfc$M` (emitted by LLVM) and `.lprfc$Z` by
+//! the suffix and merges them, so statics in the `$A` and `$Z` sections mark
+//! the start and end of the data.
+
+use alloc::vec::Vec;
+use core::{mem, ptr, slice};
+use uefi::fs::{FileSystem, Path};
+use uefi::{CStr16, boot};
+
+/// Version of the raw profile format that is supported, the one used by
+/// LLVM 19 and later.
+const RAW_VERSION: u64 = 10;
+
+/// Magic number at the start of a raw profile.
+#[cfg(target_pointer_width = "64")]
+const RAW_MAGIC: u64 = u64::from_be_bytes([255, b'l', b'p', b'r', b'o', b'f', b'r', 129]);
+#[cfg(target_pointer_width = "32")]
+const RAW_MAGIC: u64 = u64::from_be_bytes([255, b'l', b'p', b'r', b'o', b'f', b'R', 129]);
+
+/// Size of a function record in the `.lprfd` section: two 64-bit hashes,
+/// four pointers, the number of counters, the number of value sites of the
+/// three value kinds and the number of bitmap bytes, aligned to 8 bytes.
+const DATA_RECORD_SIZE: usize =
+    (2 * 8 + 4 * mem::size_of::<usize>() + 4 + 3 * 2 + 4).next_multiple_of(8);
+
+/// Last value kind of value profiling, which is not used.
+const VALUE_KIND_LAST: u64 = 2;
+
+unsafe extern "C" {
+    /// Version and variant flags of the instrumentation, defined by LLVM in
+    /// every instrumented object.
+    static __llvm_profile_raw_version: u64;
+}
+
+/// The instrumented code references this symbol to link the profiler
+/// runtime, which is replaced by this module.
+#[unsafe(no_mangle)]
+#[used]
+static __llvm_profile_runtime: i32 = 0;
+
+/// Defines the start and end markers of a section. The markers have the same
+/// characteristics as the sections emitted by LLVM, so that the linker
+/// merges them; the writable sections must be `static mut`.
+macro_rules! section_bounds {
+    ($start:ident, $end:ident, $section:literal) => {
+        section_bounds!(@marker $start, concat!($section, "$A"), static);
+        section_bounds!(@marker $end, concat!($section, "$Z"), static);
+    };
+    (mut $start:ident, $end:ident, $section:literal) => {
+        section_bounds!(@marker $start, concat!($section, "$A"), static mut);
+        section_bounds!(@marker $end, concat!($section, "$Z"), static mut);
+    };
+    (@marker $name:ident, $section:expr, $($kind:tt)+) => {
+        #[used]
+        #[unsafe(link_section = $section)]
+        $($kind)+ $name: [u64; 0] = [];
+    };
+}
+
+section_bounds!(mut DATA_START, DATA_END, ".lprfd");
+section_bounds!(mut COUNTERS_START, COUNTERS_END, ".lprfc");
+section_bounds!(mut BITMAP_START, BITMAP_END, ".lprfb");
+section_bounds!(NAMES_START, NAMES_END, ".lprfn");
+
+/// Returns the bytes between two markers.
+///
+/// # Safety
+///
+/// The markers must be the start and end of the same section.
+unsafe fn section(start: *const [u64; 0], end: *const [u64; 0]) -> &'static [u8] {
+    let start = start.cast::<u8>();
+    let len = end.cast::<u8>() as usize - start as usize;
+    unsafe { slice::from_raw_parts(start, len) }
+}
+
+/// Returns the function records of the `.lprfd` section. The linker may pad
+/// the section contributions of the objects with zeros, which are skipped;
+/// records are never zero because they start with the hash of the name.
+fn data_section() -> &'static [u8] {
+    let mut data = unsafe { section(&raw const DATA_START, &raw const DATA_END) };
+    while data.len() >= 8 && data[..8] == [0; 8] {
+        data = &data[8..];
+    }
+    let mut len = data.len() / DATA_RECORD_SIZE * DATA_RECORD_SIZE;
+    while len >= DATA_RECORD_SIZE && data[len - DATA_RECORD_SIZE..][..8] == [0; 8] {
+        len -= DATA_RECORD_SIZE;
+    }
+    &data[..len]
+}
+
+/// Creates the raw profile of the image.
+///
+/// # Panics
+///
+/// Panics if the image was instrumented for an unsupported version of the
+/// raw profile format.
+#[must_use]
+pub fn raw_profile() -> Vec<u8> {
+    let version = unsafe { ptr::read_volatile(&raw const __llvm_profile_raw_version) };
+    assert_eq!(
+        version & 0xffff_ffff,
+        RAW_VERSION,
+        "unsupported raw profile version"
+    );
+
+    let data = data_section();
+    let counters = unsafe { section(&raw const COUNTERS_START, &raw const COUNTERS_END) };
+    let bitmap = unsafe { section(&raw const BITMAP_START, &raw const BITMAP_END) };
+    let names = unsafe { section(&raw const NAMES_START, &raw const NAMES_END) };
+
+    // Counters are 64-bit unless single byte coverage is used.
+    let counter_size = if version & (1 << 60) != 0 { 1 } else { 8 };
+    let padding = |len: usize| len.next_multiple_of(8) - len;
+    let delta = |section: &[u8]| (section.as_ptr() as u64).wrapping_sub(data.as_ptr() as u64);
+
+    let header = [
+        RAW_MAGIC,
+        version,
+        // Size of the binary IDs, which PE images don't have.
+        0,
+        (data.len() / DATA_RECORD_SIZE) as u64,
+        // Padding before the counters.
+        0,
+        (counters.len() / counter_size) as u64,
+        padding(counters.len()) as u64,
+        bitmap.len() as u64,
+        padding(bitmap.len()) as u64,
+        names.len() as u64,
+        delta(counters),
+        delta(bitmap),
+        names.as_ptr() as u64,
+        // Number of virtual tables and size of their names, which are only
+        // recorded with value profiling.
+        0,
+        0,
+        VALUE_KIND_LAST,
+    ];
+
+    let mut profile = Vec::new();
+    for field in header {
+        profile.extend_from_slice(&field.to_le_bytes());
+    }
+    for section in [data, counters, bitmap, names] {
+        profile.extend_from_slice(section);
+        profile.resize(profile.len() + padding(section.len()), 0);
+    }
+    profile
+}
+
+/// Writes the raw profile of the image to `path` on the boot volume. The
+/// parent directories are created if necessary.
+pub fn write(path: &CStr16) {
+    let profile = raw_profile();
+    let sfs =
+        boot::get_image_file_system(boot::image_handle()).expect("failed to open the boot volume");
+    let mut fs = FileSystem::new(sfs);
+    let path = Path::new(path);
+    if let Some(parent) = path.parent() {
+        fs.create_dir_all(&parent)
+            .expect("failed to create the coverage directory");
+    }
+    fs.write(path, &profile)
+        .expect("failed to write the coverage data");
+    info!("Wrote {} bytes of coverage data to {path}", profile.len());
+}
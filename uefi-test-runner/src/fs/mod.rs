@@ -3,9 +3,12 @@
 //! Tests functionality from the `uefi::fs` module. See function [`test`].
 
 use alloc::string::{String, ToString};
+use alloc::vec;
 use alloc::vec::Vec;
+use core::time::Duration;
+use uefi::bench::{self, Clock};
 use uefi::boot::ScopedProtocol;
-use uefi::fs::{FileSystem, IoError, IoErrorContext, PathBuf};
+use uefi::fs::{BufReader, BufWriter, FileSystem, IoError, IoErrorContext, OpenOptions, PathBuf};
 use uefi::proto::media::fs::SimpleFileSystem;
 use uefi::{Status, cstr16, fs};
 
@@ -60,6 +63,13 @@ pub fn test(sfs: ScopedProtocol<SimpleFileSystem>) -> Result<(), fs::Error> {
     test_copy_error(&mut fs)?;
     test_copy_success(&mut fs)?;
     test_copy_success_chunks(&mut fs)?;
+    test_copy_with_progress(&mut fs)?;
+    test_open_options(&mut fs)?;
+    test_lines(&mut fs)?;
+    test_cwd(&mut fs)?;
+    test_buffered(&mut fs)?;
+    test_walk(&mut fs)?;
+    test_performance(&mut fs)?;
 
     Ok(())
 }
@@ -170,3 +180,325 @@ fn test_copy_success_chunks(fs: &mut FileSystem) -> Result<(), fs::Error> {
 
     Ok(())
 }
+
+fn test_copy_with_progress(fs: &mut FileSystem) -> Result<(), fs::Error> {
+    let file1_path = cstr16!("file1");
+    let file2_path = cstr16!("file2");
+
+    let data = (0..10_000).map(|i| (i % 251) as u8).collect::<Vec<_>>();
+    fs.write(file1_path, &data)?;
+
+    let mut steps = Vec::new();
+    fs.copy_with_progress(file1_path, file2_path, 4096, |done, total| {
+        steps.push((done, total));
+    })?;
+    assert_eq!(steps, [(4096, 10_000), (8192, 10_000), (10_000, 10_000)]);
+    assert_eq!(fs.read(file2_path)?, data);
+
+    // Copy between a buffered reader and writer with the generic helper.
+    let reader = OpenOptions::new().read(true).open(fs, file1_path)?;
+    let writer = OpenOptions::new()
+        .write(true)
+        .truncate(true)
+        .create(true)
+        .open(fs, file2_path)?;
+    let mut reader = BufReader::with_capacity(1000, reader);
+    let mut writer = BufWriter::with_capacity(1000, writer);
+    let mut calls = 0;
+    let copied = fs::io::copy(&mut reader, &mut writer, 3000, |_| calls += 1).unwrap();
+    assert_eq!(copied, 10_000);
+    assert!(calls > 0);
+    drop(writer);
+    assert_eq!(fs.read(file2_path)?, data);
+
+    // Clean up temporary files.
+    fs.remove_file(file1_path)?;
+    fs.remove_file(file2_path)?;
+
+    Ok(())
+}
+
+fn test_open_options(fs: &mut FileSystem) -> Result<(), fs::Error> {
+    let file1_path = cstr16!("file1");
+
+    // Opening a missing file without `create` fails.
+    let err = OpenOptions::new()
+        .write(true)
+        .open(fs, file1_path)
+        .unwrap_err();
+    let fs::Error::Io(err) = err else {
+        panic!("unexpected error: {err:?}");
+    };
+    assert_eq!(err.uefi_error.status(), Status::NOT_FOUND);
+
+    // Invalid flag combinations are rejected before touching the file system.
+    let err = OpenOptions::new()
+        .read(true)
+        .create(true)
+        .open(fs, file1_path)
+        .unwrap_err();
+    assert!(matches!(
+        err,
+        fs::Error::Io(IoError {
+            context: IoErrorContext::InvalidOpenOptions,
+            ..
+        })
+    ));
+    assert_eq!(fs.try_exists(file1_path), Ok(false));
+
+    // Test `create_new`.
+    let mut file = OpenOptions::new()
+        .write(true)
+        .create_new(true)
+        .open(fs, file1_path)?;
+    file.write(b"hello").unwrap();
+    drop(file);
+    let err = OpenOptions::new()
+        .write(true)
+        .create_new(true)
+        .open(fs, file1_path)
+        .unwrap_err();
+    assert!(matches!(
+        err,
+        fs::Error::Io(IoError {
+            context: IoErrorContext::AlreadyExists,
+            ..
+        })
+    ));
+
+    // Test `append`.
+    let mut file = OpenOptions::new().append(true).open(fs, file1_path)?;
+    file.write(b" world").unwrap();
+    drop(file);
+    assert_eq!(fs.read(file1_path)?, b"hello world");
+
+    // Test `truncate`.
+    let mut file = OpenOptions::new()
+        .write(true)
+        .truncate(true)
+        .open(fs, file1_path)?;
+    file.write(b"bye").unwrap();
+    drop(file);
+    assert_eq!(fs.read(file1_path)?, b"bye");
+
+    // Directories can't be opened as files.
+    fs.create_dir(cstr16!("dir"))?;
+    let err = OpenOptions::new()
+        .read(true)
+        .open(fs, cstr16!("dir"))
+        .unwrap_err();
+    assert!(matches!(
+        err,
+        fs::Error::Io(IoError {
+            context: IoErrorContext::NotAFile,
+            ..
+        })
+    ));
+
+    // Clean up temporary files.
+    fs.remove_file(file1_path)?;
+    fs.remove_dir(cstr16!("dir"))?;
+
+    Ok(())
+}
+
+fn test_lines(fs: &mut FileSystem) -> Result<(), fs::Error> {
+    let file1_path = cstr16!("file1");
+
+    fs.write_str(file1_path, "first\r\nsecond\n")?;
+    fs.append_str(file1_path, "\nlast")?;
+    assert_eq!(fs.read_to_string(file1_path)?, "first\r\nsecond\n\nlast");
+
+    let lines = fs.read_lines(file1_path)?.collect::<Result<Vec<_>, _>>()?;
+    assert_eq!(lines, ["first", "second", "", "last"]);
+
+    // Lines spanning multiple internal read buffers are reassembled.
+    let long_line = "x".repeat(10000);
+    fs.write_str(file1_path, &long_line)?;
+    fs.append_str(file1_path, "\nend\n")?;
+    let lines = fs.read_lines(file1_path)?.collect::<Result<Vec<_>, _>>()?;
+    assert_eq!(lines, [long_line.as_str(), "end"]);
+
+    // `append_str` creates missing files.
+    fs.remove_file(file1_path)?;
+    fs.append_str(file1_path, "new")?;
+    assert_eq!(fs.read_to_string(file1_path)?, "new");
+
+    // Clean up temporary files.
+    fs.remove_file(file1_path)?;
+
+    Ok(())
+}
+
+fn test_cwd(fs: &mut FileSystem) -> Result<(), fs::Error> {
+    assert_eq!(fs.cwd().to_cstr16(), cstr16!("\\"));
+
+    fs.create_dir_all(cstr16!("cwd_dir\\sub"))?;
+    fs.write_str(cstr16!("cwd_dir\\file"), "cwd")?;
+
+    // Relative paths are resolved against the current directory.
+    fs.set_cwd(cstr16!("cwd_dir"))?;
+    assert_eq!(fs.cwd().to_cstr16(), cstr16!("\\cwd_dir"));
+    assert_eq!(fs.read_to_string(cstr16!("file"))?, "cwd");
+    assert_eq!(fs.read_to_string(cstr16!("\\cwd_dir\\file"))?, "cwd");
+
+    // `.` and `..` are resolved.
+    fs.set_cwd(cstr16!("sub\\..\\.\\sub"))?;
+    assert_eq!(fs.cwd().to_cstr16(), cstr16!("\\cwd_dir\\sub"));
+    assert_eq!(fs.read_to_string(cstr16!("..\\file"))?, "cwd");
+
+    // Setting a file or a missing directory as current directory fails and
+    // keeps the current directory.
+    assert!(fs.set_cwd(cstr16!("..\\file")).is_err());
+    assert!(fs.set_cwd(cstr16!("missing")).is_err());
+    assert_eq!(fs.cwd().to_cstr16(), cstr16!("\\cwd_dir\\sub"));
+
+    // Paths as reported by the shell contain a mapping prefix.
+    fs.set_cwd_from_shell(cstr16!("FS0:\\cwd_dir"))?;
+    assert_eq!(fs.cwd().to_cstr16(), cstr16!("\\cwd_dir"));
+
+    // Clean up temporary files.
+    fs.set_cwd(cstr16!("\\"))?;
+    fs.remove_dir_all(cstr16!("cwd_dir"))?;
+
+    Ok(())
+}
+
+fn test_buffered(fs: &mut FileSystem) -> Result<(), fs::Error> {
+    let file1_path = cstr16!("file1");
+
+    // Write many small chunks through a small buffer, so that the buffer is
+    // flushed a couple of times.
+    let file = OpenOptions::new()
+        .write(true)
+        .create(true)
+        .open(fs, file1_path)?;
+    let mut writer = BufWriter::with_capacity(16, file);
+    for i in 0..100_u8 {
+        writer.write(&[i]).unwrap();
+    }
+    assert!(!writer.buffer().is_empty());
+    // Writes larger than the buffer are passed through.
+    writer.write(&[0xaa; 64]).unwrap();
+    assert!(writer.buffer().is_empty());
+    writer.write(b"end").unwrap();
+    // Dropping the writer flushes the remaining data.
+    drop(writer);
+
+    let mut expected: Vec<u8> = (0..100).collect();
+    expected.extend([0xaa; 64]);
+    expected.extend(b"end");
+    assert_eq!(fs.read(file1_path)?, expected);
+
+    // Read the data back in small pieces.
+    let file = OpenOptions::new().read(true).open(fs, file1_path)?;
+    let mut reader = BufReader::with_capacity(16, file);
+    let mut byte = [0];
+    for i in 0..100_u8 {
+        reader.read_exact(&mut byte).unwrap();
+        assert_eq!(byte[0], i);
+    }
+    assert_eq!(reader.get_position().unwrap(), 100);
+    let mut rest = Vec::new();
+    reader.read_until(b'e', &mut rest).unwrap();
+    assert_eq!(rest.len(), 65);
+    let mut end = [0; 2];
+    reader.read_exact(&mut end).unwrap();
+    assert_eq!(&end, b"nd");
+    assert_eq!(reader.read(&mut end).unwrap(), 0);
+    assert_eq!(
+        reader.read_exact(&mut end).unwrap_err().status(),
+        Status::END_OF_FILE
+    );
+
+    // Seeking discards the buffer.
+    reader.set_position(98).unwrap();
+    reader.read_exact(&mut end).unwrap();
+    assert_eq!(end, [98, 99]);
+
+    // Clean up temporary files.
+    drop(reader);
+    fs.remove_file(file1_path)?;
+
+    Ok(())
+}
+
+fn test_walk(fs: &mut FileSystem) -> Result<(), fs::Error> {
+    fs.create_dir_all(cstr16!("walk\\a\\b"))?;
+    fs.create_dir(cstr16!("walk\\c"))?;
+    fs.write_str(cstr16!("walk\\a\\file1"), "1")?;
+    fs.write_str(cstr16!("walk\\a\\b\\file2"), "22")?;
+
+    let collect = |walk: fs::Walk| {
+        let mut entries = walk
+            .map(|entry| {
+                let entry = entry.unwrap();
+                (entry.path().to_string(), entry.depth())
+            })
+            .collect::<Vec<_>>();
+        entries.sort();
+        entries
+    };
+
+    let entries = collect(fs.walk(cstr16!("walk")));
+    assert_eq!(
+        entries,
+        [
+            ("walk\\a".to_string(), 1),
+            ("walk\\a\\b".to_string(), 2),
+            ("walk\\a\\b\\file2".to_string(), 3),
+            ("walk\\a\\file1".to_string(), 2),
+            ("walk\\c".to_string(), 1),
+        ]
+    );
+
+    // Limit the depth.
+    let entries = collect(fs.walk(cstr16!("walk")).max_depth(2));
+    assert_eq!(entries.len(), 4);
+
+    // Filtering a directory also skips its content.
+    let entries = collect(
+        fs.walk(cstr16!("walk"))
+            .filter_entry(|entry| entry.info().file_name() != cstr16!("b")),
+    );
+    assert_eq!(
+        entries,
+        [
+            ("walk\\a".to_string(), 1),
+            ("walk\\a\\file1".to_string(), 2),
+            ("walk\\c".to_string(), 1),
+        ]
+    );
+
+    // Walking a missing directory yields an error.
+    let mut walk = fs.walk(cstr16!("missing"));
+    assert!(walk.next().unwrap().is_err());
+    assert!(walk.next().is_none());
+    drop(walk);
+
+    // Clean up temporary files.
+    fs.remove_dir_all(cstr16!("walk"))?;
+
+    Ok(())
+}
+
+/// Checks that writing and reading a file doesn't become dramatically
+/// slower. The limits are generous so that slow CI machines pass.
+fn test_performance(fs: &mut FileSystem) -> Result<(), fs::Error> {
+    let Ok(clock) = Clock::new() else {
+        info!("No clock available, skipping file system performance test");
+        return Ok(());
+    };
+    let path = cstr16!("bench");
+    let data = vec![0x5a; 64 * 1024];
+
+    let write = bench::measure(&clock, 5, || fs.write(path, &data).unwrap());
+    info!("Writing 64 KiB: {write}");
+    assert!(write.avg() < Duration::from_secs(1));
+
+    let read = bench::measure(&clock, 5, || assert_eq!(fs.read(path).unwrap(), data));
+    info!("Reading 64 KiB: {read}");
+    assert!(read.avg() < Duration::from_secs(1));
+
+    fs.remove_file(path)
+}
@@ -0,0 +1,141 @@
+// SPDX-License-Identifier: MIT OR Apache-2.0
+
+//! Runs the tests registered with `#[uefi_test]`.
+//!
+//! Each test runs in a new instance of this image, started with
+//! `--uefi-test <name>` as load options. A panic exits that instance with
+//! [`Status::ABORTED`] instead of ending the whole run. Only the tests whose
+//! names contain the value of `--uefi-test-filter` run, if it is given.
+//!
+//! The results are printed in the format of libtest, so they can be parsed
+//! from the serial output:
+//!
+//! ```text
+//! test uefi_test_runner::harness::test_panic_is_caught ... ok
+//! test result: ok. 1 passed; 0 failed; 0 filtered out
+//! ```
+
+use alloc::string::{String, ToString};
+use alloc::vec::Vec;
+use core::time::Duration;
+use uefi::boot::LoadImageSource;
+use uefi::helpers::{self, PanicAction};
+use uefi::prelude::*;
+use uefi::proto::BootPolicy;
+use uefi::proto::device_path::LoadedImageDevicePath;
+use uefi::proto::loaded_image::{LoadOptions, LoadedImage};
+use uefi::test_harness::{self, uefi_test};
+use uefi::{CStr16, CString16, boot, loader, println};
+
+/// Option that selects the single test an instance of the image runs.
+const TEST_OPTION: &CStr16 = cstr16!("--uefi-test");
+
+/// Option that selects the tests whose names contain its value.
+const FILTER_OPTION: &CStr16 = cstr16!("--uefi-test-filter");
+
+/// Returns the argument after `option` in the load options of the image.
+fn option_value(option: &CStr16) -> Option<CString16> {
+    let loaded_image = boot::open_protocol_exclusive::<LoadedImage>(boot::image_handle()).ok()?;
+    let args = loaded_image.load_options_args().ok()?;
+    let i = args.iter().position(|arg| **arg == *option)?;
+    args.get(i + 1).cloned()
+}
+
+/// Returns the name of the test to run, if this instance of the image was
+/// started by [`run`] to run a single test.
+pub fn requested_test() -> Option<CString16> {
+    option_value(TEST_OPTION)
+}
+
+/// Runs the test `name` in this instance of the image.
+pub fn run_requested(name: &CStr16) -> Status {
+    let _helpers = helpers::Builder::new()
+        .panic_action(PanicAction::Exit)
+        .panic_stall(Duration::ZERO)
+        .init()
+        .expect("Failed to initialize utilities");
+
+    let name = name.to_string();
+    match test_harness::test_cases()
+        .iter()
+        .find(|test| test.name() == name)
+    {
+        Some(test) => {
+            test.run();
+
+            // Each instance of the image has its own coverage data.
+            #[cfg(feature = "coverage")]
+            {
+                let path = format!("coverage\\{}.profraw", name.replace("::", "-"));
+                crate::coverage::write(&CString16::try_from(path.as_str()).unwrap());
+            }
+
+            Status::SUCCESS
+        }
+        None => {
+            error!("No test named {name}");
+            Status::NOT_FOUND
+        }
+    }
+}
+
+/// Runs the registered tests that match the filter, each in a new instance
+/// of this image, and prints the results.
+///
+/// # Panics
+///
+/// Panics if a test fails.
+pub fn run() {
+    let filter = option_value(FILTER_OPTION).map(|filter| String::from(&filter));
+    let image_path = boot::open_protocol_exclusive::<LoadedImageDevicePath>(boot::image_handle())
+        .expect("Failed to open the device path of the image")
+        .to_boxed();
+
+    let mut tests: Vec<_> = test_harness::test_cases().iter().collect();
+    tests.sort_by_key(|test| test.name());
+
+    let (mut passed, mut failed, mut filtered_out) = (0, 0, 0);
+    for test in tests {
+        if filter
+            .as_deref()
+            .is_some_and(|filter| !test.name().contains(filter))
+        {
+            filtered_out += 1;
+            continue;
+        }
+
+        let name = CString16::try_from(test.name()).unwrap();
+        let options = LoadOptions::new().args([cstr16!("test_runner.efi"), TEST_OPTION, &name]);
+        let result = loader::chainload(
+            LoadImageSource::FromDevicePath {
+                device_path: &image_path,
+                boot_policy: BootPolicy::ExactMatch,
+            },
+            Some(&options),
+        );
+
+        let ok = if test.should_panic() {
+            matches!(result, Err(ref err) if err.status() == Status::ABORTED)
+        } else {
+            result.is_ok()
+        };
+        if ok {
+            passed += 1;
+            println!("test {} ... ok", test.name());
+        } else {
+            failed += 1;
+            println!("test {} ... FAILED", test.name());
+        }
+    }
+
+    let outcome = if failed == 0 { "ok" } else { "FAILED" };
+    println!(
+        "test result: {outcome}. {passed} passed; {failed} failed; {filtered_out} filtered out"
+    );
+    assert_eq!(failed, 0, "{failed} tests failed");
+}
+
+#[uefi_test(should_panic)]
+fn test_panic_is_caught() {
+    panic!("Expected panic");
+}
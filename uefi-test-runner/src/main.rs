@@ -8,24 +8,47 @@ extern crate log;
 #[macro_use]
 extern crate alloc;
 
-use alloc::string::ToString;
+use alloc::string::{String, ToString};
 use alloc::vec::Vec;
+use core::ptr;
+use log::LevelFilter;
+use uefi::fs::FileSystem;
+use uefi::helpers::{self, LoggerBuilder};
 use uefi::mem::memory_map::MemoryMap;
 use uefi::prelude::*;
 use uefi::proto::console::serial::Serial;
 use uefi::proto::device_path::build::{self, DevicePathBuilder};
 use uefi::proto::device_path::messaging::Vendor;
+use uefi::proto::loaded_image::LoadedImage;
 use uefi::{Result, print, println, system};
 
 mod boot;
+#[cfg(feature = "coverage")]
+mod coverage;
 mod fs;
+mod harness;
+mod media;
 mod proto;
 mod runtime;
 
 #[entry]
 fn efi_main() -> Status {
-    // Initialize utilities (logging, memory allocation...)
-    uefi::helpers::init().expect("Failed to initialize utilities");
+    // Run a single test if this instance of the image was started by the
+    // test harness.
+    if let Some(name) = harness::requested_test() {
+        return harness::run_requested(&name);
+    }
+
+    // Initialize utilities (logging, memory allocation...). All records are
+    // also kept in memory, so they are shown on panic.
+    static mut LOG_HISTORY: [u8; 16384] = [0; 16384];
+    let log_history = unsafe { &mut *ptr::addr_of_mut!(LOG_HISTORY) };
+    helpers::Builder::new()
+        .logger(LoggerBuilder::default().memory(log_history, LevelFilter::Trace))
+        .hook_exit_boot_services(true)
+        .init()
+        .expect("Failed to initialize utilities")
+        .keep();
 
     // Test print! and println! macros.
     let (print, println) = ("print!", "println!"); // necessary for clippy to ignore
@@ -45,17 +68,31 @@ fn efi_main() -> Status {
     uefi::boot::get_image_file_system(uefi::boot::image_handle())
         .expect("Failed to retrieve boot file system");
 
+    check_log_to_file();
+    #[cfg(any(target_arch = "x86", target_arch = "x86_64"))]
+    check_debugcon();
+
     boot::test();
 
     // Test all the supported protocols.
     proto::test();
 
+    // Test parsing on-disk structures.
+    media::test();
+
     // TODO: runtime services work before boot services are exited, but we'd
     // probably want to test them after exit_boot_services. However,
     // exit_boot_services is currently called during shutdown.
 
     runtime::test();
 
+    // Run the tests registered with `#[uefi_test]`.
+    harness::run();
+
+    // Write the coverage data while the boot volume is still accessible.
+    #[cfg(feature = "coverage")]
+    coverage::write(cstr16!("coverage\\test_runner.profraw"));
+
     shutdown();
 }
 
@@ -80,6 +117,13 @@ fn check_system() {
     assert_eq!(system::firmware_vendor(), cstr16!("EDK II"));
     check_revision(system::uefi_revision());
 
+    // OVMF provides ACPI tables.
+    let rsdp = system::acpi_rsdp().expect("no ACPI RSDP");
+    let signature = unsafe { rsdp.cast::<[u8; 8]>().read_unaligned() };
+    assert_eq!(&signature, b"RSD PTR ");
+    let tables = system::acpi_tables().expect("invalid ACPI tables");
+    assert!(tables.headers().count() > 0);
+
     system::with_stdout(|stdout| {
         stdout
             .output_string(cstr16!("test system::with_stdout\n"))
@@ -92,6 +136,39 @@ fn check_system() {
     });
 }
 
+// Append the log to a file on the boot volume.
+fn check_log_to_file() {
+    let device = uefi::boot::open_protocol_exclusive::<LoadedImage>(uefi::boot::image_handle())
+        .unwrap()
+        .device()
+        .unwrap();
+    helpers::log_to_file(device, cstr16!("test_runner.log")).unwrap();
+    info!("Logging to test_runner.log");
+    log::logger().flush();
+
+    let sfs = uefi::boot::get_image_file_system(uefi::boot::image_handle()).unwrap();
+    let log = FileSystem::new(sfs)
+        .read_to_string(cstr16!("test_runner.log"))
+        .unwrap();
+    assert!(log.contains("Logging to test_runner.log"));
+
+    let mut history = String::new();
+    helpers::dump_memory_log(&mut history).unwrap();
+    assert!(history.contains("Logging to test_runner.log"));
+}
+
+// Write to the debug console, which the host captures in
+// `integration-test-debugcon.log` independently of the serial port.
+#[cfg(any(target_arch = "x86", target_arch = "x86_64"))]
+fn check_debugcon() {
+    use core::fmt::Write;
+    use uefi::console::debugcon::Debugcon;
+
+    let mut debugcon = Debugcon::QEMU;
+    assert!(debugcon.is_present());
+    writeln!(debugcon, "Debug console test").unwrap();
+}
+
 #[derive(Clone, Copy, Debug)]
 enum HostRequest {
     /// Tell the host to take a screenshot and compare against the
@@ -136,7 +213,7 @@ fn send_request_helper(serial: &mut Serial, request: HostRequest) -> Result {
 fn reconnect_serial_to_console(serial_handle: Handle) {
     let mut storage = Vec::new();
     // Create a device path that specifies the terminal type.
-    let terminal_guid = if cfg!(target_arch = "aarch64") {
+    let terminal_guid = if cfg!(any(target_arch = "aarch64", target_arch = "riscv64")) {
         Vendor::VT_100
     } else {
         Vendor::VT_UTF8
@@ -0,0 +1,112 @@
+// SPDX-License-Identifier: MIT OR Apache-2.0
+
+use uefi::boot::{self, OpenProtocolAttributes, OpenProtocolParams, ScopedProtocol};
+use uefi::media::fat::{FatError, FatFileSystem, FatType};
+use uefi::media::gpt::{Gpt, GptError};
+use uefi::media::mbr::{Mbr, MbrError};
+use uefi::media::{Disk, Partition};
+use uefi::proto::media::block::BlockIO;
+use uefi::proto::media::partition::MbrOsType;
+use uefi::{Handle, Result};
+
+pub fn test() {
+    info!("Testing media parsers");
+    let mut found_mbr_test_disk = false;
+    for handle in boot::find_handles::<BlockIO>().unwrap() {
+        let Ok(mut block_io) = open_block_io(handle) else {
+            continue;
+        };
+        let media = block_io.media();
+        if media.is_logical_partition() || !media.is_media_present() {
+            continue;
+        }
+        test_gpt(handle, &mut block_io);
+        found_mbr_test_disk |= test_mbr(handle, &mut block_io);
+    }
+    assert!(found_mbr_test_disk, "MBR test disk not found");
+}
+
+/// Opens the `BlockIO` protocol without disconnecting the drivers using it.
+fn open_block_io(handle: Handle) -> Result<ScopedProtocol<BlockIO>> {
+    // This cannot be opened in `EXCLUSIVE` mode, as doing so unregisters the
+    // partition and file system protocols from the handle's children.
+    unsafe {
+        boot::open_protocol::<BlockIO>(
+            OpenProtocolParams {
+                handle,
+                agent: boot::image_handle(),
+                controller: None,
+            },
+            OpenProtocolAttributes::GetProtocol,
+        )
+    }
+}
+
+/// Reading the GPT of a whole disk must either succeed or detect that the
+/// disk is not a GPT disk. The test disks are all MBR disks.
+fn test_gpt(handle: Handle, block_io: &mut BlockIO) {
+    match Gpt::read(&mut Disk::new(block_io)) {
+        Ok(gpt) => {
+            info!("GPT disk {handle:?}: {:?}", gpt.disk_guid());
+            for partition in gpt.partitions() {
+                info!("  {}: {}", partition.index(), partition.name());
+                assert!(partition.starting_lba() <= partition.ending_lba());
+            }
+        }
+        Err(GptError::NoProtectiveMbr) => info!("Non-GPT disk {handle:?}"),
+        Err(err) => panic!("failed to read GPT of {handle:?}: {err}"),
+    }
+}
+
+/// Reads the MBR of a whole disk. Returns whether the disk is the MBR test
+/// disk created by `xtask/src/disk.rs`.
+fn test_mbr(handle: Handle, block_io: &mut BlockIO) -> bool {
+    let mbr = match Mbr::read(&mut Disk::new(block_io)) {
+        Ok(mbr) => mbr,
+        Err(MbrError::InvalidSignature) => return false,
+        Err(err) => panic!("failed to read MBR of {handle:?}: {err}"),
+    };
+    for partition in mbr.partitions() {
+        info!(
+            "MBR partition {} of {handle:?}: type {:#04x}, {} blocks at {}",
+            partition.number(),
+            partition.os_type().0,
+            partition.size_in_lba(),
+            partition.starting_lba()
+        );
+    }
+
+    let [partition] = mbr.primary_partitions() else {
+        return false;
+    };
+    if partition.os_type() != MbrOsType(6) || partition.starting_lba() != 1 {
+        return false;
+    }
+    assert_eq!(partition.number(), 1);
+    assert_eq!(partition.size_in_lba(), 20479);
+    assert!(!partition.is_bootable());
+    assert!(mbr.logical_partitions().is_empty());
+
+    let partition = Partition::new(Disk::new(block_io), 1, 20479);
+    test_fat(partition);
+    true
+}
+
+/// Reads the FAT file system of the MBR test disk without the firmware's
+/// file system driver.
+fn test_fat(partition: Partition<Disk>) {
+    let mut fs = FatFileSystem::new(partition).unwrap();
+    info!("FAT file system: {fs:?}");
+    assert_eq!(fs.fat_type(), FatType::Fat16);
+    assert_eq!(fs.volume_label(), "MbrTestDisk");
+    assert_eq!(fs.cluster_count(), 10183);
+
+    let entries = fs.read_dir("/").unwrap();
+    let test_dir = entries
+        .iter()
+        .find(|entry| entry.name() == "test_dir")
+        .expect("test_dir not found");
+    assert!(test_dir.is_directory());
+    assert_eq!(fs.metadata("TEST_DIR").unwrap(), *test_dir);
+    assert_eq!(fs.read("test_dir").unwrap_err(), FatError::NotAFile);
+}
@@ -3,7 +3,8 @@
 use uefi::boot;
 use uefi::boot::{OpenProtocolAttributes, OpenProtocolParams};
 use uefi::proto::ata::AtaRequestBuilder;
-use uefi::proto::ata::pass_thru::AtaPassThru;
+use uefi::proto::ata::command::AtaFeatures;
+use uefi::proto::ata::pass_thru::{AtaDevice, AtaPassThru};
 
 pub fn test() {
     info!("Running ATA PassThru tests");
@@ -49,6 +50,7 @@ fn is_testdrive_present() -> bool {
                 let serial = core::str::from_utf8(&serial_bfr).unwrap().trim();
                 if serial == "AtaPassThru" {
                     info!("Found Testdisk at handle: {handle:?}");
+                    test_typed_commands(&mut device);
                     return true; // found our testdrive!
                 }
             }
@@ -57,3 +59,29 @@ fn is_testdrive_present() -> bool {
 
     false
 }
+
+fn test_typed_commands(device: &mut AtaDevice) {
+    let identify = device.identify().unwrap();
+    assert_eq!(identify.serial_number(), "AtaPassThru");
+    assert!(identify.features().contains(AtaFeatures::LBA));
+    assert_ne!(identify.capacity(), 0);
+    info!(
+        "ATA test disk: model '{}', {} bytes",
+        identify.model_number(),
+        identify.capacity()
+    );
+
+    if identify.features().contains(AtaFeatures::SMART_ENABLED) {
+        let smart = device.smart_read_data().unwrap();
+        for attribute in smart.attributes() {
+            info!(
+                "SMART attribute {:#04x} ({:?}): current {}, raw {}",
+                attribute.id,
+                attribute.name(),
+                attribute.current,
+                attribute.raw
+            );
+        }
+        assert!(!smart.attributes().is_empty());
+    }
+}
@@ -1,8 +1,18 @@
 // SPDX-License-Identifier: MIT OR Apache-2.0
 
 use crate::{HostRequest, send_request_to_host};
+use alloc::vec::Vec;
+use core::fmt::Write;
+use core::time::Duration;
+use uefi::bench::{self, Clock};
 use uefi::boot::{self, OpenProtocolAttributes, OpenProtocolParams};
-use uefi::proto::console::gop::{BltOp, BltPixel, FrameBuffer, GraphicsOutput, PixelFormat};
+use uefi::console::fbtext::FramebufferConsole;
+use uefi::cstr16;
+use uefi::fs::FileSystem;
+use uefi::graphics::{Image, bmp};
+use uefi::proto::console::gop::{
+    BltOp, BltPixel, FrameBuffer, GraphicsOutput, ModeCriteria, PixelFormat,
+};
 
 pub unsafe fn test() {
     info!("Running graphics output protocol test");
@@ -25,23 +35,30 @@ pub unsafe fn test() {
 
     set_graphics_mode(gop);
     fill_color(gop);
+    bench_fill(gop);
     draw_fb(gop);
 
-    // `draw_fb` is skipped on aarch64, so the screenshot doesn't match.
-    if cfg!(not(target_arch = "aarch64")) {
+    // `draw_fb` is skipped on aarch64 and riscv64, so the screenshot doesn't
+    // match.
+    if cfg!(not(any(target_arch = "aarch64", target_arch = "riscv64"))) {
         send_request_to_host(HostRequest::Screenshot("gop_test"));
     }
+    capture(gop);
+
+    draw_text(gop);
+    send_request_to_host(HostRequest::Screenshot("fbtext_test"));
+
+    draw_bmp(gop);
+    send_request_to_host(HostRequest::Screenshot("bmp_test"));
+
+    draw_backbuffer(gop);
 }
 
 // Set a larger graphics mode.
 fn set_graphics_mode(gop: &mut GraphicsOutput) {
     // We know for sure QEMU has a 1024x768 mode.
     let mode = gop
-        .modes()
-        .find(|mode| {
-            let info = mode.info();
-            info.resolution() == (1024, 768)
-        })
+        .choose_mode(ModeCriteria::Resolution(1024, 768))
         .unwrap();
 
     gop.set_mode(&mode).expect("Failed to set graphics mode");
@@ -49,21 +66,156 @@ fn set_graphics_mode(gop: &mut GraphicsOutput) {
 
 // Fill the screen with color.
 fn fill_color(gop: &mut GraphicsOutput) {
+    // Cornflower blue.
+    let color = BltPixel::new(100, 149, 237);
     let op = BltOp::VideoFill {
-        // Cornflower blue.
-        color: BltPixel::new(100, 149, 237),
+        color,
         dest: (0, 0),
         dims: (1024, 768),
     };
 
     gop.blt(op).expect("Failed to fill screen with color");
+
+    // Fill it again in two halves, which are merged into one operation.
+    let mut ops = [
+        BltOp::VideoFill {
+            color,
+            dest: (0, 0),
+            dims: (1024, 384),
+        },
+        BltOp::VideoFill {
+            color,
+            dest: (0, 384),
+            dims: (1024, 384),
+        },
+    ];
+    let len = BltOp::coalesce_fills(&mut ops);
+    assert_eq!(len, 1);
+    gop.blt_batch(&mut ops[..len])
+        .expect("Failed to fill screen with color");
+}
+
+// Check that filling the screen doesn't become dramatically slower. The limit
+// is generous so that slow CI machines pass.
+fn bench_fill(gop: &mut GraphicsOutput) {
+    let Ok(clock) = Clock::new() else {
+        info!("No clock available, skipping blit performance test");
+        return;
+    };
+    let color = BltPixel::new(100, 149, 237);
+    let stats = bench::measure(&clock, 10, || {
+        let op = BltOp::VideoFill {
+            color,
+            dest: (0, 0),
+            dims: (1024, 768),
+        };
+        gop.blt(op).expect("Failed to fill screen with color");
+    });
+    info!("Filling the screen: {stats}");
+    assert!(stats.avg() < Duration::from_millis(500));
+}
+
+// Capture the screen. With the `screenshot_bmp` feature, the capture is
+// saved on the boot volume for inspection.
+fn capture(gop: &mut GraphicsOutput) {
+    let image = gop.capture().expect("failed to capture the screen");
+    assert_eq!((image.width(), image.height()), (1024, 768));
+    let corner = image.pixel(0, 0).unwrap();
+    assert_eq!((corner.red, corner.green, corner.blue), (100, 149, 237));
+
+    let mut bmp = Vec::new();
+    image.write_bmp(&mut bmp).unwrap();
+    assert_eq!(bmp.len(), 54 + 1024 * 768 * 3);
+
+    if cfg!(feature = "screenshot_bmp") {
+        let sfs = boot::get_image_file_system(boot::image_handle()).unwrap();
+        FileSystem::new(sfs)
+            .write(cstr16!("gop_test.bmp"), &bmp)
+            .expect("failed to save the screenshot");
+        info!("Saved the screen to gop_test.bmp");
+    }
+}
+
+// Draw text with the frame buffer console.
+fn draw_text(gop: &mut GraphicsOutput) {
+    let mut console = FramebufferConsole::new(gop)
+        .expect("failed to create console")
+        .with_scrollback(100);
+    assert_eq!((console.columns(), console.rows()), (128, 48));
+
+    console.enable_cursor(true).unwrap();
+    writeln!(console, "Hello from the frame buffer console!").unwrap();
+    for row in 0..console.rows() {
+        writeln!(console, "Line {row}").unwrap();
+    }
+    assert_eq!(console.cursor_position(), (0, 47));
+
+    assert_eq!(console.scrollback_len(), 2);
+    console.scroll_view(2).unwrap();
+    assert_eq!(console.view_offset(), 2);
+    console.scroll_view(-1).unwrap();
+    writeln!(console, "Back at the bottom").unwrap();
+    assert_eq!(console.view_offset(), 0);
+}
+
+// Round-trip an image through the BMP encoder and decoder, and draw it in
+// the middle of the screen.
+fn draw_bmp(gop: &mut GraphicsOutput) {
+    fill_color(gop);
+
+    // A gradient with a different color in each corner.
+    let (width, height) = (256, 192);
+    let pixels = (0..height)
+        .flat_map(|y| {
+            (0..width).map(move |x| BltPixel::new(x as u8, (y * 255 / height) as u8, 128))
+        })
+        .collect();
+    let image = Image::new(width, height, pixels);
+
+    let mut data = Vec::new();
+    image.write_bmp(&mut data).unwrap();
+    let decoded = bmp::decode(&data).expect("failed to decode BMP image");
+    assert_eq!((decoded.width(), decoded.height()), (width, height));
+    let rgb = |p: &BltPixel| (p.red, p.green, p.blue);
+    assert!(
+        decoded
+            .pixels()
+            .iter()
+            .map(rgb)
+            .eq(image.pixels().iter().map(rgb))
+    );
+
+    decoded
+        .draw_at(gop, (1024 - width) / 2, (768 - height) / 2)
+        .expect("failed to draw BMP image");
+}
+
+// Draw in system memory and copy the changes to the screen.
+fn draw_backbuffer(gop: &mut GraphicsOutput) {
+    let mut surface = gop
+        .create_backbuffer()
+        .expect("failed to create back buffer");
+    assert_eq!(surface.resolution(), (1024, 768));
+    assert_eq!(surface.dirty_rect(), None);
+
+    surface.fill_rect((100, 100), (200, 50), BltPixel::new(255, 0, 0));
+    surface.set_pixel(1000, 700, BltPixel::new(0, 255, 0));
+    assert_eq!(surface.dirty_rect(), Some(((100, 100), (901, 601))));
+    surface.flush().expect("failed to flush back buffer");
+    assert_eq!(surface.dirty_rect(), None);
+
+    surface.set_pixel(10, 10, BltPixel::new(0, 0, 255));
+    surface
+        .flush_rect((0, 0), (20, 20))
+        .expect("failed to flush rectangle");
+    assert_eq!(surface.dirty_rect(), None);
 }
 
 // Draw directly to the frame buffer.
 fn draw_fb(gop: &mut GraphicsOutput) {
-    // The `virtio-gpu-pci` graphics device we use on aarch64 doesn't
-    // support `PixelFormat::BltOnly`.
-    if cfg!(target_arch = "aarch64") {
+    // The `virtio-gpu-pci` graphics device we use on aarch64 and riscv64
+    // doesn't support `PixelFormat::BltOnly`.
+    if cfg!(any(target_arch = "aarch64", target_arch = "riscv64")) {
         return;
     }
 
@@ -0,0 +1,31 @@
+// SPDX-License-Identifier: MIT OR Apache-2.0
+
+use uefi::boot;
+use uefi::input::{Chord, ConsoleInputs, KeyStream};
+use uefi::proto::console::text::InputEx;
+
+pub fn test() {
+    info!("Running extended text input protocol test");
+    let handle = boot::get_handle_for_protocol::<InputEx>().expect("missing InputEx protocol");
+    let mut input =
+        boot::open_protocol_exclusive::<InputEx>(handle).expect("failed to open InputEx protocol");
+
+    input.reset(false).expect("failed to reset input device");
+
+    let mut keys = KeyStream::new(&mut input);
+    keys.poll().expect("failed to poll keys");
+    assert_eq!(keys.take_chord(&[Chord::CTRL_ALT_DEL]), Ok(None));
+    keys.clear().expect("failed to clear keys");
+    assert!(keys.is_empty());
+    drop(input);
+
+    test_console_inputs();
+}
+
+// Read from all input devices at once.
+fn test_console_inputs() {
+    let mut inputs = ConsoleInputs::new().expect("failed to create ConsoleInputs");
+    // There is at least the keyboard and the serial terminal.
+    assert!(inputs.handles().len() >= 2);
+    while inputs.read_key().unwrap().is_some() {}
+}
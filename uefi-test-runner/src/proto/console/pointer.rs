@@ -1,6 +1,8 @@
 // SPDX-License-Identifier: MIT OR Apache-2.0
 
-use uefi::boot;
+use uefi::boot::{self, OpenProtocolAttributes, OpenProtocolParams};
+use uefi::input::PointerEvents;
+use uefi::proto::console::gop::GraphicsOutput;
 use uefi::proto::console::pointer::Pointer;
 
 pub fn test() {
@@ -22,4 +24,34 @@ pub fn test() {
     } else {
         info!("Pointer state has not changed since the last query");
     }
+    drop(pointer);
+
+    test_pointer_events();
+}
+
+fn test_pointer_events() {
+    info!("Running pointer events test");
+    let handle =
+        boot::get_handle_for_protocol::<GraphicsOutput>().expect("missing GraphicsOutput protocol");
+    let mode = unsafe {
+        boot::open_protocol::<GraphicsOutput>(
+            OpenProtocolParams {
+                handle,
+                agent: boot::image_handle(),
+                controller: None,
+            },
+            OpenProtocolAttributes::GetProtocol,
+        )
+    }
+    .expect("failed to open Graphics Output Protocol")
+    .current_mode_info();
+
+    let mut events = PointerEvents::new(&mode).expect("failed to open pointer devices");
+    let (width, height) = mode.resolution();
+    assert_eq!(events.position(), (width / 2, height / 2));
+    info!("Opened {} pointer devices", events.device_count());
+
+    if let Some(event) = events.poll().expect("failed to poll pointer events") {
+        info!("Pointer event: {event:?}");
+    }
 }
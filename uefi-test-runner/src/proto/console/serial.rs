@@ -1,8 +1,10 @@
 // SPDX-License-Identifier: MIT OR Apache-2.0
 
 use crate::reconnect_serial_to_console;
+use uefi::console::serial::SerialConsole;
 use uefi::proto::console::serial::{ControlBits, Serial};
-use uefi::{Result, ResultExt, Status, boot};
+use uefi::proto::console::text::{Key, ScanCode};
+use uefi::{Char16, Result, ResultExt, Status, boot};
 
 // For the duration of this function, the serial device is opened in
 // exclusive mode. That means logs will not work, which means we should
@@ -31,11 +33,22 @@ fn serial_test_helper(serial: &mut Serial) -> Result {
     let mut input = [0u8; MSG_LEN];
     serial.read(&mut input).discard_errdata()?;
 
+    // Check that escape sequences are decoded into keys.
+    let mut console = SerialConsole::new(serial);
+    console.write_str("\x1b[Ax")?;
+    let up = console.read_key()?;
+    let x = console.read_key()?;
+    let none = console.read_key()?;
+    let serial = console.serial();
+
     // Clean up after ourselves
     serial.reset()?;
     serial.set_control_bits(old_ctrl_bits & ControlBits::SETTABLE)?;
 
-    if OUTPUT == input {
+    let keys_ok = up == Some(Key::Special(ScanCode::UP))
+        && x == Some(Key::Printable(Char16::try_from('x').unwrap()))
+        && none.is_none();
+    if OUTPUT == input && keys_ok {
         Ok(())
     } else {
         Err(Status::ABORTED.into())
@@ -43,9 +56,9 @@ fn serial_test_helper(serial: &mut Serial) -> Result {
 }
 
 pub unsafe fn test() {
-    // The serial device under aarch64 doesn't support the software
-    // loopback feature needed for this test.
-    if cfg!(target_arch = "aarch64") {
+    // The serial devices under aarch64 and riscv64 don't support the
+    // software loopback feature needed for this test.
+    if cfg!(any(target_arch = "aarch64", target_arch = "riscv64")) {
         return;
     }
 
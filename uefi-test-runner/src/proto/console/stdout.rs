@@ -1,7 +1,9 @@
 // SPDX-License-Identifier: MIT OR Apache-2.0
 
+use core::fmt::Write;
+use uefi::console::ansi::AnsiOutput;
 use uefi::prelude::*;
-use uefi::proto::console::text::{Color, Output};
+use uefi::proto::console::text::{Color, Output, ScrollRegion};
 
 pub fn test(stdout: &mut Output) {
     info!("Running text output protocol test");
@@ -10,6 +12,9 @@ pub fn test(stdout: &mut Output) {
     change_text_mode(stdout);
     change_color(stdout);
     center_text(stdout);
+    ansi_output(stdout);
+    geometry(stdout);
+    scroll_region(stdout);
 
     // Print all modes.
     for (index, mode) in stdout.modes().enumerate() {
@@ -68,3 +73,47 @@ fn center_text(stdout: &mut Output) {
             _ => panic!("Failed to hide cursor"),
         });
 }
+
+// Write text with ANSI escape sequences.
+fn ansi_output(stdout: &mut Output) {
+    let mut out = AnsiOutput::new(stdout);
+    write!(out, "\x1b[2;5H\x1b[1;33;44mANSI\x1b[0m \x1b[Kcolors").unwrap();
+    assert_eq!(out.output().cursor_position(), (15, 1));
+    write!(out, "\x1b[3D\x1b[?25l\x1b[s\x1b[H\x1b[u").unwrap();
+    assert_eq!(out.output().cursor_position(), (12, 1));
+    writeln!(out).unwrap();
+}
+
+// Move the cursor relative to its position and change colors temporarily.
+fn geometry(stdout: &mut Output) {
+    let (columns, rows) = stdout.size().unwrap();
+    assert!(columns >= 80 && rows >= 25);
+
+    let saved = stdout.save_cursor();
+    stdout.set_cursor_position(10, 5).unwrap();
+    stdout.move_cursor_by(-3, 2).unwrap();
+    assert_eq!(stdout.cursor_position(), (7, 7));
+    stdout.move_cursor_by(-100, isize::MAX).unwrap();
+    assert_eq!(stdout.cursor_position(), (0, rows - 1));
+
+    stdout
+        .with_color(Color::Yellow, Color::Red, |stdout| {
+            stdout.set_cursor_position(7, 7).unwrap();
+            write!(stdout, "warning").unwrap();
+        })
+        .unwrap();
+    stdout.restore_cursor(saved).unwrap();
+    assert_eq!(stdout.cursor_position(), (saved.column, saved.row));
+}
+
+// Scroll a few rows of the screen.
+fn scroll_region(stdout: &mut Output) {
+    let mut region = ScrollRegion::new(stdout, 10, 13).unwrap();
+    for i in 0..5 {
+        writeln!(region, "line {i}").unwrap();
+    }
+    assert_eq!(region.line(0).unwrap(), "line 3");
+    assert_eq!(region.line(1).unwrap(), "line 4");
+    assert_eq!(region.line(2).unwrap(), "");
+    region.clear().unwrap();
+}
@@ -1,6 +1,7 @@
 // SPDX-License-Identifier: MIT OR Apache-2.0
 
 use alloc::boxed::Box;
+use alloc::string::ToString;
 use alloc::vec::Vec;
 use uefi::proto::device_path::build::{self, DevicePathBuilder};
 use uefi::proto::device_path::text::{
@@ -16,6 +17,7 @@ pub fn test() {
 
     test_convert_device_path_to_text();
     test_device_path_to_string();
+    test_device_path_display();
 
     test_convert_device_node_to_text();
     test_device_path_node_to_string();
@@ -102,6 +104,16 @@ fn test_convert_device_path_to_text() {
     );
 }
 
+/// Test `DevicePath::display`, which must match the firmware's output.
+fn test_device_path_display() {
+    let path = create_test_device_path();
+
+    assert_eq!(
+        path.display().to_string(),
+        "Ata(Primary,Master,0x1)/VenMsg(E0C14753-F9BE-11D2-9A0C-0090273FC14D)"
+    );
+}
+
 /// Test `DevicePath::to_string`.
 fn test_device_path_to_string() {
     let path = create_test_device_path();
@@ -0,0 +1,30 @@
+// SPDX-License-Identifier: MIT OR Apache-2.0
+
+use uefi::proto::kms::Kms;
+use uefi::{Status, boot};
+
+pub fn test() {
+    info!("Running KMS protocol test");
+
+    // OVMF doesn't provide a key management service.
+    let Ok(handle) = boot::get_handle_for_protocol::<Kms>() else {
+        info!("KMS protocol is not supported");
+        return;
+    };
+    let mut kms =
+        boot::open_protocol_exclusive::<Kms>(handle).expect("failed to open KMS protocol");
+
+    info!(
+        "KMS service {} version {}, {} key formats",
+        kms.service_id(),
+        kms.service_version(),
+        kms.key_formats().len()
+    );
+    match kms.service_status() {
+        Ok(()) => info!("KMS service is available"),
+        Err(err) => assert!(
+            [Status::NOT_READY, Status::NO_MAPPING, Status::NO_RESPONSE].contains(&err.status()),
+            "unexpected KMS service status: {err:?}"
+        ),
+    }
+}
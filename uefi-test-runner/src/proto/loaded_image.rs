@@ -1,12 +1,12 @@
 // SPDX-License-Identifier: MIT OR Apache-2.0
 
 use uefi::prelude::*;
-use uefi::proto::loaded_image::LoadedImage;
+use uefi::proto::loaded_image::{LoadOptions, LoadedImage};
 
 pub fn test() {
     info!("Running loaded image protocol test");
 
-    let loaded_image = boot::open_protocol_exclusive::<LoadedImage>(boot::image_handle())
+    let mut loaded_image = boot::open_protocol_exclusive::<LoadedImage>(boot::image_handle())
         .expect("Failed to open LoadedImage protocol");
 
     let load_options = loaded_image.load_options_as_bytes();
@@ -14,4 +14,28 @@ pub fn test() {
 
     let (image_base, image_size) = loaded_image.info();
     info!("LoadedImage image address: {image_base:?}, image size: {image_size} bytes");
+
+    test_owned_load_options(&mut loaded_image);
+}
+
+fn test_owned_load_options(loaded_image: &mut LoadedImage) {
+    let previous = loaded_image.load_options_as_bytes().map(<[u8]>::as_ptr);
+
+    let options = LoadOptions::new()
+        .arg(cstr16!("test.efi"))
+        .args([cstr16!("with space"), cstr16!(r#"q"uote"#)]);
+    let guard = loaded_image.set_owned_load_options(options);
+    assert_eq!(
+        &**guard.options(),
+        cstr16!(r#"test.efi "with space" "q^"uote""#)
+    );
+    drop(guard);
+    assert_eq!(
+        loaded_image.load_options_as_bytes().map(<[u8]>::as_ptr),
+        previous
+    );
+
+    let guard = loaded_image.set_owned_load_options(LoadOptions::new().arg(cstr16!("a b")));
+    let args = guard.load_options_args().unwrap();
+    assert_eq!(args, [cstr16!("a b")]);
 }
@@ -7,6 +7,7 @@ use uefi::boot::{
     self, EventType, OpenProtocolAttributes, OpenProtocolParams, ScopedProtocol, Tpl,
 };
 use uefi::data_types::Align;
+use uefi::fs::AsyncFile;
 use uefi::prelude::*;
 use uefi::proto::media::block::BlockIO;
 use uefi::proto::media::disk::{DiskIo, DiskIo2, DiskIo2Token};
@@ -17,6 +18,7 @@ use uefi::proto::media::file::{
 use uefi::proto::media::fs::SimpleFileSystem;
 use uefi::proto::media::partition::{MbrOsType, PartitionInfo};
 use uefi::runtime::{Daylight, Time, TimeParams};
+use uefi::task;
 
 #[repr(align(8))]
 struct AlignedBuf([u8; 256]);
@@ -127,6 +129,15 @@ fn test_existing_file(directory: &mut Directory) {
     info!("Successfully read {input_file_path}");
     assert_eq!(buffer, b"test input data");
 
+    // Read parts of the file at an offset.
+    let mut part = [0; 5];
+    file.read_exact_at(5, &mut part).unwrap();
+    assert_eq!(&part, b"input");
+    assert_eq!(
+        file.read_exact_at(12, &mut part).unwrap_err().status(),
+        Status::END_OF_FILE
+    );
+
     // Check file metadata.
     let mut info_buffer = vec![0; 128];
     let info = file.get_info::<FileInfo>(&mut info_buffer).unwrap();
@@ -201,6 +212,33 @@ fn test_create_file(directory: &mut Directory) {
     file.write(b"test output data").unwrap();
 }
 
+/// Test async writes and reads of a file.
+fn test_async_file(directory: &mut Directory) {
+    info!("Testing async file I/O");
+
+    let file = directory
+        .open(
+            cstr16!("async_test_file.txt"),
+            FileMode::CreateReadWrite,
+            FileAttribute::empty(),
+        )
+        .expect("failed to create file")
+        .into_regular_file()
+        .expect("not a regular file");
+    let mut file = AsyncFile::new(file).expect("file protocol revision 2 is not supported");
+
+    let mut buf = [0; 32];
+    let read = task::block_on(async {
+        file.write(b"async test data").await.unwrap();
+        file.flush().await.unwrap();
+        file.get_mut().set_position(0).unwrap();
+        file.read(&mut buf).await.unwrap()
+    });
+    assert_eq!(&buf[..read], b"async test data");
+
+    file.into_inner().delete().unwrap();
+}
+
 /// Test directory creation by
 /// - creating a new directory
 /// - creating a file in that directory
@@ -346,6 +384,22 @@ fn test_raw_disk_io2(handle: Handle) {
     }
 }
 
+/// Tests the async functions of the DiskIo2 protocol.
+fn test_async_disk_io2(handle: Handle) {
+    info!("Testing async disk I/O 2");
+
+    if let Ok(mut disk_io2) = boot::open_protocol_exclusive::<DiskIo2>(handle) {
+        let media_id = get_block_media_id(handle);
+
+        let mut buf = [0; 512];
+        task::block_on(disk_io2.read_disk_async(media_id, 0, &mut buf)).unwrap();
+
+        // Verify that the disk's MBR signature is correct
+        assert_eq!(buf[510], 0x55);
+        assert_eq!(buf[511], 0xaa);
+    }
+}
+
 fn test_disk_info() {
     let disk_handles = uefi::boot::find_handles::<DiskInfo>().unwrap();
 
@@ -404,8 +458,8 @@ fn find_test_disk() -> (Handle, ScopedProtocol<SimpleFileSystem>) {
     // This branch is due to the qemu machine type we use based on the architecture.
     // - *Q35* by default uses a SATA-Controller to connect disks.
     // - *virt* by default uses virtio to connect disks.
-    // The aarch64 UEFI Firmware does not yet seem to support SATA-Controllers.
-    #[cfg(any(target_arch = "arm", target_arch = "aarch64"))]
+    // The aarch64 and riscv64 UEFI Firmware does not yet seem to support SATA-Controllers.
+    #[cfg(any(target_arch = "arm", target_arch = "aarch64", target_arch = "riscv64"))]
     assert_eq!(handles.len(), 2);
     #[cfg(any(target_arch = "x86", target_arch = "x86_64"))]
     assert_eq!(handles.len(), 3);
@@ -427,6 +481,35 @@ fn find_test_disk() -> (Handle, ScopedProtocol<SimpleFileSystem>) {
     panic!("MbrTestDisk not found");
 }
 
+/// Test locating the test disk through the `uefi::fs` helpers.
+fn test_find_file_system() {
+    // The label comparison ignores ASCII case.
+    let mut fs = uefi::fs::find_by_label(cstr16!("MBRTESTDISK")).unwrap();
+    assert!(fs.try_exists(cstr16!("test_dir")).unwrap());
+    drop(fs);
+
+    assert_eq!(
+        uefi::fs::find_by_label(cstr16!("NoSuchLabel"))
+            .unwrap_err()
+            .status(),
+        Status::NOT_FOUND
+    );
+
+    // The test disk is an MBR disk, so it can't be found by partition GUID.
+    assert_eq!(
+        uefi::fs::find_by_partition_guid(uefi::guid!("01234567-89ab-cdef-0123-456789abcdef"))
+            .unwrap_err()
+            .status(),
+        Status::NOT_FOUND
+    );
+
+    // The test runner is loaded from `\EFI\Boot` of the ESP, next to the
+    // shell.
+    let mut fs = uefi::fs::from_image().unwrap();
+    assert!(fs.try_exists(cstr16!("test_runner.efi")).unwrap());
+    assert!(fs.try_exists(cstr16!("shell.efi")).unwrap());
+}
+
 /// Run various file-system related tests on a special test disk. The disk is created by
 /// `xtask/src/disk.rs`.
 pub fn test() {
@@ -474,6 +557,7 @@ pub fn test() {
         test_existing_file(&mut root_directory);
         test_create_file(&mut root_directory);
         test_create_directory(&mut root_directory);
+        test_async_file(&mut root_directory);
 
         test_partition_info(handle);
     }
@@ -485,7 +569,10 @@ pub fn test() {
     // tests work.
     crate::fs::test(sfs).unwrap();
 
+    test_find_file_system();
+
     test_raw_disk_io(handle);
     test_raw_disk_io2(handle);
+    test_async_disk_io2(handle);
     test_disk_info();
 }
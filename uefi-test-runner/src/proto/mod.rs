@@ -1,8 +1,10 @@
 // SPDX-License-Identifier: MIT OR Apache-2.0
 
+use alloc::vec;
+use core::mem::MaybeUninit;
 use uefi::boot::{self, OpenProtocolParams};
 use uefi::proto::loaded_image::LoadedImage;
-use uefi::{Identify, proto};
+use uefi::{Identify, Status, proto};
 
 pub fn test() {
     info!("Testing various protocols");
@@ -16,6 +18,7 @@ pub fn test() {
     debug::test();
     device_path::test();
     driver::test();
+    kms::test();
     load::test();
     loaded_image::test();
     media::test();
@@ -28,8 +31,8 @@ pub fn test() {
     usb::test();
     misc::test();
 
-    // disable the ATA test on aarch64 for now. The aarch64 UEFI Firmware does not yet seem
-    // to support SATA controllers (and providing an AtaPassThru protocol instance for them).
+    // disable the ATA test on aarch64 and riscv64 for now. Their UEFI Firmware does not yet
+    // seem to support SATA controllers (and providing an AtaPassThru protocol instance for them).
     #[cfg(any(target_arch = "x86", target_arch = "x86_64"))]
     ata::test();
     scsi::test();
@@ -54,6 +57,16 @@ fn find_protocol() {
         !handles.is_empty(),
         "There should be at least one implementation of Simple Text Output (stdout)"
     );
+
+    // The search without allocation finds the same handles.
+    let err = boot::find_handles_in::<proto::console::text::Output>(&mut [])
+        .expect_err("the empty buffer should be too small");
+    assert_eq!(err.status(), Status::BUFFER_TOO_SMALL);
+    assert_eq!(*err.data(), Some(handles.len()));
+    let mut buffer = vec![MaybeUninit::uninit(); handles.len()];
+    let found = boot::find_handles_in::<proto::console::text::Output>(&mut buffer)
+        .expect("Failed to retrieve list of handles");
+    assert_eq!(found, handles);
 }
 
 fn test_protocols_per_handle() {
@@ -80,6 +93,7 @@ mod console;
 mod debug;
 mod device_path;
 mod driver;
+mod kms;
 mod load;
 mod loaded_image;
 mod media;
@@ -90,14 +104,14 @@ mod pci;
 mod pi;
 mod rng;
 mod scsi;
+mod shell;
+mod shell_params;
 #[cfg(any(
     target_arch = "x86",
     target_arch = "x86_64",
     target_arch = "arm",
     target_arch = "aarch64"
 ))]
-mod shell;
-mod shell_params;
 mod shim;
 mod string;
 mod tcg;
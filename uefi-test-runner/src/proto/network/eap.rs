@@ -0,0 +1,25 @@
+// SPDX-License-Identifier: MIT OR Apache-2.0
+
+use uefi::boot;
+use uefi::proto::network::eap::{EapConfiguration, EapType};
+
+pub fn test() {
+    info!("Running EAP configuration protocol test");
+
+    // OVMF only provides the protocol if it is built with the network
+    // supplicant.
+    let Ok(handle) = boot::get_handle_for_protocol::<EapConfiguration>() else {
+        info!("EAP configuration protocol is not supported");
+        return;
+    };
+    let mut eap = boot::open_protocol_exclusive::<EapConfiguration>(handle)
+        .expect("failed to open EAP configuration protocol");
+
+    let methods = eap.supported_auth_methods().unwrap();
+    info!("Supported EAP methods: {methods:?}");
+    if methods.contains(&EapType::EAP_TLS) {
+        eap.set_auth_method(EapType::EAP_TLS).unwrap();
+        assert_eq!(eap.auth_method().unwrap(), EapType::EAP_TLS);
+    }
+    eap.set_identity("uefi-rs@example.com").unwrap();
+}
@@ -4,12 +4,17 @@ use alloc::vec::Vec;
 
 use uefi::proto::device_path::DevicePath;
 use uefi::proto::device_path::text::{AllowShortcuts, DisplayOnly};
-use uefi::proto::network::http::{HttpBinding, HttpHelper};
+use uefi::proto::network::http::{HttpBinding, HttpHelper, HttpUtilities};
 use uefi::proto::network::ip4config2::Ip4Config2;
 use uefi::{Handle, boot};
 
 use uefi_raw::protocol::network::http::HttpStatusCode;
 
+/// Files served by the HTTP server of the host, see `xtask/src/net.rs`.
+const HOST_HELLO_URL: &str = "http://192.168.17.2:21574/hello.txt";
+const HOST_LARGE_URL: &str = "http://192.168.17.2:21574/large.bin";
+const HOST_MISSING_URL: &str = "http://192.168.17.2:21574/missing";
+
 pub fn print_handle_devpath(prefix: &str, handle: &Handle) {
     let Ok(dp) = boot::open_protocol_exclusive::<DevicePath>(*handle) else {
         info!("{prefix}no device path for handle");
@@ -85,9 +90,42 @@ fn fetch_http(handle: Handle, url: &str) -> Option<Vec<u8>> {
     Some(data)
 }
 
+fn test_utilities() {
+    let Ok(handle) = boot::get_handle_for_protocol::<HttpUtilities>() else {
+        info!("HTTP utilities protocol is not supported");
+        return;
+    };
+    let utilities = boot::open_protocol_exclusive::<HttpUtilities>(handle)
+        .expect("failed to open HTTP utilities protocol");
+
+    let seed = b"Host: example.com\r\nAccept: */*\r\nConnection: close\r\n\r\n";
+    let block = utilities
+        .build(
+            seed,
+            &[c"Connection"],
+            &[(c"Accept", c"text/plain"), (c"User-Agent", c"uefi-rs")],
+        )
+        .unwrap();
+    let fields = utilities.parse(&block).unwrap();
+    let fields: Vec<_> = fields
+        .iter()
+        .map(|(name, value)| (name.to_str().unwrap(), value.to_str().unwrap()))
+        .collect();
+    assert_eq!(
+        fields,
+        [
+            ("Host", "example.com"),
+            ("Accept", "text/plain"),
+            ("User-Agent", "uefi-rs"),
+        ]
+    );
+}
+
 pub fn test() {
     info!("Testing ip4 config2 + http protocols");
 
+    test_utilities();
+
     let handles = boot::locate_handle_buffer(boot::SearchType::from_proto::<HttpBinding>())
         .expect("get nic handles");
 
@@ -98,6 +136,15 @@ pub fn test() {
         let mut ip4 = Ip4Config2::new(*h).expect("open ip4 config2 protocol");
         ip4.ifup(true).expect("acquire ipv4 address");
 
+        info!("Testing HTTP with the test host");
+        let body = fetch_http(*h, HOST_HELLO_URL).expect("http request to the host failed");
+        assert_eq!(body, b"Hello from the uefi-rs test host!\n");
+        // The large file is received in multiple parts.
+        let body = fetch_http(*h, HOST_LARGE_URL).expect("http request to the host failed");
+        assert_eq!(body.len(), 64 * 1024);
+        assert!(body.iter().enumerate().all(|(i, b)| *b == (i % 251) as u8));
+        assert!(fetch_http(*h, HOST_MISSING_URL).is_none());
+
         // hard to find web sites which still allow plain http these days ...
         info!("Testing HTTP");
         fetch_http(*h, "http://example.com/").expect("http request failed");
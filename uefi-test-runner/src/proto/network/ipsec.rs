@@ -0,0 +1,78 @@
+// SPDX-License-Identifier: MIT OR Apache-2.0
+
+use core::net::{IpAddr, Ipv4Addr};
+use uefi::proto::network::ipsec::{
+    ConfigSelector, IpAddressInfo, IpsecAction, IpsecAuthAlgorithm, IpsecConfig,
+    IpsecEncryptAlgorithm, IpsecMode, IpsecProtocolType, IpsecSaId, IpsecTrafficDirection, SaData,
+    SpdData, SpdSelector,
+};
+use uefi::{Status, boot};
+
+pub fn test() {
+    info!("Running IPsec configuration protocol test");
+
+    // OVMF only provides the protocol if it is built with IPsec support.
+    let Ok(handle) = boot::get_handle_for_protocol::<IpsecConfig>() else {
+        info!("IPsec configuration protocol is not supported");
+        return;
+    };
+    let mut ipsec = boot::open_protocol_exclusive::<IpsecConfig>(handle)
+        .expect("failed to open IPsec configuration protocol");
+
+    test_spd(&mut ipsec);
+    test_sad(&mut ipsec);
+}
+
+fn test_spd(ipsec: &mut IpsecConfig) {
+    let remote = [IpAddressInfo {
+        address: IpAddr::V4(Ipv4Addr::new(192, 0, 2, 0)).into(),
+        prefix_length: 24,
+    }];
+    let selector = SpdSelector::new(&[], &remote)
+        .unwrap()
+        .with_remote_ports(443, 0);
+    let data = SpdData::new(
+        b"uefi-rs-test",
+        IpsecTrafficDirection::OUTBOUND,
+        IpsecAction::BYPASS,
+    )
+    .unwrap();
+    ipsec.set_spd_entry(&selector, Some(&data), None).unwrap();
+
+    let stored = ipsec.get_data_vec(ConfigSelector::Spd(&selector)).unwrap();
+    assert!(stored.starts_with(b"uefi-rs-test\0"));
+
+    ipsec.set_spd_entry(&selector, None, None).unwrap();
+    let err = ipsec
+        .get_data_vec(ConfigSelector::Spd(&selector))
+        .unwrap_err();
+    assert_eq!(err.status(), Status::NOT_FOUND);
+}
+
+fn test_sad(ipsec: &mut IpsecConfig) {
+    let sa_id = IpsecSaId {
+        spi: 0x1000,
+        proto: IpsecProtocolType::ESP,
+        dest_address: IpAddr::V4(Ipv4Addr::new(192, 0, 2, 1)).into(),
+    };
+    let encrypt_key = [0x11; 16];
+    let auth_key = [0x22; 20];
+    let data = SaData::esp(
+        IpsecMode::TRANSPORT,
+        IpsecEncryptAlgorithm::AES_CBC,
+        &encrypt_key,
+        IpsecAuthAlgorithm::SHA1_HMAC,
+        &auth_key,
+    );
+    ipsec.set_sad_entry(&sa_id, Some(&data), None).unwrap();
+
+    let mut found = false;
+    let mut previous = None;
+    while let Some(next) = ipsec.next_sa_id(previous.as_ref()).unwrap() {
+        found |= next.spi == sa_id.spi;
+        previous = Some(next);
+    }
+    assert!(found, "SA not found in the SAD");
+
+    ipsec.set_sad_entry(&sa_id, None, None).unwrap();
+}
@@ -3,7 +3,10 @@
 pub fn test() {
     info!("Testing Network protocols");
 
+    eap::test();
     http::test();
+    ipsec::test();
+    sockets::test();
     pxe::test();
     // Currently, we are in the unfortunate situation that the SNP test
     // depends on the PXE test, as it assigns an IPv4 address to the
@@ -11,6 +14,9 @@ pub fn test() {
     snp::test();
 }
 
+mod eap;
 mod http;
+mod ipsec;
 mod pxe;
 mod snp;
+mod sockets;
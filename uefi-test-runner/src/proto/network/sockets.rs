@@ -0,0 +1,83 @@
+// SPDX-License-Identifier: MIT OR Apache-2.0
+
+use alloc::vec;
+use alloc::vec::Vec;
+use core::net::{Ipv4Addr, SocketAddr, SocketAddrV4};
+use core::time::Duration;
+use uefi::net::{TcpListener, TcpStream, UdpSocket};
+use uefi::proto::network::ip4config2::Ip4Config2;
+use uefi::proto::network::udp4::Udp4Binding;
+use uefi::{Handle, Status, boot, task};
+
+/// Address of the UDP echo service of the host, see `xtask/src/net.rs`.
+const ECHO_SERVICE: SocketAddr =
+    SocketAddr::V4(SocketAddrV4::new(Ipv4Addr::new(192, 168, 17, 2), 21572));
+
+/// Address of the TCP echo service of the host, see `xtask/src/net.rs`.
+const TCP_ECHO_SERVICE: SocketAddr =
+    SocketAddr::V4(SocketAddrV4::new(Ipv4Addr::new(192, 168, 17, 2), 21573));
+
+pub fn test() {
+    info!("Testing async sockets");
+
+    let handles = boot::find_handles::<Udp4Binding>().expect("failed to find UDP4 devices");
+    for handle in handles {
+        let mut ip4 = Ip4Config2::new(handle).expect("open ip4 config2 protocol");
+        ip4.ifup(false).expect("acquire ipv4 address");
+
+        task::block_on(async {
+            test_udp(handle).await;
+            test_tcp_echo(handle).await;
+            test_tcp_accept_timeout(handle).await;
+        });
+    }
+}
+
+async fn test_udp(device: Handle) {
+    let mut socket = UdpSocket::bind_on(device, (Ipv4Addr::UNSPECIFIED, 0).into()).unwrap();
+    socket.set_timeout(Some(Duration::from_secs(5)));
+    assert_ne!(socket.local_addr().unwrap().port(), 0);
+
+    // The echo service expects the length of the payload in front of it.
+    let request = [4, 1, 2, 3, 4];
+    assert_eq!(socket.send_to(&request, ECHO_SERVICE).await.unwrap(), 5);
+
+    let mut reply = [0; 16];
+    let (len, source) = socket.recv_from(&mut reply).await.unwrap();
+    assert_eq!(source, ECHO_SERVICE);
+    assert_eq!(&reply[..len], [4, 4, 3, 2, 1]);
+}
+
+async fn test_tcp_echo(device: Handle) {
+    let mut stream = TcpStream::connect_on(device, TCP_ECHO_SERVICE)
+        .await
+        .unwrap();
+    stream.set_timeout(Some(Duration::from_secs(5)));
+    assert_eq!(stream.peer_addr().unwrap(), TCP_ECHO_SERVICE);
+
+    // Send more than fits into a single segment, so that the reply arrives
+    // in multiple reads.
+    let request: Vec<u8> = (0..8000).map(|i| i as u8).collect();
+    stream.write_all(&request).await.unwrap();
+
+    let mut reply = vec![0; request.len()];
+    let mut received = 0;
+    while received < reply.len() {
+        let len = stream.read(&mut reply[received..]).await.unwrap();
+        assert_ne!(len, 0, "connection closed early");
+        received += len;
+    }
+    assert_eq!(reply, request);
+
+    stream.close().await.unwrap();
+}
+
+async fn test_tcp_accept_timeout(device: Handle) {
+    let mut listener = TcpListener::bind_on(device, (Ipv4Addr::UNSPECIFIED, 8080).into()).unwrap();
+    assert_eq!(listener.local_addr().unwrap().port(), 8080);
+
+    // Nobody connects, so the request is canceled after the timeout.
+    listener.set_timeout(Some(Duration::from_millis(100)));
+    let err = listener.accept().await.unwrap_err();
+    assert_eq!(err.status(), Status::TIMEOUT);
+}
@@ -44,6 +44,7 @@ fn has_nvme_drive() -> bool {
             let serial = core::str::from_utf8(&bfr[4..24]).unwrap().trim();
             info!("Found NVMe with serial: '{serial}'");
             if serial == "uefi-rsNvmePassThru" {
+                test_admin_commands(&nvme_pt);
                 return true;
             }
         }
@@ -51,3 +52,23 @@ fn has_nvme_drive() -> bool {
 
     false
 }
+
+fn test_admin_commands(nvme_pt: &NvmePassThru) {
+    let mut nvme_ctrl = nvme_pt.controller();
+    let identify = nvme_ctrl.identify_controller().unwrap();
+    assert_eq!(identify.serial_number(), "uefi-rsNvmePassThru");
+    assert!(identify.namespace_count() >= 1);
+
+    let smart = nvme_ctrl.smart_log().unwrap();
+    info!(
+        "NVMe temperature: {} °C, power cycles: {}",
+        smart.composite_temperature.celsius(),
+        smart.power_cycles
+    );
+    assert_ne!(smart.composite_temperature.kelvin(), 0);
+
+    let mut namespace = nvme_pt.iter_namespaces().next().unwrap();
+    let identify = namespace.identify_namespace().unwrap();
+    assert_ne!(identify.size(), 0);
+    assert!(identify.formatted_lba_format().block_size >= 512);
+}
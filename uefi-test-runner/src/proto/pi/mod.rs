@@ -4,6 +4,8 @@ pub fn test() {
     info!("Testing Platform Initialization protocols");
 
     mp::test();
+    status_code::test();
 }
 
 mod mp;
+mod status_code;
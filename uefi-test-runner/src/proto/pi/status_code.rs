@@ -0,0 +1,34 @@
+// SPDX-License-Identifier: MIT OR Apache-2.0
+
+use uefi::proto::pi::status_code::{
+    CodeType, ErrorOperation, ProgressOperation, Severity, StatusCode, SubClass,
+};
+use uefi::{boot, cstr8};
+
+pub fn test() {
+    info!("Running status code protocol test");
+    let Ok(handle) = boot::get_handle_for_protocol::<StatusCode>() else {
+        info!("Status code protocol is not supported");
+        return;
+    };
+    let status_code = boot::open_protocol_exclusive::<StatusCode>(handle)
+        .expect("failed to open status code protocol");
+
+    status_code
+        .report(
+            CodeType::Progress,
+            SubClass::EFI_APPLICATION.progress(ProgressOperation::INIT_BEGIN),
+            0,
+            None,
+        )
+        .expect("failed to report progress code");
+    status_code
+        .report_with_message(
+            CodeType::Error(Severity::MINOR),
+            SubClass::EFI_APPLICATION.error(ErrorOperation::NON_SPECIFIC),
+            0,
+            None,
+            cstr8!("uefi-test-runner status code test"),
+        )
+        .expect("failed to report error code");
+}
@@ -22,4 +22,8 @@ pub fn test() {
 
     assert_ne!([0u8; 4], buf);
     info!("Random buffer : {buf:?}");
+
+    let mut buf = [0u8; 32];
+    rng.get_rng_with_fallback(&mut buf).unwrap();
+    assert_ne!([0u8; 32], buf);
 }
@@ -1,12 +1,16 @@
 // SPDX-License-Identifier: MIT OR Apache-2.0
 
 use uefi::proto::scsi::ScsiRequestBuilder;
+use uefi::proto::scsi::command::{
+    Capacity, Inquiry, InquiryData, Read16, ReadCapacity10, ReadCapacity16, TestUnitReady,
+};
 use uefi::proto::scsi::pass_thru::ExtScsiPassThru;
 
 pub fn test() {
     info!("Running extended SCSI Pass Thru tests");
     test_allocating_api();
     test_reusing_buffer_api();
+    test_command_api();
 }
 
 fn test_allocating_api() {
@@ -17,7 +21,7 @@ fn test_allocating_api() {
     // Thus, we should see two controllers with support for EXT_SCSI_PASS_THRU on this platform
     #[cfg(any(target_arch = "x86", target_arch = "x86_64"))]
     assert_eq!(scsi_ctrl_handles.len(), 2);
-    #[cfg(any(target_arch = "arm", target_arch = "aarch64"))]
+    #[cfg(any(target_arch = "arm", target_arch = "aarch64", target_arch = "riscv64"))]
     assert_eq!(scsi_ctrl_handles.len(), 1);
 
     let mut found_drive = false;
@@ -110,3 +114,56 @@ fn test_reusing_buffer_api() {
 
     assert!(found_drive);
 }
+
+fn test_command_api() {
+    let scsi_ctrl_handles = uefi::boot::find_handles::<ExtScsiPassThru>().unwrap();
+
+    let mut found_drive = false;
+    for handle in scsi_ctrl_handles {
+        let scsi_pt = uefi::boot::open_protocol_exclusive::<ExtScsiPassThru>(handle).unwrap();
+        let io_align = scsi_pt.io_align();
+        for mut device in scsi_pt.iter_devices() {
+            let request = Inquiry::standard().builder(io_align).unwrap().build();
+            let Ok(response) = device.execute_command(request) else {
+                continue; // no device
+            };
+            let Some(inquiry) = response.read_buffer().and_then(InquiryData::parse) else {
+                continue;
+            };
+            if !inquiry.is_connected()
+                || inquiry.vendor_id() != "uefi-rs"
+                || inquiry.product_id() != "ExtScsiPassThru"
+            {
+                continue;
+            }
+            found_drive = true;
+
+            let request = TestUnitReady.builder(io_align).unwrap().build();
+            device.execute_command(request).unwrap();
+
+            // The test disk has a size of 10 MiB.
+            let request = ReadCapacity10.builder(io_align).unwrap().build();
+            let response = device.execute_command(request).unwrap();
+            let capacity = Capacity::parse_10(response.read_buffer().unwrap()).unwrap();
+            assert_eq!(capacity.size(), 10 * 1024 * 1024);
+
+            let request = ReadCapacity16.builder(io_align).unwrap().build();
+            let response = device.execute_command(request).unwrap();
+            let capacity16 = Capacity::parse_16(response.read_buffer().unwrap()).unwrap();
+            assert_eq!(capacity16.last_lba, capacity.last_lba);
+            assert_eq!(capacity16.block_size, capacity.block_size);
+
+            let request = Read16::new(capacity.last_lba, 1)
+                .builder(io_align, capacity.block_size)
+                .unwrap()
+                .build();
+            let response = device.execute_command(request).unwrap();
+            assert_eq!(
+                response.read_buffer().unwrap().len(),
+                capacity.block_size as usize
+            );
+        }
+    }
+
+    assert!(found_drive);
+}
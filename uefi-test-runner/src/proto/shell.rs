@@ -8,6 +8,17 @@ pub fn test() {
 
     let handle = boot::get_handle_for_protocol::<Shell>().expect("No Shell handles");
 
-    let mut _shell =
+    let shell =
         boot::open_protocol_exclusive::<Shell>(handle).expect("Failed to open Shell protocol");
+
+    // The current directory of the current mapping, if there is any, is
+    // prefixed by the mapping name.
+    if let Some(cur_dir) = shell.current_dir(None) {
+        info!("Shell current directory: {cur_dir}");
+        assert!(
+            cur_dir
+                .iter()
+                .any(|c| *c == uefi::Char16::try_from(':').unwrap())
+        );
+    }
 }
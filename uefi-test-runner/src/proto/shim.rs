@@ -16,6 +16,19 @@ pub fn test() {
         shim_lock
             .verify(&buffer)
             .expect_err("shim failed to reject an invalid application");
+
+        // With Secure Boot, the test runner is signed with a key in db,
+        // which shim trusts as well.
+        #[cfg(feature = "secure_boot")]
+        {
+            let image = uefi::fs::from_image()
+                .unwrap()
+                .read(uefi::cstr16!("test_runner.efi"))
+                .unwrap();
+            shim_lock
+                .verify(&image)
+                .expect("shim failed to verify the signed test runner");
+        }
     } else {
         info!("Shim lock protocol is not supported");
     }
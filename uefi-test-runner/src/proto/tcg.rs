@@ -1,8 +1,9 @@
 // SPDX-License-Identifier: MIT OR Apache-2.0
 
 use alloc::vec::Vec;
-use uefi::boot;
-use uefi::proto::tcg::{AlgorithmId, EventType, HashAlgorithm, PcrIndex, v1, v2};
+use uefi::proto::tcg::{AlgorithmId, EventData, EventType, HashAlgorithm, PcrIndex, tpm2, v1, v2};
+use uefi::runtime::{self, VariableVendor};
+use uefi::{Status, boot, cstr16, measured_boot};
 
 // Environmental note:
 //
@@ -348,13 +349,105 @@ pub fn test_tcg_v2() {
     );
 
     // PCR 8 has been extended: `sha1([0; 20], sha1("some-data"))`.
+    let expected_pcr_8 = [
+        0x16, 0x53, 0x7d, 0xaa, 0x5d, 0xbd, 0xa8, 0x45, 0xe3, 0x30, 0x9e, 0x40, 0xe8, 0x74, 0xd1,
+        0x50, 0x64, 0x73, 0x2f, 0x87,
+    ];
+
+    // Replaying the log extends PCR 8 with the digest of the event.
+    let replayed = log.replay_pcr(pcr_index, AlgorithmId::SHA1, |data| {
+        assert_eq!(data, [[0; 20], expected_hash_sha1].concat());
+        expected_pcr_8
+    });
+    assert_eq!(replayed, Some(expected_pcr_8));
+
+    assert_eq!(tcg_v2_read_pcr_8(&mut tcg), expected_pcr_8);
+    let value = tcg.read_pcr(pcr_index, AlgorithmId::SHA1).unwrap();
+    assert_eq!(value.algorithm(), AlgorithmId::SHA1);
+    assert_eq!(*value, expected_pcr_8);
     assert_eq!(
-        tcg_v2_read_pcr_8(&mut tcg),
-        [
-            0x16, 0x53, 0x7d, 0xaa, 0x5d, 0xbd, 0xa8, 0x45, 0xe3, 0x30, 0x9e, 0x40, 0xe8, 0x74,
-            0xd1, 0x50, 0x64, 0x73, 0x2f, 0x87,
-        ]
+        tcg.read_pcr(PcrIndex(24), AlgorithmId::SHA1)
+            .unwrap_err()
+            .status(),
+        Status::INVALID_PARAMETER
+    );
+
+    // Measure the same data again, with a description.
+    tcg.measure(pcr_index, EventType::IPL, b"description", data_to_hash)
+        .unwrap();
+    let log = tcg.get_event_log_v2().unwrap();
+    let entry = log.iter().last().unwrap();
+    assert_eq!(entry.pcr_index(), pcr_index);
+    assert_eq!(entry.event_data(), b"description");
+    assert_eq!(
+        entry.digests().into_iter().next(),
+        Some((AlgorithmId::SHA1, expected_hash_sha1.as_slice()))
+    );
+    assert_ne!(
+        *tcg.read_pcr(pcr_index, AlgorithmId::SHA1).unwrap(),
+        expected_pcr_8
     );
+
+    test_measured_boot(&mut tcg);
+    test_tpm2(&mut tcg);
+}
+
+/// Test the helpers of the `measured_boot` module.
+fn test_measured_boot(tcg: &mut v2::Tcg) {
+    info!("Running measured boot test");
+
+    let pcr = PcrIndex(9);
+    measured_boot::measure_blob(tcg, pcr, "cmdline", b"quiet").unwrap();
+    measured_boot::measure_variable(
+        tcg,
+        pcr,
+        EventType::EFI_VARIABLE_DRIVER_CONFIG,
+        cstr16!("PlatformLang"),
+        &VariableVendor::GLOBAL_VARIABLE,
+    )
+    .unwrap();
+
+    let log = tcg.get_event_log_v2().unwrap();
+    let mut events = log
+        .iter()
+        .skip_while(|event| event.event_data() != b"cmdline");
+    assert_eq!(events.next().unwrap().event_type(), EventType::IPL);
+    let event = events.next().unwrap();
+    assert_eq!(event.pcr_index(), pcr);
+    let EventData::Variable { vendor, data, .. } = event.data() else {
+        panic!("not a variable event");
+    };
+    assert_eq!(vendor, VariableVendor::GLOBAL_VARIABLE.0);
+    let (expected, _) =
+        runtime::get_variable_boxed(cstr16!("PlatformLang"), &VariableVendor::GLOBAL_VARIABLE)
+            .unwrap();
+    assert_eq!(data, &*expected);
+}
+
+/// Test the TPM2 commands of the `tpm2` module.
+fn test_tpm2(tcg: &mut v2::Tcg) {
+    info!("Running TPM2 commands test");
+
+    let mut random = [0; 40];
+    tpm2::get_random(tcg, &mut random).unwrap();
+    assert_ne!(random, [0; 40]);
+
+    // The software TPM of QEMU is made by IBM.
+    assert_eq!(
+        tpm2::get_tpm_property(tcg, tpm2::TpmProperty::MANUFACTURER),
+        Ok(Some(u32::from_be_bytes(*b"IBM\0")))
+    );
+
+    let value = tpm2::pcr_read(tcg, PcrIndex(0), AlgorithmId::SHA1)
+        .unwrap()
+        .unwrap();
+    assert_eq!(value.len(), 20);
+
+    // No NV index is defined.
+    let Err(tpm2::Error::Tpm(code)) = tpm2::nv_read_public(tcg, 0x0150_0000) else {
+        panic!("NV index 0x01500000 exists");
+    };
+    assert_eq!(code.base(), tpm2::ResponseCode::HANDLE);
 }
 
 pub fn test() {
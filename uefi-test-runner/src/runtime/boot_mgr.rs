@@ -0,0 +1,89 @@
+// SPDX-License-Identifier: MIT OR Apache-2.0
+
+use alloc::vec::Vec;
+use uefi::boot_mgr::{BootManager, KeyModifiers, LoadOption, LoadOptionType};
+use uefi::prelude::*;
+use uefi::proto::console::text::{Key, ScanCode};
+use uefi::proto::device_path::build::{self, DevicePathBuilder};
+
+pub fn test() {
+    info!("Testing the boot manager");
+
+    let manager = BootManager::new();
+    let order = manager.boot_order().unwrap();
+    info!("BootOrder: {order:04X?}");
+    for entry in manager.entries().unwrap() {
+        info!("Boot{:04X}: {}", entry.number, entry.option.description);
+    }
+
+    let mut buf = Vec::new();
+    let path = DevicePathBuilder::with_vec(&mut buf)
+        .push(&build::media::FilePath {
+            path_name: cstr16!("\\EFI\\TEST\\TEST.EFI"),
+        })
+        .unwrap()
+        .finalize()
+        .unwrap();
+    let mut option = LoadOption::new(cstr16!("uefi-rs test entry"), path);
+    option.optional_data = b"test".to_vec();
+
+    // Add an entry, which is appended to the boot order.
+    let number = manager.add_entry(&option).unwrap();
+    assert_eq!(manager.entry(number).unwrap(), option);
+    assert_eq!(manager.boot_order().unwrap().last(), Some(&number));
+    assert!(
+        manager
+            .entries()
+            .unwrap()
+            .iter()
+            .any(|entry| entry.number == number && entry.option == option)
+    );
+
+    manager.set_boot_next(Some(number)).unwrap();
+    assert_eq!(manager.boot_next().unwrap(), Some(number));
+
+    // Bind a hotkey to the entry.
+    let key_number = manager
+        .bind_key(
+            number,
+            KeyModifiers::CONTROL,
+            &[Key::Special(ScanCode::FUNCTION_9)],
+        )
+        .unwrap();
+    let key_entry = manager
+        .key_entries()
+        .unwrap()
+        .into_iter()
+        .find(|entry| entry.number == key_number)
+        .unwrap();
+    assert_eq!(key_entry.option.boot_option, number);
+    assert!(key_entry.option.matches(&option));
+    manager.delete_key_entry(key_number).unwrap();
+
+    // Deleting the entry restores the previous configuration.
+    manager.delete_entry(number).unwrap();
+    assert_eq!(
+        manager.entry(number).unwrap_err().status(),
+        Status::NOT_FOUND
+    );
+    assert_eq!(manager.boot_order().unwrap(), order);
+    assert_eq!(manager.boot_next().unwrap(), None);
+
+    // Driver entries work the same, with their own order.
+    let driver_order = manager.order(LoadOptionType::Driver).unwrap();
+    let number = manager
+        .add_entry_of(LoadOptionType::Driver, &option)
+        .unwrap();
+    assert_eq!(
+        manager.entry_of(LoadOptionType::Driver, number).unwrap(),
+        option
+    );
+    assert_eq!(
+        manager.order(LoadOptionType::Driver).unwrap().last(),
+        Some(&number)
+    );
+    manager
+        .delete_entry_of(LoadOptionType::Driver, number)
+        .unwrap();
+    assert_eq!(manager.order(LoadOptionType::Driver).unwrap(), driver_order);
+}
@@ -1,13 +1,21 @@
 // SPDX-License-Identifier: MIT OR Apache-2.0
 
+mod boot_mgr;
+#[cfg(feature = "secure_boot")]
+mod secure_boot;
 mod vars;
 
-use uefi::runtime::{self, Daylight, Time, TimeParams};
+use uefi::boot;
+use uefi::runtime::{self, Daylight, MonotonicId, Time, TimeParams};
 
 pub fn test() {
     info!("Testing runtime services");
     vars::test();
+    boot_mgr::test();
+    #[cfg(feature = "secure_boot")]
+    secure_boot::test();
     test_time();
+    test_monotonic_count();
 }
 
 fn test_time() {
@@ -37,3 +45,13 @@ fn test_time() {
     info!("After setting time: {now}");
     assert_eq!(now.year(), 2020);
 }
+
+fn test_monotonic_count() {
+    let count = boot::get_next_monotonic_count().unwrap();
+    assert!(boot::get_next_monotonic_count().unwrap() > count);
+
+    let id = MonotonicId::next().unwrap();
+    assert_eq!(id.high(), (count >> 32) as u32);
+    assert!(u64::from(id) > count);
+    assert!(MonotonicId::next().unwrap() > id);
+}
@@ -0,0 +1,85 @@
+// SPDX-License-Identifier: MIT OR Apache-2.0
+
+//! Tests that only work with Secure Boot enabled and the test keys of
+//! `cargo xtask run --secure-boot` enrolled.
+
+use uefi::runtime::{self, VariableAttributes, VariableVendor};
+use uefi::{Status, cstr16, fs};
+
+/// Attributes of the authenticated variables of the signature databases.
+const AUTH_ATTRS: VariableAttributes = VariableAttributes::NON_VOLATILE
+    .union(VariableAttributes::BOOTSERVICE_ACCESS)
+    .union(VariableAttributes::RUNTIME_ACCESS)
+    .union(VariableAttributes::TIME_BASED_AUTHENTICATED_WRITE_ACCESS);
+
+/// Test that writes to the Secure Boot variables are rejected unless they
+/// are signed with an enrolled key.
+fn test_unauthenticated_writes() {
+    info!("Testing unauthenticated writes of Secure Boot variables");
+
+    for (name, vendor) in [
+        (cstr16!("PK"), VariableVendor::GLOBAL_VARIABLE),
+        (cstr16!("KEK"), VariableVendor::GLOBAL_VARIABLE),
+        (cstr16!("db"), VariableVendor::IMAGE_SECURITY_DATABASE),
+    ] {
+        let err = runtime::set_variable(name, &vendor, AUTH_ATTRS, &[0; 64])
+            .expect_err("unsigned write should be rejected");
+        assert_eq!(err.status(), Status::SECURITY_VIOLATION);
+    }
+
+    // `SecureBoot` is read-only.
+    let attrs = VariableAttributes::BOOTSERVICE_ACCESS | VariableAttributes::RUNTIME_ACCESS;
+    let err = runtime::set_variable(
+        cstr16!("SecureBoot"),
+        &VariableVendor::GLOBAL_VARIABLE,
+        attrs,
+        &[0],
+    )
+    .expect_err("SecureBoot should be read-only");
+    assert_eq!(err.status(), Status::WRITE_PROTECTED);
+}
+
+/// Test appending a certificate to db with an update signed by the KEK.
+/// The update is created by xtask next to the test runner.
+fn test_authenticated_write() {
+    info!("Testing authenticated write of db");
+
+    let update = fs::from_image()
+        .unwrap()
+        .read(cstr16!("db_append.auth"))
+        .unwrap();
+
+    let name = cstr16!("db");
+    let vendor = VariableVendor::IMAGE_SECURITY_DATABASE;
+    let (db, _) = runtime::get_variable_boxed(name, &vendor).unwrap();
+
+    runtime::set_variable(
+        name,
+        &vendor,
+        AUTH_ATTRS | VariableAttributes::APPEND_WRITE,
+        &update,
+    )
+    .expect("signed update should be accepted");
+
+    let (new_db, attrs) = runtime::get_variable_boxed(name, &vendor).unwrap();
+    assert!(new_db.len() > db.len());
+    assert!(new_db.starts_with(&db));
+    assert!(attrs.contains(VariableAttributes::TIME_BASED_AUTHENTICATED_WRITE_ACCESS));
+
+    // Tampering with the update breaks its signature.
+    let mut tampered = update;
+    *tampered.last_mut().unwrap() ^= 0xff;
+    let err = runtime::set_variable(
+        name,
+        &vendor,
+        AUTH_ATTRS | VariableAttributes::APPEND_WRITE,
+        &tampered,
+    )
+    .expect_err("tampered update should be rejected");
+    assert_eq!(err.status(), Status::SECURITY_VIOLATION);
+}
+
+pub fn test() {
+    test_unauthenticated_writes();
+    test_authenticated_write();
+}
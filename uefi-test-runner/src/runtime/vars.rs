@@ -1,9 +1,11 @@
 // SPDX-License-Identifier: MIT OR Apache-2.0
 
+use alloc::vec::Vec;
 use log::info;
+use uefi::config_store::{ConfigData, ConfigStoreError, VarStore};
 use uefi::prelude::*;
 use uefi::runtime::{VariableAttributes, VariableVendor};
-use uefi::{CStr16, Error, guid, runtime};
+use uefi::{CStr16, Error, guid, runtime, secure_boot};
 
 /// Test variable name.
 const NAME: &CStr16 = cstr16!("UefiRsTestVar");
@@ -41,11 +43,34 @@ fn test_variables() {
     assert_eq!(data, VALUE);
     assert_eq!(attrs, ATTRS);
 
+    // Test `get_variable_array`.
+    let (data, attrs) =
+        runtime::get_variable_array::<9>(NAME, VENDOR).expect("failed to get variable");
+    assert_eq!(&data, VALUE);
+    assert_eq!(attrs, ATTRS);
+    assert_eq!(
+        runtime::get_variable_array::<8>(NAME, VENDOR).unwrap_err(),
+        Error::new(Status::BUFFER_TOO_SMALL, Some(9))
+    );
+    assert_eq!(
+        runtime::get_variable_array::<10>(NAME, VENDOR).unwrap_err(),
+        Error::new(Status::BAD_BUFFER_SIZE, Some(9))
+    );
+
     // Test `get_variable_boxed`.
     let (data, attrs) = runtime::get_variable_boxed(NAME, VENDOR).expect("failed to get variable");
     assert_eq!(&*data, VALUE);
     assert_eq!(attrs, ATTRS);
 
+    // Test `get_variable_boxed` with a variable that doesn't fit in its
+    // stack buffer.
+    let large_value = [0x5a; 1000];
+    runtime::set_variable(NAME, VENDOR, ATTRS, &large_value).expect("failed to set variable");
+    let (data, attrs) = runtime::get_variable_boxed(NAME, VENDOR).expect("failed to get variable");
+    assert_eq!(&*data, large_value);
+    assert_eq!(attrs, ATTRS);
+    runtime::set_variable(NAME, VENDOR, ATTRS, VALUE).expect("failed to set variable");
+
     // Test that the variable is present in the `variable_keys` iterator.
     let find_by_key = || {
         runtime::variable_keys().any(|k| {
@@ -78,7 +103,64 @@ fn test_variable_info() {
     info!("Storage for volatile runtime variables: {info:?}");
 }
 
+fn test_secure_boot_state() {
+    let state = secure_boot::state().unwrap();
+    info!("Secure Boot state: {state:?}");
+
+    // The firmware doesn't verify images in setup mode.
+    if state.mode() == secure_boot::Mode::Setup {
+        assert!(!state.secure_boot);
+    }
+    // The image is not loaded by shim.
+    assert_eq!(state.shim_validation_disabled, None);
+
+    // xtask enrolls the test keys with `--secure-boot`.
+    if cfg!(feature = "secure_boot") {
+        assert!(state.secure_boot);
+        assert_eq!(state.mode(), secure_boot::Mode::User);
+    }
+}
+
+/// Test value of the `VarStore` test.
+#[derive(Debug, Default, PartialEq)]
+struct TestConfig(u32);
+
+impl ConfigData for TestConfig {
+    const VERSION: u16 = 1;
+
+    fn encode(&self, out: &mut Vec<u8>) {
+        out.extend_from_slice(&self.0.to_le_bytes());
+    }
+
+    fn decode(bytes: &[u8]) -> Option<Self> {
+        Some(Self(u32::from_le_bytes(bytes.try_into().ok()?)))
+    }
+}
+
+fn test_config_store() {
+    let store =
+        VarStore::<TestConfig>::new(cstr16!("UefiRsTestConfig"), *VENDOR).with_attributes(ATTRS);
+    assert_eq!(store.load().unwrap(), None);
+    assert_eq!(store.load_or_default().unwrap(), TestConfig(0));
+
+    store.store(&TestConfig(42)).unwrap();
+    assert_eq!(store.load().unwrap(), Some(TestConfig(42)));
+
+    // Values above the maximum size are rejected.
+    let small = VarStore::<TestConfig>::new(cstr16!("UefiRsTestConfig"), *VENDOR)
+        .with_attributes(ATTRS)
+        .with_max_size(8);
+    assert_eq!(small.load(), Err(ConfigStoreError::TooLarge));
+    assert_eq!(small.store(&TestConfig(1)), Err(ConfigStoreError::TooLarge));
+
+    store.delete().unwrap();
+    assert_eq!(store.load().unwrap(), None);
+    store.delete().unwrap();
+}
+
 pub fn test() {
     test_variable_info();
     test_variables();
+    test_config_store();
+    test_secure_boot_state();
 }
@@ -1,11 +1,21 @@
 // SPDX-License-Identifier: MIT OR Apache-2.0
 
-//! This module exports [`Allocator`].
+//! This module exports [`Allocator`] and [`TypedAllocator`].
 //!
 //! The allocator can be used as global Rust allocator using the
-//! `global_allocator` crate feature. See [`helpers`] for more info.
+//! `global_allocator` crate feature. See [`helpers`] for more info. The
+//! memory type of its allocations can be chosen with [`set_memory_type`].
+//!
+//! With the `alloc_tracking` feature, the allocations are counted, see
+//! [`mem::alloc_stats`].
+//!
+//! [`TypedAllocator`] allocates memory of a fixed memory type. With the
+//! `unstable` feature, both allocators implement [`core::alloc::Allocator`],
+//! so collections like `Vec::new_in` can be tagged with a memory type of
+//! their own.
 //!
 //! [`helpers`]: uefi::helpers
+//! [`mem::alloc_stats`]: crate::mem::alloc_stats
 
 use crate::boot::{self, AllocateType};
 use crate::mem::memory_map::MemoryType;
@@ -15,16 +25,32 @@ use core::ptr::{self, NonNull};
 use core::sync::atomic::{AtomicU32, Ordering};
 use uefi_raw::table::boot::PAGE_SIZE;
 
-/// Get the memory type to use for allocation.
+/// Memory type of the allocations of [`Allocator`]. `RESERVED` indicates
+/// that the value hasn't been set yet.
+static MEMORY_TYPE: AtomicU32 = AtomicU32::new(MemoryType::RESERVED.0);
+
+/// Sets the memory type of the allocations of [`Allocator`], e.g. to
+/// [`MemoryType::RUNTIME_SERVICES_DATA`] for memory that must be kept by the
+/// operating system, or to a custom OEM type.
+///
+/// This only affects later allocations. It should be called as early as
+/// possible, before the first allocation. By default, the [data type] of the
+/// loaded image is used.
 ///
-/// The first time this is called, the data type of the loaded image will be
-/// retrieved. That value is cached in a static and reused on subsequent
-/// calls. If the memory type of the loaded image cannot be retrieved for some
-/// reason, a default of `LOADER_DATA` is used.
-fn get_memory_type() -> MemoryType {
-    // Initialize to a `RESERVED` to indicate the actual value hasn't been set yet.
-    static MEMORY_TYPE: AtomicU32 = AtomicU32::new(MemoryType::RESERVED.0);
+/// [data type]: LoadedImage::data_type
+pub fn set_memory_type(memory_type: MemoryType) {
+    MEMORY_TYPE.store(memory_type.0, Ordering::Release);
+}
 
+/// Get the memory type to use for allocation.
+///
+/// Unless it was set with [`set_memory_type`], the first time this is
+/// called, the data type of the loaded image will be retrieved. That value
+/// is cached in a static and reused on subsequent calls. If the memory type
+/// of the loaded image cannot be retrieved for some reason, a default of
+/// `LOADER_DATA` is used.
+#[must_use]
+pub fn memory_type() -> MemoryType {
     let memory_type = MEMORY_TYPE.load(Ordering::Acquire);
     if memory_type == MemoryType::RESERVED.0 {
         let memory_type = if let Ok(loaded_image) =
@@ -90,6 +116,78 @@ const fn layout_allows_page_alloc_shortcut(layout: &Layout) -> bool {
     layout.size() % PAGE_SIZE == 0 && layout.align() == PAGE_SIZE
 }
 
+/// Allocates memory of `memory_type` for `layout` using the UEFI boot
+/// services, or returns a null pointer.
+fn allocate(memory_type: MemoryType, layout: Layout) -> *mut u8 {
+    if !boot::are_boot_services_active() {
+        return ptr::null_mut();
+    }
+
+    let use_page_shortcut = layout_allows_page_alloc_shortcut(&layout);
+
+    let ptr = match (use_page_shortcut, layout.align()) {
+        // Allocating pages is actually very expected in UEFI OS loaders, so
+        // it makes sense to provide this optimization.
+        (true, _) => {
+            // To spammy, but useful for manual testing.
+            // log::trace!("Taking PAGE_SIZE shortcut for layout={layout:?}");
+            let count = layout.size().div_ceil(PAGE_SIZE);
+            boot::allocate_pages(AllocateType::AnyPages, memory_type, count)
+                .map(|ptr| ptr.as_ptr())
+                .unwrap_or(ptr::null_mut())
+        }
+        (false, 0..=8 /* UEFI default alignment */) => {
+            // The requested alignment is less than or equal to eight, and
+            // `allocate_pool` always provides eight-byte alignment, so we can
+            // use `allocate_pool` directly.
+            boot::allocate_pool(memory_type, layout.size())
+                .map(|ptr| ptr.as_ptr())
+                .unwrap_or(ptr::null_mut())
+        }
+        (false, 9..) => alloc_pool_aligned(memory_type, layout.size(), layout.align()),
+    };
+
+    #[cfg(feature = "alloc_tracking")]
+    if !ptr.is_null() {
+        crate::mem::record_alloc(layout.size());
+    }
+    ptr
+}
+
+/// Frees memory allocated with [`allocate`] for `layout`.
+///
+/// This will panic after exiting boot services.
+unsafe fn deallocate(ptr: *mut u8, layout: Layout) {
+    let ptr = NonNull::new(ptr).unwrap();
+
+    #[cfg(feature = "alloc_tracking")]
+    crate::mem::record_dealloc(layout.size());
+
+    let use_page_shortcut = layout_allows_page_alloc_shortcut(&layout);
+
+    match (use_page_shortcut, layout.align()) {
+        (true, _) => {
+            // To spammy, but useful for manual testing.
+            // log::trace!("Taking PAGE_SIZE shortcut for layout={layout:?}");
+            let count = layout.size().div_ceil(PAGE_SIZE);
+            unsafe { boot::free_pages(ptr, count).unwrap() }
+        }
+        (false, 0..=8 /* UEFI default alignment */) => {
+            // Warning: this will panic after exiting boot services.
+            unsafe { boot::free_pool(ptr) }.unwrap();
+        }
+        (false, 9..) => {
+            let ptr = ptr.as_ptr().cast::<*mut u8>();
+            // Retrieve the pointer to the full allocation that was packed right
+            // before the aligned allocation in `alloc`.
+            let actual_alloc_ptr = unsafe { ptr.sub(1).read() };
+            let ptr = NonNull::new(actual_alloc_ptr).unwrap();
+            // Warning: this will panic after exiting boot services.
+            unsafe { boot::free_pool(ptr) }.unwrap();
+        }
+    }
+}
+
 /// Allocator using UEFI boot services.
 ///
 /// This type implements [`GlobalAlloc`] and can be marked with the
@@ -103,7 +201,9 @@ pub struct Allocator;
 unsafe impl GlobalAlloc for Allocator {
     /// Allocate memory using the UEFI boot services.
     ///
-    /// The allocation's [memory type] matches the current image's [data type].
+    /// The allocation's [memory type] is the one returned by
+    /// [`memory_type`], which matches the current image's [data type] by
+    /// default.
     ///
     /// [memory type]: MemoryType
     /// [data type]: LoadedImage::data_type
@@ -111,61 +211,109 @@ unsafe impl GlobalAlloc for Allocator {
         if !boot::are_boot_services_active() {
             return ptr::null_mut();
         }
-
-        let memory_type = get_memory_type();
-        let use_page_shortcut = layout_allows_page_alloc_shortcut(&layout);
-
-        match (use_page_shortcut, layout.align()) {
-            // Allocating pages is actually very expected in UEFI OS loaders, so
-            // it makes sense to provide this optimization.
-            (true, _) => {
-                // To spammy, but useful for manual testing.
-                // log::trace!("Taking PAGE_SIZE shortcut for layout={layout:?}");
-                let count = layout.size().div_ceil(PAGE_SIZE);
-                boot::allocate_pages(AllocateType::AnyPages, memory_type, count)
-                    .map(|ptr| ptr.as_ptr())
-                    .unwrap_or(ptr::null_mut())
-            }
-            (false, 0..=8 /* UEFI default alignment */) => {
-                // The requested alignment is less than or equal to eight, and
-                // `allocate_pool` always provides eight-byte alignment, so we can
-                // use `allocate_pool` directly.
-                boot::allocate_pool(memory_type, layout.size())
-                    .map(|ptr| ptr.as_ptr())
-                    .unwrap_or(ptr::null_mut())
-            }
-            (false, 9..) => alloc_pool_aligned(memory_type, layout.size(), layout.align()),
-        }
+        allocate(memory_type(), layout)
     }
 
     /// Deallocate memory using the UEFI boot services.
     ///
     /// This will panic after exiting boot services.
     unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
-        let ptr = NonNull::new(ptr).unwrap();
-
-        let use_page_shortcut = layout_allows_page_alloc_shortcut(&layout);
-
-        match (use_page_shortcut, layout.align()) {
-            (true, _) => {
-                // To spammy, but useful for manual testing.
-                // log::trace!("Taking PAGE_SIZE shortcut for layout={layout:?}");
-                let count = layout.size().div_ceil(PAGE_SIZE);
-                unsafe { boot::free_pages(ptr, count).unwrap() }
-            }
-            (false, 0..=8 /* UEFI default alignment */) => {
-                // Warning: this will panic after exiting boot services.
-                unsafe { boot::free_pool(ptr) }.unwrap();
-            }
-            (false, 9..) => {
-                let ptr = ptr.as_ptr().cast::<*mut u8>();
-                // Retrieve the pointer to the full allocation that was packed right
-                // before the aligned allocation in `alloc`.
-                let actual_alloc_ptr = unsafe { ptr.sub(1).read() };
-                let ptr = NonNull::new(actual_alloc_ptr).unwrap();
-                // Warning: this will panic after exiting boot services.
-                unsafe { boot::free_pool(ptr) }.unwrap();
-            }
+        unsafe { deallocate(ptr, layout) }
+    }
+}
+
+/// Allocator using UEFI boot services, whose allocations have a fixed
+/// [`MemoryType`].
+///
+/// Unlike [`Allocator`], this is meant to be used as an allocator handle,
+/// e.g. with `Box::new_in` and `Vec::new_in` (feature `unstable`), to tag
+/// the memory of individual collections.
+///
+/// ```
+/// use uefi::allocator::TypedAllocator;
+/// use uefi::boot::MemoryType;
+///
+/// const RUNTIME_ALLOCATOR: TypedAllocator =
+///     TypedAllocator::new(MemoryType::RUNTIME_SERVICES_DATA);
+/// ```
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct TypedAllocator {
+    memory_type: MemoryType,
+}
+
+impl TypedAllocator {
+    /// Creates an allocator for memory of `memory_type`.
+    #[must_use]
+    pub const fn new(memory_type: MemoryType) -> Self {
+        Self { memory_type }
+    }
+
+    /// Returns the memory type of the allocations.
+    #[must_use]
+    pub const fn memory_type(&self) -> MemoryType {
+        self.memory_type
+    }
+}
+
+unsafe impl GlobalAlloc for TypedAllocator {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        allocate(self.memory_type, layout)
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        unsafe { deallocate(ptr, layout) }
+    }
+}
+
+#[cfg(all(feature = "unstable", feature = "alloc"))]
+mod allocator_api {
+    use super::{Allocator, TypedAllocator, allocate, deallocate, memory_type};
+    use crate::boot;
+    use crate::mem::memory_map::MemoryType;
+    use core::alloc::{AllocError, Layout};
+    use core::ptr::{self, NonNull};
+
+    /// Allocates memory for `layout`, with a dangling pointer for zero-sized
+    /// layouts. `memory_type` is only called for other layouts, while boot
+    /// services are active.
+    fn allocate_slice(
+        memory_type: impl FnOnce() -> MemoryType,
+        layout: Layout,
+    ) -> Result<NonNull<[u8]>, AllocError> {
+        let ptr = if layout.size() == 0 {
+            NonNull::new(ptr::without_provenance_mut(layout.align()))
+        } else if boot::are_boot_services_active() {
+            NonNull::new(allocate(memory_type(), layout))
+        } else {
+            None
+        };
+        ptr.map(|ptr| NonNull::slice_from_raw_parts(ptr, layout.size()))
+            .ok_or(AllocError)
+    }
+
+    unsafe fn deallocate_slice(ptr: NonNull<u8>, layout: Layout) {
+        if layout.size() != 0 {
+            unsafe { deallocate(ptr.as_ptr(), layout) }
+        }
+    }
+
+    unsafe impl core::alloc::Allocator for Allocator {
+        fn allocate(&self, layout: Layout) -> Result<NonNull<[u8]>, AllocError> {
+            allocate_slice(memory_type, layout)
+        }
+
+        unsafe fn deallocate(&self, ptr: NonNull<u8>, layout: Layout) {
+            unsafe { deallocate_slice(ptr, layout) }
+        }
+    }
+
+    unsafe impl core::alloc::Allocator for TypedAllocator {
+        fn allocate(&self, layout: Layout) -> Result<NonNull<[u8]>, AllocError> {
+            allocate_slice(|| self.memory_type, layout)
+        }
+
+        unsafe fn deallocate(&self, ptr: NonNull<u8>, layout: Layout) {
+            unsafe { deallocate_slice(ptr, layout) }
         }
     }
 }
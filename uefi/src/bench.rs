@@ -0,0 +1,489 @@
+// SPDX-License-Identifier: MIT OR Apache-2.0
+
+//! Benchmarking and timing utilities.
+//!
+//! [`Stopwatch`] measures elapsed time with the best counter available on
+//! the platform, [`Stats`] summarizes the durations of several runs, and
+//! [`measure`] combines both to benchmark a closure. The [`timed!`] macro
+//! logs how long a block of code takes.
+//!
+//! These utilities require boot services to be active.
+//!
+//! # Example
+//!
+//! ```no_run
+//! use uefi::bench::{self, Clock};
+//! use uefi::boot;
+//! use core::time::Duration;
+//!
+//! # fn example() -> uefi::Result {
+//! let clock = Clock::new()?;
+//! let stats = bench::measure(&clock, 10, || boot::stall(Duration::from_millis(1)));
+//! log::info!("stall: {stats}");
+//!
+//! let value = uefi::timed!("sum", (0..1000u32).sum::<u32>());
+//! # Ok(())
+//! # }
+//! ```
+//!
+//! [`timed!`]: crate::timed
+
+use crate::boot::{self, OpenProtocolAttributes, OpenProtocolParams};
+use crate::proto::misc::Timestamp;
+use crate::{Result, Status};
+use cfg_if::cfg_if;
+use core::fmt::{self, Display, Formatter};
+use core::ptr::NonNull;
+use core::sync::atomic::{AtomicU64, Ordering};
+use core::time::Duration;
+
+/// Counter used by a [`Clock`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ClockSource {
+    /// The [`Timestamp`] protocol.
+    Timestamp,
+    /// The x86 time stamp counter, read with `RDTSC`. Its frequency is
+    /// calibrated against [`boot::stall`] once.
+    Tsc,
+    /// The AArch64 virtual counter, read from `CNTVCT_EL0`.
+    Cntvct,
+}
+
+/// High-resolution counter.
+///
+/// The [`Timestamp`] protocol is used if the firmware provides it.
+/// Otherwise, the counter of the CPU is used on x86, x86_64 and AArch64.
+#[derive(Clone, Copy, Debug)]
+pub struct Clock {
+    source: ClockSource,
+    timestamp: Option<NonNull<Timestamp>>,
+    frequency: u64,
+    end_value: u64,
+}
+
+/// Calibrated frequency of the TSC, or zero if not calibrated yet.
+static TSC_FREQUENCY: AtomicU64 = AtomicU64::new(0);
+
+impl Clock {
+    /// Returns the best clock available.
+    ///
+    /// # Errors
+    ///
+    /// * [`Status::UNSUPPORTED`]: there is no [`Timestamp`] protocol, and
+    ///   the CPU counter can't be used on this architecture.
+    pub fn new() -> Result<Self> {
+        if let Some(clock) = Self::timestamp() {
+            return Ok(clock);
+        }
+        Self::cpu().ok_or_else(|| Status::UNSUPPORTED.into())
+    }
+
+    fn timestamp() -> Option<Self> {
+        let handle = boot::get_handle_for_protocol::<Timestamp>().ok()?;
+        let protocol = unsafe {
+            boot::open_protocol::<Timestamp>(
+                OpenProtocolParams {
+                    handle,
+                    agent: boot::image_handle(),
+                    controller: None,
+                },
+                OpenProtocolAttributes::GetProtocol,
+            )
+        }
+        .ok()?;
+        let properties = protocol.get_properties().ok()?;
+        if properties.frequency == 0 {
+            return None;
+        }
+        let timestamp = NonNull::from(protocol.get()?);
+
+        // Protocols opened with `GetProtocol` don't need to be closed.
+        core::mem::forget(protocol);
+        Some(Self {
+            source: ClockSource::Timestamp,
+            timestamp: Some(timestamp),
+            frequency: properties.frequency,
+            end_value: properties.end_value,
+        })
+    }
+
+    fn cpu() -> Option<Self> {
+        let source = cpu_source()?;
+        let frequency = match source {
+            ClockSource::Tsc => match TSC_FREQUENCY.load(Ordering::Relaxed) {
+                0 => {
+                    let start = read_cpu_counter();
+                    boot::stall(Duration::from_millis(10));
+                    let frequency = read_cpu_counter().wrapping_sub(start) * 100;
+                    TSC_FREQUENCY.store(frequency, Ordering::Relaxed);
+                    frequency
+                }
+                frequency => frequency,
+            },
+            _ => read_cpu_frequency(),
+        };
+        if frequency == 0 {
+            return None;
+        }
+        Some(Self {
+            source,
+            timestamp: None,
+            frequency,
+            end_value: u64::MAX,
+        })
+    }
+
+    /// Returns the counter used by this clock.
+    #[must_use]
+    pub const fn source(&self) -> ClockSource {
+        self.source
+    }
+
+    /// Returns the frequency of the counter, in Hz.
+    #[must_use]
+    pub const fn frequency(&self) -> u64 {
+        self.frequency
+    }
+
+    /// Returns the current value of the counter.
+    #[must_use]
+    pub fn ticks(&self) -> u64 {
+        match self.timestamp {
+            // SAFETY: the protocol stays valid until the firmware uninstalls
+            // it, which doesn't happen while boot services are active.
+            Some(timestamp) => unsafe { timestamp.as_ref() }.get_timestamp(),
+            None => read_cpu_counter(),
+        }
+    }
+
+    /// Returns the number of ticks from `start` to `end`, taking a single
+    /// rollover of the counter into account.
+    #[must_use]
+    pub const fn ticks_between(&self, start: u64, end: u64) -> u64 {
+        if end >= start {
+            end - start
+        } else {
+            self.end_value - start + end + 1
+        }
+    }
+
+    /// Converts a number of ticks to a duration.
+    #[must_use]
+    pub const fn to_duration(&self, ticks: u64) -> Duration {
+        let nanos = ticks as u128 * 1_000_000_000 / self.frequency as u128;
+        Duration::new(
+            (nanos / 1_000_000_000) as u64,
+            (nanos % 1_000_000_000) as u32,
+        )
+    }
+}
+
+/// Returns the CPU counter available on this architecture.
+const fn cpu_source() -> Option<ClockSource> {
+    cfg_if! {
+        if #[cfg(any(target_arch = "x86", target_arch = "x86_64"))] {
+            Some(ClockSource::Tsc)
+        } else if #[cfg(target_arch = "aarch64")] {
+            Some(ClockSource::Cntvct)
+        } else {
+            None
+        }
+    }
+}
+
+/// Reads the CPU counter.
+#[inline(always)]
+fn read_cpu_counter() -> u64 {
+    cfg_if! {
+        if #[cfg(any(target_arch = "x86", target_arch = "x86_64"))] {
+            let low: u32;
+            let high: u32;
+            unsafe {
+                core::arch::asm!(
+                    "rdtsc",
+                    out("eax") low,
+                    out("edx") high,
+                    options(nomem, nostack, preserves_flags)
+                );
+            }
+            (u64::from(high) << 32) | u64::from(low)
+        } else if #[cfg(target_arch = "aarch64")] {
+            let count: u64;
+            unsafe {
+                core::arch::asm!(
+                    "isb",
+                    "mrs {}, cntvct_el0",
+                    out(reg) count,
+                    options(nomem, nostack, preserves_flags)
+                );
+            }
+            count
+        } else {
+            0
+        }
+    }
+}
+
+/// Reads the frequency of the CPU counter, if the architecture reports it.
+#[allow(clippy::missing_const_for_fn)]
+fn read_cpu_frequency() -> u64 {
+    cfg_if! {
+        if #[cfg(target_arch = "aarch64")] {
+            let frequency: u64;
+            unsafe {
+                core::arch::asm!(
+                    "mrs {}, cntfrq_el0",
+                    out(reg) frequency,
+                    options(nomem, nostack, preserves_flags)
+                );
+            }
+            frequency
+        } else {
+            0
+        }
+    }
+}
+
+/// Measures the time elapsed since it was started.
+///
+/// # Example
+///
+/// ```no_run
+/// use uefi::bench::Stopwatch;
+///
+/// # fn example() -> uefi::Result {
+/// let stopwatch = Stopwatch::start()?;
+/// // ...
+/// log::info!("took {:?}", stopwatch.elapsed());
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Clone, Copy, Debug)]
+pub struct Stopwatch {
+    clock: Clock,
+    start: u64,
+}
+
+impl Stopwatch {
+    /// Starts a stopwatch with the best clock available.
+    ///
+    /// # Errors
+    ///
+    /// See [`Clock::new`].
+    pub fn start() -> Result<Self> {
+        Clock::new().map(Self::with_clock)
+    }
+
+    /// Starts a stopwatch with `clock`.
+    #[must_use]
+    pub fn with_clock(clock: Clock) -> Self {
+        let start = clock.ticks();
+        Self { clock, start }
+    }
+
+    /// Returns the clock of the stopwatch.
+    #[must_use]
+    pub const fn clock(&self) -> &Clock {
+        &self.clock
+    }
+
+    /// Restarts the stopwatch.
+    pub fn restart(&mut self) {
+        self.start = self.clock.ticks();
+    }
+
+    /// Returns the number of ticks elapsed since the stopwatch was started.
+    #[must_use]
+    pub fn elapsed_ticks(&self) -> u64 {
+        self.clock.ticks_between(self.start, self.clock.ticks())
+    }
+
+    /// Returns the time elapsed since the stopwatch was started.
+    #[must_use]
+    pub fn elapsed(&self) -> Duration {
+        self.clock.to_duration(self.elapsed_ticks())
+    }
+
+    /// Returns the time elapsed since the stopwatch was started, and
+    /// restarts it.
+    pub fn lap(&mut self) -> Duration {
+        let now = self.clock.ticks();
+        let elapsed = self.clock.ticks_between(self.start, now);
+        self.start = now;
+        self.clock.to_duration(elapsed)
+    }
+}
+
+/// Minimum, average and maximum duration of several runs.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct Stats {
+    runs: u32,
+    min: Duration,
+    max: Duration,
+    total: Duration,
+}
+
+impl Stats {
+    /// Creates empty statistics.
+    #[must_use]
+    pub const fn new() -> Self {
+        Self {
+            runs: 0,
+            min: Duration::ZERO,
+            max: Duration::ZERO,
+            total: Duration::ZERO,
+        }
+    }
+
+    /// Adds the duration of a run.
+    pub fn add(&mut self, duration: Duration) {
+        if self.runs == 0 || duration < self.min {
+            self.min = duration;
+        }
+        if duration > self.max {
+            self.max = duration;
+        }
+        self.total = self.total.saturating_add(duration);
+        self.runs += 1;
+    }
+
+    /// Returns the number of runs.
+    #[must_use]
+    pub const fn runs(&self) -> u32 {
+        self.runs
+    }
+
+    /// Returns the shortest duration, or zero if there were no runs.
+    #[must_use]
+    pub const fn min(&self) -> Duration {
+        self.min
+    }
+
+    /// Returns the longest duration, or zero if there were no runs.
+    #[must_use]
+    pub const fn max(&self) -> Duration {
+        self.max
+    }
+
+    /// Returns the sum of all durations.
+    #[must_use]
+    pub const fn total(&self) -> Duration {
+        self.total
+    }
+
+    /// Returns the average duration, or zero if there were no runs.
+    #[must_use]
+    pub fn avg(&self) -> Duration {
+        self.total.checked_div(self.runs).unwrap_or_default()
+    }
+}
+
+impl Display for Stats {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        write!(
+            f,
+            "min {:?}, avg {:?}, max {:?} over {} runs",
+            self.min,
+            self.avg(),
+            self.max,
+            self.runs
+        )
+    }
+}
+
+/// Runs `f` `runs` times and returns the statistics of its durations,
+/// measured with `clock`.
+pub fn measure(clock: &Clock, runs: u32, mut f: impl FnMut()) -> Stats {
+    let mut stats = Stats::new();
+    let mut stopwatch = Stopwatch::with_clock(*clock);
+    for _ in 0..runs {
+        stopwatch.restart();
+        f();
+        stats.add(stopwatch.elapsed());
+    }
+    stats
+}
+
+#[doc(hidden)]
+pub fn log_elapsed(label: &str, elapsed: Duration) {
+    log::info!("{label}: {elapsed:?}");
+}
+
+/// Evaluates an expression, logs how long it took with the given label, and
+/// returns its value.
+///
+/// If no clock is available, the expression is evaluated without being
+/// timed. See the [`bench`] module for details.
+///
+/// # Example
+///
+/// ```no_run
+/// let total = uefi::timed!("sum", (0..1000u32).sum::<u32>());
+/// let () = uefi::timed!("loop", {
+///     for _ in 0..1000 {
+///         core::hint::spin_loop();
+///     }
+/// });
+/// ```
+///
+/// [`bench`]: crate::bench
+#[macro_export]
+macro_rules! timed {
+    ($label:expr, $body:expr $(,)?) => {{
+        let stopwatch = $crate::bench::Stopwatch::start().ok();
+        let value = $body;
+        if let Some(stopwatch) = stopwatch {
+            $crate::bench::log_elapsed($label, stopwatch.elapsed());
+        }
+        value
+    }};
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloc::string::ToString;
+
+    fn clock(end_value: u64) -> Clock {
+        Clock {
+            source: ClockSource::Tsc,
+            timestamp: None,
+            frequency: 1_000_000,
+            end_value,
+        }
+    }
+
+    #[test]
+    fn test_clock() {
+        let clock = clock(0xff_ffff);
+        assert_eq!(clock.ticks_between(10, 25), 15);
+        assert_eq!(clock.ticks_between(0xff_fff0, 0x10), 0x20);
+        assert_eq!(clock.to_duration(1), Duration::from_micros(1));
+        assert_eq!(clock.to_duration(2_500_000), Duration::from_millis(2500));
+
+        let clock = Clock {
+            frequency: 3,
+            ..clock
+        };
+        assert_eq!(clock.to_duration(1), Duration::from_nanos(333_333_333));
+    }
+
+    #[test]
+    fn test_stats() {
+        let mut stats = Stats::new();
+        assert_eq!(stats.avg(), Duration::ZERO);
+
+        for ms in [30, 10, 20] {
+            stats.add(Duration::from_millis(ms));
+        }
+        assert_eq!(stats.runs(), 3);
+        assert_eq!(stats.min(), Duration::from_millis(10));
+        assert_eq!(stats.max(), Duration::from_millis(30));
+        assert_eq!(stats.total(), Duration::from_millis(60));
+        assert_eq!(stats.avg(), Duration::from_millis(20));
+        assert_eq!(
+            stats.to_string(),
+            "min 10ms, avg 20ms, max 30ms over 3 runs"
+        );
+    }
+}
@@ -26,7 +26,10 @@ pub use uefi_raw::table::boot::{
 };
 
 use crate::data_types::PhysicalAddress;
-use crate::mem::memory_map::{MemoryMapBackingMemory, MemoryMapKey, MemoryMapMeta, MemoryMapOwned};
+use crate::mem::memory_map::{
+    MemoryMapBackingMemory, MemoryMapBuffer, MemoryMapKey, MemoryMapMeta, MemoryMapOwned,
+    MemoryMapRefMut,
+};
 use crate::polyfill::maybe_uninit_slice_assume_init_ref;
 #[cfg(doc)]
 use crate::proto::device_path::LoadedImageDevicePath;
@@ -47,7 +50,12 @@ use core::time::Duration;
 use core::{mem, slice};
 use uefi_raw::table::boot::{AllocateType as RawAllocateType, InterfaceType, TimerDelay};
 #[cfg(feature = "alloc")]
-use {alloc::vec::Vec, uefi::ResultExt};
+use {
+    crate::{CStr16, CString16},
+    alloc::boxed::Box,
+    alloc::vec::Vec,
+    uefi::ResultExt,
+};
 
 /// Global image handle. This is only set by [`set_image_handle`], and it is
 /// only read by [`image_handle`].
@@ -346,6 +354,44 @@ pub fn memory_map(mt: MemoryType) -> Result<MemoryMapOwned> {
     Ok(MemoryMapOwned::from_initialized_mem(buffer, meta))
 }
 
+/// Stores the current UEFI memory map in a caller-owned buffer and returns a
+/// [`MemoryMapRefMut`] to it.
+///
+/// Unlike [`memory_map`], this only allocates when the buffer is used for the
+/// first time or the memory map has grown beyond its size. Loaders that
+/// query the memory map several times before [`exit_boot_services`] can
+/// reuse the buffer for that.
+///
+/// # Errors
+///
+/// * [`Status::INVALID_PARAMETER`]: Invalid [`MemoryType`] of the buffer.
+/// * [`Status::OUT_OF_RESOURCES`]: allocation failed.
+pub fn memory_map_into(buffer: &mut MemoryMapBuffer) -> Result<MemoryMapRefMut<'_>> {
+    let buf = match &mut buffer.buf {
+        Some(buf) => buf,
+        buf @ None => buf.insert(MemoryMapBackingMemory::new(buffer.memory_type)?),
+    };
+    let meta = get_memory_map_reusing(buf)?;
+    Ok(MemoryMapRefMut {
+        buf: buf.as_mut_slice(),
+        meta,
+        len: meta.entry_count(),
+    })
+}
+
+/// Retrieves the memory map into `buf`, reallocating it (with the same
+/// memory type) if it is too small.
+pub(crate) fn get_memory_map_reusing(buf: &mut MemoryMapBackingMemory) -> Result<MemoryMapMeta> {
+    loop {
+        match get_memory_map(buf.as_mut_slice()) {
+            Err(err) if err.status() == Status::BUFFER_TOO_SMALL => {
+                *buf = MemoryMapBackingMemory::new(buf.memory_type())?;
+            }
+            result => return result,
+        }
+    }
+}
+
 /// Calls the underlying `GetMemoryMap` function of UEFI. On success,
 /// the buffer is mutated and contains the map. The map might be shorter
 /// than the buffer, which is reflected by the return value.
@@ -966,11 +1012,8 @@ pub fn locate_handle_buffer(search_ty: SearchType) -> Result<HandleBuffer> {
 /// * [`Status::NOT_FOUND`]: no matching handles.
 #[cfg(feature = "alloc")]
 pub fn find_handles<P: ProtocolPointer + ?Sized>() -> Result<Vec<Handle>> {
-    // Search by protocol.
-    let search_type = SearchType::from_proto::<P>();
-
     // Determine how much we need to allocate.
-    let num_handles = match locate_handle(search_type, &mut []) {
+    let num_handles = match find_handles_in::<P>(&mut []) {
         Err(err) => {
             if err.status() == Status::BUFFER_TOO_SMALL {
                 err.data().expect("error data is missing")
@@ -987,7 +1030,7 @@ pub fn find_handles<P: ProtocolPointer + ?Sized>() -> Result<Vec<Handle>> {
     let mut handles = Vec::with_capacity(num_handles);
 
     // Perform the search.
-    let num_handles = locate_handle(search_type, handles.spare_capacity_mut())
+    let num_handles = find_handles_in::<P>(handles.spare_capacity_mut())
         .discard_errdata()?
         .len();
 
@@ -1000,6 +1043,44 @@ pub fn find_handles<P: ProtocolPointer + ?Sized>() -> Result<Vec<Handle>> {
     Ok(handles)
 }
 
+/// Returns the handles implementing a certain protocol in a caller-provided
+/// buffer, without allocating.
+///
+/// This works without the `alloc` feature, and the buffer can be reused,
+/// e.g. when polling for a device to appear.
+///
+/// # Example
+///
+/// ```no_run
+/// use core::mem::MaybeUninit;
+/// use core::time::Duration;
+/// use uefi::proto::media::block::BlockIO;
+/// use uefi::{boot, Handle, Status};
+///
+/// # fn example() -> uefi::Result {
+/// let mut buffer = [MaybeUninit::<Handle>::uninit(); 32];
+/// let handles = loop {
+///     match boot::find_handles_in::<BlockIO>(&mut buffer) {
+///         Ok(handles) => break handles,
+///         Err(err) if err.status() == Status::NOT_FOUND => boot::stall(Duration::from_millis(100)),
+///         Err(err) => return Err(err.to_err_without_payload()),
+///     }
+/// };
+/// # Ok(())
+/// # }
+/// ```
+///
+/// # Errors
+///
+/// * [`Status::NOT_FOUND`]: no matching handles.
+/// * [`Status::BUFFER_TOO_SMALL`]: the buffer is not large enough. The required
+///   size (in number of handles, not bytes) will be returned in the error data.
+pub fn find_handles_in<P: ProtocolPointer + ?Sized>(
+    buffer: &mut [MaybeUninit<Handle>],
+) -> Result<&[Handle], Option<usize>> {
+    locate_handle(SearchType::from_proto::<P>(), buffer)
+}
+
 /// Find an arbitrary handle that supports a particular [`Protocol`]. Returns
 /// [`NOT_FOUND`] if no handles support the protocol.
 ///
@@ -1186,7 +1267,7 @@ pub fn test_protocol<P: ProtocolPointer + ?Sized>(params: OpenProtocolParams) ->
 /// * [`Status::DEVICE_ERROR`]: failed to load image due to a read error.
 /// * [`Status::ACCESS_DENIED`]: failed to load image due to a security policy.
 /// * [`Status::SECURITY_VIOLATION`]: a security policy specifies that the image
-///   should not be started.
+///   should not be started. The image is unloaded again.
 pub fn load_image(parent_image_handle: Handle, source: LoadImageSource) -> Result<Handle> {
     let bt = boot_services_raw_panicking();
     let bt = unsafe { bt.as_ref() };
@@ -1194,7 +1275,7 @@ pub fn load_image(parent_image_handle: Handle, source: LoadImageSource) -> Resul
     let (boot_policy, device_path, source_buffer, source_size) = source.to_ffi_params();
 
     let mut image_handle = ptr::null_mut();
-    unsafe {
+    let status = unsafe {
         (bt.load_image)(
             boot_policy.into(),
             parent_image_handle.as_ptr(),
@@ -1203,11 +1284,18 @@ pub fn load_image(parent_image_handle: Handle, source: LoadImageSource) -> Resul
             source_size,
             &mut image_handle,
         )
-        .to_result_with_val(
-            // OK to unwrap: image handle is non-null for Status::SUCCESS.
-            || Handle::from_ptr(image_handle).unwrap(),
-        )
+    };
+    if status == Status::SECURITY_VIOLATION {
+        // The image was loaded, but the caller can't start it, so unload it
+        // instead of leaking the handle.
+        if let Some(handle) = unsafe { Handle::from_ptr(image_handle) } {
+            let _ = unload_image(handle);
+        }
     }
+    status.to_result_with_val(
+        // OK to unwrap: image handle is non-null for Status::SUCCESS.
+        || unsafe { Handle::from_ptr(image_handle) }.unwrap(),
+    )
 }
 
 /// Unloads a UEFI image.
@@ -1225,22 +1313,99 @@ pub fn unload_image(image_handle: Handle) -> Result {
 
 /// Transfers control to a loaded image's entry point.
 ///
+/// Exit data returned by the image is discarded, use
+/// [`start_image_with_exit_data`] to get it.
+///
 /// # Errors
 ///
 /// * [`Status::INVALID_PARAMETER`]: `image_handle` is not valid, or the image
 ///   has already been initialized with `start_image`.
 /// * [`Status::SECURITY_VIOLATION`]: a security policy specifies that the image
 ///   should not be started.
+/// * Any other status returned by the image's entry point or passed to [`exit`].
 pub fn start_image(image_handle: Handle) -> Result {
+    let (status, exit_data, _) = unsafe { start_image_raw(image_handle) };
+    if let Some(exit_data) = exit_data {
+        // Safety: the exit data was allocated from pool memory by the image.
+        let _ = unsafe { free_pool(exit_data.cast()) };
+    }
+    status.to_result()
+}
+
+/// Transfers control to a loaded image's entry point, and returns the exit
+/// data of the image if it failed.
+///
+/// # Errors
+///
+/// See [`start_image`]. The error data is the exit data passed by the image
+/// to [`exit`], if any.
+#[cfg(feature = "alloc")]
+pub fn start_image_with_exit_data(image_handle: Handle) -> Result<(), Option<ExitData>> {
+    let (status, exit_data, exit_data_size) = unsafe { start_image_raw(image_handle) };
+    let exit_data = exit_data.map(|ptr| {
+        // Safety: the image returned `exit_data_size` bytes of exit data,
+        // allocated from pool memory.
+        let data = unsafe { slice::from_raw_parts(ptr.as_ptr().cast::<u8>(), exit_data_size) };
+        let data = ExitData::new(data);
+        let _ = unsafe { free_pool(ptr.cast()) };
+        data
+    });
+    status.to_result_with_err(|_| exit_data)
+}
+
+/// Calls `StartImage`, returning the status and the exit data.
+unsafe fn start_image_raw(image_handle: Handle) -> (Status, Option<NonNull<u16>>, usize) {
     let bt = boot_services_raw_panicking();
     let bt = unsafe { bt.as_ref() };
 
-    // TODO: implement returning exit data to the caller.
     let mut exit_data_size: usize = 0;
     let mut exit_data: *mut u16 = ptr::null_mut();
 
-    unsafe {
-        (bt.start_image)(image_handle.as_ptr(), &mut exit_data_size, &mut exit_data).to_result()
+    let status =
+        unsafe { (bt.start_image)(image_handle.as_ptr(), &mut exit_data_size, &mut exit_data) };
+    (status, NonNull::new(exit_data), exit_data_size)
+}
+
+/// Exit data returned by an image started with
+/// [`start_image_with_exit_data`].
+///
+/// The exit data starts with a null-terminated string that describes the
+/// exit status, optionally followed by binary data.
+#[cfg(feature = "alloc")]
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ExitData {
+    description: Option<CString16>,
+    data: Box<[u8]>,
+}
+
+#[cfg(feature = "alloc")]
+impl ExitData {
+    fn new(data: &[u8]) -> Self {
+        let chars: Vec<u16> = data
+            .chunks_exact(2)
+            .map(|c| u16::from_le_bytes([c[0], c[1]]))
+            .collect();
+        let description = chars
+            .iter()
+            .position(|&c| c == 0)
+            .and_then(|len| CString16::try_from(chars[..=len].to_vec()).ok());
+        Self {
+            description,
+            data: data.into(),
+        }
+    }
+
+    /// Returns the string at the start of the exit data, or `None` if the
+    /// exit data doesn't start with a valid null-terminated UCS-2 string.
+    #[must_use]
+    pub fn description(&self) -> Option<&CStr16> {
+        self.description.as_deref()
+    }
+
+    /// Returns the raw bytes of the exit data, including the description.
+    #[must_use]
+    pub fn as_bytes(&self) -> &[u8] {
+        &self.data
     }
 }
 
@@ -1471,6 +1636,26 @@ pub fn stall(duration: Duration) {
     }
 }
 
+/// Returns the next value of the platform's monotonic counter.
+///
+/// The lower 32 bits are incremented on every call, and the upper 32 bits
+/// hold the value of the high count, which is incremented on every boot and
+/// on overflow of the lower bits. See [`MonotonicId`] for a helper that also
+/// works after exiting boot services.
+///
+/// # Errors
+///
+/// * [`Status::DEVICE_ERROR`]: the device is not functioning properly.
+///
+/// [`MonotonicId`]: crate::runtime::MonotonicId
+pub fn get_next_monotonic_count() -> Result<u64> {
+    let bt = boot_services_raw_panicking();
+    let bt = unsafe { bt.as_ref() };
+
+    let mut count = 0;
+    unsafe { (bt.get_next_monotonic_count)(&mut count) }.to_result_with_val(|| count)
+}
+
 /// Retrieves a [`SimpleFileSystem`] protocol associated with the device the given
 /// image was loaded from.
 ///
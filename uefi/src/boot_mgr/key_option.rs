@@ -0,0 +1,285 @@
+// SPDX-License-Identifier: MIT OR Apache-2.0
+
+//! [`KeyOption`], the contents of the `Key####` variables.
+
+use super::LoadOption;
+use crate::Char16;
+use crate::media::crc32::crc32;
+use crate::proto::console::text::{Key, ScanCode};
+use alloc::vec::Vec;
+use bitflags::bitflags;
+use core::fmt::{self, Display, Formatter};
+
+bitflags! {
+    /// Modifier keys that must be held for a [`KeyOption`].
+    ///
+    /// A side of a modifier key can't be selected: [`SHIFT`] matches both the
+    /// left and the right shift key.
+    ///
+    /// [`SHIFT`]: Self::SHIFT
+    #[derive(Clone, Copy, Debug, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+    #[repr(transparent)]
+    pub struct KeyModifiers: u32 {
+        /// Shift key.
+        const SHIFT = 0x0000_0100;
+        /// Control key.
+        const CONTROL = 0x0000_0200;
+        /// Alt key.
+        const ALT = 0x0000_0400;
+        /// Logo key, e.g. the Windows key.
+        const LOGO = 0x0000_0800;
+        /// Menu key.
+        const MENU = 0x0000_1000;
+        /// SysReq key.
+        const SYS_REQ = 0x0000_2000;
+    }
+}
+
+/// Errors of parsing or building a [`KeyOption`].
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum KeyOptionError {
+    /// The size of the data doesn't match the number of keys.
+    InvalidLength,
+    /// More than [`KeyOption::MAX_KEYS`] keys.
+    TooManyKeys,
+    /// A key is neither a special key nor a valid UCS-2 character.
+    InvalidKey,
+}
+
+impl Display for KeyOptionError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        let s = match self {
+            Self::InvalidLength => "invalid key option length",
+            Self::TooManyKeys => "too many keys in key option",
+            Self::InvalidKey => "invalid key in key option",
+        };
+        f.write_str(s)
+    }
+}
+
+impl core::error::Error for KeyOptionError {}
+
+/// A key option (`EFI_KEY_OPTION`), which binds a hotkey to an entry of the
+/// boot manager: the contents of a `Key####` variable.
+///
+/// When the keys are pressed while the boot manager runs, with the
+/// [`modifiers`] held, it boots the `Boot####` entry with the number
+/// [`boot_option`]. The binding is ignored if the CRC32 of that entry
+/// doesn't match [`boot_option_crc`], so that it doesn't apply to an unrelated
+/// entry that reuses the number.
+///
+/// [`modifiers`]: Self::modifiers
+/// [`boot_option`]: Self::boot_option
+/// [`boot_option_crc`]: Self::boot_option_crc
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct KeyOption {
+    /// Revision of the key data. Zero for this version of the format.
+    revision: u8,
+    /// Modifier keys that must be held.
+    pub modifiers: KeyModifiers,
+    /// CRC32 of the contents of the `Boot####` variable.
+    pub boot_option_crc: u32,
+    /// Number of the `Boot####` entry to boot.
+    pub boot_option: u16,
+    /// Keys that must be pressed, at most [`Self::MAX_KEYS`].
+    keys: Vec<Key>,
+}
+
+/// Size of the key data, the CRC and the number of the boot option.
+const HEADER_SIZE: usize = 10;
+
+/// Size of an `EFI_INPUT_KEY`.
+const KEY_SIZE: usize = 4;
+
+impl KeyOption {
+    /// Maximum number of keys of a key option, besides the modifiers.
+    pub const MAX_KEYS: usize = 3;
+
+    /// Creates a key option that boots entry `boot_option`, whose contents are
+    /// `option`, when the `keys` are pressed with the `modifiers` held.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`KeyOptionError::TooManyKeys`] if there are more than
+    /// [`Self::MAX_KEYS`] keys.
+    pub fn new(
+        boot_option: u16,
+        option: &LoadOption,
+        modifiers: KeyModifiers,
+        keys: &[Key],
+    ) -> Result<Self, KeyOptionError> {
+        if keys.len() > Self::MAX_KEYS {
+            return Err(KeyOptionError::TooManyKeys);
+        }
+        Ok(Self {
+            revision: 0,
+            modifiers,
+            boot_option_crc: crc32(&option.to_bytes()),
+            boot_option,
+            keys: keys.to_vec(),
+        })
+    }
+
+    /// Parses a key option, e.g. the contents of a `Key####` variable.
+    ///
+    /// # Errors
+    ///
+    /// Returns a [`KeyOptionError`] if the data is not a valid key option.
+    pub fn parse(bytes: &[u8]) -> Result<Self, KeyOptionError> {
+        let header = bytes
+            .get(..HEADER_SIZE)
+            .ok_or(KeyOptionError::InvalidLength)?;
+        let key_data = u32::from_le_bytes(header[..4].try_into().unwrap());
+        let key_count = (key_data >> 30) as usize;
+        if bytes.len() != HEADER_SIZE + key_count * KEY_SIZE {
+            return Err(KeyOptionError::InvalidLength);
+        }
+
+        let keys = bytes[HEADER_SIZE..]
+            .chunks_exact(KEY_SIZE)
+            .map(|key| {
+                let scan_code = u16::from_le_bytes([key[0], key[1]]);
+                let unicode_char = u16::from_le_bytes([key[2], key[3]]);
+                if scan_code == ScanCode::NULL.0 {
+                    Char16::try_from(unicode_char)
+                        .map(Key::Printable)
+                        .map_err(|_| KeyOptionError::InvalidKey)
+                } else {
+                    Ok(Key::Special(ScanCode(scan_code)))
+                }
+            })
+            .collect::<Result<_, _>>()?;
+
+        Ok(Self {
+            revision: key_data as u8,
+            modifiers: KeyModifiers::from_bits_truncate(key_data),
+            boot_option_crc: u32::from_le_bytes(header[4..8].try_into().unwrap()),
+            boot_option: u16::from_le_bytes([header[8], header[9]]),
+            keys,
+        })
+    }
+
+    /// Serializes the key option, e.g. to write it to a `Key####` variable.
+    #[must_use]
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let key_data =
+            u32::from(self.revision) | self.modifiers.bits() | ((self.keys.len() as u32) << 30);
+
+        let mut bytes = Vec::with_capacity(HEADER_SIZE + self.keys.len() * KEY_SIZE);
+        bytes.extend_from_slice(&key_data.to_le_bytes());
+        bytes.extend_from_slice(&self.boot_option_crc.to_le_bytes());
+        bytes.extend_from_slice(&self.boot_option.to_le_bytes());
+        for key in &self.keys {
+            let (scan_code, unicode_char) = match key {
+                Key::Printable(c) => (ScanCode::NULL.0, u16::from(*c)),
+                Key::Special(scan_code) => (scan_code.0, 0),
+            };
+            bytes.extend_from_slice(&scan_code.to_le_bytes());
+            bytes.extend_from_slice(&unicode_char.to_le_bytes());
+        }
+        bytes
+    }
+
+    /// Returns the keys that must be pressed.
+    #[must_use]
+    pub fn keys(&self) -> &[Key] {
+        &self.keys
+    }
+
+    /// Returns whether the key option applies to a `Boot####` entry with the
+    /// contents `option`, i.e. whether the CRC32 matches.
+    #[must_use]
+    pub fn matches(&self, option: &LoadOption) -> bool {
+        self.boot_option_crc == crc32(&option.to_bytes())
+    }
+}
+
+impl TryFrom<&[u8]> for KeyOption {
+    type Error = KeyOptionError;
+
+    fn try_from(bytes: &[u8]) -> Result<Self, Self::Error> {
+        Self::parse(bytes)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cstr16;
+    use crate::proto::device_path::build::{self, DevicePathBuilder};
+
+    fn load_option(buf: &mut Vec<u8>) -> LoadOption {
+        let path = DevicePathBuilder::with_vec(buf)
+            .push(&build::media::FilePath {
+                path_name: cstr16!("\\recovery.efi"),
+            })
+            .unwrap()
+            .finalize()
+            .unwrap();
+        LoadOption::new(cstr16!("Recovery"), path)
+    }
+
+    #[test]
+    fn test_key_option_round_trip() {
+        let mut buf = Vec::new();
+        let option = load_option(&mut buf);
+        let key_option = KeyOption::new(
+            0x12,
+            &option,
+            KeyModifiers::CONTROL | KeyModifiers::ALT,
+            &[
+                Key::Special(ScanCode::FUNCTION_9),
+                Key::Printable(Char16::try_from('r').unwrap()),
+            ],
+        )
+        .unwrap();
+        assert!(key_option.matches(&option));
+
+        let bytes = key_option.to_bytes();
+        let crc = crc32(&option.to_bytes()).to_le_bytes();
+        #[rustfmt::skip]
+        assert_eq!(bytes, [
+            // Key data: two keys, control and alt
+            0x00, 0x06, 0x00, 0x80,
+            // CRC
+            crc[0], crc[1], crc[2], crc[3],
+            // Boot option
+            0x12, 0x00,
+            // Keys
+            0x13, 0x00, 0x00, 0x00,
+            0x00, 0x00, b'r', 0x00,
+        ]);
+        assert_eq!(KeyOption::parse(&bytes).unwrap(), key_option);
+
+        let mut other = option.clone();
+        other.optional_data.push(1);
+        assert!(!key_option.matches(&other));
+    }
+
+    #[test]
+    fn test_key_option_invalid() {
+        let mut buf = Vec::new();
+        let option = load_option(&mut buf);
+        let keys = [Key::Special(ScanCode::FUNCTION_9); 4];
+        assert_eq!(
+            KeyOption::new(0, &option, KeyModifiers::empty(), &keys),
+            Err(KeyOptionError::TooManyKeys)
+        );
+
+        let bytes = KeyOption::new(0, &option, KeyModifiers::SHIFT, &keys[..1])
+            .unwrap()
+            .to_bytes();
+        assert_eq!(
+            KeyOption::parse(&bytes[..bytes.len() - 1]),
+            Err(KeyOptionError::InvalidLength)
+        );
+        assert_eq!(
+            KeyOption::parse(&[bytes.as_slice(), &[0; 4]].concat()),
+            Err(KeyOptionError::InvalidLength)
+        );
+
+        let mut invalid = bytes;
+        invalid[10..14].copy_from_slice(&[0, 0, 0x00, 0xd8]);
+        assert_eq!(KeyOption::parse(&invalid), Err(KeyOptionError::InvalidKey));
+    }
+}
@@ -0,0 +1,348 @@
+// SPDX-License-Identifier: MIT OR Apache-2.0
+
+//! [`LoadOption`], the contents of the `Boot####` variables.
+
+use crate::proto::device_path::DevicePath;
+use crate::{CStr16, CString16};
+use alloc::vec::Vec;
+use bitflags::bitflags;
+use core::fmt::{self, Display, Formatter};
+
+bitflags! {
+    /// Attributes of a [`LoadOption`].
+    #[derive(Clone, Copy, Debug, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+    #[repr(transparent)]
+    pub struct LoadOptionAttributes: u32 {
+        /// The boot manager tries this option. Inactive options are skipped.
+        const ACTIVE = 0x0000_0001;
+
+        /// All UEFI drivers are reconnected after a driver option is loaded.
+        const FORCE_RECONNECT = 0x0000_0002;
+
+        /// The option is not shown in the menu of the boot manager.
+        const HIDDEN = 0x0000_0008;
+
+        /// The option is an application, e.g. a setup utility, rather than a
+        /// boot option. It is only started from the boot menu or a hotkey,
+        /// never automatically.
+        const CATEGORY_APP = 0x0000_0100;
+    }
+}
+
+/// Errors of parsing a [`LoadOption`].
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum LoadOptionError {
+    /// The data ends before the end of the header, the description or the
+    /// file path list.
+    TooShort,
+    /// The description is not a valid null-terminated UCS-2 string.
+    InvalidDescription,
+    /// The file path list is empty or doesn't consist of valid device paths.
+    InvalidFilePathList,
+}
+
+impl Display for LoadOptionError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        let s = match self {
+            Self::TooShort => "load option is too short",
+            Self::InvalidDescription => "invalid load option description",
+            Self::InvalidFilePathList => "invalid load option file path list",
+        };
+        f.write_str(s)
+    }
+}
+
+impl core::error::Error for LoadOptionError {}
+
+/// A load option (`EFI_LOAD_OPTION`), which describes an entry of the boot
+/// manager: the contents of a `Boot####`, `Driver####` or `SysPrep####`
+/// variable.
+///
+/// A load option consists of attributes, a description that is shown in the
+/// boot menu, a list of device paths of which the first one is the image to
+/// load, and optional data that is passed to the image as its load options.
+///
+/// # Example
+///
+/// ```
+/// use uefi::boot_mgr::LoadOption;
+/// use uefi::cstr16;
+/// use uefi::proto::device_path::build::{self, DevicePathBuilder};
+///
+/// # fn example() -> Result<(), Box<dyn core::error::Error>> {
+/// let mut buf = Vec::new();
+/// let path = DevicePathBuilder::with_vec(&mut buf)
+///     .push(&build::media::FilePath {
+///         path_name: cstr16!("\\EFI\\BOOT\\BOOTX64.EFI"),
+///     })?
+///     .finalize()?;
+///
+/// let option = LoadOption::new(cstr16!("My OS"), path);
+/// let bytes = option.to_bytes();
+/// assert_eq!(LoadOption::parse(&bytes)?, option);
+/// # Ok(())
+/// # }
+/// # example().unwrap();
+/// ```
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct LoadOption {
+    /// Attributes of the option.
+    pub attributes: LoadOptionAttributes,
+    /// Description of the option, shown in the boot menu.
+    pub description: CString16,
+    /// Device paths, each ending with an end-entire node.
+    file_path_list: Vec<u8>,
+    /// Data that is passed to the image as its load options.
+    pub optional_data: Vec<u8>,
+}
+
+/// Size of the attributes and the length of the file path list.
+const HEADER_SIZE: usize = 6;
+
+impl LoadOption {
+    /// Creates an active load option for the image at `file_path`, without
+    /// optional data.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `file_path` is larger than 64 KiB.
+    #[must_use]
+    pub fn new(description: &CStr16, file_path: &DevicePath) -> Self {
+        let mut option = Self {
+            attributes: LoadOptionAttributes::ACTIVE,
+            description: description.into(),
+            file_path_list: Vec::new(),
+            optional_data: Vec::new(),
+        };
+        option.push_file_path(file_path);
+        option
+    }
+
+    /// Parses a load option, e.g. the contents of a `Boot####` variable.
+    ///
+    /// # Errors
+    ///
+    /// Returns a [`LoadOptionError`] if the data is not a valid load option.
+    pub fn parse(bytes: &[u8]) -> Result<Self, LoadOptionError> {
+        let header = bytes.get(..HEADER_SIZE).ok_or(LoadOptionError::TooShort)?;
+        let attributes = LoadOptionAttributes::from_bits_retain(u32::from_le_bytes(
+            header[..4].try_into().unwrap(),
+        ));
+        let file_path_list_len = usize::from(u16::from_le_bytes([header[4], header[5]]));
+
+        // The description is aligned to two bytes within the option, but
+        // the option itself may be unaligned, so copy the characters.
+        let mut description = Vec::new();
+        let mut rest = &bytes[HEADER_SIZE..];
+        loop {
+            let (c, tail) = rest.split_first_chunk().ok_or(LoadOptionError::TooShort)?;
+            rest = tail;
+            description.push(u16::from_le_bytes(*c));
+            if description.last() == Some(&0) {
+                break;
+            }
+        }
+        let description =
+            CString16::try_from(description).map_err(|_| LoadOptionError::InvalidDescription)?;
+
+        if rest.len() < file_path_list_len {
+            return Err(LoadOptionError::TooShort);
+        }
+        let (file_path_list, optional_data) = rest.split_at(file_path_list_len);
+        if file_path_list.is_empty() || !is_valid_file_path_list(file_path_list) {
+            return Err(LoadOptionError::InvalidFilePathList);
+        }
+
+        Ok(Self {
+            attributes,
+            description,
+            file_path_list: file_path_list.to_vec(),
+            optional_data: optional_data.to_vec(),
+        })
+    }
+
+    /// Serializes the load option, e.g. to write it to a `Boot####`
+    /// variable.
+    #[must_use]
+    pub fn to_bytes(&self) -> Vec<u8> {
+        // OK to unwrap: the length is checked by `push_file_path`.
+        let file_path_list_len = u16::try_from(self.file_path_list.len()).unwrap();
+
+        let mut bytes = Vec::with_capacity(
+            HEADER_SIZE
+                + self.description.num_bytes()
+                + self.file_path_list.len()
+                + self.optional_data.len(),
+        );
+        bytes.extend_from_slice(&self.attributes.bits().to_le_bytes());
+        bytes.extend_from_slice(&file_path_list_len.to_le_bytes());
+        for c in self.description.as_slice_with_nul() {
+            bytes.extend_from_slice(&u16::from(*c).to_le_bytes());
+        }
+        bytes.extend_from_slice(&self.file_path_list);
+        bytes.extend_from_slice(&self.optional_data);
+        bytes
+    }
+
+    /// Returns the device path of the image to load, the first of
+    /// [`Self::file_paths`].
+    #[must_use]
+    pub fn file_path(&self) -> &DevicePath {
+        // OK to unwrap: the list always contains at least one path.
+        self.file_paths().next().unwrap()
+    }
+
+    /// Returns an iterator over the device paths of the option. The first
+    /// one is the image to load, the meaning of the others is specific to
+    /// the image or the firmware.
+    pub fn file_paths(&self) -> impl Iterator<Item = &DevicePath> {
+        let mut rest = self.file_path_list.as_slice();
+        core::iter::from_fn(move || {
+            // The list has been validated, so this only fails at its end.
+            let path = <&DevicePath>::try_from(rest).ok()?;
+            rest = &rest[size_of_val(path)..];
+            Some(path)
+        })
+    }
+
+    /// Appends a device path to the file path list.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the file path list would be larger than 64 KiB.
+    pub fn push_file_path(&mut self, path: &DevicePath) {
+        assert!(
+            self.file_path_list.len() + path.as_bytes().len() <= usize::from(u16::MAX),
+            "file path list of load option is too large"
+        );
+        self.file_path_list.extend_from_slice(path.as_bytes());
+    }
+
+    /// Returns whether the option is [`ACTIVE`].
+    ///
+    /// [`ACTIVE`]: LoadOptionAttributes::ACTIVE
+    #[must_use]
+    pub const fn is_active(&self) -> bool {
+        self.attributes.contains(LoadOptionAttributes::ACTIVE)
+    }
+}
+
+impl TryFrom<&[u8]> for LoadOption {
+    type Error = LoadOptionError;
+
+    fn try_from(bytes: &[u8]) -> Result<Self, Self::Error> {
+        Self::parse(bytes)
+    }
+}
+
+/// Returns whether `list` consists of complete device paths.
+fn is_valid_file_path_list(mut list: &[u8]) -> bool {
+    while !list.is_empty() {
+        match <&DevicePath>::try_from(list) {
+            Ok(path) => list = &list[size_of_val(path)..],
+            Err(_) => return false,
+        }
+    }
+    true
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cstr16;
+    use crate::proto::device_path::build::{self, DevicePathBuilder};
+    use alloc::vec;
+
+    fn file_path<'a>(buf: &'a mut Vec<u8>, name: &CStr16) -> &'a DevicePath {
+        DevicePathBuilder::with_vec(buf)
+            .push(&build::media::FilePath { path_name: name })
+            .unwrap()
+            .finalize()
+            .unwrap()
+    }
+
+    #[test]
+    fn test_load_option_round_trip() {
+        let (mut buf1, mut buf2) = (Vec::new(), Vec::new());
+        let mut option = LoadOption::new(cstr16!("Test"), file_path(&mut buf1, cstr16!("\\a")));
+        option.push_file_path(file_path(&mut buf2, cstr16!("\\bc")));
+        option.attributes |= LoadOptionAttributes::HIDDEN;
+        option.optional_data = vec![1, 2, 3];
+
+        let bytes = option.to_bytes();
+        #[rustfmt::skip]
+        assert_eq!(bytes, [
+            // Attributes
+            0x09, 0x00, 0x00, 0x00,
+            // File path list length
+            0x1e, 0x00,
+            // Description
+            b'T', 0, b'e', 0, b's', 0, b't', 0, 0, 0,
+            // File path node, end-entire node
+            0x04, 0x04, 0x0a, 0x00, b'\\', 0, b'a', 0, 0, 0,
+            0x7f, 0xff, 0x04, 0x00,
+            // File path node, end-entire node
+            0x04, 0x04, 0x0c, 0x00, b'\\', 0, b'b', 0, b'c', 0, 0, 0,
+            0x7f, 0xff, 0x04, 0x00,
+            // Optional data
+            1, 2, 3,
+        ][..]);
+
+        let parsed = LoadOption::parse(&bytes).unwrap();
+        assert_eq!(parsed, option);
+        assert!(parsed.is_active());
+        assert_eq!(parsed.file_paths().count(), 2);
+        assert_eq!(parsed.file_path().as_bytes(), &bytes[16..30]);
+    }
+
+    #[test]
+    fn test_load_option_invalid() {
+        let mut buf = Vec::new();
+        let option = LoadOption::new(cstr16!("Test"), file_path(&mut buf, cstr16!("\\a")));
+        let bytes = option.to_bytes();
+
+        // Truncated anywhere.
+        for len in 0..bytes.len() {
+            assert!(LoadOption::parse(&bytes[..len]).is_err());
+        }
+
+        // Unterminated description.
+        assert_eq!(
+            LoadOption::parse(&bytes[..12]),
+            Err(LoadOptionError::TooShort)
+        );
+
+        // Description with an invalid character.
+        let mut invalid = bytes.clone();
+        invalid[6..8].copy_from_slice(&0xd800u16.to_le_bytes());
+        assert_eq!(
+            LoadOption::parse(&invalid),
+            Err(LoadOptionError::InvalidDescription)
+        );
+
+        // File path list length that ends within a node.
+        let mut invalid = bytes.clone();
+        invalid[4] -= 2;
+        assert_eq!(
+            LoadOption::parse(&invalid),
+            Err(LoadOptionError::InvalidFilePathList)
+        );
+
+        // File path list ending with a zero-length node.
+        let mut invalid = bytes.clone();
+        let end = invalid.len() - 4;
+        invalid[end..].copy_from_slice(&[0x54, 0x5d, 0, 0]);
+        assert_eq!(
+            LoadOption::parse(&invalid),
+            Err(LoadOptionError::InvalidFilePathList)
+        );
+
+        // Empty file path list.
+        let mut invalid = bytes;
+        invalid[4] = 0;
+        assert_eq!(
+            LoadOption::parse(&invalid),
+            Err(LoadOptionError::InvalidFilePathList)
+        );
+    }
+}
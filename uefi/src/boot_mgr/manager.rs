@@ -0,0 +1,666 @@
+// SPDX-License-Identifier: MIT OR Apache-2.0
+
+//! [`BootManager`], an interface to the boot manager variables.
+
+use super::{KeyModifiers, KeyOption, LoadOption};
+use crate::proto::console::text::Key;
+use crate::proto::device_path::DevicePath;
+use crate::proto::device_path::build::{self, DevicePathBuilder};
+use crate::runtime::{self, ResetType, VariableAttributes, VariableVendor};
+use crate::{CStr16, CString16, Handle, Result, Status, boot, cstr16};
+use alloc::boxed::Box;
+use alloc::format;
+use alloc::string::ToString;
+use alloc::vec::Vec;
+
+/// Attributes of the variables of the boot manager, as required by the UEFI
+/// specification.
+const ATTRIBUTES: VariableAttributes = VariableAttributes::NON_VOLATILE
+    .union(VariableAttributes::BOOTSERVICE_ACCESS)
+    .union(VariableAttributes::RUNTIME_ACCESS);
+
+/// An entry of the boot manager: a `Boot####`, `Driver####` or `SysPrep####`
+/// variable.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct BootEntry {
+    /// Number of the entry, the `####` of the variable name.
+    pub number: u16,
+    /// Contents of the variable.
+    pub option: LoadOption,
+}
+
+/// Type of the entries of the boot manager.
+///
+/// Each type has its own set of `<type>####` variables and its own order
+/// variable, which lists the entries that the boot manager processes.
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Hash)]
+pub enum LoadOptionType {
+    /// `Boot####` entries, ordered by `BootOrder`: operating systems and
+    /// applications that the boot manager boots.
+    Boot,
+    /// `Driver####` entries, ordered by `DriverOrder`: drivers that the boot
+    /// manager loads on every boot, before the boot entries are processed.
+    Driver,
+    /// `SysPrep####` entries, ordered by `SysPrepOrder`: applications that
+    /// the boot manager runs to prepare the system, after the drivers and
+    /// before the boot entries.
+    SysPrep,
+}
+
+impl LoadOptionType {
+    /// Returns the prefix of the variable names, e.g. `Boot`.
+    const fn prefix(self) -> &'static str {
+        match self {
+            Self::Boot => "Boot",
+            Self::Driver => "Driver",
+            Self::SysPrep => "SysPrep",
+        }
+    }
+
+    /// Returns the name of the order variable, e.g. `BootOrder`.
+    const fn order_name(self) -> &'static CStr16 {
+        match self {
+            Self::Boot => cstr16!("BootOrder"),
+            Self::Driver => cstr16!("DriverOrder"),
+            Self::SysPrep => cstr16!("SysPrepOrder"),
+        }
+    }
+}
+
+/// A hotkey of the boot manager: a `Key####` variable.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct KeyEntry {
+    /// Number of the hotkey, the `####` of the variable name.
+    pub number: u16,
+    /// Contents of the variable.
+    pub option: KeyOption,
+}
+
+/// Interface to the boot manager of the firmware, similar to `efibootmgr`.
+///
+/// The boot manager stores its configuration in variables of the [global
+/// variable vendor]:
+/// * `Boot####`: the entries, each a [`LoadOption`].
+/// * `BootOrder`: the numbers of the entries that are tried on a normal
+///   boot, in order.
+/// * `BootNext`: the number of an entry that is tried first on the next boot
+///   only.
+/// * `BootCurrent`: the number of the entry that was booted.
+/// * `Key####`: hotkeys that boot an entry, each a [`KeyOption`].
+///
+/// `Driver####` and `SysPrep####` entries, with their `DriverOrder` and
+/// `SysPrepOrder`, are managed with the `*_of` methods and a
+/// [`LoadOptionType`].
+///
+/// Changes take effect on the next boot, which can be triggered with
+/// [`BootManager::reset`].
+///
+/// # Example
+///
+/// ```no_run
+/// use uefi::boot_mgr::BootManager;
+/// use uefi::{Handle, cstr16};
+///
+/// # fn example(partition: Handle) -> uefi::Result {
+/// let manager = BootManager::new();
+/// for entry in manager.entries()? {
+///     uefi::println!("Boot{:04X}: {}", entry.number, entry.option.description);
+/// }
+///
+/// // Boot the installer on the partition once.
+/// let number = manager.create_entry(
+///     cstr16!("Installer"),
+///     partition,
+///     cstr16!("\\EFI\\INSTALL\\SETUP.EFI"),
+/// )?;
+/// manager.set_boot_next(Some(number))?;
+/// manager.reset();
+/// # }
+/// ```
+///
+/// [global variable vendor]: VariableVendor::GLOBAL_VARIABLE
+#[derive(Debug, Default)]
+pub struct BootManager {
+    _private: (),
+}
+
+impl BootManager {
+    /// Creates an interface to the boot manager.
+    #[must_use]
+    pub const fn new() -> Self {
+        Self { _private: () }
+    }
+
+    /// Returns all entries of the boot manager, sorted by their number.
+    ///
+    /// Variables that don't contain a valid [`LoadOption`], or that are
+    /// deleted while the entries are read, are skipped.
+    ///
+    /// # Errors
+    ///
+    /// Errors of [`runtime::variable_keys`] and
+    /// [`runtime::get_variable_boxed`].
+    pub fn entries(&self) -> Result<Vec<BootEntry>> {
+        self.entries_of(LoadOptionType::Boot)
+    }
+
+    /// Returns all entries of type `ty`, sorted by their number, like
+    /// [`entries`] does for boot entries.
+    ///
+    /// # Errors
+    ///
+    /// See [`entries`].
+    ///
+    /// [`entries`]: Self::entries
+    pub fn entries_of(&self, ty: LoadOptionType) -> Result<Vec<BootEntry>> {
+        read_entries(ty, &option_numbers(ty.prefix())?, |name| {
+            runtime::get_variable_boxed(name, &VariableVendor::GLOBAL_VARIABLE)
+                .map(|(data, _)| data)
+        })
+    }
+
+    /// Returns the load option of entry `number`.
+    ///
+    /// # Errors
+    ///
+    /// * [`Status::NOT_FOUND`]: the entry doesn't exist.
+    /// * [`Status::VOLUME_CORRUPTED`]: the variable doesn't contain a valid
+    ///   [`LoadOption`].
+    /// * Errors of [`runtime::get_variable_boxed`].
+    pub fn entry(&self, number: u16) -> Result<LoadOption> {
+        self.entry_of(LoadOptionType::Boot, number)
+    }
+
+    /// Returns the load option of entry `number` of type `ty`.
+    ///
+    /// # Errors
+    ///
+    /// See [`entry`].
+    ///
+    /// [`entry`]: Self::entry
+    pub fn entry_of(&self, ty: LoadOptionType, number: u16) -> Result<LoadOption> {
+        let (data, _) = runtime::get_variable_boxed(
+            &option_name(ty.prefix(), number),
+            &VariableVendor::GLOBAL_VARIABLE,
+        )?;
+        LoadOption::parse(&data).map_err(|_| Status::VOLUME_CORRUPTED.into())
+    }
+
+    /// Writes entry `number`, replacing the entry if it exists. `BootOrder`
+    /// is not changed.
+    ///
+    /// # Errors
+    ///
+    /// Errors of [`runtime::set_variable`].
+    pub fn set_entry(&self, number: u16, option: &LoadOption) -> Result {
+        self.set_entry_of(LoadOptionType::Boot, number, option)
+    }
+
+    /// Writes entry `number` of type `ty`, replacing the entry if it exists.
+    /// The order is not changed.
+    ///
+    /// # Errors
+    ///
+    /// Errors of [`runtime::set_variable`].
+    pub fn set_entry_of(&self, ty: LoadOptionType, number: u16, option: &LoadOption) -> Result {
+        runtime::set_variable(
+            &option_name(ty.prefix(), number),
+            &VariableVendor::GLOBAL_VARIABLE,
+            ATTRIBUTES,
+            &option.to_bytes(),
+        )
+    }
+
+    /// Adds an entry with the lowest free number and appends it to
+    /// `BootOrder`. Returns the number of the entry.
+    ///
+    /// # Errors
+    ///
+    /// * [`Status::OUT_OF_RESOURCES`]: all numbers are in use.
+    /// * Errors of [`runtime::variable_keys`] and [`runtime::set_variable`].
+    pub fn add_entry(&self, option: &LoadOption) -> Result<u16> {
+        self.add_entry_of(LoadOptionType::Boot, option)
+    }
+
+    /// Adds an entry of type `ty` with the lowest free number and appends it
+    /// to the order of the type. Returns the number of the entry.
+    ///
+    /// # Errors
+    ///
+    /// See [`add_entry`].
+    ///
+    /// [`add_entry`]: Self::add_entry
+    pub fn add_entry_of(&self, ty: LoadOptionType, option: &LoadOption) -> Result<u16> {
+        let used = option_numbers(ty.prefix())?;
+        let number = (0..=u16::MAX)
+            .find(|n| !used.contains(n))
+            .ok_or(Status::OUT_OF_RESOURCES)?;
+
+        self.set_entry_of(ty, number, option)?;
+        let mut order = self.order(ty)?;
+        order.retain(|n| *n != number);
+        order.push(number);
+        self.set_order(ty, &order)?;
+        Ok(number)
+    }
+
+    /// Adds an active entry that loads the file at `path` on the file system
+    /// of the `partition` handle, like [`add_entry`] does.
+    ///
+    /// # Errors
+    ///
+    /// * Errors of [`boot::open_protocol_exclusive`], if `partition` has no
+    ///   [`DevicePath`].
+    /// * [`Status::INVALID_PARAMETER`]: the device path is too long.
+    /// * Errors of [`add_entry`].
+    ///
+    /// [`add_entry`]: Self::add_entry
+    pub fn create_entry(
+        &self,
+        description: &CStr16,
+        partition: Handle,
+        path: &CStr16,
+    ) -> Result<u16> {
+        self.create_entry_of(LoadOptionType::Boot, description, partition, path)
+    }
+
+    /// Adds an active entry of type `ty` that loads the file at `path` on the
+    /// file system of the `partition` handle, like [`create_entry`] does for
+    /// boot entries.
+    ///
+    /// # Errors
+    ///
+    /// See [`create_entry`].
+    ///
+    /// [`create_entry`]: Self::create_entry
+    pub fn create_entry_of(
+        &self,
+        ty: LoadOptionType,
+        description: &CStr16,
+        partition: Handle,
+        path: &CStr16,
+    ) -> Result<u16> {
+        let partition_path = boot::open_protocol_exclusive::<DevicePath>(partition)?;
+
+        let mut buf = Vec::new();
+        let mut builder = DevicePathBuilder::with_vec(&mut buf);
+        for node in partition_path.node_iter() {
+            builder = builder.push(&node).map_err(|_| Status::INVALID_PARAMETER)?;
+        }
+        let file_path = builder
+            .push(&build::media::FilePath { path_name: path })
+            .and_then(|builder| builder.finalize())
+            .map_err(|_| Status::INVALID_PARAMETER)?;
+
+        self.add_entry_of(ty, &LoadOption::new(description, file_path))
+    }
+
+    /// Deletes entry `number` and removes it from `BootOrder` and
+    /// `BootNext`.
+    ///
+    /// # Errors
+    ///
+    /// * [`Status::NOT_FOUND`]: the entry doesn't exist.
+    /// * Errors of [`runtime::delete_variable`] and
+    ///   [`runtime::set_variable`].
+    pub fn delete_entry(&self, number: u16) -> Result {
+        self.delete_entry_of(LoadOptionType::Boot, number)
+    }
+
+    /// Deletes entry `number` of type `ty` and removes it from the order of
+    /// the type, like [`delete_entry`] does for boot entries.
+    ///
+    /// # Errors
+    ///
+    /// See [`delete_entry`].
+    ///
+    /// [`delete_entry`]: Self::delete_entry
+    pub fn delete_entry_of(&self, ty: LoadOptionType, number: u16) -> Result {
+        runtime::delete_variable(
+            &option_name(ty.prefix(), number),
+            &VariableVendor::GLOBAL_VARIABLE,
+        )?;
+
+        let mut order = self.order(ty)?;
+        let len = order.len();
+        order.retain(|n| *n != number);
+        if order.len() != len {
+            self.set_order(ty, &order)?;
+        }
+        if ty == LoadOptionType::Boot && self.boot_next()? == Some(number) {
+            self.set_boot_next(None)?;
+        }
+        Ok(())
+    }
+
+    /// Returns the numbers of the entries that are tried on boot, in order.
+    /// The list is empty if `BootOrder` doesn't exist.
+    ///
+    /// # Errors
+    ///
+    /// * [`Status::VOLUME_CORRUPTED`]: the variable has an odd size.
+    /// * Errors of [`runtime::get_variable_boxed`].
+    pub fn boot_order(&self) -> Result<Vec<u16>> {
+        self.order(LoadOptionType::Boot)
+    }
+
+    /// Sets the numbers of the entries that are tried on boot, in order.
+    ///
+    /// # Errors
+    ///
+    /// Errors of [`runtime::set_variable`].
+    pub fn set_boot_order(&self, order: &[u16]) -> Result {
+        self.set_order(LoadOptionType::Boot, order)
+    }
+
+    /// Returns the numbers of the entries of type `ty` that are processed, in
+    /// order. The list is empty if the order variable doesn't exist.
+    ///
+    /// # Errors
+    ///
+    /// See [`boot_order`].
+    ///
+    /// [`boot_order`]: Self::boot_order
+    pub fn order(&self, ty: LoadOptionType) -> Result<Vec<u16>> {
+        read_number_list(ty.order_name())
+    }
+
+    /// Sets the numbers of the entries of type `ty` that are processed, in
+    /// order.
+    ///
+    /// # Errors
+    ///
+    /// Errors of [`runtime::set_variable`].
+    pub fn set_order(&self, ty: LoadOptionType, order: &[u16]) -> Result {
+        write_number_list(ty.order_name(), order)
+    }
+
+    /// Returns the number of the entry that is tried first on the next boot
+    /// only, if any.
+    ///
+    /// # Errors
+    ///
+    /// Errors of [`runtime::get_variable_array`].
+    pub fn boot_next(&self) -> Result<Option<u16>> {
+        read_number(cstr16!("BootNext"))
+    }
+
+    /// Sets the number of the entry that is tried first on the next boot
+    /// only. `None` deletes `BootNext`.
+    ///
+    /// # Errors
+    ///
+    /// Errors of [`runtime::set_variable`] and [`runtime::delete_variable`],
+    /// except [`Status::NOT_FOUND`] when deleting.
+    pub fn set_boot_next(&self, number: Option<u16>) -> Result {
+        let name = cstr16!("BootNext");
+        let vendor = &VariableVendor::GLOBAL_VARIABLE;
+        match number {
+            Some(number) => runtime::set_variable(name, vendor, ATTRIBUTES, &number.to_le_bytes()),
+            None => match runtime::delete_variable(name, vendor) {
+                Err(err) if err.status() == Status::NOT_FOUND => Ok(()),
+                result => result,
+            },
+        }
+    }
+
+    /// Returns the number of the entry that was booted, if the firmware
+    /// provides it.
+    ///
+    /// # Errors
+    ///
+    /// Errors of [`runtime::get_variable_array`].
+    pub fn boot_current(&self) -> Result<Option<u16>> {
+        read_number(cstr16!("BootCurrent"))
+    }
+
+    /// Returns all hotkeys of the boot manager, sorted by their number.
+    ///
+    /// Variables that don't contain a valid [`KeyOption`] are skipped.
+    ///
+    /// # Errors
+    ///
+    /// Errors of [`runtime::variable_keys`] and
+    /// [`runtime::get_variable_boxed`].
+    pub fn key_entries(&self) -> Result<Vec<KeyEntry>> {
+        let mut entries = Vec::new();
+        for number in option_numbers("Key")? {
+            let name = option_name("Key", number);
+            let (data, _) = runtime::get_variable_boxed(&name, &VariableVendor::GLOBAL_VARIABLE)?;
+            match KeyOption::parse(&data) {
+                Ok(option) => entries.push(KeyEntry { number, option }),
+                Err(err) => log::debug!("Skipping invalid {name}: {err}"),
+            }
+        }
+        Ok(entries)
+    }
+
+    /// Binds a hotkey to entry `boot_option`: when the `keys` are pressed
+    /// with the `modifiers` held, the boot manager boots that entry. Returns
+    /// the number of the new `Key####` variable.
+    ///
+    /// The hotkey only applies to the current contents of the entry. It must
+    /// be bound again if the entry is changed with [`set_entry`].
+    ///
+    /// Hotkeys are optional: firmware that supports them sets
+    /// `EFI_BOOT_OPTION_SUPPORT_KEY` in the `BootOptionSupport` variable,
+    /// along with the maximum number of keys.
+    ///
+    /// # Errors
+    ///
+    /// * [`Status::INVALID_PARAMETER`]: there are more than
+    ///   [`KeyOption::MAX_KEYS`] keys.
+    /// * [`Status::OUT_OF_RESOURCES`]: all numbers are in use.
+    /// * Errors of [`entry`] and [`runtime::set_variable`].
+    ///
+    /// [`entry`]: Self::entry
+    /// [`set_entry`]: Self::set_entry
+    pub fn bind_key(&self, boot_option: u16, modifiers: KeyModifiers, keys: &[Key]) -> Result<u16> {
+        let option = self.entry(boot_option)?;
+        let key_option = KeyOption::new(boot_option, &option, modifiers, keys)
+            .map_err(|_| Status::INVALID_PARAMETER)?;
+
+        let used = option_numbers("Key")?;
+        let number = (0..=u16::MAX)
+            .find(|n| !used.contains(n))
+            .ok_or(Status::OUT_OF_RESOURCES)?;
+        runtime::set_variable(
+            &option_name("Key", number),
+            &VariableVendor::GLOBAL_VARIABLE,
+            ATTRIBUTES,
+            &key_option.to_bytes(),
+        )?;
+        Ok(number)
+    }
+
+    /// Deletes hotkey `number`.
+    ///
+    /// # Errors
+    ///
+    /// * [`Status::NOT_FOUND`]: the hotkey doesn't exist.
+    /// * Errors of [`runtime::delete_variable`].
+    pub fn delete_key_entry(&self, number: u16) -> Result {
+        runtime::delete_variable(
+            &option_name("Key", number),
+            &VariableVendor::GLOBAL_VARIABLE,
+        )
+    }
+
+    /// Resets the system, so that the changes to the boot configuration take
+    /// effect.
+    pub fn reset(&self) -> ! {
+        runtime::reset(ResetType::COLD, Status::SUCCESS, None)
+    }
+}
+
+/// Returns the name of load option variable `number`, e.g. `Boot0001`.
+pub(super) fn option_name(prefix: &str, number: u16) -> CString16 {
+    // OK to unwrap: the name is ASCII.
+    CString16::try_from(format!("{prefix}{number:04X}").as_str()).unwrap()
+}
+
+/// Returns the number of load option variable `name`, if it consists of
+/// `prefix` and four uppercase hexadecimal digits.
+pub(super) fn parse_option_number(name: &CStr16, prefix: &str) -> Option<u16> {
+    let name = name.to_string();
+    let digits = name.strip_prefix(prefix)?;
+    let is_digit = |c: char| c.is_ascii_digit() || ('A'..='F').contains(&c);
+    if digits.len() != 4 || !digits.chars().all(is_digit) {
+        return None;
+    }
+    u16::from_str_radix(digits, 16).ok()
+}
+
+/// Returns the numbers of all load option variables with `prefix`, sorted.
+pub(super) fn option_numbers(prefix: &str) -> Result<Vec<u16>> {
+    let mut numbers = Vec::new();
+    for key in runtime::variable_keys() {
+        let key = key?;
+        if key.vendor == VariableVendor::GLOBAL_VARIABLE {
+            numbers.extend(parse_option_number(&key.name, prefix));
+        }
+    }
+    numbers.sort_unstable();
+    Ok(numbers)
+}
+
+/// Reads the entries `numbers` of type `ty` with `read`, skipping the
+/// variables that are missing or don't contain a valid [`LoadOption`].
+fn read_entries(
+    ty: LoadOptionType,
+    numbers: &[u16],
+    mut read: impl FnMut(&CStr16) -> Result<Box<[u8]>>,
+) -> Result<Vec<BootEntry>> {
+    let mut entries = Vec::new();
+    for &number in numbers {
+        let name = option_name(ty.prefix(), number);
+        let data = match read(&name) {
+            Ok(data) => data,
+            Err(err) if err.status() == Status::NOT_FOUND => continue,
+            Err(err) => return Err(err),
+        };
+        match LoadOption::parse(&data) {
+            Ok(option) => entries.push(BootEntry { number, option }),
+            Err(err) => log::debug!("Skipping invalid {name}: {err}"),
+        }
+    }
+    Ok(entries)
+}
+
+/// Reads a variable that contains a single number, or `None` if it doesn't
+/// exist.
+fn read_number(name: &CStr16) -> Result<Option<u16>> {
+    match runtime::get_variable_array::<2>(name, &VariableVendor::GLOBAL_VARIABLE) {
+        Ok((data, _)) => Ok(Some(u16::from_le_bytes(data))),
+        Err(err) if err.status() == Status::NOT_FOUND => Ok(None),
+        Err(err) => Err(err.to_err_without_payload()),
+    }
+}
+
+/// Reads a variable that contains a list of numbers, or an empty list if it
+/// doesn't exist.
+pub(super) fn read_number_list(name: &CStr16) -> Result<Vec<u16>> {
+    let data = match runtime::get_variable_boxed(name, &VariableVendor::GLOBAL_VARIABLE) {
+        Ok((data, _)) => data,
+        Err(err) if err.status() == Status::NOT_FOUND => return Ok(Vec::new()),
+        Err(err) => return Err(err),
+    };
+    if data.len() % 2 != 0 {
+        return Err(Status::VOLUME_CORRUPTED.into());
+    }
+    Ok(data
+        .chunks_exact(2)
+        .map(|n| u16::from_le_bytes([n[0], n[1]]))
+        .collect())
+}
+
+/// Writes a variable that contains a list of numbers.
+pub(super) fn write_number_list(name: &CStr16, numbers: &[u16]) -> Result {
+    let data: Vec<u8> = numbers.iter().flat_map(|n| n.to_le_bytes()).collect();
+    runtime::set_variable(name, &VariableVendor::GLOBAL_VARIABLE, ATTRIBUTES, &data)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloc::vec;
+
+    #[test]
+    fn test_option_name() {
+        assert_eq!(option_name("Boot", 0x1a), cstr16!("Boot001A"));
+        assert_eq!(option_name("Boot", 0xffff), cstr16!("BootFFFF"));
+        assert_eq!(
+            option_name(LoadOptionType::SysPrep.prefix(), 2),
+            cstr16!("SysPrep0002")
+        );
+        assert_eq!(LoadOptionType::Driver.order_name(), cstr16!("DriverOrder"));
+    }
+
+    #[test]
+    fn test_parse_option_number() {
+        assert_eq!(parse_option_number(cstr16!("Boot0000"), "Boot"), Some(0));
+        assert_eq!(parse_option_number(cstr16!("Boot00AF"), "Boot"), Some(0xaf));
+        assert_eq!(
+            parse_option_number(cstr16!("BootFFFF"), "Boot"),
+            Some(0xffff)
+        );
+
+        for name in [
+            cstr16!("BootOrder"),
+            cstr16!("BootNext"),
+            cstr16!("Boot00af"),
+            cstr16!("Boot+001"),
+            cstr16!("Boot001"),
+            cstr16!("Boot00001"),
+            cstr16!("Key0001"),
+        ] {
+            assert_eq!(parse_option_number(name, "Boot"), None, "{name}");
+        }
+    }
+
+    #[test]
+    fn test_read_entries_skips_invalid() {
+        let mut buf = Vec::new();
+        let path = DevicePathBuilder::with_vec(&mut buf)
+            .push(&build::media::FilePath {
+                path_name: cstr16!("\\a"),
+            })
+            .unwrap()
+            .finalize()
+            .unwrap();
+        let valid = LoadOption::new(cstr16!("Test"), path);
+        let bytes = valid.to_bytes();
+        // File path list ending with a zero-length node.
+        let mut invalid = bytes.clone();
+        let end = invalid.len() - 4;
+        invalid[end..].copy_from_slice(&[0x54, 0x5d, 0, 0]);
+
+        let entries = read_entries(LoadOptionType::Driver, &[1, 2, 3, 4], |name| {
+            match name.to_string().as_str() {
+                "Driver0001" | "Driver0004" => Ok(bytes.clone().into_boxed_slice()),
+                "Driver0002" => Ok(invalid.clone().into_boxed_slice()),
+                _ => Err(Status::NOT_FOUND.into()),
+            }
+        })
+        .unwrap();
+        assert_eq!(
+            entries,
+            vec![
+                BootEntry {
+                    number: 1,
+                    option: valid.clone(),
+                },
+                BootEntry {
+                    number: 4,
+                    option: valid,
+                },
+            ]
+        );
+
+        let err = read_entries(LoadOptionType::Boot, &[1], |_| {
+            Err(Status::DEVICE_ERROR.into())
+        })
+        .unwrap_err();
+        assert_eq!(err.status(), Status::DEVICE_ERROR);
+    }
+}
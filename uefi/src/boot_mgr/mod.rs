@@ -0,0 +1,21 @@
+// SPDX-License-Identifier: MIT OR Apache-2.0
+
+//! Boot manager entries.
+//!
+//! The boot manager of the firmware stores its entries in variables of the
+//! [global variable vendor]: each `Boot####` variable, where `####` is a
+//! hexadecimal number, contains a [`LoadOption`] that describes the image to
+//! load, and each `Key####` variable contains a [`KeyOption`] that binds a
+//! hotkey to one of them. `Driver####` and `SysPrep####` variables contain
+//! load options of drivers and system preparation applications that are run
+//! before booting. See chapter 3 of the UEFI specification for details.
+//!
+//! [global variable vendor]: crate::runtime::VariableVendor::GLOBAL_VARIABLE
+
+mod key_option;
+mod load_option;
+mod manager;
+
+pub use key_option::{KeyModifiers, KeyOption, KeyOptionError};
+pub use load_option::{LoadOption, LoadOptionAttributes, LoadOptionError};
+pub use manager::{BootEntry, BootManager, KeyEntry, LoadOptionType};
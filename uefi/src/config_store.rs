@@ -0,0 +1,436 @@
+// SPDX-License-Identifier: MIT OR Apache-2.0
+
+//! Typed configuration stored in a UEFI variable.
+//!
+//! [`VarStore`] persists a value that implements [`ConfigData`] in a variable
+//! of an application-specific vendor GUID. The value is stored with a small
+//! header containing its format version, its size and a CRC32, so that
+//! truncated or foreign data is detected and old formats can be migrated:
+//!
+//! ```no_run
+//! use uefi::config_store::{ConfigData, VarStore};
+//! use uefi::runtime::VariableVendor;
+//! use uefi::{cstr16, guid};
+//!
+//! #[derive(Default)]
+//! struct Settings {
+//!     timeout: u16,
+//!     verbose: bool,
+//! }
+//!
+//! impl ConfigData for Settings {
+//!     const VERSION: u16 = 2;
+//!
+//!     fn encode(&self, out: &mut Vec<u8>) {
+//!         out.extend_from_slice(&self.timeout.to_le_bytes());
+//!         out.push(u8::from(self.verbose));
+//!     }
+//!
+//!     fn decode(bytes: &[u8]) -> Option<Self> {
+//!         let [t0, t1, verbose] = *bytes else {
+//!             return None;
+//!         };
+//!         Some(Self {
+//!             timeout: u16::from_le_bytes([t0, t1]),
+//!             verbose: verbose != 0,
+//!         })
+//!     }
+//!
+//!     // Version 1 only had the timeout.
+//!     fn migrate(version: u16, bytes: &[u8]) -> Option<Self> {
+//!         match (version, bytes) {
+//!             (1, [t0, t1]) => Some(Self {
+//!                 timeout: u16::from_le_bytes([*t0, *t1]),
+//!                 verbose: false,
+//!             }),
+//!             _ => None,
+//!         }
+//!     }
+//! }
+//!
+//! # fn example() -> Result<(), uefi::config_store::ConfigStoreError> {
+//! let vendor = VariableVendor(guid!("7d1d2e0c-1b9f-4a4c-9a43-5a0c2c1d3e4f"));
+//! let store = VarStore::<Settings>::new(cstr16!("Settings"), vendor);
+//!
+//! let mut settings = store.load_or_default()?;
+//! settings.timeout += 1;
+//! store.store(&settings)?;
+//! # Ok(())
+//! # }
+//! ```
+
+use crate::media::crc32::crc32;
+use crate::runtime::{self, VariableAttributes, VariableVendor};
+use crate::{CStr16, CString16, Status};
+use alloc::vec::Vec;
+use core::cmp::Ordering;
+use core::fmt::{self, Debug, Display, Formatter};
+use core::marker::PhantomData;
+
+/// Signature at the start of a stored value.
+const MAGIC: [u8; 4] = *b"UCFG";
+
+/// Size of the header: magic, version, reserved, payload size and CRC32.
+const HEADER_SIZE: usize = 16;
+
+/// A value that can be stored by a [`VarStore`].
+///
+/// The encoding is up to the implementation, but must be stable: a value
+/// that was encoded by one build of an application is decoded by later
+/// builds. Changes of the encoding need a new [`VERSION`], and [`migrate`]
+/// to decode the older versions.
+///
+/// [`VERSION`]: Self::VERSION
+/// [`migrate`]: Self::migrate
+pub trait ConfigData: Sized {
+    /// Version of the encoding of [`encode`] and [`decode`].
+    ///
+    /// [`encode`]: Self::encode
+    /// [`decode`]: Self::decode
+    const VERSION: u16;
+
+    /// Appends the encoded value to `out`.
+    fn encode(&self, out: &mut Vec<u8>);
+
+    /// Decodes a value that was encoded with the current [`VERSION`], or
+    /// returns `None` if `bytes` are invalid.
+    ///
+    /// [`VERSION`]: Self::VERSION
+    fn decode(bytes: &[u8]) -> Option<Self>;
+
+    /// Decodes a value that was encoded with an older `version`, or returns
+    /// `None` if that version is not supported. By default, no older version
+    /// is supported.
+    #[must_use]
+    fn migrate(version: u16, bytes: &[u8]) -> Option<Self> {
+        let _ = (version, bytes);
+        None
+    }
+}
+
+/// Errors of a [`VarStore`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum ConfigStoreError {
+    /// Reading or writing the variable failed.
+    Variable(crate::Error),
+    /// The variable doesn't contain a stored value, or its size or CRC32
+    /// doesn't match.
+    Corrupted,
+    /// The value has a newer version than [`ConfigData::VERSION`], or an older
+    /// version that [`ConfigData::migrate`] doesn't support.
+    UnsupportedVersion(u16),
+    /// [`ConfigData::decode`] rejected the value.
+    InvalidData,
+    /// The encoded value is larger than the maximum size of the store.
+    TooLarge,
+}
+
+impl Display for ConfigStoreError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Variable(err) => write!(f, "failed to access variable: {err}"),
+            Self::Corrupted => write!(f, "stored configuration is corrupted"),
+            Self::UnsupportedVersion(version) => {
+                write!(f, "unsupported configuration version {version}")
+            }
+            Self::InvalidData => write!(f, "invalid configuration data"),
+            Self::TooLarge => write!(f, "configuration is too large"),
+        }
+    }
+}
+
+impl core::error::Error for ConfigStoreError {
+    fn source(&self) -> Option<&(dyn core::error::Error + 'static)> {
+        match self {
+            Self::Variable(err) => Some(err),
+            _ => None,
+        }
+    }
+}
+
+impl From<crate::Error> for ConfigStoreError {
+    fn from(err: crate::Error) -> Self {
+        Self::Variable(err)
+    }
+}
+
+/// A [`ConfigData`] value stored in a UEFI variable.
+///
+/// By default, the variable is non-volatile and only accessible while boot
+/// services are active, and the stored value may be up to
+/// [`DEFAULT_MAX_SIZE`] bytes, including a 16-byte header. Variable storage
+/// is small and shared with the firmware, so values should be kept small.
+///
+/// See the [module documentation] for an example.
+///
+/// [`DEFAULT_MAX_SIZE`]: Self::DEFAULT_MAX_SIZE
+/// [module documentation]: self
+pub struct VarStore<T> {
+    name: CString16,
+    vendor: VariableVendor,
+    attributes: VariableAttributes,
+    max_size: usize,
+    _data: PhantomData<fn() -> T>,
+}
+
+impl<T> Debug for VarStore<T> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.debug_struct("VarStore")
+            .field("name", &self.name)
+            .field("vendor", &self.vendor)
+            .field("attributes", &self.attributes)
+            .field("max_size", &self.max_size)
+            .finish()
+    }
+}
+
+impl<T: ConfigData> VarStore<T> {
+    /// Default maximum size of the stored value, including the header.
+    pub const DEFAULT_MAX_SIZE: usize = 1024;
+
+    /// Creates a store for the variable `name` of `vendor`. The variable is
+    /// not accessed until the value is loaded or stored.
+    #[must_use]
+    pub fn new(name: &CStr16, vendor: VariableVendor) -> Self {
+        Self {
+            name: name.into(),
+            vendor,
+            attributes: VariableAttributes::NON_VOLATILE | VariableAttributes::BOOTSERVICE_ACCESS,
+            max_size: Self::DEFAULT_MAX_SIZE,
+            _data: PhantomData,
+        }
+    }
+
+    /// Sets the attributes of the variable, e.g. to make it accessible at
+    /// runtime.
+    #[must_use]
+    pub const fn with_attributes(mut self, attributes: VariableAttributes) -> Self {
+        self.attributes = attributes;
+        self
+    }
+
+    /// Sets the maximum size of the stored value, including the 16-byte
+    /// header.
+    #[must_use]
+    pub const fn with_max_size(mut self, max_size: usize) -> Self {
+        self.max_size = max_size;
+        self
+    }
+
+    /// Loads the value, or returns `None` if the variable doesn't exist.
+    ///
+    /// Values of an older version are migrated with [`ConfigData::migrate`].
+    /// The variable keeps the old version until the value is stored again.
+    ///
+    /// # Errors
+    ///
+    /// * [`ConfigStoreError::Variable`]: errors of
+    ///   [`runtime::get_variable_boxed`].
+    /// * [`ConfigStoreError::TooLarge`]: the variable is larger than the
+    ///   maximum size.
+    /// * [`ConfigStoreError::Corrupted`], [`ConfigStoreError::UnsupportedVersion`]
+    ///   and [`ConfigStoreError::InvalidData`]: the value can't be decoded.
+    pub fn load(&self) -> Result<Option<T>, ConfigStoreError> {
+        let data = match runtime::get_variable_boxed(&self.name, &self.vendor) {
+            Ok((data, _)) => data,
+            Err(err) if err.status() == Status::NOT_FOUND => return Ok(None),
+            Err(err) => return Err(err.into()),
+        };
+        if data.len() > self.max_size {
+            return Err(ConfigStoreError::TooLarge);
+        }
+        decode_value(&data).map(Some)
+    }
+
+    /// Loads the value, or returns the default value if the variable doesn't
+    /// exist.
+    ///
+    /// # Errors
+    ///
+    /// See [`load`].
+    ///
+    /// [`load`]: Self::load
+    pub fn load_or_default(&self) -> Result<T, ConfigStoreError>
+    where
+        T: Default,
+    {
+        self.load().map(Option::unwrap_or_default)
+    }
+
+    /// Stores the value with the current [`ConfigData::VERSION`], creating
+    /// or replacing the variable.
+    ///
+    /// # Errors
+    ///
+    /// * [`ConfigStoreError::TooLarge`]: the encoded value is larger than the
+    ///   maximum size.
+    /// * [`ConfigStoreError::Variable`]: errors of [`runtime::set_variable`].
+    pub fn store(&self, value: &T) -> Result<(), ConfigStoreError> {
+        let data = encode_value(value);
+        if data.len() > self.max_size {
+            return Err(ConfigStoreError::TooLarge);
+        }
+        runtime::set_variable(&self.name, &self.vendor, self.attributes, &data)?;
+        Ok(())
+    }
+
+    /// Deletes the variable. Deleting a variable that doesn't exist is not
+    /// an error.
+    ///
+    /// # Errors
+    ///
+    /// [`ConfigStoreError::Variable`]: errors of [`runtime::delete_variable`].
+    pub fn delete(&self) -> Result<(), ConfigStoreError> {
+        match runtime::delete_variable(&self.name, &self.vendor) {
+            Err(err) if err.status() != Status::NOT_FOUND => Err(err.into()),
+            _ => Ok(()),
+        }
+    }
+}
+
+/// Encodes `value` with a header.
+fn encode_value<T: ConfigData>(value: &T) -> Vec<u8> {
+    let mut data = Vec::with_capacity(HEADER_SIZE);
+    data.extend_from_slice(&[0; HEADER_SIZE]);
+    value.encode(&mut data);
+
+    let payload_len = u32::try_from(data.len() - HEADER_SIZE).unwrap_or(u32::MAX);
+    let crc = crc32(&data[HEADER_SIZE..]);
+    data[0..4].copy_from_slice(&MAGIC);
+    data[4..6].copy_from_slice(&T::VERSION.to_le_bytes());
+    data[8..12].copy_from_slice(&payload_len.to_le_bytes());
+    data[12..16].copy_from_slice(&crc.to_le_bytes());
+    data
+}
+
+/// Checks the header of `data` and decodes the value, migrating it if
+/// necessary.
+fn decode_value<T: ConfigData>(data: &[u8]) -> Result<T, ConfigStoreError> {
+    let (header, payload) = data
+        .split_at_checked(HEADER_SIZE)
+        .ok_or(ConfigStoreError::Corrupted)?;
+    let version = u16::from_le_bytes([header[4], header[5]]);
+    let payload_len = u32::from_le_bytes(header[8..12].try_into().unwrap());
+    let crc = u32::from_le_bytes(header[12..16].try_into().unwrap());
+    if header[..4] != MAGIC
+        || usize::try_from(payload_len) != Ok(payload.len())
+        || crc32(payload) != crc
+    {
+        return Err(ConfigStoreError::Corrupted);
+    }
+
+    match version.cmp(&T::VERSION) {
+        Ordering::Equal => T::decode(payload).ok_or(ConfigStoreError::InvalidData),
+        Ordering::Less => {
+            T::migrate(version, payload).ok_or(ConfigStoreError::UnsupportedVersion(version))
+        }
+        Ordering::Greater => Err(ConfigStoreError::UnsupportedVersion(version)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[derive(Debug, PartialEq)]
+    struct Config {
+        value: u32,
+    }
+
+    impl ConfigData for Config {
+        const VERSION: u16 = 3;
+
+        fn encode(&self, out: &mut Vec<u8>) {
+            out.extend_from_slice(&self.value.to_le_bytes());
+        }
+
+        fn decode(bytes: &[u8]) -> Option<Self> {
+            Some(Self {
+                value: u32::from_le_bytes(bytes.try_into().ok()?),
+            })
+        }
+
+        fn migrate(version: u16, bytes: &[u8]) -> Option<Self> {
+            match (version, bytes) {
+                (2, [value]) => Some(Self {
+                    value: u32::from(*value),
+                }),
+                _ => None,
+            }
+        }
+    }
+
+    #[test]
+    fn test_round_trip() {
+        let data = encode_value(&Config { value: 0x1234_5678 });
+        let crc = crc32(&[0x78, 0x56, 0x34, 0x12]).to_le_bytes();
+        #[rustfmt::skip]
+        assert_eq!(data, [
+            b'U', b'C', b'F', b'G',
+            3, 0, 0, 0,
+            4, 0, 0, 0,
+            crc[0], crc[1], crc[2], crc[3],
+            0x78, 0x56, 0x34, 0x12,
+        ]);
+        assert_eq!(
+            decode_value::<Config>(&data),
+            Ok(Config { value: 0x1234_5678 })
+        );
+    }
+
+    #[test]
+    fn test_migration() {
+        /// Encodes `payload` as version `version`.
+        fn encode_version(version: u16, payload: &[u8]) -> Vec<u8> {
+            let mut data = encode_value(&Config { value: 0 });
+            data.truncate(HEADER_SIZE);
+            data.extend_from_slice(payload);
+            data[4..6].copy_from_slice(&version.to_le_bytes());
+            data[8..12].copy_from_slice(&(payload.len() as u32).to_le_bytes());
+            data[12..16].copy_from_slice(&crc32(payload).to_le_bytes());
+            data
+        }
+
+        assert_eq!(
+            decode_value::<Config>(&encode_version(2, &[7])),
+            Ok(Config { value: 7 })
+        );
+        assert_eq!(
+            decode_value::<Config>(&encode_version(1, &[7])),
+            Err(ConfigStoreError::UnsupportedVersion(1))
+        );
+        assert_eq!(
+            decode_value::<Config>(&encode_version(4, &[0; 4])),
+            Err(ConfigStoreError::UnsupportedVersion(4))
+        );
+        assert_eq!(
+            decode_value::<Config>(&encode_version(3, &[0; 3])),
+            Err(ConfigStoreError::InvalidData)
+        );
+    }
+
+    #[test]
+    fn test_corrupted() {
+        let data = encode_value(&Config { value: 1 });
+        for len in 0..data.len() {
+            assert_eq!(
+                decode_value::<Config>(&data[..len]),
+                Err(ConfigStoreError::Corrupted)
+            );
+        }
+
+        let mut invalid = data.clone();
+        invalid[0] = b'X';
+        assert_eq!(
+            decode_value::<Config>(&invalid),
+            Err(ConfigStoreError::Corrupted)
+        );
+
+        let mut invalid = data;
+        invalid[HEADER_SIZE] ^= 1;
+        assert_eq!(
+            decode_value::<Config>(&invalid),
+            Err(ConfigStoreError::Corrupted)
+        );
+    }
+}
@@ -0,0 +1,488 @@
+// SPDX-License-Identifier: MIT OR Apache-2.0
+
+//! ANSI escape sequences on the text console.
+//!
+//! [`AnsiOutput`] interprets the escape sequences that terminal-oriented
+//! libraries write, and translates them into calls of the [`Output`]
+//! protocol. The following sequences are supported:
+//!
+//! * Select Graphic Rendition (`ESC [ ... m`): reset, bold, and the eight
+//!   normal and bright foreground and background colors.
+//! * Cursor movement: `ESC [ n A/B/C/D/E/F/G/H/d/f`, as well as saving and
+//!   restoring the cursor position with `ESC [ s/u` and `ESC 7/8`.
+//! * Erasing: `ESC [ n J` (screen) and `ESC [ n K` (line).
+//! * Showing and hiding the cursor with `ESC [ ? 25 h/l`.
+//!
+//! Other sequences are removed from the output.
+
+use crate::proto::console::text::{Color, Output};
+use crate::{Result, Status};
+use core::fmt::{self, Write};
+
+/// Maximum number of parameters of a control sequence. Further parameters
+/// are ignored.
+const MAX_PARAMS: usize = 8;
+
+/// UEFI colors of the eight ANSI colors, in ANSI order.
+const COLORS: [Color; 8] = [
+    Color::Black,
+    Color::Red,
+    Color::Green,
+    Color::Brown,
+    Color::Blue,
+    Color::Magenta,
+    Color::Cyan,
+    Color::LightGray,
+];
+
+/// UEFI colors of the eight bright ANSI colors, in ANSI order.
+const BRIGHT_COLORS: [Color; 8] = [
+    Color::DarkGray,
+    Color::LightRed,
+    Color::LightGreen,
+    Color::Yellow,
+    Color::LightBlue,
+    Color::LightMagenta,
+    Color::LightCyan,
+    Color::White,
+];
+
+/// A text console that interprets ANSI escape sequences, see the
+/// [module documentation].
+///
+/// # Example
+///
+/// ```no_run
+/// use core::fmt::Write;
+/// use uefi::console::ansi::AnsiOutput;
+/// use uefi::system;
+///
+/// system::with_stdout(|stdout| {
+///     let mut out = AnsiOutput::new(stdout);
+///     let _ = writeln!(out, "\x1b[1;32mOK\x1b[0m: all checks passed");
+/// });
+/// ```
+///
+/// [module documentation]: self
+#[derive(Debug)]
+pub struct AnsiOutput<'a> {
+    output: &'a mut Output,
+    parser: Parser,
+    style: Style,
+    saved_cursor: (usize, usize),
+}
+
+impl<'a> AnsiOutput<'a> {
+    /// Creates a console writing to `output`. The colors are set to light
+    /// gray on black once the first text is written.
+    pub const fn new(output: &'a mut Output) -> Self {
+        Self {
+            output,
+            parser: Parser::new(),
+            style: Style::new(),
+            saved_cursor: (0, 0),
+        }
+    }
+
+    /// Returns the underlying output.
+    pub const fn output(&mut self) -> &mut Output {
+        self.output
+    }
+
+    /// Writes a string, interpreting the escape sequences in it. Sequences
+    /// may be split across calls.
+    pub fn write_str(&mut self, s: &str) -> Result {
+        let mut parser = core::mem::take(&mut self.parser);
+        let result = parser.feed(s, |command| self.apply(command));
+        self.parser = parser;
+        result
+    }
+
+    fn apply(&mut self, command: Command<'_>) -> Result {
+        let (column, row) = self.output.cursor_position();
+        match command {
+            Command::Text(text) => {
+                self.output
+                    .set_color(self.style.foreground(), self.style.background())?;
+                Write::write_str(self.output, text).map_err(|_| Status::DEVICE_ERROR.into())
+            }
+            Command::Sgr(params) => {
+                self.style.apply_sgr(params);
+                Ok(())
+            }
+            Command::MoveBy(columns, rows) => {
+                let column = column.saturating_add_signed(columns);
+                let row = row.saturating_add_signed(rows);
+                self.move_to(column, row)
+            }
+            Command::MoveTo(new_column, new_row) => {
+                self.move_to(new_column.unwrap_or(column), new_row.unwrap_or(row))
+            }
+            Command::EraseDisplay(mode) => self.erase_display(mode),
+            Command::EraseLine(mode) => {
+                let (columns, _) = self.output.size()?;
+                let range = match mode {
+                    0 => column..columns,
+                    1 => 0..column + 1,
+                    _ => 0..columns,
+                };
+                self.erase(row, range)?;
+                self.output.set_cursor_position(column, row)
+            }
+            Command::SaveCursor => {
+                self.saved_cursor = (column, row);
+                Ok(())
+            }
+            Command::RestoreCursor => self.move_to(self.saved_cursor.0, self.saved_cursor.1),
+            Command::ShowCursor(visible) => match self.output.enable_cursor(visible) {
+                // Not all consoles can hide the cursor.
+                Err(err) if err.status() == Status::UNSUPPORTED => Ok(()),
+                result => result,
+            },
+        }
+    }
+
+    /// Moves the cursor, limited to the screen.
+    fn move_to(&mut self, column: usize, row: usize) -> Result {
+        let (columns, rows) = self.output.size()?;
+        self.output.set_cursor_position(
+            column.min(columns.saturating_sub(1)),
+            row.min(rows.saturating_sub(1)),
+        )
+    }
+
+    fn erase_display(&mut self, mode: u16) -> Result {
+        let (column, row) = self.output.cursor_position();
+        let (columns, rows) = self.output.size()?;
+        match mode {
+            0 => {
+                self.erase(row, column..columns)?;
+                for row in row + 1..rows {
+                    self.erase(row, 0..columns)?;
+                }
+            }
+            1 => {
+                for row in 0..row {
+                    self.erase(row, 0..columns)?;
+                }
+                self.erase(row, 0..column + 1)?;
+            }
+            _ => {
+                self.output
+                    .set_color(self.style.foreground(), self.style.background())?;
+                self.output.clear()?;
+            }
+        }
+        self.output.set_cursor_position(column, row)
+    }
+
+    /// Overwrites the cells of `columns` in `row` with spaces. The last
+    /// cell of the screen is skipped, as writing it scrolls the screen.
+    fn erase(&mut self, row: usize, columns: core::ops::Range<usize>) -> Result {
+        let (width, height) = self.output.size()?;
+        let end = if row + 1 == height {
+            columns.end.min(width - 1)
+        } else {
+            columns.end.min(width)
+        };
+        if columns.start >= end {
+            return Ok(());
+        }
+        self.output
+            .set_color(self.style.foreground(), self.style.background())?;
+        self.output.set_cursor_position(columns.start, row)?;
+        for _ in columns.start..end {
+            Write::write_str(self.output, " ").map_err(|_| Status::DEVICE_ERROR)?;
+        }
+        Ok(())
+    }
+}
+
+impl fmt::Write for AnsiOutput<'_> {
+    fn write_str(&mut self, s: &str) -> fmt::Result {
+        Self::write_str(self, s).map_err(|_| fmt::Error)
+    }
+}
+
+/// Colors selected with SGR sequences.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+struct Style {
+    /// ANSI index of the foreground color, or `None` for the default.
+    foreground: Option<u8>,
+    /// ANSI index of the background color, or `None` for the default.
+    background: Option<u8>,
+    bold: bool,
+}
+
+impl Style {
+    const fn new() -> Self {
+        Self {
+            foreground: None,
+            background: None,
+            bold: false,
+        }
+    }
+
+    fn apply_sgr(&mut self, params: &[u16]) {
+        if params.is_empty() {
+            *self = Self::new();
+        }
+        for &param in params {
+            match param {
+                0 => *self = Self::new(),
+                1 => self.bold = true,
+                22 => self.bold = false,
+                30..=37 => self.foreground = Some((param - 30) as u8),
+                39 => self.foreground = None,
+                40..=47 => self.background = Some((param - 40) as u8),
+                49 => self.background = None,
+                90..=97 => self.foreground = Some((param - 90) as u8 + 8),
+                100..=107 => self.background = Some((param - 100) as u8 + 8),
+                _ => {}
+            }
+        }
+    }
+
+    const fn foreground(&self) -> Color {
+        match self.foreground {
+            // Bold text is shown in the bright variant of its color.
+            Some(index @ 0..=7) if self.bold => BRIGHT_COLORS[index as usize],
+            Some(index @ 0..=7) => COLORS[index as usize],
+            Some(index) => BRIGHT_COLORS[(index & 7) as usize],
+            None if self.bold => Color::White,
+            None => Color::LightGray,
+        }
+    }
+
+    const fn background(&self) -> Color {
+        // The console only supports the eight normal background colors.
+        match self.background {
+            Some(index) => COLORS[(index & 7) as usize],
+            None => Color::Black,
+        }
+    }
+}
+
+/// An action of a piece of text written to an [`AnsiOutput`].
+#[derive(Debug, Eq, PartialEq)]
+enum Command<'a> {
+    /// Text without escape sequences.
+    Text(&'a str),
+    /// Parameters of a Select Graphic Rendition sequence.
+    Sgr(&'a [u16]),
+    /// Move the cursor by `(columns, rows)`.
+    MoveBy(isize, isize),
+    /// Move the cursor to `(column, row)`, keeping the current coordinate
+    /// if `None`.
+    MoveTo(Option<usize>, Option<usize>),
+    EraseDisplay(u16),
+    EraseLine(u16),
+    SaveCursor,
+    RestoreCursor,
+    ShowCursor(bool),
+}
+
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+enum State {
+    #[default]
+    Ground,
+    Escape,
+    Csi,
+}
+
+/// Splits text into [`Command`]s.
+#[derive(Debug, Default)]
+struct Parser {
+    state: State,
+    params: [u16; MAX_PARAMS],
+    count: usize,
+    private: bool,
+}
+
+impl Parser {
+    const fn new() -> Self {
+        Self {
+            state: State::Ground,
+            params: [0; MAX_PARAMS],
+            count: 0,
+            private: false,
+        }
+    }
+
+    /// Parses `s` and passes the resulting commands to `f`.
+    fn feed(&mut self, s: &str, mut f: impl FnMut(Command<'_>) -> Result) -> Result {
+        let mut text_start = 0;
+        for (i, c) in s.char_indices() {
+            match self.state {
+                State::Ground => {
+                    if c == '\x1b' {
+                        if text_start < i {
+                            f(Command::Text(&s[text_start..i]))?;
+                        }
+                        self.state = State::Escape;
+                    }
+                    continue;
+                }
+                State::Escape => {
+                    self.state = State::Ground;
+                    match c {
+                        '[' => {
+                            self.state = State::Csi;
+                            self.params = [0; MAX_PARAMS];
+                            self.count = 0;
+                            self.private = false;
+                        }
+                        '7' => f(Command::SaveCursor)?,
+                        '8' => f(Command::RestoreCursor)?,
+                        _ => {}
+                    }
+                }
+                State::Csi => match c {
+                    '0'..='9' => {
+                        let digit = c as u16 - u16::from(b'0');
+                        if self.count == 0 {
+                            self.count = 1;
+                        }
+                        if let Some(param) = self.params.get_mut(self.count - 1) {
+                            *param = param.saturating_mul(10).saturating_add(digit);
+                        }
+                    }
+                    ';' => self.count = self.count.max(1) + 1,
+                    '?' => self.private = true,
+                    '\x20'..='\x3f' => {}
+                    _ => {
+                        self.state = State::Ground;
+                        self.dispatch(c, &mut f)?;
+                    }
+                },
+            }
+            text_start = i + c.len_utf8();
+        }
+        if self.state == State::Ground && text_start < s.len() {
+            f(Command::Text(&s[text_start..]))?;
+        }
+        Ok(())
+    }
+
+    /// Handles the final character of a control sequence.
+    fn dispatch(&self, c: char, f: &mut impl FnMut(Command<'_>) -> Result) -> Result {
+        let count = self.count.min(MAX_PARAMS);
+        let params = &self.params[..count];
+        // Returns parameter `i`, or `default` if it's missing or zero.
+        let param = |i: usize, default: u16| match params.get(i) {
+            Some(0) | None => default,
+            Some(&value) => value,
+        };
+        let amount = isize::from(param(0, 1) as i16);
+        let position = |i| usize::from(param(i, 1)) - 1;
+
+        let command = match (self.private, c) {
+            (true, 'h' | 'l') if params == [25] => Command::ShowCursor(c == 'h'),
+            (true, _) => return Ok(()),
+            (false, 'm') => Command::Sgr(params),
+            (false, 'A') => Command::MoveBy(0, -amount),
+            (false, 'B') => Command::MoveBy(0, amount),
+            (false, 'C') => Command::MoveBy(amount, 0),
+            (false, 'D') => Command::MoveBy(-amount, 0),
+            (false, 'E') => {
+                f(Command::MoveTo(Some(0), None))?;
+                Command::MoveBy(0, amount)
+            }
+            (false, 'F') => {
+                f(Command::MoveTo(Some(0), None))?;
+                Command::MoveBy(0, -amount)
+            }
+            (false, 'G') => Command::MoveTo(Some(position(0)), None),
+            (false, 'd') => Command::MoveTo(None, Some(position(0))),
+            (false, 'H' | 'f') => Command::MoveTo(Some(position(1)), Some(position(0))),
+            (false, 'J') => Command::EraseDisplay(params.first().copied().unwrap_or(0)),
+            (false, 'K') => Command::EraseLine(params.first().copied().unwrap_or(0)),
+            (false, 's') => Command::SaveCursor,
+            (false, 'u') => Command::RestoreCursor,
+            _ => return Ok(()),
+        };
+        f(command)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloc::format;
+    use alloc::string::String;
+    use alloc::vec::Vec;
+
+    /// Parses `chunks` and returns the commands in debug format.
+    fn parse(chunks: &[&str]) -> Vec<String> {
+        let mut parser = Parser::new();
+        let mut commands = Vec::new();
+        for chunk in chunks {
+            parser
+                .feed(chunk, |command| {
+                    commands.push(format!("{command:?}"));
+                    Ok(())
+                })
+                .unwrap();
+        }
+        commands
+    }
+
+    #[test]
+    fn test_parser() {
+        assert_eq!(
+            parse(&["a\x1b[1;31mb\x1b[0mc"]),
+            [
+                "Text(\"a\")",
+                "Sgr([1, 31])",
+                "Text(\"b\")",
+                "Sgr([0])",
+                "Text(\"c\")"
+            ]
+        );
+        assert_eq!(
+            parse(&["\x1b[H\x1b[5;10H\x1b[3A\x1b[C\x1b[2J\x1b[K"]),
+            [
+                "MoveTo(Some(0), Some(0))",
+                "MoveTo(Some(9), Some(4))",
+                "MoveBy(0, -3)",
+                "MoveBy(1, 0)",
+                "EraseDisplay(2)",
+                "EraseLine(0)"
+            ]
+        );
+        assert_eq!(
+            parse(&["\x1b[?25l\x1b7\x1b8\x1b[?1049h\x1b]x"]),
+            [
+                "ShowCursor(false)",
+                "SaveCursor",
+                "RestoreCursor",
+                "Text(\"x\")"
+            ]
+        );
+        assert_eq!(parse(&["\x1b[m"]), ["Sgr([])"]);
+    }
+
+    #[test]
+    fn test_parser_split() {
+        assert_eq!(
+            parse(&["ab\x1b", "[3", "2mc"]),
+            ["Text(\"ab\")", "Sgr([32])", "Text(\"c\")"]
+        );
+    }
+
+    #[test]
+    fn test_style() {
+        let mut style = Style::new();
+        style.apply_sgr(&[31, 44]);
+        assert!(matches!(style.foreground(), Color::Red));
+        assert!(matches!(style.background(), Color::Blue));
+        style.apply_sgr(&[1]);
+        assert!(matches!(style.foreground(), Color::LightRed));
+        style.apply_sgr(&[22, 93, 103]);
+        assert!(matches!(style.foreground(), Color::Yellow));
+        assert!(matches!(style.background(), Color::Brown));
+        style.apply_sgr(&[]);
+        assert_eq!(style, Style::new());
+        assert!(matches!(style.foreground(), Color::LightGray));
+    }
+}
@@ -0,0 +1,98 @@
+// SPDX-License-Identifier: MIT OR Apache-2.0
+
+//! Output to the debug console I/O port of QEMU, Bochs and
+//! cloud-hypervisor (x86 only).
+//!
+//! The debug console is a write-only device that forwards every byte
+//! written to its I/O port to the host, e.g. to a file with QEMU's
+//! `-debugcon file:debugcon.log` option. It needs no setup, so it works even
+//! when the serial port or the console protocols are misconfigured, and also
+//! after boot services have been exited.
+//!
+//! More info: <https://phip1611.de/blog/how-to-use-qemus-debugcon-feature/>
+//!
+//! The [`helpers`] logger writes to the debug console with the
+//! `log-debugcon` feature, see `LoggerBuilder::debugcon`.
+//!
+//! [`helpers`]: crate::helpers
+
+use core::fmt;
+
+/// Writer to a debug console I/O port.
+///
+/// Writing to a port without a debug console has no effect on common
+/// machines, but that is not guaranteed. Use [`is_present`] to check for
+/// the device first.
+///
+/// [`is_present`]: Self::is_present
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct Debugcon {
+    port: u16,
+}
+
+impl Debugcon {
+    /// The debug console at I/O port `0xe9`, the default port of QEMU and
+    /// Bochs.
+    pub const QEMU: Self = Self::new(0xe9);
+
+    /// The debug console at I/O port `0x402`, which OVMF uses for its own
+    /// debug messages.
+    pub const OVMF: Self = Self::new(0x402);
+
+    /// Creates a writer to the debug console at I/O port `port`.
+    #[must_use]
+    pub const fn new(port: u16) -> Self {
+        Self { port }
+    }
+
+    /// Returns the I/O port of the debug console.
+    #[must_use]
+    pub const fn port(&self) -> u16 {
+        self.port
+    }
+
+    /// Returns whether a debug console is present at the port.
+    ///
+    /// QEMU and Bochs return `0xe9` when the port of the debug console is
+    /// read. Other devices at the port may be affected by the read.
+    #[must_use]
+    pub fn is_present(&self) -> bool {
+        let value: u8;
+        unsafe {
+            core::arch::asm!(
+                "inb %dx, %al",
+                in("dx") self.port,
+                out("al") value,
+                options(att_syntax, nomem, nostack, preserves_flags)
+            );
+        }
+        value == 0xe9
+    }
+
+    /// Writes `bytes` to the debug console.
+    pub fn write_bytes(&self, bytes: &[u8]) {
+        for &byte in bytes {
+            unsafe {
+                core::arch::asm!(
+                    "outb %al, %dx",
+                    in("al") byte,
+                    in("dx") self.port,
+                    options(att_syntax, nomem, nostack, preserves_flags)
+                );
+            }
+        }
+    }
+}
+
+impl Default for Debugcon {
+    fn default() -> Self {
+        Self::QEMU
+    }
+}
+
+impl fmt::Write for Debugcon {
+    fn write_str(&mut self, s: &str) -> fmt::Result {
+        self.write_bytes(s.as_bytes());
+        Ok(())
+    }
+}
@@ -0,0 +1,367 @@
+// SPDX-License-Identifier: MIT OR Apache-2.0
+
+//! Drawing with [`embedded-graphics`] on a [`GraphicsOutput`].
+//!
+//! [`GopDisplay`] implements [`DrawTarget`] for the current mode of a
+//! [`GraphicsOutput`], so the primitives, fonts and image decoders of the
+//! `embedded-graphics` ecosystem can be used to draw boot user interfaces.
+//! With the `alloc` feature, [`DrawTarget`] is also implemented for the back
+//! buffer [`Surface`], which is the faster choice for drawing many pixels.
+//!
+//! # Example
+//!
+//! ```no_run
+//! use embedded_graphics_core::pixelcolor::{Rgb888, WebColors};
+//! use embedded_graphics_core::prelude::*;
+//! use embedded_graphics_core::primitives::Rectangle;
+//! use uefi::boot;
+//! use uefi::console::display::GopDisplay;
+//! use uefi::proto::console::gop::GraphicsOutput;
+//!
+//! fn draw() -> uefi::Result {
+//!     let handle = boot::get_handle_for_protocol::<GraphicsOutput>()?;
+//!     let mut gop = boot::open_protocol_exclusive::<GraphicsOutput>(handle)?;
+//!
+//!     let mut display = GopDisplay::new(&mut gop);
+//!     display.clear(Rgb888::CSS_MIDNIGHT_BLUE)?;
+//!     let area = Rectangle::new(Point::new(10, 10), Size::new(100, 50));
+//!     display.fill_solid(&area, Rgb888::CSS_ORANGE)?;
+//!     Ok(())
+//! }
+//! ```
+//!
+//! [`embedded-graphics`]: https://docs.rs/embedded-graphics
+
+#[cfg(feature = "alloc")]
+use crate::proto::console::gop::Surface;
+use crate::proto::console::gop::{
+    BltOp, BltPixel, GraphicsOutput, ModeInfo, PixelBitmask, PixelFormat,
+};
+use crate::{Error, Result};
+#[cfg(feature = "alloc")]
+use core::convert::Infallible;
+use embedded_graphics_core::Pixel;
+use embedded_graphics_core::draw_target::DrawTarget;
+use embedded_graphics_core::geometry::{Dimensions, OriginDimensions, Size};
+use embedded_graphics_core::pixelcolor::{Rgb888, RgbColor};
+use embedded_graphics_core::primitives::Rectangle;
+
+impl From<Rgb888> for BltPixel {
+    fn from(color: Rgb888) -> Self {
+        Self::new(color.r(), color.g(), color.b())
+    }
+}
+
+impl From<BltPixel> for Rgb888 {
+    fn from(pixel: BltPixel) -> Self {
+        Self::new(pixel.red, pixel.green, pixel.blue)
+    }
+}
+
+/// Position and width of a color channel within a pixel.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+struct Channel {
+    shift: u32,
+    bits: u32,
+}
+
+impl Channel {
+    const fn from_mask(mask: u32) -> Self {
+        if mask == 0 {
+            return Self { shift: 0, bits: 0 };
+        }
+        let shift = mask.trailing_zeros();
+        Self {
+            shift,
+            bits: (mask >> shift).count_ones(),
+        }
+    }
+
+    /// Scales an 8-bit channel value to the width of the channel and moves
+    /// it into position.
+    const fn encode(self, value: u8) -> u32 {
+        let value = value as u32;
+        let scaled = if self.bits >= 8 {
+            value << (self.bits - 8)
+        } else {
+            value >> (8 - self.bits)
+        };
+        if self.bits == 0 {
+            0
+        } else {
+            scaled << self.shift
+        }
+    }
+}
+
+/// Converts colors to the pixel format of a frame buffer.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+struct PixelEncoder {
+    red: Channel,
+    green: Channel,
+    blue: Channel,
+    bytes_per_pixel: usize,
+}
+
+impl PixelEncoder {
+    /// Returns the encoder for the pixel format of `info`, or `None` if the
+    /// frame buffer can't be accessed directly.
+    const fn new(info: &ModeInfo) -> Option<Self> {
+        let mask = match info.pixel_format() {
+            // Byte 0 is red in RGB modes, and blue in BGR modes.
+            PixelFormat::Rgb => PixelBitmask {
+                red: 0x0000ff,
+                green: 0x00ff00,
+                blue: 0xff0000,
+                reserved: 0xff000000,
+            },
+            PixelFormat::Bgr => PixelBitmask {
+                red: 0xff0000,
+                green: 0x00ff00,
+                blue: 0x0000ff,
+                reserved: 0xff000000,
+            },
+            PixelFormat::Bitmask => match info.pixel_bitmask() {
+                Some(mask) => mask,
+                None => return None,
+            },
+            PixelFormat::BltOnly => return None,
+        };
+        Some(Self::from_bitmask(mask))
+    }
+
+    const fn from_bitmask(mask: PixelBitmask) -> Self {
+        let all = mask.red | mask.green | mask.blue | mask.reserved;
+        Self {
+            red: Channel::from_mask(mask.red),
+            green: Channel::from_mask(mask.green),
+            blue: Channel::from_mask(mask.blue),
+            bytes_per_pixel: (32 - all.leading_zeros() as usize).div_ceil(8),
+        }
+    }
+
+    /// Returns the little-endian bytes of the encoded color. Only the first
+    /// [`bytes_per_pixel`] bytes are used.
+    ///
+    /// [`bytes_per_pixel`]: Self::bytes_per_pixel
+    fn encode(&self, color: Rgb888) -> [u8; 4] {
+        let value =
+            self.red.encode(color.r()) | self.green.encode(color.g()) | self.blue.encode(color.b());
+        value.to_le_bytes()
+    }
+}
+
+/// An [`embedded-graphics`] [`DrawTarget`] drawing on the current mode of a
+/// [`GraphicsOutput`].
+///
+/// Individual pixels are written directly to the frame buffer, converting
+/// them to its RGB, BGR or bitmask pixel format. Filling rectangles is done
+/// with blits. In [`PixelFormat::BltOnly`] modes, each pixel is drawn with
+/// a separate blit, which is slow.
+///
+/// The display must not be used after the mode has been changed.
+///
+/// [`embedded-graphics`]: https://docs.rs/embedded-graphics
+#[derive(Debug)]
+pub struct GopDisplay<'gop> {
+    gop: &'gop mut GraphicsOutput,
+    info: ModeInfo,
+    encoder: Option<PixelEncoder>,
+}
+
+impl<'gop> GopDisplay<'gop> {
+    /// Creates a display for the current mode of `gop`.
+    #[must_use]
+    pub const fn new(gop: &'gop mut GraphicsOutput) -> Self {
+        let info = gop.current_mode_info();
+        Self {
+            gop,
+            info,
+            encoder: PixelEncoder::new(&info),
+        }
+    }
+
+    /// Returns the information about the mode the display draws on.
+    #[must_use]
+    pub const fn mode_info(&self) -> &ModeInfo {
+        &self.info
+    }
+
+    /// Returns the underlying [`GraphicsOutput`].
+    #[must_use]
+    pub const fn into_inner(self) -> &'gop mut GraphicsOutput {
+        self.gop
+    }
+}
+
+impl OriginDimensions for GopDisplay<'_> {
+    fn size(&self) -> Size {
+        let (width, height) = self.info.resolution();
+        Size::new(width as u32, height as u32)
+    }
+}
+
+impl DrawTarget for GopDisplay<'_> {
+    type Color = Rgb888;
+    type Error = Error;
+
+    fn draw_iter<I>(&mut self, pixels: I) -> Result
+    where
+        I: IntoIterator<Item = Pixel<Self::Color>>,
+    {
+        let bounds = self.bounding_box();
+        let pixels = pixels
+            .into_iter()
+            .filter(|Pixel(point, _)| bounds.contains(*point));
+
+        let Some(encoder) = self.encoder else {
+            for Pixel(point, color) in pixels {
+                self.gop.blt(BltOp::VideoFill {
+                    color: color.into(),
+                    dest: (point.x as usize, point.y as usize),
+                    dims: (1, 1),
+                })?;
+            }
+            return Ok(());
+        };
+
+        let stride = self.info.stride();
+        let bytes_per_pixel = encoder.bytes_per_pixel;
+        let mut frame_buffer = self.gop.frame_buffer();
+        for Pixel(point, color) in pixels {
+            let index = (point.y as usize * stride + point.x as usize) * bytes_per_pixel;
+            if index + bytes_per_pixel > frame_buffer.size() {
+                continue;
+            }
+            let bytes = encoder.encode(color);
+            // SAFETY: the write is within the frame buffer and uses its
+            // pixel format.
+            unsafe {
+                if bytes_per_pixel == 4 {
+                    frame_buffer.write_value(index, bytes);
+                } else {
+                    for (i, byte) in bytes[..bytes_per_pixel].iter().enumerate() {
+                        frame_buffer.write_byte(index + i, *byte);
+                    }
+                }
+            }
+        }
+        Ok(())
+    }
+
+    fn fill_solid(&mut self, area: &Rectangle, color: Self::Color) -> Result {
+        let area = area.intersection(&self.bounding_box());
+        if area.is_zero_sized() {
+            return Ok(());
+        }
+        self.gop.blt(BltOp::VideoFill {
+            color: color.into(),
+            dest: (area.top_left.x as usize, area.top_left.y as usize),
+            dims: (area.size.width as usize, area.size.height as usize),
+        })
+    }
+
+    fn clear(&mut self, color: Self::Color) -> Result {
+        self.fill_solid(&self.bounding_box(), color)
+    }
+}
+
+#[cfg(feature = "alloc")]
+impl OriginDimensions for Surface<'_> {
+    fn size(&self) -> Size {
+        let (width, height) = self.resolution();
+        Size::new(width as u32, height as u32)
+    }
+}
+
+/// Drawing on a back buffer. Changes become visible with [`Surface::flush`].
+#[cfg(feature = "alloc")]
+impl DrawTarget for Surface<'_> {
+    type Color = Rgb888;
+    type Error = Infallible;
+
+    fn draw_iter<I>(&mut self, pixels: I) -> core::result::Result<(), Self::Error>
+    where
+        I: IntoIterator<Item = Pixel<Self::Color>>,
+    {
+        for Pixel(point, color) in pixels {
+            if let (Ok(x), Ok(y)) = (usize::try_from(point.x), usize::try_from(point.y)) {
+                self.set_pixel(x, y, color.into());
+            }
+        }
+        Ok(())
+    }
+
+    fn fill_solid(
+        &mut self,
+        area: &Rectangle,
+        color: Self::Color,
+    ) -> core::result::Result<(), Self::Error> {
+        let area = area.intersection(&self.bounding_box());
+        self.fill_rect(
+            (area.top_left.x as usize, area.top_left.y as usize),
+            (area.size.width as usize, area.size.height as usize),
+            color.into(),
+        );
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const COLOR: Rgb888 = Rgb888::new(0x12, 0x34, 0x56);
+
+    #[test]
+    fn test_rgb_bgr() {
+        let rgb = PixelEncoder::from_bitmask(PixelBitmask {
+            red: 0x0000ff,
+            green: 0x00ff00,
+            blue: 0xff0000,
+            reserved: 0xff000000,
+        });
+        assert_eq!(rgb.bytes_per_pixel, 4);
+        assert_eq!(rgb.encode(COLOR), [0x12, 0x34, 0x56, 0]);
+
+        let bgr = PixelEncoder::from_bitmask(PixelBitmask {
+            red: 0xff0000,
+            green: 0x00ff00,
+            blue: 0x0000ff,
+            reserved: 0,
+        });
+        assert_eq!(bgr.bytes_per_pixel, 3);
+        assert_eq!(bgr.encode(COLOR), [0x56, 0x34, 0x12, 0]);
+    }
+
+    #[test]
+    fn test_bitmask() {
+        // RGB565
+        let encoder = PixelEncoder::from_bitmask(PixelBitmask {
+            red: 0xf800,
+            green: 0x07e0,
+            blue: 0x001f,
+            reserved: 0,
+        });
+        assert_eq!(encoder.bytes_per_pixel, 2);
+        let value = (0x12 >> 3) << 11 | (0x34 >> 2) << 5 | 0x56 >> 3;
+        assert_eq!(encoder.encode(COLOR), u32::to_le_bytes(value));
+
+        // 10 bits per channel
+        let encoder = PixelEncoder::from_bitmask(PixelBitmask {
+            red: 0x3ff00000,
+            green: 0x000ffc00,
+            blue: 0x000003ff,
+            reserved: 0xc0000000,
+        });
+        let value = 0x12 << 22 | 0x34 << 12 | 0x56 << 2;
+        assert_eq!(encoder.encode(COLOR), u32::to_le_bytes(value));
+    }
+
+    #[test]
+    fn test_blt_pixel_conversion() {
+        let pixel = BltPixel::from(COLOR);
+        assert_eq!((pixel.red, pixel.green, pixel.blue), (0x12, 0x34, 0x56));
+        assert_eq!(Rgb888::from(pixel), COLOR);
+    }
+}
@@ -0,0 +1,873 @@
+// SPDX-License-Identifier: MIT OR Apache-2.0
+
+//! Text console rendered onto a frame buffer.
+//!
+//! [`FramebufferConsole`] draws text with the built-in [`font`] onto any
+//! [`Canvas`], which is implemented for [`GraphicsOutput`]. All drawing is
+//! done with blits, so it also works in [`PixelFormat::BltOnly`] modes.
+//!
+//! With the `alloc` feature, [`Canvas`] is also implemented for the back
+//! buffer [`Surface`], which avoids slow reads from video memory when
+//! scrolling. The surface must then be flushed to make the text visible.
+//! Lines that scrolled off the screen can be kept in a scrollback buffer,
+//! see [`FramebufferConsole::with_scrollback`].
+//!
+//! # Example
+//!
+//! ```no_run
+//! use core::fmt::Write;
+//! use uefi::boot;
+//! use uefi::console::fbtext::FramebufferConsole;
+//! use uefi::proto::console::gop::GraphicsOutput;
+//! use uefi::proto::console::text::Color;
+//!
+//! fn hello() -> uefi::Result {
+//!     let handle = boot::get_handle_for_protocol::<GraphicsOutput>()?;
+//!     let mut gop = boot::open_protocol_exclusive::<GraphicsOutput>(handle)?;
+//!
+//!     let mut console = FramebufferConsole::new(&mut *gop)?;
+//!     console.set_color(Color::Yellow, Color::Blue);
+//!     writeln!(console, "Hello from the frame buffer!").unwrap();
+//!     Ok(())
+//! }
+//! ```
+//!
+//! [`PixelFormat::BltOnly`]: crate::proto::console::gop::PixelFormat::BltOnly
+
+use super::font::{self, GLYPH_HEIGHT, GLYPH_WIDTH};
+#[cfg(feature = "alloc")]
+use crate::proto::console::gop::Surface;
+use crate::proto::console::gop::{BltOp, BltPixel, BltRegion, GraphicsOutput};
+use crate::proto::console::text::Color;
+use crate::{Result, Status};
+#[cfg(feature = "alloc")]
+use alloc::{collections::VecDeque, vec, vec::Vec};
+use core::fmt;
+
+/// Width of a tab stop in columns.
+const TAB_WIDTH: usize = 8;
+
+/// First row of a character cell covered by the cursor. The glyphs of the
+/// built-in font leave the last two rows of a cell empty.
+const CURSOR_ROW: usize = GLYPH_HEIGHT - 2;
+
+/// A surface that [`FramebufferConsole`] can draw on.
+///
+/// Coordinates and dimensions are in pixels, as `(x, y)` and
+/// `(width, height)` respectively. Callers keep all rectangles within
+/// [`resolution`].
+///
+/// [`resolution`]: Self::resolution
+pub trait Canvas {
+    /// Returns the `(width, height)` of the canvas.
+    fn resolution(&self) -> (usize, usize);
+
+    /// Fills a rectangle with `color`.
+    fn fill_rect(&mut self, dest: (usize, usize), dims: (usize, usize), color: BltPixel) -> Result;
+
+    /// Copies `pixels`, which are stored row by row without padding, into a
+    /// rectangle.
+    fn write_rect(
+        &mut self,
+        dest: (usize, usize),
+        dims: (usize, usize),
+        pixels: &[BltPixel],
+    ) -> Result;
+
+    /// Copies a rectangle to another position. The rectangles may overlap.
+    fn copy_rect(
+        &mut self,
+        src: (usize, usize),
+        dest: (usize, usize),
+        dims: (usize, usize),
+    ) -> Result;
+
+    /// Moves the content of a rectangle up by `amount` pixels, and fills the
+    /// freed rows at its bottom with `color`.
+    ///
+    /// The content is moved with [`copy_rect`], which is a single
+    /// [`BltOp::VideoToVideo`] on [`GraphicsOutput`].
+    ///
+    /// [`copy_rect`]: Self::copy_rect
+    fn scroll_up(
+        &mut self,
+        dest: (usize, usize),
+        dims: (usize, usize),
+        amount: usize,
+        color: BltPixel,
+    ) -> Result {
+        let amount = amount.min(dims.1);
+        if amount < dims.1 {
+            self.copy_rect((dest.0, dest.1 + amount), dest, (dims.0, dims.1 - amount))?;
+        }
+        self.fill_rect((dest.0, dest.1 + dims.1 - amount), (dims.0, amount), color)
+    }
+}
+
+impl Canvas for GraphicsOutput {
+    fn resolution(&self) -> (usize, usize) {
+        self.current_mode_info().resolution()
+    }
+
+    fn fill_rect(&mut self, dest: (usize, usize), dims: (usize, usize), color: BltPixel) -> Result {
+        self.blt(BltOp::VideoFill { color, dest, dims })
+    }
+
+    fn write_rect(
+        &mut self,
+        dest: (usize, usize),
+        dims: (usize, usize),
+        pixels: &[BltPixel],
+    ) -> Result {
+        self.blt(BltOp::BufferToVideo {
+            buffer: pixels,
+            src: BltRegion::Full,
+            dest,
+            dims,
+        })
+    }
+
+    fn copy_rect(
+        &mut self,
+        src: (usize, usize),
+        dest: (usize, usize),
+        dims: (usize, usize),
+    ) -> Result {
+        self.blt(BltOp::VideoToVideo { src, dest, dims })
+    }
+}
+
+#[cfg(feature = "alloc")]
+impl Canvas for Surface<'_> {
+    fn resolution(&self) -> (usize, usize) {
+        Surface::resolution(self)
+    }
+
+    fn fill_rect(&mut self, dest: (usize, usize), dims: (usize, usize), color: BltPixel) -> Result {
+        Surface::fill_rect(self, dest, dims, color);
+        Ok(())
+    }
+
+    fn write_rect(
+        &mut self,
+        dest: (usize, usize),
+        dims: (usize, usize),
+        pixels: &[BltPixel],
+    ) -> Result {
+        Surface::write_rect(self, dest, dims, pixels);
+        Ok(())
+    }
+
+    fn copy_rect(
+        &mut self,
+        src: (usize, usize),
+        dest: (usize, usize),
+        dims: (usize, usize),
+    ) -> Result {
+        Surface::copy_rect(self, src, dest, dims);
+        Ok(())
+    }
+}
+
+impl<C: Canvas + ?Sized> Canvas for &mut C {
+    fn resolution(&self) -> (usize, usize) {
+        (**self).resolution()
+    }
+
+    fn fill_rect(&mut self, dest: (usize, usize), dims: (usize, usize), color: BltPixel) -> Result {
+        (**self).fill_rect(dest, dims, color)
+    }
+
+    fn write_rect(
+        &mut self,
+        dest: (usize, usize),
+        dims: (usize, usize),
+        pixels: &[BltPixel],
+    ) -> Result {
+        (**self).write_rect(dest, dims, pixels)
+    }
+
+    fn copy_rect(
+        &mut self,
+        src: (usize, usize),
+        dest: (usize, usize),
+        dims: (usize, usize),
+    ) -> Result {
+        (**self).copy_rect(src, dest, dims)
+    }
+
+    fn scroll_up(
+        &mut self,
+        dest: (usize, usize),
+        dims: (usize, usize),
+        amount: usize,
+        color: BltPixel,
+    ) -> Result {
+        (**self).scroll_up(dest, dims, amount, color)
+    }
+}
+
+/// Returns the RGB value of a console color, using the palette of the
+/// EDK II graphics console.
+#[must_use]
+pub const fn color_to_pixel(color: Color) -> BltPixel {
+    let (red, green, blue) = match color {
+        Color::Black => (0x00, 0x00, 0x00),
+        Color::Blue => (0x00, 0x00, 0x98),
+        Color::Green => (0x00, 0x98, 0x00),
+        Color::Cyan => (0x00, 0x98, 0x98),
+        Color::Red => (0x98, 0x00, 0x00),
+        Color::Magenta => (0x98, 0x00, 0x98),
+        Color::Brown => (0x98, 0x98, 0x00),
+        Color::LightGray => (0x98, 0x98, 0x98),
+        Color::DarkGray => (0x30, 0x30, 0x30),
+        Color::LightBlue => (0x00, 0x00, 0xff),
+        Color::LightGreen => (0x00, 0xff, 0x00),
+        Color::LightCyan => (0x00, 0xff, 0xff),
+        Color::LightRed => (0xff, 0x00, 0x00),
+        Color::LightMagenta => (0xff, 0x00, 0xff),
+        Color::Yellow => (0xff, 0xff, 0x00),
+        Color::White => (0xff, 0xff, 0xff),
+    };
+    BltPixel::new(red, green, blue)
+}
+
+/// Text console drawing onto a [`Canvas`] with the built-in 8x16 font.
+///
+/// The console covers as many whole character cells as fit on the canvas,
+/// starting in the top-left corner. Text wraps at the end of a line, and
+/// the console scrolls up when the cursor moves past the last row.
+///
+/// The following control characters are interpreted:
+/// * `\n` moves the cursor to the start of the next line.
+/// * `\r` moves the cursor to the start of the current line.
+/// * `\t` moves the cursor to the next tab stop, which are 8 columns apart.
+/// * `\x08` (backspace) moves the cursor one column to the left.
+///
+/// Other control characters are ignored, and characters not covered by the
+/// font are shown as `?`.
+///
+/// Scrolling moves the pixels on the canvas instead of drawing the glyphs
+/// again, so it costs a single copy of the screen per line.
+#[derive(Debug)]
+pub struct FramebufferConsole<C: Canvas> {
+    canvas: C,
+    columns: usize,
+    rows: usize,
+    cursor: (usize, usize),
+    cursor_visible: bool,
+    foreground: BltPixel,
+    background: BltPixel,
+    /// Number of lines the view is scrolled back into the scrollback
+    /// buffer.
+    view_offset: usize,
+    #[cfg(feature = "alloc")]
+    scrollback: Option<Scrollback>,
+}
+
+/// A character cell of the console.
+#[derive(Clone, Copy, Debug)]
+struct Cell {
+    c: char,
+    foreground: BltPixel,
+    background: BltPixel,
+}
+
+/// The text of the screen and of the lines that scrolled off it.
+#[cfg(feature = "alloc")]
+#[derive(Debug)]
+struct Scrollback {
+    /// Maximum number of lines in `history`.
+    capacity: usize,
+    /// Lines that scrolled off the screen, oldest first.
+    history: VecDeque<Vec<Cell>>,
+    /// Cells of the screen, row by row.
+    screen: Vec<Cell>,
+}
+
+impl<C: Canvas> FramebufferConsole<C> {
+    /// Creates a console on `canvas` and clears it. The text is light gray
+    /// on black, and the cursor is hidden.
+    ///
+    /// # Errors
+    /// * [`Status::UNSUPPORTED`]: the canvas is smaller than a character.
+    /// * Errors of the canvas when clearing it.
+    pub fn new(canvas: C) -> Result<Self> {
+        let (width, height) = canvas.resolution();
+        let columns = width / GLYPH_WIDTH;
+        let rows = height / GLYPH_HEIGHT;
+        if columns == 0 || rows == 0 {
+            return Err(Status::UNSUPPORTED.into());
+        }
+
+        let mut console = Self {
+            canvas,
+            columns,
+            rows,
+            cursor: (0, 0),
+            cursor_visible: false,
+            foreground: color_to_pixel(Color::LightGray),
+            background: color_to_pixel(Color::Black),
+            view_offset: 0,
+            #[cfg(feature = "alloc")]
+            scrollback: None,
+        };
+        console.clear()?;
+        Ok(console)
+    }
+
+    /// Keeps up to `lines` lines that scroll off the top of the screen in
+    /// system memory, so that they can be shown again with
+    /// [`scroll_view`].
+    ///
+    /// Text that is already on the console is not recorded, so this should
+    /// be called before writing to the console.
+    ///
+    /// [`scroll_view`]: Self::scroll_view
+    #[cfg(feature = "alloc")]
+    #[must_use]
+    pub fn with_scrollback(mut self, lines: usize) -> Self {
+        let blank = self.blank_cell();
+        self.scrollback = Some(Scrollback {
+            capacity: lines,
+            history: VecDeque::new(),
+            screen: vec![blank; self.columns * self.rows],
+        });
+        self
+    }
+
+    /// Returns the number of lines in the scrollback buffer.
+    #[cfg(feature = "alloc")]
+    #[must_use]
+    pub fn scrollback_len(&self) -> usize {
+        self.scrollback
+            .as_ref()
+            .map_or(0, |scrollback| scrollback.history.len())
+    }
+
+    /// Returns the number of lines the view is scrolled back.
+    #[must_use]
+    pub const fn view_offset(&self) -> usize {
+        self.view_offset
+    }
+
+    /// Scrolls the view back into the scrollback buffer by `lines` lines,
+    /// or forward if `lines` is negative. The view is limited to the
+    /// buffered lines.
+    ///
+    /// The cursor is hidden while the view is scrolled back. Writing to the
+    /// console scrolls the view forward to the current screen again.
+    #[cfg(feature = "alloc")]
+    pub fn scroll_view(&mut self, lines: isize) -> Result {
+        let old = self.view_offset;
+        let new = old.saturating_add_signed(lines).min(self.scrollback_len());
+        if new == old {
+            return Ok(());
+        }
+        if old == 0 {
+            self.hide_cursor()?;
+        }
+        self.view_offset = new;
+
+        // Move the lines that stay visible, and draw the others.
+        let delta = new.abs_diff(old);
+        let width = self.columns * GLYPH_WIDTH;
+        let redraw = if delta >= self.rows {
+            0..self.rows
+        } else if new > old {
+            let kept = (self.rows - delta) * GLYPH_HEIGHT;
+            self.canvas
+                .copy_rect((0, 0), (0, delta * GLYPH_HEIGHT), (width, kept))?;
+            0..delta
+        } else {
+            let kept = (self.rows - delta) * GLYPH_HEIGHT;
+            self.canvas
+                .copy_rect((0, delta * GLYPH_HEIGHT), (0, 0), (width, kept))?;
+            self.rows - delta..self.rows
+        };
+        for row in redraw {
+            self.draw_view_row(row)?;
+        }
+
+        if new == 0 {
+            self.show_cursor()?;
+        }
+        Ok(())
+    }
+
+    /// Returns the number of columns.
+    #[must_use]
+    pub const fn columns(&self) -> usize {
+        self.columns
+    }
+
+    /// Returns the number of rows.
+    #[must_use]
+    pub const fn rows(&self) -> usize {
+        self.rows
+    }
+
+    /// Returns the column and row of the cursor.
+    #[must_use]
+    pub const fn cursor_position(&self) -> (usize, usize) {
+        self.cursor
+    }
+
+    /// Sets the cursor's position, relative to the top-left corner, which is
+    /// (0, 0).
+    ///
+    /// # Errors
+    /// * [`Status::INVALID_PARAMETER`]: the position is outside the console.
+    pub fn set_cursor_position(&mut self, column: usize, row: usize) -> Result {
+        if column >= self.columns || row >= self.rows {
+            return Err(Status::INVALID_PARAMETER.into());
+        }
+        self.hide_cursor()?;
+        self.cursor = (column, row);
+        self.show_cursor()
+    }
+
+    /// Returns whether the cursor is shown.
+    #[must_use]
+    pub const fn cursor_visible(&self) -> bool {
+        self.cursor_visible
+    }
+
+    /// Shows or hides the cursor, which is drawn as an underline in the
+    /// foreground color.
+    pub fn enable_cursor(&mut self, visible: bool) -> Result {
+        self.hide_cursor()?;
+        self.cursor_visible = visible;
+        self.show_cursor()
+    }
+
+    /// Sets the text and background colors for subsequent output.
+    ///
+    /// Unlike with [`Output::set_color`], all colors can be used as
+    /// background colors.
+    ///
+    /// [`Output::set_color`]: crate::proto::console::text::Output::set_color
+    pub const fn set_color(&mut self, foreground: Color, background: Color) {
+        self.set_rgb_color(color_to_pixel(foreground), color_to_pixel(background));
+    }
+
+    /// Sets arbitrary text and background colors for subsequent output.
+    pub const fn set_rgb_color(&mut self, foreground: BltPixel, background: BltPixel) {
+        self.foreground = foreground;
+        self.background = background;
+    }
+
+    /// Fills the console with the background color and moves the cursor to
+    /// the top-left corner.
+    pub fn clear(&mut self) -> Result {
+        self.view_offset = 0;
+        #[cfg(feature = "alloc")]
+        {
+            let blank = self.blank_cell();
+            if let Some(scrollback) = &mut self.scrollback {
+                scrollback.screen.fill(blank);
+            }
+        }
+        self.canvas.fill_rect(
+            (0, 0),
+            (self.columns * GLYPH_WIDTH, self.rows * GLYPH_HEIGHT),
+            self.background,
+        )?;
+        self.cursor = (0, 0);
+        self.show_cursor()
+    }
+
+    /// Writes a character at the cursor position and advances the cursor.
+    pub fn write_char(&mut self, c: char) -> Result {
+        #[cfg(feature = "alloc")]
+        self.reset_view()?;
+        self.hide_cursor()?;
+        self.put_char(c)?;
+        self.show_cursor()
+    }
+
+    /// Writes a string at the cursor position and advances the cursor.
+    pub fn write_str(&mut self, s: &str) -> Result {
+        #[cfg(feature = "alloc")]
+        self.reset_view()?;
+        self.hide_cursor()?;
+        for c in s.chars() {
+            self.put_char(c)?;
+        }
+        self.show_cursor()
+    }
+
+    /// Returns a reference to the canvas.
+    #[must_use]
+    pub const fn canvas(&self) -> &C {
+        &self.canvas
+    }
+
+    /// Returns the canvas, leaving its content as it is.
+    #[must_use]
+    pub fn into_inner(self) -> C {
+        self.canvas
+    }
+
+    fn put_char(&mut self, c: char) -> Result {
+        let (column, _) = self.cursor;
+        match c {
+            '\n' => self.new_line(),
+            '\r' => {
+                self.cursor.0 = 0;
+                Ok(())
+            }
+            '\t' => {
+                let next = (column / TAB_WIDTH + 1) * TAB_WIDTH;
+                for _ in column..next.min(self.columns) {
+                    self.put_char(' ')?;
+                }
+                Ok(())
+            }
+            '\x08' => {
+                self.cursor.0 = column.saturating_sub(1);
+                Ok(())
+            }
+            c if c.is_control() => Ok(()),
+            c => {
+                self.draw_glyph(c)?;
+                if column + 1 == self.columns {
+                    self.new_line()
+                } else {
+                    self.cursor.0 += 1;
+                    Ok(())
+                }
+            }
+        }
+    }
+
+    fn draw_glyph(&mut self, c: char) -> Result {
+        let cell = Cell {
+            c,
+            foreground: self.foreground,
+            background: self.background,
+        };
+        #[cfg(feature = "alloc")]
+        if let Some(scrollback) = &mut self.scrollback {
+            let (column, row) = self.cursor;
+            scrollback.screen[row * self.columns + column] = cell;
+        }
+        self.draw_cell(self.cursor.0, self.cursor.1, cell)
+    }
+
+    fn draw_cell(&mut self, column: usize, row: usize, cell: Cell) -> Result {
+        let mut pixels = [cell.background; GLYPH_WIDTH * GLYPH_HEIGHT];
+        for (pixel_row, bits) in pixels
+            .chunks_exact_mut(GLYPH_WIDTH)
+            .zip(font::glyph(cell.c))
+        {
+            for (x, pixel) in pixel_row.iter_mut().enumerate() {
+                if bits & (0x80 >> x) != 0 {
+                    *pixel = cell.foreground;
+                }
+            }
+        }
+        self.canvas.write_rect(
+            (column * GLYPH_WIDTH, row * GLYPH_HEIGHT),
+            (GLYPH_WIDTH, GLYPH_HEIGHT),
+            &pixels,
+        )
+    }
+
+    #[cfg(feature = "alloc")]
+    const fn blank_cell(&self) -> Cell {
+        Cell {
+            c: ' ',
+            foreground: self.foreground,
+            background: self.background,
+        }
+    }
+
+    /// Draws row `row` of the view from the scrollback buffer.
+    #[cfg(feature = "alloc")]
+    fn draw_view_row(&mut self, row: usize) -> Result {
+        let Some(scrollback) = &self.scrollback else {
+            return Ok(());
+        };
+        let index = scrollback.history.len() + row - self.view_offset;
+        let cells = match scrollback.history.get(index) {
+            Some(line) => line.clone(),
+            None => {
+                let screen_row = index - scrollback.history.len();
+                scrollback.screen[screen_row * self.columns..][..self.columns].to_vec()
+            }
+        };
+        for (column, cell) in cells.into_iter().enumerate() {
+            self.draw_cell(column, row, cell)?;
+        }
+        Ok(())
+    }
+
+    /// Scrolls the view forward to the current screen.
+    #[cfg(feature = "alloc")]
+    fn reset_view(&mut self) -> Result {
+        if self.view_offset != 0 {
+            return self.scroll_view(-(self.view_offset as isize));
+        }
+        Ok(())
+    }
+
+    fn new_line(&mut self) -> Result {
+        self.cursor.0 = 0;
+        if self.cursor.1 + 1 < self.rows {
+            self.cursor.1 += 1;
+            return Ok(());
+        }
+
+        #[cfg(feature = "alloc")]
+        {
+            let blank = self.blank_cell();
+            if let Some(scrollback) = &mut self.scrollback {
+                let top: Vec<Cell> = scrollback.screen.drain(..self.columns).collect();
+                if scrollback.capacity > 0 {
+                    if scrollback.history.len() == scrollback.capacity {
+                        scrollback.history.pop_front();
+                    }
+                    scrollback.history.push_back(top);
+                }
+                scrollback
+                    .screen
+                    .extend(core::iter::repeat_n(blank, self.columns));
+            }
+        }
+
+        // Scroll everything up by one row and clear the last one.
+        self.canvas.scroll_up(
+            (0, 0),
+            (self.columns * GLYPH_WIDTH, self.rows * GLYPH_HEIGHT),
+            GLYPH_HEIGHT,
+            self.background,
+        )
+    }
+
+    const fn cell_origin(&self) -> (usize, usize) {
+        (self.cursor.0 * GLYPH_WIDTH, self.cursor.1 * GLYPH_HEIGHT)
+    }
+
+    fn draw_cursor(&mut self, color: BltPixel) -> Result {
+        if !self.cursor_visible || self.view_offset != 0 {
+            return Ok(());
+        }
+        let (x, y) = self.cell_origin();
+        self.canvas.fill_rect(
+            (x, y + CURSOR_ROW),
+            (GLYPH_WIDTH, GLYPH_HEIGHT - CURSOR_ROW),
+            color,
+        )
+    }
+
+    fn show_cursor(&mut self) -> Result {
+        self.draw_cursor(self.foreground)
+    }
+
+    fn hide_cursor(&mut self) -> Result {
+        self.draw_cursor(self.background)
+    }
+}
+
+impl<C: Canvas> fmt::Write for FramebufferConsole<C> {
+    fn write_str(&mut self, s: &str) -> fmt::Result {
+        Self::write_str(self, s).map_err(|_| fmt::Error)
+    }
+
+    fn write_char(&mut self, c: char) -> fmt::Result {
+        Self::write_char(self, c).map_err(|_| fmt::Error)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use core::fmt::Write;
+
+    const WIDTH: usize = 4 * GLYPH_WIDTH + 3;
+    const HEIGHT: usize = 2 * GLYPH_HEIGHT + 5;
+
+    /// Canvas storing pixels as `0xRRGGBB`.
+    struct TestCanvas {
+        pixels: [u32; WIDTH * HEIGHT],
+    }
+
+    const fn to_rgb(pixel: BltPixel) -> u32 {
+        ((pixel.red as u32) << 16) | ((pixel.green as u32) << 8) | pixel.blue as u32
+    }
+
+    impl TestCanvas {
+        const fn new() -> Self {
+            Self {
+                pixels: [0x123456; WIDTH * HEIGHT],
+            }
+        }
+
+        const fn pixel(&self, x: usize, y: usize) -> u32 {
+            self.pixels[y * WIDTH + x]
+        }
+
+        /// Returns the rows of the character cell at `(column, row)`, with
+        /// set bits for pixels of color `fg`.
+        fn cell(&self, column: usize, row: usize, fg: u32) -> [u8; GLYPH_HEIGHT] {
+            let mut rows = [0; GLYPH_HEIGHT];
+            for (y, bits) in rows.iter_mut().enumerate() {
+                for x in 0..GLYPH_WIDTH {
+                    let pixel = self.pixel(column * GLYPH_WIDTH + x, row * GLYPH_HEIGHT + y);
+                    if pixel == fg {
+                        *bits |= 0x80 >> x;
+                    }
+                }
+            }
+            rows
+        }
+    }
+
+    impl Canvas for TestCanvas {
+        fn resolution(&self) -> (usize, usize) {
+            (WIDTH, HEIGHT)
+        }
+
+        fn fill_rect(
+            &mut self,
+            dest: (usize, usize),
+            dims: (usize, usize),
+            color: BltPixel,
+        ) -> Result {
+            for y in dest.1..dest.1 + dims.1 {
+                self.pixels[y * WIDTH + dest.0..][..dims.0].fill(to_rgb(color));
+            }
+            Ok(())
+        }
+
+        fn write_rect(
+            &mut self,
+            dest: (usize, usize),
+            dims: (usize, usize),
+            pixels: &[BltPixel],
+        ) -> Result {
+            for (y, row) in pixels.chunks_exact(dims.0).enumerate() {
+                for (x, pixel) in row.iter().enumerate() {
+                    self.pixels[(dest.1 + y) * WIDTH + dest.0 + x] = to_rgb(*pixel);
+                }
+            }
+            Ok(())
+        }
+
+        fn copy_rect(
+            &mut self,
+            src: (usize, usize),
+            dest: (usize, usize),
+            dims: (usize, usize),
+        ) -> Result {
+            let copy_row = |pixels: &mut [u32], y| {
+                let from = (src.1 + y) * WIDTH + src.0;
+                let to = (dest.1 + y) * WIDTH + dest.0;
+                pixels.copy_within(from..from + dims.0, to);
+            };
+            if dest.1 <= src.1 {
+                (0..dims.1).for_each(|y| copy_row(&mut self.pixels, y));
+            } else {
+                (0..dims.1)
+                    .rev()
+                    .for_each(|y| copy_row(&mut self.pixels, y));
+            }
+            Ok(())
+        }
+    }
+
+    const GRAY: u32 = 0x989898;
+    const YELLOW: u32 = 0xffff00;
+
+    #[test]
+    fn test_write() {
+        let mut console = FramebufferConsole::new(TestCanvas::new()).unwrap();
+        assert_eq!((console.columns(), console.rows()), (4, 2));
+        // Pixels outside of the character cells are untouched.
+        assert_eq!(console.canvas().pixel(WIDTH - 1, 0), 0x123456);
+        assert_eq!(console.canvas().pixel(0, HEIGHT - 1), 0x123456);
+        assert_eq!(console.canvas().pixel(0, 0), 0);
+
+        write!(console, "A\tb").unwrap();
+        assert_eq!(console.cursor_position(), (1, 1));
+        assert_eq!(console.canvas().cell(0, 0, GRAY), font::glyph('A'));
+        assert_eq!(console.canvas().cell(3, 0, GRAY), [0; GLYPH_HEIGHT]);
+        assert_eq!(console.canvas().cell(0, 1, GRAY), font::glyph('b'));
+
+        console.set_color(Color::Yellow, Color::Blue);
+        console.write_str("\rx\u{e9}").unwrap();
+        assert_eq!(console.cursor_position(), (2, 1));
+        assert_eq!(console.canvas().cell(0, 1, YELLOW), font::glyph('x'));
+        assert_eq!(console.canvas().cell(1, 1, YELLOW), font::glyph('?'));
+        assert_eq!(console.canvas().pixel(0, GLYPH_HEIGHT), 0x000098);
+    }
+
+    #[test]
+    fn test_scroll() {
+        let mut console = FramebufferConsole::new(TestCanvas::new()).unwrap();
+        console.write_str("1\n2\n3").unwrap();
+        assert_eq!(console.cursor_position(), (1, 1));
+        assert_eq!(console.canvas().cell(0, 0, GRAY), font::glyph('2'));
+        assert_eq!(console.canvas().cell(0, 1, GRAY), font::glyph('3'));
+
+        console.write_str("wrap").unwrap();
+        assert_eq!(console.cursor_position(), (1, 1));
+        assert_eq!(console.canvas().cell(1, 0, GRAY), font::glyph('w'));
+        assert_eq!(console.canvas().cell(3, 0, GRAY), font::glyph('a'));
+        assert_eq!(console.canvas().cell(0, 1, GRAY), font::glyph('p'));
+    }
+
+    #[test]
+    fn test_scrollback() {
+        let mut console = FramebufferConsole::new(TestCanvas::new())
+            .unwrap()
+            .with_scrollback(2);
+        console.enable_cursor(true).unwrap();
+        console.write_str("1\n2\n3\n4").unwrap();
+        assert_eq!(console.scrollback_len(), 2);
+
+        console.scroll_view(1).unwrap();
+        assert_eq!(console.view_offset(), 1);
+        assert_eq!(console.canvas().cell(0, 0, GRAY), font::glyph('2'));
+        assert_eq!(console.canvas().cell(0, 1, GRAY), font::glyph('3'));
+        // The cursor after `4` is hidden.
+        assert_eq!(console.canvas().cell(1, 1, GRAY), [0; GLYPH_HEIGHT]);
+
+        // Scrolling is limited to the two buffered lines.
+        console.scroll_view(5).unwrap();
+        assert_eq!(console.view_offset(), 2);
+        assert_eq!(console.canvas().cell(0, 0, GRAY), font::glyph('1'));
+        assert_eq!(console.canvas().cell(0, 1, GRAY), font::glyph('2'));
+
+        // Writing returns to the screen.
+        console.write_char('x').unwrap();
+        assert_eq!(console.view_offset(), 0);
+        assert_eq!(console.canvas().cell(0, 0, GRAY), font::glyph('3'));
+        assert_eq!(console.canvas().cell(0, 1, GRAY), font::glyph('4'));
+        assert_eq!(console.canvas().cell(1, 1, GRAY), font::glyph('x'));
+        assert_eq!(console.canvas().cell(2, 1, GRAY)[CURSOR_ROW], 0xff);
+    }
+
+    #[test]
+    fn test_cursor() {
+        let mut console = FramebufferConsole::new(TestCanvas::new()).unwrap();
+        console.enable_cursor(true).unwrap();
+        let mut underline = [0; GLYPH_HEIGHT];
+        underline[CURSOR_ROW..].fill(0xff);
+        assert_eq!(console.canvas().cell(0, 0, GRAY), underline);
+
+        console.set_cursor_position(2, 1).unwrap();
+        assert_eq!(console.canvas().cell(0, 0, GRAY), [0; GLYPH_HEIGHT]);
+        assert_eq!(console.canvas().cell(2, 1, GRAY), underline);
+
+        console.write_char('\x08').unwrap();
+        assert_eq!(console.cursor_position(), (1, 1));
+        assert_eq!(console.canvas().cell(2, 1, GRAY), [0; GLYPH_HEIGHT]);
+
+        assert_eq!(
+            console.set_cursor_position(4, 0).unwrap_err().status(),
+            Status::INVALID_PARAMETER
+        );
+    }
+}
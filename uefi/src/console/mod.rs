@@ -0,0 +1,30 @@
+// SPDX-License-Identifier: MIT OR Apache-2.0
+
+//! Console output that doesn't rely on the console protocols of the
+//! firmware.
+//!
+//! Many firmware implementations stop providing a usable
+//! [`Output`] protocol once the video mode has been changed with
+//! [`GraphicsOutput::set_mode`]. The [`fbtext`] module renders text directly
+//! onto the frame buffer instead.
+//!
+//! On machines without video output, the [`serial`] module provides a text
+//! console on a serial port.
+//!
+//! On x86, the `debugcon` module writes to the debug console of QEMU and
+//! Bochs, which needs no setup at all.
+//!
+//! The [`ansi`] module interprets ANSI escape sequences written to the
+//! [`Output`] protocol, for libraries that expect a VT100-style terminal.
+//!
+//! [`GraphicsOutput::set_mode`]: crate::proto::console::gop::GraphicsOutput::set_mode
+//! [`Output`]: crate::proto::console::text::Output
+
+pub mod ansi;
+#[cfg(any(target_arch = "x86", target_arch = "x86_64"))]
+pub mod debugcon;
+#[cfg(feature = "embedded-graphics")]
+pub mod display;
+pub mod fbtext;
+pub mod font;
+pub mod serial;
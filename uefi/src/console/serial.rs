@@ -0,0 +1,376 @@
+// SPDX-License-Identifier: MIT OR Apache-2.0
+
+//! Text console on a serial port, for machines without video output.
+//!
+//! [`SerialConsole`] provides the same interface as the text console
+//! protocols: text is written with [`fmt::Write`] and keys are read with
+//! [`SerialConsole::read_key`]. The escape sequences that terminals send for
+//! special keys are decoded into [`ScanCode`]s. With the `alloc` feature,
+//! [`SerialConsole`] is also a [`tui::Terminal`] that is controlled with
+//! ANSI escape sequences.
+//!
+//! [`tui::Terminal`]: crate::tui::Terminal
+
+use crate::proto::console::serial::{ControlBits, Serial};
+use crate::proto::console::text::{Key, ScanCode};
+use crate::{Char16, Result, ResultExt, Status};
+use core::fmt;
+
+/// Text console on a [`Serial`] device, see the [module documentation].
+///
+/// [module documentation]: self
+#[derive(Debug)]
+pub struct SerialConsole<'a> {
+    serial: &'a mut Serial,
+    size: (usize, usize),
+    pending: Option<u8>,
+}
+
+impl<'a> SerialConsole<'a> {
+    /// Creates a console on `serial`, assuming a terminal of 80x25
+    /// characters.
+    #[must_use]
+    pub const fn new(serial: &'a mut Serial) -> Self {
+        Self {
+            serial,
+            size: (80, 25),
+            pending: None,
+        }
+    }
+
+    /// Sets the baud rate of the serial port, keeping its other settings.
+    pub fn set_baud_rate(&mut self, baud_rate: u64) -> Result {
+        set_baud_rate(self.serial, baud_rate)
+    }
+
+    /// Returns the size of the terminal as `(columns, rows)`.
+    #[must_use]
+    pub const fn size(&self) -> (usize, usize) {
+        self.size
+    }
+
+    /// Sets the size of the terminal, which can't be queried over a serial
+    /// line.
+    pub const fn set_size(&mut self, columns: usize, rows: usize) {
+        self.size = (columns, rows);
+    }
+
+    /// Returns the serial device.
+    pub const fn serial(&mut self) -> &mut Serial {
+        self.serial
+    }
+
+    /// Writes a string, translating `\n` to `\r\n`.
+    pub fn write_str(&mut self, s: &str) -> Result {
+        for (i, line) in s.split('\n').enumerate() {
+            if i > 0 {
+                self.write_bytes(b"\r\n")?;
+            }
+            self.write_bytes(line.as_bytes())?;
+        }
+        Ok(())
+    }
+
+    /// Reads the next key, if any, without waiting.
+    ///
+    /// Enter is reported as `\r`, and both backspace and delete characters
+    /// as `\x08`, like on the text console.
+    pub fn read_key(&mut self) -> Result<Option<Key>> {
+        let Some(byte) = self.read_byte()? else {
+            return Ok(None);
+        };
+        let mut error = None;
+        let key = decode_key(byte, || match self.read_byte() {
+            Ok(byte) => byte,
+            Err(err) => {
+                error = Some(err);
+                None
+            }
+        });
+        if let Some(err) = error {
+            return Err(err);
+        }
+        match key {
+            Decoded::Key(key) => Ok(Some(key)),
+            Decoded::Unread(key, byte) => {
+                self.pending = Some(byte);
+                Ok(Some(key))
+            }
+            Decoded::Unknown => Ok(None),
+        }
+    }
+
+    fn read_byte(&mut self) -> Result<Option<u8>> {
+        if let Some(byte) = self.pending.take() {
+            return Ok(Some(byte));
+        }
+        let bits = self.serial.get_control_bits()?;
+        if bits.contains(ControlBits::INPUT_BUFFER_EMPTY) {
+            return Ok(None);
+        }
+        let mut byte = [0];
+        match self.serial.read(&mut byte) {
+            Ok(()) => Ok(Some(byte[0])),
+            Err(err) if err.status() == Status::TIMEOUT => Ok(None),
+            Err(err) => Err(err.to_err_without_payload()),
+        }
+    }
+
+    pub(crate) fn write_bytes(&mut self, bytes: &[u8]) -> Result {
+        self.serial.write(bytes).discard_errdata()
+    }
+}
+
+impl fmt::Write for SerialConsole<'_> {
+    fn write_str(&mut self, s: &str) -> fmt::Result {
+        Self::write_str(self, s).map_err(|_| fmt::Error)
+    }
+}
+
+/// Sets the baud rate of `serial`, keeping its other settings.
+pub(crate) fn set_baud_rate(serial: &mut Serial, baud_rate: u64) -> Result {
+    let mut mode = *serial.io_mode();
+    mode.baud_rate = baud_rate;
+    serial.set_attributes(&mode)
+}
+
+/// Result of decoding the bytes of a key.
+#[derive(Debug, Eq, PartialEq)]
+enum Decoded {
+    Key(Key),
+    /// A key, followed by a byte that belongs to the next key.
+    Unread(Key, u8),
+    Unknown,
+}
+
+/// Decodes the key starting with `first`, reading further bytes with
+/// `next`, which returns `None` if no byte is available.
+fn decode_key(first: u8, mut next: impl FnMut() -> Option<u8>) -> Decoded {
+    let special = |scan_code| Decoded::Key(Key::Special(scan_code));
+    let printable =
+        |c: char| Char16::try_from(c).map_or(Decoded::Unknown, |c| Decoded::Key(Key::Printable(c)));
+
+    match first {
+        0x1b => {}
+        b'\r' | b'\n' => return printable('\r'),
+        0x08 | 0x7f => return printable('\x08'),
+        byte if byte.is_ascii() => return printable(char::from(byte)),
+        byte => return decode_utf8(byte, next).map_or(Decoded::Unknown, printable),
+    }
+
+    // Escape sequences are sent at once, so a lone escape is the key itself.
+    let Some(intro) = next() else {
+        return special(ScanCode::ESCAPE);
+    };
+    match intro {
+        b'[' | b'O' => {}
+        byte => return Decoded::Unread(Key::Special(ScanCode::ESCAPE), byte),
+    }
+
+    // Collect the first parameter up to the final byte. Modifier
+    // parameters, e.g. in `\x1b[1;5A`, are ignored.
+    let mut param = 0u16;
+    let mut first_param = true;
+    let final_byte = loop {
+        match next() {
+            Some(digit @ b'0'..=b'9') => {
+                if first_param {
+                    param = param
+                        .saturating_mul(10)
+                        .saturating_add(u16::from(digit - b'0'));
+                }
+            }
+            Some(b';') => first_param = false,
+            Some(byte) => break byte,
+            None => return Decoded::Unknown,
+        }
+    };
+
+    let scan_code = match (final_byte, param) {
+        (b'A', _) => ScanCode::UP,
+        (b'B', _) => ScanCode::DOWN,
+        (b'C', _) => ScanCode::RIGHT,
+        (b'D', _) => ScanCode::LEFT,
+        (b'H', _) | (b'~', 1 | 7) => ScanCode::HOME,
+        (b'F', _) | (b'~', 4 | 8) => ScanCode::END,
+        (b'~', 2) => ScanCode::INSERT,
+        (b'~', 3) => ScanCode::DELETE,
+        (b'~', 5) => ScanCode::PAGE_UP,
+        (b'~', 6) => ScanCode::PAGE_DOWN,
+        (b'P', _) => ScanCode::FUNCTION_1,
+        (b'Q', _) => ScanCode::FUNCTION_2,
+        (b'R', _) => ScanCode::FUNCTION_3,
+        (b'S', _) => ScanCode::FUNCTION_4,
+        (b'~', 15) => ScanCode::FUNCTION_5,
+        (b'~', 17) => ScanCode::FUNCTION_6,
+        (b'~', 18) => ScanCode::FUNCTION_7,
+        (b'~', 19) => ScanCode::FUNCTION_8,
+        (b'~', 20) => ScanCode::FUNCTION_9,
+        (b'~', 21) => ScanCode::FUNCTION_10,
+        (b'~', 23) => ScanCode::FUNCTION_11,
+        (b'~', 24) => ScanCode::FUNCTION_12,
+        _ => return Decoded::Unknown,
+    };
+    special(scan_code)
+}
+
+/// Decodes a UTF-8 character starting with `first`.
+fn decode_utf8(first: u8, mut next: impl FnMut() -> Option<u8>) -> Option<char> {
+    let len = match first {
+        0xc0..=0xdf => 2,
+        0xe0..=0xef => 3,
+        0xf0..=0xf7 => 4,
+        _ => return None,
+    };
+    let mut bytes = [first, 0, 0, 0];
+    for byte in &mut bytes[1..len] {
+        *byte = next()?;
+    }
+    core::str::from_utf8(&bytes[..len]).ok()?.chars().next()
+}
+
+#[cfg(feature = "alloc")]
+impl crate::tui::Terminal for SerialConsole<'_> {
+    fn size(&self) -> Result<(usize, usize)> {
+        Ok(self.size)
+    }
+
+    fn clear(&mut self) -> Result {
+        self.write_bytes(b"\x1b[2J\x1b[H")
+    }
+
+    fn move_to(&mut self, column: usize, row: usize) -> Result {
+        let mut buf = Buffer::default();
+        let _ = fmt::Write::write_fmt(&mut buf, format_args!("\x1b[{};{}H", row + 1, column + 1));
+        self.write_bytes(buf.as_bytes())
+    }
+
+    fn set_color(
+        &mut self,
+        foreground: crate::proto::console::text::Color,
+        background: crate::proto::console::text::Color,
+    ) -> Result {
+        let mut buf = Buffer::default();
+        let _ = fmt::Write::write_fmt(
+            &mut buf,
+            format_args!(
+                "\x1b[{};{}m",
+                ansi_color(foreground, 30),
+                ansi_color(background, 40)
+            ),
+        );
+        self.write_bytes(buf.as_bytes())
+    }
+
+    fn write_str(&mut self, s: &str) -> Result {
+        Self::write_str(self, s)
+    }
+
+    fn show_cursor(&mut self, visible: bool) -> Result {
+        self.write_bytes(if visible { b"\x1b[?25h" } else { b"\x1b[?25l" })
+    }
+}
+
+/// Returns the ANSI color code of `color`, with `base` being 30 for
+/// foreground and 40 for background colors.
+#[cfg(feature = "alloc")]
+const fn ansi_color(color: crate::proto::console::text::Color, base: u8) -> u8 {
+    // UEFI colors are ordered blue, green, red; ANSI colors red, green, blue.
+    const ANSI: [u8; 8] = [0, 4, 2, 6, 1, 5, 3, 7];
+    let color = color as u8;
+    let code = base + ANSI[(color & 7) as usize];
+    if color >= 8 { code + 60 } else { code }
+}
+
+/// Small buffer for formatting escape sequences.
+#[cfg(feature = "alloc")]
+#[derive(Default)]
+struct Buffer {
+    data: [u8; 16],
+    len: usize,
+}
+
+#[cfg(feature = "alloc")]
+impl Buffer {
+    fn as_bytes(&self) -> &[u8] {
+        &self.data[..self.len]
+    }
+}
+
+#[cfg(feature = "alloc")]
+impl fmt::Write for Buffer {
+    fn write_str(&mut self, s: &str) -> fmt::Result {
+        let end = self.len + s.len();
+        self.data
+            .get_mut(self.len..end)
+            .ok_or(fmt::Error)?
+            .copy_from_slice(s.as_bytes());
+        self.len = end;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn decode(bytes: &[u8]) -> Decoded {
+        let mut rest = bytes[1..].iter().copied();
+        decode_key(bytes[0], || rest.next())
+    }
+
+    fn printable(c: char) -> Decoded {
+        Decoded::Key(Key::Printable(Char16::try_from(c).unwrap()))
+    }
+
+    #[test]
+    fn test_decode_key() {
+        assert_eq!(decode(b"a"), printable('a'));
+        assert_eq!(decode(b"\n"), printable('\r'));
+        assert_eq!(decode(b"\x7f"), printable('\x08'));
+        assert_eq!(decode("ä".as_bytes()), printable('ä'));
+        assert_eq!(
+            decode(b"\x1b"),
+            Decoded::Key(Key::Special(ScanCode::ESCAPE))
+        );
+        assert_eq!(
+            decode(b"\x1bx"),
+            Decoded::Unread(Key::Special(ScanCode::ESCAPE), b'x')
+        );
+        assert_eq!(decode(b"\x1b[A"), Decoded::Key(Key::Special(ScanCode::UP)));
+        assert_eq!(
+            decode(b"\x1b[1;5D"),
+            Decoded::Key(Key::Special(ScanCode::LEFT))
+        );
+        assert_eq!(
+            decode(b"\x1b[3~"),
+            Decoded::Key(Key::Special(ScanCode::DELETE))
+        );
+        assert_eq!(
+            decode(b"\x1b[3;5~"),
+            Decoded::Key(Key::Special(ScanCode::DELETE))
+        );
+        assert_eq!(
+            decode(b"\x1bOQ"),
+            Decoded::Key(Key::Special(ScanCode::FUNCTION_2))
+        );
+        assert_eq!(
+            decode(b"\x1b[24~"),
+            Decoded::Key(Key::Special(ScanCode::FUNCTION_12))
+        );
+        assert_eq!(decode(b"\x1b[99~"), Decoded::Unknown);
+        assert_eq!(decode(b"\x1b["), Decoded::Unknown);
+    }
+
+    #[cfg(feature = "alloc")]
+    #[test]
+    fn test_ansi_color() {
+        use crate::proto::console::text::Color;
+
+        assert_eq!(ansi_color(Color::Black, 30), 30);
+        assert_eq!(ansi_color(Color::Blue, 40), 44);
+        assert_eq!(ansi_color(Color::Brown, 30), 33);
+        assert_eq!(ansi_color(Color::LightRed, 30), 91);
+        assert_eq!(ansi_color(Color::White, 30), 97);
+    }
+}
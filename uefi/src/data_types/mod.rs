@@ -61,6 +61,14 @@ impl Handle {
     }
 }
 
+impl From<Handle> for NonNull<c_void> {
+    /// Converts the handle to a pointer, e.g. for the functions of
+    /// `std::os::uefi::env`.
+    fn from(handle: Handle) -> Self {
+        handle.0
+    }
+}
+
 /// Handle to an event structure, guaranteed to be non-null.
 ///
 /// If you need to have a nullable event, use `Option<Event>`.
@@ -169,9 +177,13 @@ pub use strs::{
     UnalignedCStr16Error,
 };
 
-/// These functions are used in the implementation of the [`cstr8`] macro.
+/// These functions are used in the implementation of the [`cstr8`] and
+/// [`cstr16`] macros.
 #[doc(hidden)]
-pub use strs::{str_num_latin1_chars, str_to_latin1};
+pub use strs::{
+    str_num_latin1_chars, str_to_latin1, strs_num_latin1_chars, strs_num_ucs2_chars,
+    strs_to_latin1, strs_to_ucs2,
+};
 
 #[cfg(feature = "alloc")]
 mod owned_strs;
@@ -277,6 +277,35 @@ const unsafe fn latin1_from_utf8_at_offset(bytes: &[u8], offset: usize) -> (u8,
     }
 }
 
+/// Get a UCS-2 character from a UTF-8 byte slice at the given offset.
+///
+/// Returns a pair containing the UCS-2 character and the number of bytes in
+/// the UTF-8 encoding of that character.
+///
+/// Panics if the string cannot be encoded in UCS-2.
+///
+/// # Safety
+///
+/// The input `bytes` must be valid UTF-8.
+const unsafe fn ucs2_from_utf8_at_offset(bytes: &[u8], offset: usize) -> (u16, usize) {
+    if bytes[offset] & 0b1000_0000 == 0b0000_0000 {
+        (bytes[offset] as u16, 1)
+    } else if bytes[offset] & 0b1110_0000 == 0b1100_0000 {
+        let a = (bytes[offset] & 0b0001_1111) as u16;
+        let b = (bytes[offset + 1] & 0b0011_1111) as u16;
+        ((a << 6) | b, 2)
+    } else if bytes[offset] & 0b1111_0000 == 0b1110_0000 {
+        let a = (bytes[offset] & 0b0000_1111) as u16;
+        let b = (bytes[offset + 1] & 0b0011_1111) as u16;
+        let c = (bytes[offset + 2] & 0b0011_1111) as u16;
+        ((a << 12) | (b << 6) | c, 3)
+    } else {
+        // UCS-2 only covers the basic multilingual plane, which is encoded
+        // with up to three bytes in UTF-8.
+        panic!("input string cannot be encoded as UCS-2");
+    }
+}
+
 /// Count the number of Latin-1 characters in a string.
 ///
 /// Panics if the string cannot be encoded in Latin-1.
@@ -284,20 +313,32 @@ const unsafe fn latin1_from_utf8_at_offset(bytes: &[u8], offset: usize) -> (u8,
 /// This is public but hidden; it is used in the `cstr8` macro.
 #[must_use]
 pub const fn str_num_latin1_chars(s: &str) -> usize {
-    let bytes = s.as_bytes();
-    let len = bytes.len();
-
-    let mut offset = 0;
-    let mut num_latin1_chars = 0;
+    strs_num_latin1_chars(&[s])
+}
 
-    while offset < len {
-        // SAFETY: `bytes` is valid UTF-8.
-        let (_, num_utf8_bytes) = unsafe { latin1_from_utf8_at_offset(bytes, offset) };
-        offset += num_utf8_bytes;
-        num_latin1_chars += 1;
+/// Count the number of Latin-1 characters in the concatenation of `strs`.
+///
+/// Panics if the strings cannot be encoded in Latin-1.
+///
+/// This is public but hidden; it is used in the `cstr8` macro.
+#[must_use]
+pub const fn strs_num_latin1_chars(strs: &[&str]) -> usize {
+    let mut num_chars = 0;
+
+    let mut i = 0;
+    while i < strs.len() {
+        let bytes = strs[i].as_bytes();
+        let mut offset = 0;
+        while offset < bytes.len() {
+            // SAFETY: `bytes` is valid UTF-8.
+            let (_, num_utf8_bytes) = unsafe { latin1_from_utf8_at_offset(bytes, offset) };
+            offset += num_utf8_bytes;
+            num_chars += 1;
+        }
+        i += 1;
     }
 
-    num_latin1_chars
+    num_chars
 }
 
 /// Convert a `str` into a null-terminated Latin-1 character array.
@@ -307,23 +348,99 @@ pub const fn str_num_latin1_chars(s: &str) -> usize {
 /// This is public but hidden; it is used in the `cstr8` macro.
 #[must_use]
 pub const fn str_to_latin1<const N: usize>(s: &str) -> [u8; N] {
-    let bytes = s.as_bytes();
-    let len = bytes.len();
+    strs_to_latin1(&[s])
+}
 
+/// Convert the concatenation of `strs` into a null-terminated Latin-1
+/// character array.
+///
+/// Panics if the strings cannot be encoded in Latin-1.
+///
+/// This is public but hidden; it is used in the `cstr8` macro.
+#[must_use]
+pub const fn strs_to_latin1<const N: usize>(strs: &[&str]) -> [u8; N] {
     let mut output = [0; N];
 
     let mut output_offset = 0;
-    let mut input_offset = 0;
-    while input_offset < len {
-        // SAFETY: `bytes` is valid UTF-8.
-        let (ch, num_utf8_bytes) = unsafe { latin1_from_utf8_at_offset(bytes, input_offset) };
-        if ch == 0 {
-            panic!("interior null character");
-        } else {
-            output[output_offset] = ch;
-            output_offset += 1;
-            input_offset += num_utf8_bytes;
+    let mut i = 0;
+    while i < strs.len() {
+        let bytes = strs[i].as_bytes();
+        let mut input_offset = 0;
+        while input_offset < bytes.len() {
+            // SAFETY: `bytes` is valid UTF-8.
+            let (ch, num_utf8_bytes) = unsafe { latin1_from_utf8_at_offset(bytes, input_offset) };
+            if ch == 0 {
+                panic!("interior null character");
+            } else {
+                output[output_offset] = ch;
+                output_offset += 1;
+                input_offset += num_utf8_bytes;
+            }
         }
+        i += 1;
+    }
+
+    // The output array must be one bigger than the converted string,
+    // to leave room for the trailing null character.
+    if output_offset + 1 != N {
+        panic!("incorrect array length");
+    }
+
+    output
+}
+
+/// Count the number of UCS-2 characters in the concatenation of `strs`.
+///
+/// Panics if the strings cannot be encoded in UCS-2.
+///
+/// This is public but hidden; it is used in the `cstr16` macro.
+#[must_use]
+pub const fn strs_num_ucs2_chars(strs: &[&str]) -> usize {
+    let mut num_chars = 0;
+
+    let mut i = 0;
+    while i < strs.len() {
+        let bytes = strs[i].as_bytes();
+        let mut offset = 0;
+        while offset < bytes.len() {
+            // SAFETY: `bytes` is valid UTF-8.
+            let (_, num_utf8_bytes) = unsafe { ucs2_from_utf8_at_offset(bytes, offset) };
+            offset += num_utf8_bytes;
+            num_chars += 1;
+        }
+        i += 1;
+    }
+
+    num_chars
+}
+
+/// Convert the concatenation of `strs` into a null-terminated UCS-2
+/// character array.
+///
+/// Panics if the strings cannot be encoded in UCS-2.
+///
+/// This is public but hidden; it is used in the `cstr16` macro.
+#[must_use]
+pub const fn strs_to_ucs2<const N: usize>(strs: &[&str]) -> [u16; N] {
+    let mut output = [0; N];
+
+    let mut output_offset = 0;
+    let mut i = 0;
+    while i < strs.len() {
+        let bytes = strs[i].as_bytes();
+        let mut input_offset = 0;
+        while input_offset < bytes.len() {
+            // SAFETY: `bytes` is valid UTF-8.
+            let (ch, num_utf8_bytes) = unsafe { ucs2_from_utf8_at_offset(bytes, input_offset) };
+            if ch == 0 {
+                panic!("interior null character");
+            } else {
+                output[output_offset] = ch;
+                output_offset += 1;
+                input_offset += num_utf8_bytes;
+            }
+        }
+        i += 1;
     }
 
     // The output array must be one bigger than the converted string,
@@ -829,6 +946,24 @@ mod tests {
         assert_eq!(format!("{s}"), "abc");
     }
 
+    #[test]
+    fn test_cstr8_concat() {
+        const PREFIX: &str = "ab";
+        let s = cstr8!(PREFIX, concat!("c", "ÿ"), "");
+        assert_eq!(s.as_bytes(), [b'a', b'b', b'c', 255, 0]);
+    }
+
+    #[test]
+    fn test_cstr16_concat() {
+        const PREFIX: &str = "aä";
+        let s = cstr16!(PREFIX, concat!("€", 1), "");
+        assert_eq!(s.to_u16_slice_with_nul(), [0x61, 0xe4, 0x20ac, 0x31, 0]);
+        assert_eq!(
+            cstr16!(env!("CARGO_PKG_NAME"), ".efi"),
+            CString16::try_from("uefi.efi").unwrap()
+        );
+    }
+
     #[test]
     fn test_cstr16_display() {
         let s = cstr16!("abc");
@@ -0,0 +1,120 @@
+// SPDX-License-Identifier: MIT OR Apache-2.0
+
+//! Support for resident drivers.
+//!
+//! The firmware unloads an application when its entry point returns. A
+//! driver, i.e. an image with the `EFI_BOOT_SERVICE_DRIVER` or
+//! `EFI_RUNTIME_DRIVER` subsystem, instead stays in memory if its entry
+//! point succeeds, so the protocols it installed, its events and the memory
+//! of the global allocator stay in use. The driver is only unloaded if it
+//! registered an unload function, and [`boot::unload_image`] is called for
+//! it.
+//!
+//! The [`driver_entry`] attribute generates the entry point of such a driver.
+//! It differs from [`entry`] as follows:
+//! * With the `init` option, the helpers are installed with [`init_helpers`],
+//!   which also disables them when boot services are exited.
+//! * If the entry function fails, the firmware unloads the image right away,
+//!   so the helpers are torn down with [`teardown`] first.
+//! * With the `unload = path` option, the function at `path` is registered
+//!   as the unload function of the image with [`set_unload`]. Like the entry
+//!   function, it must have no parameters and return [`Status`] or
+//!   [`Result<()>`]. If it succeeds, the helpers are torn down and the image
+//!   is unloaded. The function must uninstall all protocols and close all
+//!   events of the driver before, since their code and data is freed.
+//!
+//! # Example
+//!
+//! ```no_run
+//! #![no_main]
+//!
+//! use uefi::{Status, driver_entry};
+//!
+//! #[driver_entry(init, unload = unload)]
+//! fn main() -> Status {
+//!     // Install protocols and create events.
+//!     Status::SUCCESS
+//! }
+//!
+//! fn unload() -> uefi::Result {
+//!     // Uninstall the protocols and close the events.
+//!     Ok(())
+//! }
+//! ```
+//!
+//! [`boot::unload_image`]: crate::boot::unload_image
+//! [`driver_entry`]: crate::driver_entry
+//! [`entry`]: crate::entry
+//! [`Result<()>`]: crate::Result
+
+use crate::boot;
+use crate::helpers::{Builder, Guard};
+use crate::proto::loaded_image::LoadedImage;
+use crate::{Handle, Result, Status};
+use core::cell::UnsafeCell;
+use core::sync::atomic::{AtomicBool, Ordering};
+
+/// Guard of the helpers installed by [`init_helpers`].
+struct Helpers {
+    busy: AtomicBool,
+    guard: UnsafeCell<Option<Guard>>,
+}
+
+// SAFETY: access to the guard is serialized with `busy`.
+unsafe impl Sync for Helpers {}
+
+impl Helpers {
+    /// Runs `f` on the guard, unless it is already in use.
+    fn with(&self, f: impl FnOnce(&mut Option<Guard>)) {
+        if self.busy.swap(true, Ordering::Acquire) {
+            return;
+        }
+        f(unsafe { &mut *self.guard.get() });
+        self.busy.store(false, Ordering::Release);
+    }
+}
+
+static HELPERS: Helpers = Helpers {
+    busy: AtomicBool::new(false),
+    guard: UnsafeCell::new(None),
+};
+
+/// Installs the default helpers, like [`helpers::init`], and disables them
+/// when boot services are exited. They stay installed until [`teardown`] is
+/// called.
+///
+/// # Errors
+///
+/// See [`Builder::init`].
+///
+/// [`helpers::init`]: crate::helpers::init
+pub fn init_helpers() -> Result {
+    let guard = Builder::new().hook_exit_boot_services(true).init()?;
+    HELPERS.with(|helpers| *helpers = Some(guard));
+    Ok(())
+}
+
+/// Tears down the helpers installed by [`init_helpers`]. This must be done
+/// before the image is unloaded, since the logger and the event for exiting
+/// boot services refer to its memory.
+pub fn teardown() {
+    HELPERS.with(|helpers| drop(helpers.take()));
+}
+
+/// Registers `unload` as the unload function of the running image, which is
+/// called by [`boot::unload_image`].
+///
+/// # Errors
+///
+/// Errors of opening the [`LoadedImage`] protocol.
+///
+/// # Safety
+///
+/// `unload` must be a function of the running image.
+///
+/// [`boot::unload_image`]: crate::boot::unload_image
+pub unsafe fn set_unload(unload: extern "efiapi" fn(image_handle: Handle) -> Status) -> Result {
+    let mut loaded_image = boot::open_protocol_exclusive::<LoadedImage>(boot::image_handle())?;
+    unsafe { loaded_image.set_unload(unload) };
+    Ok(())
+}
@@ -0,0 +1,1034 @@
+// SPDX-License-Identifier: MIT OR Apache-2.0
+
+//! Parsing of firmware volumes and the Firmware File System.
+//!
+//! Firmware images are made of firmware volumes, which contain the files
+//! of the firmware, e.g. drivers and applications, in the Firmware File
+//! System (FFS). The files in turn consist of sections, some of which
+//! encapsulate other sections, e.g. compressed or signed ones.
+//!
+//! [`FirmwareVolume`] parses a volume from a byte slice, e.g. a
+//! memory-mapped volume or a dump of the flash, and iterates over its
+//! [`File`]s and their [`Section`]s. Both the FFS2 and the FFS3 format are
+//! supported. With the `alloc` feature, [`walk_sections`] descends into
+//! encapsulation sections, and a [`GuidedSectionHandler`] can decode the
+//! data of GUID-defined sections, e.g. to decompress them.
+//!
+//! The formats are defined in volume 3 of the [Platform Initialization
+//! Specification][spec].
+//!
+//! [spec]: https://uefi.org/specifications
+
+use crate::data_types::UnalignedSlice;
+use crate::{Char16, Guid, guid};
+use core::fmt::{self, Display, Formatter};
+use uefi_raw::firmware_storage::{FirmwareVolumeAttributes, FirmwareVolumeHeader};
+
+#[cfg(feature = "alloc")]
+use alloc::vec::Vec;
+
+/// Size of the fixed part of the volume header, without the block map.
+const VOLUME_HEADER_SIZE: usize = 56;
+/// Size of the header of a file.
+const FILE_HEADER_SIZE: usize = 24;
+/// Size of the header of a file with the [`FileAttributes::LARGE_FILE`]
+/// attribute.
+const LARGE_FILE_HEADER_SIZE: usize = 32;
+/// Size of the header of a section.
+const SECTION_HEADER_SIZE: usize = 4;
+/// Size of the header of a section whose size doesn't fit into 24 bits.
+const LARGE_SECTION_HEADER_SIZE: usize = 8;
+/// Size field of a large section.
+const LARGE_SECTION_SIZE: usize = 0xff_ffff;
+/// Value of the checksum of the file data if the file has no
+/// [`FileAttributes::CHECKSUM`] attribute.
+const FILE_CHECKSUM_NONE: u8 = 0xaa;
+
+newtype_enum! {
+    /// Type of a [`File`] (`EFI_FV_FILETYPE_*`).
+    pub enum FileType: u8 => {
+        /// Raw data without sections.
+        RAW = 0x01,
+        /// Sections of any kind.
+        FREEFORM = 0x02,
+        /// The SEC core.
+        SECURITY_CORE = 0x03,
+        /// The PEI core.
+        PEI_CORE = 0x04,
+        /// The DXE core.
+        DXE_CORE = 0x05,
+        /// A PEI module.
+        PEIM = 0x06,
+        /// A DXE driver.
+        DRIVER = 0x07,
+        /// A PEI module that is also a DXE driver.
+        COMBINED_PEIM_DRIVER = 0x08,
+        /// A UEFI application.
+        APPLICATION = 0x09,
+        /// An MM driver.
+        MM = 0x0a,
+        /// A nested firmware volume.
+        FIRMWARE_VOLUME_IMAGE = 0x0b,
+        /// An MM driver that is also a DXE driver.
+        COMBINED_MM_DXE = 0x0c,
+        /// The MM core.
+        MM_CORE = 0x0d,
+        /// A standalone MM driver.
+        MM_STANDALONE = 0x0e,
+        /// The standalone MM core.
+        MM_CORE_STANDALONE = 0x0f,
+        /// Padding between files.
+        FFS_PAD = 0xf0,
+    }
+}
+
+impl FileType {
+    /// Returns whether the data of files of this type consists of sections.
+    #[must_use]
+    pub fn has_sections(self) -> bool {
+        self != Self::RAW && self != Self::FFS_PAD
+    }
+}
+
+bitflags::bitflags! {
+    /// Attributes of a [`File`] (`FFS_ATTRIB_*`).
+    #[derive(Clone, Copy, Debug, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+    #[repr(transparent)]
+    pub struct FileAttributes: u8 {
+        /// The file uses the extended size field of FFS3.
+        const LARGE_FILE = 0x01;
+        /// Extension of the data alignment to 128 KiB and more.
+        const DATA_ALIGNMENT_2 = 0x02;
+        /// The file must not be moved.
+        const FIXED = 0x04;
+        /// Alignment of the data of the file.
+        const DATA_ALIGNMENT = 0x38;
+        /// The data of the file is covered by a checksum.
+        const CHECKSUM = 0x40;
+    }
+}
+
+newtype_enum! {
+    /// State of a [`File`], the highest state bit that is set
+    /// (`EFI_FILE_*`).
+    pub enum FileState: u8 => {
+        /// The header is being written.
+        HEADER_CONSTRUCTION = 0x01,
+        /// The header is valid, the data is being written.
+        HEADER_VALID = 0x02,
+        /// The file is valid.
+        DATA_VALID = 0x04,
+        /// The file is valid, but is being replaced by a new version.
+        MARKED_FOR_UPDATE = 0x08,
+        /// The file has been deleted.
+        DELETED = 0x10,
+        /// The header is invalid.
+        HEADER_INVALID = 0x20,
+    }
+}
+
+newtype_enum! {
+    /// Type of a [`Section`] (`EFI_SECTION_*`).
+    pub enum SectionType: u8 => {
+        /// Encapsulation of compressed sections.
+        COMPRESSION = 0x01,
+        /// Encapsulation of sections that are processed as defined by a
+        /// GUID, see [`GuidDefinedSection`].
+        GUID_DEFINED = 0x02,
+        /// Encapsulation of sections that are not needed after loading.
+        DISPOSABLE = 0x03,
+        /// A PE32+ image.
+        PE32 = 0x10,
+        /// A position-independent image.
+        PIC = 0x11,
+        /// A Terse Executable image.
+        TE = 0x12,
+        /// Dependency expression of a DXE driver.
+        DXE_DEPEX = 0x13,
+        /// Version of the file.
+        VERSION = 0x14,
+        /// Name of the file.
+        USER_INTERFACE = 0x15,
+        /// 16-bit code.
+        COMPATIBILITY16 = 0x16,
+        /// A nested firmware volume.
+        FIRMWARE_VOLUME_IMAGE = 0x17,
+        /// Data with a GUID that defines its format.
+        FREEFORM_SUBTYPE_GUID = 0x18,
+        /// Raw data.
+        RAW = 0x19,
+        /// Dependency expression of a PEI module.
+        PEI_DEPEX = 0x1b,
+        /// Dependency expression of an MM driver.
+        MM_DEPEX = 0x1c,
+    }
+}
+
+bitflags::bitflags! {
+    /// Attributes of a [`GuidDefinedSection`] (`EFI_GUIDED_SECTION_*`).
+    #[derive(Clone, Copy, Debug, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+    #[repr(transparent)]
+    pub struct GuidedSectionAttributes: u16 {
+        /// The data must be processed, e.g. decompressed, to get the
+        /// encapsulated sections.
+        const PROCESSING_REQUIRED = 0x01;
+        /// The data is authenticated, e.g. signed.
+        const AUTH_STATUS_VALID = 0x02;
+    }
+}
+
+/// Error of parsing a firmware volume, a file or a section.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ParseError {
+    /// The volume header has no `_FVH` signature.
+    NotFirmwareVolume,
+    /// The file system of the volume is neither FFS2 nor FFS3.
+    UnsupportedFileSystem(Guid),
+    /// The checksum of the volume header is invalid.
+    InvalidChecksum,
+    /// A header, a file or a section extends beyond its container, or is
+    /// smaller than its header.
+    OutOfBounds,
+    /// Sections are nested more than [`MAX_SECTION_DEPTH`] levels deep.
+    TooDeep,
+}
+
+impl Display for ParseError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::NotFirmwareVolume => write!(f, "not a firmware volume"),
+            Self::UnsupportedFileSystem(guid) => {
+                write!(f, "unsupported file system {guid}")
+            }
+            Self::InvalidChecksum => write!(f, "invalid volume header checksum"),
+            Self::OutOfBounds => write!(f, "firmware volume is truncated"),
+            Self::TooDeep => write!(f, "sections are nested too deeply"),
+        }
+    }
+}
+
+impl core::error::Error for ParseError {}
+
+/// Parsed firmware volume.
+#[derive(Clone, Copy, Debug)]
+pub struct FirmwareVolume<'a> {
+    data: &'a [u8],
+    file_system: Guid,
+    attributes: FirmwareVolumeAttributes,
+    name: Option<Guid>,
+    files: usize,
+}
+
+impl<'a> FirmwareVolume<'a> {
+    /// File system GUID of FFS2 (`EFI_FIRMWARE_FILE_SYSTEM2_GUID`).
+    pub const FFS2_GUID: Guid = guid!("8c8ce578-8a3d-4f1c-9935-896185c32dd3");
+    /// File system GUID of FFS3 (`EFI_FIRMWARE_FILE_SYSTEM3_GUID`).
+    pub const FFS3_GUID: Guid = guid!("5473c07a-3dcb-4dca-bd6f-1e9689e7349a");
+
+    /// Parses the header of the firmware volume at the start of `data`.
+    /// Data beyond the length of the volume is ignored.
+    ///
+    /// # Errors
+    ///
+    /// See [`ParseError`].
+    pub fn parse(data: &'a [u8]) -> Result<Self, ParseError> {
+        if data.get(40..44) != Some(&FirmwareVolumeHeader::SIGNATURE[..]) {
+            return Err(ParseError::NotFirmwareVolume);
+        }
+        let file_system = guid_at(data, 16).ok_or(ParseError::OutOfBounds)?;
+        if file_system != Self::FFS2_GUID && file_system != Self::FFS3_GUID {
+            return Err(ParseError::UnsupportedFileSystem(file_system));
+        }
+        let length = u64_at(data, 32).ok_or(ParseError::OutOfBounds)?;
+        let data = usize::try_from(length)
+            .ok()
+            .and_then(|length| data.get(..length))
+            .ok_or(ParseError::OutOfBounds)?;
+        let attributes = FirmwareVolumeAttributes::from_bits_retain(
+            u32_at(data, 44).ok_or(ParseError::OutOfBounds)?,
+        );
+        let header_length = usize::from(u16_at(data, 48).ok_or(ParseError::OutOfBounds)?);
+        let header = data
+            .get(..header_length)
+            .filter(|header| header.len() >= VOLUME_HEADER_SIZE)
+            .ok_or(ParseError::OutOfBounds)?;
+        let checksum = header.chunks_exact(2).fold(0u16, |sum, b| {
+            sum.wrapping_add(u16::from_le_bytes([b[0], b[1]]))
+        });
+        if checksum != 0 {
+            return Err(ParseError::InvalidChecksum);
+        }
+
+        // The files start after the extended header, if any.
+        let ext_header_offset = usize::from(u16_at(data, 52).ok_or(ParseError::OutOfBounds)?);
+        let (name, files) = if ext_header_offset == 0 {
+            (None, header_length)
+        } else {
+            let name = guid_at(data, ext_header_offset).ok_or(ParseError::OutOfBounds)?;
+            let size = u32_at(data, ext_header_offset + 16).ok_or(ParseError::OutOfBounds)?;
+            let end = usize::try_from(size)
+                .ok()
+                .and_then(|size| ext_header_offset.checked_add(size))
+                .filter(|&end| end <= data.len())
+                .ok_or(ParseError::OutOfBounds)?;
+            (Some(name), end)
+        };
+
+        Ok(Self {
+            data,
+            file_system,
+            attributes,
+            name,
+            files,
+        })
+    }
+
+    /// Bytes of the volume, including the header.
+    #[must_use]
+    pub const fn data(&self) -> &'a [u8] {
+        self.data
+    }
+
+    /// File system of the volume, [`FFS2_GUID`] or [`FFS3_GUID`].
+    ///
+    /// [`FFS2_GUID`]: Self::FFS2_GUID
+    /// [`FFS3_GUID`]: Self::FFS3_GUID
+    #[must_use]
+    pub const fn file_system(&self) -> Guid {
+        self.file_system
+    }
+
+    /// Attributes of the volume.
+    #[must_use]
+    pub const fn attributes(&self) -> FirmwareVolumeAttributes {
+        self.attributes
+    }
+
+    /// Name of the volume from its extended header, if it has one.
+    #[must_use]
+    pub const fn name(&self) -> Option<Guid> {
+        self.name
+    }
+
+    /// Returns an iterator over the files of the volume. The iterator ends
+    /// at the free space of the volume, and after an error.
+    #[must_use]
+    pub const fn files(&self) -> Files<'a> {
+        Files {
+            data: self.data,
+            offset: self.files,
+            erase_polarity: self
+                .attributes
+                .contains(FirmwareVolumeAttributes::ERASE_POLARITY),
+        }
+    }
+
+    /// Returns the valid file named `name`, if any.
+    #[must_use]
+    pub fn find_file(&self, name: &Guid) -> Option<File<'a>> {
+        self.files()
+            .map_while(Result::ok)
+            .find(|file| file.name() == *name && file.is_valid())
+    }
+}
+
+/// Iterator over the files of a [`FirmwareVolume`].
+#[derive(Clone, Debug)]
+pub struct Files<'a> {
+    data: &'a [u8],
+    offset: usize,
+    erase_polarity: bool,
+}
+
+impl<'a> Files<'a> {
+    fn parse_next(&mut self) -> Result<Option<File<'a>>, ParseError> {
+        // Files are aligned to 8 bytes.
+        let offset = self.offset.next_multiple_of(8);
+        let Some(header) = self.data.get(offset..offset + FILE_HEADER_SIZE) else {
+            return Ok(None);
+        };
+        let erased = if self.erase_polarity { 0xff } else { 0 };
+        if header.iter().all(|&b| b == erased) {
+            return Ok(None);
+        }
+
+        let attributes = FileAttributes::from_bits_retain(header[19]);
+        let (header_size, size) = if attributes.contains(FileAttributes::LARGE_FILE) {
+            let size =
+                u64_at(self.data, offset + FILE_HEADER_SIZE).ok_or(ParseError::OutOfBounds)?;
+            (
+                LARGE_FILE_HEADER_SIZE,
+                usize::try_from(size).map_err(|_| ParseError::OutOfBounds)?,
+            )
+        } else {
+            (FILE_HEADER_SIZE, u24_at(header, 20))
+        };
+        let bytes = offset
+            .checked_add(size)
+            .and_then(|end| self.data.get(offset..end))
+            .filter(|bytes| bytes.len() >= header_size)
+            .ok_or(ParseError::OutOfBounds)?;
+        self.offset = offset + size;
+
+        let mut state = header[23];
+        if self.erase_polarity {
+            state = !state;
+        }
+        Ok(Some(File {
+            bytes,
+            header_size,
+            name: guid_at(header, 0).ok_or(ParseError::OutOfBounds)?,
+            file_type: FileType(header[18]),
+            attributes,
+            state,
+        }))
+    }
+}
+
+impl<'a> Iterator for Files<'a> {
+    type Item = Result<File<'a>, ParseError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let result = self.parse_next().transpose();
+        if matches!(result, Some(Err(_))) {
+            self.offset = self.data.len();
+        }
+        result
+    }
+}
+
+/// File of a [`FirmwareVolume`].
+#[derive(Clone, Copy, Debug)]
+pub struct File<'a> {
+    bytes: &'a [u8],
+    header_size: usize,
+    name: Guid,
+    file_type: FileType,
+    attributes: FileAttributes,
+    state: u8,
+}
+
+impl<'a> File<'a> {
+    /// Name of the file.
+    #[must_use]
+    pub const fn name(&self) -> Guid {
+        self.name
+    }
+
+    /// Type of the file.
+    #[must_use]
+    pub const fn file_type(&self) -> FileType {
+        self.file_type
+    }
+
+    /// Attributes of the file.
+    #[must_use]
+    pub const fn attributes(&self) -> FileAttributes {
+        self.attributes
+    }
+
+    /// State of the file. The erase polarity of the volume has already been
+    /// applied.
+    #[must_use]
+    pub const fn state(&self) -> FileState {
+        let bit = match self.state.checked_ilog2() {
+            Some(bit) => bit,
+            None => return FileState(0),
+        };
+        FileState(1 << bit)
+    }
+
+    /// Returns whether the file is valid, i.e. its state is
+    /// [`FileState::DATA_VALID`] or [`FileState::MARKED_FOR_UPDATE`].
+    #[must_use]
+    pub const fn is_valid(&self) -> bool {
+        matches!(
+            self.state(),
+            FileState::DATA_VALID | FileState::MARKED_FOR_UPDATE
+        )
+    }
+
+    /// Returns whether the checksums of the header and, if the file has the
+    /// [`FileAttributes::CHECKSUM`] attribute, of the data are valid.
+    #[must_use]
+    pub fn is_checksum_valid(&self) -> bool {
+        // The header checksum covers the header without the data checksum
+        // and the state.
+        let header = self.bytes[..self.header_size]
+            .iter()
+            .enumerate()
+            .filter(|&(i, _)| i != 17 && i != 23)
+            .fold(0u8, |sum, (_, &b)| sum.wrapping_add(b));
+        let data_checksum = self.bytes[17];
+        let data = if self.attributes.contains(FileAttributes::CHECKSUM) {
+            self.data()
+                .iter()
+                .fold(data_checksum, |sum, &b| sum.wrapping_add(b))
+                == 0
+        } else {
+            data_checksum == FILE_CHECKSUM_NONE
+        };
+        header == 0 && data
+    }
+
+    /// Bytes of the file, including the header.
+    #[must_use]
+    pub const fn bytes(&self) -> &'a [u8] {
+        self.bytes
+    }
+
+    /// Data of the file, after the header.
+    #[must_use]
+    pub fn data(&self) -> &'a [u8] {
+        &self.bytes[self.header_size..]
+    }
+
+    /// Returns an iterator over the sections of the file, or `None` if the
+    /// file type has no sections, see [`FileType::has_sections`].
+    #[must_use]
+    pub fn sections(&self) -> Option<Sections<'a>> {
+        self.file_type
+            .has_sections()
+            .then(|| Sections::new(self.data()))
+    }
+}
+
+/// Iterator over [`Section`]s, e.g. the sections of a [`File`].
+#[derive(Clone, Debug)]
+pub struct Sections<'a> {
+    data: &'a [u8],
+    offset: usize,
+}
+
+impl<'a> Sections<'a> {
+    /// Creates an iterator over the sections in `data`, e.g. the
+    /// decoded data of a [`GuidDefinedSection`].
+    #[must_use]
+    pub const fn new(data: &'a [u8]) -> Self {
+        Self { data, offset: 0 }
+    }
+
+    fn parse_next(&mut self) -> Result<Option<Section<'a>>, ParseError> {
+        // Sections are aligned to 4 bytes.
+        let offset = self.offset.next_multiple_of(4);
+        let Some(header) = self.data.get(offset..offset + SECTION_HEADER_SIZE) else {
+            return Ok(None);
+        };
+        let (header_size, size) = match u24_at(header, 0) {
+            LARGE_SECTION_SIZE => {
+                let size = u32_at(self.data, offset + SECTION_HEADER_SIZE)
+                    .ok_or(ParseError::OutOfBounds)?;
+                (
+                    LARGE_SECTION_HEADER_SIZE,
+                    usize::try_from(size).map_err(|_| ParseError::OutOfBounds)?,
+                )
+            }
+            size => (SECTION_HEADER_SIZE, size),
+        };
+        let bytes = offset
+            .checked_add(size)
+            .and_then(|end| self.data.get(offset..end))
+            .filter(|bytes| bytes.len() >= header_size)
+            .ok_or(ParseError::OutOfBounds)?;
+        self.offset = offset + size;
+        Ok(Some(Section {
+            bytes,
+            header_size,
+            section_type: SectionType(header[3]),
+        }))
+    }
+}
+
+impl<'a> Iterator for Sections<'a> {
+    type Item = Result<Section<'a>, ParseError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let result = self.parse_next().transpose();
+        if matches!(result, Some(Err(_))) {
+            self.offset = self.data.len();
+        }
+        result
+    }
+}
+
+/// Section of a [`File`].
+#[derive(Clone, Copy, Debug)]
+pub struct Section<'a> {
+    bytes: &'a [u8],
+    header_size: usize,
+    section_type: SectionType,
+}
+
+impl<'a> Section<'a> {
+    /// Type of the section.
+    #[must_use]
+    pub const fn section_type(&self) -> SectionType {
+        self.section_type
+    }
+
+    /// Bytes of the section, including the header.
+    #[must_use]
+    pub const fn bytes(&self) -> &'a [u8] {
+        self.bytes
+    }
+
+    /// Data of the section, after the common section header.
+    #[must_use]
+    pub fn data(&self) -> &'a [u8] {
+        &self.bytes[self.header_size..]
+    }
+
+    /// Returns the section as a GUID-defined section, if it is one.
+    ///
+    /// # Errors
+    ///
+    /// * [`ParseError::OutOfBounds`]: the header of the section is
+    ///   truncated.
+    pub fn guid_defined(&self) -> Result<Option<GuidDefinedSection<'a>>, ParseError> {
+        if self.section_type != SectionType::GUID_DEFINED {
+            return Ok(None);
+        }
+        let data = self.data();
+        let definition = guid_at(data, 0).ok_or(ParseError::OutOfBounds)?;
+        let data_offset = usize::from(u16_at(data, 16).ok_or(ParseError::OutOfBounds)?);
+        let attributes = GuidedSectionAttributes::from_bits_retain(
+            u16_at(data, 18).ok_or(ParseError::OutOfBounds)?,
+        );
+        // The data offset is relative to the start of the section.
+        let payload = self
+            .bytes
+            .get(data_offset..)
+            .filter(|_| data_offset >= self.header_size + 20)
+            .ok_or(ParseError::OutOfBounds)?;
+        Ok(Some(GuidDefinedSection {
+            section: *self,
+            definition,
+            attributes,
+            data: payload,
+        }))
+    }
+
+    /// Returns an iterator over the encapsulated sections, if the section
+    /// is an encapsulation section whose data can be used as is: a
+    /// [`SectionType::DISPOSABLE`] section, an uncompressed
+    /// [`SectionType::COMPRESSION`] section, or a
+    /// [`SectionType::GUID_DEFINED`] section that doesn't require
+    /// processing.
+    #[must_use]
+    pub fn sections(&self) -> Option<Sections<'a>> {
+        match self.section_type {
+            SectionType::DISPOSABLE => Some(Sections::new(self.data())),
+            SectionType::COMPRESSION => {
+                // The uncompressed length is followed by the compression
+                // type, where 0 means not compressed.
+                let data = self.data();
+                (*data.get(4)? == 0).then(|| Sections::new(&data[5..]))
+            }
+            SectionType::GUID_DEFINED => {
+                let section = self.guid_defined().ok()??;
+                (!section.requires_processing()).then(|| Sections::new(section.data()))
+            }
+            _ => None,
+        }
+    }
+
+    /// Returns the name of the file of a [`SectionType::USER_INTERFACE`]
+    /// section, without null terminator.
+    #[must_use]
+    pub fn user_interface(&self) -> Option<UnalignedSlice<'a, Char16>> {
+        (self.section_type == SectionType::USER_INTERFACE).then(|| ucs2_string(self.data()))
+    }
+
+    /// Returns the build number and the version string, without null
+    /// terminator, of a [`SectionType::VERSION`] section.
+    #[must_use]
+    pub fn version(&self) -> Option<(u16, UnalignedSlice<'a, Char16>)> {
+        if self.section_type != SectionType::VERSION {
+            return None;
+        }
+        let data = self.data();
+        Some((u16_at(data, 0)?, ucs2_string(&data[2..])))
+    }
+}
+
+/// Section of type [`SectionType::GUID_DEFINED`], whose data is
+/// processed as defined by a GUID, e.g. compressed with LZMA or signed.
+#[derive(Clone, Copy, Debug)]
+pub struct GuidDefinedSection<'a> {
+    section: Section<'a>,
+    definition: Guid,
+    attributes: GuidedSectionAttributes,
+    data: &'a [u8],
+}
+
+impl<'a> GuidDefinedSection<'a> {
+    /// GUID that defines the processing of the data.
+    #[must_use]
+    pub const fn definition(&self) -> Guid {
+        self.definition
+    }
+
+    /// Attributes of the section.
+    #[must_use]
+    pub const fn attributes(&self) -> GuidedSectionAttributes {
+        self.attributes
+    }
+
+    /// Returns whether the data must be processed to get the encapsulated
+    /// sections.
+    #[must_use]
+    pub const fn requires_processing(&self) -> bool {
+        self.attributes
+            .contains(GuidedSectionAttributes::PROCESSING_REQUIRED)
+    }
+
+    /// The section itself, including its headers.
+    #[must_use]
+    pub const fn section(&self) -> Section<'a> {
+        self.section
+    }
+
+    /// Data of the section, after the GUID-specific header.
+    #[must_use]
+    pub const fn data(&self) -> &'a [u8] {
+        self.data
+    }
+}
+
+/// Decoder of [`GuidDefinedSection`]s that require processing, used by
+/// [`walk_sections`].
+#[cfg(feature = "alloc")]
+pub trait GuidedSectionHandler {
+    /// Processes the data of `section`, and returns the encapsulated
+    /// sections. Returns `None` if the [`definition`] of the section is
+    /// not supported, or the data can't be processed.
+    ///
+    /// [`definition`]: GuidDefinedSection::definition
+    fn decode(&mut self, section: &GuidDefinedSection<'_>) -> Option<Vec<u8>>;
+}
+
+/// Calls `visit` with each of `sections` and, recursively, with the
+/// sections they encapsulate.
+///
+/// Encapsulation sections that don't need processing are descended into
+/// directly, see [`Section::sections`]. GUID-defined sections that need
+/// processing are decoded with `handler`; they are skipped if the handler
+/// doesn't support them. The depth of the sections is passed to `visit`,
+/// starting at 0.
+///
+/// # Errors
+///
+/// Returns the first [`ParseError`] of any of the sections, or
+/// [`ParseError::TooDeep`] if sections are nested more than
+/// [`MAX_SECTION_DEPTH`] levels deep. The sections before the error have
+/// been visited.
+#[cfg(feature = "alloc")]
+pub fn walk_sections(
+    sections: Sections<'_>,
+    handler: &mut dyn GuidedSectionHandler,
+    visit: &mut dyn FnMut(&Section<'_>, usize),
+) -> Result<(), ParseError> {
+    walk_sections_at(sections, handler, visit, 0)
+}
+
+/// Maximum nesting depth of the sections visited by `walk_sections`.
+pub const MAX_SECTION_DEPTH: usize = 32;
+
+#[cfg(feature = "alloc")]
+fn walk_sections_at(
+    sections: Sections<'_>,
+    handler: &mut dyn GuidedSectionHandler,
+    visit: &mut dyn FnMut(&Section<'_>, usize),
+    depth: usize,
+) -> Result<(), ParseError> {
+    if depth > MAX_SECTION_DEPTH {
+        return Err(ParseError::TooDeep);
+    }
+    for section in sections {
+        let section = section?;
+        visit(&section, depth);
+        if let Some(inner) = section.sections() {
+            walk_sections_at(inner, handler, visit, depth + 1)?;
+        } else if let Some(guided) = section.guid_defined()? {
+            if let Some(data) = handler.decode(&guided) {
+                walk_sections_at(Sections::new(&data), handler, visit, depth + 1)?;
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Returns the null-terminated UCS-2 string at the start of `data`, without
+/// the null terminator. Without a terminator, the whole data is returned.
+fn ucs2_string(data: &[u8]) -> UnalignedSlice<'_, Char16> {
+    let len = data
+        .chunks_exact(2)
+        .position(|c| c == [0, 0])
+        .unwrap_or(data.len() / 2);
+    // Safety: `data` has room for `len` characters.
+    unsafe { UnalignedSlice::new(data.as_ptr().cast(), len) }
+}
+
+fn guid_at(bytes: &[u8], offset: usize) -> Option<Guid> {
+    let bytes = bytes.get(offset..offset.checked_add(16)?)?;
+    Some(Guid::from_bytes(bytes.try_into().ok()?))
+}
+
+fn u16_at(bytes: &[u8], offset: usize) -> Option<u16> {
+    let bytes = bytes.get(offset..offset.checked_add(2)?)?;
+    Some(u16::from_le_bytes(bytes.try_into().ok()?))
+}
+
+fn u24_at(bytes: &[u8], offset: usize) -> usize {
+    usize::from(bytes[offset])
+        | (usize::from(bytes[offset + 1]) << 8)
+        | (usize::from(bytes[offset + 2]) << 16)
+}
+
+fn u32_at(bytes: &[u8], offset: usize) -> Option<u32> {
+    let bytes = bytes.get(offset..offset.checked_add(4)?)?;
+    Some(u32::from_le_bytes(bytes.try_into().ok()?))
+}
+
+fn u64_at(bytes: &[u8], offset: usize) -> Option<u64> {
+    let bytes = bytes.get(offset..offset.checked_add(8)?)?;
+    Some(u64::from_le_bytes(bytes.try_into().ok()?))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloc::vec;
+
+    const APP: Guid = guid!("a3c2f3b4-1c0f-4d8a-9b59-2f1c7f1a0b01");
+    const DATA: Guid = guid!("b0d7e1a2-6a3e-4f0c-8e4b-7a5f8c9d0e02");
+    const REVERSED: Guid = guid!("c1e2f3a4-b5c6-4d7e-8f90-a1b2c3d4e503");
+
+    fn section(section_type: SectionType, data: &[u8]) -> Vec<u8> {
+        let size = (SECTION_HEADER_SIZE + data.len()) as u32;
+        let mut bytes = size.to_le_bytes()[..3].to_vec();
+        bytes.push(section_type.0);
+        bytes.extend(data);
+        while bytes.len() % 4 != 0 {
+            bytes.push(0);
+        }
+        bytes
+    }
+
+    fn file(name: Guid, file_type: FileType, sections: &[Vec<u8>]) -> Vec<u8> {
+        let data = sections.concat();
+        let size = (FILE_HEADER_SIZE + data.len()) as u32;
+        let mut bytes = name.to_bytes().to_vec();
+        bytes.extend([0, FILE_CHECKSUM_NONE, file_type.0, 0]);
+        bytes.extend(&size.to_le_bytes()[..3]);
+        // Valid state, inverted for the erase polarity.
+        bytes.push(!0x07);
+        let sum = bytes
+            .iter()
+            .enumerate()
+            .filter(|&(i, _)| i != 17 && i != 23)
+            .fold(0u8, |sum, (_, &b)| sum.wrapping_add(b));
+        bytes[16] = 0u8.wrapping_sub(sum);
+        bytes.extend(data);
+        bytes
+    }
+
+    fn volume(files: &[Vec<u8>]) -> Vec<u8> {
+        const HEADER_LENGTH: usize = VOLUME_HEADER_SIZE + 16;
+        let mut body = Vec::new();
+        for file in files {
+            while body.len() % 8 != 0 {
+                body.push(0xff);
+            }
+            body.extend(file);
+        }
+        body.resize(body.len() + 64, 0xff);
+        let length = HEADER_LENGTH + body.len();
+
+        let mut bytes = vec![0; 16];
+        bytes.extend(FirmwareVolume::FFS2_GUID.to_bytes());
+        bytes.extend((length as u64).to_le_bytes());
+        bytes.extend(FirmwareVolumeHeader::SIGNATURE);
+        bytes.extend(
+            FirmwareVolumeAttributes::ERASE_POLARITY
+                .bits()
+                .to_le_bytes(),
+        );
+        bytes.extend((HEADER_LENGTH as u16).to_le_bytes());
+        bytes.extend([0, 0, 0, 0, 0, 2]);
+        bytes.extend(1u32.to_le_bytes());
+        bytes.extend((length as u32).to_le_bytes());
+        bytes.extend([0; 8]);
+        let sum = bytes.chunks_exact(2).fold(0u16, |sum, b| {
+            sum.wrapping_add(u16::from_le_bytes([b[0], b[1]]))
+        });
+        bytes[50..52].copy_from_slice(&0u16.wrapping_sub(sum).to_le_bytes());
+        bytes.extend(body);
+        bytes
+    }
+
+    fn ucs2(s: &str) -> Vec<u8> {
+        s.encode_utf16()
+            .chain([0])
+            .flat_map(u16::to_le_bytes)
+            .collect()
+    }
+
+    fn test_volume() -> Vec<u8> {
+        let mut guided = REVERSED.to_bytes().to_vec();
+        // Data offset after the common header and the GUID-defined header.
+        guided.extend(24u16.to_le_bytes());
+        guided.extend(
+            GuidedSectionAttributes::PROCESSING_REQUIRED
+                .bits()
+                .to_le_bytes(),
+        );
+        let mut encoded = section(SectionType::RAW, b"hidden");
+        encoded.reverse();
+        guided.extend(encoded);
+
+        let mut compression = 8u32.to_le_bytes().to_vec();
+        compression.push(0);
+        compression.extend(section(SectionType::RAW, b"data"));
+
+        volume(&[
+            file(
+                APP,
+                FileType::APPLICATION,
+                &[
+                    section(SectionType::USER_INTERFACE, &ucs2("App")),
+                    section(SectionType::PE32, b"MZ"),
+                ],
+            ),
+            file(
+                DATA,
+                FileType::FREEFORM,
+                &[
+                    section(SectionType::GUID_DEFINED, &guided),
+                    section(SectionType::COMPRESSION, &compression),
+                ],
+            ),
+        ])
+    }
+
+    #[test]
+    fn test_files() {
+        let data = test_volume();
+        let volume = FirmwareVolume::parse(&data).unwrap();
+        assert_eq!(volume.file_system(), FirmwareVolume::FFS2_GUID);
+        assert_eq!(volume.name(), None);
+
+        let files: Vec<_> = volume.files().map(Result::unwrap).collect();
+        assert_eq!(files.len(), 2);
+        assert_eq!(files[0].name(), APP);
+        assert_eq!(files[0].file_type(), FileType::APPLICATION);
+        assert_eq!(files[0].state(), FileState::DATA_VALID);
+        assert!(files[0].is_checksum_valid());
+
+        let app = volume.find_file(&APP).unwrap();
+        let sections: Vec<_> = app.sections().unwrap().map(Result::unwrap).collect();
+        assert_eq!(sections.len(), 2);
+        let name: Vec<u16> = sections[0]
+            .user_interface()
+            .unwrap()
+            .iter()
+            .map(u16::from)
+            .collect();
+        assert_eq!(name, [u16::from(b'A'), u16::from(b'p'), u16::from(b'p')]);
+        assert_eq!(sections[1].section_type(), SectionType::PE32);
+        assert_eq!(sections[1].data(), b"MZ");
+
+        assert!(volume.find_file(&REVERSED).is_none());
+    }
+
+    #[test]
+    fn test_walk_sections() {
+        struct Reverse;
+
+        impl GuidedSectionHandler for Reverse {
+            fn decode(&mut self, section: &GuidDefinedSection<'_>) -> Option<Vec<u8>> {
+                (section.definition() == REVERSED).then(|| {
+                    let mut data = section.data().to_vec();
+                    data.reverse();
+                    data
+                })
+            }
+        }
+
+        let data = test_volume();
+        let volume = FirmwareVolume::parse(&data).unwrap();
+        let file = volume.find_file(&DATA).unwrap();
+        let mut visited = Vec::new();
+        walk_sections(
+            file.sections().unwrap(),
+            &mut Reverse,
+            &mut |section, depth| {
+                visited.push((section.section_type(), depth, section.data().to_vec()));
+            },
+        )
+        .unwrap();
+        assert_eq!(visited.len(), 4);
+        assert_eq!(visited[0].0, SectionType::GUID_DEFINED);
+        assert_eq!(visited[1], (SectionType::RAW, 1, b"hidden".to_vec()));
+        assert_eq!(visited[2].0, SectionType::COMPRESSION);
+        assert_eq!(visited[3], (SectionType::RAW, 1, b"data".to_vec()));
+    }
+
+    #[test]
+    fn test_walk_sections_too_deep() {
+        struct NoHandler;
+
+        impl GuidedSectionHandler for NoHandler {
+            fn decode(&mut self, _: &GuidDefinedSection<'_>) -> Option<Vec<u8>> {
+                None
+            }
+        }
+
+        let nested = |levels| {
+            let mut data = section(SectionType::RAW, b"data");
+            for _ in 0..levels {
+                let mut compression = 0u32.to_le_bytes().to_vec();
+                compression.push(0);
+                compression.extend(data);
+                data = section(SectionType::COMPRESSION, &compression);
+            }
+            data
+        };
+        let walk = |data: &[u8]| {
+            let mut max_depth = 0;
+            walk_sections(Sections::new(data), &mut NoHandler, &mut |_, depth| {
+                max_depth = max_depth.max(depth);
+            })
+            .map(|()| max_depth)
+        };
+
+        assert_eq!(walk(&nested(MAX_SECTION_DEPTH)), Ok(MAX_SECTION_DEPTH));
+        assert_eq!(
+            walk(&nested(MAX_SECTION_DEPTH + 1)),
+            Err(ParseError::TooDeep)
+        );
+    }
+
+    #[test]
+    fn test_parse_errors() {
+        let mut data = test_volume();
+        assert_eq!(
+            FirmwareVolume::parse(&data[1..]).unwrap_err(),
+            ParseError::NotFirmwareVolume
+        );
+
+        data[50] ^= 1;
+        assert_eq!(
+            FirmwareVolume::parse(&data).unwrap_err(),
+            ParseError::InvalidChecksum
+        );
+        data[50] ^= 1;
+
+        // The size of the first file exceeds the volume.
+        data[72 + 22] = 0x7f;
+        let volume = FirmwareVolume::parse(&data).unwrap();
+        let mut files = volume.files();
+        assert_eq!(files.next().unwrap().unwrap_err(), ParseError::OutOfBounds);
+        assert!(files.next().is_none());
+    }
+}
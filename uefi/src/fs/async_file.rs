@@ -0,0 +1,147 @@
+// SPDX-License-Identifier: MIT OR Apache-2.0
+
+//! Module for [`AsyncFile`].
+
+use super::*;
+use crate::task::{self, Completion};
+use crate::{Error, Result, Status, StatusExt};
+use alloc::vec;
+use core::ptr;
+use uefi_raw::protocol::file_system::{FileIoToken, FileProtocolV2};
+
+/// Adapter that reads and writes a [`RegularFile`] with `async` functions,
+/// run by the executor of [`task`].
+///
+/// The requests use the non-blocking functions of revision 2 of the file
+/// protocol, so other tasks, e.g. a network download, can run while the
+/// file system driver waits for the disk.
+///
+/// The file protocol can't cancel requests. If a future of this type is
+/// dropped before its request is done, the drop blocks until the request
+/// is done.
+///
+/// The data is read and written through a buffer owned by the future, so
+/// the file system driver never accesses the caller's buffer after the
+/// future is gone, even if it is leaked.
+///
+/// # Example
+///
+/// ```no_run
+/// use uefi::fs::AsyncFile;
+/// use uefi::proto::media::file::RegularFile;
+/// use uefi::task;
+///
+/// fn save(file: RegularFile, data: &[u8]) -> uefi::Result {
+///     let mut file = AsyncFile::new(file).map_err(|err| err.to_err_without_payload())?;
+///     task::block_on(async {
+///         file.write(data).await?;
+///         file.flush().await
+///     })
+/// }
+/// ```
+///
+/// [`RegularFile`]: crate::proto::media::file::RegularFile
+#[derive(Debug)]
+pub struct AsyncFile {
+    file: UefiRegularFile,
+    protocol: *mut FileProtocolV2,
+}
+
+impl AsyncFile {
+    /// Wraps `file`.
+    ///
+    /// # Errors
+    /// * [`Status::UNSUPPORTED`]: the file system only supports revision 1
+    ///   of the file protocol. The error contains `file`.
+    pub fn new(mut file: UefiRegularFile) -> Result<Self, UefiRegularFile> {
+        match file.handle().protocol_v2() {
+            Some(protocol) => Ok(Self { file, protocol }),
+            None => Err(Error::new(Status::UNSUPPORTED, file)),
+        }
+    }
+
+    /// Returns the wrapped file, e.g. to read or write it synchronously.
+    pub const fn get_mut(&mut self) -> &mut UefiRegularFile {
+        &mut self.file
+    }
+
+    /// Returns the wrapped file.
+    #[must_use]
+    pub fn into_inner(self) -> UefiRegularFile {
+        self.file
+    }
+
+    /// Reads data at the current position into `buffer`, and advances the
+    /// position. Returns the number of bytes read, which is zero at the end
+    /// of the file.
+    ///
+    /// # Errors
+    /// * [`Status::NO_MEDIA`]
+    /// * [`Status::DEVICE_ERROR`]
+    /// * [`Status::VOLUME_CORRUPTED`]
+    /// * Errors of [`Completion::new`].
+    pub async fn read(&mut self, buffer: &mut [u8]) -> Result<usize> {
+        let read_ex = unsafe { (*self.protocol).read_ex };
+        let mut data = vec![0; buffer.len()];
+        let len = self.run(data.as_mut_ptr(), data.len(), read_ex).await?;
+        let len = len.min(data.len());
+        buffer[..len].copy_from_slice(&data[..len]);
+        Ok(len)
+    }
+
+    /// Writes all of `buffer` at the current position, and advances the
+    /// position.
+    ///
+    /// # Errors
+    /// * [`Status::NO_MEDIA`]
+    /// * [`Status::DEVICE_ERROR`]
+    /// * [`Status::VOLUME_CORRUPTED`]
+    /// * [`Status::WRITE_PROTECTED`]
+    /// * [`Status::ACCESS_DENIED`]
+    /// * [`Status::VOLUME_FULL`]
+    /// * Errors of [`Completion::new`].
+    pub async fn write(&mut self, buffer: &[u8]) -> Result {
+        let write_ex = unsafe { (*self.protocol).write_ex };
+        let mut data = buffer.to_vec();
+        self.run(data.as_mut_ptr(), data.len(), write_ex)
+            .await
+            .map(|_| ())
+    }
+
+    /// Flushes all modified data of the file to the device.
+    ///
+    /// # Errors
+    /// * [`Status::NO_MEDIA`]
+    /// * [`Status::DEVICE_ERROR`]
+    /// * [`Status::VOLUME_CORRUPTED`]
+    /// * [`Status::WRITE_PROTECTED`]
+    /// * [`Status::ACCESS_DENIED`]
+    /// * [`Status::VOLUME_FULL`]
+    /// * Errors of [`Completion::new`].
+    pub async fn flush(&mut self) -> Result {
+        let flush_ex = unsafe { (*self.protocol).flush_ex };
+        self.run(ptr::null_mut(), 0, flush_ex).await.map(|_| ())
+    }
+
+    /// Runs a request with `function` until it completes, and returns the
+    /// buffer size of the token.
+    async fn run(
+        &mut self,
+        buffer: *mut u8,
+        buffer_size: usize,
+        function: unsafe extern "efiapi" fn(*mut FileProtocolV2, *mut FileIoToken) -> Status,
+    ) -> Result<usize> {
+        let mut done = Completion::new()?;
+        let mut token = FileIoToken {
+            event: done.event().as_ptr(),
+            status: Status::NOT_READY,
+            buffer_size,
+            buffer: buffer.cast(),
+        };
+        let token_ptr = ptr::from_mut(&mut token);
+        unsafe { function(self.protocol, token_ptr) }.to_result()?;
+        task::request(&mut done, || {}).await;
+        let token = unsafe { &*token_ptr };
+        token.status.to_result_with_val(|| token.buffer_size)
+    }
+}
@@ -0,0 +1,298 @@
+// SPDX-License-Identifier: MIT OR Apache-2.0
+
+//! Buffered reading and writing of files. See [`BufReader`] and
+//! [`BufWriter`].
+//!
+//! Every call into the file protocol has a considerable overhead, especially
+//! on real hardware. These wrappers coalesce many small reads or writes into
+//! few large calls to [`UefiRegularFile::read`] and
+//! [`UefiRegularFile::write`].
+
+use super::*;
+use crate::{Result, Status};
+use alloc::boxed::Box;
+use alloc::vec;
+use alloc::vec::Vec;
+
+/// Default capacity of [`BufReader`] and [`BufWriter`].
+pub const DEFAULT_BUF_CAPACITY: usize = 64 * 1024;
+
+/// Adds read buffering to a [`UefiRegularFile`].
+///
+/// Reads are served from an internal buffer, which is refilled with a single
+/// large read whenever it runs empty.
+#[derive(Debug)]
+pub struct BufReader {
+    file: UefiRegularFile,
+    buf: Box<[u8]>,
+    pos: usize,
+    filled: usize,
+}
+
+impl BufReader {
+    /// Creates a new reader with a capacity of [`DEFAULT_BUF_CAPACITY`].
+    #[must_use]
+    pub fn new(file: UefiRegularFile) -> Self {
+        Self::with_capacity(DEFAULT_BUF_CAPACITY, file)
+    }
+
+    /// Creates a new reader with the given buffer capacity.
+    ///
+    /// # Panics
+    /// Panics if `capacity` is zero.
+    #[must_use]
+    pub fn with_capacity(capacity: usize, file: UefiRegularFile) -> Self {
+        assert_ne!(capacity, 0, "capacity must not be zero");
+        Self {
+            file,
+            buf: vec![0; capacity].into_boxed_slice(),
+            pos: 0,
+            filled: 0,
+        }
+    }
+
+    /// Returns the capacity of the internal buffer.
+    #[must_use]
+    pub const fn capacity(&self) -> usize {
+        self.buf.len()
+    }
+
+    /// Returns the currently buffered data that was not consumed yet.
+    #[must_use]
+    pub fn buffer(&self) -> &[u8] {
+        &self.buf[self.pos..self.filled]
+    }
+
+    /// Returns a reference to the underlying file.
+    #[must_use]
+    pub const fn get_ref(&self) -> &UefiRegularFile {
+        &self.file
+    }
+
+    /// Returns a mutable reference to the underlying file.
+    ///
+    /// Reading from or seeking in the file directly will make the buffered
+    /// data inconsistent with the file position.
+    pub const fn get_mut(&mut self) -> &mut UefiRegularFile {
+        &mut self.file
+    }
+
+    /// Unwraps the reader, returning the underlying file. Buffered data that
+    /// was not consumed yet is lost.
+    #[must_use]
+    pub fn into_inner(self) -> UefiRegularFile {
+        self.file
+    }
+
+    /// Returns the buffered data, refilling the buffer from the file if it is
+    /// empty. An empty slice means end of file.
+    pub fn fill_buf(&mut self) -> Result<&[u8]> {
+        if self.pos == self.filled {
+            self.filled = self.file.read(&mut self.buf)?;
+            self.pos = 0;
+        }
+        Ok(self.buffer())
+    }
+
+    /// Marks `amount` bytes of the buffer returned by [`Self::fill_buf`] as
+    /// consumed.
+    pub fn consume(&mut self, amount: usize) {
+        self.pos = (self.pos + amount).min(self.filled);
+    }
+
+    /// Reads data into `buffer`. Returns the number of bytes read, which is
+    /// zero at end of file.
+    ///
+    /// Reads that are at least as large as the internal buffer bypass it if
+    /// it is empty.
+    pub fn read(&mut self, buffer: &mut [u8]) -> Result<usize> {
+        if self.pos == self.filled && buffer.len() >= self.capacity() {
+            return self.file.read(buffer);
+        }
+        let available = self.fill_buf()?;
+        let len = available.len().min(buffer.len());
+        buffer[..len].copy_from_slice(&available[..len]);
+        self.consume(len);
+        Ok(len)
+    }
+
+    /// Reads exactly `buffer.len()` bytes.
+    ///
+    /// Returns [`Status::END_OF_FILE`] if the file ends before the buffer is
+    /// filled. In that case, the content of `buffer` is unspecified.
+    pub fn read_exact(&mut self, mut buffer: &mut [u8]) -> Result {
+        while !buffer.is_empty() {
+            let read = self.read(buffer)?;
+            if read == 0 {
+                return Err(Status::END_OF_FILE.into());
+            }
+            buffer = &mut buffer[read..];
+        }
+        Ok(())
+    }
+
+    /// Reads all bytes until `delimiter` or end of file is reached and
+    /// appends them to `output`. The delimiter is included, if found.
+    ///
+    /// Returns the number of bytes appended, which is zero at end of file.
+    pub fn read_until(&mut self, delimiter: u8, output: &mut Vec<u8>) -> Result<usize> {
+        let mut total = 0;
+        loop {
+            let available = self.fill_buf()?;
+            if available.is_empty() {
+                return Ok(total);
+            }
+            let (done, used) = match available.iter().position(|b| *b == delimiter) {
+                Some(i) => (true, i + 1),
+                None => (false, available.len()),
+            };
+            output.extend_from_slice(&available[..used]);
+            self.consume(used);
+            total += used;
+            if done {
+                return Ok(total);
+            }
+        }
+    }
+
+    /// Returns the logical position in the file, taking buffered data into
+    /// account.
+    pub fn get_position(&mut self) -> Result<u64> {
+        let remaining = (self.filled - self.pos) as u64;
+        Ok(self.file.get_position()? - remaining)
+    }
+
+    /// Sets the position in the file. This discards the buffered data.
+    ///
+    /// See [`UefiRegularFile::set_position`].
+    pub fn set_position(&mut self, position: u64) -> Result {
+        self.pos = 0;
+        self.filled = 0;
+        self.file.set_position(position)
+    }
+}
+
+/// Adds write buffering to a [`UefiRegularFile`].
+///
+/// Writes are collected in an internal buffer, which is written to the file
+/// with a single call when it is full, when [`Self::flush`] is called, or
+/// when the writer is dropped.
+///
+/// Errors that happen while flushing on drop are ignored. Call
+/// [`Self::flush`] or [`Self::into_inner`] to handle them.
+#[derive(Debug)]
+pub struct BufWriter {
+    // Only `None` after `into_inner`, so that `Drop` can be skipped.
+    file: Option<UefiRegularFile>,
+    buf: Vec<u8>,
+}
+
+impl BufWriter {
+    /// Creates a new writer with a capacity of [`DEFAULT_BUF_CAPACITY`].
+    #[must_use]
+    pub fn new(file: UefiRegularFile) -> Self {
+        Self::with_capacity(DEFAULT_BUF_CAPACITY, file)
+    }
+
+    /// Creates a new writer with the given buffer capacity.
+    ///
+    /// # Panics
+    /// Panics if `capacity` is zero.
+    #[must_use]
+    pub fn with_capacity(capacity: usize, file: UefiRegularFile) -> Self {
+        assert_ne!(capacity, 0, "capacity must not be zero");
+        Self {
+            file: Some(file),
+            buf: Vec::with_capacity(capacity),
+        }
+    }
+
+    /// Returns the capacity of the internal buffer.
+    #[must_use]
+    pub fn capacity(&self) -> usize {
+        self.buf.capacity()
+    }
+
+    /// Returns the data that is buffered but not written yet.
+    #[must_use]
+    pub fn buffer(&self) -> &[u8] {
+        &self.buf
+    }
+
+    /// Returns a reference to the underlying file.
+    #[must_use]
+    pub const fn get_ref(&self) -> &UefiRegularFile {
+        self.file.as_ref().unwrap()
+    }
+
+    /// Returns a mutable reference to the underlying file.
+    ///
+    /// Writing to or seeking in the file directly bypasses the buffered data
+    /// that was not written yet.
+    pub const fn get_mut(&mut self) -> &mut UefiRegularFile {
+        self.file.as_mut().unwrap()
+    }
+
+    /// Writes all of `buffer`.
+    ///
+    /// Data is only written to the file once the internal buffer is full.
+    /// Writes that don't fit into the internal buffer are passed through
+    /// directly after flushing the buffer.
+    pub fn write(&mut self, buffer: &[u8]) -> Result {
+        if self.buf.len() + buffer.len() > self.capacity() {
+            self.flush_buf()?;
+        }
+        if buffer.len() >= self.capacity() {
+            self.get_mut()
+                .write(buffer)
+                .map_err(|err| err.to_err_without_payload())
+        } else {
+            self.buf.extend_from_slice(buffer);
+            Ok(())
+        }
+    }
+
+    /// Writes the buffered data to the file and flushes the file.
+    pub fn flush(&mut self) -> Result {
+        self.flush_buf()?;
+        UefiFileTrait::flush(self.get_mut())
+    }
+
+    /// Flushes the writer and returns the underlying file.
+    ///
+    /// On error, the writer is dropped without retrying the flush.
+    pub fn into_inner(mut self) -> Result<UefiRegularFile> {
+        self.flush()?;
+        Ok(self.file.take().unwrap())
+    }
+
+    /// Writes the buffered data to the file without flushing the file.
+    fn flush_buf(&mut self) -> Result {
+        if self.buf.is_empty() {
+            return Ok(());
+        }
+        let file = self.file.as_mut().unwrap();
+        let result = file.write(&self.buf);
+        // Keep the data that was not written, so that a retry continues
+        // where this attempt stopped.
+        match result {
+            Ok(()) => {
+                self.buf.clear();
+                Ok(())
+            }
+            Err(err) => {
+                let written = *err.data();
+                self.buf.drain(..written.min(self.buf.len()));
+                Err(err.to_err_without_payload())
+            }
+        }
+    }
+}
+
+impl Drop for BufWriter {
+    fn drop(&mut self) {
+        if self.file.is_some() {
+            let _ = self.flush();
+        }
+    }
+}
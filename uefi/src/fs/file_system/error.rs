@@ -70,6 +70,15 @@ pub enum IoErrorContext {
     /// The path exists but does not correspond to a file when a file was
     /// expected.
     NotAFile,
+    /// The path exists but the operation requires that it doesn't.
+    AlreadyExists,
+    /// The combination of [`OpenOptions`] is invalid, for example because
+    /// `create` was requested without write access.
+    ///
+    /// [`OpenOptions`]: super::OpenOptions
+    InvalidOpenOptions,
+    /// Error changing the position of the file cursor.
+    SeekFailure,
 }
 
 impl Display for IoErrorContext {
@@ -85,6 +94,9 @@ impl Display for IoErrorContext {
             Self::WriteFailure => "failed to write file",
             Self::NotADirectory => "expected a directory",
             Self::NotAFile => "expected a file",
+            Self::AlreadyExists => "file already exists",
+            Self::InvalidOpenOptions => "invalid combination of open options",
+            Self::SeekFailure => "failed to seek in file",
         };
         write!(f, "{s}")
     }
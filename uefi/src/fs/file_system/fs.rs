@@ -2,8 +2,8 @@
 
 //! Module for [`FileSystem`].
 
-use crate::Status;
 use crate::fs::*;
+use crate::{CStr16, CString16, Char16, Status};
 use alloc::boxed::Box;
 use alloc::string::String;
 use alloc::vec;
@@ -19,10 +19,16 @@ pub type FileSystemResult<T> = Result<T, Error>;
 /// close to `std::fs`. It acts as convenient accessor around the
 /// [`SimpleFileSystemProtocol`].
 ///
+/// Relative paths are resolved against the current directory of the file
+/// system, see [`Self::set_cwd`]. Initially, this is the root directory.
+///
 /// Please refer to the [module documentation] for more information.
 ///
 /// [module documentation]: uefi::fs
-pub struct FileSystem(ScopedProtocol<SimpleFileSystemProtocol>);
+pub struct FileSystem {
+    proto: ScopedProtocol<SimpleFileSystemProtocol>,
+    cwd: PathBuf,
+}
 
 impl FileSystem {
     /// Constructor.
@@ -31,6 +37,61 @@ impl FileSystem {
         proto.into()
     }
 
+    /// Returns the current directory, which is used to resolve relative
+    /// paths. This is always a normalized absolute path.
+    #[must_use]
+    pub fn cwd(&self) -> &Path {
+        &self.cwd
+    }
+
+    /// Sets the current directory, which is used to resolve relative paths.
+    /// `path` itself may be relative to the current directory.
+    ///
+    /// Returns an error if the path doesn't exist or is not a directory. In
+    /// that case, the current directory is not changed.
+    pub fn set_cwd(&mut self, path: impl AsRef<Path>) -> FileSystemResult<()> {
+        let path = path.as_ref();
+        self.open(path, UefiFileMode::Read, false)?
+            .into_directory()
+            .ok_or(Error::Io(IoError {
+                path: path.to_path_buf(),
+                context: IoErrorContext::NotADirectory,
+                // We do not have a real UEFI error here as we have a logical
+                // problem.
+                uefi_error: Status::INVALID_PARAMETER.into(),
+            }))?;
+        self.cwd = self.absolute(path);
+        Ok(())
+    }
+
+    /// Sets the current directory from a path as reported by the UEFI shell,
+    /// for example from [`Shell::current_dir`]. Such paths are prefixed by the
+    /// file system mapping, e.g. `FS0:\EFI\BOOT`; the prefix is stripped.
+    ///
+    /// It is up to the caller to make sure that the mapping refers to the
+    /// volume of this file system.
+    ///
+    /// [`Shell::current_dir`]: crate::proto::shell::Shell::current_dir
+    pub fn set_cwd_from_shell(&mut self, shell_dir: &CStr16) -> FileSystemResult<()> {
+        const COLON: Char16 = unsafe { Char16::from_u16_unchecked(':' as u16) };
+
+        let chars = shell_dir.as_slice();
+        let start = chars.iter().position(|c| *c == COLON).map_or(0, |i| i + 1);
+        let mut path = CString16::new();
+        path.push(SEPARATOR);
+        chars[start..].iter().for_each(|c| path.push(*c));
+
+        self.set_cwd(PathBuf::from(path))
+    }
+
+    /// Returns the normalized absolute path for `path`. Relative paths are
+    /// resolved against the current directory. No file system access is
+    /// involved.
+    #[must_use]
+    pub fn absolute(&self, path: impl AsRef<Path>) -> PathBuf {
+        self.cwd.join(path).normalize()
+    }
+
     /// Returns `Ok(true)` if the path points at an existing file.
     ///
     /// If the file does not exist, `Ok(false)` is returned. If it cannot be
@@ -56,6 +117,42 @@ impl FileSystem {
         src_path: impl AsRef<Path>,
         dest_path: impl AsRef<Path>,
     ) -> FileSystemResult<()> {
+        self.copy_with_progress(src_path, dest_path, 1024 * 1024, |_, _| {})
+    }
+
+    /// Copies the contents of one file to another, like [`Self::copy`], in
+    /// chunks of `chunk_size` bytes.
+    ///
+    /// After each chunk, `progress` is called with the number of bytes copied
+    /// so far and the total size of the source file. This is useful to
+    /// display progress when copying large files.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use uefi::cstr16;
+    /// use uefi::fs::{FileSystem, FileSystemResult};
+    ///
+    /// fn install(fs: &mut FileSystem) -> FileSystemResult<()> {
+    ///     fs.copy_with_progress(
+    ///         cstr16!("\\image.bin"),
+    ///         cstr16!("\\EFI\\image.bin"),
+    ///         4 * 1024 * 1024,
+    ///         |done, total| log::info!("{}%", done * 100 / total.max(1)),
+    ///     )
+    /// }
+    /// ```
+    ///
+    /// # Panics
+    /// Panics if `chunk_size` is zero.
+    pub fn copy_with_progress(
+        &mut self,
+        src_path: impl AsRef<Path>,
+        dest_path: impl AsRef<Path>,
+        chunk_size: usize,
+        mut progress: impl FnMut(u64, u64),
+    ) -> FileSystemResult<()> {
+        assert_ne!(chunk_size, 0, "chunk size must not be zero");
         let src_path = src_path.as_ref();
         let dest_path = dest_path.as_ref();
 
@@ -97,8 +194,7 @@ impl FileSystem {
                 uefi_error: Status::INVALID_PARAMETER.into(),
             }))?;
 
-        // 1 MiB copy buffer.
-        let mut chunk = vec![0; 1024 * 1024];
+        let mut chunk = vec![0; chunk_size];
 
         // Read chunks from the source file and write to the destination file.
         let mut remaining_size = src_size;
@@ -132,6 +228,7 @@ impl FileSystem {
             })?;
 
             remaining_size -= u64::try_from(num_bytes_read).unwrap();
+            progress(src_size - remaining_size, src_size);
         }
 
         dest.flush().map_err(|err| {
@@ -247,7 +344,48 @@ impl FileSystem {
         Ok(UefiDirectoryIter::new(dir))
     }
 
+    /// Returns an iterator over the lines of a file. See [`Lines`].
+    pub fn read_lines(&mut self, path: impl AsRef<Path>) -> FileSystemResult<Lines> {
+        let path = path.as_ref();
+        let file = self
+            .open(path, UefiFileMode::Read, false)?
+            .into_regular_file()
+            .ok_or(Error::Io(IoError {
+                path: path.to_path_buf(),
+                context: IoErrorContext::NotAFile,
+                // We do not have a real UEFI error here as we have a logical
+                // problem.
+                uefi_error: Status::INVALID_PARAMETER.into(),
+            }))?;
+        Ok(Lines::new(BufReader::new(file), path.to_path_buf()))
+    }
+
+    /// Returns a depth-first iterator over all entries below a directory,
+    /// including the content of all sub directories. See [`Walk`].
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use uefi::cstr16;
+    /// use uefi::fs::FileSystem;
+    ///
+    /// fn list_efi_files(fs: &mut FileSystem) {
+    ///     let walk = fs
+    ///         .walk(cstr16!("\\EFI"))
+    ///         .max_depth(4)
+    ///         .filter_entry(|entry| entry.depth() < 4 || !entry.is_directory());
+    ///     for entry in walk.flatten() {
+    ///         log::info!("{} ({} bytes)", entry.path(), entry.info().file_size());
+    ///     }
+    /// }
+    /// ```
+    pub fn walk(&mut self, path: impl AsRef<Path>) -> Walk<'_> {
+        Walk::new(self, path.as_ref().to_path_buf())
+    }
+
     /// Read the entire contents of a file into a Rust string.
+    ///
+    /// Returns [`Error::Utf8Encoding`] if the content is not valid UTF-8.
     pub fn read_to_string(&mut self, path: impl AsRef<Path>) -> FileSystemResult<String> {
         String::from_utf8(self.read(path)?).map_err(Error::Utf8Encoding)
     }
@@ -386,9 +524,43 @@ impl FileSystem {
         Ok(())
     }
 
+    /// Write a string as the entire contents of a file. This function will
+    /// create a file if it does not exist, and will entirely replace its
+    /// contents if it does.
+    pub fn write_str(&mut self, path: impl AsRef<Path>, content: &str) -> FileSystemResult<()> {
+        self.write(path, content)
+    }
+
+    /// Append a string to the end of a file. This function will create a
+    /// file if it does not exist.
+    pub fn append_str(&mut self, path: impl AsRef<Path>, content: &str) -> FileSystemResult<()> {
+        let path = path.as_ref();
+
+        let mut handle = OpenOptions::new()
+            .append(true)
+            .create(true)
+            .open(self, path)?;
+
+        handle.write(content.as_bytes()).map_err(|err| {
+            Error::Io(IoError {
+                path: path.to_path_buf(),
+                context: IoErrorContext::WriteFailure,
+                uefi_error: err.to_err_without_payload(),
+            })
+        })?;
+        handle.flush().map_err(|err| {
+            Error::Io(IoError {
+                path: path.to_path_buf(),
+                context: IoErrorContext::FlushFailure,
+                uefi_error: err,
+            })
+        })?;
+        Ok(())
+    }
+
     /// Opens a fresh handle to the root directory of the volume.
     fn open_root(&mut self) -> FileSystemResult<UefiDirectoryHandle> {
-        self.0.open_volume().map_err(|err| {
+        self.proto.open_volume().map_err(|err| {
             Error::Io(IoError {
                 path: {
                     let mut path = PathBuf::new();
@@ -401,13 +573,13 @@ impl FileSystem {
         })
     }
 
-    /// Wrapper around [`Self::open_root`] that opens the provided path as
-    /// absolute path.
+    /// Wrapper around [`Self::open_root`] that opens the provided path.
+    /// Relative paths are resolved against the current directory.
     ///
     /// May create a file if [`UefiFileMode::CreateReadWrite`] is set. May
     /// create a directory if [`UefiFileMode::CreateReadWrite`] and `create_dir`
     /// is set. The parameter `create_dir` is ignored otherwise.
-    fn open(
+    pub(super) fn open(
         &mut self,
         path: &Path,
         mode: UefiFileMode,
@@ -422,7 +594,7 @@ impl FileSystem {
         };
 
         self.open_root()?
-            .open(path.to_cstr16(), mode, attr)
+            .open(self.absolute(path).to_cstr16(), mode, attr)
             .map_err(|err| {
                 Error::Io(IoError {
                     path: path.to_path_buf(),
@@ -435,13 +607,19 @@ impl FileSystem {
 
 impl Debug for FileSystem {
     fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
-        let ptr: *const _ = &self.0;
-        f.debug_tuple("FileSystem").field(&ptr).finish()
+        let ptr: *const _ = &self.proto;
+        f.debug_struct("FileSystem")
+            .field("proto", &ptr)
+            .field("cwd", &self.cwd)
+            .finish()
     }
 }
 
 impl From<uefi::boot::ScopedProtocol<SimpleFileSystemProtocol>> for FileSystem {
     fn from(proto: uefi::boot::ScopedProtocol<SimpleFileSystemProtocol>) -> Self {
-        Self(proto)
+        Self {
+            proto,
+            cwd: PathBuf::from(SEPARATOR_STR),
+        }
     }
 }
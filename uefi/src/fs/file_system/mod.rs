@@ -2,6 +2,8 @@
 
 mod error;
 mod fs;
+mod open_options;
 
 pub use error::*;
 pub use fs::*;
+pub use open_options::*;
@@ -0,0 +1,248 @@
+// SPDX-License-Identifier: MIT OR Apache-2.0
+
+//! Module for [`OpenOptions`].
+
+use crate::Status;
+use crate::fs::*;
+
+/// Options and flags which can be used to configure how a file is opened,
+/// similar to `std::fs::OpenOptions`.
+///
+/// The UEFI file protocol only knows three open modes (see
+/// [`UefiFileMode`]). This builder maps the more fine-grained flags onto
+/// these modes and emulates everything else by additional operations before
+/// or after opening the file:
+/// - `truncate` deletes an existing file and creates it again, as there is
+///   no truncate operation in UEFI.
+/// - `append` moves the file cursor to the end of the file right after
+///   opening it. Unlike on POSIX systems, the cursor is not moved again
+///   before each write.
+/// - `create_new` checks whether the file exists before creating it. This is
+///   not atomic.
+///
+/// UEFI does not support write-only files, so every opened file is readable,
+/// regardless of the `read` flag. The flag is still required to form a valid
+/// set of options if neither `write` nor `append` is set.
+///
+/// # Example
+///
+/// ```no_run
+/// use uefi::cstr16;
+/// use uefi::fs::{FileSystem, FileSystemResult, OpenOptions};
+///
+/// fn append_log_line(fs: &mut FileSystem, line: &str) -> FileSystemResult<()> {
+///     let mut file = OpenOptions::new()
+///         .append(true)
+///         .create(true)
+///         .open(fs, cstr16!("\\log.txt"))?;
+///     file.write(line.as_bytes()).unwrap();
+///     Ok(())
+/// }
+/// ```
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct OpenOptions {
+    read: bool,
+    write: bool,
+    append: bool,
+    truncate: bool,
+    create: bool,
+    create_new: bool,
+}
+
+impl OpenOptions {
+    /// Creates a blank set of options with all flags set to `false`.
+    #[must_use]
+    pub const fn new() -> Self {
+        Self {
+            read: false,
+            write: false,
+            append: false,
+            truncate: false,
+            create: false,
+            create_new: false,
+        }
+    }
+
+    /// Sets the option for read access.
+    pub const fn read(&mut self, read: bool) -> &mut Self {
+        self.read = read;
+        self
+    }
+
+    /// Sets the option for write access.
+    pub const fn write(&mut self, write: bool) -> &mut Self {
+        self.write = write;
+        self
+    }
+
+    /// Sets the option for append mode. Implies write access.
+    ///
+    /// The file cursor is placed at the end of the file after opening it.
+    pub const fn append(&mut self, append: bool) -> &mut Self {
+        self.append = append;
+        self
+    }
+
+    /// Sets the option for truncating an existing file to a length of zero.
+    ///
+    /// Requires write access and can't be combined with `append`.
+    pub const fn truncate(&mut self, truncate: bool) -> &mut Self {
+        self.truncate = truncate;
+        self
+    }
+
+    /// Sets the option to create the file if it does not exist yet.
+    ///
+    /// Requires write access.
+    pub const fn create(&mut self, create: bool) -> &mut Self {
+        self.create = create;
+        self
+    }
+
+    /// Sets the option to create a new file, failing if it already exists.
+    /// If set, `create` and `truncate` are ignored.
+    ///
+    /// Requires write access.
+    pub const fn create_new(&mut self, create_new: bool) -> &mut Self {
+        self.create_new = create_new;
+        self
+    }
+
+    /// Opens the file at `path` on the given file system with the options
+    /// specified by `self`.
+    ///
+    /// # Errors
+    /// - [`IoErrorContext::InvalidOpenOptions`] if the flags do not form a
+    ///   valid combination, see [`Self::validate`].
+    /// - [`IoErrorContext::AlreadyExists`] if `create_new` is set and the file
+    ///   already exists.
+    /// - [`IoErrorContext::NotAFile`] if the path points to a directory.
+    /// - [`IoErrorContext::SeekFailure`] if `append` is set but the cursor
+    ///   could not be moved to the end of the file.
+    /// - Any error that occurs when opening, creating or deleting the file.
+    pub fn open(
+        &self,
+        fs: &mut FileSystem,
+        path: impl AsRef<Path>,
+    ) -> FileSystemResult<UefiRegularFile> {
+        let path = path.as_ref();
+
+        if !self.validate() {
+            return Err(Error::Io(IoError {
+                path: path.to_path_buf(),
+                context: IoErrorContext::InvalidOpenOptions,
+                // We do not have a real UEFI error here as we have a logical
+                // problem.
+                uefi_error: Status::INVALID_PARAMETER.into(),
+            }));
+        }
+
+        let create = if self.create_new {
+            if fs.try_exists(path)? {
+                return Err(Error::Io(IoError {
+                    path: path.to_path_buf(),
+                    context: IoErrorContext::AlreadyExists,
+                    uefi_error: Status::ACCESS_DENIED.into(),
+                }));
+            }
+            true
+        } else if self.truncate && fs.try_exists(path)? {
+            // Since there is no truncate operation in UEFI, we delete the
+            // file and create it again.
+            fs.remove_file(path)?;
+            true
+        } else {
+            self.create
+        };
+
+        let mode = if create {
+            UefiFileMode::CreateReadWrite
+        } else if self.write || self.append {
+            UefiFileMode::ReadWrite
+        } else {
+            UefiFileMode::Read
+        };
+
+        let mut file = fs
+            .open(path, mode, false)?
+            .into_regular_file()
+            .ok_or(Error::Io(IoError {
+                path: path.to_path_buf(),
+                context: IoErrorContext::NotAFile,
+                // We do not have a real UEFI error here as we have a logical
+                // problem.
+                uefi_error: Status::INVALID_PARAMETER.into(),
+            }))?;
+
+        if self.append {
+            file.set_position(UefiRegularFile::END_OF_FILE)
+                .map_err(|err| {
+                    Error::Io(IoError {
+                        path: path.to_path_buf(),
+                        context: IoErrorContext::SeekFailure,
+                        uefi_error: err,
+                    })
+                })?;
+        }
+
+        Ok(file)
+    }
+
+    /// Checks whether the flags form a valid combination, using the same
+    /// rules as `std::fs::OpenOptions`:
+    /// - At least one of `read`, `write` or `append` must be set.
+    /// - `truncate`, `create` and `create_new` require `write` or `append`.
+    /// - `truncate` can't be combined with `append`.
+    #[must_use]
+    pub const fn validate(&self) -> bool {
+        let writable = self.write || self.append;
+        if !self.read && !writable {
+            return false;
+        }
+        if (self.truncate || self.create || self.create_new) && !writable {
+            return false;
+        }
+        if self.truncate && self.append && !self.create_new {
+            return false;
+        }
+        true
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_validate() {
+        assert!(!OpenOptions::new().validate());
+        assert!(OpenOptions::new().read(true).validate());
+        assert!(OpenOptions::new().write(true).validate());
+        assert!(OpenOptions::new().append(true).validate());
+
+        // Creating or truncating requires write access.
+        assert!(!OpenOptions::new().read(true).create(true).validate());
+        assert!(!OpenOptions::new().read(true).create_new(true).validate());
+        assert!(!OpenOptions::new().read(true).truncate(true).validate());
+        assert!(OpenOptions::new().write(true).create(true).validate());
+        assert!(OpenOptions::new().append(true).create_new(true).validate());
+        assert!(
+            OpenOptions::new()
+                .write(true)
+                .truncate(true)
+                .create(true)
+                .validate()
+        );
+
+        // Truncate and append are mutually exclusive, unless `create_new`
+        // overrides `truncate`.
+        assert!(!OpenOptions::new().append(true).truncate(true).validate());
+        assert!(
+            OpenOptions::new()
+                .append(true)
+                .truncate(true)
+                .create_new(true)
+                .validate()
+        );
+    }
+}
@@ -0,0 +1,178 @@
+// SPDX-License-Identifier: MIT OR Apache-2.0
+
+//! Generic reading and writing of byte streams, similar to `std::io`.
+//!
+//! The [`Read`] and [`Write`] traits abstract over the readers and writers
+//! of this crate, so that [`copy`] can copy data between any of them. They
+//! are not re-exported from the parent module, as their methods collide with
+//! those of [`File`] and [`RegularFile`].
+//!
+//! [`File`]: crate::proto::media::file::File
+//! [`RegularFile`]: crate::proto::media::file::RegularFile
+
+use super::*;
+use crate::Result;
+use alloc::vec;
+use alloc::vec::Vec;
+
+/// A source of bytes, similar to `std::io::Read`.
+///
+/// Implemented by [`RegularFile`], [`BufReader`] and byte slices.
+///
+/// [`RegularFile`]: crate::proto::media::file::RegularFile
+pub trait Read {
+    /// Reads data into `buffer`. Returns the number of bytes read, which is
+    /// zero at the end of the stream.
+    fn read(&mut self, buffer: &mut [u8]) -> Result<usize>;
+}
+
+/// A sink for bytes, similar to `std::io::Write`.
+///
+/// Implemented by [`RegularFile`], [`BufWriter`] and [`Vec<u8>`].
+///
+/// [`RegularFile`]: crate::proto::media::file::RegularFile
+pub trait Write {
+    /// Writes all of `buffer`.
+    fn write(&mut self, buffer: &[u8]) -> Result;
+
+    /// Flushes data that was written but is still buffered.
+    fn flush(&mut self) -> Result;
+}
+
+impl<R: Read + ?Sized> Read for &mut R {
+    fn read(&mut self, buffer: &mut [u8]) -> Result<usize> {
+        (**self).read(buffer)
+    }
+}
+
+impl<W: Write + ?Sized> Write for &mut W {
+    fn write(&mut self, buffer: &[u8]) -> Result {
+        (**self).write(buffer)
+    }
+
+    fn flush(&mut self) -> Result {
+        (**self).flush()
+    }
+}
+
+impl Read for UefiRegularFile {
+    fn read(&mut self, buffer: &mut [u8]) -> Result<usize> {
+        Self::read(self, buffer)
+    }
+}
+
+impl Write for UefiRegularFile {
+    fn write(&mut self, buffer: &[u8]) -> Result {
+        Self::write(self, buffer).map_err(|err| err.to_err_without_payload())
+    }
+
+    fn flush(&mut self) -> Result {
+        UefiFileTrait::flush(self)
+    }
+}
+
+impl Read for BufReader {
+    fn read(&mut self, buffer: &mut [u8]) -> Result<usize> {
+        Self::read(self, buffer)
+    }
+}
+
+impl Write for BufWriter {
+    fn write(&mut self, buffer: &[u8]) -> Result {
+        Self::write(self, buffer)
+    }
+
+    fn flush(&mut self) -> Result {
+        Self::flush(self)
+    }
+}
+
+impl Read for &[u8] {
+    fn read(&mut self, buffer: &mut [u8]) -> Result<usize> {
+        let len = self.len().min(buffer.len());
+        let (data, rest) = self.split_at(len);
+        buffer[..len].copy_from_slice(data);
+        *self = rest;
+        Ok(len)
+    }
+}
+
+impl Write for Vec<u8> {
+    fn write(&mut self, buffer: &[u8]) -> Result {
+        self.extend_from_slice(buffer);
+        Ok(())
+    }
+
+    fn flush(&mut self) -> Result {
+        Ok(())
+    }
+}
+
+/// Copies all data from `reader` to `writer` in chunks of `chunk_size` bytes
+/// and flushes the writer. Returns the number of bytes copied.
+///
+/// After each chunk, `progress` is called with the number of bytes copied so
+/// far.
+///
+/// # Example
+///
+/// ```no_run
+/// use uefi::fs::{self, BufReader};
+/// use uefi::proto::media::file::RegularFile;
+///
+/// fn read_all(file: RegularFile) -> uefi::Result<Vec<u8>> {
+///     let mut reader = BufReader::new(file);
+///     let mut data = Vec::new();
+///     fs::io::copy(&mut reader, &mut data, 4096, |_| {})?;
+///     Ok(data)
+/// }
+/// ```
+///
+/// # Errors
+/// Errors of [`Read::read`], [`Write::write`] and [`Write::flush`] are
+/// passed on. Bytes that were read before the error may have been written.
+///
+/// # Panics
+/// Panics if `chunk_size` is zero.
+pub fn copy<R: Read + ?Sized, W: Write + ?Sized>(
+    reader: &mut R,
+    writer: &mut W,
+    chunk_size: usize,
+    mut progress: impl FnMut(u64),
+) -> Result<u64> {
+    assert_ne!(chunk_size, 0, "chunk size must not be zero");
+    let mut chunk = vec![0; chunk_size];
+    let mut total = 0;
+    loop {
+        let read = reader.read(&mut chunk)?;
+        if read == 0 {
+            break;
+        }
+        writer.write(&chunk[..read])?;
+        total += read as u64;
+        progress(total);
+    }
+    writer.flush()?;
+    Ok(total)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_copy() {
+        let data = (0..=255).cycle().take(1000).collect::<Vec<u8>>();
+        let mut reader = data.as_slice();
+        let mut output = Vec::new();
+        let mut steps = Vec::new();
+        let copied = copy(&mut reader, &mut output, 300, |done| steps.push(done)).unwrap();
+        assert_eq!(copied, 1000);
+        assert_eq!(output, data);
+        assert_eq!(steps, [300, 600, 900, 1000]);
+        assert!(reader.is_empty());
+
+        let mut empty: &[u8] = &[];
+        assert_eq!(copy(&mut empty, &mut output, 1, |_| panic!()), Ok(0));
+    }
+}
@@ -0,0 +1,96 @@
+// SPDX-License-Identifier: MIT OR Apache-2.0
+
+//! Module for line-wise reading of files. See [`Lines`].
+
+use super::*;
+use alloc::string::String;
+use alloc::vec::Vec;
+
+/// Iterator over the lines of a file, created by [`FileSystem::read_lines`]
+/// or [`Lines::new`].
+///
+/// The file is read through a [`BufReader`], so only the current line needs
+/// to be kept in memory. Lines are split at `\n`; a trailing `\r` is removed
+/// as well. Each line must be valid UTF-8, otherwise an
+/// [`Error::Utf8Encoding`] is returned for that line.
+#[derive(Debug)]
+pub struct Lines {
+    reader: BufReader,
+    path: PathBuf,
+    failed: bool,
+}
+
+impl Lines {
+    /// Creates a line iterator over `reader`. The `path` is only used for
+    /// error reporting.
+    #[must_use]
+    pub const fn new(reader: BufReader, path: PathBuf) -> Self {
+        Self {
+            reader,
+            path,
+            failed: false,
+        }
+    }
+
+    /// Unwraps the iterator, returning the underlying reader.
+    #[must_use]
+    pub fn into_inner(self) -> BufReader {
+        self.reader
+    }
+}
+
+impl Iterator for Lines {
+    type Item = FileSystemResult<String>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.failed {
+            return None;
+        }
+
+        let mut line = Vec::new();
+        match self.reader.read_until(b'\n', &mut line) {
+            Ok(0) => None,
+            Ok(_) => {
+                if line.last() == Some(&b'\n') {
+                    line.pop();
+                }
+                Some(finish_line(line))
+            }
+            Err(err) => {
+                // Don't try to continue after a failed read.
+                self.failed = true;
+                Some(Err(Error::Io(IoError {
+                    path: self.path.clone(),
+                    context: IoErrorContext::ReadFailure,
+                    uefi_error: err,
+                })))
+            }
+        }
+    }
+}
+
+/// Removes a trailing `\r` and converts the line to a string.
+fn finish_line(mut line: Vec<u8>) -> FileSystemResult<String> {
+    if line.last() == Some(&b'\r') {
+        line.pop();
+    }
+    String::from_utf8(line).map_err(Error::Utf8Encoding)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloc::vec;
+
+    #[test]
+    fn test_finish_line() {
+        assert_eq!(finish_line(b"abc".to_vec()).unwrap(), "abc");
+        assert_eq!(finish_line(b"abc\r".to_vec()).unwrap(), "abc");
+        assert_eq!(finish_line(b"\r".to_vec()).unwrap(), "");
+        assert_eq!(finish_line(b"a\rb".to_vec()).unwrap(), "a\rb");
+        assert!(matches!(
+            finish_line(vec![0xff, 0xfe]),
+            Err(Error::Utf8Encoding(_))
+        ));
+    }
+}
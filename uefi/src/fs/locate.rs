@@ -0,0 +1,238 @@
+// SPDX-License-Identifier: MIT OR Apache-2.0
+
+//! Locating volumes by their properties. See [`find_by_label`],
+//! [`find_by_partition_guid`] and [`from_image`].
+
+use super::*;
+use crate::boot::{self, ScopedProtocol};
+use crate::proto::device_path::media::{FilePath, HardDrive, PartitionSignature};
+use crate::proto::device_path::{DevicePath, LoadedImageDevicePath};
+use crate::proto::loaded_image::LoadedImage;
+use crate::proto::media::file::{FileSystemInfo, FileSystemVolumeLabel};
+use crate::proto::media::partition::PartitionInfo;
+use crate::{CStr16, Guid, Handle, Result, Status, cstr16};
+
+/// Returns a [`FileSystem`] for the first volume whose label matches `label`.
+/// The comparison ignores ASCII case, as FAT volume labels are usually
+/// stored in upper case.
+///
+/// The label is read with [`FileSystemVolumeLabel`], falling back to
+/// [`FileSystemInfo`] for file system drivers that don't support the former.
+///
+/// # Example
+///
+/// ```no_run
+/// use uefi::{cstr16, fs};
+///
+/// let mut esp = fs::find_by_label(cstr16!("ESP")).unwrap();
+/// let config = esp.read_to_string(cstr16!("\\config.txt")).unwrap();
+/// ```
+///
+/// # Errors
+/// * [`Status::NOT_FOUND`] if no volume with that label exists.
+/// * Errors from [`boot::find_handles`].
+pub fn find_by_label(label: &CStr16) -> Result<FileSystem> {
+    find_file_system(|_, sfs| {
+        let Ok(mut root) = sfs.open_volume() else {
+            return false;
+        };
+        if let Ok(info) = root.get_boxed_info::<FileSystemVolumeLabel>() {
+            eq_ignore_ascii_case(info.volume_label(), label)
+        } else if let Ok(info) = root.get_boxed_info::<FileSystemInfo>() {
+            eq_ignore_ascii_case(info.volume_label(), label)
+        } else {
+            false
+        }
+    })
+}
+
+/// Returns a [`FileSystem`] for the volume on the GPT partition with the
+/// unique partition GUID `guid`.
+///
+/// The GUID is taken from the [`PartitionInfo`] protocol if the firmware
+/// installs it, otherwise from the hard drive node of the volume's device
+/// path.
+///
+/// # Errors
+/// * [`Status::NOT_FOUND`] if no volume on such a partition exists.
+/// * Errors from [`boot::find_handles`].
+pub fn find_by_partition_guid(guid: Guid) -> Result<FileSystem> {
+    find_file_system(|handle, _| partition_guid(handle) == Some(guid))
+}
+
+/// Returns a [`FileSystem`] for the volume the running image was loaded
+/// from, with the directory of the image as current directory. Relative
+/// paths are thus resolved next to the image, e.g. `config.txt` next to
+/// `\EFI\BOOT\BOOTX64.EFI` is `\EFI\BOOT\config.txt`.
+///
+/// The volume and the path of the image are taken from the
+/// [`LoadedImageDevicePath`] protocol if the firmware installs it, otherwise
+/// from the [`LoadedImage`] protocol. If the image was not loaded from a
+/// file, e.g. from memory, the current directory is the root directory.
+///
+/// # Example
+///
+/// ```no_run
+/// use uefi::{cstr16, fs};
+///
+/// let mut volume = fs::from_image().unwrap();
+/// let config = volume.read_to_string(cstr16!("config.toml")).unwrap();
+/// ```
+///
+/// # Errors
+/// * [`Status::UNSUPPORTED`] if the image was not loaded from a device.
+/// * Errors from [`boot::open_protocol_exclusive`] and
+///   [`boot::locate_device_path`], e.g. [`Status::NOT_FOUND`] if the device
+///   has no file system.
+pub fn from_image() -> Result<FileSystem> {
+    let image_handle = boot::image_handle();
+    let (sfs, dir) = if let Ok(device_path) =
+        boot::open_protocol_exclusive::<LoadedImageDevicePath>(image_handle)
+    {
+        let mut file_path: &DevicePath = &device_path;
+        let handle = boot::locate_device_path::<SimpleFileSystemProtocol>(&mut file_path)?;
+        let sfs = boot::open_protocol_exclusive::<SimpleFileSystemProtocol>(handle)?;
+        (sfs, image_dir(file_path))
+    } else {
+        let dir = {
+            let loaded_image = boot::open_protocol_exclusive::<LoadedImage>(image_handle)?;
+            loaded_image.file_path().and_then(image_dir)
+        };
+        (boot::get_image_file_system(image_handle)?, dir)
+    };
+
+    let mut fs = FileSystem::new(sfs);
+    if let Some(dir) = dir {
+        fs.set_cwd(dir).map_err(|err| match err {
+            Error::Io(err) => err.uefi_error,
+            _ => Status::NOT_FOUND.into(),
+        })?;
+    }
+    Ok(fs)
+}
+
+/// Returns the directory of the file that the file path nodes of
+/// `file_path` point to, or `None` if it has no such nodes.
+fn image_dir(file_path: &DevicePath) -> Option<PathBuf> {
+    // The path may be split across several nodes.
+    let mut path = PathBuf::new();
+    for node in file_path.node_iter() {
+        if let Ok(node) = <&FilePath>::try_from(node) {
+            path.push(PathBuf::from(node.path_name().to_cstring16().ok()?));
+        }
+    }
+    if path.is_empty() {
+        return None;
+    }
+    let path = Path::new(cstr16!("\\")).join(&path).normalize();
+    Some(path.parent().unwrap_or(path))
+}
+
+/// Returns the unique GPT partition GUID of the partition `handle` belongs
+/// to.
+fn partition_guid(handle: Handle) -> Option<Guid> {
+    if let Ok(pi) = boot::open_protocol_exclusive::<PartitionInfo>(handle) {
+        if let Some(entry) = pi.gpt_partition_entry() {
+            return Some(entry.unique_partition_guid);
+        }
+    }
+
+    let device_path = boot::open_protocol_exclusive::<DevicePath>(handle).ok()?;
+    device_path
+        .node_iter()
+        .filter_map(|node| <&HardDrive>::try_from(node).ok())
+        .last()
+        .and_then(|hd| match hd.partition_signature() {
+            PartitionSignature::Guid(guid) => Some(guid),
+            _ => None,
+        })
+}
+
+/// Returns a [`FileSystem`] for the first handle with the
+/// [`SimpleFileSystemProtocol`] for which `predicate` returns `true`. Handles
+/// for which the protocol can't be opened are skipped.
+fn find_file_system(
+    mut predicate: impl FnMut(Handle, &mut ScopedProtocol<SimpleFileSystemProtocol>) -> bool,
+) -> Result<FileSystem> {
+    for handle in boot::find_handles::<SimpleFileSystemProtocol>()? {
+        let Ok(mut sfs) = boot::open_protocol_exclusive::<SimpleFileSystemProtocol>(handle) else {
+            continue;
+        };
+        if predicate(handle, &mut sfs) {
+            return Ok(FileSystem::new(sfs));
+        }
+    }
+    Err(Status::NOT_FOUND.into())
+}
+
+/// Compares two strings, ignoring ASCII case.
+fn eq_ignore_ascii_case(a: &CStr16, b: &CStr16) -> bool {
+    let to_upper = |c: u16| {
+        if (u16::from(b'a')..=u16::from(b'z')).contains(&c) {
+            c - 0x20
+        } else {
+            c
+        }
+    };
+    let a = a.to_u16_slice();
+    let b = b.to_u16_slice();
+    a.len() == b.len()
+        && a.iter()
+            .zip(b.iter())
+            .all(|(x, y)| to_upper(*x) == to_upper(*y))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::proto::device_path::build::{self, DevicePathBuilder};
+    use alloc::vec::Vec;
+
+    #[test]
+    fn test_eq_ignore_ascii_case() {
+        assert!(eq_ignore_ascii_case(cstr16!("ESP"), cstr16!("esp")));
+        assert!(eq_ignore_ascii_case(
+            cstr16!("MbrTestDisk"),
+            cstr16!("MBRTESTDISK")
+        ));
+        assert!(eq_ignore_ascii_case(cstr16!(""), cstr16!("")));
+        assert!(!eq_ignore_ascii_case(cstr16!("ESP"), cstr16!("ESP2")));
+        assert!(!eq_ignore_ascii_case(cstr16!("ä"), cstr16!("Ä")));
+        assert!(!eq_ignore_ascii_case(cstr16!("@"), cstr16!("`")));
+    }
+
+    fn file_path<'a>(buf: &'a mut Vec<u8>, names: &[&CStr16]) -> &'a DevicePath {
+        let mut builder = DevicePathBuilder::with_vec(buf);
+        for name in names {
+            builder = builder
+                .push(&build::media::FilePath { path_name: name })
+                .unwrap();
+        }
+        builder.finalize().unwrap()
+    }
+
+    #[test]
+    fn test_image_dir() {
+        let dir = |names: &[&CStr16]| image_dir(file_path(&mut Vec::new(), names));
+        let boot_dir = Some(PathBuf::from(cstr16!("\\EFI\\BOOT")));
+        assert_eq!(dir(&[cstr16!("\\EFI\\BOOT\\BOOTX64.EFI")]), boot_dir);
+        assert_eq!(dir(&[cstr16!("EFI\\BOOT\\BOOTX64.EFI")]), boot_dir);
+        assert_eq!(
+            dir(&[cstr16!("\\EFI\\BOOT"), cstr16!("BOOTX64.EFI")]),
+            boot_dir
+        );
+        assert_eq!(
+            dir(&[
+                cstr16!("\\EFI"),
+                cstr16!("\\BOOT\\"),
+                cstr16!("BOOTX64.EFI")
+            ]),
+            boot_dir
+        );
+        assert_eq!(
+            dir(&[cstr16!("\\BOOTX64.EFI")]),
+            Some(PathBuf::from(cstr16!("\\")))
+        );
+        assert_eq!(dir(&[]), None);
+    }
+}
@@ -10,13 +10,20 @@
 //!
 //! Unlike in the API of typical UNIX file system abstractions, there is
 //! no virtual file system. Unlike in Windows, there is no way to access volumes
-//! by a dedicated name.
+//! by a dedicated name. However, [`find_by_label`] and
+//! [`find_by_partition_guid`] search all volumes for a matching one.
 //!
 //! # Paths
-//! All paths are absolute and follow the FAT-like file system conventions for
-//! paths. Thus, there is no current working directory and path components
-//! like `.` and `..` are not supported. In other words, the current working
-//! directory is always `/`, i.e., the root, of the opened volume.
+//! All paths follow the FAT-like file system conventions for paths. Paths
+//! starting with a separator are absolute, i.e., relative to the root of the
+//! opened volume. All other paths are relative to the current directory of
+//! the [`FileSystem`], which initially is the root directory and can be
+//! changed with [`FileSystem::set_cwd`]. Path components like `.` and `..`
+//! are resolved before a path is passed to the firmware.
+//!
+//! Applications launched from the UEFI shell can start in the directory they
+//! were invoked from by combining [`FileSystem::set_cwd_from_shell`] with
+//! [`Shell::current_dir`].
 //!
 //! Symlinks or hard-links are not supported but only directories and regular
 //! files with plain linear paths to them. For more information, see
@@ -43,20 +50,39 @@
 //! # API Hints
 //! There is no `File` abstraction as in the Rust `std` library. Instead, it is
 //! intended to work with the file system via dedicated functions, similar to
-//! the public functions of the `std::fs` module.
+//! the public functions of the `std::fs` module. If finer control over how a
+//! file is opened is needed, [`OpenOptions`] hands out the underlying
+//! [`RegularFile`] handle. Wrap it in a [`BufReader`] or [`BufWriter`] when
+//! doing many small reads or writes. The traits in [`io`] abstract over
+//! these types, so that [`io::copy`] can copy data between any of them.
+//! [`AsyncFile`] reads and writes a file with `async` functions instead.
 //!
 //! There is no automatic synchronization of the file system for concurrent
 //! accesses. This is in the responsibility of the user.
 //!
 //! [`cstr16!`]: crate::cstr16
+//! [`Shell::current_dir`]: crate::proto::shell::Shell::current_dir
+//! [`RegularFile`]: crate::proto::media::file::RegularFile
+
+pub mod io;
 
+mod async_file;
+mod buffered;
 mod dir_entry_iter;
 mod file_system;
+mod lines;
+mod locate;
 mod path;
 mod uefi_types;
+mod walk;
 
+pub use async_file::*;
+pub use buffered::*;
 pub use dir_entry_iter::*;
 pub use file_system::*;
+pub use lines::*;
+pub use locate::*;
 pub use path::*;
+pub use walk::*;
 
 use uefi_types::*;
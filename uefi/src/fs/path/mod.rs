@@ -11,9 +11,10 @@
 //!
 //! # Path Structure
 //!
-//! Paths use the [`SEPARATOR`] character as separator. Paths are absolute and
-//! do not contain `.` or `..` components. However, this can be implemented in
-//! the future.
+//! Paths use the [`SEPARATOR`] character as separator. Paths starting with
+//! [`SEPARATOR`] are absolute, all other paths are relative. The special
+//! components [`CURRENT_DIR`] (`.`) and [`PARENT_DIR`] (`..`) can be resolved
+//! lexically with [`Path::normalize`].
 
 mod path;
 mod pathbuf;
@@ -33,6 +34,12 @@ pub const SEPARATOR: Char16 = unsafe { Char16::from_u16_unchecked('\\' as u16) }
 /// Stringified version of [`SEPARATOR`].
 pub const SEPARATOR_STR: &CStr16 = cstr16!("\\");
 
+/// Path component referring to the current directory.
+pub const CURRENT_DIR: &CStr16 = cstr16!(".");
+
+/// Path component referring to the parent directory.
+pub const PARENT_DIR: &CStr16 = cstr16!("..");
+
 /// Deny list of characters for path components. UEFI supports FAT-like file
 /// systems. According to <https://en.wikipedia.org/wiki/Comparison_of_file_systems>,
 /// paths should not contain these symbols.
@@ -3,8 +3,9 @@
 // allow "path.rs" in "path"
 #![allow(clippy::module_inception)]
 
-use crate::fs::path::{PathBuf, SEPARATOR};
+use crate::fs::path::{CURRENT_DIR, PARENT_DIR, PathBuf, SEPARATOR};
 use crate::{CStr16, CString16};
+use alloc::vec::Vec;
 use core::fmt::{Display, Formatter};
 use core::ptr;
 
@@ -44,6 +45,77 @@ impl Path {
         }
     }
 
+    /// Returns `true` if the path is absolute, i.e., if it starts with
+    /// [`SEPARATOR`].
+    ///
+    /// [`SEPARATOR`]: super::SEPARATOR
+    #[must_use]
+    pub fn is_absolute(&self) -> bool {
+        self.0.as_slice().first() == Some(&SEPARATOR)
+    }
+
+    /// Returns `true` if the path is relative, i.e., not absolute. Relative
+    /// paths are resolved against the current directory of the
+    /// [`FileSystem`].
+    ///
+    /// [`FileSystem`]: crate::fs::FileSystem
+    #[must_use]
+    pub fn is_relative(&self) -> bool {
+        !self.is_absolute()
+    }
+
+    /// Creates an owned [`PathBuf`] with `path` adjoined to `self`.
+    ///
+    /// If `path` is absolute, it replaces the current path.
+    #[must_use]
+    pub fn join<P: AsRef<Self>>(&self, path: P) -> PathBuf {
+        let path = path.as_ref();
+        if path.is_absolute() {
+            return path.to_path_buf();
+        }
+        let mut buf = self.to_path_buf();
+        buf.push(path);
+        buf
+    }
+
+    /// Lexically normalizes the path by removing `.` components and resolving
+    /// `..` components against their preceding component. No file system
+    /// access is involved.
+    ///
+    /// For absolute paths, `..` components at the root are ignored. For
+    /// relative paths, leading `..` components are kept.
+    #[must_use]
+    pub fn normalize(&self) -> PathBuf {
+        let mut components: Vec<CString16> = Vec::new();
+        for component in self.components() {
+            if component == CURRENT_DIR {
+                continue;
+            } else if component == PARENT_DIR {
+                match components.last() {
+                    Some(last) if *last != PARENT_DIR => {
+                        components.pop();
+                    }
+                    _ if self.is_absolute() => {}
+                    _ => components.push(component),
+                }
+            } else {
+                components.push(component);
+            }
+        }
+
+        let mut path = CString16::new();
+        if self.is_absolute() {
+            path.push(SEPARATOR);
+        }
+        for (i, component) in components.iter().enumerate() {
+            if i != 0 {
+                path.push(SEPARATOR);
+            }
+            path.push_str(component);
+        }
+        PathBuf::from(path)
+    }
+
     /// Returns the parent directory as [`PathBuf`].
     ///
     /// If the path is a top-level component, this returns None.
@@ -65,17 +137,23 @@ impl Path {
             return None;
         }
 
-        let path =
-            self.components()
-                .take(components_count - 1)
-                .fold(CString16::new(), |mut acc, next| {
-                    // Add separator, as needed.
-                    if !acc.is_empty() && *acc.as_slice().last().unwrap() != SEPARATOR {
-                        acc.push(SEPARATOR);
-                    }
-                    acc.push_str(next.as_ref());
-                    acc
-                });
+        // Keep the path absolute if it was absolute before.
+        let mut init = CString16::new();
+        if self.is_absolute() {
+            init.push(SEPARATOR);
+        }
+
+        let path = self
+            .components()
+            .take(components_count - 1)
+            .fold(init, |mut acc, next| {
+                // Add separator, as needed.
+                if !acc.is_empty() && *acc.as_slice().last().unwrap() != SEPARATOR {
+                    acc.push(SEPARATOR);
+                }
+                acc.push_str(next.as_ref());
+                acc
+            });
         let path = PathBuf::from(path);
         Some(path)
     }
@@ -274,6 +352,56 @@ mod tests {
             Some(PathBuf::from(cstr16!("a\\b\\c"))),
         );
         assert_eq!(Path::new(cstr16!("abc")).parent(), None,);
+
+        // The parent of an absolute path is absolute as well.
+        let parent = Path::new(cstr16!("\\a\\b")).parent().unwrap();
+        assert_eq!(parent.to_cstr16(), cstr16!("\\a"));
+        let parent = Path::new(cstr16!("\\a")).parent().unwrap();
+        assert_eq!(parent.to_cstr16(), cstr16!("\\"));
+    }
+
+    #[test]
+    fn test_is_absolute() {
+        assert!(Path::new(cstr16!("\\")).is_absolute());
+        assert!(Path::new(cstr16!("\\a\\b")).is_absolute());
+        assert!(Path::new(cstr16!("a\\b")).is_relative());
+        assert!(Path::new(cstr16!("")).is_relative());
+    }
+
+    #[test]
+    fn test_join() {
+        let base = Path::new(cstr16!("\\a\\b"));
+        assert_eq!(base.join(cstr16!("c")).to_cstr16(), cstr16!("\\a\\b\\c"));
+        assert_eq!(base.join(cstr16!("\\c")).to_cstr16(), cstr16!("\\c"));
+        assert_eq!(base.join(cstr16!("")).to_cstr16(), cstr16!("\\a\\b"));
+        assert_eq!(
+            Path::new(cstr16!("\\")).join(cstr16!("c")).to_cstr16(),
+            cstr16!("\\c")
+        );
+    }
+
+    #[test]
+    fn test_normalize() {
+        let normalize = |s: &CStr16| Path::new(s).normalize();
+        assert_eq!(normalize(cstr16!("\\")).to_cstr16(), cstr16!("\\"));
+        assert_eq!(
+            normalize(cstr16!("\\a\\.\\b\\..\\c")).to_cstr16(),
+            cstr16!("\\a\\c")
+        );
+        assert_eq!(
+            normalize(cstr16!("\\..\\..\\a")).to_cstr16(),
+            cstr16!("\\a")
+        );
+        assert_eq!(normalize(cstr16!("\\a\\..")).to_cstr16(), cstr16!("\\"));
+        assert_eq!(
+            normalize(cstr16!("a\\..\\..\\b")).to_cstr16(),
+            cstr16!("..\\b")
+        );
+        assert_eq!(
+            normalize(cstr16!("..\\..\\a")).to_cstr16(),
+            cstr16!("..\\..\\a")
+        );
+        assert_eq!(normalize(cstr16!(".")).to_cstr16(), cstr16!(""));
     }
 
     #[test]
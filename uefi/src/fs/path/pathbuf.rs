@@ -38,12 +38,7 @@ impl PathBuf {
         }
 
         let empty = self.0.is_empty();
-        let needs_sep = *self
-            .0
-            .as_slice_with_nul()
-            .last()
-            .expect("Should have at least null character")
-            != SEPARATOR;
+        let needs_sep = self.0.as_slice().last() != Some(&SEPARATOR);
         if !empty && needs_sep {
             self.0.push(SEPARATOR)
         }
@@ -167,6 +162,11 @@ mod tests {
         pathbuf.push(empty_cstring16.as_ref());
         pathbuf.push(cstr16!("second"));
         assert_eq!(pathbuf.to_cstr16(), cstr16!("first\\second"));
+
+        // no duplicate separator after a trailing separator
+        let mut pathbuf = PathBuf::from(cstr16!("\\"));
+        pathbuf.push(cstr16!("first"));
+        assert_eq!(pathbuf.to_cstr16(), cstr16!("\\first"));
     }
 
     #[test]
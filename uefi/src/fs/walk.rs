@@ -0,0 +1,174 @@
+// SPDX-License-Identifier: MIT OR Apache-2.0
+
+//! Module for recursive directory traversal. See [`Walk`].
+
+use super::*;
+use alloc::boxed::Box;
+use alloc::vec::Vec;
+use core::fmt::{self, Debug, Formatter};
+
+/// An entry yielded by [`Walk`].
+#[derive(Debug)]
+pub struct WalkEntry {
+    path: PathBuf,
+    depth: usize,
+    info: Box<UefiFileInfo>,
+}
+
+impl WalkEntry {
+    /// Path of the entry, made up of the path passed to
+    /// [`FileSystem::walk`] and the names of all directories on the way.
+    #[must_use]
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Depth of the entry. Entries directly in the walked directory have a
+    /// depth of `1`.
+    #[must_use]
+    pub const fn depth(&self) -> usize {
+        self.depth
+    }
+
+    /// Metadata of the entry.
+    #[must_use]
+    pub fn info(&self) -> &UefiFileInfo {
+        &self.info
+    }
+
+    /// Returns whether the entry is a directory.
+    #[must_use]
+    pub fn is_directory(&self) -> bool {
+        self.info.is_directory()
+    }
+}
+
+/// Filter callback of [`Walk`].
+type WalkFilter<'a> = Box<dyn FnMut(&WalkEntry) -> bool + 'a>;
+
+/// Depth-first iterator over all entries below a directory, created by
+/// [`FileSystem::walk`].
+///
+/// Directories are yielded before their content. The `.` and `..` entries
+/// are skipped, so the traversal can't run into cycles. Errors for a single
+/// entry or directory are yielded as `Err` items, after which the traversal
+/// continues with the next entry.
+pub struct Walk<'a> {
+    fs: &'a mut FileSystem,
+    root: PathBuf,
+    max_depth: usize,
+    filter: Option<WalkFilter<'a>>,
+    stack: Vec<(UefiDirectoryIter, PathBuf)>,
+    // Directory that was yielded last and still needs to be descended into.
+    pending: Option<PathBuf>,
+    started: bool,
+}
+
+impl<'a> Walk<'a> {
+    pub(super) fn new(fs: &'a mut FileSystem, root: PathBuf) -> Self {
+        Self {
+            fs,
+            root,
+            max_depth: usize::MAX,
+            filter: None,
+            stack: Vec::new(),
+            pending: None,
+            started: false,
+        }
+    }
+
+    /// Sets the maximum depth of yielded entries. A depth of `1` only yields
+    /// the entries of the walked directory itself.
+    #[must_use]
+    pub const fn max_depth(mut self, depth: usize) -> Self {
+        self.max_depth = depth;
+        self
+    }
+
+    /// Sets a filter that decides whether an entry is yielded. If a
+    /// directory is filtered out, its content is skipped as well.
+    #[must_use]
+    pub fn filter_entry(mut self, filter: impl FnMut(&WalkEntry) -> bool + 'a) -> Self {
+        self.filter = Some(Box::new(filter));
+        self
+    }
+
+    /// Opens `path` and pushes its iterator on the stack.
+    fn push_dir(&mut self, path: PathBuf) -> FileSystemResult<()> {
+        let iter = self.fs.read_dir(&path)?;
+        self.stack.push((iter, path));
+        Ok(())
+    }
+}
+
+impl Iterator for Walk<'_> {
+    type Item = FileSystemResult<WalkEntry>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if !self.started {
+            self.started = true;
+            if self.max_depth == 0 {
+                return None;
+            }
+            if let Err(err) = self.push_dir(self.root.clone()) {
+                return Some(Err(err));
+            }
+        }
+
+        if let Some(path) = self.pending.take() {
+            if let Err(err) = self.push_dir(path) {
+                return Some(Err(err));
+            }
+        }
+
+        loop {
+            let depth = self.stack.len();
+            let (iter, dir_path) = self.stack.last_mut()?;
+            let info = match iter.next() {
+                None => {
+                    self.stack.pop();
+                    continue;
+                }
+                Some(Ok(info)) => info,
+                Some(Err(err)) => {
+                    let path = dir_path.clone();
+                    // Stop reading this directory, reading further entries
+                    // would probably fail again.
+                    self.stack.pop();
+                    return Some(Err(Error::Io(IoError {
+                        path,
+                        context: IoErrorContext::ReadFailure,
+                        uefi_error: err,
+                    })));
+                }
+            };
+            if COMMON_SKIP_DIRS.contains(&info.file_name()) {
+                continue;
+            }
+
+            let mut path = dir_path.clone();
+            path.push(info.file_name());
+            let entry = WalkEntry { path, depth, info };
+
+            if let Some(filter) = &mut self.filter {
+                if !filter(&entry) {
+                    continue;
+                }
+            }
+            if entry.is_directory() && depth < self.max_depth {
+                self.pending = Some(entry.path.clone());
+            }
+            return Some(Ok(entry));
+        }
+    }
+}
+
+impl Debug for Walk<'_> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Walk")
+            .field("root", &self.root)
+            .field("max_depth", &self.max_depth)
+            .field("depth", &self.stack.len())
+            .finish_non_exhaustive()
+    }
+}
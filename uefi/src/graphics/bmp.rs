@@ -0,0 +1,442 @@
+// SPDX-License-Identifier: MIT OR Apache-2.0
+
+//! Decoder for BMP images and device-independent bitmaps (DIBs), and an
+//! encoder for uncompressed 24-bit BMP images.
+//!
+//! BMP is the image format used by UEFI itself, e.g. for the boot logo
+//! referenced by the BGRT ACPI table. Uncompressed images with 1, 2, 4, 8,
+//! 16, 24 and 32 bits per pixel are supported, including the `BI_BITFIELDS`
+//! encoding with custom color masks. Run-length encoded and embedded
+//! JPEG/PNG images are not supported. Alpha channels are ignored.
+
+use super::Image;
+use crate::fs::io::Write;
+use crate::proto::console::gop::BltPixel;
+use alloc::vec::Vec;
+use core::fmt::{self, Display, Formatter};
+
+/// Size of the file header preceding the DIB.
+const FILE_HEADER_SIZE: usize = 14;
+
+/// Size of the `BITMAPCOREHEADER`, which uses 16-bit dimensions and 3-byte
+/// palette entries.
+const CORE_HEADER_SIZE: usize = 12;
+
+/// Size of the `BITMAPINFOHEADER`.
+const INFO_HEADER_SIZE: usize = 40;
+
+const BI_RGB: u32 = 0;
+const BI_BITFIELDS: u32 = 3;
+const BI_ALPHABITFIELDS: u32 = 6;
+
+/// Errors that can happen while decoding an image.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum BmpError {
+    /// The data is not a valid image or is truncated.
+    Invalid,
+    /// The image uses an unsupported encoding, such as compression.
+    Unsupported,
+}
+
+impl Display for BmpError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Invalid => write!(f, "invalid BMP image"),
+            Self::Unsupported => write!(f, "unsupported BMP encoding"),
+        }
+    }
+}
+
+impl core::error::Error for BmpError {}
+
+fn read_u16(data: &[u8], offset: usize) -> Result<u16, BmpError> {
+    data.get(offset..offset + 2)
+        .map(|b| u16::from_le_bytes([b[0], b[1]]))
+        .ok_or(BmpError::Invalid)
+}
+
+fn read_u32(data: &[u8], offset: usize) -> Result<u32, BmpError> {
+    data.get(offset..offset + 4)
+        .map(|b| u32::from_le_bytes([b[0], b[1], b[2], b[3]]))
+        .ok_or(BmpError::Invalid)
+}
+
+/// Decodes a BMP file, which starts with the `BM` signature.
+///
+/// # Errors
+/// * [`BmpError::Invalid`] if the data is not a valid BMP file.
+/// * [`BmpError::Unsupported`] if the image uses an unsupported encoding.
+pub fn decode(data: &[u8]) -> Result<Image, BmpError> {
+    if !data.starts_with(b"BM") {
+        return Err(BmpError::Invalid);
+    }
+    let pixel_offset = read_u32(data, 10)? as usize;
+    let dib = data.get(FILE_HEADER_SIZE..).ok_or(BmpError::Invalid)?;
+    let pixels = data.get(pixel_offset..).ok_or(BmpError::Invalid)?;
+    decode_with_pixels(dib, Some(pixels))
+}
+
+/// Decodes a device-independent bitmap, which is a BMP file without the
+/// file header. The pixels must directly follow the header, color masks
+/// and palette.
+///
+/// # Errors
+/// * [`BmpError::Invalid`] if the data is not a valid DIB.
+/// * [`BmpError::Unsupported`] if the image uses an unsupported encoding.
+pub fn decode_dib(data: &[u8]) -> Result<Image, BmpError> {
+    decode_with_pixels(data, None)
+}
+
+/// Encodes `image` as an uncompressed 24-bit BMP file and writes it to
+/// `writer`, one row at a time.
+///
+/// Returns [`Status::BAD_BUFFER_SIZE`] if the image is too large for the
+/// BMP format.
+///
+/// [`Status::BAD_BUFFER_SIZE`]: crate::Status::BAD_BUFFER_SIZE
+pub fn encode<W: Write + ?Sized>(image: &Image, writer: &mut W) -> crate::Result {
+    let too_large = || crate::Status::BAD_BUFFER_SIZE;
+    let row_size = image
+        .width()
+        .checked_mul(3)
+        .and_then(|size| size.checked_next_multiple_of(4))
+        .ok_or_else(too_large)?;
+    let pixel_offset = FILE_HEADER_SIZE + INFO_HEADER_SIZE;
+    let file_size = row_size
+        .checked_mul(image.height())
+        .and_then(|size| size.checked_add(pixel_offset))
+        .and_then(|size| u32::try_from(size).ok())
+        .ok_or_else(too_large)?;
+    let width = i32::try_from(image.width()).map_err(|_| too_large())?;
+    let height = i32::try_from(image.height()).map_err(|_| too_large())?;
+
+    let mut header = Vec::with_capacity(pixel_offset);
+    header.extend(b"BM");
+    header.extend(file_size.to_le_bytes());
+    header.extend([0; 4]);
+    header.extend((pixel_offset as u32).to_le_bytes());
+    header.extend((INFO_HEADER_SIZE as u32).to_le_bytes());
+    header.extend(width.to_le_bytes());
+    header.extend(height.to_le_bytes());
+    header.extend(1u16.to_le_bytes());
+    header.extend(24u16.to_le_bytes());
+    header.extend(BI_RGB.to_le_bytes());
+    header.extend((file_size - pixel_offset as u32).to_le_bytes());
+    // 2835 pixels per meter are 72 DPI.
+    header.extend(2835u32.to_le_bytes());
+    header.extend(2835u32.to_le_bytes());
+    header.extend([0; 8]);
+    writer.write(&header)?;
+
+    // Rows are stored from bottom to top.
+    let mut row = Vec::with_capacity(row_size);
+    for y in (0..image.height()).rev() {
+        row.clear();
+        for pixel in &image.pixels()[y * image.width()..][..image.width()] {
+            row.extend([pixel.blue, pixel.green, pixel.red]);
+        }
+        row.resize(row_size, 0);
+        writer.write(&row)?;
+    }
+    Ok(())
+}
+
+/// Layout of the pixel data.
+#[derive(Debug)]
+enum Format {
+    /// Indices into a palette.
+    Indexed(Vec<BltPixel>),
+    /// Color masks of a 16 or 32-bit pixel.
+    Bitfields([u32; 3]),
+    /// Blue, green and red bytes.
+    Bgr24,
+}
+
+/// Decodes the DIB in `dib`. The pixels are taken from `pixels` if given,
+/// and otherwise follow the palette.
+fn decode_with_pixels(dib: &[u8], pixels: Option<&[u8]>) -> Result<Image, BmpError> {
+    let header_size = read_u32(dib, 0)? as usize;
+    let (width, height, bpp, compression) = if header_size == CORE_HEADER_SIZE {
+        let width = i32::from(read_u16(dib, 4)?);
+        let height = i32::from(read_u16(dib, 6)?);
+        (width, height, read_u16(dib, 10)?, BI_RGB)
+    } else if header_size >= INFO_HEADER_SIZE {
+        let width = read_u32(dib, 4)? as i32;
+        let height = read_u32(dib, 8)? as i32;
+        (width, height, read_u16(dib, 14)?, read_u32(dib, 16)?)
+    } else {
+        return Err(BmpError::Invalid);
+    };
+    // A negative height indicates that the rows are stored top-down.
+    let top_down = height < 0;
+    let width = usize::try_from(width).map_err(|_| BmpError::Invalid)?;
+    let height = height.unsigned_abs() as usize;
+
+    let mut offset = header_size;
+    let format = match (bpp, compression) {
+        (1 | 2 | 4 | 8, BI_RGB) => {
+            let entry_size = if header_size == CORE_HEADER_SIZE {
+                3
+            } else {
+                4
+            };
+            let colors_used = if header_size == CORE_HEADER_SIZE {
+                0
+            } else {
+                read_u32(dib, 32)? as usize
+            };
+            let count = match colors_used {
+                0 => 1 << bpp,
+                n => n.min(1 << bpp),
+            };
+            let palette = dib
+                .get(offset..offset + count * entry_size)
+                .ok_or(BmpError::Invalid)?;
+            offset += palette.len();
+            Format::Indexed(
+                palette
+                    .chunks_exact(entry_size)
+                    .map(|c| BltPixel::new(c[2], c[1], c[0]))
+                    .collect(),
+            )
+        }
+        (16, BI_RGB) => Format::Bitfields([0x7c00, 0x03e0, 0x001f]),
+        (24, BI_RGB) => Format::Bgr24,
+        (32, BI_RGB) => Format::Bitfields([0xff0000, 0x00ff00, 0x0000ff]),
+        (16 | 32, BI_BITFIELDS | BI_ALPHABITFIELDS) => {
+            // The masks follow a `BITMAPINFOHEADER`, and are part of the
+            // larger headers.
+            let masks_offset = INFO_HEADER_SIZE;
+            if header_size == INFO_HEADER_SIZE {
+                offset += if compression == BI_ALPHABITFIELDS {
+                    16
+                } else {
+                    12
+                };
+            }
+            Format::Bitfields([
+                read_u32(dib, masks_offset)?,
+                read_u32(dib, masks_offset + 4)?,
+                read_u32(dib, masks_offset + 8)?,
+            ])
+        }
+        (1 | 2 | 4 | 8 | 16 | 24 | 32, _) => return Err(BmpError::Unsupported),
+        _ => return Err(BmpError::Invalid),
+    };
+
+    let pixels = match pixels {
+        Some(pixels) => pixels,
+        None => dib.get(offset..).ok_or(BmpError::Invalid)?,
+    };
+    let row_size = width
+        .checked_mul(usize::from(bpp))
+        .ok_or(BmpError::Invalid)?
+        .div_ceil(32)
+        * 4;
+    let size = row_size.checked_mul(height).ok_or(BmpError::Invalid)?;
+    let pixels = pixels.get(..size).ok_or(BmpError::Invalid)?;
+
+    let mut image = Vec::with_capacity(width * height);
+    for y in 0..height {
+        let row = if top_down { y } else { height - 1 - y };
+        let row = &pixels[row * row_size..][..row_size];
+        decode_row(row, width, bpp, &format, &mut image)?;
+    }
+    Ok(Image::new(width, height, image))
+}
+
+fn decode_row(
+    row: &[u8],
+    width: usize,
+    bpp: u16,
+    format: &Format,
+    out: &mut Vec<BltPixel>,
+) -> Result<(), BmpError> {
+    match format {
+        Format::Indexed(palette) => {
+            let per_byte = 8 / usize::from(bpp);
+            let mask = ((1u16 << bpp) - 1) as u8;
+            for x in 0..width {
+                let byte = row[x / per_byte];
+                let shift = 8 - usize::from(bpp) * (x % per_byte + 1);
+                let index = usize::from((byte >> shift) & mask);
+                out.push(*palette.get(index).ok_or(BmpError::Invalid)?);
+            }
+        }
+        Format::Bgr24 => {
+            out.extend(
+                row.chunks_exact(3)
+                    .take(width)
+                    .map(|c| BltPixel::new(c[2], c[1], c[0])),
+            );
+        }
+        Format::Bitfields(masks) => {
+            let channels = masks.map(|mask| {
+                let shift = mask.trailing_zeros();
+                (mask, shift, (mask >> shift.min(31)).max(1))
+            });
+            let extract = |value: u32, (mask, shift, max): (u32, u32, u32)| {
+                if mask == 0 {
+                    0
+                } else {
+                    (u64::from((value & mask) >> shift) * 255 / u64::from(max)) as u8
+                }
+            };
+            let bytes = usize::from(bpp / 8);
+            for pixel in row.chunks_exact(bytes).take(width) {
+                let value = if bytes == 2 {
+                    u32::from(u16::from_le_bytes([pixel[0], pixel[1]]))
+                } else {
+                    u32::from_le_bytes([pixel[0], pixel[1], pixel[2], pixel[3]])
+                };
+                out.push(BltPixel::new(
+                    extract(value, channels[0]),
+                    extract(value, channels[1]),
+                    extract(value, channels[2]),
+                ));
+            }
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloc::vec;
+
+    fn rgb(image: &Image) -> Vec<(u8, u8, u8)> {
+        image
+            .pixels()
+            .iter()
+            .map(|p| (p.red, p.green, p.blue))
+            .collect()
+    }
+
+    /// Builds a BMP file with a `BITMAPINFOHEADER`.
+    fn make_bmp(
+        width: i32,
+        height: i32,
+        bpp: u16,
+        compression: u32,
+        extra: &[u8],
+        pixels: &[u8],
+    ) -> Vec<u8> {
+        let pixel_offset = FILE_HEADER_SIZE + INFO_HEADER_SIZE + extra.len();
+        let mut data = Vec::new();
+        data.extend(b"BM");
+        data.extend(((pixel_offset + pixels.len()) as u32).to_le_bytes());
+        data.extend([0; 4]);
+        data.extend((pixel_offset as u32).to_le_bytes());
+        data.extend((INFO_HEADER_SIZE as u32).to_le_bytes());
+        data.extend(width.to_le_bytes());
+        data.extend(height.to_le_bytes());
+        data.extend(1u16.to_le_bytes());
+        data.extend(bpp.to_le_bytes());
+        data.extend(compression.to_le_bytes());
+        data.extend([0; 20]);
+        data.extend(extra);
+        data.extend(pixels);
+        data
+    }
+
+    #[test]
+    fn test_24bit() {
+        // Bottom-up rows of 2 pixels, padded to 8 bytes.
+        let pixels = [
+            1, 2, 3, 4, 5, 6, 0, 0, //
+            7, 8, 9, 10, 11, 12, 0, 0,
+        ];
+        let image = decode(&make_bmp(2, 2, 24, BI_RGB, &[], &pixels)).unwrap();
+        assert_eq!((image.width(), image.height()), (2, 2));
+        assert_eq!(rgb(&image), [(9, 8, 7), (12, 11, 10), (3, 2, 1), (6, 5, 4)]);
+
+        // Top-down
+        let image = decode(&make_bmp(2, -2, 24, BI_RGB, &[], &pixels)).unwrap();
+        assert_eq!(rgb(&image), [(3, 2, 1), (6, 5, 4), (9, 8, 7), (12, 11, 10)]);
+
+        assert_eq!(
+            decode(&make_bmp(2, 3, 24, BI_RGB, &[], &pixels)).unwrap_err(),
+            BmpError::Invalid
+        );
+        assert_eq!(
+            decode(&make_bmp(2, 2, 24, 1, &[], &pixels)).unwrap_err(),
+            BmpError::Unsupported
+        );
+        assert_eq!(decode(b"XY").unwrap_err(), BmpError::Invalid);
+    }
+
+    #[test]
+    fn test_encode() {
+        let pixels = (0..6).map(|i| BltPixel::new(i, i + 10, i + 20)).collect();
+        let image = Image::new(3, 2, pixels);
+        let mut data = Vec::new();
+        encode(&image, &mut data).unwrap();
+
+        // 3 pixels per row are padded from 9 to 12 bytes.
+        assert_eq!(data.len(), FILE_HEADER_SIZE + INFO_HEADER_SIZE + 2 * 12);
+        assert_eq!(read_u32(&data, 2), Ok(data.len() as u32));
+        assert_eq!(&data[54..66], [23, 13, 3, 24, 14, 4, 25, 15, 5, 0, 0, 0]);
+
+        let decoded = decode(&data).unwrap();
+        assert_eq!(rgb(&decoded), rgb(&image));
+
+        encode(&Image::new(0, 0, vec![]), &mut data).unwrap();
+    }
+
+    #[test]
+    fn test_indexed() {
+        let palette = [0, 0, 0, 0, 0xff, 0xff, 0xff, 0];
+        // 10 pixels at 1 bit per pixel.
+        let pixels = [0b1010_0000, 0b1100_0000, 0, 0];
+        let image = decode(&make_bmp(10, 1, 1, BI_RGB, &palette, &pixels)).unwrap();
+        let white: Vec<bool> = image.pixels().iter().map(|p| p.red == 0xff).collect();
+        assert_eq!(
+            white,
+            [
+                true, false, true, false, false, false, false, false, true, true
+            ]
+        );
+
+        // 3 pixels at 8 bits per pixel, with 2 used palette entries.
+        let mut data = make_bmp(3, 1, 8, BI_RGB, &palette, &[1, 0, 1, 0]);
+        data[46..50].copy_from_slice(&2u32.to_le_bytes());
+        let image = decode(&data).unwrap();
+        assert_eq!(
+            rgb(&image),
+            [(0xff, 0xff, 0xff), (0, 0, 0), (0xff, 0xff, 0xff)]
+        );
+    }
+
+    #[test]
+    fn test_bitfields() {
+        // RGB565
+        let masks: Vec<u8> = [0xf800u32, 0x07e0, 0x001f]
+            .iter()
+            .flat_map(|m| m.to_le_bytes())
+            .collect();
+        let pixels = [0x00, 0xf8, 0xe0, 0x07];
+        let image = decode(&make_bmp(2, 1, 16, BI_BITFIELDS, &masks, &pixels)).unwrap();
+        assert_eq!(rgb(&image), [(0xff, 0, 0), (0, 0xff, 0)]);
+
+        // Default 32-bit format
+        let pixels = [0x10, 0x20, 0x30, 0xff];
+        let image = decode(&make_bmp(1, 1, 32, BI_RGB, &[], &pixels)).unwrap();
+        assert_eq!(rgb(&image), [(0x30, 0x20, 0x10)]);
+    }
+
+    #[test]
+    fn test_dib() {
+        // `BITMAPCOREHEADER` with a 2-color palette of 3-byte entries.
+        let mut data = vec![];
+        data.extend((CORE_HEADER_SIZE as u32).to_le_bytes());
+        data.extend(2u16.to_le_bytes());
+        data.extend(1u16.to_le_bytes());
+        data.extend(1u16.to_le_bytes());
+        data.extend(1u16.to_le_bytes());
+        data.extend([1, 2, 3, 4, 5, 6]);
+        data.extend([0b0100_0000, 0, 0, 0]);
+        let image = decode_dib(&data).unwrap();
+        assert_eq!(rgb(&image), [(3, 2, 1), (6, 5, 4)]);
+    }
+}
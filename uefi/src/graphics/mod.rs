@@ -0,0 +1,162 @@
+// SPDX-License-Identifier: MIT OR Apache-2.0
+
+//! Decoding and drawing of images, e.g. for boot splash screens.
+//!
+//! Decoders produce an [`Image`], whose pixels are [`BltPixel`]s that can be
+//! passed to [`GraphicsOutput::blt`] directly. BMP images are always
+//! supported, PNG images require the `png` feature. Images can be saved as
+//! BMP files with [`Image::write_bmp`], e.g. screenshots taken with
+//! [`GraphicsOutput::capture`].
+//!
+//! # Example
+//!
+//! ```no_run
+//! use uefi::boot;
+//! use uefi::graphics::bmp;
+//! use uefi::proto::console::gop::GraphicsOutput;
+//!
+//! fn show_logo(logo: &[u8]) -> Result<(), Box<dyn core::error::Error>> {
+//!     let handle = boot::get_handle_for_protocol::<GraphicsOutput>()?;
+//!     let mut gop = boot::open_protocol_exclusive::<GraphicsOutput>(handle)?;
+//!
+//!     let image = bmp::decode(logo)?;
+//!     let (width, height) = gop.current_mode_info().resolution();
+//!     let x = width.saturating_sub(image.width()) / 2;
+//!     let y = height.saturating_sub(image.height()) / 2;
+//!     image.draw_at(&mut gop, x, y)?;
+//!     Ok(())
+//! }
+//! ```
+
+pub mod bmp;
+#[cfg(feature = "png")]
+pub mod png;
+
+use crate::Result;
+use crate::fs::io::Write;
+use crate::proto::console::gop::{BltOp, BltPixel, BltRegion, GraphicsOutput};
+use alloc::vec::Vec;
+
+/// An image in system memory.
+#[derive(Clone, Debug)]
+pub struct Image {
+    width: usize,
+    height: usize,
+    pixels: Vec<BltPixel>,
+}
+
+impl Image {
+    /// Creates an image from its pixels, which are stored row by row from
+    /// top to bottom without padding.
+    ///
+    /// # Panics
+    /// Panics if the number of pixels is not `width * height`.
+    #[must_use]
+    pub fn new(width: usize, height: usize, pixels: Vec<BltPixel>) -> Self {
+        assert_eq!(
+            Some(pixels.len()),
+            width.checked_mul(height),
+            "wrong number of pixels"
+        );
+        Self {
+            width,
+            height,
+            pixels,
+        }
+    }
+
+    /// Returns the width in pixels.
+    #[must_use]
+    pub const fn width(&self) -> usize {
+        self.width
+    }
+
+    /// Returns the height in pixels.
+    #[must_use]
+    pub const fn height(&self) -> usize {
+        self.height
+    }
+
+    /// Returns the pixels, row by row from top to bottom without padding.
+    #[must_use]
+    pub fn pixels(&self) -> &[BltPixel] {
+        &self.pixels
+    }
+
+    /// Returns the pixel at `(x, y)`, or `None` if it is outside the image.
+    #[must_use]
+    pub fn pixel(&self, x: usize, y: usize) -> Option<BltPixel> {
+        (x < self.width && y < self.height).then(|| self.pixels[y * self.width + x])
+    }
+
+    /// Returns the pixels, consuming the image.
+    #[must_use]
+    pub fn into_pixels(self) -> Vec<BltPixel> {
+        self.pixels
+    }
+
+    /// Returns a copy of the image resized to `width` x `height` pixels,
+    /// using nearest-neighbor sampling.
+    #[must_use]
+    pub fn scaled(&self, width: usize, height: usize) -> Self {
+        let mut pixels = Vec::with_capacity(width * height);
+        if self.width > 0 && self.height > 0 {
+            for y in 0..height {
+                let row = &self.pixels[(y * self.height / height) * self.width..][..self.width];
+                pixels.extend((0..width).map(|x| row[x * self.width / width]));
+            }
+        } else {
+            pixels.resize(width * height, BltPixel::new(0, 0, 0));
+        }
+        Self::new(width, height, pixels)
+    }
+
+    /// Writes the image to `writer` as an uncompressed 24-bit BMP file, see
+    /// [`bmp::encode`].
+    pub fn write_bmp<W: Write + ?Sized>(&self, writer: &mut W) -> Result {
+        bmp::encode(self, writer)
+    }
+
+    /// Draws the image with its top-left corner at `(x, y)` on the screen.
+    /// The parts of the image outside the screen are not drawn.
+    pub fn draw_at(&self, gop: &mut GraphicsOutput, x: usize, y: usize) -> Result {
+        let (screen_width, screen_height) = gop.current_mode_info().resolution();
+        let width = self.width.min(screen_width.saturating_sub(x));
+        let height = self.height.min(screen_height.saturating_sub(y));
+        if width == 0 || height == 0 {
+            return Ok(());
+        }
+        gop.blt(BltOp::BufferToVideo {
+            buffer: &self.pixels,
+            src: BltRegion::SubRectangle {
+                coords: (0, 0),
+                px_stride: self.width,
+            },
+            dest: (x, y),
+            dims: (width, height),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloc::vec;
+
+    #[test]
+    fn test_scaled() {
+        let pixels = (0..6).map(|i| BltPixel::new(i, 0, 0)).collect();
+        let image = Image::new(3, 2, pixels);
+
+        let scaled = image.scaled(6, 1);
+        let red: Vec<u8> = scaled.pixels().iter().map(|p| p.red).collect();
+        assert_eq!(red, [0, 0, 1, 1, 2, 2]);
+
+        let scaled = image.scaled(2, 4);
+        let red: Vec<u8> = scaled.pixels().iter().map(|p| p.red).collect();
+        assert_eq!(red, [0, 1, 0, 1, 3, 4, 3, 4]);
+
+        let empty = Image::new(0, 0, vec![]);
+        assert_eq!(empty.scaled(2, 2).pixels().len(), 4);
+    }
+}
@@ -0,0 +1,489 @@
+// SPDX-License-Identifier: MIT OR Apache-2.0
+
+//! Decoder for PNG images.
+//!
+//! All color types and bit depths of the PNG specification are supported,
+//! as well as interlaced images and transparency given by an alpha channel
+//! or a `tRNS` chunk. As [`BltPixel`]s have no alpha channel, transparent
+//! pixels are composited onto a background color while decoding. Ancillary
+//! chunks other than `tRNS`, such as gamma or color profiles, are ignored.
+
+use super::Image;
+use crate::proto::console::gop::BltPixel;
+use alloc::vec;
+use alloc::vec::Vec;
+use core::fmt::{self, Display, Formatter};
+use miniz_oxide::inflate::decompress_to_vec_zlib_with_limit;
+
+/// Signature at the start of every PNG file.
+const SIGNATURE: [u8; 8] = [0x89, b'P', b'N', b'G', b'\r', b'\n', 0x1a, b'\n'];
+
+/// Origin and spacing of the pixels of the seven Adam7 passes, as
+/// `(x, y, dx, dy)`.
+const ADAM7_PASSES: [(usize, usize, usize, usize); 7] = [
+    (0, 0, 8, 8),
+    (4, 0, 8, 8),
+    (0, 4, 4, 8),
+    (2, 0, 4, 4),
+    (0, 2, 2, 4),
+    (1, 0, 2, 2),
+    (0, 1, 1, 2),
+];
+
+/// Errors that can happen while decoding an image.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum PngError {
+    /// The data is not a valid image or is truncated.
+    Invalid,
+    /// The image uses an encoding that is not part of the PNG specification.
+    Unsupported,
+}
+
+impl Display for PngError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Invalid => write!(f, "invalid PNG image"),
+            Self::Unsupported => write!(f, "unsupported PNG encoding"),
+        }
+    }
+}
+
+impl core::error::Error for PngError {}
+
+/// Contents of the `IHDR` chunk.
+#[derive(Clone, Copy, Debug)]
+struct Header {
+    width: usize,
+    height: usize,
+    depth: u8,
+    color_type: u8,
+    interlaced: bool,
+}
+
+impl Header {
+    fn parse(data: &[u8]) -> Result<Self, PngError> {
+        if data.len() != 13 {
+            return Err(PngError::Invalid);
+        }
+        let width = u32::from_be_bytes([data[0], data[1], data[2], data[3]]);
+        let height = u32::from_be_bytes([data[4], data[5], data[6], data[7]]);
+        let (depth, color_type) = (data[8], data[9]);
+        let valid_depth = match color_type {
+            0 => matches!(depth, 1 | 2 | 4 | 8 | 16),
+            3 => matches!(depth, 1 | 2 | 4 | 8),
+            2 | 4 | 6 => matches!(depth, 8 | 16),
+            _ => false,
+        };
+        if width == 0 || height == 0 || !valid_depth {
+            return Err(PngError::Invalid);
+        }
+        if data[10] != 0 || data[11] != 0 || data[12] > 1 {
+            return Err(PngError::Unsupported);
+        }
+        Ok(Self {
+            width: width as usize,
+            height: height as usize,
+            depth,
+            color_type,
+            interlaced: data[12] == 1,
+        })
+    }
+
+    const fn channels(&self) -> usize {
+        match self.color_type {
+            2 => 3,
+            4 => 2,
+            6 => 4,
+            _ => 1,
+        }
+    }
+
+    /// Returns the size in bytes of a row of `width` pixels, without the
+    /// filter type byte.
+    fn row_size(&self, width: usize) -> Option<usize> {
+        let bits = width.checked_mul(self.channels() * usize::from(self.depth))?;
+        Some(bits.div_ceil(8))
+    }
+
+    /// Returns the origin, spacing and dimensions of the passes that
+    /// contain pixels.
+    fn passes(&self) -> impl Iterator<Item = ((usize, usize, usize, usize), (usize, usize))> {
+        let passes: &[_] = if self.interlaced {
+            &ADAM7_PASSES
+        } else {
+            &[(0, 0, 1, 1)]
+        };
+        let (width, height) = (self.width, self.height);
+        passes.iter().filter_map(move |&(x, y, dx, dy)| {
+            let pass_width = width.saturating_sub(x).div_ceil(dx);
+            let pass_height = height.saturating_sub(y).div_ceil(dy);
+            (pass_width > 0 && pass_height > 0)
+                .then_some(((x, y, dx, dy), (pass_width, pass_height)))
+        })
+    }
+}
+
+/// Decodes a PNG image, compositing transparent pixels onto `background`.
+///
+/// # Errors
+/// * [`PngError::Invalid`] if the data is not a valid PNG image.
+/// * [`PngError::Unsupported`] if the image uses an unknown compression,
+///   filter or interlace method.
+pub fn decode(data: &[u8], background: BltPixel) -> Result<Image, PngError> {
+    let mut chunks = data.strip_prefix(&SIGNATURE).ok_or(PngError::Invalid)?;
+
+    let mut header = None;
+    let mut palette: &[u8] = &[];
+    let mut transparency: &[u8] = &[];
+    let mut compressed = Vec::new();
+    loop {
+        let length = chunks
+            .get(..4)
+            .map(|b| u32::from_be_bytes([b[0], b[1], b[2], b[3]]) as usize)
+            .ok_or(PngError::Invalid)?;
+        let end = length.checked_add(12).ok_or(PngError::Invalid)?;
+        let kind = chunks.get(4..8).ok_or(PngError::Invalid)?;
+        let body = chunks.get(8..end - 4).ok_or(PngError::Invalid)?;
+        // Skip the chunk and its CRC.
+        chunks = chunks.get(end..).ok_or(PngError::Invalid)?;
+
+        match (kind, header) {
+            (b"IHDR", None) => header = Some(Header::parse(body)?),
+            (_, None) => return Err(PngError::Invalid),
+            (b"PLTE", _) => palette = body,
+            (b"tRNS", _) => transparency = body,
+            (b"IDAT", _) => compressed.extend_from_slice(body),
+            (b"IEND", _) => break,
+            // Unknown critical chunks have an uppercase first letter.
+            (kind, _) if kind[0].is_ascii_uppercase() => return Err(PngError::Unsupported),
+            _ => {}
+        }
+    }
+    let header = header.ok_or(PngError::Invalid)?;
+    if header.color_type == 3 && palette.is_empty() {
+        return Err(PngError::Invalid);
+    }
+
+    let mut size = 0usize;
+    for (_, (width, height)) in header.passes() {
+        let row_size = header.row_size(width).ok_or(PngError::Invalid)?;
+        size = (row_size + 1)
+            .checked_mul(height)
+            .and_then(|pass_size| size.checked_add(pass_size))
+            .ok_or(PngError::Invalid)?;
+    }
+    let mut raw =
+        decompress_to_vec_zlib_with_limit(&compressed, size).map_err(|_| PngError::Invalid)?;
+    if raw.len() != size {
+        return Err(PngError::Invalid);
+    }
+
+    let num_pixels = header
+        .width
+        .checked_mul(header.height)
+        .ok_or(PngError::Invalid)?;
+    let mut pixels = vec![background; num_pixels];
+    let converter = Converter {
+        header,
+        palette,
+        transparency,
+        background,
+    };
+    let mut raw = raw.as_mut_slice();
+    for ((x0, y0, dx, dy), (width, height)) in header.passes() {
+        let row_size = header.row_size(width).ok_or(PngError::Invalid)?;
+        let (pass, rest) = raw.split_at_mut((row_size + 1) * height);
+        raw = rest;
+        unfilter(pass, row_size, header.depth, header.channels())?;
+
+        for (j, row) in pass.chunks_exact(row_size + 1).enumerate() {
+            let y = y0 + j * dy;
+            for i in 0..width {
+                let x = x0 + i * dx;
+                pixels[y * header.width + x] = converter.pixel(&row[1..], i)?;
+            }
+        }
+    }
+    Ok(Image::new(header.width, header.height, pixels))
+}
+
+/// Reverses the filters of the rows in `data`, each of which starts with
+/// the filter type.
+fn unfilter(data: &mut [u8], row_size: usize, depth: u8, channels: usize) -> Result<(), PngError> {
+    // Distance to the corresponding byte of the previous pixel.
+    let bpp = (usize::from(depth) * channels).div_ceil(8);
+    let mut previous = vec![0; row_size];
+    for row in data.chunks_exact_mut(row_size + 1) {
+        let (filter, row) = row.split_first_mut().unwrap();
+        for i in 0..row_size {
+            let left = if i >= bpp { row[i - bpp] } else { 0 };
+            let up = previous[i];
+            let up_left = if i >= bpp { previous[i - bpp] } else { 0 };
+            let predictor = match *filter {
+                0 => 0,
+                1 => left,
+                2 => up,
+                3 => ((u16::from(left) + u16::from(up)) / 2) as u8,
+                4 => paeth(left, up, up_left),
+                _ => return Err(PngError::Invalid),
+            };
+            row[i] = row[i].wrapping_add(predictor);
+        }
+        previous.copy_from_slice(row);
+    }
+    Ok(())
+}
+
+const fn paeth(a: u8, b: u8, c: u8) -> u8 {
+    let p = a as i16 + b as i16 - c as i16;
+    let pa = (p - a as i16).abs();
+    let pb = (p - b as i16).abs();
+    let pc = (p - c as i16).abs();
+    if pa <= pb && pa <= pc {
+        a
+    } else if pb <= pc {
+        b
+    } else {
+        c
+    }
+}
+
+/// Converts the samples of a row to composited pixels.
+#[derive(Debug)]
+struct Converter<'a> {
+    header: Header,
+    palette: &'a [u8],
+    transparency: &'a [u8],
+    background: BltPixel,
+}
+
+impl Converter<'_> {
+    /// Returns the raw value of the sample with the given index in a row.
+    fn sample(&self, row: &[u8], index: usize) -> u16 {
+        match self.header.depth {
+            16 => u16::from_be_bytes([row[2 * index], row[2 * index + 1]]),
+            8 => u16::from(row[index]),
+            depth => {
+                let bit = index * usize::from(depth);
+                let shift = 8 - usize::from(depth) - bit % 8;
+                u16::from(row[bit / 8] >> shift) & ((1 << depth) - 1)
+            }
+        }
+    }
+
+    /// Scales a raw sample value to 8 bits.
+    fn scale(&self, value: u16) -> u8 {
+        match self.header.depth {
+            16 => (value >> 8) as u8,
+            depth => (u32::from(value) * 255 / ((1 << depth) - 1)) as u8,
+        }
+    }
+
+    /// Returns whether the raw samples match the transparent color key of a
+    /// grayscale or RGB image.
+    fn is_key(&self, samples: &[u16]) -> bool {
+        self.transparency.len() == 2 * samples.len()
+            && samples.iter().enumerate().all(|(i, sample)| {
+                u16::from_be_bytes([self.transparency[2 * i], self.transparency[2 * i + 1]])
+                    == *sample
+            })
+    }
+
+    fn pixel(&self, row: &[u8], x: usize) -> Result<BltPixel, PngError> {
+        let channels = self.header.channels();
+        let sample = |c: usize| self.sample(row, x * channels + c);
+        let ([red, green, blue], alpha) = match self.header.color_type {
+            0 => {
+                let gray = sample(0);
+                let alpha = if self.is_key(&[gray]) { 0 } else { 255 };
+                ([self.scale(gray); 3], alpha)
+            }
+            2 => {
+                let rgb = [sample(0), sample(1), sample(2)];
+                let alpha = if self.is_key(&rgb) { 0 } else { 255 };
+                (rgb.map(|value| self.scale(value)), alpha)
+            }
+            3 => {
+                let index = usize::from(sample(0));
+                let color = self
+                    .palette
+                    .get(3 * index..3 * index + 3)
+                    .ok_or(PngError::Invalid)?;
+                let alpha = self.transparency.get(index).copied().unwrap_or(255);
+                ([color[0], color[1], color[2]], alpha)
+            }
+            4 => ([self.scale(sample(0)); 3], self.scale(sample(1))),
+            _ => (
+                [
+                    self.scale(sample(0)),
+                    self.scale(sample(1)),
+                    self.scale(sample(2)),
+                ],
+                self.scale(sample(3)),
+            ),
+        };
+        Ok(BltPixel::new(
+            blend(red, self.background.red, alpha),
+            blend(green, self.background.green, alpha),
+            blend(blue, self.background.blue, alpha),
+        ))
+    }
+}
+
+/// Blends `color` with `alpha` over `background`.
+const fn blend(color: u8, background: u8, alpha: u8) -> u8 {
+    let (color, background, alpha) = (color as u32, background as u32, alpha as u32);
+    ((color * alpha + background * (255 - alpha) + 127) / 255) as u8
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use miniz_oxide::deflate::compress_to_vec_zlib;
+
+    const BACKGROUND: BltPixel = BltPixel::new(0, 0, 0xff);
+
+    fn rgb(image: &Image) -> Vec<(u8, u8, u8)> {
+        image
+            .pixels()
+            .iter()
+            .map(|p| (p.red, p.green, p.blue))
+            .collect()
+    }
+
+    fn chunk(png: &mut Vec<u8>, kind: &[u8; 4], data: &[u8]) {
+        png.extend((data.len() as u32).to_be_bytes());
+        png.extend(kind);
+        png.extend(data);
+        // The CRC is not checked.
+        png.extend([0; 4]);
+    }
+
+    /// Builds a PNG image from the filtered rows in `raw`.
+    fn make_png(
+        (width, height): (u32, u32),
+        depth: u8,
+        color_type: u8,
+        interlaced: bool,
+        extra: &[(&[u8; 4], &[u8])],
+        raw: &[u8],
+    ) -> Vec<u8> {
+        let mut png = SIGNATURE.to_vec();
+        let mut header = Vec::new();
+        header.extend(width.to_be_bytes());
+        header.extend(height.to_be_bytes());
+        header.extend([depth, color_type, 0, 0, u8::from(interlaced)]);
+        chunk(&mut png, b"IHDR", &header);
+        for (kind, data) in extra {
+            chunk(&mut png, kind, data);
+        }
+        chunk(&mut png, b"IDAT", &compress_to_vec_zlib(raw, 6));
+        chunk(&mut png, b"IEND", &[]);
+        png
+    }
+
+    #[test]
+    fn test_rgba() {
+        // Row 0 unfiltered, row 1 with the Sub filter.
+        let raw = [
+            0, 0xff, 0, 0, 0xff, 0, 0xff, 0, 0x80, //
+            1, 0, 0, 0, 0, 0, 0, 0, 0x7f,
+        ];
+        let png = make_png((2, 2), 8, 6, false, &[], &raw);
+        let image = decode(&png, BACKGROUND).unwrap();
+        assert_eq!(
+            rgb(&image),
+            [(0xff, 0, 0), (0, 0x80, 0x7f), (0, 0, 0xff), (0, 0, 0x80)]
+        );
+
+        // Transparent pixels are composited onto the background.
+        let image = decode(&png, BltPixel::new(0xff, 0xff, 0xff)).unwrap();
+        assert_eq!(rgb(&image)[1], (0x7f, 0xff, 0x7f));
+    }
+
+    #[test]
+    fn test_filters() {
+        // 3x5 grayscale image, each row filtered with a different filter.
+        let raw = [
+            0, 10, 20, 30, //
+            1, 40, 10, 10, //
+            2, 30, 30, 30, //
+            3, 65, 35, 30, //
+            4, 30, 10, 10,
+        ];
+        let png = make_png((3, 5), 8, 0, false, &[], &raw);
+        let image = decode(&png, BACKGROUND).unwrap();
+        let gray: Vec<u8> = image.pixels().iter().map(|p| p.green).collect();
+        assert_eq!(
+            gray,
+            [
+                10, 20, 30, 40, 50, 60, 70, 80, 90, 100, 125, 137, 130, 140, 150
+            ]
+        );
+    }
+
+    #[test]
+    fn test_palette() {
+        // 2 bits per pixel, with a transparent third color.
+        let palette = [0, 0, 0, 0xff, 0xff, 0xff, 0xff, 0, 0];
+        let transparency = [0xff, 0xff, 0];
+        let raw = [0, 0b00_01_10_00];
+        let png = make_png(
+            (3, 1),
+            2,
+            3,
+            false,
+            &[(b"PLTE", &palette), (b"tRNS", &transparency)],
+            &raw,
+        );
+        let image = decode(&png, BACKGROUND).unwrap();
+        assert_eq!(rgb(&image), [(0, 0, 0), (0xff, 0xff, 0xff), (0, 0, 0xff)]);
+
+        // Indices outside the palette are invalid.
+        let png = make_png((1, 1), 2, 3, false, &[(b"PLTE", &palette)], &[0, 0xc0]);
+        assert_eq!(decode(&png, BACKGROUND).unwrap_err(), PngError::Invalid);
+    }
+
+    #[test]
+    fn test_gray16_key() {
+        let transparency = [0x12, 0x34];
+        let raw = [0, 0x12, 0x34, 0xab, 0xcd];
+        let png = make_png((2, 1), 16, 0, false, &[(b"tRNS", &transparency)], &raw);
+        let image = decode(&png, BACKGROUND).unwrap();
+        assert_eq!(rgb(&image), [(0, 0, 0xff), (0xab, 0xab, 0xab)]);
+    }
+
+    #[test]
+    fn test_interlaced() {
+        // In a 3x3 image, pass 1 contains (0, 0), pass 4 contains (2, 0),
+        // pass 5 contains (0, 2) and (2, 2), pass 6 contains (1, 0) and
+        // (1, 2), and pass 7 contains the middle row.
+        let raw = [
+            0, 0, //
+            0, 2, //
+            0, 6, 8, //
+            0, 1, 0, 7, //
+            0, 3, 4, 5,
+        ];
+        let png = make_png((3, 3), 8, 0, true, &[], &raw);
+        let image = decode(&png, BACKGROUND).unwrap();
+        let gray: Vec<u8> = image.pixels().iter().map(|p| p.red).collect();
+        assert_eq!(gray, [0, 1, 2, 3, 4, 5, 6, 7, 8]);
+    }
+
+    #[test]
+    fn test_invalid() {
+        assert_eq!(
+            decode(b"GIF89a", BACKGROUND).unwrap_err(),
+            PngError::Invalid
+        );
+
+        // Not enough image data.
+        let png = make_png((2, 2), 8, 2, false, &[], &[0; 7]);
+        assert_eq!(decode(&png, BACKGROUND).unwrap_err(), PngError::Invalid);
+
+        // Unknown critical chunk.
+        let png = make_png((1, 1), 8, 0, false, &[(b"ABCD", &[])], &[0, 0]);
+        assert_eq!(decode(&png, BACKGROUND).unwrap_err(), PngError::Unsupported);
+    }
+}
@@ -0,0 +1,170 @@
+// SPDX-License-Identifier: MIT OR Apache-2.0
+
+//! Stack walking by frame pointers.
+
+use cfg_if::cfg_if;
+use core::mem::size_of;
+
+/// Maximum number of frames returned by [`StackFrames`].
+const MAX_FRAMES: usize = 64;
+
+/// Maximum distance of a frame from the stack pointer.
+const MAX_STACK_SIZE: usize = 1 << 20;
+
+const WORD: usize = size_of::<usize>();
+
+/// Iterator over the return addresses of the calling functions, innermost
+/// first, found by following the chain of frame pointers.
+///
+/// This requires the code to keep frame pointers, which the UEFI targets
+/// don't do by default. Build with `-C force-frame-pointers=yes` to get
+/// complete backtraces; otherwise the walk ends early or returns bogus
+/// addresses.
+///
+/// To avoid reading outside of the stack, the walk stops at frame pointers
+/// that are misaligned, that are not above the previous frame, or that are
+/// more than 1 MiB above the stack pointer. It also stops after 64 frames.
+///
+/// Stack walking is supported on x86, x86_64 and aarch64. On other
+/// architectures, the iterator is empty.
+#[derive(Clone, Debug)]
+pub struct StackFrames {
+    fp: usize,
+    /// Lowest address of the next frame.
+    low: usize,
+    /// Highest address of the next frame.
+    high: usize,
+    remaining: usize,
+}
+
+impl StackFrames {
+    /// Starts a walk at the frame of the caller.
+    #[inline(always)]
+    #[must_use]
+    pub fn current() -> Self {
+        let (fp, sp) = registers();
+        // SAFETY: the walk stays within the current stack.
+        unsafe { Self::new(fp, sp) }
+    }
+
+    /// Starts a walk at the frame pointer `fp`, on a stack whose top is
+    /// at `sp`.
+    ///
+    /// # Safety
+    ///
+    /// The memory from `sp` up to 1 MiB above it must be readable.
+    #[must_use]
+    pub const unsafe fn new(fp: usize, sp: usize) -> Self {
+        Self {
+            fp,
+            low: sp,
+            high: sp.saturating_add(MAX_STACK_SIZE),
+            remaining: MAX_FRAMES,
+        }
+    }
+}
+
+impl Iterator for StackFrames {
+    type Item = usize;
+
+    fn next(&mut self) -> Option<usize> {
+        let fp = self.fp;
+        if self.remaining == 0
+            || fp == 0
+            || fp < self.low
+            || fp > self.high - 2 * WORD
+            || fp % WORD != 0
+        {
+            return None;
+        }
+        self.remaining -= 1;
+
+        // A frame starts with the frame pointer of the caller, followed by
+        // the return address.
+        let frame = fp as *const usize;
+        let (next, return_address) = unsafe { (frame.read(), frame.add(1).read()) };
+        if return_address == 0 {
+            self.remaining = 0;
+            return None;
+        }
+
+        // The stack grows down, so the frames of callers are above.
+        self.low = fp + 2 * WORD;
+        self.fp = next;
+        Some(return_address)
+    }
+}
+
+/// Returns the frame pointer and the stack pointer.
+#[inline(always)]
+fn registers() -> (usize, usize) {
+    let fp: usize;
+    let sp: usize;
+    cfg_if! {
+        if #[cfg(target_arch = "x86_64")] {
+            unsafe {
+                core::arch::asm!(
+                    "mov {}, rbp",
+                    "mov {}, rsp",
+                    out(reg) fp,
+                    out(reg) sp,
+                    options(nomem, nostack, preserves_flags)
+                );
+            }
+        } else if #[cfg(target_arch = "x86")] {
+            unsafe {
+                core::arch::asm!(
+                    "mov {}, ebp",
+                    "mov {}, esp",
+                    out(reg) fp,
+                    out(reg) sp,
+                    options(nomem, nostack, preserves_flags)
+                );
+            }
+        } else if #[cfg(target_arch = "aarch64")] {
+            unsafe {
+                core::arch::asm!(
+                    "mov {}, x29",
+                    "mov {}, sp",
+                    out(reg) fp,
+                    out(reg) sp,
+                    options(nomem, nostack, preserves_flags)
+                );
+            }
+        } else {
+            fp = 0;
+            sp = 0;
+        }
+    }
+    (fp, sp)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloc::vec::Vec;
+
+    fn walk(stack: &[usize], sp_offset: usize) -> Vec<usize> {
+        let base = stack.as_ptr() as usize;
+        unsafe { StackFrames::new(base, base + sp_offset) }.collect()
+    }
+
+    #[test]
+    fn test_stack_frames() {
+        let mut stack = [0usize; 8];
+        let base = stack.as_ptr() as usize;
+        stack[0] = base + 4 * WORD;
+        stack[1] = 0x1000;
+        stack[4] = base + 6 * WORD;
+        stack[5] = 0x2000;
+        stack[7] = 0x3000;
+        assert_eq!(walk(&stack, 0), [0x1000, 0x2000, 0x3000]);
+
+        // A frame pointer that doesn't increase ends the walk.
+        stack[6] = base + 4 * WORD;
+        assert_eq!(walk(&stack, 0), [0x1000, 0x2000, 0x3000]);
+
+        // So does a frame pointer below the stack pointer.
+        assert!(walk(&stack, WORD).is_empty());
+    }
+}
@@ -0,0 +1,233 @@
+// SPDX-License-Identifier: MIT OR Apache-2.0
+
+#[cfg(feature = "logger")]
+use super::LoggerBuilder;
+#[cfg(feature = "panic_handler")]
+use super::PanicAction;
+use crate::allocator;
+use crate::boot::{self, EventType, MemoryType, Tpl};
+use crate::{Event, Result};
+use core::ffi::c_void;
+use core::marker::PhantomData;
+use core::mem;
+use core::ptr::NonNull;
+#[cfg(feature = "panic_handler")]
+use core::time::Duration;
+
+/// Selects the helpers that [`init`] installs, for applications that need
+/// more control than [`helpers::init`] offers, e.g. loaders and drivers.
+///
+/// Helpers whose Cargo feature is not enabled can't be selected.
+///
+/// # Example
+///
+/// ```no_run
+/// use log::LevelFilter;
+/// use uefi::boot::MemoryType;
+/// use uefi::helpers::{Builder, LoggerBuilder};
+///
+/// # fn main() -> uefi::Result {
+/// let _helpers = Builder::new()
+///     .logger(LoggerBuilder::new().console(LevelFilter::Info))
+///     .allocator_memory_type(MemoryType::RUNTIME_SERVICES_DATA)
+///     .hook_exit_boot_services(true)
+///     .init()?;
+/// # Ok(())
+/// # }
+/// ```
+///
+/// [`init`]: Self::init
+/// [`helpers::init`]: super::init
+#[derive(Debug)]
+pub struct Builder<'a> {
+    #[cfg(feature = "logger")]
+    logger: Option<LoggerBuilder<'a>>,
+    allocator_memory_type: Option<MemoryType>,
+    #[cfg(feature = "panic_handler")]
+    panic_action: PanicAction,
+    #[cfg(feature = "panic_handler")]
+    panic_stall: Duration,
+    hook_exit_boot_services: bool,
+    _marker: PhantomData<&'a ()>,
+}
+
+impl<'a> Builder<'a> {
+    /// Creates a builder with the same choices as [`helpers::init`]: the
+    /// [default logger], the default memory type of the allocator and the
+    /// default panic handler behavior.
+    ///
+    /// [`helpers::init`]: super::init
+    /// [default logger]: LoggerBuilder::default
+    #[must_use]
+    pub const fn new() -> Self {
+        Self {
+            #[cfg(feature = "logger")]
+            logger: Some(LoggerBuilder::with_default_sinks()),
+            allocator_memory_type: None,
+            #[cfg(feature = "panic_handler")]
+            panic_action: PanicAction::Shutdown,
+            #[cfg(feature = "panic_handler")]
+            panic_stall: Duration::from_secs(10),
+            hook_exit_boot_services: false,
+            _marker: PhantomData,
+        }
+    }
+
+    /// Sets up the logger with the sinks of `logger`.
+    #[cfg(feature = "logger")]
+    #[must_use]
+    pub const fn logger(mut self, logger: LoggerBuilder<'a>) -> Self {
+        self.logger = Some(logger);
+        self
+    }
+
+    /// Doesn't set up the logger, e.g. because the application uses its
+    /// own.
+    #[cfg(feature = "logger")]
+    #[must_use]
+    pub const fn no_logger(mut self) -> Self {
+        self.logger = None;
+        self
+    }
+
+    /// Sets the memory type of the allocations of
+    /// [`allocator::Allocator`], see [`allocator::set_memory_type`].
+    #[must_use]
+    pub const fn allocator_memory_type(mut self, memory_type: MemoryType) -> Self {
+        self.allocator_memory_type = Some(memory_type);
+        self
+    }
+
+    /// Sets what the panic handler does after printing the panic message.
+    /// The default is [`PanicAction::Shutdown`].
+    #[cfg(feature = "panic_handler")]
+    #[must_use]
+    pub const fn panic_action(mut self, action: PanicAction) -> Self {
+        self.panic_action = action;
+        self
+    }
+
+    /// Sets how long the panic handler waits before its
+    /// [action](Self::panic_action), so the message can be read. The
+    /// default is 10 seconds.
+    #[cfg(feature = "panic_handler")]
+    #[must_use]
+    pub const fn panic_stall(mut self, stall: Duration) -> Self {
+        self.panic_stall = stall;
+        self
+    }
+
+    /// Sets whether the helpers are disabled by an event when boot services
+    /// are exited.
+    ///
+    /// [`boot::exit_boot_services`] always disables the helpers. The event
+    /// is needed if boot services are exited by other means, e.g. by a
+    /// kernel's EFI stub. Buffered output of [`log_to_file`] is lost in
+    /// that case.
+    ///
+    /// [`log_to_file`]: super::log_to_file
+    #[must_use]
+    pub const fn hook_exit_boot_services(mut self, hook: bool) -> Self {
+        self.hook_exit_boot_services = hook;
+        self
+    }
+
+    /// Installs the selected helpers.
+    ///
+    /// The helpers stay installed until the returned [`Guard`] is dropped.
+    ///
+    /// # Errors
+    ///
+    /// * Errors of [`boot::create_event`] for the event of
+    ///   [`hook_exit_boot_services`].
+    /// * Errors of opening the serial port or the file of the log sinks.
+    ///
+    /// The helpers are torn down again on errors.
+    ///
+    /// [`hook_exit_boot_services`]: Self::hook_exit_boot_services
+    pub fn init(self) -> Result<Guard> {
+        if let Some(memory_type) = self.allocator_memory_type {
+            allocator::set_memory_type(memory_type);
+        }
+
+        #[cfg(feature = "panic_handler")]
+        super::panic_handler::configure(self.panic_action, self.panic_stall);
+
+        let mut guard = Guard {
+            exit_event: None,
+            #[cfg(feature = "logger")]
+            logger: false,
+        };
+
+        if self.hook_exit_boot_services {
+            guard.exit_event = Some(unsafe {
+                boot::create_event(
+                    EventType::SIGNAL_EXIT_BOOT_SERVICES,
+                    Tpl::NOTIFY,
+                    Some(exit_boot_services_notify),
+                    None,
+                )
+            }?);
+        }
+
+        #[cfg(feature = "logger")]
+        if let Some(logger) = self.logger {
+            guard.logger = true;
+            unsafe { super::logger::init(logger) }?;
+        }
+
+        Ok(guard)
+    }
+}
+
+impl Default for Builder<'_> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Keeps the helpers installed by [`Builder::init`]. Dropping the guard
+/// tears them down: the event for exiting boot services is closed and the
+/// logger is disabled.
+///
+/// Nothing happens on drop after boot services have been exited.
+#[derive(Debug)]
+#[must_use = "dropping the guard tears the helpers down"]
+pub struct Guard {
+    exit_event: Option<Event>,
+    #[cfg(feature = "logger")]
+    logger: bool,
+}
+
+impl Guard {
+    /// Keeps the helpers installed for the rest of the program.
+    pub const fn keep(self) {
+        mem::forget(self);
+    }
+}
+
+impl Drop for Guard {
+    fn drop(&mut self) {
+        if !boot::are_boot_services_active() {
+            return;
+        }
+        if let Some(event) = self.exit_event.take() {
+            // Ignore errors, since we can't propagate them from `drop`.
+            let _ = boot::close_event(event);
+        }
+        #[cfg(feature = "logger")]
+        if self.logger {
+            super::logger::deinit();
+        }
+    }
+}
+
+/// Disables the helpers when boot services are exited.
+#[allow(clippy::missing_const_for_fn)]
+unsafe extern "efiapi" fn exit_boot_services_notify(
+    _event: Event,
+    _context: Option<NonNull<c_void>>,
+) {
+    #[cfg(feature = "logger")]
+    super::logger::disable_on_exit_boot_services();
+}
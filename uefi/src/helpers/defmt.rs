@@ -0,0 +1,130 @@
+// SPDX-License-Identifier: MIT OR Apache-2.0
+
+//! A [`defmt`] global logger.
+//!
+//! With the `defmt` feature, the crate provides the global logger of
+//! [`defmt`], which writes the encoded frames to a serial port and, on x86,
+//! to the debugcon device. Unlike the text of the `log` pipeline, `defmt`
+//! sends compact binary records whose format strings are stored in the
+//! image, so large applications can log a lot over slow debug UARTs. The
+//! output is decoded on the host with `defmt-print` and the symbols of the
+//! image.
+//!
+//! No output is written until a sink is enabled with [`defmt_to_serial`] or
+//! [`defmt_to_debugcon`].
+//!
+//! [`defmt`]: https://docs.rs/defmt
+
+use crate::boot::{self, OpenProtocolAttributes, OpenProtocolParams};
+#[cfg(any(target_arch = "x86", target_arch = "x86_64"))]
+use crate::console::debugcon::Debugcon;
+use crate::console::serial;
+use crate::proto::console::serial::Serial;
+use crate::{Handle, Result, Status};
+use core::cell::UnsafeCell;
+use core::ptr;
+#[cfg(any(target_arch = "x86", target_arch = "x86_64"))]
+use core::sync::atomic::AtomicU32;
+use core::sync::atomic::{AtomicBool, AtomicPtr, Ordering};
+
+/// Serial port that the frames are written to, or null.
+static SERIAL: AtomicPtr<Serial> = AtomicPtr::new(ptr::null_mut());
+
+/// I/O port of the debugcon device that the frames are written to, or
+/// [`NO_DEBUGCON`].
+#[cfg(any(target_arch = "x86", target_arch = "x86_64"))]
+static DEBUGCON: AtomicU32 = AtomicU32::new(NO_DEBUGCON);
+
+#[cfg(any(target_arch = "x86", target_arch = "x86_64"))]
+const NO_DEBUGCON: u32 = u32::MAX;
+
+/// Whether a frame is being written.
+static TAKEN: AtomicBool = AtomicBool::new(false);
+
+/// Encoder of the frame that is being written.
+static ENCODER: EncoderCell = EncoderCell(UnsafeCell::new(defmt::Encoder::new()));
+
+struct EncoderCell(UnsafeCell<defmt::Encoder>);
+
+// SAFETY: The encoder is only accessed between `acquire` and `release`,
+// which `TAKEN` makes exclusive.
+unsafe impl Sync for EncoderCell {}
+
+/// Writes the `defmt` frames to the serial port of `handle` as well, with
+/// the given baud rate. Calling this function again replaces the previous
+/// serial port.
+///
+/// Output to the serial port stops when boot services are exited.
+///
+/// # Errors
+/// * [`Status::UNSUPPORTED`]: `handle` doesn't support the [`Serial`]
+///   protocol.
+/// * Errors of [`Serial::set_attributes`], e.g. if the baud rate is not
+///   supported.
+pub fn defmt_to_serial(handle: Handle, baud_rate: u64) -> Result {
+    let mut protocol = unsafe {
+        boot::open_protocol::<Serial>(
+            OpenProtocolParams {
+                handle,
+                agent: boot::image_handle(),
+                controller: None,
+            },
+            OpenProtocolAttributes::GetProtocol,
+        )
+    }?;
+    let port = protocol.get_mut().ok_or(Status::UNSUPPORTED)?;
+    serial::set_baud_rate(port, baud_rate)?;
+    let port: *mut Serial = port;
+
+    // Protocols opened with `GetProtocol` don't need to be closed, so keep
+    // it open for as long as the logger uses it.
+    core::mem::forget(protocol);
+    SERIAL.store(port, Ordering::Release);
+    Ok(())
+}
+
+/// Writes the `defmt` frames to `debugcon` as well. This keeps working after
+/// boot services have been exited.
+#[cfg(any(target_arch = "x86", target_arch = "x86_64"))]
+pub fn defmt_to_debugcon(debugcon: Debugcon) {
+    DEBUGCON.store(u32::from(debugcon.port()), Ordering::Release);
+}
+
+/// Writes encoded bytes to the enabled sinks.
+fn write_sinks(bytes: &[u8]) {
+    let port = SERIAL.load(Ordering::Acquire);
+    if !port.is_null() && boot::are_boot_services_active() {
+        // A frame can't be reported as lost, so errors are ignored.
+        let _ = unsafe { &mut *port }.write(bytes);
+    }
+
+    #[cfg(any(target_arch = "x86", target_arch = "x86_64"))]
+    if let Ok(port) = u16::try_from(DEBUGCON.load(Ordering::Acquire)) {
+        Debugcon::new(port).write_bytes(bytes);
+    }
+}
+
+#[defmt::global_logger]
+struct Logger;
+
+// SAFETY: `TAKEN` makes the access to the encoder exclusive, and a nested
+// `acquire` panics as required.
+unsafe impl defmt::Logger for Logger {
+    fn acquire() {
+        if TAKEN.swap(true, Ordering::Acquire) {
+            panic!("defmt logger taken reentrantly");
+        }
+        unsafe { &mut *ENCODER.0.get() }.start_frame(write_sinks);
+    }
+
+    unsafe fn flush() {}
+
+    unsafe fn release() {
+        unsafe { &mut *ENCODER.0.get() }.end_frame(write_sinks);
+        TAKEN.store(false, Ordering::Release);
+    }
+
+    unsafe fn write(bytes: &[u8]) {
+        unsafe { &mut *ENCODER.0.get() }.write(bytes, write_sinks);
+    }
+}
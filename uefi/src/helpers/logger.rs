@@ -1,10 +1,18 @@
 // SPDX-License-Identifier: MIT OR Apache-2.0
 
 //! This optional feature adds support for the `log` crate, providing
-//! a custom logger implementation which writes to a UEFI text output protocol.
+//! a custom logger implementation which writes to several sinks:
 //!
-//! The main export of this module is the `Logger` structure,
-//! which implements the `log` crate's trait `Log`.
+//! * the UEFI text output protocol of the console,
+//! * a serial port, see [`log_to_serial`],
+//! * the QEMU debugcon device (feature `log-debugcon`, x86 only),
+//! * a file, see [`log_to_file`],
+//! * a ring buffer in memory, see [`dump_memory_log`],
+//! * the status code handlers of the platform, see [`log_to_status_code`].
+//!
+//! Each sink has its own [`LogFilter`], with a level and optional levels for
+//! individual modules. The sinks are chosen with a [`LoggerBuilder`] passed
+//! to [`helpers::init_with_logger`].
 //!
 //! # Implementation details
 //!
@@ -13,79 +21,738 @@
 //!
 //! The last part also means that some Unicode characters might not be
 //! supported by the UEFI console. Don't expect emoji output support.
+//!
+//! [`helpers::init_with_logger`]: super::init_with_logger
 
+use super::ring_buffer::RingBuffer;
+use crate::boot::{self, OpenProtocolAttributes, OpenProtocolParams};
+#[cfg(all(
+    any(target_arch = "x86", target_arch = "x86_64"),
+    feature = "log-debugcon"
+))]
+use crate::console::debugcon::Debugcon;
+use crate::console::serial::{self, SerialConsole};
+use crate::proto::console::serial::Serial;
 use crate::proto::console::text::Output;
-use crate::system;
+use crate::proto::media::file::{File, FileAttribute, FileMode, RegularFile};
+use crate::proto::media::fs::SimpleFileSystem;
+use crate::proto::pi::status_code::{CodeType, ErrorOperation, Severity, StatusCode, SubClass};
+use crate::{CStr8, CStr16, Handle, Result, Status, system};
+use core::cell::UnsafeCell;
 use core::fmt::{self, Write};
 use core::ptr;
-use core::sync::atomic::{AtomicPtr, Ordering};
+use core::sync::atomic::{AtomicBool, AtomicPtr, Ordering};
+use log::{LevelFilter, Metadata, Record};
+
+/// Size of the buffer of [`log_to_file`]. Records are written to the file
+/// when the buffer is full, or when the log is flushed.
+const FILE_BUFFER_SIZE: usize = 4096;
+
+/// Size of the messages of the status code sink, including the null
+/// terminator. Longer messages are truncated.
+const STATUS_CODE_MESSAGE_SIZE: usize = 256;
 
 /// Global logger object
 static LOGGER: Logger = Logger::new();
 
-/// Set up logging
+/// Whether [`LOGGER`] has been set as the logger of the `log` crate, which
+/// can only be done once.
+static INSTALLED: AtomicBool = AtomicBool::new(false);
+
+/// Set up logging with the sinks of `builder`.
 ///
 /// This is unsafe because you must arrange for the logger to be reset with
 /// disable() on exit from UEFI boot services.
-pub unsafe fn init() {
+pub unsafe fn init(builder: LoggerBuilder<'_>) -> Result {
+    let LoggerBuilder {
+        console,
+        serial,
+        debugcon,
+        file,
+        memory,
+        status_code,
+    } = builder;
+
     // Connect the logger to stdout.
-    system::with_stdout(|stdout| unsafe {
-        LOGGER.set_output(stdout);
+    if console.is_some() {
+        system::with_stdout(|stdout| unsafe {
+            LOGGER.set_output(stdout);
+        });
+    }
+    let memory = memory.map(|(buffer, filter)| {
+        LOGGER
+            .memory
+            .with(|memory| *memory = Some(RingBuffer::new(buffer)));
+        filter
     });
+    unsafe {
+        LOGGER.update_filters(|filters| {
+            *filters = Filters {
+                console: console.unwrap_or(LogFilter::OFF),
+                serial: serial.map_or(LogFilter::OFF, |(_, _, filter)| filter),
+                debugcon: debugcon.unwrap_or(LogFilter::OFF),
+                file: file.map_or(LogFilter::OFF, |(_, _, filter)| filter),
+                memory: memory.unwrap_or(LogFilter::OFF),
+                status_code: status_code.unwrap_or(LogFilter::OFF),
+            }
+        });
+    }
 
-    // Set the logger.
-    log::set_logger(&LOGGER).unwrap(); // Can only fail if already initialized.
+    // Set the logger. This can only fail if another logger has been set.
+    if !INSTALLED.swap(true, Ordering::AcqRel) {
+        log::set_logger(&LOGGER).unwrap();
+    }
 
-    // Set logger max level to level specified by log features
-    log::set_max_level(log::STATIC_MAX_LEVEL);
+    // The sinks that need protocols are attached last, so that errors can
+    // be logged.
+    if let Some((handle, baud_rate, _)) = serial {
+        attach_serial(handle, baud_rate)?;
+    }
+    if let Some((handle, path, _)) = file {
+        attach_file(handle, path)?;
+    }
+    if status_code.is_some() {
+        attach_status_code()?;
+    }
+    Ok(())
 }
 
 pub fn disable() {
+    log::Log::flush(&LOGGER);
     LOGGER.disable();
 }
 
-/// Writer to the QEMU debugcon device and the debug-console of
-/// cloud-hypervisor.
+/// Disables the logger from the notification of `ExitBootServices`, where
+/// the file sink can't be written anymore.
+pub(super) fn disable_on_exit_boot_services() {
+    LOGGER.disable();
+}
+
+/// Stops logging to all sinks, including the ring buffer, so that the
+/// logger can be set up again with [`init`].
+pub(super) fn deinit() {
+    disable();
+    LOGGER.memory.with(|memory| *memory = None);
+    unsafe { LOGGER.update_filters(|filters| *filters = Filters::new()) };
+}
+
+/// Level filter of a log sink.
 ///
-/// More info: <https://phip1611.de/blog/how-to-use-qemus-debugcon-feature/>
-#[cfg(all(
-    any(target_arch = "x86", target_arch = "x86_64"),
-    feature = "log-debugcon"
-))]
-#[derive(Copy, Clone, Debug)]
-struct DebugconWriter;
+/// Records are passed to the sink if their level is at most the level of
+/// the filter. The level can be overridden for the targets of individual
+/// modules, which by default are the module paths of the records. The
+/// override with the longest matching module path applies.
+///
+/// # Example
+///
+/// ```
+/// use log::LevelFilter;
+/// use uefi::helpers::LogFilter;
+///
+/// // Log warnings and errors, and everything of `my_app::net`, except for
+/// // `my_app::net::dhcp`.
+/// let filter = LogFilter::new(LevelFilter::Warn).with_targets(&[
+///     ("my_app::net", LevelFilter::Trace),
+///     ("my_app::net::dhcp", LevelFilter::Info),
+/// ]);
+/// ```
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct LogFilter {
+    level: LevelFilter,
+    targets: &'static [(&'static str, LevelFilter)],
+}
 
-#[cfg(all(
-    any(target_arch = "x86", target_arch = "x86_64"),
-    feature = "log-debugcon"
-))]
-impl DebugconWriter {
-    const IO_PORT: u16 = 0xe9;
+impl LogFilter {
+    /// A filter that lets no record pass.
+    pub const OFF: Self = Self::new(LevelFilter::Off);
+
+    /// Creates a filter that passes records up to `level`.
+    #[must_use]
+    pub const fn new(level: LevelFilter) -> Self {
+        Self {
+            level,
+            targets: &[],
+        }
+    }
+
+    /// Sets the levels for the targets of modules, given as pairs of module
+    /// path and level.
+    #[must_use]
+    pub const fn with_targets(mut self, targets: &'static [(&'static str, LevelFilter)]) -> Self {
+        self.targets = targets;
+        self
+    }
+
+    /// Returns the level of records of `target` that pass the filter.
+    #[must_use]
+    pub fn level_of(&self, target: &str) -> LevelFilter {
+        self.targets
+            .iter()
+            .filter(|(module, _)| {
+                target
+                    .strip_prefix(module)
+                    .is_some_and(|rest| rest.is_empty() || rest.starts_with("::"))
+            })
+            .max_by_key(|(module, _)| module.len())
+            .map_or(self.level, |&(_, level)| level)
+    }
+
+    /// Returns whether records with `metadata` pass the filter.
+    #[must_use]
+    pub fn enabled(&self, metadata: &Metadata) -> bool {
+        metadata.level() <= self.level_of(metadata.target())
+    }
+
+    /// Returns the highest level of records that can pass the filter.
+    fn max_level(&self) -> LevelFilter {
+        self.targets
+            .iter()
+            .map(|&(_, level)| level)
+            .fold(self.level, Ord::max)
+    }
 }
 
-#[cfg(all(
-    any(target_arch = "x86", target_arch = "x86_64"),
-    feature = "log-debugcon"
-))]
-impl core::fmt::Write for DebugconWriter {
+impl Default for LogFilter {
+    /// Passes all records.
+    fn default() -> Self {
+        Self::new(LevelFilter::Trace)
+    }
+}
+
+impl From<LevelFilter> for LogFilter {
+    fn from(level: LevelFilter) -> Self {
+        Self::new(level)
+    }
+}
+
+/// Selects the sinks of the logger of [`helpers::init_with_logger`].
+///
+/// The [`Default`] builder logs everything to the console and, with the
+/// `log-debugcon` feature, to the debugcon device, like [`helpers::init`].
+///
+/// # Example
+///
+/// ```no_run
+/// use log::LevelFilter;
+/// use uefi::helpers::{self, LogFilter, LoggerBuilder};
+/// use uefi::proto::console::serial::Serial;
+/// use uefi::{boot, cstr16};
+///
+/// # fn main() -> uefi::Result {
+/// static mut LOG_BUFFER: [u8; 16384] = [0; 16384];
+///
+/// let serial = boot::get_handle_for_protocol::<Serial>()?;
+/// let esp = boot::get_handle_for_protocol::<uefi::proto::media::fs::SimpleFileSystem>()?;
+/// helpers::init_with_logger(
+///     LoggerBuilder::new()
+///         .console(LevelFilter::Info)
+///         .serial(serial, 115_200, LevelFilter::Debug)
+///         .file(esp, cstr16!("app.log"), LogFilter::default())
+///         .memory(unsafe { &mut *core::ptr::addr_of_mut!(LOG_BUFFER) }, LevelFilter::Trace),
+/// )?;
+/// # Ok(())
+/// # }
+/// ```
+///
+/// [`helpers::init`]: super::init
+/// [`helpers::init_with_logger`]: super::init_with_logger
+pub struct LoggerBuilder<'a> {
+    console: Option<LogFilter>,
+    serial: Option<(Handle, u64, LogFilter)>,
+    debugcon: Option<LogFilter>,
+    file: Option<(Handle, &'a CStr16, LogFilter)>,
+    memory: Option<(&'static mut [u8], LogFilter)>,
+    status_code: Option<LogFilter>,
+}
+
+impl<'a> LoggerBuilder<'a> {
+    /// Creates a builder without sinks.
+    #[must_use]
+    pub const fn new() -> Self {
+        Self {
+            console: None,
+            serial: None,
+            debugcon: None,
+            file: None,
+            memory: None,
+            status_code: None,
+        }
+    }
+
+    /// Creates the builder returned by [`Default::default`], which logs to the
+    /// console and, with the `log-debugcon` feature, to the debugcon device.
+    pub(crate) const fn with_default_sinks() -> Self {
+        let mut builder = Self::new();
+        builder.console = Some(LogFilter::new(LevelFilter::Trace));
+        #[cfg(feature = "log-debugcon")]
+        {
+            builder.debugcon = Some(LogFilter::new(LevelFilter::Trace));
+        }
+        builder
+    }
+
+    /// Logs to the console output of the system table.
+    #[must_use]
+    pub fn console(mut self, filter: impl Into<LogFilter>) -> Self {
+        self.console = Some(filter.into());
+        self
+    }
+
+    /// Logs to the serial port of `handle`, like [`log_to_serial`].
+    #[must_use]
+    pub fn serial(mut self, handle: Handle, baud_rate: u64, filter: impl Into<LogFilter>) -> Self {
+        self.serial = Some((handle, baud_rate, filter.into()));
+        self
+    }
+
+    /// Logs to the QEMU debugcon device. This has no effect on other
+    /// architectures than x86.
+    #[cfg(feature = "log-debugcon")]
+    #[must_use]
+    pub fn debugcon(mut self, filter: impl Into<LogFilter>) -> Self {
+        self.debugcon = Some(filter.into());
+        self
+    }
+
+    /// Appends the log to the file at `path` on the file system of `handle`,
+    /// like [`log_to_file`].
+    #[must_use]
+    pub fn file(mut self, handle: Handle, path: &'a CStr16, filter: impl Into<LogFilter>) -> Self {
+        self.file = Some((handle, path, filter.into()));
+        self
+    }
+
+    /// Logs to a ring buffer in `buffer`, which keeps the most recent
+    /// records. The buffer can be read with [`dump_memory_log`].
+    ///
+    /// The filter of the ring buffer is independent of the other sinks, so
+    /// it can record more detail than is shown on the console. With the
+    /// `panic_handler` feature, the buffer is written to the other sinks on
+    /// panic, so the records leading up to the panic can be recovered.
+    ///
+    /// Unlike the other sinks, the ring buffer keeps working after boot
+    /// services have been exited.
+    #[must_use]
+    pub fn memory(mut self, buffer: &'static mut [u8], filter: impl Into<LogFilter>) -> Self {
+        self.memory = Some((buffer, filter.into()));
+        self
+    }
+
+    /// Reports records as status codes, like [`log_to_status_code`].
+    #[must_use]
+    pub fn status_code(mut self, filter: impl Into<LogFilter>) -> Self {
+        self.status_code = Some(filter.into());
+        self
+    }
+}
+
+impl Default for LoggerBuilder<'_> {
+    fn default() -> Self {
+        Self::with_default_sinks()
+    }
+}
+
+impl fmt::Debug for LoggerBuilder<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("LoggerBuilder")
+            .field("console", &self.console)
+            .field("serial", &self.serial)
+            .field("debugcon", &self.debugcon)
+            .field("file", &self.file)
+            .field(
+                "memory",
+                &self
+                    .memory
+                    .as_ref()
+                    .map(|(buffer, filter)| (buffer.len(), filter)),
+            )
+            .field("status_code", &self.status_code)
+            .finish()
+    }
+}
+
+/// Sends the log output to the serial port of `handle` as well, with a baud
+/// rate of `baud_rate`. The other settings of the port are kept.
+///
+/// The port is opened non-exclusively, so it can still be used as a console
+/// by the firmware. Logging to it stops when boot services are exited
+/// through [`boot::exit_boot_services`].
+///
+/// This requires the logger to be set up with [`helpers::init`]. If the
+/// serial sink wasn't configured with a [`LoggerBuilder`], all records are
+/// sent to the port.
+///
+/// # Errors
+/// * [`Status::UNSUPPORTED`]: `handle` doesn't support the [`Serial`]
+///   protocol.
+/// * Errors of [`Serial::set_attributes`], e.g. if the baud rate is not
+///   supported.
+///
+/// [`helpers::init`]: super::init
+pub fn log_to_serial(handle: Handle, baud_rate: u64) -> Result {
+    attach_serial(handle, baud_rate)?;
+    unsafe { LOGGER.update_filters(|filters| filters.serial.enable()) };
+    Ok(())
+}
+
+fn attach_serial(handle: Handle, baud_rate: u64) -> Result {
+    let mut protocol = unsafe {
+        boot::open_protocol::<Serial>(
+            OpenProtocolParams {
+                handle,
+                agent: boot::image_handle(),
+                controller: None,
+            },
+            OpenProtocolAttributes::GetProtocol,
+        )
+    }?;
+    let port = protocol.get_mut().ok_or(Status::UNSUPPORTED)?;
+    serial::set_baud_rate(port, baud_rate)?;
+    let port: *mut Serial = port;
+
+    // Protocols opened with `GetProtocol` don't need to be closed, so keep
+    // it open for as long as the logger uses it.
+    core::mem::forget(protocol);
+    unsafe { LOGGER.set_serial(port) };
+    Ok(())
+}
+
+/// Appends the log output to the file at `path` on the file system of
+/// `handle` as well. The file is created if it doesn't exist.
+///
+/// The output is buffered in memory and written to the file when 4 KiB
+/// have been logged, when the log is flushed with
+/// [`log::Log::flush`], on panic with the `panic_handler` feature, and
+/// before boot services are exited through [`boot::exit_boot_services`].
+/// Logging to the file stops when boot services are exited.
+///
+/// This requires the logger to be set up with [`helpers::init`]. Calling
+/// this function again replaces the previous file. If the file sink wasn't
+/// configured with a [`LoggerBuilder`], all records are written to the
+/// file.
+///
+/// # Errors
+/// * [`Status::UNSUPPORTED`]: `handle` doesn't support the
+///   [`SimpleFileSystem`] protocol, or `path` is a directory.
+/// * Errors of [`SimpleFileSystem::open_volume`] and [`File::open`], e.g.
+///   [`Status::WRITE_PROTECTED`] for read-only file systems.
+///
+/// [`helpers::init`]: super::init
+pub fn log_to_file(handle: Handle, path: &CStr16) -> Result {
+    attach_file(handle, path)?;
+    unsafe { LOGGER.update_filters(|filters| filters.file.enable()) };
+    Ok(())
+}
+
+fn attach_file(handle: Handle, path: &CStr16) -> Result {
+    let mut sfs = unsafe {
+        boot::open_protocol::<SimpleFileSystem>(
+            OpenProtocolParams {
+                handle,
+                agent: boot::image_handle(),
+                controller: None,
+            },
+            OpenProtocolAttributes::GetProtocol,
+        )
+    }?;
+    let mut file = sfs
+        .open_volume()?
+        .open(path, FileMode::CreateReadWrite, FileAttribute::empty())?
+        .into_regular_file()
+        .ok_or(Status::UNSUPPORTED)?;
+    file.set_position(RegularFile::END_OF_FILE)?;
+
+    LOGGER.file.with(|state| {
+        if let Some(old) = state {
+            let _ = old.flush();
+        }
+        *state = Some(FileLog {
+            file,
+            buffer: [0; FILE_BUFFER_SIZE],
+            len: 0,
+        });
+    });
+    Ok(())
+}
+
+/// Reports the log records as status codes with the [`StatusCode`] protocol
+/// as well, so that they reach the status code handlers of the platform,
+/// e.g. the event log of a BMC.
+///
+/// Errors are reported as error codes of major severity, warnings as error
+/// codes of minor severity, and other records as debug codes. The message of
+/// a record is attached to the status code as an ASCII string of up to 255
+/// characters. Reporting stops when boot services are exited through
+/// [`boot::exit_boot_services`].
+///
+/// This requires the logger to be set up with [`helpers::init`]. If the
+/// status code sink wasn't configured with a [`LoggerBuilder`], all records
+/// are reported.
+///
+/// # Errors
+/// * [`Status::NOT_FOUND`]: the [`StatusCode`] protocol is not available.
+///
+/// [`helpers::init`]: super::init
+pub fn log_to_status_code() -> Result {
+    attach_status_code()?;
+    unsafe { LOGGER.update_filters(|filters| filters.status_code.enable()) };
+    Ok(())
+}
+
+fn attach_status_code() -> Result {
+    let handle = boot::get_handle_for_protocol::<StatusCode>()?;
+    let protocol = unsafe {
+        boot::open_protocol::<StatusCode>(
+            OpenProtocolParams {
+                handle,
+                agent: boot::image_handle(),
+                controller: None,
+            },
+            OpenProtocolAttributes::GetProtocol,
+        )
+    }?;
+    let status_code: *const StatusCode = protocol.get().ok_or(Status::UNSUPPORTED)?;
+
+    // Protocols opened with `GetProtocol` don't need to be closed, so keep
+    // it open for as long as the logger uses it.
+    core::mem::forget(protocol);
+    LOGGER
+        .status_code
+        .store(status_code.cast_mut(), Ordering::Release);
+    Ok(())
+}
+
+/// Writes the records in the ring buffer of the memory sink to `writer`,
+/// oldest first. Nothing is written if the memory sink is not enabled.
+///
+/// If older records have been replaced by newer ones, the output starts
+/// with the first complete line.
+pub fn dump_memory_log(writer: &mut dyn Write) -> fmt::Result {
+    let mut result = Ok(());
+    LOGGER.memory.with(|memory| {
+        if let Some(memory) = memory {
+            result = memory.write_to(writer);
+        }
+    });
+    result
+}
+
+/// Writes the records in the ring buffer of the memory sink to the console,
+/// serial, debugcon and file sinks that are active, between marker lines.
+///
+/// This is used by the panic handler, so that the records leading up to a
+/// panic can be recovered even if they were filtered out of the other sinks.
+#[cfg_attr(not(feature = "panic_handler"), allow(dead_code))]
+pub(super) fn dump_memory_log_to_sinks() {
+    LOGGER.memory.with(|memory| {
+        let Some(memory) = memory else {
+            return;
+        };
+        // Errors are ignored, like in `Logger::log`.
+        if let Some(writer) = unsafe { LOGGER.output().as_mut() } {
+            let _ = write_history(memory, writer);
+        }
+        if let Some(serial) = unsafe { LOGGER.serial.load(Ordering::Acquire).as_mut() } {
+            let _ = write_history(memory, &mut SerialConsole::new(serial));
+        }
+        LOGGER.file.with(|state| {
+            if let Some(file) = state {
+                let _ = write_history(memory, file);
+            }
+        });
+        #[cfg(all(
+            any(target_arch = "x86", target_arch = "x86_64"),
+            feature = "log-debugcon"
+        ))]
+        {
+            let _ = write_history(memory, &mut Debugcon::default());
+        }
+    });
+}
+
+/// Writes the text of `memory` to `writer`, between marker lines.
+fn write_history(memory: &mut RingBuffer, writer: &mut dyn Write) -> fmt::Result {
+    writeln!(writer, "---- Log history ----")?;
+    memory.write_to(writer)?;
+    writeln!(writer, "---- End of log history ----")
+}
+
+/// A file that log records are appended to.
+struct FileLog {
+    file: RegularFile,
+    buffer: [u8; FILE_BUFFER_SIZE],
+    len: usize,
+}
+
+impl FileLog {
+    /// Writes the buffered output to the file.
+    fn flush(&mut self) -> Result {
+        let len = core::mem::take(&mut self.len);
+        self.file
+            .write(&self.buffer[..len])
+            .map_err(|err| err.to_err_without_payload())?;
+        self.file.flush()
+    }
+}
+
+impl fmt::Write for FileLog {
     fn write_str(&mut self, s: &str) -> fmt::Result {
-        for &byte in s.as_bytes() {
-            unsafe {
-                core::arch::asm!("outb %al, %dx", in("al") byte, in("dx") Self::IO_PORT, options(att_syntax))
+        for chunk in s.as_bytes().chunks(FILE_BUFFER_SIZE) {
+            if self.len + chunk.len() > FILE_BUFFER_SIZE {
+                self.flush().map_err(|_| fmt::Error)?;
+            }
+            self.buffer[self.len..][..chunk.len()].copy_from_slice(chunk);
+            self.len += chunk.len();
+        }
+        Ok(())
+    }
+}
+
+/// ASCII message of a status code. Characters that are not ASCII are
+/// replaced with `?`, and the message is truncated when the buffer is full.
+struct StatusCodeMessage {
+    buffer: [u8; STATUS_CODE_MESSAGE_SIZE],
+    len: usize,
+}
+
+impl StatusCodeMessage {
+    const fn new() -> Self {
+        Self {
+            buffer: [0; STATUS_CODE_MESSAGE_SIZE],
+            len: 0,
+        }
+    }
+
+    fn as_cstr8(&mut self) -> &CStr8 {
+        self.buffer[self.len] = 0;
+        // OK to unwrap: the message is null-terminated and contains no
+        // other null characters.
+        CStr8::from_bytes_with_nul(&self.buffer[..=self.len]).unwrap()
+    }
+}
+
+impl fmt::Write for StatusCodeMessage {
+    fn write_str(&mut self, s: &str) -> fmt::Result {
+        for c in s.chars() {
+            // Keep room for the null terminator.
+            if self.len == STATUS_CODE_MESSAGE_SIZE - 1 {
+                break;
+            }
+            self.buffer[self.len] = match c {
+                '\0' => b' ',
+                c if c.is_ascii() => c as u8,
+                _ => b'?',
             };
+            self.len += 1;
         }
         Ok(())
     }
 }
 
-/// Logging implementation which writes to a UEFI output stream.
+/// Reports `record` as a status code with its message.
+fn report_record(status_code: &StatusCode, record: &Record) -> Result {
+    let code_type = match record.level() {
+        log::Level::Error => CodeType::Error(Severity::MAJOR),
+        log::Level::Warn => CodeType::Error(Severity::MINOR),
+        _ => CodeType::Debug,
+    };
+    let value = SubClass::EFI_APPLICATION.error(ErrorOperation::NON_SPECIFIC);
+    let mut message = StatusCodeMessage::new();
+    let _ = write!(message, "{}", record.args());
+    status_code.report_with_message(code_type, value, 0, None, message.as_cstr8())
+}
+
+/// State of a sink of the logger, guarded against reentrant use, e.g. by a
+/// panic while writing to a file.
+struct Slot<T> {
+    busy: AtomicBool,
+    state: UnsafeCell<Option<T>>,
+}
+
+impl<T> Slot<T> {
+    const fn new() -> Self {
+        Self {
+            busy: AtomicBool::new(false),
+            state: UnsafeCell::new(None),
+        }
+    }
+
+    /// Runs `f` on the state, unless it is already in use.
+    fn with(&self, f: impl FnOnce(&mut Option<T>)) {
+        if self.busy.swap(true, Ordering::Acquire) {
+            return;
+        }
+        f(unsafe { &mut *self.state.get() });
+        self.busy.store(false, Ordering::Release);
+    }
+}
+
+/// The filters of all sinks.
+#[derive(Clone, Copy, Debug)]
+struct Filters {
+    console: LogFilter,
+    serial: LogFilter,
+    debugcon: LogFilter,
+    file: LogFilter,
+    memory: LogFilter,
+    status_code: LogFilter,
+}
+
+impl Filters {
+    const fn new() -> Self {
+        Self {
+            console: LogFilter::OFF,
+            serial: LogFilter::OFF,
+            debugcon: LogFilter::OFF,
+            file: LogFilter::OFF,
+            memory: LogFilter::OFF,
+            status_code: LogFilter::OFF,
+        }
+    }
+
+    const fn all(&self) -> [&LogFilter; 6] {
+        [
+            &self.console,
+            &self.serial,
+            &self.debugcon,
+            &self.file,
+            &self.memory,
+            &self.status_code,
+        ]
+    }
+
+    /// Returns the highest level of records that can pass any filter,
+    /// limited to the static maximum level of the `log` crate.
+    fn max_level(&self) -> LevelFilter {
+        self.all()
+            .iter()
+            .map(|filter| filter.max_level())
+            .fold(LevelFilter::Off, Ord::max)
+            .min(log::STATIC_MAX_LEVEL)
+    }
+}
+
+impl LogFilter {
+    /// Lets all records pass if the filter lets none pass.
+    fn enable(&mut self) {
+        if self.max_level() == LevelFilter::Off {
+            *self = Self::default();
+        }
+    }
+}
+
+/// Logging implementation which writes to the configured sinks.
 ///
 /// If this logger is used as a global logger, you must disable it using the
 /// `disable` method before exiting UEFI boot services in order to prevent
 /// undefined behaviour from inadvertent logging.
-#[derive(Debug)]
 pub struct Logger {
     writer: AtomicPtr<Output>,
+    serial: AtomicPtr<Serial>,
+    status_code: AtomicPtr<StatusCode>,
+    file: Slot<FileLog>,
+    memory: Slot<RingBuffer<'static>>,
+    filters: UnsafeCell<Filters>,
 }
 
 impl Logger {
@@ -98,6 +765,11 @@ impl Logger {
     pub const fn new() -> Self {
         Self {
             writer: AtomicPtr::new(ptr::null_mut()),
+            serial: AtomicPtr::new(ptr::null_mut()),
+            status_code: AtomicPtr::new(ptr::null_mut()),
+            file: Slot::new(),
+            memory: Slot::new(),
+            filters: UnsafeCell::new(Filters::new()),
         }
     }
 
@@ -126,53 +798,126 @@ impl Logger {
         self.writer.store(output, Ordering::Release);
     }
 
+    /// Set the [`Serial`] device to which the logger will write in addition
+    /// to the [`Output`]. A null pointer stops logging to a serial device.
+    ///
+    /// # Safety
+    ///
+    /// The same requirements as for [`set_output`] apply to `serial`.
+    ///
+    /// [`set_output`]: Self::set_output
+    pub unsafe fn set_serial(&self, serial: *mut Serial) {
+        self.serial.store(serial, Ordering::Release);
+    }
+
+    /// Changes the filters of the sinks with `f`, and updates the maximum
+    /// level of the `log` crate.
+    ///
+    /// # Safety
+    ///
+    /// Must not be called while a record is logged.
+    unsafe fn update_filters(&self, f: impl FnOnce(&mut Filters)) {
+        let filters = unsafe { &mut *self.filters.get() };
+        f(filters);
+        log::set_max_level(filters.max_level());
+    }
+
+    const fn filters(&self) -> &Filters {
+        unsafe { &*self.filters.get() }
+    }
+
     /// Disable the logger.
+    ///
+    /// Only the sinks that don't depend on boot services are kept.
     pub fn disable(&self) {
-        unsafe { self.set_output(ptr::null_mut()) }
+        unsafe {
+            self.set_output(ptr::null_mut());
+            self.set_serial(ptr::null_mut());
+        }
+        self.status_code.store(ptr::null_mut(), Ordering::Release);
+        // Dropping the file would close it, which isn't possible once boot
+        // services have been exited.
+        self.file.with(|state| core::mem::forget(state.take()));
+    }
+}
+
+impl fmt::Debug for Logger {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Logger")
+            .field("writer", &self.writer)
+            .field("serial", &self.serial)
+            .field("status_code", &self.status_code)
+            .field("filters", self.filters())
+            .finish_non_exhaustive()
     }
 }
 
 impl log::Log for Logger {
-    fn enabled(&self, _metadata: &log::Metadata) -> bool {
-        // We decide in `log` already if something is printed. We do not
-        // need micro optimizations here.
-        true
-    }
-
-    fn log(&self, record: &log::Record) {
-        if let Some(writer) = unsafe { self.output().as_mut() } {
-            // Ignore all errors. Since we're in the logger implementation we
-            // can't log the error. We also don't want to panic, since logging
-            // is generally not critical functionality.
-            let _ = DecoratedLog::write(
-                writer,
-                record.level(),
-                record.args(),
-                record.file().unwrap_or("<unknown file>"),
-                record.line().unwrap_or(0),
-            );
+    fn enabled(&self, metadata: &Metadata) -> bool {
+        self.filters()
+            .all()
+            .iter()
+            .any(|filter| filter.enabled(metadata))
+    }
+
+    fn log(&self, record: &Record) {
+        // Ignore all errors of the sinks. Since we're in the logger
+        // implementation we can't log the error. We also don't want to
+        // panic, since logging is generally not critical functionality.
+        let filters = self.filters();
+        let metadata = record.metadata();
+
+        if filters.console.enabled(metadata) {
+            if let Some(writer) = unsafe { self.output().as_mut() } {
+                let _ = write_record(writer, record);
+            }
+        }
+
+        if filters.serial.enabled(metadata) {
+            if let Some(serial) = unsafe { self.serial.load(Ordering::Acquire).as_mut() } {
+                let _ = write_record(&mut SerialConsole::new(serial), record);
+            }
+        }
+
+        if filters.file.enabled(metadata) {
+            self.file.with(|state| {
+                if let Some(file) = state {
+                    let _ = write_record(file, record);
+                }
+            });
+        }
+
+        if filters.status_code.enabled(metadata) {
+            if let Some(status_code) = unsafe { self.status_code.load(Ordering::Acquire).as_ref() }
+            {
+                let _ = report_record(status_code, record);
+            }
+        }
+
+        if filters.memory.enabled(metadata) {
+            self.memory.with(|state| {
+                if let Some(memory) = state {
+                    let _ = write_record(memory, record);
+                }
+            });
         }
 
         #[cfg(all(
             any(target_arch = "x86", target_arch = "x86_64"),
             feature = "log-debugcon"
         ))]
-        {
-            // Ignore all errors. Since we're in the logger implementation we
-            // can't log the error. We also don't want to panic, since logging
-            // is generally not critical functionality.
-            let _ = DecoratedLog::write(
-                &mut DebugconWriter,
-                record.level(),
-                record.args(),
-                record.file().unwrap_or("<unknown file>"),
-                record.line().unwrap_or(0),
-            );
+        if filters.debugcon.enabled(metadata) {
+            let _ = write_record(&mut Debugcon::default(), record);
         }
     }
 
     fn flush(&self) {
-        // This simple logger does not buffer output.
+        // Only the file output is buffered.
+        self.file.with(|state| {
+            if let Some(file) = state {
+                let _ = file.flush();
+            }
+        });
     }
 }
 
@@ -180,6 +925,17 @@ impl log::Log for Logger {
 unsafe impl Sync for Logger {}
 unsafe impl Send for Logger {}
 
+/// Writes `record` with its level and location to `writer`.
+fn write_record<W: fmt::Write>(writer: &mut W, record: &Record) -> fmt::Result {
+    DecoratedLog::write(
+        writer,
+        record.level(),
+        record.args(),
+        record.file().unwrap_or("<unknown file>"),
+        record.line().unwrap_or(0),
+    )
+}
+
 /// Writer wrapper which prints a log level in front of every line of text
 ///
 /// This is less easy than it sounds because...
@@ -255,3 +1011,71 @@ impl<W: fmt::Write> fmt::Write for DecoratedLog<'_, '_, W> {
         Ok(())
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cstr8;
+    use log::Level;
+
+    fn enabled(filter: &LogFilter, target: &str, level: Level) -> bool {
+        filter.enabled(&Metadata::builder().target(target).level(level).build())
+    }
+
+    #[test]
+    fn test_log_filter() {
+        let filter = LogFilter::new(LevelFilter::Warn).with_targets(&[
+            ("app::net", LevelFilter::Trace),
+            ("app::net::dhcp", LevelFilter::Info),
+        ]);
+        assert!(enabled(&filter, "app", Level::Warn));
+        assert!(!enabled(&filter, "app", Level::Info));
+        assert!(enabled(&filter, "app::net", Level::Trace));
+        assert!(enabled(&filter, "app::net::tcp", Level::Debug));
+        assert!(!enabled(&filter, "app::network", Level::Info));
+        assert!(!enabled(&filter, "app::net::dhcp::client", Level::Debug));
+        assert!(enabled(&filter, "app::net::dhcp::client", Level::Info));
+        assert_eq!(filter.max_level(), LevelFilter::Trace);
+
+        assert!(!enabled(&LogFilter::OFF, "app", Level::Error));
+        assert!(enabled(&LogFilter::default(), "app", Level::Trace));
+    }
+
+    #[test]
+    fn test_status_code_message() {
+        let mut message = StatusCodeMessage::new();
+        write!(message, "caf\u{e9} {}", 42).unwrap();
+        assert_eq!(message.as_cstr8(), cstr8!("caf? 42"));
+
+        let mut message = StatusCodeMessage::new();
+        write!(message, "{:300}", "").unwrap();
+        assert_eq!(
+            message.as_cstr8().as_bytes().len(),
+            STATUS_CODE_MESSAGE_SIZE
+        );
+    }
+
+    #[test]
+    fn test_write_history() {
+        let mut buffer = [0; 16];
+        let mut memory = RingBuffer::new(&mut buffer);
+        memory.push(b"[ INFO]: a\n");
+        let mut s = alloc::string::String::new();
+        write_history(&mut memory, &mut s).unwrap();
+        assert_eq!(
+            s,
+            "---- Log history ----\n[ INFO]: a\n---- End of log history ----\n"
+        );
+    }
+
+    #[test]
+    fn test_filters_max_level() {
+        let mut filters = Filters::new();
+        assert_eq!(filters.max_level(), LevelFilter::Off);
+        filters.serial = LevelFilter::Info.into();
+        filters.file.enable();
+        assert_eq!(filters.file, LogFilter::default());
+        filters.serial.enable();
+        assert_eq!(filters.serial, LevelFilter::Info.into());
+    }
+}
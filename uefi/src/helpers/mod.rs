@@ -9,10 +9,22 @@
 //! - an implementation of  [`log::Log`] (feature `logger`) which logs to
 //!   the stdout text protocol of UEFI (as long as boot services were not
 //!   excited) and to the [debugcon device](https://phip1611.de/blog/how-to-use-qemus-debugcon-feature/)
-//!   (only on x86)  (feature `log-debugcon`).
+//!   (only on x86)  (feature `log-debugcon`). The output can also be sent
+//!   to a serial port with [`log_to_serial`], to a file with
+//!   [`log_to_file`] and to a ring buffer in memory. Each sink has its own
+//!   [`LogFilter`], configured with a [`LoggerBuilder`] passed to
+//!   [`init_with_logger`].
+//! - a global logger of the `defmt` crate (feature `defmt`), which writes
+//!   the binary frames to a serial port with [`defmt_to_serial`] or to the
+//!   debugcon device
 //! - [`print!`][print_macro] and [`println!`][println_macro] macros defaulting
 //!   to the uefi boot service stdout stream
-//! - default panic handler (feature `panic_handler`)
+//! - default panic handler (feature `panic_handler`), which prints a
+//!   backtrace (see [`StackFrames`]) and writes the ring buffer of the logger
+//!   to the other log sinks
+//!
+//! The helpers are installed with [`init`], or selectively with a
+//! [`Builder`].
 //!
 //! **PLEASE NOTE** that these helpers are meant for the pre exit boot service
 //! epoch.
@@ -21,21 +33,42 @@
 //! [println_macro]: uefi::println!
 
 use crate::Result;
+pub use backtrace::StackFrames;
+pub use builder::{Builder, Guard};
 #[doc(hidden)]
 pub use println::_print;
 
+mod backtrace;
+mod builder;
+#[cfg(feature = "defmt")]
+mod defmt;
+#[cfg(all(feature = "defmt", any(target_arch = "x86", target_arch = "x86_64")))]
+pub use defmt::defmt_to_debugcon;
+#[cfg(feature = "defmt")]
+pub use defmt::defmt_to_serial;
 #[cfg(feature = "global_allocator")]
 mod global_allocator;
 #[cfg(feature = "logger")]
 mod logger;
+#[cfg(feature = "logger")]
+pub use logger::{
+    LogFilter, LoggerBuilder, dump_memory_log, log_to_file, log_to_serial, log_to_status_code,
+};
 #[cfg(feature = "panic_handler")]
 mod panic_handler;
+#[cfg(feature = "panic_handler")]
+pub use panic_handler::PanicAction;
 mod println;
+#[cfg(feature = "logger")]
+mod ring_buffer;
 
 /// Initialize all helpers defined in [`uefi::helpers`] whose Cargo features
 /// are activated.
 ///
 /// This must be called as early as possible, before trying to use logging.
+/// The logger writes to the console and, with the `log-debugcon` feature, to
+/// the debugcon device. Use [`init_with_logger`] to choose other sinks, or
+/// a [`Builder`] to choose which helpers are installed.
 ///
 /// **PLEASE NOTE** that these helpers are meant for the pre exit boot service
 /// epoch. Limited functionality might work after exiting them, such as logging
@@ -46,15 +79,25 @@ mod println;
 /// This function may panic if called more than once.
 #[allow(clippy::missing_const_for_fn)]
 pub fn init() -> Result<()> {
-    // Set up logging.
-    #[cfg(feature = "logger")]
-    unsafe {
-        logger::init();
-    }
-
+    Builder::new().init()?.keep();
     Ok(())
 }
 
+/// Initialize all helpers like [`init`], with the log sinks of `logger`.
+///
+/// # Errors
+///
+/// Errors of opening the serial port or the file of the sinks. The other
+/// sinks are set up nevertheless.
+///
+/// # Panics
+///
+/// This function may panic if called more than once.
+#[cfg(feature = "logger")]
+pub fn init_with_logger(logger: LoggerBuilder<'_>) -> Result<()> {
+    unsafe { logger::init(logger) }
+}
+
 #[allow(clippy::missing_const_for_fn)]
 pub(crate) fn exit() {
     #[cfg(feature = "logger")]
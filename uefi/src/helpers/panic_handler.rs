@@ -1,66 +1,199 @@
 // SPDX-License-Identifier: MIT OR Apache-2.0
 
+use core::sync::atomic::{AtomicU8, AtomicU64, Ordering};
 use core::time::Duration;
 
-use crate::{boot, println};
+use super::StackFrames;
+use crate::boot::{self, OpenProtocolAttributes, OpenProtocolParams};
+use crate::println;
+use crate::proto::loaded_image::LoadedImage;
 use cfg_if::cfg_if;
 
+/// What the panic handler does after printing the panic message, see
+/// [`Builder::panic_action`].
+///
+/// [`Builder::panic_action`]: super::Builder::panic_action
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+#[repr(u8)]
+pub enum PanicAction {
+    /// Shut the system down, or exit QEMU with the `qemu` feature.
+    #[default]
+    Shutdown,
+    /// Reset the system, e.g. to boot the next entry of the boot order.
+    Reset,
+    /// Halt the processor, so the message stays on the screen.
+    Halt,
+    /// Exit the image with [`Status::ABORTED`], which returns to the image
+    /// that started it, e.g. a test harness or the UEFI Shell. Falls back
+    /// to halting if boot services are no longer active.
+    ///
+    /// Resources of the image aren't cleaned up, so the image must not leave
+    /// event callbacks or protocol interfaces installed when it panics.
+    ///
+    /// [`Status::ABORTED`]: crate::Status::ABORTED
+    Exit,
+}
+
+static ACTION: AtomicU8 = AtomicU8::new(PanicAction::Shutdown as u8);
+
+/// Time to wait before the action, in milliseconds.
+static STALL_MS: AtomicU64 = AtomicU64::new(10_000);
+
+pub(super) fn configure(action: PanicAction, stall: Duration) {
+    ACTION.store(action as u8, Ordering::Relaxed);
+    let stall_ms = u64::try_from(stall.as_millis()).unwrap_or(u64::MAX);
+    STALL_MS.store(stall_ms, Ordering::Relaxed);
+}
+
 #[panic_handler]
 fn panic_handler(info: &core::panic::PanicInfo) -> ! {
+    // Show the log records leading up to the panic, if they were kept in
+    // memory.
+    #[cfg(feature = "logger")]
+    super::logger::dump_memory_log_to_sinks();
+
     println!("[PANIC]: {}", info);
+    print_backtrace();
+
+    // Write buffered log output, e.g. to a log file.
+    log::logger().flush();
 
     // Give the user some time to read the message
-    if boot::are_boot_services_active() {
-        boot::stall(Duration::from_secs(10));
+    let stall_ms = STALL_MS.load(Ordering::Relaxed);
+    if stall_ms == 0 {
+        // Act immediately.
+    } else if boot::are_boot_services_active() {
+        boot::stall(Duration::from_millis(stall_ms));
     } else {
         let mut dummy = 0u64;
         // FIXME: May need different counter values in debug & release builds
-        for i in 0..300_000_000 {
+        for i in 0..stall_ms.saturating_mul(30_000) {
             unsafe {
                 core::ptr::write_volatile(&mut dummy, i);
             }
         }
     }
 
-    cfg_if! {
-        if #[cfg(all(target_arch = "x86_64", feature = "qemu"))] {
-            // If running in QEMU, use the f4 exit port to signal the error and exit
-            use qemu_exit::QEMUExit;
-            let custom_exit_success = 3;
-            let qemu_exit_handle = qemu_exit::X86::new(0xF4, custom_exit_success);
-            qemu_exit_handle.exit_failure();
-        } else {
-            // If the system table is available, use UEFI's standard shutdown mechanism
-            if let Some(st) = crate::table::system_table_raw() {
-                if !unsafe { st.as_ref().runtime_services }.is_null() {
-                    crate::runtime::reset(crate::runtime::ResetType::SHUTDOWN, crate::Status::ABORTED, None);
+    let action = ACTION.load(Ordering::Relaxed);
+    if action == PanicAction::Exit as u8 {
+        if boot::are_boot_services_active() {
+            // SAFETY: there's no way to clean up after a panic. Users of
+            // `PanicAction::Exit` must not leave resources behind.
+            unsafe {
+                boot::exit(
+                    boot::image_handle(),
+                    crate::Status::ABORTED,
+                    0,
+                    core::ptr::null_mut(),
+                )
+            }
+        }
+        log::error!("Could not exit, please reset the system manually...");
+    } else if action == PanicAction::Reset as u8 {
+        if let Some(st) = crate::table::system_table_raw() {
+            if !unsafe { st.as_ref().runtime_services }.is_null() {
+                crate::runtime::reset(
+                    crate::runtime::ResetType::COLD,
+                    crate::Status::ABORTED,
+                    None,
+                );
+            }
+        }
+        log::error!("Could not reset, please reset the system manually...");
+    } else if action == PanicAction::Shutdown as u8 {
+        cfg_if! {
+            if #[cfg(all(target_arch = "x86_64", feature = "qemu"))] {
+                // If running in QEMU, use the f4 exit port to signal the error and exit
+                use qemu_exit::QEMUExit;
+                let custom_exit_success = 3;
+                let qemu_exit_handle = qemu_exit::X86::new(0xF4, custom_exit_success);
+                qemu_exit_handle.exit_failure();
+            } else {
+                // If the system table is available, use UEFI's standard shutdown mechanism
+                if let Some(st) = crate::table::system_table_raw() {
+                    if !unsafe { st.as_ref().runtime_services }.is_null() {
+                        crate::runtime::reset(crate::runtime::ResetType::SHUTDOWN, crate::Status::ABORTED, None);
+                    }
                 }
+
+                // If we don't have any shutdown mechanism handy, the best we can do is loop
+                log::error!("Could not shut down, please power off the system manually...");
             }
+        }
+    }
 
-            // If we don't have any shutdown mechanism handy, the best we can do is loop
-            log::error!("Could not shut down, please power off the system manually...");
+    halt()
+}
 
-            cfg_if! {
-                if #[cfg(target_arch = "x86_64")] {
-                    loop {
-                        unsafe {
-                            // Try to at least keep CPU from running at 100%
-                            core::arch::asm!("hlt", options(nomem, nostack));
-                        }
-                    }
-                } else if #[cfg(target_arch = "aarch64")] {
-                    loop {
-                        unsafe {
-                            // Try to at least keep CPU from running at 100%
-                            core::arch::asm!("hlt 420", options(nomem, nostack));
-                        }
-                    }
-                } else {
-                    loop {
-                        // just run forever dammit how do you return never anyway
-                    }
+/// Halts the processor forever.
+fn halt() -> ! {
+    cfg_if! {
+        if #[cfg(target_arch = "x86_64")] {
+            loop {
+                unsafe {
+                    // Try to at least keep CPU from running at 100%
+                    core::arch::asm!("hlt", options(nomem, nostack));
+                }
+            }
+        } else if #[cfg(target_arch = "aarch64")] {
+            loop {
+                unsafe {
+                    // Try to at least keep CPU from running at 100%
+                    core::arch::asm!("hlt 420", options(nomem, nostack));
                 }
             }
+        } else {
+            loop {
+                // just run forever dammit how do you return never anyway
+            }
         }
     }
 }
+
+/// Prints the return addresses on the stack as offsets from the base of the
+/// image, which can be symbolized offline against the PDB or ELF file of the
+/// image after adding its preferred base address.
+///
+/// The base of the image is only known while boot services are active. After
+/// that, the absolute addresses are printed.
+fn print_backtrace() {
+    let image = image_range();
+    match image {
+        Some((base, size)) => {
+            println!("Image base: {base:#x} (size {size:#x})");
+            println!("Backtrace (offsets from the image base):");
+        }
+        None => println!("Backtrace (absolute addresses):"),
+    }
+    for (i, address) in StackFrames::current().enumerate() {
+        match image {
+            Some((base, size)) if (base..base + size).contains(&address) => {
+                println!("  #{i:<2} {:#x}", address - base);
+            }
+            Some(_) => println!("  #{i:<2} {address:#x} (outside of the image)"),
+            None => println!("  #{i:<2} {address:#x}"),
+        }
+    }
+}
+
+/// Returns the base address and size of the running image.
+fn image_range() -> Option<(usize, usize)> {
+    if !boot::are_boot_services_active() {
+        return None;
+    }
+    // Opening the protocol non-exclusively works even if the image has
+    // opened it itself.
+    let loaded_image = unsafe {
+        boot::open_protocol::<LoadedImage>(
+            OpenProtocolParams {
+                handle: boot::image_handle(),
+                agent: boot::image_handle(),
+                controller: None,
+            },
+            OpenProtocolAttributes::GetProtocol,
+        )
+    }
+    .ok()?;
+    let (base, size) = loaded_image.info();
+    Some((base as usize, usize::try_from(size).ok()?))
+}
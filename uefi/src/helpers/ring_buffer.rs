@@ -0,0 +1,118 @@
+// SPDX-License-Identifier: MIT OR Apache-2.0
+
+//! A byte ring buffer for the in-memory log.
+
+use core::fmt::{self, Write};
+
+/// Text in a fixed-size buffer. When the buffer is full, new text replaces
+/// the oldest text.
+#[derive(Debug)]
+pub(super) struct RingBuffer<'a> {
+    buffer: &'a mut [u8],
+    /// Index of the oldest byte.
+    start: usize,
+    len: usize,
+    /// Whether text has been replaced.
+    wrapped: bool,
+}
+
+impl<'a> RingBuffer<'a> {
+    pub(super) const fn new(buffer: &'a mut [u8]) -> Self {
+        Self {
+            buffer,
+            start: 0,
+            len: 0,
+            wrapped: false,
+        }
+    }
+
+    /// Appends `bytes`, replacing the oldest bytes if the buffer is full.
+    pub(super) fn push(&mut self, mut bytes: &[u8]) {
+        let capacity = self.buffer.len();
+        if bytes.len() >= capacity {
+            self.wrapped |= self.len > 0 || bytes.len() > capacity;
+            bytes = &bytes[bytes.len() - capacity..];
+            self.start = 0;
+            self.len = 0;
+        }
+        for &byte in bytes {
+            self.buffer[(self.start + self.len) % capacity] = byte;
+            if self.len == capacity {
+                self.start = (self.start + 1) % capacity;
+                self.wrapped = true;
+            } else {
+                self.len += 1;
+            }
+        }
+    }
+
+    /// Writes the text in the buffer, oldest first, to `writer`.
+    ///
+    /// If text has been replaced, the incomplete first line is skipped.
+    pub(super) fn write_to(&mut self, writer: &mut dyn Write) -> fmt::Result {
+        // Move the oldest byte to the start of the buffer, so that the text
+        // is contiguous. The text only wraps around when the buffer is full.
+        self.buffer.rotate_left(self.start);
+        self.start = 0;
+
+        let mut text = &self.buffer[..self.len];
+        if self.wrapped {
+            let line_end = text.iter().position(|&b| b == b'\n').map_or(0, |i| i + 1);
+            text = &text[line_end..];
+        }
+        for chunk in text.utf8_chunks() {
+            writer.write_str(chunk.valid())?;
+            if !chunk.invalid().is_empty() {
+                writer.write_char(char::REPLACEMENT_CHARACTER)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+impl Write for RingBuffer<'_> {
+    fn write_str(&mut self, s: &str) -> fmt::Result {
+        self.push(s.as_bytes());
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloc::string::String;
+
+    fn contents(ring: &mut RingBuffer) -> String {
+        let mut s = String::new();
+        ring.write_to(&mut s).unwrap();
+        s
+    }
+
+    #[test]
+    fn test_ring_buffer() {
+        let mut buffer = [0; 12];
+        let mut ring = RingBuffer::new(&mut buffer);
+        assert_eq!(contents(&mut ring), "");
+
+        ring.push(b"one\ntwo\n");
+        assert_eq!(contents(&mut ring), "one\ntwo\n");
+
+        // The oldest text is replaced, and the partial line is skipped.
+        ring.push(b"three\n");
+        assert_eq!(contents(&mut ring), "two\nthree\n");
+        ring.push(b"ab");
+        assert_eq!(contents(&mut ring), "three\nab");
+
+        ring.push(b"0123456789\nxyz");
+        assert_eq!(contents(&mut ring), "xyz");
+    }
+
+    #[test]
+    fn test_ring_buffer_utf8() {
+        let mut buffer = [0; 8];
+        let mut ring = RingBuffer::new(&mut buffer);
+        // A character cut in half at the start is replaced.
+        ring.push("\u{e9}\u{e9}\u{e9}\u{e9}x".as_bytes());
+        assert_eq!(contents(&mut ring), "\u{fffd}\u{e9}\u{e9}\u{e9}x");
+    }
+}
@@ -0,0 +1,164 @@
+// SPDX-License-Identifier: MIT OR Apache-2.0
+
+use crate::boot::{
+    self, EventType, OpenProtocolAttributes, OpenProtocolParams, ScopedProtocol, Tpl,
+};
+use crate::proto::console::text::{Input, Key};
+use crate::{Event, Handle, Identify, Result, ResultExt, Status};
+use alloc::vec::Vec;
+
+/// Keystrokes of all [`Input`] devices, such as USB keyboards, serial
+/// terminals and IPMI Serial-over-LAN consoles.
+///
+/// The console input of the system table often covers only some of these
+/// devices, depending on the `ConIn` variable, so keys typed on other
+/// devices are lost. `ConsoleInputs` reads from all of them in turn instead.
+/// Each keystroke is only reported once, also if a device is read through
+/// the console splitter of the firmware as well.
+///
+/// Devices that are connected later, e.g. hot-plugged USB keyboards, are
+/// picked up with a protocol notification. Devices are opened only for each
+/// read, so they can be removed at any time and the firmware console keeps
+/// working.
+#[derive(Debug)]
+pub struct ConsoleInputs {
+    handles: Vec<Handle>,
+    /// Signaled when an [`Input`] protocol is installed.
+    notify: Option<Event>,
+    next: usize,
+}
+
+impl ConsoleInputs {
+    /// Finds all input devices and registers for new ones.
+    ///
+    /// # Errors
+    /// * [`Status::OUT_OF_RESOURCES`]: the notification event could not be
+    ///   created.
+    pub fn new() -> Result<Self> {
+        let notify = unsafe { boot::create_event(EventType::empty(), Tpl::CALLBACK, None, None)? };
+        if let Err(err) = boot::register_protocol_notify(&Input::GUID, &notify) {
+            boot::close_event(notify)?;
+            return Err(err);
+        }
+
+        let mut inputs = Self {
+            handles: Vec::new(),
+            notify: Some(notify),
+            next: 0,
+        };
+        inputs.rescan();
+        Ok(inputs)
+    }
+
+    /// Returns the handles of the input devices.
+    #[must_use]
+    pub fn handles(&self) -> &[Handle] {
+        &self.handles
+    }
+
+    /// Returns the next keystroke of any device, or `None` if no key was
+    /// pressed.
+    ///
+    /// Devices are read in turn, so a device with many pending keys doesn't
+    /// hide the keys of others. Devices that fail to read, e.g. because
+    /// they were removed, are dropped.
+    pub fn read_key(&mut self) -> Result<Option<Key>> {
+        self.check_for_new_devices();
+
+        let mut remaining = self.handles.len();
+        while remaining > 0 {
+            remaining -= 1;
+            self.next %= self.handles.len();
+            let handle = self.handles[self.next];
+            match Self::read_device(handle) {
+                Ok(Some(key)) => {
+                    self.next += 1;
+                    return Ok(Some(key));
+                }
+                Ok(None) => self.next += 1,
+                Err(_) => {
+                    self.handles.remove(self.next);
+                }
+            }
+        }
+        Ok(None)
+    }
+
+    /// Waits for a keystroke on any device and returns it.
+    ///
+    /// # Errors
+    /// * [`Status::NOT_READY`]: there is no device to wait for.
+    pub fn wait_for_key(&mut self) -> Result<Key> {
+        loop {
+            if let Some(key) = self.read_key()? {
+                return Ok(key);
+            }
+
+            let mut events: Vec<Event> = self
+                .handles
+                .iter()
+                .filter_map(|&handle| {
+                    Self::open(handle)
+                        .ok()
+                        .and_then(|input| input.wait_for_key_event())
+                })
+                .collect();
+            if let Some(notify) = &self.notify {
+                events.push(unsafe { notify.unsafe_clone() });
+            }
+            if events.is_empty() {
+                return Err(Status::NOT_READY.into());
+            }
+            boot::wait_for_event(&mut events).discard_errdata()?;
+        }
+    }
+
+    /// Adds the devices found since the last scan, if any.
+    fn check_for_new_devices(&mut self) {
+        let signaled = self.notify.as_ref().is_some_and(|notify| {
+            matches!(
+                boot::check_event(unsafe { notify.unsafe_clone() }),
+                Ok(true)
+            )
+        });
+        if signaled {
+            self.rescan();
+        }
+    }
+
+    fn rescan(&mut self) {
+        for handle in boot::find_handles::<Input>().unwrap_or_default() {
+            if !self.handles.contains(&handle) {
+                self.handles.push(handle);
+            }
+        }
+    }
+
+    fn open(handle: Handle) -> Result<ScopedProtocol<Input>> {
+        // SAFETY: the protocol is only used while no other code runs, so it
+        // can't be uninstalled while it's open.
+        unsafe {
+            boot::open_protocol::<Input>(
+                OpenProtocolParams {
+                    handle,
+                    agent: boot::image_handle(),
+                    controller: None,
+                },
+                OpenProtocolAttributes::GetProtocol,
+            )
+        }
+    }
+
+    fn read_device(handle: Handle) -> Result<Option<Key>> {
+        Self::open(handle)?.read_key()
+    }
+}
+
+impl Drop for ConsoleInputs {
+    fn drop(&mut self) {
+        if let Some(notify) = self.notify.take() {
+            // Closing the event also removes the protocol notification.
+            let _ = boot::close_event(notify);
+        }
+    }
+}
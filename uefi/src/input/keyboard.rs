@@ -0,0 +1,341 @@
+// SPDX-License-Identifier: MIT OR Apache-2.0
+
+use crate::boot;
+use crate::proto::console::text::{InputEx, Key, KeyStroke, ScanCode};
+use crate::{Char16, Result, ResultExt, Status};
+use alloc::collections::VecDeque;
+use bitflags::bitflags;
+use core::future::Future;
+use core::pin::Pin;
+use core::task::{Context, Poll};
+
+/// Number of keystrokes buffered by [`KeyStream::new`].
+const DEFAULT_CAPACITY: usize = 32;
+
+bitflags! {
+    /// Modifier keys of a [`Chord`], without distinguishing the left and
+    /// right keys.
+    #[derive(Clone, Copy, Debug, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+    pub struct Modifiers: u8 {
+        /// Either shift key.
+        const SHIFT = 0x01;
+        /// Either control key.
+        const CTRL = 0x02;
+        /// Either alt key.
+        const ALT = 0x04;
+        /// Either logo key.
+        const LOGO = 0x08;
+    }
+}
+
+impl Modifiers {
+    /// Returns the modifiers pressed during `stroke`. Empty if the input
+    /// device doesn't report them.
+    #[must_use]
+    pub fn of(stroke: &KeyStroke) -> Self {
+        let mut modifiers = Self::empty();
+        modifiers.set(Self::SHIFT, stroke.shift());
+        modifiers.set(Self::CTRL, stroke.ctrl());
+        modifiers.set(Self::ALT, stroke.alt());
+        modifiers.set(Self::LOGO, stroke.logo());
+        modifiers
+    }
+}
+
+/// A key pressed together with a set of modifiers, e.g. Ctrl+Alt+Del.
+///
+/// Letters are matched case-insensitively, and the control characters that
+/// some firmware reports for Ctrl+letter are treated as the letter.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct Chord {
+    /// Modifiers that must be pressed. Other modifiers must not be pressed.
+    pub modifiers: Modifiers,
+    /// The key that must be pressed.
+    pub key: Key,
+}
+
+impl Chord {
+    /// Ctrl+Alt+Del.
+    pub const CTRL_ALT_DEL: Self = Self::new(
+        Modifiers::CTRL.union(Modifiers::ALT),
+        Key::Special(ScanCode::DELETE),
+    );
+
+    /// Creates a chord from a key and its modifiers.
+    #[must_use]
+    pub const fn new(modifiers: Modifiers, key: Key) -> Self {
+        Self { modifiers, key }
+    }
+
+    /// Creates a chord of a printable character, or `None` if `c` isn't a
+    /// UCS-2 character.
+    #[must_use]
+    pub fn char(modifiers: Modifiers, c: char) -> Option<Self> {
+        Char16::try_from(c)
+            .ok()
+            .map(|c| Self::new(modifiers, Key::Printable(c)))
+    }
+
+    /// Creates a chord of the function key F`n`, or `None` if `n` is not in
+    /// `1..=24`.
+    #[must_use]
+    pub const fn function_key(n: u8, modifiers: Modifiers) -> Option<Self> {
+        let scan_code = match n {
+            1..=12 => ScanCode::FUNCTION_1.0 + (n as u16 - 1),
+            13..=24 => ScanCode::FUNCTION_13.0 + (n as u16 - 13),
+            _ => return None,
+        };
+        Some(Self::new(modifiers, Key::Special(ScanCode(scan_code))))
+    }
+
+    /// Returns whether `stroke` is this chord.
+    #[must_use]
+    pub fn matches(&self, stroke: &KeyStroke) -> bool {
+        let modifiers = Modifiers::of(stroke);
+        if modifiers != self.modifiers {
+            return false;
+        }
+        match (self.key, stroke.key) {
+            (Key::Printable(expected), Key::Printable(actual)) => {
+                let expected = fold(char::from(expected), false);
+                expected == fold(char::from(actual), modifiers.contains(Modifiers::CTRL))
+            }
+            (expected, actual) => expected == actual,
+        }
+    }
+}
+
+/// Folds letters to lowercase, and control characters to letters if `ctrl`
+/// is pressed.
+fn fold(c: char, ctrl: bool) -> char {
+    match c {
+        '\x01'..='\x1a' if ctrl => char::from(c as u8 - 1 + b'a'),
+        c => c.to_ascii_lowercase(),
+    }
+}
+
+/// Buffered stream of keystrokes from an [`InputEx`] device.
+///
+/// The firmware only buffers a few keystrokes, so applications doing
+/// long-running work should call [`poll`] regularly to move them into the
+/// stream's buffer. When the buffer is full, the oldest keystrokes are
+/// dropped. Partial keystrokes, which only change the modifier state, are
+/// skipped.
+///
+/// [`poll`]: Self::poll
+#[derive(Debug)]
+pub struct KeyStream<'a> {
+    input: &'a mut InputEx,
+    buffer: VecDeque<KeyStroke>,
+    capacity: usize,
+}
+
+impl<'a> KeyStream<'a> {
+    /// Creates a stream that buffers up to 32 keystrokes.
+    #[must_use]
+    pub fn new(input: &'a mut InputEx) -> Self {
+        Self::with_capacity(input, DEFAULT_CAPACITY)
+    }
+
+    /// Creates a stream that buffers up to `capacity` keystrokes.
+    ///
+    /// # Panics
+    /// Panics if `capacity` is zero.
+    #[must_use]
+    pub fn with_capacity(input: &'a mut InputEx, capacity: usize) -> Self {
+        assert!(capacity > 0, "capacity must not be zero");
+        Self {
+            input,
+            buffer: VecDeque::with_capacity(capacity),
+            capacity,
+        }
+    }
+
+    /// Moves all keystrokes available from the device into the buffer, and
+    /// returns the number of buffered keystrokes.
+    pub fn poll(&mut self) -> Result<usize> {
+        while let Some(stroke) = self.input.read_key()? {
+            if !stroke.is_partial() {
+                push(&mut self.buffer, self.capacity, stroke);
+            }
+        }
+        Ok(self.buffer.len())
+    }
+
+    /// Returns the number of buffered keystrokes, without polling.
+    #[must_use]
+    pub fn len(&self) -> usize {
+        self.buffer.len()
+    }
+
+    /// Returns whether no keystrokes are buffered, without polling.
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.buffer.is_empty()
+    }
+
+    /// Returns the oldest keystroke, or `None` if no keystroke is available.
+    pub fn try_next(&mut self) -> Result<Option<KeyStroke>> {
+        self.poll()?;
+        Ok(self.buffer.pop_front())
+    }
+
+    /// Waits for a keystroke and returns it.
+    ///
+    /// # Errors
+    /// * [`Status::UNSUPPORTED`]: the device provides no event to wait for
+    ///   keys.
+    /// * Errors of the device.
+    pub fn wait_for_key(&mut self) -> Result<KeyStroke> {
+        loop {
+            if let Some(stroke) = self.try_next()? {
+                return Ok(stroke);
+            }
+            let event = self.input.wait_for_key_event().ok_or(Status::UNSUPPORTED)?;
+            boot::wait_for_event(&mut [event]).discard_errdata()?;
+        }
+    }
+
+    /// Returns a future that resolves to the next keystroke.
+    ///
+    /// Key presses don't wake up the future, it asks to be polled again
+    /// instead. This suits the simple executors used in UEFI applications,
+    /// which poll all tasks in turn.
+    pub const fn next_key(&mut self) -> NextKey<'_, 'a> {
+        NextKey { stream: self }
+    }
+
+    /// Removes the oldest keystroke matching one of `chords` from the
+    /// stream, and returns the index of the chord it matched. Other
+    /// keystrokes stay in the stream.
+    pub fn take_chord(&mut self, chords: &[Chord]) -> Result<Option<usize>> {
+        self.poll()?;
+        Ok(take_chord(&mut self.buffer, chords))
+    }
+
+    /// Discards all buffered keystrokes, including those not yet read from
+    /// the device.
+    pub fn clear(&mut self) -> Result {
+        self.poll()?;
+        self.buffer.clear();
+        Ok(())
+    }
+}
+
+fn push(buffer: &mut VecDeque<KeyStroke>, capacity: usize, stroke: KeyStroke) {
+    if buffer.len() == capacity {
+        buffer.pop_front();
+    }
+    buffer.push_back(stroke);
+}
+
+fn take_chord(buffer: &mut VecDeque<KeyStroke>, chords: &[Chord]) -> Option<usize> {
+    let (i, chord) = buffer.iter().enumerate().find_map(|(i, stroke)| {
+        let chord = chords.iter().position(|chord| chord.matches(stroke))?;
+        Some((i, chord))
+    })?;
+    buffer.remove(i);
+    Some(chord)
+}
+
+/// Future returned by [`KeyStream::next_key`].
+#[derive(Debug)]
+pub struct NextKey<'s, 'a> {
+    stream: &'s mut KeyStream<'a>,
+}
+
+impl Future for NextKey<'_, '_> {
+    type Output = Result<KeyStroke>;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        match self.get_mut().stream.try_next() {
+            Ok(Some(stroke)) => Poll::Ready(Ok(stroke)),
+            Ok(None) => {
+                cx.waker().wake_by_ref();
+                Poll::Pending
+            }
+            Err(err) => Poll::Ready(Err(err)),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::proto::console::text::{KeyShiftState, KeyToggleState};
+
+    fn stroke(key: Key, shift_state: KeyShiftState) -> KeyStroke {
+        KeyStroke {
+            key,
+            shift_state: shift_state | KeyShiftState::SHIFT_STATE_VALID,
+            toggle_state: KeyToggleState::empty(),
+        }
+    }
+
+    fn printable(c: char) -> Key {
+        Key::Printable(Char16::try_from(c).unwrap())
+    }
+
+    #[test]
+    fn test_chord_matches() {
+        let ctrl_alt = KeyShiftState::LEFT_CONTROL_PRESSED | KeyShiftState::RIGHT_ALT_PRESSED;
+        let del = stroke(Key::Special(ScanCode::DELETE), ctrl_alt);
+        assert!(Chord::CTRL_ALT_DEL.matches(&del));
+        let del = stroke(
+            Key::Special(ScanCode::DELETE),
+            KeyShiftState::LEFT_ALT_PRESSED,
+        );
+        assert!(!Chord::CTRL_ALT_DEL.matches(&del));
+
+        let shift_f5 = Chord::function_key(5, Modifiers::SHIFT).unwrap();
+        assert_eq!(shift_f5.key, Key::Special(ScanCode::FUNCTION_5));
+        assert_eq!(
+            Chord::function_key(24, Modifiers::empty()).unwrap().key,
+            Key::Special(ScanCode::FUNCTION_24)
+        );
+        assert_eq!(Chord::function_key(25, Modifiers::empty()), None);
+        let f5 = stroke(
+            Key::Special(ScanCode::FUNCTION_5),
+            KeyShiftState::RIGHT_SHIFT_PRESSED,
+        );
+        assert!(shift_f5.matches(&f5));
+
+        let ctrl_c = Chord::char(Modifiers::CTRL, 'c').unwrap();
+        let ctrl = KeyShiftState::RIGHT_CONTROL_PRESSED;
+        assert!(ctrl_c.matches(&stroke(printable('c'), ctrl)));
+        assert!(ctrl_c.matches(&stroke(printable('C'), ctrl)));
+        assert!(ctrl_c.matches(&stroke(printable('\x03'), ctrl)));
+        assert!(!ctrl_c.matches(&stroke(printable('c'), KeyShiftState::empty())));
+        // Without control, the control character is not folded.
+        let tab = Chord::char(Modifiers::empty(), '\t').unwrap();
+        assert!(tab.matches(&stroke(printable('\t'), KeyShiftState::empty())));
+        assert!(!tab.matches(&stroke(printable('i'), KeyShiftState::empty())));
+    }
+
+    #[test]
+    fn test_modifiers_not_reported() {
+        let mut stroke = stroke(printable('x'), KeyShiftState::LEFT_CONTROL_PRESSED);
+        assert_eq!(Modifiers::of(&stroke), Modifiers::CTRL);
+        stroke.shift_state.remove(KeyShiftState::SHIFT_STATE_VALID);
+        assert_eq!(Modifiers::of(&stroke), Modifiers::empty());
+    }
+
+    #[test]
+    fn test_buffer() {
+        let mut buffer = VecDeque::new();
+        for c in ['a', 'b', 'c'] {
+            push(&mut buffer, 2, stroke(printable(c), KeyShiftState::empty()));
+        }
+        assert_eq!(buffer.len(), 2);
+        assert_eq!(buffer[0].key, printable('b'));
+
+        let chords = [
+            Chord::CTRL_ALT_DEL,
+            Chord::char(Modifiers::empty(), 'c').unwrap(),
+        ];
+        assert_eq!(take_chord(&mut buffer, &chords), Some(1));
+        assert_eq!(take_chord(&mut buffer, &chords), None);
+        assert_eq!(buffer.len(), 1);
+        assert_eq!(buffer[0].key, printable('b'));
+    }
+}
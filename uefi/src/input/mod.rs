@@ -0,0 +1,23 @@
+// SPDX-License-Identifier: MIT OR Apache-2.0
+
+//! High-level access to input devices.
+//!
+//! [`KeyStream`] buffers keystrokes from the [`InputEx`] protocol, can be
+//! polled during long-running work or awaited, and detects hotkeys such as
+//! Ctrl+Alt+Del with [`Chord`]s.
+//!
+//! [`ConsoleInputs`] reads keys from all keyboards and serial terminals,
+//! including those that aren't part of the firmware's console input.
+//!
+//! [`PointerEvents`] combines all mice, touchpads and touchscreens into a
+//! single stream of events in screen coordinates.
+//!
+//! [`InputEx`]: crate::proto::console::text::InputEx
+
+mod console;
+mod keyboard;
+mod pointer;
+
+pub use console::ConsoleInputs;
+pub use keyboard::{Chord, KeyStream, Modifiers, NextKey};
+pub use pointer::{PointerButtons, PointerEvent, PointerEvents, PointerKind};
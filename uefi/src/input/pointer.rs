@@ -0,0 +1,418 @@
+// SPDX-License-Identifier: MIT OR Apache-2.0
+
+use crate::boot::{self, ScopedProtocol};
+use crate::proto::console::gop::ModeInfo;
+use crate::proto::console::pointer::{
+    AbsolutePointer, AbsolutePointerMode, AbsolutePointerState, Pointer, PointerState,
+};
+use crate::{Event, Result, ResultExt, Status};
+use alloc::vec::Vec;
+use bitflags::bitflags;
+
+/// Default pointer speed of relative devices, in pixels per millimeter.
+const DEFAULT_SPEED: u32 = 8;
+
+bitflags! {
+    /// Buttons of a pointer device that are pressed.
+    #[derive(Clone, Copy, Debug, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+    pub struct PointerButtons: u8 {
+        /// The left mouse button, or a touch of a touchscreen.
+        const PRIMARY = 0x01;
+        /// The right mouse button, or the alternate button of an absolute
+        /// pointer device.
+        const SECONDARY = 0x02;
+    }
+}
+
+/// Kind of device a [`PointerEvent`] came from.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum PointerKind {
+    /// A device reporting relative movement, e.g. a mouse or touchpad.
+    Relative,
+    /// A device reporting absolute coordinates, e.g. a touchscreen.
+    Absolute,
+}
+
+/// A change of the state of a pointer device.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct PointerEvent {
+    /// Position of the pointer in screen coordinates after the change.
+    pub position: (usize, usize),
+    /// Buttons pressed after the change.
+    pub buttons: PointerButtons,
+    /// Movement of the scroll wheel, in device-specific units. Positive
+    /// values scroll towards the user.
+    pub scroll: i32,
+    /// Kind of device the event came from.
+    pub kind: PointerKind,
+}
+
+/// Pointer events of all [`Pointer`] and [`AbsolutePointer`] devices,
+/// normalized to screen coordinates.
+///
+/// Relative movement is accumulated into a single pointer position, which
+/// absolute devices set directly. The position is limited to the screen
+/// given by the [`ModeInfo`] of the graphics output.
+///
+/// The devices are opened exclusively, so the firmware doesn't see their
+/// input while the `PointerEvents` exists.
+pub struct PointerEvents {
+    devices: Vec<Device>,
+    screen: (usize, usize),
+    position: (usize, usize),
+    buttons: PointerButtons,
+    speed: u32,
+    next: usize,
+}
+
+impl PointerEvents {
+    /// Opens all pointer devices, and places the pointer in the center of a
+    /// screen with the resolution of `mode`.
+    ///
+    /// Devices that can't be opened, e.g. because they are in use, are
+    /// skipped.
+    ///
+    /// # Errors
+    /// * [`Status::NOT_FOUND`]: there is no pointer device that can be
+    ///   opened.
+    pub fn new(mode: &ModeInfo) -> Result<Self> {
+        let mut devices = Vec::new();
+        for handle in boot::find_handles::<Pointer>().unwrap_or_default() {
+            if let Ok(pointer) = boot::open_protocol_exclusive::<Pointer>(handle) {
+                devices.push(Device::Relative {
+                    pointer,
+                    remainder: (0, 0),
+                });
+            }
+        }
+        for handle in boot::find_handles::<AbsolutePointer>().unwrap_or_default() {
+            if let Ok(pointer) = boot::open_protocol_exclusive::<AbsolutePointer>(handle) {
+                devices.push(Device::Absolute(pointer));
+            }
+        }
+        if devices.is_empty() {
+            return Err(Status::NOT_FOUND.into());
+        }
+
+        let screen = mode.resolution();
+        Ok(Self {
+            devices,
+            screen,
+            position: (screen.0 / 2, screen.1 / 2),
+            buttons: PointerButtons::empty(),
+            speed: DEFAULT_SPEED,
+            next: 0,
+        })
+    }
+
+    /// Returns the number of devices.
+    #[must_use]
+    pub fn device_count(&self) -> usize {
+        self.devices.len()
+    }
+
+    /// Returns the position of the pointer in screen coordinates.
+    #[must_use]
+    pub const fn position(&self) -> (usize, usize) {
+        self.position
+    }
+
+    /// Moves the pointer to `(x, y)`, limited to the screen.
+    pub fn set_position(&mut self, x: usize, y: usize) {
+        self.position = clamp_to_screen((x, y), self.screen);
+    }
+
+    /// Returns the buttons that are pressed.
+    #[must_use]
+    pub const fn buttons(&self) -> PointerButtons {
+        self.buttons
+    }
+
+    /// Sets the speed of relative devices in pixels per millimeter of
+    /// movement. The default is 8.
+    pub const fn set_speed(&mut self, pixels_per_mm: u32) {
+        self.speed = pixels_per_mm;
+    }
+
+    /// Changes the screen resolution to that of `mode`, e.g. after a mode
+    /// change. The pointer keeps its relative position on the screen.
+    pub const fn set_mode(&mut self, mode: &ModeInfo) {
+        let screen = mode.resolution();
+        self.position = (
+            rescale(self.position.0, self.screen.0, screen.0),
+            rescale(self.position.1, self.screen.1, screen.1),
+        );
+        self.screen = screen;
+    }
+
+    /// Returns the next state change of any device, or `None` if no device
+    /// has changed since the last call.
+    ///
+    /// Devices are checked in turn, so a busy device doesn't hide the
+    /// events of others.
+    pub fn poll(&mut self) -> Result<Option<PointerEvent>> {
+        for _ in 0..self.devices.len() {
+            let index = self.next;
+            self.next = (self.next + 1) % self.devices.len();
+            if let Some(event) = self.read_device(index)? {
+                return Ok(Some(event));
+            }
+        }
+        Ok(None)
+    }
+
+    /// Waits for the next state change of any device and returns it.
+    pub fn wait_for_event(&mut self) -> Result<PointerEvent> {
+        loop {
+            if let Some(event) = self.poll()? {
+                return Ok(event);
+            }
+            let mut events: Vec<Event> = self
+                .devices
+                .iter()
+                .filter_map(Device::wait_for_input_event)
+                .collect();
+            if events.is_empty() {
+                return Err(Status::UNSUPPORTED.into());
+            }
+            boot::wait_for_event(&mut events).discard_errdata()?;
+        }
+    }
+
+    fn read_device(&mut self, index: usize) -> Result<Option<PointerEvent>> {
+        let event = match &mut self.devices[index] {
+            Device::Relative { pointer, remainder } => {
+                let Some(state) = pointer.read_state()? else {
+                    return Ok(None);
+                };
+                let resolution = pointer.mode().resolution;
+                let has_button = pointer.mode().has_button;
+                self.position = apply_relative(
+                    self.position,
+                    self.screen,
+                    &state,
+                    [resolution[0], resolution[1]],
+                    self.speed,
+                    remainder,
+                );
+                let mut buttons = PointerButtons::empty();
+                buttons.set(PointerButtons::PRIMARY, has_button[0] && state.button[0]);
+                buttons.set(PointerButtons::SECONDARY, has_button[1] && state.button[1]);
+                PointerEvent {
+                    position: self.position,
+                    buttons,
+                    scroll: if resolution[2] != 0 {
+                        state.relative_movement[2]
+                    } else {
+                        0
+                    },
+                    kind: PointerKind::Relative,
+                }
+            }
+            Device::Absolute(pointer) => {
+                let Some(state) = pointer.read_state()? else {
+                    return Ok(None);
+                };
+                let mode = *pointer.mode();
+                if let Some(position) = absolute_position(&state, &mode, self.screen) {
+                    self.position = position;
+                }
+                let mut buttons = PointerButtons::empty();
+                buttons.set(
+                    PointerButtons::PRIMARY,
+                    state.active_buttons & AbsolutePointer::TOUCH_ACTIVE != 0,
+                );
+                buttons.set(
+                    PointerButtons::SECONDARY,
+                    state.active_buttons & AbsolutePointer::ALT_ACTIVE != 0,
+                );
+                PointerEvent {
+                    position: self.position,
+                    buttons,
+                    scroll: 0,
+                    kind: PointerKind::Absolute,
+                }
+            }
+        };
+        self.buttons = event.buttons;
+        Ok(Some(event))
+    }
+}
+
+impl core::fmt::Debug for PointerEvents {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.debug_struct("PointerEvents")
+            .field("devices", &self.devices.len())
+            .field("screen", &self.screen)
+            .field("position", &self.position)
+            .field("buttons", &self.buttons)
+            .field("speed", &self.speed)
+            .finish_non_exhaustive()
+    }
+}
+
+enum Device {
+    Relative {
+        pointer: ScopedProtocol<Pointer>,
+        /// Movement in counts that didn't amount to a whole pixel yet.
+        remainder: (i64, i64),
+    },
+    Absolute(ScopedProtocol<AbsolutePointer>),
+}
+
+impl Device {
+    fn wait_for_input_event(&self) -> Option<Event> {
+        match self {
+            Self::Relative { pointer, .. } => pointer.wait_for_input_event(),
+            Self::Absolute(pointer) => pointer.wait_for_input_event(),
+        }
+    }
+}
+
+/// Limits `position` to a screen of `(width, height)` pixels.
+fn clamp_to_screen(position: (usize, usize), screen: (usize, usize)) -> (usize, usize) {
+    (
+        position.0.min(screen.0.saturating_sub(1)),
+        position.1.min(screen.1.saturating_sub(1)),
+    )
+}
+
+/// Maps `value` from `0..from` to `0..to`.
+const fn rescale(value: usize, from: usize, to: usize) -> usize {
+    if from == 0 {
+        return 0;
+    }
+    (value as u128 * to as u128 / from as u128) as usize
+}
+
+/// Moves `position` by the relative movement of `state`.
+///
+/// `resolution` is given in counts per millimeter, and `remainder` holds the
+/// movement that didn't amount to a whole pixel yet. Devices without a
+/// resolution report their movement in pixels.
+fn apply_relative(
+    position: (usize, usize),
+    screen: (usize, usize),
+    state: &PointerState,
+    resolution: [u64; 2],
+    speed: u32,
+    remainder: &mut (i64, i64),
+) -> (usize, usize) {
+    let axis = |position: usize, movement: i32, resolution: u64, remainder: &mut i64| {
+        let movement = if resolution == 0 {
+            i64::from(movement)
+        } else {
+            let resolution = i64::try_from(resolution).unwrap_or(i64::MAX);
+            let counts = *remainder + i64::from(movement) * i64::from(speed);
+            *remainder = counts % resolution;
+            counts / resolution
+        };
+        let position = i64::try_from(position).unwrap_or(i64::MAX);
+        usize::try_from(position.saturating_add(movement).max(0)).unwrap_or(usize::MAX)
+    };
+    let x = axis(
+        position.0,
+        state.relative_movement[0],
+        resolution[0],
+        &mut remainder.0,
+    );
+    let y = axis(
+        position.1,
+        state.relative_movement[1],
+        resolution[1],
+        &mut remainder.1,
+    );
+    clamp_to_screen((x, y), screen)
+}
+
+/// Maps the coordinates of `state` to a screen of `(width, height)`
+/// pixels, or `None` if the device reports no usable range.
+fn absolute_position(
+    state: &AbsolutePointerState,
+    mode: &AbsolutePointerMode,
+    screen: (usize, usize),
+) -> Option<(usize, usize)> {
+    let axis = |value: u64, min: u64, max: u64, size: usize| {
+        let range = max.checked_sub(min).filter(|range| *range > 0)?;
+        let offset = value.clamp(min, max) - min;
+        let size = size.saturating_sub(1) as u128;
+        Some((u128::from(offset) * size / u128::from(range)) as usize)
+    };
+    Some((
+        axis(
+            state.current_x,
+            mode.absolute_min_x,
+            mode.absolute_max_x,
+            screen.0,
+        )?,
+        axis(
+            state.current_y,
+            mode.absolute_min_y,
+            mode.absolute_max_y,
+            screen.1,
+        )?,
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_apply_relative() {
+        let screen = (800, 600);
+        let mut remainder = (0, 0);
+        let mut state = PointerState {
+            relative_movement: [3, -2, 0],
+            button: [false; 2],
+        };
+
+        // 3 counts * 8 px/mm / 16 counts/mm = 1.5 px.
+        let position = apply_relative((100, 100), screen, &state, [16, 16], 8, &mut remainder);
+        assert_eq!(position, (101, 99));
+        assert_eq!(remainder, (8, 0));
+        let position = apply_relative(position, screen, &state, [16, 16], 8, &mut remainder);
+        assert_eq!(position, (103, 98));
+        assert_eq!(remainder, (0, 0));
+
+        // Movement is limited to the screen, and devices without a
+        // resolution move in pixels.
+        state.relative_movement = [1000, -1000, 0];
+        let position = apply_relative(position, screen, &state, [0, 0], 8, &mut remainder);
+        assert_eq!(position, (799, 0));
+    }
+
+    #[test]
+    fn test_absolute_position() {
+        let mode = AbsolutePointerMode {
+            absolute_min_x: 100,
+            absolute_max_x: 1100,
+            absolute_max_y: 500,
+            ..Default::default()
+        };
+        let mut state = AbsolutePointerState {
+            current_x: 600,
+            current_y: 500,
+            ..Default::default()
+        };
+        assert_eq!(
+            absolute_position(&state, &mode, (1001, 101)),
+            Some((500, 100))
+        );
+        state.current_x = 0;
+        assert_eq!(
+            absolute_position(&state, &mode, (1001, 101)),
+            Some((0, 100))
+        );
+
+        let mode = AbsolutePointerMode::default();
+        assert_eq!(absolute_position(&state, &mode, (1001, 101)), None);
+    }
+
+    #[test]
+    fn test_rescale() {
+        assert_eq!(rescale(400, 800, 1024), 512);
+        assert_eq!(rescale(5, 0, 1024), 0);
+        assert_eq!(clamp_to_screen((2000, 20), (1024, 768)), (1023, 20));
+    }
+}
@@ -136,6 +136,15 @@
 //!   using this feature, or no allocator at all if you don't need to
 //!   dynamically allocate any memory. Note that even without that feature,
 //!   some code might use the internal UEFI allocator.
+//! - `std`: Implement conversions to types of the standard library, e.g.
+//!   from [`Error`] to `std::io::Error`, for programs that use the [`std`
+//!   implementation](#rust-std-implementation) for UEFI and for tools on the
+//!   host. Implies `alloc`.
+//! - `std-interop`: Set up this crate from the `std` implementation for
+//!   UEFI, see `std_interop`. Only has an effect on UEFI targets, and needs
+//!   a nightly toolchain. Implies `std`.
+//! - `alloc_tracking`: Count the allocations of [`allocator::Allocator`],
+//!   to find memory leaks. See [`mem::report`].
 //! - `logger`: Logging implementation for the standard [`log`] crate
 //!   that prints output to the UEFI console. No buffering is done; this
 //!   is not a high-performance logger.
@@ -146,6 +155,32 @@
 //!   features] in the nightly compiler.
 //!   As example, in conjunction with the `alloc`-feature, this gate allows
 //!   the `allocator_api` on certain functions.
+//! - `fat`: Enable [`media::fat`] for reading FAT file systems directly
+//!   from a disk, without the firmware's file system driver.
+//! - `png`: Enable [`graphics::png`] for decoding PNG images.
+//! - `linux-loader`: Enable [`loader::linux`] for booting Linux kernels.
+//! - `embedded-storage`: Implement the traits of the `embedded-storage`
+//!   crate for the block devices of [`media`], see [`media::storage`].
+//! - `embedded-graphics`: Implement the drawing traits of the
+//!   `embedded-graphics` crate for the graphics output protocol, see
+//!   [`console::display`].
+//! - `embedded-io`: Implement the `Read` and `Write` traits of the
+//!   `embedded-io` crate for [`Serial`](proto::console::serial::Serial) and
+//!   [`DebugPort`](proto::debug::DebugPort), and with `alloc` the ones of
+//!   `embedded-io-async` for [`net::TcpStream`].
+//! - `rand_core`: Implement the `RngCore` and `CryptoRng` traits of the
+//!   `rand_core` crate for the [`Rng`](proto::rng::Rng) protocol.
+//! - `chrono`, `time`: Implement conversions between [`runtime::Time`] and
+//!   the date and time types of the `chrono` and `time` crates.
+//! - `defmt`: Provide the global logger of the `defmt` crate, see
+//!   [`helpers::defmt_to_serial`].
+//! - `smoltcp`: Implement the `Device` trait of the `smoltcp` crate over the
+//!   Simple Network protocol, see [`net::SnpDevice`].
+//! - `acpi`: Implement the `AcpiHandler` trait of the `acpi` crate, and
+//!   parse the ACPI tables of the firmware with [`system::acpi_tables`].
+//! - `getrandom`: Provide a custom backend of the `getrandom` crate that uses
+//!   the [`Rng`](proto::rng::Rng) protocol. It has to be selected with
+//!   `--cfg getrandom_backend="custom"` when building for a UEFI target.
 //! - `qemu`: Enable some code paths to adapt their execution when executed
 //!   in QEMU, such as using the special `qemu-exit` device when the panic
 //!   handler is called.
@@ -188,11 +223,12 @@
 //! the binary. For example, our [`#[entry]`][entry-macro] macro won't be
 //! required any longer. As the `std` implementation evolves over time, you'll
 //! need fewer and fewer abstractions of this crate. For everything not covered
-//! by the `std` implementation, you can obtain relevant structures to work with
-//! our crate via:
+//! by the `std` implementation, you can use the protocol wrappers of this
+//! crate after calling `std_interop::init()` (with the `std-interop` feature).
+//! The underlying structures are also available via:
 //! - `std::os::uefi::env::boot_services()`
-//! - `std::os::uefi::env::get_system_handle()`
-//! - `std::os::uefi::env::get_system_table()`
+//! - `std::os::uefi::env::image_handle()`
+//! - `std::os::uefi::env::system_table()`
 //!
 //! ## `r-efi`
 //!
@@ -230,6 +266,7 @@
 //! [unstable features]: https://doc.rust-lang.org/unstable-book/
 
 #![cfg_attr(all(feature = "unstable", feature = "alloc"), feature(allocator_api))]
+#![cfg_attr(all(feature = "std-interop", target_os = "uefi"), feature(uefi_std))]
 #![cfg_attr(docsrs, feature(doc_auto_cfg))]
 #![no_std]
 #![deny(
@@ -247,25 +284,58 @@
 
 #[cfg(feature = "alloc")]
 extern crate alloc;
+#[cfg(feature = "std")]
+extern crate std;
 // allow referring to self as ::uefi for macros to work universally (from this crate and from others)
 // see https://github.com/rust-lang/rust/issues/54647
 extern crate self as uefi;
-#[macro_use]
+#[macro_use(newtype_enum)]
 extern crate uefi_raw;
 
 #[macro_use]
 pub mod data_types;
 pub mod allocator;
+pub mod bench;
 pub mod boot;
 #[cfg(feature = "alloc")]
+pub mod boot_mgr;
+#[cfg(feature = "alloc")]
+pub mod config_store;
+pub mod console;
+pub mod driver;
+pub mod ffs;
+#[cfg(feature = "alloc")]
 pub mod fs;
+#[cfg(feature = "alloc")]
+pub mod graphics;
 pub mod helpers;
+#[cfg(feature = "alloc")]
+pub mod input;
+#[cfg(feature = "alloc")]
+pub mod loader;
+#[cfg(feature = "alloc")]
+pub mod measured_boot;
+#[cfg(feature = "alloc")]
+pub mod media;
 pub mod mem;
+#[cfg(feature = "alloc")]
+pub mod net;
+pub mod pe;
 pub mod prelude;
 pub mod proto;
 pub mod runtime;
+pub mod secure_boot;
+#[cfg(feature = "alloc")]
+pub mod smbios;
+#[cfg(all(feature = "std-interop", target_os = "uefi"))]
+pub mod std_interop;
 pub mod system;
 pub mod table;
+#[cfg(feature = "alloc")]
+pub mod task;
+pub mod test_harness;
+#[cfg(feature = "alloc")]
+pub mod tui;
 
 pub(crate) mod polyfill;
 
@@ -276,10 +346,5 @@ mod util;
 #[cfg(feature = "alloc")]
 pub use data_types::CString16;
 pub use data_types::{CStr8, CStr16, Char8, Char16, Event, Guid, Handle, Identify};
-pub use result::{Error, Result, ResultExt, Status, StatusExt};
-/// Re-export ucs2_cstr so that it can be used in the implementation of the
-/// cstr16 macro. It is hidden since it's not intended to be used directly.
-#[doc(hidden)]
-pub use ucs2::ucs2_cstr;
-pub use uefi_macros::entry;
-pub use uguid::guid;
+pub use result::{Error, Result, ResultExt, Status, StatusExt, Termination, Warned, WarningPolicy};
+pub use uefi_macros::{driver_entry, entry};
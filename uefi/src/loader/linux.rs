@@ -0,0 +1,413 @@
+// SPDX-License-Identifier: MIT OR Apache-2.0
+
+//! Booting Linux kernels with the EFI stub.
+//!
+//! Linux kernels for x86_64 and aarch64 built with `CONFIG_EFI_STUB` are
+//! UEFI applications. They take their command line from the load options,
+//! and load the initial ramdisk from a [`LoadFile2`] protocol that is
+//! installed on a handle with a vendor media device path of
+//! [`INITRD_MEDIA_GUID`]. [`LinuxBoot`] sets both up and starts the kernel.
+//!
+//! # Example
+//!
+//! ```no_run
+//! use uefi::cstr16;
+//! use uefi::loader::linux::LinuxBoot;
+//!
+//! # fn boot(kernel: &[u8], initrd: Vec<u8>) -> uefi::Result<(), Option<uefi::boot::ExitData>> {
+//! LinuxBoot::new(kernel)
+//!     .initrd(initrd)
+//!     .cmdline(cstr16!("root=/dev/sda2 quiet"))
+//!     .boot()
+//! # }
+//! ```
+//!
+//! [`LoadFile2`]: crate::proto::media::load_file::LoadFile2
+
+use crate::boot::{self, ExitData, LoadImageSource, OpenProtocolAttributes, OpenProtocolParams};
+use crate::mem::memory_map::MemoryType;
+use crate::pe::{LoadError, Machine, PeImage};
+use crate::proto::device_path::{DevicePath, DeviceSubType, DeviceType};
+use crate::proto::loaded_image::{LoadOptions, LoadedImage};
+use crate::proto::media::load_file::LoadFile2;
+use crate::{CStr16, CString16, Error, Guid, Handle, Result, Status, StatusExt, guid, table};
+use alloc::boxed::Box;
+use core::ffi::c_void;
+use core::ptr;
+use uefi_raw::Boolean;
+use uefi_raw::protocol::device_path::DevicePathProtocol;
+use uefi_raw::protocol::loaded_image::LoadedImageProtocol;
+use uefi_raw::protocol::media::LoadFile2Protocol;
+
+/// GUID of the vendor media device path node on which Linux looks for the
+/// [`LoadFile2`] protocol that provides the initial ramdisk.
+///
+/// [`LoadFile2`]: crate::proto::media::load_file::LoadFile2
+pub const INITRD_MEDIA_GUID: Guid = guid!("5568e427-68fc-4f3d-ac74-ca555231cc68");
+
+/// Device path of the initial ramdisk: a vendor media node with
+/// [`INITRD_MEDIA_GUID`], followed by an end node.
+static INITRD_DEVICE_PATH: [u8; 24] = initrd_device_path();
+
+const fn initrd_device_path() -> [u8; 24] {
+    let mut path = [0; 24];
+    path[0] = DeviceType::MEDIA.0;
+    path[1] = DeviceSubType::MEDIA_VENDOR.0;
+    path[2] = 20;
+    let guid = INITRD_MEDIA_GUID.to_bytes();
+    let mut i = 0;
+    while i < guid.len() {
+        path[4 + i] = guid[i];
+        i += 1;
+    }
+    path[20] = DeviceType::END.0;
+    path[21] = DeviceSubType::END_ENTIRE.0;
+    path[22] = 4;
+    path
+}
+
+/// Revision of the [`LoadedImage`] protocol installed for kernels that are
+/// loaded with the PE loader.
+const LOADED_IMAGE_REVISION: u32 = 0x1000;
+
+/// Interface of the [`LoadFile2`] protocol installed by [`Initrd`].
+///
+/// [`LoadFile2`]: crate::proto::media::load_file::LoadFile2
+#[repr(C)]
+struct InitrdProvider {
+    protocol: LoadFile2Protocol,
+    data: Box<[u8]>,
+}
+
+unsafe extern "efiapi" fn load_initrd(
+    this: *mut LoadFile2Protocol,
+    file_path: *const DevicePathProtocol,
+    boot_policy: Boolean,
+    buffer_size: *mut usize,
+    buffer: *mut c_void,
+) -> Status {
+    if this.is_null() || file_path.is_null() || buffer_size.is_null() {
+        return Status::INVALID_PARAMETER;
+    }
+    if bool::from(boot_policy) {
+        return Status::UNSUPPORTED;
+    }
+
+    // Safety: `this` is the protocol of an `InitrdProvider`, which is its
+    // first field.
+    let data = unsafe { &(*this.cast::<InitrdProvider>()).data };
+    let size = unsafe { &mut *buffer_size };
+    if buffer.is_null() || *size < data.len() {
+        *size = data.len();
+        return Status::BUFFER_TOO_SMALL;
+    }
+    // Safety: the caller provides a buffer of `*size` bytes.
+    unsafe { ptr::copy_nonoverlapping(data.as_ptr(), buffer.cast(), data.len()) };
+    *size = data.len();
+    Status::SUCCESS
+}
+
+/// Initial ramdisk that is provided to Linux with the [`LoadFile2`]
+/// protocol.
+///
+/// The protocol is installed on a new handle with the device path that
+/// Linux looks for, and uninstalled when this is dropped.
+///
+/// [`LoadFile2`]: crate::proto::media::load_file::LoadFile2
+#[derive(Debug)]
+pub struct Initrd {
+    handle: Handle,
+    provider: *mut InitrdProvider,
+}
+
+impl Initrd {
+    /// Installs the provider of the initial ramdisk `data`.
+    ///
+    /// # Errors
+    ///
+    /// * [`Status::ALREADY_STARTED`]: another initial ramdisk is installed.
+    /// * Errors of [`boot::install_protocol_interface`].
+    pub fn install(data: impl Into<Box<[u8]>>) -> Result<Self> {
+        if boot::locate_device_path::<LoadFile2>(&mut initrd_device_path_ref()).is_ok() {
+            return Err(Status::ALREADY_STARTED.into());
+        }
+
+        let provider = Box::into_raw(Box::new(InitrdProvider {
+            protocol: LoadFile2Protocol {
+                load_file: load_initrd,
+            },
+            data: data.into(),
+        }));
+
+        // Safety: the interfaces match their GUIDs, and stay valid until they
+        // are uninstalled.
+        let result = unsafe {
+            boot::install_protocol_interface(
+                None,
+                &DevicePathProtocol::GUID,
+                INITRD_DEVICE_PATH.as_ptr().cast(),
+            )
+            .and_then(|handle| {
+                boot::install_protocol_interface(
+                    Some(handle),
+                    &LoadFile2Protocol::GUID,
+                    provider.cast(),
+                )
+                .inspect_err(|_| {
+                    let _ = boot::uninstall_protocol_interface(
+                        handle,
+                        &DevicePathProtocol::GUID,
+                        INITRD_DEVICE_PATH.as_ptr().cast(),
+                    );
+                })
+            })
+        };
+        match result {
+            Ok(handle) => Ok(Self { handle, provider }),
+            Err(err) => {
+                // Safety: the provider was not installed.
+                drop(unsafe { Box::from_raw(provider) });
+                Err(err)
+            }
+        }
+    }
+
+    /// Returns the handle on which the protocols are installed.
+    #[must_use]
+    pub const fn handle(&self) -> Handle {
+        self.handle
+    }
+}
+
+impl Drop for Initrd {
+    fn drop(&mut self) {
+        // Safety: the interfaces were installed by `install`.
+        let uninstalled = unsafe {
+            boot::uninstall_protocol_interface(
+                self.handle,
+                &LoadFile2Protocol::GUID,
+                self.provider.cast(),
+            )
+        };
+        if uninstalled.is_err() {
+            // The protocol is still in use, so the provider is leaked.
+            return;
+        }
+        // Safety: the provider is no longer installed.
+        drop(unsafe { Box::from_raw(self.provider) });
+        let _ = unsafe {
+            boot::uninstall_protocol_interface(
+                self.handle,
+                &DevicePathProtocol::GUID,
+                INITRD_DEVICE_PATH.as_ptr().cast(),
+            )
+        };
+    }
+}
+
+fn initrd_device_path_ref() -> &'static DevicePath {
+    // OK to unwrap: the device path is valid.
+    <&DevicePath>::try_from(INITRD_DEVICE_PATH.as_slice()).unwrap()
+}
+
+/// Builder to boot a Linux kernel, see the [module documentation](self).
+#[derive(Debug)]
+pub struct LinuxBoot<'a> {
+    kernel: &'a [u8],
+    file_path: Option<&'a DevicePath>,
+    initrd: Option<Box<[u8]>>,
+    cmdline: LoadOptions,
+}
+
+impl<'a> LinuxBoot<'a> {
+    /// Creates a builder to boot the kernel image `kernel`.
+    #[must_use]
+    pub fn new(kernel: &'a [u8]) -> Self {
+        Self {
+            kernel,
+            file_path: None,
+            initrd: None,
+            cmdline: LoadOptions::new(),
+        }
+    }
+
+    /// Sets the file path of the kernel image, e.g. the path from which it
+    /// was read. This is not required to boot the kernel.
+    #[must_use]
+    pub const fn file_path(mut self, file_path: &'a DevicePath) -> Self {
+        self.file_path = Some(file_path);
+        self
+    }
+
+    /// Sets the initial ramdisk to provide to the kernel.
+    #[must_use]
+    pub fn initrd(mut self, initrd: impl Into<Box<[u8]>>) -> Self {
+        self.initrd = Some(initrd.into());
+        self
+    }
+
+    /// Sets the kernel command line. It is passed to the kernel as is.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the command line is larger than 4 GiB.
+    #[must_use]
+    pub fn cmdline(mut self, cmdline: &CStr16) -> Self {
+        self.cmdline = LoadOptions::from(CString16::from(cmdline));
+        self
+    }
+
+    /// Boots the kernel with [`boot::load_image`] and
+    /// [`boot::start_image`], so it is verified by the firmware if Secure
+    /// Boot is enabled.
+    ///
+    /// On success, this function does not return, since the kernel exits
+    /// boot services. If the kernel fails to boot, the initial ramdisk is
+    /// uninstalled again.
+    ///
+    /// # Errors
+    ///
+    /// * [`Status::LOAD_ERROR`]: the kernel is not a PE/COFF image.
+    /// * [`Status::UNSUPPORTED`]: the kernel is built for another
+    ///   architecture.
+    /// * Errors of [`Initrd::install`].
+    /// * Errors of [`loader::chainload`](super::chainload), e.g. the exit
+    ///   data of the kernel.
+    pub fn boot(mut self) -> Result<(), Option<ExitData>> {
+        self.check_kernel()?;
+        let _initrd = self.install_initrd()?;
+        super::chainload(
+            LoadImageSource::FromBuffer {
+                buffer: self.kernel,
+                file_path: self.file_path,
+            },
+            Some(&self.cmdline),
+        )
+    }
+
+    /// Boots the kernel by mapping it with the [`pe`](crate::pe) loader and
+    /// calling its entry point, without involving the firmware's image
+    /// verification.
+    ///
+    /// The kernel is passed a new handle with a [`LoadedImage`] protocol
+    /// that describes it. Since that handle is not known to the firmware as
+    /// an image, errors are only returned if the kernel returns from its
+    /// entry point.
+    ///
+    /// # Safety
+    ///
+    /// The kernel runs with the privileges of the caller, so the caller must
+    /// verify that it is trusted, e.g. with
+    /// [`secure_boot::authenticode`](crate::secure_boot::authenticode).
+    ///
+    /// # Errors
+    ///
+    /// * [`Status::LOAD_ERROR`]: the kernel is not a PE/COFF image, or it
+    ///   can't be relocated.
+    /// * [`Status::UNSUPPORTED`]: the kernel is built for another
+    ///   architecture.
+    /// * Errors of [`Initrd::install`].
+    /// * The status returned by the kernel.
+    pub unsafe fn boot_with_pe_loader(mut self) -> Result<(), Option<ExitData>> {
+        let kernel = self.check_kernel()?;
+        let mapped = kernel.load().map_err(|err| match err {
+            LoadError::Uefi(err) => Error::new(err.status(), None),
+            LoadError::UnsupportedMachine(_) => Error::new(Status::UNSUPPORTED, None),
+            _ => Error::new(Status::LOAD_ERROR, None),
+        })?;
+        let _initrd = self.install_initrd()?;
+
+        let parent = boot::image_handle();
+        // Safety: the protocol is only read.
+        let device = unsafe {
+            boot::open_protocol::<LoadedImage>(
+                OpenProtocolParams {
+                    handle: parent,
+                    agent: parent,
+                    controller: None,
+                },
+                OpenProtocolAttributes::GetProtocol,
+            )
+        }
+        .map_err(|err| Error::new(err.status(), None))?
+        .device();
+
+        let memory = mapped.as_slice();
+        let loaded_image = Box::new(LoadedImageProtocol {
+            revision: LOADED_IMAGE_REVISION,
+            parent_handle: parent.as_ptr(),
+            system_table: table::system_table_raw()
+                .map_or(ptr::null(), |table| table.as_ptr().cast_const()),
+            device_handle: Handle::opt_to_ptr(device),
+            file_path: self
+                .file_path
+                .map_or(ptr::null(), |path| path.as_ffi_ptr().cast()),
+            reserved: ptr::null(),
+            load_options_size: u32::try_from(self.cmdline.num_bytes()).unwrap(),
+            load_options: self.cmdline.as_ptr().cast(),
+            image_base: memory.as_ptr().cast(),
+            image_size: memory.len() as u64,
+            image_code_type: MemoryType::LOADER_CODE,
+            image_data_type: MemoryType::LOADER_DATA,
+            unload: None,
+        });
+        let interface: *const LoadedImageProtocol = &*loaded_image;
+
+        // Safety: the interface matches the GUID, and stays valid until it
+        // is uninstalled.
+        let handle = unsafe {
+            boot::install_protocol_interface(None, &LoadedImageProtocol::GUID, interface.cast())
+        }
+        .map_err(|err| Error::new(err.status(), None))?;
+
+        // Safety: the caller guarantees that the kernel is trusted.
+        let status = unsafe { mapped.start(handle) };
+
+        let uninstalled = unsafe {
+            boot::uninstall_protocol_interface(handle, &LoadedImageProtocol::GUID, interface.cast())
+        };
+        if uninstalled.is_err() {
+            // The protocol is still in use, so its interface is leaked.
+            Box::leak(loaded_image);
+        }
+        status.to_result_with_err(|_| None)
+    }
+
+    /// Checks that the kernel is a PE/COFF image for the current
+    /// architecture.
+    fn check_kernel(&self) -> Result<PeImage<'a>, Option<ExitData>> {
+        let kernel =
+            PeImage::parse(self.kernel).map_err(|_| Error::new(Status::LOAD_ERROR, None))?;
+        if kernel.machine() != Machine::NATIVE {
+            return Err(Error::new(Status::UNSUPPORTED, None));
+        }
+        Ok(kernel)
+    }
+
+    fn install_initrd(&mut self) -> Result<Option<Initrd>, Option<ExitData>> {
+        self.initrd
+            .take()
+            .map(Initrd::install)
+            .transpose()
+            .map_err(|err| Error::new(err.status(), None))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::proto::device_path::DevicePathNodeEnum;
+
+    #[test]
+    fn test_initrd_device_path() {
+        let path = initrd_device_path_ref();
+        let mut nodes = path.node_iter();
+        let node = nodes.next().unwrap();
+        let Ok(DevicePathNodeEnum::MediaVendor(vendor)) = node.as_enum() else {
+            panic!("not a vendor node");
+        };
+        assert_eq!(vendor.vendor_guid(), INITRD_MEDIA_GUID);
+        assert!(vendor.vendor_defined_data().is_empty());
+        assert!(nodes.next().is_none());
+    }
+}
@@ -0,0 +1,101 @@
+// SPDX-License-Identifier: MIT OR Apache-2.0
+
+//! Loading and starting other UEFI images.
+//!
+//! [`chainload`] wraps the sequence of boot services calls that a boot
+//! manager makes to start another image: load it with
+//! [`boot::load_image`], pass it its load options through the
+//! [`LoadedImage`] protocol, start it with [`boot::start_image`], and
+//! unload it again if it fails.
+//!
+//! With the `linux-loader` feature, the [`linux`] module boots Linux kernels
+//! with an initial ramdisk.
+
+use crate::boot::{
+    self, ExitData, LoadImageSource, OpenProtocolAttributes, OpenProtocolParams, ScopedProtocol,
+};
+use crate::proto::loaded_image::{LoadOptions, LoadedImage};
+use crate::{Error, Handle, Result};
+
+#[cfg(feature = "linux-loader")]
+pub mod linux;
+
+/// Loads the image from `source` as a child of the current image, starts
+/// it, and returns once it has exited.
+///
+/// Before starting the image:
+/// * The load options of the image are set to `options`, if any.
+/// * If the image has no device, e.g. because it was loaded from a buffer
+///   without a file path, its device is set to the device of the current
+///   image. This allows it to load files from the same file system.
+///
+/// If loading, preparing or starting the image fails, the image is
+/// unloaded again, so that a boot manager can try the next image.
+///
+/// # Errors
+///
+/// * Errors of [`boot::load_image`]. The error data is `None`.
+/// * Errors of [`boot::start_image_with_exit_data`]. The error data is the
+///   exit data of the image, if any.
+pub fn chainload(
+    source: LoadImageSource<'_>,
+    options: Option<&LoadOptions>,
+) -> Result<(), Option<ExitData>> {
+    let parent = boot::image_handle();
+    let image = boot::load_image(parent, source).map_err(|err| Error::new(err.status(), None))?;
+
+    if let Err(err) = prepare(parent, image, options) {
+        let _ = boot::unload_image(image);
+        return Err(Error::new(err.status(), None));
+    }
+
+    let result = boot::start_image_with_exit_data(image);
+    if result.is_err() {
+        // Images that returned an error are usually unloaded by the firmware
+        // already, so the result of this is ignored.
+        let _ = boot::unload_image(image);
+    }
+    result
+}
+
+/// Sets the load options and the device of the loaded `image`.
+///
+/// The opened protocols are closed again before returning, because the
+/// image handle is destroyed when an application exits.
+fn prepare(parent: Handle, image: Handle, options: Option<&LoadOptions>) -> Result {
+    let parent_device = open_loaded_image(parent)?.device();
+
+    let mut loaded_image = open_loaded_image(image)?;
+    if loaded_image.device().is_none() {
+        if let Some(device) = parent_device {
+            loaded_image.set_device(device);
+        }
+    }
+    if let Some(options) = options {
+        // The size fits into `u32`, see `LoadOptions::arg`.
+        let size = u32::try_from(options.num_bytes()).unwrap();
+        // Safety: the options are borrowed for the whole duration of
+        // `chainload`, so they outlive the image.
+        unsafe {
+            loaded_image.set_load_options(options.as_ptr().cast(), size);
+        }
+    }
+    Ok(())
+}
+
+/// Opens the [`LoadedImage`] protocol of `image` without exclusive access,
+/// so that the image can still open it itself.
+fn open_loaded_image(image: Handle) -> Result<ScopedProtocol<LoadedImage>> {
+    // Safety: the protocol isn't modified in a way that conflicts with other
+    // users of the protocol, and it's closed before the image is started.
+    unsafe {
+        boot::open_protocol::<LoadedImage>(
+            OpenProtocolParams {
+                handle: image,
+                agent: boot::image_handle(),
+                controller: None,
+            },
+            OpenProtocolAttributes::GetProtocol,
+        )
+    }
+}
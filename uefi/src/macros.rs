@@ -8,6 +8,10 @@
 /// An empty string containing just a null character can be created with either
 /// `cstr8!()` or `cstr8!("")`.
 ///
+/// Instead of a literal, the input can be any constant `&str` expression,
+/// such as a `const` item or an invocation of [`concat!`] or [`env!`].
+/// Multiple inputs separated by commas are concatenated.
+///
 /// # Example
 ///
 /// ```
@@ -19,6 +23,9 @@
 /// const EMPTY: &CStr8 = cstr8!();
 /// assert_eq!(EMPTY.as_bytes(), [0]);
 /// assert_eq!(cstr8!(""), EMPTY);
+///
+/// const NAME: &str = "ab";
+/// assert_eq!(cstr8!(NAME, "ÿ"), S);
 /// ```
 ///
 /// [`&CStr8`]: crate::CStr8
@@ -29,17 +36,18 @@ macro_rules! cstr8 {
         // SAFETY: `S` is a trivially correct Latin-1 C string.
         unsafe { $crate::CStr8::from_bytes_with_nul_unchecked(S) }
     }};
-    ($s:literal) => {{
+    ($($s:expr),+ $(,)?) => {{
         // Use `const` values here to force errors to happen at compile
         // time.
+        const STRS: &[&str] = &[$($s),+];
 
         // Add one for the null char.
-        const NUM_CHARS: usize = $crate::data_types::str_num_latin1_chars($s) + 1;
+        const NUM_CHARS: usize = $crate::data_types::strs_num_latin1_chars(STRS) + 1;
 
-        const VAL: [u8; NUM_CHARS] = $crate::data_types::str_to_latin1($s);
+        const VAL: [u8; NUM_CHARS] = $crate::data_types::strs_to_latin1(STRS);
 
-        // SAFETY: the `str_to_latin1` function always produces a valid Latin-1
-        // string with a trailing null character.
+        // SAFETY: the `strs_to_latin1` function always produces a valid
+        // Latin-1 string with a trailing null character.
         unsafe { $crate::CStr8::from_bytes_with_nul_unchecked(&VAL) }
     }};
 }
@@ -52,6 +60,11 @@ macro_rules! cstr8 {
 /// An empty string containing just a null character can be created with either
 /// `cstr16!()` or `cstr16!("")`.
 ///
+/// Instead of a literal, the input can be any constant `&str` expression,
+/// such as a `const` item or an invocation of [`concat!`] or [`env!`].
+/// Multiple inputs separated by commas are concatenated. This allows building
+/// strings like menu entries and paths without allocating at runtime.
+///
 /// # Example
 ///
 /// ```
@@ -63,6 +76,12 @@ macro_rules! cstr8 {
 /// const EMPTY: &CStr16 = cstr16!();
 /// assert_eq!(EMPTY.to_u16_slice_with_nul(), [0]);
 /// assert_eq!(cstr16!(""), EMPTY);
+///
+/// const DIR: &str = r"\EFI\BOOT";
+/// const PATH: &CStr16 = cstr16!(DIR, r"\", "BOOTX64.EFI");
+/// assert_eq!(PATH, cstr16!(r"\EFI\BOOT\BOOTX64.EFI"));
+///
+/// const TITLE: &CStr16 = cstr16!(concat!("Loader v", env!("CARGO_PKG_VERSION")));
 /// ```
 ///
 /// [`&CStr16`]: crate::CStr16
@@ -73,10 +92,46 @@ macro_rules! cstr16 {
         // SAFETY: `S` is a trivially correct UCS-2 C string.
         unsafe { $crate::CStr16::from_u16_with_nul_unchecked(S) }
     }};
-    ($s:literal) => {{
-        const S: &[u16] = &$crate::ucs2_cstr!($s);
-        // SAFETY: the ucs2_cstr macro always produces a valid UCS-2 string with
-        // a trailing null character.
-        unsafe { $crate::CStr16::from_u16_with_nul_unchecked(S) }
+    ($($s:expr),+ $(,)?) => {{
+        // Use `const` values here to force errors to happen at compile
+        // time.
+        const STRS: &[&str] = &[$($s),+];
+
+        // Add one for the null char.
+        const NUM_CHARS: usize = $crate::data_types::strs_num_ucs2_chars(STRS) + 1;
+
+        const VAL: [u16; NUM_CHARS] = $crate::data_types::strs_to_ucs2(STRS);
+
+        // SAFETY: the `strs_to_ucs2` function always produces a valid UCS-2
+        // string with a trailing null character.
+        unsafe { $crate::CStr16::from_u16_with_nul_unchecked(&VAL) }
+    }};
+}
+
+/// Create a [`Guid`] from a string at compile time.
+///
+/// Like [`cstr8!`] and [`cstr16!`], the input can be a literal or any
+/// constant `&str` expression, such as a `const` item or an invocation of
+/// [`concat!`] or [`env!`].
+///
+/// # Example
+///
+/// ```
+/// use uefi::{Guid, guid};
+///
+/// const VENDOR: Guid = guid!("01234567-89ab-cdef-0123-456789abcdef");
+///
+/// const VENDOR_STR: &str = "01234567-89ab-cdef-0123-456789abcdef";
+/// assert_eq!(guid!(VENDOR_STR), VENDOR);
+/// ```
+///
+/// [`Guid`]: crate::Guid
+#[macro_export]
+macro_rules! guid {
+    ($s:expr $(,)?) => {{
+        // Create a temporary const value to force an error in the input
+        // to fail at compile time.
+        const G: $crate::Guid = $crate::Guid::parse_or_panic($s);
+        G
     }};
 }
@@ -0,0 +1,123 @@
+// SPDX-License-Identifier: MIT OR Apache-2.0
+
+//! Measurements of boot loader configuration into TPM PCRs.
+//!
+//! Boot loaders record what they load and which configuration they use by
+//! measuring it into the PCRs of the TPM. The functions of this module
+//! format the event data of such measurements as described in the [TCG PC
+//! Client Platform Firmware Profile Specification][spec], so that tools
+//! that parse the event log, such as [`EventData`], understand them.
+//!
+//! The spec recommends PCR 8 and 9 for the configuration and the files of
+//! boot loaders, and PCR 7 for the variables of the Secure Boot policy.
+//!
+//! [`EventData`]: crate::proto::tcg::EventData
+//! [spec]: https://trustedcomputinggroup.org/resource/pc-client-specific-platform-firmware-profile-specification/
+
+use crate::proto::tcg::v2::Tcg;
+use crate::proto::tcg::{EventType, PcrIndex};
+use crate::runtime::{self, VariableVendor};
+use crate::{CStr16, Result, Status};
+use alloc::vec::Vec;
+
+/// Measures `data`, e.g. a command line, into PCR `pcr` with an
+/// [`EventType::IPL`] event that is described by `description`.
+///
+/// # Errors
+///
+/// See [`Tcg::measure`].
+pub fn measure_blob(tcg: &mut Tcg, pcr: PcrIndex, description: &str, data: &[u8]) -> Result {
+    tcg.measure(pcr, EventType::IPL, description.as_bytes(), data)
+}
+
+/// Measures the contents of the file at `path` into PCR `pcr` with an
+/// [`EventType::IPL`] event. The data of the event is the path as a
+/// null-terminated UCS-2 string.
+///
+/// # Errors
+///
+/// See [`Tcg::measure`].
+pub fn measure_file(tcg: &mut Tcg, pcr: PcrIndex, path: &CStr16, contents: &[u8]) -> Result {
+    tcg.measure(pcr, EventType::IPL, path.as_bytes(), contents)
+}
+
+/// Reads the variable `name` of `vendor`, and measures it into PCR `pcr`
+/// with an event of type `event_type`.
+///
+/// The data of the event is a `UEFI_VARIABLE_DATA` structure with the name
+/// and the data of the variable. For [`EventType::EFI_VARIABLE_BOOT`] only
+/// the data of the variable is measured, for the other types the whole
+/// structure is measured.
+///
+/// # Errors
+///
+/// * [`Status::INVALID_PARAMETER`]: `event_type` is not one of
+///   [`EventType::EFI_VARIABLE_DRIVER_CONFIG`],
+///   [`EventType::EFI_VARIABLE_BOOT`], [`EventType::EFI_VARIABLE_BOOT2`] and
+///   [`EventType::EFI_VARIABLE_AUTHORITY`].
+/// * Errors of [`runtime::get_variable_boxed`], e.g. [`Status::NOT_FOUND`].
+/// * Errors of [`Tcg::measure`].
+pub fn measure_variable(
+    tcg: &mut Tcg,
+    pcr: PcrIndex,
+    event_type: EventType,
+    name: &CStr16,
+    vendor: &VariableVendor,
+) -> Result {
+    if !matches!(
+        event_type,
+        EventType::EFI_VARIABLE_DRIVER_CONFIG
+            | EventType::EFI_VARIABLE_BOOT
+            | EventType::EFI_VARIABLE_BOOT2
+            | EventType::EFI_VARIABLE_AUTHORITY
+    ) {
+        return Err(Status::INVALID_PARAMETER.into());
+    }
+
+    let (data, _) = runtime::get_variable_boxed(name, vendor)?;
+    let event_data = variable_event_data(name, vendor, &data);
+    if event_type == EventType::EFI_VARIABLE_BOOT {
+        tcg.measure(pcr, event_type, &event_data, &data)
+    } else {
+        tcg.measure(pcr, event_type, &event_data, &event_data)
+    }
+}
+
+/// Builds the `UEFI_VARIABLE_DATA` structure of a variable.
+fn variable_event_data(name: &CStr16, vendor: &VariableVendor, data: &[u8]) -> Vec<u8> {
+    let name = name.to_u16_slice();
+    let mut event_data = Vec::with_capacity(32 + 2 * name.len() + data.len());
+    event_data.extend(vendor.0.to_bytes());
+    event_data.extend((name.len() as u64).to_le_bytes());
+    event_data.extend((data.len() as u64).to_le_bytes());
+    for c in name {
+        event_data.extend(c.to_le_bytes());
+    }
+    event_data.extend(data);
+    event_data
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::proto::tcg::EventData;
+    use crate::{Char16, cstr16};
+
+    #[test]
+    fn test_variable_event_data() {
+        let vendor = VariableVendor::GLOBAL_VARIABLE;
+        let event_data = variable_event_data(cstr16!("SecureBoot"), &vendor, &[1]);
+        let EventData::Variable {
+            vendor: parsed_vendor,
+            name,
+            data,
+        } = EventData::parse(EventType::EFI_VARIABLE_DRIVER_CONFIG, &event_data)
+        else {
+            panic!("not a variable");
+        };
+        assert_eq!(parsed_vendor, vendor.0);
+        let name: Vec<Char16> = name.to_vec();
+        assert_eq!(name, cstr16!("SecureBoot").as_slice());
+        assert_eq!(data, [1]);
+    }
+}
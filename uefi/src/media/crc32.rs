@@ -0,0 +1,59 @@
+// SPDX-License-Identifier: MIT OR Apache-2.0
+
+//! CRC32 as used by the UEFI specification, e.g. for GPT headers.
+//!
+//! [`boot::calculate_crc32`] computes the same checksum, but requires boot
+//! services.
+//!
+//! [`boot::calculate_crc32`]: crate::boot::calculate_crc32
+
+const TABLE: [u32; 256] = {
+    let mut table = [0; 256];
+    let mut i = 0;
+    while i < 256 {
+        let mut crc = i as u32;
+        let mut bit = 0;
+        while bit < 8 {
+            crc = if crc & 1 == 1 {
+                (crc >> 1) ^ 0xedb8_8320
+            } else {
+                crc >> 1
+            };
+            bit += 1;
+        }
+        table[i] = crc;
+        i += 1;
+    }
+    table
+};
+
+/// Continues the CRC32 computation of `crc` with `data`. Start with a `crc` of
+/// zero.
+pub(crate) fn update(crc: u32, data: &[u8]) -> u32 {
+    let mut crc = !crc;
+    for byte in data {
+        crc = TABLE[((crc ^ u32::from(*byte)) & 0xff) as usize] ^ (crc >> 8);
+    }
+    !crc
+}
+
+/// Computes the CRC32 of `data`.
+pub(crate) fn crc32(data: &[u8]) -> u32 {
+    update(0, data)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_crc32() {
+        assert_eq!(crc32(b""), 0);
+        assert_eq!(crc32(b"123456789"), 0xcbf4_3926);
+        assert_eq!(
+            update(crc32(b"1234"), b"56789"),
+            crc32(b"123456789"),
+            "incremental computation"
+        );
+    }
+}
@@ -0,0 +1,421 @@
+// SPDX-License-Identifier: MIT OR Apache-2.0
+
+//! Byte-addressed access to block devices. See [`BlockDevice`].
+
+use crate::mem::AlignedBuffer;
+use crate::proto::media::block::BlockIO;
+use crate::proto::media::disk::DiskIo;
+use crate::{Result, Status};
+use alloc::vec::Vec;
+use core::slice;
+
+/// A device that stores data in blocks of a fixed size, such as a disk, but
+/// can be accessed at byte granularity.
+pub trait BlockDevice {
+    /// Size of a block in bytes.
+    fn block_size(&self) -> u32;
+
+    /// Number of blocks on the device.
+    fn num_blocks(&self) -> u64;
+
+    /// Reads `buffer.len()` bytes starting at the byte `offset`.
+    fn read_at(&mut self, offset: u64, buffer: &mut [u8]) -> Result;
+
+    /// Writes `buffer` starting at the byte `offset`.
+    ///
+    /// The default implementation returns [`Status::WRITE_PROTECTED`].
+    fn write_at(&mut self, offset: u64, buffer: &[u8]) -> Result {
+        let _ = (offset, buffer);
+        Err(Status::WRITE_PROTECTED.into())
+    }
+
+    /// Flushes all written data to the device.
+    ///
+    /// The default implementation does nothing.
+    fn flush(&mut self) -> Result {
+        Ok(())
+    }
+
+    /// Size of the device in bytes.
+    fn size(&self) -> u64 {
+        u64::from(self.block_size()) * self.num_blocks()
+    }
+
+    /// Reads the block with the logical block address `lba` into `buffer`,
+    /// whose length must be a multiple of the block size.
+    fn read_blocks(&mut self, lba: u64, buffer: &mut [u8]) -> Result {
+        let offset = lba
+            .checked_mul(u64::from(self.block_size()))
+            .ok_or(Status::INVALID_PARAMETER)?;
+        self.read_at(offset, buffer)
+    }
+}
+
+impl<D: BlockDevice + ?Sized> BlockDevice for &mut D {
+    fn block_size(&self) -> u32 {
+        (**self).block_size()
+    }
+
+    fn num_blocks(&self) -> u64 {
+        (**self).num_blocks()
+    }
+
+    fn read_at(&mut self, offset: u64, buffer: &mut [u8]) -> Result {
+        (**self).read_at(offset, buffer)
+    }
+
+    fn write_at(&mut self, offset: u64, buffer: &[u8]) -> Result {
+        (**self).write_at(offset, buffer)
+    }
+
+    fn flush(&mut self) -> Result {
+        (**self).flush()
+    }
+}
+
+/// A [`BlockDevice`] backed by the [`BlockIO`] protocol and, optionally, the
+/// [`DiskIo`] protocol of the same handle.
+///
+/// The [`DiskIo`] protocol handles unaligned accesses itself. Without it,
+/// unaligned accesses go through a temporary buffer, and writes that don't
+/// cover whole blocks read the affected blocks first.
+///
+/// # Example
+///
+/// ```no_run
+/// use uefi::boot;
+/// use uefi::media::{BlockDevice, Disk};
+/// use uefi::proto::media::block::BlockIO;
+/// use uefi::Handle;
+///
+/// fn read_first_block(handle: Handle) -> uefi::Result<Vec<u8>> {
+///     let mut block_io = boot::open_protocol_exclusive::<BlockIO>(handle)?;
+///     let mut disk = Disk::new(&mut block_io);
+///     let mut block = vec![0; disk.block_size() as usize];
+///     disk.read_at(0, &mut block)?;
+///     Ok(block)
+/// }
+/// ```
+#[derive(Debug)]
+pub struct Disk<'a> {
+    block_io: &'a mut BlockIO,
+    disk_io: Option<&'a mut DiskIo>,
+}
+
+impl<'a> Disk<'a> {
+    /// Creates a disk that is only accessed through the [`BlockIO`] protocol.
+    #[must_use]
+    pub const fn new(block_io: &'a mut BlockIO) -> Self {
+        Self {
+            block_io,
+            disk_io: None,
+        }
+    }
+
+    /// Creates a disk that is read and written through the [`DiskIo`]
+    /// protocol. Both protocols must belong to the same handle.
+    #[must_use]
+    pub const fn with_disk_io(block_io: &'a mut BlockIO, disk_io: &'a mut DiskIo) -> Self {
+        Self {
+            block_io,
+            disk_io: Some(disk_io),
+        }
+    }
+
+    /// Returns the underlying [`BlockIO`] protocol.
+    #[must_use]
+    pub const fn block_io(&self) -> &BlockIO {
+        self.block_io
+    }
+
+    const fn media_id(&self) -> u32 {
+        self.block_io.media().media_id()
+    }
+
+    /// Reads the blocks covering `offset..offset + len` into a buffer with
+    /// the alignment required by the device. Returns the buffer and the
+    /// offset of the requested data in it.
+    fn read_covering_blocks(&self, offset: u64, len: usize) -> Result<(AlignedBuffer, usize)> {
+        let block_size = u64::from(self.block_io.media().block_size());
+        let first = offset / block_size;
+        let end = offset
+            .checked_add(len as u64)
+            .ok_or(Status::INVALID_PARAMETER)?
+            .div_ceil(block_size);
+        let size =
+            usize::try_from((end - first) * block_size).map_err(|_| Status::BAD_BUFFER_SIZE)?;
+        let align = self.block_io.media().io_align().max(1) as usize;
+        let mut buffer =
+            AlignedBuffer::from_size_align(size, align).map_err(|_| Status::INVALID_PARAMETER)?;
+        // SAFETY: The buffer owns `size` bytes of memory.
+        unsafe { buffer.ptr_mut().write_bytes(0, size) };
+        self.block_io
+            .read_blocks(self.media_id(), first, aligned_slice(&mut buffer))?;
+        Ok((buffer, (offset - first * block_size) as usize))
+    }
+}
+
+impl BlockDevice for Disk<'_> {
+    fn block_size(&self) -> u32 {
+        self.block_io.media().block_size()
+    }
+
+    fn num_blocks(&self) -> u64 {
+        self.block_io.media().last_block() + 1
+    }
+
+    fn read_at(&mut self, offset: u64, buffer: &mut [u8]) -> Result {
+        if buffer.is_empty() {
+            return Ok(());
+        }
+        let media_id = self.media_id();
+        if let Some(disk_io) = &self.disk_io {
+            return disk_io.read_disk(media_id, offset, buffer);
+        }
+        let (mut blocks, start) = self.read_covering_blocks(offset, buffer.len())?;
+        let len = buffer.len();
+        buffer.copy_from_slice(&aligned_slice(&mut blocks)[start..start + len]);
+        Ok(())
+    }
+
+    fn write_at(&mut self, offset: u64, buffer: &[u8]) -> Result {
+        if buffer.is_empty() {
+            return Ok(());
+        }
+        let media_id = self.media_id();
+        if let Some(disk_io) = &mut self.disk_io {
+            return disk_io.write_disk(media_id, offset, buffer);
+        }
+        let (mut blocks, start) = self.read_covering_blocks(offset, buffer.len())?;
+        let first = offset / u64::from(self.block_size());
+        aligned_slice(&mut blocks)[start..start + buffer.len()].copy_from_slice(buffer);
+        self.block_io
+            .write_blocks(media_id, first, aligned_slice(&mut blocks))
+    }
+
+    fn flush(&mut self) -> Result {
+        self.block_io.flush_blocks()
+    }
+}
+
+/// Returns the content of `buffer` as a slice.
+const fn aligned_slice(buffer: &mut AlignedBuffer) -> &mut [u8] {
+    // SAFETY: The buffer owns `size` bytes of memory, which are initialized
+    // by `read_covering_blocks`.
+    unsafe { slice::from_raw_parts_mut(buffer.ptr_mut(), buffer.size()) }
+}
+
+/// A range of blocks of another [`BlockDevice`], e.g. a partition of a disk.
+///
+/// Offsets are relative to the start of the range. Accesses beyond its end
+/// fail with [`Status::INVALID_PARAMETER`].
+///
+/// # Example
+///
+/// ```no_run
+/// use uefi::media::gpt::Gpt;
+/// use uefi::media::{Disk, Partition};
+/// use uefi::proto::media::block::BlockIO;
+///
+/// fn first_partition(block_io: &mut BlockIO) -> Option<Partition<Disk<'_>>> {
+///     let mut disk = Disk::new(block_io);
+///     let gpt = Gpt::read(&mut disk).ok()?;
+///     let partition = gpt.partitions().first()?;
+///     Some(Partition::new(
+///         disk,
+///         partition.starting_lba(),
+///         partition.num_blocks()?,
+///     ))
+/// }
+/// ```
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Partition<D> {
+    device: D,
+    starting_lba: u64,
+    num_blocks: u64,
+}
+
+impl<D: BlockDevice> Partition<D> {
+    /// Creates a view of `num_blocks` blocks of `device`, starting at the
+    /// block `starting_lba`.
+    #[must_use]
+    pub const fn new(device: D, starting_lba: u64, num_blocks: u64) -> Self {
+        Self {
+            device,
+            starting_lba,
+            num_blocks,
+        }
+    }
+
+    /// First block of the range on the underlying device.
+    #[must_use]
+    pub const fn starting_lba(&self) -> u64 {
+        self.starting_lba
+    }
+
+    /// Unwraps the partition, returning the underlying device.
+    #[must_use]
+    pub fn into_inner(self) -> D {
+        self.device
+    }
+
+    /// Returns the absolute offset of an access at `offset`, or
+    /// [`Status::INVALID_PARAMETER`] if it is out of bounds.
+    fn absolute_offset(&self, offset: u64, len: usize) -> Result<u64> {
+        let end = offset
+            .checked_add(len as u64)
+            .ok_or(Status::INVALID_PARAMETER)?;
+        if end > self.size() {
+            return Err(Status::INVALID_PARAMETER.into());
+        }
+        Ok(self.starting_lba * u64::from(self.block_size()) + offset)
+    }
+}
+
+impl<D: BlockDevice> BlockDevice for Partition<D> {
+    fn block_size(&self) -> u32 {
+        self.device.block_size()
+    }
+
+    fn num_blocks(&self) -> u64 {
+        self.num_blocks
+    }
+
+    fn read_at(&mut self, offset: u64, buffer: &mut [u8]) -> Result {
+        let offset = self.absolute_offset(offset, buffer.len())?;
+        self.device.read_at(offset, buffer)
+    }
+
+    fn write_at(&mut self, offset: u64, buffer: &[u8]) -> Result {
+        let offset = self.absolute_offset(offset, buffer.len())?;
+        self.device.write_at(offset, buffer)
+    }
+
+    fn flush(&mut self) -> Result {
+        self.device.flush()
+    }
+}
+
+/// A [`BlockDevice`] backed by memory, e.g. a disk image that was loaded
+/// from a file.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct MemoryDisk {
+    data: Vec<u8>,
+    block_size: u32,
+}
+
+impl MemoryDisk {
+    /// Creates a disk with the content `data`.
+    ///
+    /// # Panics
+    /// Panics if `block_size` is zero or the length of `data` is not a
+    /// multiple of `block_size`.
+    #[must_use]
+    pub fn new(data: Vec<u8>, block_size: u32) -> Self {
+        assert_ne!(block_size, 0, "block size must not be zero");
+        assert_eq!(
+            data.len() % block_size as usize,
+            0,
+            "size must be a multiple of the block size"
+        );
+        Self { data, block_size }
+    }
+
+    /// Returns the content of the disk.
+    #[must_use]
+    pub fn data(&self) -> &[u8] {
+        &self.data
+    }
+
+    /// Unwraps the disk, returning its content.
+    #[must_use]
+    pub fn into_inner(self) -> Vec<u8> {
+        self.data
+    }
+
+    /// Returns the range of `data` for an access at `offset`, or
+    /// [`Status::INVALID_PARAMETER`] if it is out of bounds.
+    fn range(&self, offset: u64, len: usize) -> Result<core::ops::Range<usize>> {
+        let start = usize::try_from(offset).map_err(|_| Status::INVALID_PARAMETER)?;
+        let end = start.checked_add(len).ok_or(Status::INVALID_PARAMETER)?;
+        if end > self.data.len() {
+            return Err(Status::INVALID_PARAMETER.into());
+        }
+        Ok(start..end)
+    }
+}
+
+impl BlockDevice for MemoryDisk {
+    fn block_size(&self) -> u32 {
+        self.block_size
+    }
+
+    fn num_blocks(&self) -> u64 {
+        (self.data.len() / self.block_size as usize) as u64
+    }
+
+    fn read_at(&mut self, offset: u64, buffer: &mut [u8]) -> Result {
+        let range = self.range(offset, buffer.len())?;
+        buffer.copy_from_slice(&self.data[range]);
+        Ok(())
+    }
+
+    fn write_at(&mut self, offset: u64, buffer: &[u8]) -> Result {
+        let range = self.range(offset, buffer.len())?;
+        self.data[range].copy_from_slice(buffer);
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloc::vec;
+
+    #[test]
+    fn test_memory_disk() {
+        let mut disk = MemoryDisk::new(vec![0; 1024], 512);
+        assert_eq!(disk.num_blocks(), 2);
+        assert_eq!(disk.size(), 1024);
+
+        disk.write_at(510, &[1, 2, 3, 4]).unwrap();
+        let mut buf = [0; 4];
+        disk.read_at(510, &mut buf).unwrap();
+        assert_eq!(buf, [1, 2, 3, 4]);
+
+        let mut block = [0; 512];
+        disk.read_blocks(1, &mut block).unwrap();
+        assert_eq!(block[..2], [3, 4]);
+
+        assert_eq!(
+            disk.read_at(1022, &mut buf),
+            Err(Status::INVALID_PARAMETER.into())
+        );
+        assert_eq!(
+            disk.write_at(u64::MAX, &buf),
+            Err(Status::INVALID_PARAMETER.into())
+        );
+    }
+
+    #[test]
+    fn test_partition() {
+        let mut disk = MemoryDisk::new(vec![0; 4 * 512], 512);
+        let mut partition = Partition::new(&mut disk, 1, 2);
+        assert_eq!(partition.size(), 1024);
+
+        partition.write_at(0, &[1]).unwrap();
+        partition.write_at(1023, &[2]).unwrap();
+        assert_eq!(
+            partition.write_at(1024, &[3]),
+            Err(Status::INVALID_PARAMETER.into())
+        );
+        assert_eq!(
+            partition.read_at(1000, &mut [0; 25]),
+            Err(Status::INVALID_PARAMETER.into())
+        );
+
+        assert_eq!(disk.data()[512], 1);
+        assert_eq!(disk.data()[3 * 512 - 1], 2);
+        assert_eq!(disk.data()[3 * 512], 0);
+    }
+}
@@ -0,0 +1,807 @@
+// SPDX-License-Identifier: MIT OR Apache-2.0
+
+//! Read-only access to FAT12, FAT16 and FAT32 file systems on a
+//! [`BlockDevice`], without going through the firmware's
+//! [`SimpleFileSystem`] driver. See [`FatFileSystem`].
+//!
+//! This allows reading partitions that the firmware did not enumerate, e.g.
+//! on disks found with the [`gpt`] or [`mbr`] parsers, and working around
+//! broken platform FAT drivers.
+//!
+//! Long file names (VFAT) are supported. Writing is not supported.
+//!
+//! [`SimpleFileSystem`]: crate::proto::media::fs::SimpleFileSystem
+//! [`gpt`]: super::gpt
+//! [`mbr`]: super::mbr
+
+use super::BlockDevice;
+use crate::fs::io::Read;
+use alloc::string::String;
+use alloc::vec;
+use alloc::vec::Vec;
+use core::fmt::{self, Debug, Display, Formatter};
+
+/// Size of a directory entry in bytes.
+const DIR_ENTRY_SIZE: usize = 32;
+
+/// Attribute value that marks a long file name entry.
+const LFN_ATTRIBUTES: u8 = 0x0f;
+
+/// Errors that can happen while accessing a FAT file system.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum FatError {
+    /// Reading from the device failed.
+    Io(crate::Error),
+    /// The boot sector does not describe a valid FAT file system.
+    InvalidBootSector,
+    /// A cluster chain is invalid, e.g. it contains a loop or a cluster
+    /// outside of the data region.
+    InvalidClusterChain,
+    /// The path does not exist.
+    NotFound,
+    /// A path component that must be a directory is a file.
+    NotADirectory,
+    /// The path points to a directory, but a file was expected.
+    NotAFile,
+}
+
+impl Display for FatError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Io(err) => write!(f, "failed to read from device: {err}"),
+            Self::InvalidBootSector => write!(f, "invalid FAT boot sector"),
+            Self::InvalidClusterChain => write!(f, "invalid FAT cluster chain"),
+            Self::NotFound => write!(f, "path not found"),
+            Self::NotADirectory => write!(f, "not a directory"),
+            Self::NotAFile => write!(f, "not a file"),
+        }
+    }
+}
+
+impl core::error::Error for FatError {
+    fn source(&self) -> Option<&(dyn core::error::Error + 'static)> {
+        match self {
+            Self::Io(err) => Some(err),
+            _ => None,
+        }
+    }
+}
+
+impl From<crate::Error> for FatError {
+    fn from(err: crate::Error) -> Self {
+        Self::Io(err)
+    }
+}
+
+/// Type of a FAT file system, determined by its number of clusters.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum FatType {
+    /// FAT12, with less than 4085 clusters.
+    Fat12,
+    /// FAT16, with less than 65525 clusters.
+    Fat16,
+    /// FAT32.
+    Fat32,
+}
+
+bitflags::bitflags! {
+    /// Attributes of a [`FatDirEntry`].
+    #[derive(Clone, Copy, Debug, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+    #[repr(transparent)]
+    pub struct FatAttributes: u8 {
+        /// The file must not be modified.
+        const READ_ONLY = 0x01;
+        /// The entry is hidden from normal directory listings.
+        const HIDDEN = 0x02;
+        /// The entry belongs to the operating system.
+        const SYSTEM = 0x04;
+        /// The entry holds the volume label.
+        const VOLUME_ID = 0x08;
+        /// The entry is a directory.
+        const DIRECTORY = 0x10;
+        /// The file was modified since the last backup.
+        const ARCHIVE = 0x20;
+    }
+}
+
+/// An entry of a directory.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct FatDirEntry {
+    name: String,
+    short_name: String,
+    attributes: FatAttributes,
+    first_cluster: u32,
+    size: u32,
+}
+
+impl FatDirEntry {
+    /// Name of the entry. This is the long file name if the entry has one,
+    /// otherwise the short name.
+    #[must_use]
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// Short 8.3 name of the entry, e.g. `BOOTX64.EFI`.
+    #[must_use]
+    pub fn short_name(&self) -> &str {
+        &self.short_name
+    }
+
+    /// Attributes of the entry.
+    #[must_use]
+    pub const fn attributes(&self) -> FatAttributes {
+        self.attributes
+    }
+
+    /// Returns whether the entry is a directory.
+    #[must_use]
+    pub const fn is_directory(&self) -> bool {
+        self.attributes.contains(FatAttributes::DIRECTORY)
+    }
+
+    /// Size of the file in bytes. Zero for directories.
+    #[must_use]
+    pub const fn size(&self) -> u32 {
+        self.size
+    }
+
+    /// First cluster of the entry's data. Zero for empty files.
+    #[must_use]
+    pub const fn first_cluster(&self) -> u32 {
+        self.first_cluster
+    }
+}
+
+/// A FAT file system on a [`BlockDevice`].
+///
+/// Paths use `/` or `\` as separator and are always relative to the root
+/// directory. Names are compared ignoring ASCII case.
+///
+/// # Example
+///
+/// ```no_run
+/// use uefi::media::fat::FatFileSystem;
+/// use uefi::media::gpt::Gpt;
+/// use uefi::media::{Disk, Partition};
+/// use uefi::proto::media::block::BlockIO;
+/// use uefi::proto::media::partition::GptPartitionType;
+///
+/// fn read_loader(block_io: &mut BlockIO) -> Option<Vec<u8>> {
+///     let mut disk = Disk::new(block_io);
+///     let gpt = Gpt::read(&mut disk).ok()?;
+///     let esp = gpt
+///         .partitions()
+///         .iter()
+///         .find(|p| p.partition_type() == GptPartitionType::EFI_SYSTEM_PARTITION)?;
+///     let partition = Partition::new(disk, esp.starting_lba(), esp.num_blocks()?);
+///     let mut fs = FatFileSystem::new(partition).ok()?;
+///     fs.read("EFI/BOOT/BOOTX64.EFI").ok()
+/// }
+/// ```
+pub struct FatFileSystem<D> {
+    device: D,
+    fat_type: FatType,
+    bytes_per_sector: u32,
+    cluster_size: u32,
+    cluster_count: u32,
+    fat_offset: u64,
+    data_offset: u64,
+    root: DirLocation,
+    volume_label: String,
+}
+
+/// Location of a directory's entries.
+#[derive(Clone, Copy, Debug)]
+enum DirLocation {
+    /// The fixed root directory region of FAT12 and FAT16.
+    FixedRoot { offset: u64, size: u32 },
+    /// A directory stored in a cluster chain.
+    Clusters(u32),
+}
+
+impl<D: BlockDevice> FatFileSystem<D> {
+    /// Opens the FAT file system on `device` by parsing its boot sector.
+    ///
+    /// # Errors
+    /// * [`FatError::InvalidBootSector`] if the device holds no FAT file
+    ///   system.
+    /// * [`FatError::Io`] if reading from the device fails.
+    pub fn new(mut device: D) -> Result<Self, FatError> {
+        let mut bs = [0; 512];
+        device.read_at(0, &mut bs)?;
+        if bs[510..512] != [0x55, 0xaa] {
+            return Err(FatError::InvalidBootSector);
+        }
+
+        let bytes_per_sector = u32::from(read_u16(&bs, 11));
+        let sectors_per_cluster = u32::from(bs[13]);
+        let reserved_sectors = u32::from(read_u16(&bs, 14));
+        let num_fats = u32::from(bs[16]);
+        let root_entry_count = u32::from(read_u16(&bs, 17));
+        let total_sectors = match read_u16(&bs, 19) {
+            0 => read_u32(&bs, 32),
+            n => u32::from(n),
+        };
+        let fat_size = match read_u16(&bs, 22) {
+            0 => read_u32(&bs, 36),
+            n => u32::from(n),
+        };
+
+        if !matches!(bytes_per_sector, 512 | 1024 | 2048 | 4096)
+            || !sectors_per_cluster.is_power_of_two()
+            || reserved_sectors == 0
+            || num_fats == 0
+            || fat_size == 0
+        {
+            return Err(FatError::InvalidBootSector);
+        }
+
+        let root_dir_sectors =
+            (root_entry_count * DIR_ENTRY_SIZE as u32).div_ceil(bytes_per_sector);
+        let data_start = u64::from(reserved_sectors)
+            + u64::from(num_fats) * u64::from(fat_size)
+            + u64::from(root_dir_sectors);
+        let data_sectors = u64::from(total_sectors)
+            .checked_sub(data_start)
+            .ok_or(FatError::InvalidBootSector)?;
+        let cluster_count = u32::try_from(data_sectors / u64::from(sectors_per_cluster))
+            .map_err(|_| FatError::InvalidBootSector)?;
+        let fat_type = match cluster_count {
+            0..4085 => FatType::Fat12,
+            4085..65525 => FatType::Fat16,
+            _ => FatType::Fat32,
+        };
+
+        let sector = u64::from(bytes_per_sector);
+        let root = if fat_type == FatType::Fat32 {
+            DirLocation::Clusters(read_u32(&bs, 44))
+        } else {
+            DirLocation::FixedRoot {
+                offset: (u64::from(reserved_sectors) + u64::from(num_fats) * u64::from(fat_size))
+                    * sector,
+                size: root_dir_sectors * bytes_per_sector,
+            }
+        };
+        let label_offset = if fat_type == FatType::Fat32 { 71 } else { 43 };
+        let volume_label = String::from_utf8_lossy(&bs[label_offset..label_offset + 11])
+            .trim_end()
+            .into();
+
+        let mut fs = Self {
+            device,
+            fat_type,
+            bytes_per_sector,
+            cluster_size: sectors_per_cluster * bytes_per_sector,
+            cluster_count,
+            fat_offset: u64::from(reserved_sectors) * sector,
+            data_offset: data_start * sector,
+            root,
+            volume_label,
+        };
+
+        // The label in the root directory takes precedence, as it's the one
+        // updated by most tools.
+        let root = fs.read_dir_data(fs.root)?;
+        if let Some(label) = find_volume_label(&root) {
+            fs.volume_label = label;
+        }
+        Ok(fs)
+    }
+
+    /// Type of the file system.
+    #[must_use]
+    pub const fn fat_type(&self) -> FatType {
+        self.fat_type
+    }
+
+    /// Label of the volume, without trailing spaces.
+    #[must_use]
+    pub fn volume_label(&self) -> &str {
+        &self.volume_label
+    }
+
+    /// Size of a cluster in bytes.
+    #[must_use]
+    pub const fn cluster_size(&self) -> u32 {
+        self.cluster_size
+    }
+
+    /// Number of clusters in the data region.
+    #[must_use]
+    pub const fn cluster_count(&self) -> u32 {
+        self.cluster_count
+    }
+
+    /// Unwraps the file system, returning the underlying device.
+    #[must_use]
+    pub fn into_inner(self) -> D {
+        self.device
+    }
+
+    /// Returns the entries of the directory at `path`. The `.` and `..`
+    /// entries and the volume label are not included.
+    ///
+    /// # Errors
+    /// * [`FatError::NotFound`] if the path does not exist.
+    /// * [`FatError::NotADirectory`] if the path is no directory.
+    pub fn read_dir(&mut self, path: &str) -> Result<Vec<FatDirEntry>, FatError> {
+        let location = match self.lookup(path)? {
+            None => self.root,
+            Some(entry) if entry.is_directory() => self.dir_location(&entry),
+            Some(_) => return Err(FatError::NotADirectory),
+        };
+        let data = self.read_dir_data(location)?;
+        Ok(parse_dir_entries(&data, self.fat_type))
+    }
+
+    /// Returns the entry at `path`.
+    ///
+    /// # Errors
+    /// * [`FatError::NotFound`] if the path does not exist or is the root
+    ///   directory, which has no entry.
+    pub fn metadata(&mut self, path: &str) -> Result<FatDirEntry, FatError> {
+        self.lookup(path)?.ok_or(FatError::NotFound)
+    }
+
+    /// Opens the file at `path` for reading.
+    ///
+    /// # Errors
+    /// * [`FatError::NotFound`] if the path does not exist.
+    /// * [`FatError::NotAFile`] if the path is a directory.
+    pub fn open(&mut self, path: &str) -> Result<FatFile<'_, D>, FatError> {
+        let entry = self.metadata(path)?;
+        if entry.is_directory() {
+            return Err(FatError::NotAFile);
+        }
+        let clusters = if entry.size == 0 {
+            Vec::new()
+        } else {
+            self.cluster_chain(entry.first_cluster)?
+        };
+        if (clusters.len() as u64) * u64::from(self.cluster_size) < u64::from(entry.size) {
+            return Err(FatError::InvalidClusterChain);
+        }
+        Ok(FatFile {
+            fs: self,
+            clusters,
+            size: entry.size,
+            position: 0,
+        })
+    }
+
+    /// Reads the entire content of the file at `path`.
+    ///
+    /// # Errors
+    /// See [`Self::open`].
+    pub fn read(&mut self, path: &str) -> Result<Vec<u8>, FatError> {
+        let mut file = self.open(path)?;
+        let mut data = vec![0; file.size() as usize];
+        let mut filled = 0;
+        while filled < data.len() {
+            filled += file.read_bytes(&mut data[filled..])?;
+        }
+        Ok(data)
+    }
+
+    /// Looks up `path`. Returns `None` for the root directory.
+    fn lookup(&mut self, path: &str) -> Result<Option<FatDirEntry>, FatError> {
+        let mut current: Option<FatDirEntry> = None;
+        for component in path.split(['/', '\\']).filter(|c| !c.is_empty()) {
+            let location = match &current {
+                None => self.root,
+                Some(entry) if entry.is_directory() => self.dir_location(entry),
+                Some(_) => return Err(FatError::NotADirectory),
+            };
+            let data = self.read_dir_data(location)?;
+            let entry = parse_dir_entries(&data, self.fat_type)
+                .into_iter()
+                .find(|e| {
+                    e.name.eq_ignore_ascii_case(component)
+                        || e.short_name.eq_ignore_ascii_case(component)
+                })
+                .ok_or(FatError::NotFound)?;
+            current = Some(entry);
+        }
+        Ok(current)
+    }
+
+    const fn dir_location(&self, entry: &FatDirEntry) -> DirLocation {
+        // A first cluster of zero refers to the root directory.
+        if entry.first_cluster == 0 {
+            self.root
+        } else {
+            DirLocation::Clusters(entry.first_cluster)
+        }
+    }
+
+    /// Reads all entries of a directory.
+    fn read_dir_data(&mut self, location: DirLocation) -> Result<Vec<u8>, FatError> {
+        match location {
+            DirLocation::FixedRoot { offset, size } => {
+                let mut data = vec![0; size as usize];
+                self.device.read_at(offset, &mut data)?;
+                Ok(data)
+            }
+            DirLocation::Clusters(first) => {
+                let clusters = self.cluster_chain(first)?;
+                let cluster_size = self.cluster_size as usize;
+                let mut data = vec![0; clusters.len() * cluster_size];
+                for (cluster, chunk) in clusters.iter().zip(data.chunks_exact_mut(cluster_size)) {
+                    self.device.read_at(self.cluster_offset(*cluster), chunk)?;
+                }
+                Ok(data)
+            }
+        }
+    }
+
+    /// Byte offset of the data of `cluster`.
+    fn cluster_offset(&self, cluster: u32) -> u64 {
+        self.data_offset + u64::from(cluster - 2) * u64::from(self.cluster_size)
+    }
+
+    fn is_valid_cluster(&self, cluster: u32) -> bool {
+        (2..self.cluster_count + 2).contains(&cluster)
+    }
+
+    /// Returns all clusters of the chain starting at `first`.
+    fn cluster_chain(&mut self, first: u32) -> Result<Vec<u32>, FatError> {
+        let mut chain = Vec::new();
+        let mut cluster = first;
+        loop {
+            // A chain can't be longer than the number of clusters, otherwise
+            // it contains a loop.
+            if !self.is_valid_cluster(cluster) || chain.len() >= self.cluster_count as usize {
+                return Err(FatError::InvalidClusterChain);
+            }
+            chain.push(cluster);
+            match self.next_cluster(cluster)? {
+                Some(next) => cluster = next,
+                None => return Ok(chain),
+            }
+        }
+    }
+
+    /// Reads the FAT entry of `cluster`. Returns `None` at the end of the
+    /// chain.
+    fn next_cluster(&mut self, cluster: u32) -> Result<Option<u32>, FatError> {
+        let (offset, len) = match self.fat_type {
+            FatType::Fat12 => (u64::from(cluster) * 3 / 2, 2),
+            FatType::Fat16 => (u64::from(cluster) * 2, 2),
+            FatType::Fat32 => (u64::from(cluster) * 4, 4),
+        };
+        let mut bytes = [0; 4];
+        self.device
+            .read_at(self.fat_offset + offset, &mut bytes[..len])?;
+        let value = u32::from_le_bytes(bytes);
+        let (value, end) = match self.fat_type {
+            FatType::Fat12 if cluster % 2 == 0 => (value & 0xfff, 0xff8),
+            FatType::Fat12 => (value >> 4, 0xff8),
+            FatType::Fat16 => (value, 0xfff8),
+            FatType::Fat32 => (value & 0x0fff_ffff, 0x0fff_fff8),
+        };
+        Ok((value < end).then_some(value))
+    }
+}
+
+impl<D> Debug for FatFileSystem<D> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.debug_struct("FatFileSystem")
+            .field("fat_type", &self.fat_type)
+            .field("volume_label", &self.volume_label)
+            .field("bytes_per_sector", &self.bytes_per_sector)
+            .field("cluster_size", &self.cluster_size)
+            .field("cluster_count", &self.cluster_count)
+            .finish_non_exhaustive()
+    }
+}
+
+/// A file opened for reading with [`FatFileSystem::open`].
+pub struct FatFile<'a, D> {
+    fs: &'a mut FatFileSystem<D>,
+    clusters: Vec<u32>,
+    size: u32,
+    position: u32,
+}
+
+impl<D: BlockDevice> FatFile<'_, D> {
+    /// Size of the file in bytes.
+    #[must_use]
+    pub const fn size(&self) -> u32 {
+        self.size
+    }
+
+    /// Current position in the file.
+    #[must_use]
+    pub const fn position(&self) -> u32 {
+        self.position
+    }
+
+    /// Sets the position in the file. Positions beyond the end of the file
+    /// are clamped to the file size.
+    pub fn set_position(&mut self, position: u32) {
+        self.position = position.min(self.size);
+    }
+
+    /// Reads data into `buffer`. Returns the number of bytes read, which is
+    /// zero at the end of the file.
+    fn read_bytes(&mut self, buffer: &mut [u8]) -> Result<usize, FatError> {
+        let cluster_size = self.fs.cluster_size;
+        let index = (self.position / cluster_size) as usize;
+        let in_cluster = self.position % cluster_size;
+        let len = buffer
+            .len()
+            .min((self.size - self.position) as usize)
+            .min((cluster_size - in_cluster) as usize);
+        if len == 0 {
+            return Ok(0);
+        }
+        let offset = self.fs.cluster_offset(self.clusters[index]) + u64::from(in_cluster);
+        self.fs.device.read_at(offset, &mut buffer[..len])?;
+        self.position += len as u32;
+        Ok(len)
+    }
+}
+
+impl<D: BlockDevice> Read for FatFile<'_, D> {
+    fn read(&mut self, buffer: &mut [u8]) -> crate::Result<usize> {
+        self.read_bytes(buffer).map_err(|err| match err {
+            FatError::Io(err) => err,
+            _ => crate::Status::VOLUME_CORRUPTED.into(),
+        })
+    }
+}
+
+impl<D> Debug for FatFile<'_, D> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.debug_struct("FatFile")
+            .field("size", &self.size)
+            .field("position", &self.position)
+            .finish_non_exhaustive()
+    }
+}
+
+/// Returns the volume label stored in the entries of a root directory.
+fn find_volume_label(data: &[u8]) -> Option<String> {
+    data.chunks_exact(DIR_ENTRY_SIZE)
+        .take_while(|raw| raw[0] != 0)
+        .find(|raw| raw[0] != 0xe5 && raw[11] & 0x3f == FatAttributes::VOLUME_ID.bits())
+        .map(|raw| String::from_utf8_lossy(&raw[..11]).trim_end().into())
+}
+
+/// Parses the entries of a directory, combining long file name entries with
+/// their short entry.
+fn parse_dir_entries(data: &[u8], fat_type: FatType) -> Vec<FatDirEntry> {
+    let mut entries = Vec::new();
+    // Long name collected from the preceding LFN entries and its checksum.
+    let mut lfn: Option<(Vec<u16>, u8)> = None;
+
+    for raw in data.chunks_exact(DIR_ENTRY_SIZE) {
+        match raw[0] {
+            0 => break,
+            0xe5 => {
+                lfn = None;
+                continue;
+            }
+            _ => {}
+        }
+
+        let attributes = raw[11];
+        if attributes & 0x3f == LFN_ATTRIBUTES {
+            let order = usize::from(raw[0] & 0x1f);
+            if order == 0 {
+                lfn = None;
+                continue;
+            }
+            if raw[0] & 0x40 != 0 {
+                lfn = Some((vec![0xffff; order * 13], raw[13]));
+            }
+            if let Some((name, checksum)) = &mut lfn {
+                if *checksum != raw[13] || order * 13 > name.len() {
+                    lfn = None;
+                    continue;
+                }
+                let chars = raw[1..11]
+                    .chunks_exact(2)
+                    .chain(raw[14..26].chunks_exact(2))
+                    .chain(raw[28..32].chunks_exact(2))
+                    .map(|c| u16::from_le_bytes([c[0], c[1]]));
+                for (slot, c) in name[(order - 1) * 13..].iter_mut().zip(chars) {
+                    *slot = c;
+                }
+            }
+            continue;
+        }
+
+        let lfn_data = lfn.take();
+        if attributes & FatAttributes::VOLUME_ID.bits() != 0 {
+            continue;
+        }
+        let short_raw: &[u8; 11] = raw[..11].try_into().unwrap();
+        let short_name = decode_short_name(short_raw, raw[12]);
+        if short_name == "." || short_name == ".." {
+            continue;
+        }
+        let name = lfn_data
+            .filter(|(_, checksum)| *checksum == short_name_checksum(short_raw))
+            .map(|(chars, _)| {
+                let len = chars.iter().position(|c| *c == 0 || *c == 0xffff);
+                char::decode_utf16(chars[..len.unwrap_or(chars.len())].iter().copied())
+                    .map(|c| c.unwrap_or(char::REPLACEMENT_CHARACTER))
+                    .collect()
+            })
+            .unwrap_or_else(|| short_name.clone());
+
+        let high = if fat_type == FatType::Fat32 {
+            u32::from(read_u16(raw, 20)) << 16
+        } else {
+            0
+        };
+        entries.push(FatDirEntry {
+            name,
+            short_name,
+            attributes: FatAttributes::from_bits_retain(attributes),
+            first_cluster: high | u32::from(read_u16(raw, 26)),
+            size: read_u32(raw, 28),
+        });
+    }
+    entries
+}
+
+/// Decodes an 8.3 name. `case` holds the flags used by Windows to store
+/// all-lowercase base names (`0x08`) and extensions (`0x10`).
+fn decode_short_name(raw: &[u8; 11], case: u8) -> String {
+    let convert = |bytes: &[u8], lower: bool| {
+        let mut part = String::new();
+        for (i, b) in bytes.iter().enumerate() {
+            // 0x05 in the first byte stands for 0xe5, which marks deleted
+            // entries.
+            let b = if i == 0 && *b == 0x05 { 0xe5 } else { *b };
+            let c = char::from(b);
+            part.push(if lower { c.to_ascii_lowercase() } else { c });
+        }
+        part.trim_end().into()
+    };
+    let mut name: String = convert(&raw[..8], case & 0x08 != 0);
+    let ext: String = convert(&raw[8..], case & 0x10 != 0);
+    if !ext.is_empty() {
+        name.push('.');
+        name.push_str(&ext);
+    }
+    name
+}
+
+/// Checksum of an 8.3 name, stored in the long file name entries.
+fn short_name_checksum(raw: &[u8; 11]) -> u8 {
+    raw.iter()
+        .fold(0u8, |sum, b| sum.rotate_right(1).wrapping_add(*b))
+}
+
+fn read_u16(bytes: &[u8], offset: usize) -> u16 {
+    u16::from_le_bytes([bytes[offset], bytes[offset + 1]])
+}
+
+fn read_u32(bytes: &[u8], offset: usize) -> u32 {
+    u32::from_le_bytes(bytes[offset..offset + 4].try_into().unwrap())
+}
+
+#[cfg(test)]
+mod tests {
+    extern crate std;
+
+    use super::*;
+    use crate::media::MemoryDisk;
+    use std::io::{Cursor, Write};
+
+    /// Formats a disk image with the `fatfs` crate and fills it with some
+    /// files and directories.
+    fn make_image(size: usize, fat_type: fatfs::FatType) -> Vec<u8> {
+        let mut image = vec![0; size];
+        fatfs::format_volume(
+            Cursor::new(image.as_mut_slice()),
+            fatfs::FormatVolumeOptions::new()
+                .fat_type(fat_type)
+                .volume_label(*b"TESTVOLUME "),
+        )
+        .unwrap();
+
+        let fs = fatfs::FileSystem::new(Cursor::new(image.as_mut_slice()), fatfs::FsOptions::new())
+            .unwrap();
+        let root = fs.root_dir();
+        root.create_file("short.txt")
+            .unwrap()
+            .write_all(b"hello")
+            .unwrap();
+        let dir = root.create_dir("A Long Directory Name").unwrap();
+        let big = (0..20_000u32).map(|i| i as u8).collect::<Vec<_>>();
+        dir.create_file("big file.bin")
+            .unwrap()
+            .write_all(&big)
+            .unwrap();
+        dir.create_file("empty").unwrap();
+        for i in 0..40 {
+            dir.create_dir(&std::format!("sub{i}")).unwrap();
+        }
+        drop(root);
+        drop(dir);
+        fs.unmount().unwrap();
+        image
+    }
+
+    fn check_image(image: Vec<u8>, fat_type: FatType) {
+        let mut fs = FatFileSystem::new(MemoryDisk::new(image, 512)).unwrap();
+        assert_eq!(fs.fat_type(), fat_type);
+        assert_eq!(fs.volume_label(), "TESTVOLUME");
+
+        let root = fs.read_dir("/").unwrap();
+        let names = root.iter().map(|e| e.name()).collect::<Vec<_>>();
+        assert_eq!(names, ["short.txt", "A Long Directory Name"]);
+        assert_eq!(root[0].short_name(), "SHORT.TXT");
+        assert!(root[1].is_directory());
+
+        assert_eq!(fs.read("SHORT.TXT").unwrap(), b"hello");
+        let big = fs.read("a long directory name\\Big File.bin").unwrap();
+        assert_eq!(big.len(), 20_000);
+        assert!(big.iter().enumerate().all(|(i, b)| *b == i as u8));
+        assert_eq!(fs.read("/A Long Directory Name/empty").unwrap(), b"");
+
+        // The directory spans multiple clusters.
+        assert_eq!(fs.read_dir("A Long Directory Name").unwrap().len(), 42);
+
+        let mut file = fs.open("A Long Directory Name/big file.bin").unwrap();
+        file.set_position(19_990);
+        let mut buf = [0; 20];
+        assert_eq!(Read::read(&mut file, &mut buf), Ok(10));
+        assert_eq!(Read::read(&mut file, &mut buf), Ok(0));
+
+        assert_eq!(fs.read("missing").unwrap_err(), FatError::NotFound);
+        assert_eq!(
+            fs.read("A Long Directory Name").unwrap_err(),
+            FatError::NotAFile
+        );
+        assert_eq!(
+            fs.read_dir("short.txt/x").unwrap_err(),
+            FatError::NotADirectory
+        );
+    }
+
+    #[test]
+    fn test_fat12() {
+        check_image(
+            make_image(1024 * 1024, fatfs::FatType::Fat12),
+            FatType::Fat12,
+        );
+    }
+
+    #[test]
+    fn test_fat16() {
+        check_image(
+            make_image(8 * 1024 * 1024, fatfs::FatType::Fat16),
+            FatType::Fat16,
+        );
+    }
+
+    #[test]
+    fn test_fat32() {
+        check_image(
+            make_image(40 * 1024 * 1024, fatfs::FatType::Fat32),
+            FatType::Fat32,
+        );
+    }
+
+    #[test]
+    fn test_invalid() {
+        assert_eq!(
+            FatFileSystem::new(MemoryDisk::new(vec![0; 4096], 512)).unwrap_err(),
+            FatError::InvalidBootSector
+        );
+    }
+
+    #[test]
+    fn test_short_name() {
+        assert_eq!(decode_short_name(b"README  TXT", 0), "README.TXT");
+        assert_eq!(decode_short_name(b"README  TXT", 0x18), "readme.txt");
+        assert_eq!(decode_short_name(b"NOEXT      ", 0), "NOEXT");
+        assert_eq!(decode_short_name(b"\x05BC     TXT", 0), "\u{e5}BC.TXT");
+    }
+}
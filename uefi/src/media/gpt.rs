@@ -0,0 +1,1089 @@
+// SPDX-License-Identifier: MIT OR Apache-2.0
+
+//! Reader and writer for the GUID Partition Table (GPT).
+//!
+//! A GPT disk starts with a protective MBR, followed by the primary GPT
+//! header in block 1 and the partition entry array. A backup copy of the
+//! header is stored in the last block of the disk. See [`Gpt::read`].
+//!
+//! A table can be created with [`Gpt::new`] or modified after reading it,
+//! and then written back with [`Gpt::write`].
+//!
+//! The firmware provides the entry of a single partition through the
+//! [`PartitionInfo`] protocol, but only for partitions it enumerated. This
+//! module reads the whole table of any disk instead.
+//!
+//! [`PartitionInfo`]: crate::proto::media::partition::PartitionInfo
+
+use super::mbr::{MBR_SIGNATURE, Mbr};
+use super::{BlockDevice, crc32};
+use crate::data_types::chars::NUL_16;
+use crate::proto::media::partition::{
+    GptPartitionAttributes, GptPartitionEntry, GptPartitionType, MbrOsType,
+};
+use crate::{CStr16, CString16, Char16, Guid};
+use alloc::vec;
+use alloc::vec::Vec;
+use core::fmt::{self, Display, Formatter};
+
+/// Signature at the start of a GPT header.
+pub const GPT_SIGNATURE: [u8; 8] = *b"EFI PART";
+
+/// Minimum size of a GPT header in bytes.
+pub const GPT_HEADER_MIN_SIZE: u32 = 92;
+
+/// Minimum size of a GPT partition entry in bytes.
+pub const GPT_ENTRY_MIN_SIZE: u32 = 128;
+
+/// Number of entries in the partition entry array of a new table.
+const DEFAULT_NUM_ENTRIES: u32 = 128;
+
+/// Maximum size of a partition entry array in bytes. The array of a new
+/// table is 16 KiB, so this only rejects corrupted or crafted headers.
+const MAX_ENTRY_ARRAY_SIZE: u64 = 4 * 1024 * 1024;
+
+/// Errors that can happen while reading or writing a GPT.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum GptError {
+    /// Reading from or writing to the disk failed.
+    Io(crate::Error),
+    /// The first block does not contain a protective MBR.
+    NoProtectiveMbr,
+    /// The header does not start with [`GPT_SIGNATURE`].
+    InvalidSignature,
+    /// A header field has an invalid value, e.g. a size that is too small,
+    /// or a partition entry array that is too large.
+    InvalidHeader,
+    /// The CRC32 of the header does not match.
+    HeaderCrcMismatch,
+    /// The CRC32 of the partition entry array does not match.
+    EntryArrayCrcMismatch,
+    /// The disk is too small for a GPT.
+    DiskTooSmall,
+    /// A partition is outside of the usable blocks, ends before it starts,
+    /// or overlaps another partition.
+    InvalidPartition,
+    /// All entries of the partition entry array are used.
+    TableFull,
+    /// No partition uses the entry with the given index.
+    PartitionNotFound,
+    /// A partition name is longer than 35 characters.
+    NameTooLong,
+}
+
+impl Display for GptError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Io(err) => write!(f, "disk I/O failed: {err}"),
+            Self::NoProtectiveMbr => write!(f, "no protective MBR"),
+            Self::InvalidSignature => write!(f, "invalid GPT header signature"),
+            Self::InvalidHeader => write!(f, "invalid GPT header"),
+            Self::HeaderCrcMismatch => write!(f, "GPT header CRC mismatch"),
+            Self::EntryArrayCrcMismatch => write!(f, "GPT partition entry array CRC mismatch"),
+            Self::DiskTooSmall => write!(f, "disk is too small for a GPT"),
+            Self::InvalidPartition => write!(f, "invalid GPT partition range"),
+            Self::TableFull => write!(f, "GPT partition entry array is full"),
+            Self::PartitionNotFound => write!(f, "GPT partition not found"),
+            Self::NameTooLong => write!(f, "GPT partition name is too long"),
+        }
+    }
+}
+
+impl core::error::Error for GptError {
+    fn source(&self) -> Option<&(dyn core::error::Error + 'static)> {
+        match self {
+            Self::Io(err) => Some(err),
+            _ => None,
+        }
+    }
+}
+
+impl From<crate::Error> for GptError {
+    fn from(err: crate::Error) -> Self {
+        Self::Io(err)
+    }
+}
+
+/// A parsed and validated GPT header.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct GptHeader {
+    /// Revision of the GPT format, `0x0001_0000` for version 1.0.
+    pub revision: u32,
+    /// Size of the header in bytes.
+    pub header_size: u32,
+    /// CRC32 of the header.
+    pub header_crc32: u32,
+    /// LBA of the block containing this header.
+    pub my_lba: u64,
+    /// LBA of the block containing the other copy of the header.
+    pub alternate_lba: u64,
+    /// First LBA that may be used by a partition.
+    pub first_usable_lba: u64,
+    /// Last LBA that may be used by a partition.
+    pub last_usable_lba: u64,
+    /// GUID identifying the disk.
+    pub disk_guid: Guid,
+    /// Starting LBA of the partition entry array.
+    pub partition_entry_lba: u64,
+    /// Number of entries in the partition entry array.
+    pub number_of_partition_entries: u32,
+    /// Size of each entry in the partition entry array in bytes.
+    pub size_of_partition_entry: u32,
+    /// CRC32 of the partition entry array.
+    pub partition_entry_array_crc32: u32,
+}
+
+impl GptHeader {
+    /// Parses and validates a header from the content of its block.
+    ///
+    /// # Errors
+    /// * [`GptError::InvalidSignature`] if the signature does not match.
+    /// * [`GptError::InvalidHeader`] if the header or entry size is invalid.
+    /// * [`GptError::HeaderCrcMismatch`] if the CRC32 does not match.
+    pub fn parse(block: &[u8]) -> Result<Self, GptError> {
+        if block.len() < GPT_HEADER_MIN_SIZE as usize {
+            return Err(GptError::InvalidHeader);
+        }
+        if block[..8] != GPT_SIGNATURE {
+            return Err(GptError::InvalidSignature);
+        }
+
+        let header = Self {
+            revision: read_u32(block, 8),
+            header_size: read_u32(block, 12),
+            header_crc32: read_u32(block, 16),
+            my_lba: read_u64(block, 24),
+            alternate_lba: read_u64(block, 32),
+            first_usable_lba: read_u64(block, 40),
+            last_usable_lba: read_u64(block, 48),
+            disk_guid: read_guid(block, 56),
+            partition_entry_lba: read_u64(block, 72),
+            number_of_partition_entries: read_u32(block, 80),
+            size_of_partition_entry: read_u32(block, 84),
+            partition_entry_array_crc32: read_u32(block, 88),
+        };
+
+        let header_size = header.header_size as usize;
+        if header_size < GPT_HEADER_MIN_SIZE as usize || header_size > block.len() {
+            return Err(GptError::InvalidHeader);
+        }
+        // The CRC is computed with the CRC field itself set to zero.
+        let crc = crc32::update(crc32::crc32(&block[..16]), &[0; 4]);
+        let crc = crc32::update(crc, &block[20..header_size]);
+        if crc != header.header_crc32 {
+            return Err(GptError::HeaderCrcMismatch);
+        }
+
+        // The entry size must be 128 multiplied by a power of two.
+        let entry_size = header.size_of_partition_entry;
+        let valid_entry_size = entry_size % GPT_ENTRY_MIN_SIZE == 0
+            && (entry_size / GPT_ENTRY_MIN_SIZE).is_power_of_two();
+        if !valid_entry_size || header.first_usable_lba > header.last_usable_lba {
+            return Err(GptError::InvalidHeader);
+        }
+        header.entry_array_len()?;
+
+        Ok(header)
+    }
+
+    /// Size of the partition entry array in bytes.
+    #[must_use]
+    pub const fn partition_entry_array_size(&self) -> u64 {
+        self.number_of_partition_entries as u64 * self.size_of_partition_entry as u64
+    }
+
+    /// Serializes the header. The fields are written as they are, the CRC
+    /// is not updated.
+    #[must_use]
+    pub fn to_bytes(&self) -> [u8; GPT_HEADER_MIN_SIZE as usize] {
+        let mut bytes = [0; GPT_HEADER_MIN_SIZE as usize];
+        bytes[..8].copy_from_slice(&GPT_SIGNATURE);
+        bytes[8..12].copy_from_slice(&self.revision.to_le_bytes());
+        bytes[12..16].copy_from_slice(&self.header_size.to_le_bytes());
+        bytes[16..20].copy_from_slice(&self.header_crc32.to_le_bytes());
+        bytes[24..32].copy_from_slice(&self.my_lba.to_le_bytes());
+        bytes[32..40].copy_from_slice(&self.alternate_lba.to_le_bytes());
+        bytes[40..48].copy_from_slice(&self.first_usable_lba.to_le_bytes());
+        bytes[48..56].copy_from_slice(&self.last_usable_lba.to_le_bytes());
+        bytes[56..72].copy_from_slice(&self.disk_guid.to_bytes());
+        bytes[72..80].copy_from_slice(&self.partition_entry_lba.to_le_bytes());
+        bytes[80..84].copy_from_slice(&self.number_of_partition_entries.to_le_bytes());
+        bytes[84..88].copy_from_slice(&self.size_of_partition_entry.to_le_bytes());
+        bytes[88..92].copy_from_slice(&self.partition_entry_array_crc32.to_le_bytes());
+        bytes
+    }
+
+    /// Size of the partition entry array in bytes, if it is not larger than
+    /// [`MAX_ENTRY_ARRAY_SIZE`].
+    fn entry_array_len(&self) -> Result<usize, GptError> {
+        let size = self.partition_entry_array_size();
+        if size > MAX_ENTRY_ARRAY_SIZE {
+            return Err(GptError::InvalidHeader);
+        }
+        usize::try_from(size).map_err(|_| GptError::InvalidHeader)
+    }
+
+    /// Number of blocks of the partition entry array.
+    fn entry_array_blocks(&self, block_size: u32) -> u64 {
+        self.partition_entry_array_size()
+            .div_ceil(u64::from(block_size))
+    }
+}
+
+/// A used entry of the partition entry array.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct GptPartition {
+    index: u32,
+    entry: GptPartitionEntry,
+}
+
+impl GptPartition {
+    /// Parses a partition entry. Only the first [`GPT_ENTRY_MIN_SIZE`] bytes
+    /// of `bytes` are used.
+    ///
+    /// Characters of the partition name that are not valid UCS-2 are
+    /// replaced with `?`.
+    ///
+    /// # Panics
+    /// Panics if `bytes` is shorter than [`GPT_ENTRY_MIN_SIZE`].
+    #[must_use]
+    pub fn parse(index: u32, bytes: &[u8]) -> Self {
+        assert!(bytes.len() >= GPT_ENTRY_MIN_SIZE as usize);
+        let mut partition_name = [NUL_16; 36];
+        for (i, c) in partition_name.iter_mut().enumerate() {
+            let raw = u16::from_le_bytes([bytes[56 + 2 * i], bytes[57 + 2 * i]]);
+            *c = Char16::try_from(raw).unwrap_or_else(|_| Char16::try_from('?').unwrap());
+        }
+        Self {
+            index,
+            entry: GptPartitionEntry {
+                partition_type_guid: GptPartitionType(read_guid(bytes, 0)),
+                unique_partition_guid: read_guid(bytes, 16),
+                starting_lba: read_u64(bytes, 32),
+                ending_lba: read_u64(bytes, 40),
+                attributes: GptPartitionAttributes::from_bits_retain(read_u64(bytes, 48)),
+                partition_name,
+            },
+        }
+    }
+
+    /// Serializes the entry into [`GPT_ENTRY_MIN_SIZE`] bytes.
+    #[must_use]
+    pub fn to_bytes(&self) -> [u8; GPT_ENTRY_MIN_SIZE as usize] {
+        let entry = self.entry;
+        let mut bytes = [0; GPT_ENTRY_MIN_SIZE as usize];
+        bytes[..16].copy_from_slice(&entry.partition_type_guid.0.to_bytes());
+        bytes[16..32].copy_from_slice(&entry.unique_partition_guid.to_bytes());
+        bytes[32..40].copy_from_slice(&entry.starting_lba.to_le_bytes());
+        bytes[40..48].copy_from_slice(&entry.ending_lba.to_le_bytes());
+        bytes[48..56].copy_from_slice(&{ entry.attributes }.bits().to_le_bytes());
+        let partition_name = entry.partition_name;
+        for (i, c) in partition_name.into_iter().enumerate() {
+            bytes[56 + 2 * i..58 + 2 * i].copy_from_slice(&u16::from(c).to_le_bytes());
+        }
+        bytes
+    }
+
+    /// Index of the entry in the partition entry array.
+    #[must_use]
+    pub const fn index(&self) -> u32 {
+        self.index
+    }
+
+    /// Returns the raw entry.
+    #[must_use]
+    pub const fn entry(&self) -> &GptPartitionEntry {
+        &self.entry
+    }
+
+    /// GUID that defines the type of the partition.
+    #[must_use]
+    pub const fn partition_type(&self) -> GptPartitionType {
+        self.entry.partition_type_guid
+    }
+
+    /// GUID that is unique for every partition.
+    #[must_use]
+    pub const fn unique_guid(&self) -> Guid {
+        self.entry.unique_partition_guid
+    }
+
+    /// First LBA of the partition.
+    #[must_use]
+    pub const fn starting_lba(&self) -> u64 {
+        self.entry.starting_lba
+    }
+
+    /// Last LBA of the partition, inclusive.
+    #[must_use]
+    pub const fn ending_lba(&self) -> u64 {
+        self.entry.ending_lba
+    }
+
+    /// Number of blocks in the partition. See
+    /// [`GptPartitionEntry::num_blocks`].
+    #[must_use]
+    pub fn num_blocks(&self) -> Option<u64> {
+        self.entry.num_blocks()
+    }
+
+    /// Attributes of the partition.
+    #[must_use]
+    pub const fn attributes(&self) -> GptPartitionAttributes {
+        self.entry.attributes
+    }
+
+    /// Human-readable name of the partition.
+    #[must_use]
+    pub fn name(&self) -> CString16 {
+        let chars = self.entry.partition_name;
+        let mut name = CString16::new();
+        for c in chars.into_iter().take_while(|c| *c != NUL_16) {
+            name.push(c);
+        }
+        name
+    }
+}
+
+/// A GUID partition table read from a disk.
+///
+/// # Example
+///
+/// ```no_run
+/// use uefi::boot;
+/// use uefi::media::Disk;
+/// use uefi::media::gpt::Gpt;
+/// use uefi::proto::media::block::BlockIO;
+/// use uefi::Handle;
+///
+/// fn print_partitions(handle: Handle) {
+///     let mut block_io = boot::open_protocol_exclusive::<BlockIO>(handle).unwrap();
+///     let gpt = Gpt::read(&mut Disk::new(&mut block_io)).unwrap();
+///     for partition in gpt.partitions() {
+///         log::info!(
+///             "{}: {} {}..={}",
+///             partition.index(),
+///             partition.name(),
+///             partition.starting_lba(),
+///             partition.ending_lba()
+///         );
+///     }
+/// }
+/// ```
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Gpt {
+    header: GptHeader,
+    partitions: Vec<GptPartition>,
+    is_backup: bool,
+}
+
+impl Gpt {
+    /// Reads the GPT of `disk`.
+    ///
+    /// The protective MBR is validated first. Then the primary header and
+    /// its partition entry array are read. If either is invalid, the backup
+    /// header in the last block of the disk is used instead.
+    ///
+    /// # Errors
+    /// * [`GptError::NoProtectiveMbr`] if the disk has no protective MBR.
+    /// * The error of the primary header, if both headers are invalid.
+    pub fn read(disk: &mut impl BlockDevice) -> Result<Self, GptError> {
+        let block_size = disk.block_size() as usize;
+        let mut block = vec![0; block_size];
+
+        disk.read_blocks(0, &mut block)?;
+        if !Mbr::parse(&block).is_ok_and(|mbr| mbr.is_protective()) {
+            return Err(GptError::NoProtectiveMbr);
+        }
+
+        let primary = Self::read_at(disk, 1, &mut block);
+        if primary.is_ok() {
+            return primary;
+        }
+        let last_block = disk.num_blocks().saturating_sub(1);
+        Self::read_at(disk, last_block, &mut block)
+            .map(|gpt| Self {
+                is_backup: true,
+                ..gpt
+            })
+            .or(primary)
+    }
+
+    /// Reads the header at `lba` and its partition entry array.
+    fn read_at(disk: &mut impl BlockDevice, lba: u64, block: &mut [u8]) -> Result<Self, GptError> {
+        disk.read_blocks(lba, block)?;
+        let header = GptHeader::parse(block)?;
+        if header.my_lba != lba {
+            return Err(GptError::InvalidHeader);
+        }
+
+        // The entry array must be within the disk.
+        let size = header.entry_array_len()?;
+        let entry_end = header
+            .partition_entry_lba
+            .checked_add(header.entry_array_blocks(disk.block_size()));
+        if entry_end.is_none_or(|end| end > disk.num_blocks()) {
+            return Err(GptError::InvalidHeader);
+        }
+        let mut entries = vec![0; size];
+        disk.read_blocks(header.partition_entry_lba, &mut entries)?;
+        if crc32::crc32(&entries) != header.partition_entry_array_crc32 {
+            return Err(GptError::EntryArrayCrcMismatch);
+        }
+
+        let partitions = entries
+            .chunks_exact(header.size_of_partition_entry as usize)
+            .zip(0..)
+            .map(|(bytes, index)| GptPartition::parse(index, bytes))
+            .filter(|partition| partition.partition_type() != GptPartitionType::UNUSED_ENTRY)
+            .collect();
+        Ok(Self {
+            header,
+            partitions,
+            is_backup: false,
+        })
+    }
+
+    /// Returns the header the table was read from.
+    #[must_use]
+    pub const fn header(&self) -> &GptHeader {
+        &self.header
+    }
+
+    /// GUID identifying the disk.
+    #[must_use]
+    pub const fn disk_guid(&self) -> Guid {
+        self.header.disk_guid
+    }
+
+    /// Returns the used partition entries, ordered by their index.
+    #[must_use]
+    pub fn partitions(&self) -> &[GptPartition] {
+        &self.partitions
+    }
+
+    /// Returns whether the table was read from the backup header, because
+    /// the primary header or its entry array is corrupted.
+    #[must_use]
+    pub const fn is_backup(&self) -> bool {
+        self.is_backup
+    }
+
+    /// Creates an empty table for `disk` with the GUID `disk_guid`, and room
+    /// for 128 partitions. Nothing is written until [`write`](Self::write)
+    /// is called.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use uefi::media::gpt::{self, Gpt, GptError};
+    /// use uefi::media::BlockDevice;
+    /// use uefi::proto::media::partition::GptPartitionType;
+    /// use uefi::{cstr16, guid};
+    ///
+    /// fn partition(disk: &mut impl BlockDevice) -> Result<(), GptError> {
+    ///     let mut gpt = Gpt::new(disk, guid!("5c7b4f8e-0d4a-4f5e-9a7b-2d6c1e3f4a5b"))?;
+    ///     // A 512 MiB ESP, aligned to 1 MiB.
+    ///     let blocks = (512 << 20) / u64::from(disk.block_size());
+    ///     let align = (1 << 20) / u64::from(disk.block_size());
+    ///     let start = gpt.find_free_space(blocks, align).ok_or(GptError::DiskTooSmall)?;
+    ///     gpt.add_partition(
+    ///         GptPartitionType::EFI_SYSTEM_PARTITION,
+    ///         guid!("0b2d3e4f-5a6b-4c7d-8e9f-a0b1c2d3e4f5"),
+    ///         start,
+    ///         start + blocks - 1,
+    ///         cstr16!("EFI System Partition"),
+    ///     )?;
+    ///     gpt::write_protective_mbr(disk)?;
+    ///     gpt.write(disk)
+    /// }
+    /// ```
+    ///
+    /// # Errors
+    /// * [`GptError::DiskTooSmall`] if the disk can't hold both headers and
+    ///   entry arrays, and at least one usable block.
+    pub fn new(disk: &impl BlockDevice, disk_guid: Guid) -> Result<Self, GptError> {
+        let mut header = GptHeader {
+            revision: 0x0001_0000,
+            header_size: GPT_HEADER_MIN_SIZE,
+            header_crc32: 0,
+            my_lba: 1,
+            alternate_lba: 0,
+            first_usable_lba: 0,
+            last_usable_lba: 0,
+            disk_guid,
+            partition_entry_lba: 2,
+            number_of_partition_entries: DEFAULT_NUM_ENTRIES,
+            size_of_partition_entry: GPT_ENTRY_MIN_SIZE,
+            partition_entry_array_crc32: 0,
+        };
+        let entry_blocks = header.entry_array_blocks(disk.block_size());
+        let last_block = disk.num_blocks().saturating_sub(1);
+        header.alternate_lba = last_block;
+        header.first_usable_lba = 2 + entry_blocks;
+        header.last_usable_lba = last_block
+            .checked_sub(entry_blocks + 1)
+            .ok_or(GptError::DiskTooSmall)?;
+        if header.first_usable_lba > header.last_usable_lba {
+            return Err(GptError::DiskTooSmall);
+        }
+        Ok(Self {
+            header,
+            partitions: Vec::new(),
+            is_backup: false,
+        })
+    }
+
+    /// Returns the first block of a free range of `num_blocks` blocks in
+    /// the usable area, aligned to `alignment` blocks, if there is one.
+    #[must_use]
+    pub fn find_free_space(&self, num_blocks: u64, alignment: u64) -> Option<u64> {
+        let alignment = alignment.max(1);
+        let mut start = self.header.first_usable_lba.next_multiple_of(alignment);
+        // Partitions are checked in the order of their starting blocks.
+        let mut partitions: Vec<_> = self.partitions.iter().collect();
+        partitions.sort_by_key(|p| p.starting_lba());
+        for partition in partitions {
+            let end = start.checked_add(num_blocks.checked_sub(1)?)?;
+            if end < partition.starting_lba() {
+                break;
+            }
+            if partition.ending_lba() >= start {
+                start = partition
+                    .ending_lba()
+                    .checked_add(1)?
+                    .checked_next_multiple_of(alignment)?;
+            }
+        }
+        let end = start.checked_add(num_blocks.checked_sub(1)?)?;
+        (end <= self.header.last_usable_lba).then_some(start)
+    }
+
+    /// Adds a partition of type `partition_type` that spans the blocks
+    /// `starting_lba..=ending_lba` in the first free entry, and returns the
+    /// index of the entry.
+    ///
+    /// # Errors
+    /// * [`GptError::InvalidPartition`] if the blocks are outside of the
+    ///   usable area or overlap another partition.
+    /// * [`GptError::NameTooLong`] if `name` has more than 35 characters.
+    /// * [`GptError::TableFull`] if there is no free entry.
+    pub fn add_partition(
+        &mut self,
+        partition_type: GptPartitionType,
+        unique_guid: Guid,
+        starting_lba: u64,
+        ending_lba: u64,
+        name: &CStr16,
+    ) -> Result<u32, GptError> {
+        self.check_range(None, starting_lba, ending_lba)?;
+        let partition_name = encode_name(name)?;
+        let index = (0..self.header.number_of_partition_entries)
+            .find(|index| self.position(*index).is_err())
+            .ok_or(GptError::TableFull)?;
+        let partition = GptPartition {
+            index,
+            entry: GptPartitionEntry {
+                partition_type_guid: partition_type,
+                unique_partition_guid: unique_guid,
+                starting_lba,
+                ending_lba,
+                attributes: GptPartitionAttributes::empty(),
+                partition_name,
+            },
+        };
+        let position = self.position(index).unwrap_err();
+        self.partitions.insert(position, partition);
+        Ok(index)
+    }
+
+    /// Deletes the partition with the entry `index`, and returns it.
+    ///
+    /// # Errors
+    /// * [`GptError::PartitionNotFound`] if the entry is unused.
+    pub fn delete_partition(&mut self, index: u32) -> Result<GptPartition, GptError> {
+        let position = self
+            .position(index)
+            .map_err(|_| GptError::PartitionNotFound)?;
+        Ok(self.partitions.remove(position))
+    }
+
+    /// Moves the last block of the partition with the entry `index` to
+    /// `ending_lba`. The data of the partition is not touched.
+    ///
+    /// # Errors
+    /// * [`GptError::PartitionNotFound`] if the entry is unused.
+    /// * [`GptError::InvalidPartition`] if the partition would end outside
+    ///   of the usable area, before it starts, or overlap another partition.
+    pub fn resize_partition(&mut self, index: u32, ending_lba: u64) -> Result<(), GptError> {
+        let starting_lba = self.partition_mut(index)?.starting_lba();
+        self.check_range(Some(index), starting_lba, ending_lba)?;
+        self.partition_mut(index)?.entry.ending_lba = ending_lba;
+        Ok(())
+    }
+
+    /// Sets the attributes of the partition with the entry `index`.
+    ///
+    /// # Errors
+    /// * [`GptError::PartitionNotFound`] if the entry is unused.
+    pub fn set_attributes(
+        &mut self,
+        index: u32,
+        attributes: GptPartitionAttributes,
+    ) -> Result<(), GptError> {
+        self.partition_mut(index)?.entry.attributes = attributes;
+        Ok(())
+    }
+
+    /// Sets the name of the partition with the entry `index`.
+    ///
+    /// # Errors
+    /// * [`GptError::PartitionNotFound`] if the entry is unused.
+    /// * [`GptError::NameTooLong`] if `name` has more than 35 characters.
+    pub fn set_name(&mut self, index: u32, name: &CStr16) -> Result<(), GptError> {
+        let partition_name = encode_name(name)?;
+        self.partition_mut(index)?.entry.partition_name = partition_name;
+        Ok(())
+    }
+
+    /// Writes the primary and the backup headers and partition entry
+    /// arrays to `disk`, and flushes it.
+    ///
+    /// The backup header is written to the last block of the disk, even if
+    /// it was elsewhere before. Bytes of the partition entries beyond the
+    /// first [`GPT_ENTRY_MIN_SIZE`] are written as zeros. A new table
+    /// needs a protective MBR as well, see [`write_protective_mbr`].
+    ///
+    /// # Errors
+    /// * [`GptError::Io`] if writing to the disk fails.
+    /// * [`GptError::DiskTooSmall`] if the backup entry array would overlap
+    ///   the usable area.
+    pub fn write(&mut self, disk: &mut impl BlockDevice) -> Result<(), GptError> {
+        let block_size = disk.block_size();
+        let entry_size = self.header.size_of_partition_entry as usize;
+        let size = self.header.entry_array_len()?;
+        let mut entries = vec![0; size];
+        for partition in &self.partitions {
+            let offset = partition.index as usize * entry_size;
+            entries[offset..offset + GPT_ENTRY_MIN_SIZE as usize]
+                .copy_from_slice(&partition.to_bytes());
+        }
+
+        let entry_blocks = self.header.entry_array_blocks(block_size);
+        let last_block = disk.num_blocks().saturating_sub(1);
+        let backup_entry_lba = last_block
+            .checked_sub(entry_blocks)
+            .filter(|lba| *lba > self.header.last_usable_lba)
+            .ok_or(GptError::DiskTooSmall)?;
+        let primary_entry_lba = if self.is_backup {
+            2
+        } else {
+            self.header.partition_entry_lba
+        };
+
+        let mut primary = GptHeader {
+            header_size: GPT_HEADER_MIN_SIZE,
+            my_lba: 1,
+            alternate_lba: last_block,
+            partition_entry_lba: primary_entry_lba,
+            partition_entry_array_crc32: crc32::crc32(&entries),
+            ..self.header
+        };
+        let mut backup = GptHeader {
+            my_lba: last_block,
+            alternate_lba: 1,
+            partition_entry_lba: backup_entry_lba,
+            ..primary
+        };
+        for header in [&mut primary, &mut backup] {
+            header.header_crc32 = 0;
+            header.header_crc32 = crc32::crc32(&header.to_bytes());
+        }
+
+        // The backup is written first, so that a valid copy remains if
+        // writing is interrupted.
+        let mut block = vec![0; block_size as usize];
+        for header in [&backup, &primary] {
+            write_blocks(disk, header.partition_entry_lba, &entries)?;
+            block[..GPT_HEADER_MIN_SIZE as usize].copy_from_slice(&header.to_bytes());
+            write_blocks(disk, header.my_lba, &block)?;
+        }
+        disk.flush()?;
+
+        self.header = primary;
+        self.is_backup = false;
+        Ok(())
+    }
+
+    /// Returns the position of the partition with the entry `index` in
+    /// `self.partitions`, or the position to insert it at.
+    fn position(&self, index: u32) -> Result<usize, usize> {
+        self.partitions.binary_search_by_key(&index, |p| p.index)
+    }
+
+    fn partition_mut(&mut self, index: u32) -> Result<&mut GptPartition, GptError> {
+        let position = self
+            .position(index)
+            .map_err(|_| GptError::PartitionNotFound)?;
+        Ok(&mut self.partitions[position])
+    }
+
+    /// Checks that `starting_lba..=ending_lba` is in the usable area and
+    /// doesn't overlap a partition other than the one with the entry
+    /// `index`.
+    fn check_range(
+        &self,
+        index: Option<u32>,
+        starting_lba: u64,
+        ending_lba: u64,
+    ) -> Result<(), GptError> {
+        let valid = starting_lba >= self.header.first_usable_lba
+            && starting_lba <= ending_lba
+            && ending_lba <= self.header.last_usable_lba
+            && self.partitions.iter().all(|p| {
+                Some(p.index) == index
+                    || ending_lba < p.starting_lba()
+                    || starting_lba > p.ending_lba()
+            });
+        if valid {
+            Ok(())
+        } else {
+            Err(GptError::InvalidPartition)
+        }
+    }
+}
+
+/// Writes a protective MBR to the first block of `disk`, which marks the
+/// whole disk as used by a GPT. Any boot code in the block is overwritten.
+///
+/// # Errors
+/// * [`GptError::Io`] if writing to the disk fails.
+pub fn write_protective_mbr(disk: &mut impl BlockDevice) -> Result<(), GptError> {
+    let size = u32::try_from(disk.num_blocks().saturating_sub(1)).unwrap_or(u32::MAX);
+    let mut block = vec![0; disk.block_size() as usize];
+    let record = &mut block[446..462];
+    // Starting CHS address of LBA 1.
+    record[1..4].copy_from_slice(&[0x00, 0x02, 0x00]);
+    record[4] = MbrOsType::GPT_PROTECTIVE.0;
+    record[5..8].copy_from_slice(&[0xff; 3]);
+    record[8..12].copy_from_slice(&1u32.to_le_bytes());
+    record[12..16].copy_from_slice(&size.to_le_bytes());
+    block[510..512].copy_from_slice(&MBR_SIGNATURE);
+    write_blocks(disk, 0, &block)?;
+    Ok(())
+}
+
+/// Encodes a partition name, which must leave room for a null character.
+fn encode_name(name: &CStr16) -> Result<[Char16; 36], GptError> {
+    let chars = name.as_slice();
+    let mut partition_name = [NUL_16; 36];
+    if chars.len() >= partition_name.len() {
+        return Err(GptError::NameTooLong);
+    }
+    partition_name[..chars.len()].copy_from_slice(chars);
+    Ok(partition_name)
+}
+
+fn write_blocks(disk: &mut impl BlockDevice, lba: u64, buffer: &[u8]) -> crate::Result {
+    let offset = lba
+        .checked_mul(u64::from(disk.block_size()))
+        .ok_or(crate::Status::INVALID_PARAMETER)?;
+    disk.write_at(offset, buffer)
+}
+
+fn read_u32(bytes: &[u8], offset: usize) -> u32 {
+    u32::from_le_bytes(bytes[offset..offset + 4].try_into().unwrap())
+}
+
+fn read_u64(bytes: &[u8], offset: usize) -> u64 {
+    u64::from_le_bytes(bytes[offset..offset + 8].try_into().unwrap())
+}
+
+fn read_guid(bytes: &[u8], offset: usize) -> Guid {
+    Guid::from_bytes(bytes[offset..offset + 16].try_into().unwrap())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::media::MemoryDisk;
+    use crate::{cstr16, guid};
+
+    const BLOCK_SIZE: usize = 512;
+    const NUM_BLOCKS: u64 = 128;
+    const ESP_GUID: Guid = guid!("11111111-2222-3333-4444-555555555555");
+    const DISK_GUID: Guid = guid!("66666666-7777-8888-9999-aaaaaaaaaaaa");
+
+    fn write_header(disk: &mut [u8], my_lba: u64, alternate_lba: u64, entry_lba: u64) {
+        let entries_crc = {
+            let start = entry_lba as usize * BLOCK_SIZE;
+            crc32::crc32(&disk[start..start + 128 * 128])
+        };
+        let block = &mut disk[my_lba as usize * BLOCK_SIZE..][..BLOCK_SIZE];
+        block.fill(0);
+        block[..8].copy_from_slice(&GPT_SIGNATURE);
+        block[8..12].copy_from_slice(&0x0001_0000u32.to_le_bytes());
+        block[12..16].copy_from_slice(&92u32.to_le_bytes());
+        block[24..32].copy_from_slice(&my_lba.to_le_bytes());
+        block[32..40].copy_from_slice(&alternate_lba.to_le_bytes());
+        block[40..48].copy_from_slice(&34u64.to_le_bytes());
+        block[48..56].copy_from_slice(&(NUM_BLOCKS - 34).to_le_bytes());
+        block[56..72].copy_from_slice(&DISK_GUID.to_bytes());
+        block[72..80].copy_from_slice(&entry_lba.to_le_bytes());
+        block[80..84].copy_from_slice(&128u32.to_le_bytes());
+        block[84..88].copy_from_slice(&128u32.to_le_bytes());
+        block[88..92].copy_from_slice(&entries_crc.to_le_bytes());
+        let crc = crc32::crc32(&block[..92]);
+        block[16..20].copy_from_slice(&crc.to_le_bytes());
+    }
+
+    fn make_disk() -> Vec<u8> {
+        let mut disk = vec![0; NUM_BLOCKS as usize * BLOCK_SIZE];
+
+        // Protective MBR.
+        disk[446 + 4] = 0xee;
+        disk[510] = 0x55;
+        disk[511] = 0xaa;
+
+        // A single partition in entry 1 of the primary and backup arrays.
+        for entry_lba in [2, NUM_BLOCKS - 33] {
+            let entry = &mut disk[entry_lba as usize * BLOCK_SIZE + 128..][..128];
+            entry[..16].copy_from_slice(&GptPartitionType::EFI_SYSTEM_PARTITION.0.to_bytes());
+            entry[16..32].copy_from_slice(&ESP_GUID.to_bytes());
+            entry[32..40].copy_from_slice(&34u64.to_le_bytes());
+            entry[40..48].copy_from_slice(&99u64.to_le_bytes());
+            entry[48..56].copy_from_slice(&1u64.to_le_bytes());
+            for (i, c) in "ESP".encode_utf16().enumerate() {
+                entry[56 + 2 * i..58 + 2 * i].copy_from_slice(&c.to_le_bytes());
+            }
+        }
+
+        write_header(&mut disk, 1, NUM_BLOCKS - 1, 2);
+        write_header(&mut disk, NUM_BLOCKS - 1, 1, NUM_BLOCKS - 33);
+        disk
+    }
+
+    fn check_partitions(gpt: &Gpt) {
+        assert_eq!(gpt.disk_guid(), DISK_GUID);
+        let [partition] = gpt.partitions() else {
+            panic!("expected one partition");
+        };
+        assert_eq!(partition.index(), 1);
+        assert_eq!(
+            partition.partition_type(),
+            GptPartitionType::EFI_SYSTEM_PARTITION
+        );
+        assert_eq!(partition.unique_guid(), ESP_GUID);
+        assert_eq!(partition.starting_lba(), 34);
+        assert_eq!(partition.ending_lba(), 99);
+        assert_eq!(partition.num_blocks(), Some(66));
+        assert_eq!(
+            partition.attributes(),
+            GptPartitionAttributes::REQUIRED_PARTITION
+        );
+        assert_eq!(partition.name(), CString16::try_from("ESP").unwrap());
+    }
+
+    #[test]
+    fn test_read_primary() {
+        let mut disk = MemoryDisk::new(make_disk(), BLOCK_SIZE as u32);
+        let gpt = Gpt::read(&mut disk).unwrap();
+        assert!(!gpt.is_backup());
+        assert_eq!(gpt.header().my_lba, 1);
+        check_partitions(&gpt);
+    }
+
+    #[test]
+    fn test_oversized_entry_array() {
+        let mut data = make_disk();
+        let block = &mut data[BLOCK_SIZE..2 * BLOCK_SIZE];
+        block[80..84].copy_from_slice(&0x0100_0000u32.to_le_bytes());
+        block[16..20].fill(0);
+        let crc = crc32::crc32(&block[..92]);
+        block[16..20].copy_from_slice(&crc.to_le_bytes());
+        assert_eq!(GptHeader::parse(block), Err(GptError::InvalidHeader));
+
+        // The backup is used instead.
+        let gpt = Gpt::read(&mut MemoryDisk::new(data, BLOCK_SIZE as u32)).unwrap();
+        assert!(gpt.is_backup());
+    }
+
+    #[test]
+    fn test_read_backup() {
+        // Corrupt the primary entry array.
+        let mut data = make_disk();
+        data[2 * BLOCK_SIZE + 200] ^= 1;
+        let mut disk = MemoryDisk::new(data, BLOCK_SIZE as u32);
+        let gpt = Gpt::read(&mut disk).unwrap();
+        assert!(gpt.is_backup());
+        assert_eq!(gpt.header().my_lba, NUM_BLOCKS - 1);
+        check_partitions(&gpt);
+
+        // Corrupt the primary header as well.
+        let mut data = disk.into_inner();
+        data[BLOCK_SIZE + 40] ^= 1;
+        let gpt = Gpt::read(&mut MemoryDisk::new(data.clone(), BLOCK_SIZE as u32)).unwrap();
+        assert!(gpt.is_backup());
+
+        // With both copies corrupted, the primary error is returned.
+        data[(NUM_BLOCKS as usize - 1) * BLOCK_SIZE + 40] ^= 1;
+        assert_eq!(
+            Gpt::read(&mut MemoryDisk::new(data, BLOCK_SIZE as u32)),
+            Err(GptError::HeaderCrcMismatch)
+        );
+    }
+
+    #[test]
+    fn test_no_protective_mbr() {
+        let mut data = make_disk();
+        data[446 + 4] = 0x83;
+        assert_eq!(
+            Gpt::read(&mut MemoryDisk::new(data, BLOCK_SIZE as u32)),
+            Err(GptError::NoProtectiveMbr)
+        );
+    }
+
+    #[test]
+    fn test_parse_header_errors() {
+        let disk = make_disk();
+        let block = &disk[BLOCK_SIZE..2 * BLOCK_SIZE];
+        assert!(GptHeader::parse(block).is_ok());
+        assert_eq!(GptHeader::parse(&block[..91]), Err(GptError::InvalidHeader));
+
+        let mut bad = block.to_vec();
+        bad[0] = b'X';
+        assert_eq!(GptHeader::parse(&bad), Err(GptError::InvalidSignature));
+    }
+
+    fn blank_disk() -> MemoryDisk {
+        MemoryDisk::new(vec![0; NUM_BLOCKS as usize * BLOCK_SIZE], BLOCK_SIZE as u32)
+    }
+
+    #[test]
+    fn test_create() {
+        let mut disk = blank_disk();
+        let mut gpt = Gpt::new(&disk, DISK_GUID).unwrap();
+        assert_eq!(gpt.header().first_usable_lba, 34);
+        assert_eq!(gpt.header().last_usable_lba, NUM_BLOCKS - 34);
+
+        assert_eq!(gpt.find_free_space(30, 2), Some(34));
+        let index = gpt
+            .add_partition(
+                GptPartitionType::EFI_SYSTEM_PARTITION,
+                ESP_GUID,
+                34,
+                63,
+                cstr16!("ESP"),
+            )
+            .unwrap();
+        assert_eq!(index, 0);
+        gpt.set_attributes(0, GptPartitionAttributes::REQUIRED_PARTITION)
+            .unwrap();
+        assert_eq!(gpt.find_free_space(1, 4), Some(64));
+        assert_eq!(gpt.find_free_space(31, 1), Some(64));
+        assert_eq!(gpt.find_free_space(32, 1), None);
+
+        write_protective_mbr(&mut disk).unwrap();
+        gpt.write(&mut disk).unwrap();
+        assert!(Mbr::read(&mut disk).unwrap().is_protective());
+
+        let read = Gpt::read(&mut disk).unwrap();
+        assert_eq!(read, gpt);
+        let [partition] = read.partitions() else {
+            panic!("expected one partition");
+        };
+        assert_eq!(partition.name(), CString16::try_from("ESP").unwrap());
+        assert_eq!(partition.num_blocks(), Some(30));
+
+        // The backup copy is valid as well.
+        let data = disk.into_inner();
+        let backup = GptHeader::parse(&data[(NUM_BLOCKS as usize - 1) * BLOCK_SIZE..]).unwrap();
+        assert_eq!(backup.partition_entry_lba, NUM_BLOCKS - 33);
+        assert_eq!(backup.alternate_lba, 1);
+        assert_eq!(
+            backup.partition_entry_array_crc32,
+            read.header().partition_entry_array_crc32
+        );
+
+        let small = MemoryDisk::new(vec![0; 67 * BLOCK_SIZE], BLOCK_SIZE as u32);
+        assert_eq!(Gpt::new(&small, DISK_GUID), Err(GptError::DiskTooSmall));
+    }
+
+    #[test]
+    fn test_modify() {
+        let mut disk = blank_disk();
+        write_protective_mbr(&mut disk).unwrap();
+        let mut gpt = Gpt::new(&disk, DISK_GUID).unwrap();
+        let other = guid!("bbbbbbbb-cccc-dddd-eeee-ffffffffffff");
+        let ty = GptPartitionType::EFI_SYSTEM_PARTITION;
+        gpt.add_partition(ty, ESP_GUID, 50, 60, cstr16!("ESP"))
+            .unwrap();
+
+        assert_eq!(
+            gpt.add_partition(ty, other, 60, 70, cstr16!("Data")),
+            Err(GptError::InvalidPartition)
+        );
+        assert_eq!(
+            gpt.add_partition(ty, other, 33, 40, cstr16!("Data")),
+            Err(GptError::InvalidPartition)
+        );
+        assert_eq!(
+            gpt.add_partition(ty, other, 70, NUM_BLOCKS - 33, cstr16!("Data")),
+            Err(GptError::InvalidPartition)
+        );
+        assert_eq!(
+            gpt.add_partition(
+                ty,
+                other,
+                70,
+                80,
+                cstr16!("A very long name for a partition !!!")
+            ),
+            Err(GptError::NameTooLong)
+        );
+        assert_eq!(gpt.add_partition(ty, other, 70, 80, cstr16!("Data")), Ok(1));
+        assert_eq!(gpt.find_free_space(16, 1), Some(34));
+        assert_eq!(gpt.find_free_space(17, 1), None);
+        assert_eq!(gpt.find_free_space(5, 16), Some(64));
+
+        assert_eq!(gpt.resize_partition(0, 70), Err(GptError::InvalidPartition));
+        assert_eq!(
+            gpt.resize_partition(2, 70),
+            Err(GptError::PartitionNotFound)
+        );
+        gpt.resize_partition(1, NUM_BLOCKS - 34).unwrap();
+        gpt.set_name(0, cstr16!("Boot")).unwrap();
+        gpt.write(&mut disk).unwrap();
+
+        let mut gpt = Gpt::read(&mut disk).unwrap();
+        let [esp, data] = gpt.partitions() else {
+            panic!("expected two partitions");
+        };
+        assert_eq!(esp.name(), CString16::try_from("Boot").unwrap());
+        assert_eq!(data.unique_guid(), other);
+        assert_eq!(data.ending_lba(), NUM_BLOCKS - 34);
+
+        assert_eq!(gpt.delete_partition(0).unwrap().unique_guid(), ESP_GUID);
+        assert_eq!(gpt.delete_partition(0), Err(GptError::PartitionNotFound));
+        assert_eq!(gpt.add_partition(ty, ESP_GUID, 34, 40, cstr16!("")), Ok(0));
+        gpt.write(&mut disk).unwrap();
+        assert_eq!(Gpt::read(&mut disk).unwrap().partitions().len(), 2);
+    }
+
+    #[test]
+    fn test_write_after_backup() {
+        // Corrupt the primary header, and repair it by writing the table.
+        let mut data = make_disk();
+        data[BLOCK_SIZE + 40] ^= 1;
+        let mut disk = MemoryDisk::new(data, BLOCK_SIZE as u32);
+        let mut gpt = Gpt::read(&mut disk).unwrap();
+        assert!(gpt.is_backup());
+        gpt.write(&mut disk).unwrap();
+        assert!(!gpt.is_backup());
+
+        let gpt = Gpt::read(&mut disk).unwrap();
+        assert!(!gpt.is_backup());
+        check_partitions(&gpt);
+        assert_eq!(disk.into_inner(), make_disk());
+    }
+}
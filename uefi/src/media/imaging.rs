@@ -0,0 +1,369 @@
+// SPDX-License-Identifier: MIT OR Apache-2.0
+
+//! Writing raw disk images to block devices, e.g. to flash an installer
+//! image onto a USB stick. See [`write_image`].
+
+use super::BlockDevice;
+use crate::fs::io::Read;
+use alloc::boxed::Box;
+use alloc::vec;
+use core::fmt::{self, Debug, Display, Formatter};
+
+/// Default size of the buffer used by [`write_image`].
+const DEFAULT_BUFFER_SIZE: usize = 1024 * 1024;
+
+/// Errors that can happen while writing an image.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum ImageError {
+    /// Reading the image or accessing the device failed.
+    Io(crate::Error),
+    /// The image is larger than the device. Everything up to the part that
+    /// didn't fit has been written.
+    TooLarge,
+    /// The data read back from the device differs from the image.
+    VerifyFailed {
+        /// Byte offset on the device of the first difference.
+        offset: u64,
+    },
+}
+
+impl Display for ImageError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Io(err) => write!(f, "failed to write image: {err}"),
+            Self::TooLarge => write!(f, "image is larger than the device"),
+            Self::VerifyFailed { offset } => {
+                write!(f, "verification failed at offset {offset:#x}")
+            }
+        }
+    }
+}
+
+impl core::error::Error for ImageError {
+    fn source(&self) -> Option<&(dyn core::error::Error + 'static)> {
+        match self {
+            Self::Io(err) => Some(err),
+            _ => None,
+        }
+    }
+}
+
+impl From<crate::Error> for ImageError {
+    fn from(err: crate::Error) -> Self {
+        Self::Io(err)
+    }
+}
+
+/// Callback receiving the number of image bytes processed so far.
+type ProgressFn<'a> = Box<dyn FnMut(u64) + 'a>;
+
+/// Options for [`write_image`].
+///
+/// By default, a buffer of 1 MiB is used, all blocks are written and
+/// nothing is verified.
+pub struct ImageOptions<'a> {
+    buffer_size: usize,
+    skip_zero_blocks: bool,
+    verify: bool,
+    progress: Option<ProgressFn<'a>>,
+}
+
+impl<'a> ImageOptions<'a> {
+    /// Creates the default options.
+    #[must_use]
+    pub const fn new() -> Self {
+        Self {
+            buffer_size: DEFAULT_BUFFER_SIZE,
+            skip_zero_blocks: false,
+            verify: false,
+            progress: None,
+        }
+    }
+
+    /// Sets the size of the buffer, which is the amount of data read from
+    /// the image and written to the device at once. It is rounded down to
+    /// a multiple of the block size, but is at least one block.
+    #[must_use]
+    pub const fn buffer_size(mut self, buffer_size: usize) -> Self {
+        self.buffer_size = buffer_size;
+        self
+    }
+
+    /// Sets whether blocks containing only zeros are skipped instead of
+    /// written. This speeds up writing sparse images, but the skipped
+    /// blocks keep their previous content, so it should only be used on
+    /// devices that are known to be zeroed.
+    #[must_use]
+    pub const fn skip_zero_blocks(mut self, skip_zero_blocks: bool) -> Self {
+        self.skip_zero_blocks = skip_zero_blocks;
+        self
+    }
+
+    /// Sets whether written data is read back and compared to the image.
+    /// Each buffer is verified right after it has been written.
+    #[must_use]
+    pub const fn verify(mut self, verify: bool) -> Self {
+        self.verify = verify;
+        self
+    }
+
+    /// Sets a callback that is called after each buffer with the number of
+    /// image bytes processed so far.
+    #[must_use]
+    pub fn progress(mut self, progress: impl FnMut(u64) + 'a) -> Self {
+        self.progress = Some(Box::new(progress));
+        self
+    }
+}
+
+impl Default for ImageOptions<'_> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Debug for ImageOptions<'_> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ImageOptions")
+            .field("buffer_size", &self.buffer_size)
+            .field("skip_zero_blocks", &self.skip_zero_blocks)
+            .field("verify", &self.verify)
+            .finish_non_exhaustive()
+    }
+}
+
+/// Statistics of a successful [`write_image`] call.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct ImageSummary {
+    /// Size of the image in bytes.
+    pub bytes_read: u64,
+    /// Number of blocks written to the device.
+    pub blocks_written: u64,
+    /// Number of all-zero blocks that were skipped.
+    pub blocks_skipped: u64,
+}
+
+/// Writes the raw disk image provided by `reader` to the start of `device`.
+///
+/// The image is written in block-aligned chunks; if its size is not a
+/// multiple of the block size, the last block is padded with zeros. The
+/// device is flushed at the end.
+///
+/// # Example
+///
+/// ```no_run
+/// use uefi::fs::FileSystem;
+/// use uefi::media::Disk;
+/// use uefi::media::imaging::{write_image, ImageOptions};
+/// use uefi::proto::media::block::BlockIO;
+/// use uefi::proto::media::file::{File, FileAttribute, FileMode};
+/// use uefi::{boot, cstr16};
+///
+/// fn flash(block_io: &mut BlockIO) -> Result<(), Box<dyn core::error::Error>> {
+///     let mut volume = boot::get_image_file_system(boot::image_handle())?;
+///     let mut image = volume
+///         .open_volume()?
+///         .open(cstr16!("installer.img"), FileMode::Read, FileAttribute::empty())?
+///         .into_regular_file()
+///         .unwrap();
+///
+///     let options = ImageOptions::new()
+///         .verify(true)
+///         .progress(|bytes| log::info!("{} MiB written", bytes >> 20));
+///     write_image(&mut image, &mut Disk::new(block_io), options)?;
+///     Ok(())
+/// }
+/// ```
+///
+/// # Errors
+/// * [`ImageError::TooLarge`] if the image doesn't fit on the device.
+/// * [`ImageError::VerifyFailed`] if verification is enabled and the data
+///   read back differs.
+/// * [`ImageError::Io`] if reading the image or accessing the device fails.
+pub fn write_image<R: Read + ?Sized, D: BlockDevice + ?Sized>(
+    reader: &mut R,
+    device: &mut D,
+    mut options: ImageOptions,
+) -> Result<ImageSummary, ImageError> {
+    let block_size = device.block_size() as usize;
+    let chunk_size = (options.buffer_size / block_size).max(1) * block_size;
+    let mut buffer = vec![0; chunk_size];
+    let mut verify_buffer = vec![0; if options.verify { chunk_size } else { 0 }];
+    let is_skipped = |block: &[u8]| options.skip_zero_blocks && block.iter().all(|b| *b == 0);
+
+    let mut summary = ImageSummary::default();
+    let mut offset = 0;
+    loop {
+        let filled = fill_buffer(reader, &mut buffer)?;
+        if filled == 0 {
+            break;
+        }
+        let len = filled.next_multiple_of(block_size);
+        buffer[filled..len].fill(0);
+        if offset + len as u64 > device.size() {
+            return Err(ImageError::TooLarge);
+        }
+
+        // Write runs of consecutive blocks that are not skipped.
+        let mut start = 0;
+        while start < len {
+            if is_skipped(&buffer[start..start + block_size]) {
+                summary.blocks_skipped += 1;
+                start += block_size;
+                continue;
+            }
+            let mut end = start + block_size;
+            while end < len && !is_skipped(&buffer[end..end + block_size]) {
+                end += block_size;
+            }
+
+            let run = &buffer[start..end];
+            let run_offset = offset + start as u64;
+            device.write_at(run_offset, run)?;
+            if options.verify {
+                let read_back = &mut verify_buffer[..run.len()];
+                device.read_at(run_offset, read_back)?;
+                if let Some(pos) = run.iter().zip(&*read_back).position(|(a, b)| a != b) {
+                    return Err(ImageError::VerifyFailed {
+                        offset: run_offset + pos as u64,
+                    });
+                }
+            }
+            summary.blocks_written += ((end - start) / block_size) as u64;
+            start = end;
+        }
+
+        offset += len as u64;
+        summary.bytes_read += filled as u64;
+        if let Some(progress) = &mut options.progress {
+            progress(summary.bytes_read);
+        }
+        if filled < chunk_size {
+            break;
+        }
+    }
+
+    device.flush()?;
+    Ok(summary)
+}
+
+/// Reads from `reader` until `buffer` is full or the end of the stream is
+/// reached. Returns the number of bytes read.
+fn fill_buffer<R: Read + ?Sized>(reader: &mut R, buffer: &mut [u8]) -> crate::Result<usize> {
+    let mut filled = 0;
+    while filled < buffer.len() {
+        match reader.read(&mut buffer[filled..])? {
+            0 => break,
+            n => filled += n,
+        }
+    }
+    Ok(filled)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::media::MemoryDisk;
+    use alloc::vec::Vec;
+
+    /// Image of 8 blocks of 512 bytes, of which blocks 2, 3 and 6 are
+    /// zero, followed by a partial block.
+    fn make_image() -> Vec<u8> {
+        let mut image = vec![0; 8 * 512 + 100];
+        for (i, block) in image.chunks_mut(512).enumerate() {
+            if ![2, 3, 6].contains(&i) {
+                block.fill(i as u8 + 1);
+            }
+        }
+        image
+    }
+
+    #[test]
+    fn test_write_image() {
+        let image = make_image();
+        let mut disk = MemoryDisk::new(vec![0xff; 16 * 512], 512);
+        let mut progress = Vec::new();
+        let options = ImageOptions::new()
+            .buffer_size(2000)
+            .verify(true)
+            .progress(|bytes| progress.push(bytes));
+        let summary = write_image(&mut image.as_slice(), &mut disk, options).unwrap();
+
+        assert_eq!(
+            summary,
+            ImageSummary {
+                bytes_read: image.len() as u64,
+                blocks_written: 9,
+                blocks_skipped: 0,
+            }
+        );
+        // The buffer size is rounded down to 3 blocks.
+        assert_eq!(progress, [1536, 3072, 4196]);
+        assert_eq!(&disk.data()[..image.len()], image.as_slice());
+        assert!(disk.data()[image.len()..9 * 512].iter().all(|b| *b == 0));
+        assert!(disk.data()[9 * 512..].iter().all(|b| *b == 0xff));
+    }
+
+    #[test]
+    fn test_skip_zero_blocks() {
+        let image = make_image();
+        let mut disk = MemoryDisk::new(vec![0xff; 16 * 512], 512);
+        let options = ImageOptions::new().skip_zero_blocks(true);
+        let summary = write_image(&mut image.as_slice(), &mut disk, options).unwrap();
+
+        assert_eq!(summary.blocks_written, 6);
+        assert_eq!(summary.blocks_skipped, 3);
+        // Skipped blocks keep their previous content.
+        assert!(disk.data()[2 * 512..4 * 512].iter().all(|b| *b == 0xff));
+        assert_eq!(&disk.data()[4 * 512..6 * 512], &image[4 * 512..6 * 512]);
+    }
+
+    #[test]
+    fn test_too_large() {
+        let image = make_image();
+        let mut disk = MemoryDisk::new(vec![0; 8 * 512], 512);
+        assert_eq!(
+            write_image(&mut image.as_slice(), &mut disk, ImageOptions::new()),
+            Err(ImageError::TooLarge)
+        );
+    }
+
+    /// A device that silently drops writes to its second block.
+    struct FaultyDisk(MemoryDisk);
+
+    impl BlockDevice for FaultyDisk {
+        fn block_size(&self) -> u32 {
+            self.0.block_size()
+        }
+
+        fn num_blocks(&self) -> u64 {
+            self.0.num_blocks()
+        }
+
+        fn read_at(&mut self, offset: u64, buffer: &mut [u8]) -> crate::Result {
+            self.0.read_at(offset, buffer)
+        }
+
+        fn write_at(&mut self, offset: u64, buffer: &[u8]) -> crate::Result {
+            self.0.write_at(offset, buffer)?;
+            if offset <= 512 && offset + buffer.len() as u64 > 512 {
+                self.0.write_at(512, &[0; 512])?;
+            }
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn test_verify_failed() {
+        let image = make_image();
+        let mut disk = FaultyDisk(MemoryDisk::new(vec![0; 16 * 512], 512));
+        assert_eq!(
+            write_image(
+                &mut image.as_slice(),
+                &mut disk,
+                ImageOptions::new().verify(true)
+            ),
+            Err(ImageError::VerifyFailed { offset: 512 })
+        );
+    }
+}
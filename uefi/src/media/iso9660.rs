@@ -0,0 +1,781 @@
+// SPDX-License-Identifier: MIT OR Apache-2.0
+
+//! Read-only parser for ISO 9660 file systems, as found on CDs, DVDs and
+//! installer images, including the Joliet extension and the El Torito boot
+//! catalog. See [`Iso9660`].
+//!
+//! Rock Ridge extensions and multi-extent files are not supported. Names are
+//! taken from the Joliet directory tree if the volume has one, otherwise
+//! from the primary directory tree, with the `;1` version suffix removed.
+
+use super::BlockDevice;
+use alloc::string::String;
+use alloc::vec;
+use alloc::vec::Vec;
+use core::fmt::{self, Debug, Display, Formatter};
+
+/// Size of a sector of a CD, which is also the logical block size of
+/// almost all ISO 9660 file systems.
+pub const ISO_SECTOR_SIZE: u32 = 2048;
+
+/// Identifier of ISO 9660 volume descriptors.
+const STANDARD_ID: &[u8; 5] = b"CD001";
+
+/// Identifier of the El Torito boot record.
+const EL_TORITO_ID: &[u8] = b"EL TORITO SPECIFICATION";
+
+/// Sector of the first volume descriptor.
+const FIRST_DESCRIPTOR_SECTOR: u64 = 16;
+
+/// Upper limit for the number of volume descriptors, in case the terminator
+/// is missing.
+const MAX_DESCRIPTORS: u64 = 64;
+
+/// Errors that can happen while accessing an ISO 9660 file system.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum IsoError {
+    /// Reading from the device failed.
+    Io(crate::Error),
+    /// The device holds no ISO 9660 file system.
+    InvalidVolumeDescriptor,
+    /// A directory record or the boot catalog is invalid.
+    InvalidRecord,
+    /// The path does not exist.
+    NotFound,
+    /// A path component that must be a directory is a file.
+    NotADirectory,
+    /// The path points to a directory, but a file was expected.
+    NotAFile,
+}
+
+impl Display for IsoError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Io(err) => write!(f, "failed to read from device: {err}"),
+            Self::InvalidVolumeDescriptor => write!(f, "invalid ISO 9660 volume descriptor"),
+            Self::InvalidRecord => write!(f, "invalid ISO 9660 record"),
+            Self::NotFound => write!(f, "path not found"),
+            Self::NotADirectory => write!(f, "not a directory"),
+            Self::NotAFile => write!(f, "not a file"),
+        }
+    }
+}
+
+impl core::error::Error for IsoError {
+    fn source(&self) -> Option<&(dyn core::error::Error + 'static)> {
+        match self {
+            Self::Io(err) => Some(err),
+            _ => None,
+        }
+    }
+}
+
+impl From<crate::Error> for IsoError {
+    fn from(err: crate::Error) -> Self {
+        Self::Io(err)
+    }
+}
+
+/// An entry of a directory.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct IsoDirEntry {
+    name: String,
+    extent_lba: u32,
+    size: u32,
+    flags: u8,
+}
+
+impl IsoDirEntry {
+    /// Name of the entry.
+    #[must_use]
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// Returns whether the entry is a directory.
+    #[must_use]
+    pub const fn is_directory(&self) -> bool {
+        self.flags & 0x02 != 0
+    }
+
+    /// Returns whether the entry is hidden.
+    #[must_use]
+    pub const fn is_hidden(&self) -> bool {
+        self.flags & 0x01 != 0
+    }
+
+    /// Size of the entry's data in bytes.
+    #[must_use]
+    pub const fn size(&self) -> u32 {
+        self.size
+    }
+
+    /// First logical block of the entry's data.
+    #[must_use]
+    pub const fn extent_lba(&self) -> u32 {
+        self.extent_lba
+    }
+}
+
+newtype_enum! {
+    /// Platform of an El Torito boot entry.
+    pub enum BootPlatform: u8 => {
+        /// x86 BIOS.
+        X86 = 0x00,
+        /// PowerPC.
+        POWER_PC = 0x01,
+        /// Mac.
+        MAC = 0x02,
+        /// UEFI.
+        EFI = 0xef,
+    }
+}
+
+newtype_enum! {
+    /// Media emulated by an El Torito boot entry.
+    pub enum BootMediaType: u8 => {
+        /// No emulation, the image is loaded as is.
+        NO_EMULATION = 0x00,
+        /// 1.2 MB floppy disk.
+        FLOPPY_1_2M = 0x01,
+        /// 1.44 MB floppy disk.
+        FLOPPY_1_44M = 0x02,
+        /// 2.88 MB floppy disk.
+        FLOPPY_2_88M = 0x03,
+        /// Hard disk.
+        HARD_DISK = 0x04,
+    }
+}
+
+/// A boot entry of the El Torito boot catalog.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct BootEntry {
+    /// Platform the boot image is meant for.
+    pub platform: BootPlatform,
+    /// Whether the entry is marked as bootable.
+    pub bootable: bool,
+    /// Media emulated by the boot image.
+    pub media_type: BootMediaType,
+    /// Load segment for x86 BIOSes.
+    pub load_segment: u16,
+    /// Partition type of the boot image, for hard disk emulation.
+    pub system_type: u8,
+    /// Size of the boot image in virtual 512-byte sectors. Often set to `0`
+    /// or `1` for UEFI images that are larger.
+    pub sector_count: u16,
+    /// First logical block of the boot image.
+    pub load_lba: u32,
+}
+
+/// The El Torito boot catalog, which lists the boot images of a bootable
+/// CD.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct BootCatalog {
+    /// Manufacturer ID from the validation entry.
+    pub id_string: String,
+    /// All boot entries. The first one is the default entry, whose platform
+    /// is taken from the validation entry.
+    pub entries: Vec<BootEntry>,
+}
+
+impl BootCatalog {
+    /// Parses a boot catalog from its first sector.
+    ///
+    /// # Errors
+    /// * [`IsoError::InvalidRecord`] if the validation entry is invalid.
+    pub fn parse(data: &[u8]) -> Result<Self, IsoError> {
+        let validation = data.get(..32).ok_or(IsoError::InvalidRecord)?;
+        let checksum = validation.chunks_exact(2).fold(0u16, |sum, w| {
+            sum.wrapping_add(u16::from_le_bytes([w[0], w[1]]))
+        });
+        if validation[0] != 1 || validation[30..32] != [0x55, 0xaa] || checksum != 0 {
+            return Err(IsoError::InvalidRecord);
+        }
+        let id_string = trim_id(&validation[4..28]);
+
+        let default_entry = data.get(32..64).ok_or(IsoError::InvalidRecord)?;
+        let mut entries = vec![parse_boot_entry(default_entry, BootPlatform(validation[1]))];
+        // Section headers (0x90, or 0x91 for the last one), each followed by
+        // its section entries.
+        let mut offset = 64;
+        while let Some(header) = data.get(offset..offset + 32) {
+            if header[0] != 0x90 && header[0] != 0x91 {
+                break;
+            }
+            let platform = BootPlatform(header[1]);
+            let count = usize::from(u16::from_le_bytes([header[2], header[3]]));
+            for _ in 0..count {
+                offset += 32;
+                let entry = data
+                    .get(offset..offset + 32)
+                    .ok_or(IsoError::InvalidRecord)?;
+                entries.push(parse_boot_entry(entry, platform));
+            }
+            offset += 32;
+            if header[0] == 0x91 {
+                break;
+            }
+        }
+
+        Ok(Self { id_string, entries })
+    }
+}
+
+fn parse_boot_entry(entry: &[u8], platform: BootPlatform) -> BootEntry {
+    BootEntry {
+        platform,
+        bootable: entry[0] == 0x88,
+        media_type: BootMediaType(entry[1] & 0x0f),
+        load_segment: u16::from_le_bytes([entry[2], entry[3]]),
+        system_type: entry[4],
+        sector_count: u16::from_le_bytes([entry[6], entry[7]]),
+        load_lba: u32::from_le_bytes(entry[8..12].try_into().unwrap()),
+    }
+}
+
+/// An ISO 9660 file system on a [`BlockDevice`].
+///
+/// Paths use `/` or `\` as separator and are always relative to the root
+/// directory. Names are compared ignoring ASCII case.
+///
+/// # Example
+///
+/// ```no_run
+/// use uefi::media::Disk;
+/// use uefi::media::iso9660::Iso9660;
+/// use uefi::proto::media::block::BlockIO;
+///
+/// fn read_kernel(block_io: &mut BlockIO) -> Option<Vec<u8>> {
+///     let mut iso = Iso9660::new(Disk::new(block_io)).ok()?;
+///     log::info!("Volume: {}", iso.volume_id());
+///     iso.read("/boot/vmlinuz").ok()
+/// }
+/// ```
+pub struct Iso9660<D> {
+    device: D,
+    block_size: u32,
+    volume_id: String,
+    root: IsoDirEntry,
+    joliet: bool,
+    boot_catalog_lba: Option<u32>,
+}
+
+impl<D: BlockDevice> Iso9660<D> {
+    /// Opens the ISO 9660 file system on `device` by parsing its volume
+    /// descriptors.
+    ///
+    /// # Errors
+    /// * [`IsoError::InvalidVolumeDescriptor`] if the device holds no
+    ///   ISO 9660 file system.
+    /// * [`IsoError::Io`] if reading from the device fails.
+    pub fn new(mut device: D) -> Result<Self, IsoError> {
+        let mut primary = None;
+        let mut joliet = None;
+        let mut boot_catalog_lba = None;
+
+        let mut descriptor = [0; ISO_SECTOR_SIZE as usize];
+        for sector in FIRST_DESCRIPTOR_SECTOR..FIRST_DESCRIPTOR_SECTOR + MAX_DESCRIPTORS {
+            device.read_at(sector * u64::from(ISO_SECTOR_SIZE), &mut descriptor)?;
+            if &descriptor[1..6] != STANDARD_ID {
+                return Err(IsoError::InvalidVolumeDescriptor);
+            }
+            match descriptor[0] {
+                0 if descriptor[7..7 + EL_TORITO_ID.len()] == *EL_TORITO_ID => {
+                    boot_catalog_lba = Some(read_u32(&descriptor, 71));
+                }
+                1 if primary.is_none() => primary = Some(descriptor),
+                // Escape sequences for the UCS-2 levels 1 to 3.
+                2 if matches!(&descriptor[88..91], b"%/@" | b"%/C" | b"%/E") => {
+                    joliet = Some(descriptor);
+                }
+                255 => break,
+                _ => {}
+            }
+        }
+
+        let primary = primary.ok_or(IsoError::InvalidVolumeDescriptor)?;
+        let block_size = u32::from(read_u16(&primary, 128));
+        if !block_size.is_power_of_two() || block_size < 512 {
+            return Err(IsoError::InvalidVolumeDescriptor);
+        }
+        let (descriptor, is_joliet) = match &joliet {
+            Some(joliet) => (joliet, true),
+            None => (&primary, false),
+        };
+        let root = parse_dir_record(&descriptor[156..190], is_joliet)
+            .ok_or(IsoError::InvalidVolumeDescriptor)?;
+        let volume_id = if is_joliet {
+            decode_ucs2_be(&descriptor[40..72]).trim_end().into()
+        } else {
+            trim_id(&descriptor[40..72])
+        };
+
+        Ok(Self {
+            device,
+            block_size,
+            volume_id,
+            root,
+            joliet: is_joliet,
+            boot_catalog_lba,
+        })
+    }
+
+    /// Volume identifier, without trailing spaces.
+    #[must_use]
+    pub fn volume_id(&self) -> &str {
+        &self.volume_id
+    }
+
+    /// Returns whether names are taken from the Joliet directory tree.
+    #[must_use]
+    pub const fn is_joliet(&self) -> bool {
+        self.joliet
+    }
+
+    /// Logical block size of the file system in bytes.
+    #[must_use]
+    pub const fn block_size(&self) -> u32 {
+        self.block_size
+    }
+
+    /// Unwraps the file system, returning the underlying device.
+    #[must_use]
+    pub fn into_inner(self) -> D {
+        self.device
+    }
+
+    /// Returns the entries of the directory at `path`, without the `.` and
+    /// `..` entries.
+    ///
+    /// # Errors
+    /// * [`IsoError::NotFound`] if the path does not exist.
+    /// * [`IsoError::NotADirectory`] if the path is no directory.
+    pub fn read_dir(&mut self, path: &str) -> Result<Vec<IsoDirEntry>, IsoError> {
+        let dir = self.metadata(path)?;
+        if !dir.is_directory() {
+            return Err(IsoError::NotADirectory);
+        }
+        self.read_dir_entries(&dir)
+    }
+
+    /// Returns the entry at `path`.
+    ///
+    /// # Errors
+    /// * [`IsoError::NotFound`] if the path does not exist.
+    pub fn metadata(&mut self, path: &str) -> Result<IsoDirEntry, IsoError> {
+        let mut current = self.root.clone();
+        for component in path.split(['/', '\\']).filter(|c| !c.is_empty()) {
+            if !current.is_directory() {
+                return Err(IsoError::NotADirectory);
+            }
+            current = self
+                .read_dir_entries(&current)?
+                .into_iter()
+                .find(|entry| entry.name.eq_ignore_ascii_case(component))
+                .ok_or(IsoError::NotFound)?;
+        }
+        Ok(current)
+    }
+
+    /// Reads the entire content of the file at `path`.
+    ///
+    /// # Errors
+    /// * [`IsoError::NotFound`] if the path does not exist.
+    /// * [`IsoError::NotAFile`] if the path is a directory.
+    pub fn read(&mut self, path: &str) -> Result<Vec<u8>, IsoError> {
+        let entry = self.metadata(path)?;
+        if entry.is_directory() {
+            return Err(IsoError::NotAFile);
+        }
+        self.read_extent(entry.extent_lba, entry.size as usize)
+    }
+
+    /// Reads the El Torito boot catalog. Returns `None` if the volume is not
+    /// bootable.
+    ///
+    /// # Errors
+    /// * [`IsoError::InvalidRecord`] if the boot catalog is invalid.
+    pub fn boot_catalog(&mut self) -> Result<Option<BootCatalog>, IsoError> {
+        let Some(lba) = self.boot_catalog_lba else {
+            return Ok(None);
+        };
+        let data = self.read_extent(lba, ISO_SECTOR_SIZE as usize)?;
+        BootCatalog::parse(&data).map(Some)
+    }
+
+    /// Reads the boot image of `entry`.
+    ///
+    /// UEFI boot images are usually FAT file system images whose size is
+    /// not stored in the entry. If the entry's sector count is `0` or `1`,
+    /// the size is taken from the image's FAT boot sector instead.
+    ///
+    /// # Errors
+    /// * [`IsoError::Io`] if reading from the device fails.
+    /// * [`IsoError::InvalidRecord`] if the image extends beyond the end of
+    ///   the device.
+    pub fn read_boot_image(&mut self, entry: &BootEntry) -> Result<Vec<u8>, IsoError> {
+        let mut size = usize::from(entry.sector_count) * 512;
+        if entry.sector_count <= 1 {
+            let boot_sector = self.read_extent(entry.load_lba, 512)?;
+            let sectors = match u16::from_le_bytes([boot_sector[19], boot_sector[20]]) {
+                0 => read_u32(&boot_sector, 32),
+                n => u32::from(n),
+            };
+            let bytes_per_sector = read_u16(&boot_sector, 11);
+            if boot_sector[510..512] == [0x55, 0xaa] && bytes_per_sector != 0 {
+                size = usize::try_from(sectors)
+                    .ok()
+                    .and_then(|sectors| sectors.checked_mul(usize::from(bytes_per_sector)))
+                    .ok_or(IsoError::InvalidRecord)?;
+            }
+        }
+        self.read_extent(entry.load_lba, size)
+    }
+
+    /// Reads `size` bytes starting at the logical block `lba`. Sizes read
+    /// from the volume are untrusted, so the extent must be within the
+    /// device.
+    fn read_extent(&mut self, lba: u32, size: usize) -> Result<Vec<u8>, IsoError> {
+        let offset = u64::from(lba) * u64::from(self.block_size);
+        let end = u64::try_from(size)
+            .ok()
+            .and_then(|size| offset.checked_add(size));
+        if end.is_none_or(|end| end > self.device.size()) {
+            return Err(IsoError::InvalidRecord);
+        }
+        let mut data = vec![0; size];
+        self.device.read_at(offset, &mut data)?;
+        Ok(data)
+    }
+
+    fn read_dir_entries(&mut self, dir: &IsoDirEntry) -> Result<Vec<IsoDirEntry>, IsoError> {
+        let data = self.read_extent(dir.extent_lba, dir.size as usize)?;
+        let mut entries = Vec::new();
+        let mut offset = 0;
+        while offset < data.len() {
+            let len = usize::from(data[offset]);
+            if len == 0 {
+                // Records don't cross block boundaries, the rest of the
+                // block is padding.
+                offset = (offset / self.block_size as usize + 1) * self.block_size as usize;
+                continue;
+            }
+            let record = data
+                .get(offset..offset + len)
+                .ok_or(IsoError::InvalidRecord)?;
+            let entry = parse_dir_record(record, self.joliet).ok_or(IsoError::InvalidRecord)?;
+            // Skip `.` and `..`, which have the names 0x00 and 0x01.
+            if !entry.name.is_empty() && entry.name != "\u{1}" {
+                entries.push(entry);
+            }
+            offset += len;
+        }
+        Ok(entries)
+    }
+}
+
+impl<D> Debug for Iso9660<D> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Iso9660")
+            .field("volume_id", &self.volume_id)
+            .field("block_size", &self.block_size)
+            .field("joliet", &self.joliet)
+            .field("boot_catalog_lba", &self.boot_catalog_lba)
+            .finish_non_exhaustive()
+    }
+}
+
+/// Parses a directory record. Returns `None` if the record is truncated.
+fn parse_dir_record(record: &[u8], joliet: bool) -> Option<IsoDirEntry> {
+    let name_len = usize::from(*record.get(32)?);
+    let raw_name = record.get(33..33 + name_len)?;
+    let flags = record[25];
+    let name = match raw_name {
+        [0] => String::new(),
+        [1] => String::from("\u{1}"),
+        _ if joliet => decode_ucs2_be(raw_name),
+        _ => String::from_utf8_lossy(raw_name).into(),
+    };
+    Some(IsoDirEntry {
+        name: strip_version(name, flags & 0x02 != 0),
+        extent_lba: read_u32(record, 2),
+        size: read_u32(record, 10),
+        flags,
+    })
+}
+
+/// Removes the `;1` version suffix of file names, and the trailing `.` of
+/// names without extension.
+fn strip_version(mut name: String, is_directory: bool) -> String {
+    if !is_directory {
+        if let Some(pos) = name.rfind(';') {
+            name.truncate(pos);
+        }
+        if name.ends_with('.') {
+            name.pop();
+        }
+    }
+    name
+}
+
+fn decode_ucs2_be(bytes: &[u8]) -> String {
+    char::decode_utf16(
+        bytes
+            .chunks_exact(2)
+            .map(|c| u16::from_be_bytes([c[0], c[1]])),
+    )
+    .map(|c| c.unwrap_or(char::REPLACEMENT_CHARACTER))
+    .collect()
+}
+
+/// Converts a space- or NUL-padded identifier to a string.
+fn trim_id(bytes: &[u8]) -> String {
+    String::from_utf8_lossy(bytes)
+        .trim_end_matches([' ', '\0'])
+        .into()
+}
+
+fn read_u16(bytes: &[u8], offset: usize) -> u16 {
+    u16::from_le_bytes([bytes[offset], bytes[offset + 1]])
+}
+
+fn read_u32(bytes: &[u8], offset: usize) -> u32 {
+    u32::from_le_bytes(bytes[offset..offset + 4].try_into().unwrap())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::media::MemoryDisk;
+
+    const SECTOR: usize = ISO_SECTOR_SIZE as usize;
+
+    fn sector(image: &mut [u8], lba: usize) -> &mut [u8] {
+        &mut image[lba * SECTOR..(lba + 1) * SECTOR]
+    }
+
+    fn dir_record(name: &[u8], lba: u32, size: u32, is_dir: bool) -> Vec<u8> {
+        let len = (33 + name.len()).next_multiple_of(2);
+        let mut record = vec![0; len];
+        record[0] = len as u8;
+        record[2..6].copy_from_slice(&lba.to_le_bytes());
+        record[6..10].copy_from_slice(&lba.to_be_bytes());
+        record[10..14].copy_from_slice(&size.to_le_bytes());
+        record[14..18].copy_from_slice(&size.to_be_bytes());
+        record[25] = if is_dir { 2 } else { 0 };
+        record[32] = name.len() as u8;
+        record[33..33 + name.len()].copy_from_slice(name);
+        record
+    }
+
+    fn write_dir(image: &mut [u8], lba: usize, parent: u32, records: &[Vec<u8>]) {
+        let block = sector(image, lba);
+        let mut offset = 0;
+        let mut all = vec![
+            dir_record(&[0], lba as u32, SECTOR as u32, true),
+            dir_record(&[1], parent, SECTOR as u32, true),
+        ];
+        all.extend_from_slice(records);
+        for record in all {
+            block[offset..offset + record.len()].copy_from_slice(&record);
+            offset += record.len();
+        }
+    }
+
+    fn descriptor(image: &mut [u8], lba: usize, ty: u8) -> &mut [u8] {
+        let block = sector(image, lba);
+        block[0] = ty;
+        block[1..6].copy_from_slice(STANDARD_ID);
+        block[6] = 1;
+        block
+    }
+
+    fn ucs2(s: &str) -> Vec<u8> {
+        s.encode_utf16().flat_map(u16::to_be_bytes).collect()
+    }
+
+    /// Builds an image with a primary and a Joliet directory tree, and a boot
+    /// catalog with a BIOS and a UEFI entry.
+    fn make_image(with_joliet: bool) -> Vec<u8> {
+        let mut image = vec![0; 40 * SECTOR];
+
+        let pvd = descriptor(&mut image, 16, 1);
+        pvd[40..72].fill(b' ');
+        pvd[40..47].copy_from_slice(b"TESTISO");
+        pvd[128..130].copy_from_slice(&2048u16.to_le_bytes());
+        pvd[156..190].copy_from_slice(&dir_record(&[0], 20, SECTOR as u32, true));
+
+        let boot = descriptor(&mut image, 17, 0);
+        boot[7..7 + EL_TORITO_ID.len()].copy_from_slice(EL_TORITO_ID);
+        boot[71..75].copy_from_slice(&28u32.to_le_bytes());
+
+        // Without the escape sequence, this is not a Joliet descriptor.
+        let svd = descriptor(&mut image, 18, 2);
+        svd[40..72].copy_from_slice(&ucs2("Test ISO        "));
+        svd[156..190].copy_from_slice(&dir_record(&[0], 22, SECTOR as u32, true));
+        if with_joliet {
+            svd[88..91].copy_from_slice(b"%/E");
+        }
+        descriptor(&mut image, 19, 255);
+
+        write_dir(
+            &mut image,
+            20,
+            20,
+            &[
+                dir_record(b"README.TXT;1", 24, 11, false),
+                dir_record(b"SUBDIR", 21, SECTOR as u32, true),
+            ],
+        );
+        write_dir(
+            &mut image,
+            21,
+            20,
+            &[dir_record(b"DATA.BIN;1", 25, 5000, false)],
+        );
+        write_dir(
+            &mut image,
+            22,
+            22,
+            &[
+                dir_record(&ucs2("Readme File.txt"), 24, 11, false),
+                dir_record(&ucs2("Sub Directory"), 23, SECTOR as u32, true),
+            ],
+        );
+        write_dir(
+            &mut image,
+            23,
+            22,
+            &[dir_record(&ucs2("data.bin"), 25, 5000, false)],
+        );
+
+        sector(&mut image, 24)[..11].copy_from_slice(b"hello world");
+        for (i, b) in image[25 * SECTOR..25 * SECTOR + 5000]
+            .iter_mut()
+            .enumerate()
+        {
+            *b = i as u8;
+        }
+
+        // Boot catalog.
+        let catalog = sector(&mut image, 28);
+        catalog[0] = 1;
+        catalog[4..8].copy_from_slice(b"TEST");
+        catalog[30..32].copy_from_slice(&[0x55, 0xaa]);
+        let sum = catalog[..32].chunks_exact(2).fold(0u16, |sum, w| {
+            sum.wrapping_add(u16::from_le_bytes([w[0], w[1]]))
+        });
+        catalog[28..30].copy_from_slice(&0u16.wrapping_sub(sum).to_le_bytes());
+        catalog[32] = 0x88;
+        catalog[38..40].copy_from_slice(&4u16.to_le_bytes());
+        catalog[40..44].copy_from_slice(&29u32.to_le_bytes());
+        catalog[64] = 0x91;
+        catalog[65] = 0xef;
+        catalog[66..68].copy_from_slice(&1u16.to_le_bytes());
+        catalog[96] = 0x88;
+        catalog[102..104].copy_from_slice(&1u16.to_le_bytes());
+        catalog[104..108].copy_from_slice(&30u32.to_le_bytes());
+
+        // A FAT boot sector announcing 8 sectors of 512 bytes.
+        let efi_image = sector(&mut image, 30);
+        efi_image[11..13].copy_from_slice(&512u16.to_le_bytes());
+        efi_image[19..21].copy_from_slice(&8u16.to_le_bytes());
+        efi_image[510..512].copy_from_slice(&[0x55, 0xaa]);
+        image
+    }
+
+    #[test]
+    fn test_joliet() {
+        let mut iso = Iso9660::new(MemoryDisk::new(make_image(true), 512)).unwrap();
+        assert!(iso.is_joliet());
+        assert_eq!(iso.volume_id(), "Test ISO");
+
+        let names = iso
+            .read_dir("/")
+            .unwrap()
+            .into_iter()
+            .map(|e| e.name)
+            .collect::<Vec<_>>();
+        assert_eq!(names, ["Readme File.txt", "Sub Directory"]);
+        assert_eq!(iso.read("readme file.txt").unwrap(), b"hello world");
+
+        let data = iso.read("Sub Directory\\data.bin").unwrap();
+        assert_eq!(data.len(), 5000);
+        assert!(data.iter().enumerate().all(|(i, b)| *b == i as u8));
+
+        assert_eq!(iso.read("missing").unwrap_err(), IsoError::NotFound);
+        assert_eq!(iso.read("Sub Directory").unwrap_err(), IsoError::NotAFile);
+        assert_eq!(
+            iso.read_dir("Readme File.txt").unwrap_err(),
+            IsoError::NotADirectory
+        );
+    }
+
+    #[test]
+    fn test_primary() {
+        let mut iso = Iso9660::new(MemoryDisk::new(make_image(false), 512)).unwrap();
+        assert!(!iso.is_joliet());
+        assert_eq!(iso.volume_id(), "TESTISO");
+        let entry = iso.metadata("/SUBDIR/DATA.BIN").unwrap();
+        assert_eq!(entry.name(), "DATA.BIN");
+        assert_eq!(entry.size(), 5000);
+        assert_eq!(iso.read("readme.txt").unwrap(), b"hello world");
+    }
+
+    #[test]
+    fn test_boot_catalog() {
+        let mut iso = Iso9660::new(MemoryDisk::new(make_image(true), 512)).unwrap();
+        let catalog = iso.boot_catalog().unwrap().unwrap();
+        assert_eq!(catalog.id_string, "TEST");
+        let [bios, efi] = catalog.entries.as_slice() else {
+            panic!("expected two entries");
+        };
+        assert_eq!(bios.platform, BootPlatform::X86);
+        assert!(bios.bootable);
+        assert_eq!(bios.media_type, BootMediaType::NO_EMULATION);
+        assert_eq!(bios.sector_count, 4);
+        assert_eq!(bios.load_lba, 29);
+        assert_eq!(iso.read_boot_image(bios).unwrap().len(), 2048);
+
+        assert_eq!(efi.platform, BootPlatform::EFI);
+        assert_eq!(efi.load_lba, 30);
+        assert_eq!(iso.read_boot_image(efi).unwrap().len(), 8 * 512);
+    }
+
+    #[test]
+    fn test_invalid_boot_records() {
+        // A validation entry without the default entry.
+        let mut catalog = [0; 32];
+        catalog[0] = 1;
+        catalog[30..32].copy_from_slice(&[0x55, 0xaa]);
+        let checksum = catalog.chunks_exact(2).fold(0u16, |sum, w| {
+            sum.wrapping_add(u16::from_le_bytes([w[0], w[1]]))
+        });
+        catalog[28..30].copy_from_slice(&0u16.wrapping_sub(checksum).to_le_bytes());
+        assert_eq!(
+            BootCatalog::parse(&catalog).unwrap_err(),
+            IsoError::InvalidRecord
+        );
+
+        // A boot image whose FAT boot sector claims a huge size.
+        let mut image = make_image(true);
+        let boot_sector = &mut image[30 * SECTOR..][..512];
+        boot_sector[11..13].copy_from_slice(&4096u16.to_le_bytes());
+        boot_sector[19..21].fill(0);
+        boot_sector[32..36].copy_from_slice(&u32::MAX.to_le_bytes());
+        let mut iso = Iso9660::new(MemoryDisk::new(image, 512)).unwrap();
+        let catalog = iso.boot_catalog().unwrap().unwrap();
+        assert_eq!(
+            iso.read_boot_image(&catalog.entries[1]).unwrap_err(),
+            IsoError::InvalidRecord
+        );
+    }
+
+    #[test]
+    fn test_invalid() {
+        assert_eq!(
+            Iso9660::new(MemoryDisk::new(vec![0; 40 * SECTOR], 512)).unwrap_err(),
+            IsoError::InvalidVolumeDescriptor
+        );
+    }
+}
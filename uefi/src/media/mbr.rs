@@ -0,0 +1,456 @@
+// SPDX-License-Identifier: MIT OR Apache-2.0
+
+//! Parser for the legacy Master Boot Record (MBR) partition table.
+//!
+//! The MBR in the first block of a disk holds up to four primary partitions.
+//! One of them may be an extended partition, which contains a chain of
+//! Extended Boot Records (EBRs) that each describe one logical partition.
+//! See [`Mbr::read`].
+
+use super::BlockDevice;
+use crate::proto::media::partition::{MbrOsType, MbrPartitionRecord};
+use alloc::vec::Vec;
+use core::fmt::{self, Display, Formatter};
+
+/// Boot signature at the end of an MBR or EBR.
+pub const MBR_SIGNATURE: [u8; 2] = [0x55, 0xaa];
+
+/// Byte offset of the partition records in an MBR or EBR.
+const RECORDS_OFFSET: usize = 446;
+
+/// Size of an MBR in bytes.
+const MBR_SIZE: usize = 512;
+
+/// Upper limit for the number of logical partitions, which guards against
+/// EBR chains that are corrupted in a way that isn't detected otherwise.
+const MAX_LOGICAL_PARTITIONS: usize = 256;
+
+/// Errors that can happen while reading an MBR.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum MbrError {
+    /// Reading from the disk failed.
+    Io(crate::Error),
+    /// The block does not end with [`MBR_SIGNATURE`].
+    InvalidSignature,
+    /// The chain of EBRs is invalid, e.g. it contains a loop or points
+    /// outside of the extended partition.
+    InvalidExtendedPartition,
+}
+
+impl Display for MbrError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Io(err) => write!(f, "failed to read from disk: {err}"),
+            Self::InvalidSignature => write!(f, "invalid MBR signature"),
+            Self::InvalidExtendedPartition => write!(f, "invalid extended partition"),
+        }
+    }
+}
+
+impl core::error::Error for MbrError {
+    fn source(&self) -> Option<&(dyn core::error::Error + 'static)> {
+        match self {
+            Self::Io(err) => Some(err),
+            _ => None,
+        }
+    }
+}
+
+impl From<crate::Error> for MbrError {
+    fn from(err: crate::Error) -> Self {
+        Self::Io(err)
+    }
+}
+
+/// A cylinder-head-sector address, as used by legacy BIOSes.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Chs {
+    /// Cylinder, `0..=1023`.
+    pub cylinder: u16,
+    /// Head, `0..=255`.
+    pub head: u8,
+    /// Sector, `1..=63`. Zero means that the address is unused.
+    pub sector: u8,
+}
+
+impl Chs {
+    /// Decodes the three-byte on-disk representation.
+    #[must_use]
+    pub const fn from_bytes(bytes: [u8; 3]) -> Self {
+        Self {
+            cylinder: ((bytes[1] as u16 & 0xc0) << 2) | bytes[2] as u16,
+            head: bytes[0],
+            sector: bytes[1] & 0x3f,
+        }
+    }
+
+    /// Encodes the address in its three-byte on-disk representation.
+    #[must_use]
+    pub const fn to_bytes(self) -> [u8; 3] {
+        [
+            self.head,
+            (self.sector & 0x3f) | ((self.cylinder >> 2) as u8 & 0xc0),
+            self.cylinder as u8,
+        ]
+    }
+}
+
+/// A used partition record of an MBR or EBR.
+#[derive(Clone, Copy, Debug)]
+pub struct MbrPartition {
+    number: u32,
+    starting_lba: u64,
+    record: MbrPartitionRecord,
+}
+
+impl MbrPartition {
+    /// Number of the partition. Primary partitions are numbered `1..=4` by
+    /// their slot in the MBR, logical partitions are numbered from `5` on in
+    /// the order of the EBR chain.
+    #[must_use]
+    pub const fn number(&self) -> u32 {
+        self.number
+    }
+
+    /// Returns whether this is a logical partition inside an extended
+    /// partition.
+    #[must_use]
+    pub const fn is_logical(&self) -> bool {
+        self.number > 4
+    }
+
+    /// Returns the raw record. Its starting LBA is relative to the EBR for
+    /// logical partitions.
+    #[must_use]
+    pub const fn record(&self) -> &MbrPartitionRecord {
+        &self.record
+    }
+
+    /// Type of the partition.
+    #[must_use]
+    pub const fn os_type(&self) -> MbrOsType {
+        self.record.os_type
+    }
+
+    /// Returns whether the partition is marked as bootable.
+    #[must_use]
+    pub const fn is_bootable(&self) -> bool {
+        self.record.is_bootable()
+    }
+
+    /// Returns whether this is an extended partition that contains logical
+    /// partitions.
+    #[must_use]
+    pub const fn is_extended(&self) -> bool {
+        is_extended(self.record.os_type)
+    }
+
+    /// Start of the partition in CHS address format.
+    #[must_use]
+    pub const fn starting_chs(&self) -> Chs {
+        Chs::from_bytes(self.record.starting_chs)
+    }
+
+    /// End of the partition in CHS address format.
+    #[must_use]
+    pub const fn ending_chs(&self) -> Chs {
+        Chs::from_bytes(self.record.ending_chs)
+    }
+
+    /// First LBA of the partition, relative to the start of the disk.
+    #[must_use]
+    pub const fn starting_lba(&self) -> u64 {
+        self.starting_lba
+    }
+
+    /// Number of blocks in the partition.
+    #[must_use]
+    pub const fn size_in_lba(&self) -> u64 {
+        self.record.size_in_lba as u64
+    }
+}
+
+/// An MBR partition table read from a disk.
+///
+/// # Example
+///
+/// ```no_run
+/// use uefi::media::MemoryDisk;
+/// use uefi::media::mbr::Mbr;
+///
+/// fn print_partitions(disk: &mut MemoryDisk) {
+///     let mbr = Mbr::read(disk).unwrap();
+///     for partition in mbr.partitions() {
+///         log::info!(
+///             "{}: type {:#04x}, {} blocks at {}",
+///             partition.number(),
+///             partition.os_type().0,
+///             partition.size_in_lba(),
+///             partition.starting_lba()
+///         );
+///     }
+/// }
+/// ```
+#[derive(Clone, Debug)]
+pub struct Mbr {
+    disk_signature: u32,
+    primary: Vec<MbrPartition>,
+    logical: Vec<MbrPartition>,
+}
+
+impl Mbr {
+    /// Parses the MBR in `block`, the first block of a disk. Logical
+    /// partitions are not read, use [`Self::read`] for that.
+    ///
+    /// # Errors
+    /// * [`MbrError::InvalidSignature`] if the block is no MBR.
+    pub fn parse(block: &[u8]) -> Result<Self, MbrError> {
+        let primary = parse_records(block)?
+            .into_iter()
+            .zip(1..)
+            .filter(|(record, _)| record.os_type.0 != 0)
+            .map(|(record, number)| MbrPartition {
+                number,
+                starting_lba: u64::from(record.starting_lba),
+                record,
+            })
+            .collect();
+        Ok(Self {
+            disk_signature: u32::from_le_bytes(block[440..444].try_into().unwrap()),
+            primary,
+            logical: Vec::new(),
+        })
+    }
+
+    /// Reads the MBR of `disk`, including all logical partitions.
+    ///
+    /// # Errors
+    /// * [`MbrError::InvalidSignature`] if the first block or an EBR has no
+    ///   valid signature.
+    /// * [`MbrError::InvalidExtendedPartition`] if the EBR chain is invalid.
+    pub fn read(disk: &mut impl BlockDevice) -> Result<Self, MbrError> {
+        let mut block = [0; MBR_SIZE];
+        disk.read_at(0, &mut block)?;
+        let mut mbr = Self::parse(&block)?;
+        if let Some(extended) = mbr.primary.iter().find(|p| p.is_extended()) {
+            let extended = *extended;
+            mbr.logical = read_logical_partitions(disk, &extended, &mut block)?;
+        }
+        Ok(mbr)
+    }
+
+    /// Disk signature stored at byte offset 440.
+    #[must_use]
+    pub const fn disk_signature(&self) -> u32 {
+        self.disk_signature
+    }
+
+    /// Returns the used primary partitions, including extended partitions.
+    #[must_use]
+    pub fn primary_partitions(&self) -> &[MbrPartition] {
+        &self.primary
+    }
+
+    /// Returns the logical partitions.
+    #[must_use]
+    pub fn logical_partitions(&self) -> &[MbrPartition] {
+        &self.logical
+    }
+
+    /// Returns all partitions that hold data, i.e. all primary partitions
+    /// except for extended partitions, followed by the logical partitions.
+    pub fn partitions(&self) -> impl Iterator<Item = &MbrPartition> {
+        self.primary
+            .iter()
+            .filter(|p| !p.is_extended())
+            .chain(&self.logical)
+    }
+
+    /// Returns whether this is the protective MBR of a GPT disk.
+    #[must_use]
+    pub fn is_protective(&self) -> bool {
+        self.primary
+            .iter()
+            .any(|p| p.os_type() == MbrOsType::GPT_PROTECTIVE)
+    }
+}
+
+/// Returns whether `os_type` denotes an extended partition.
+const fn is_extended(os_type: MbrOsType) -> bool {
+    matches!(os_type.0, 0x05 | 0x0f | 0x85)
+}
+
+/// Checks the signature and returns the four partition records of an MBR or
+/// EBR.
+fn parse_records(block: &[u8]) -> Result<[MbrPartitionRecord; 4], MbrError> {
+    if block.len() < MBR_SIZE || block[510..512] != MBR_SIGNATURE {
+        return Err(MbrError::InvalidSignature);
+    }
+    Ok(core::array::from_fn(|i| {
+        let bytes = &block[RECORDS_OFFSET + 16 * i..RECORDS_OFFSET + 16 * (i + 1)];
+        MbrPartitionRecord {
+            boot_indicator: bytes[0],
+            starting_chs: [bytes[1], bytes[2], bytes[3]],
+            os_type: MbrOsType(bytes[4]),
+            ending_chs: [bytes[5], bytes[6], bytes[7]],
+            starting_lba: u32::from_le_bytes(bytes[8..12].try_into().unwrap()),
+            size_in_lba: u32::from_le_bytes(bytes[12..16].try_into().unwrap()),
+        }
+    }))
+}
+
+/// Follows the EBR chain of `extended`.
+fn read_logical_partitions(
+    disk: &mut impl BlockDevice,
+    extended: &MbrPartition,
+    block: &mut [u8; MBR_SIZE],
+) -> Result<Vec<MbrPartition>, MbrError> {
+    let block_size = u64::from(disk.block_size());
+    let extended_end = extended.starting_lba() + extended.size_in_lba();
+    let mut logical = Vec::new();
+    // Offset of the current EBR relative to the extended partition.
+    let mut offset = 0;
+    loop {
+        let ebr_lba = extended.starting_lba() + offset;
+        if ebr_lba >= extended_end || logical.len() >= MAX_LOGICAL_PARTITIONS {
+            return Err(MbrError::InvalidExtendedPartition);
+        }
+        disk.read_at(ebr_lba * block_size, block)?;
+        let [record, next, ..] = parse_records(block)?;
+
+        if record.os_type.0 != 0 {
+            logical.push(MbrPartition {
+                number: 5 + logical.len() as u32,
+                starting_lba: ebr_lba + u64::from(record.starting_lba),
+                record,
+            });
+        }
+
+        // Each EBR must come after the previous one, which rules out loops.
+        let next_offset = u64::from(next.starting_lba);
+        if !is_extended(next.os_type) || next_offset == 0 {
+            return Ok(logical);
+        }
+        if next_offset <= offset {
+            return Err(MbrError::InvalidExtendedPartition);
+        }
+        offset = next_offset;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::media::MemoryDisk;
+    use alloc::vec;
+
+    const BLOCK_SIZE: usize = 512;
+
+    fn write_record(block: &mut [u8], slot: usize, os_type: u8, start: u32, size: u32) {
+        let record = &mut block[RECORDS_OFFSET + 16 * slot..][..16];
+        record[1..4].copy_from_slice(&Chs::from_bytes([1, 2, 3]).to_bytes());
+        record[4] = os_type;
+        record[8..12].copy_from_slice(&start.to_le_bytes());
+        record[12..16].copy_from_slice(&size.to_le_bytes());
+        block[510..512].copy_from_slice(&MBR_SIGNATURE);
+    }
+
+    fn block(disk: &mut [u8], lba: usize) -> &mut [u8] {
+        &mut disk[lba * BLOCK_SIZE..(lba + 1) * BLOCK_SIZE]
+    }
+
+    /// A disk with a primary FAT partition and an extended partition at LBA
+    /// 100 containing two logical partitions.
+    fn make_disk() -> Vec<u8> {
+        let mut disk = vec![0; 200 * BLOCK_SIZE];
+        disk[440..444].copy_from_slice(&0x1234_5678u32.to_le_bytes());
+        write_record(block(&mut disk, 0), 0, 0x0c, 1, 99);
+        write_record(block(&mut disk, 0), 2, 0x0f, 100, 100);
+        disk[446] = 0x80;
+
+        write_record(block(&mut disk, 100), 0, 0x83, 1, 49);
+        write_record(block(&mut disk, 100), 1, 0x05, 50, 50);
+        write_record(block(&mut disk, 150), 0, 0x82, 2, 48);
+        disk
+    }
+
+    #[test]
+    fn test_chs() {
+        let chs = Chs::from_bytes([0xfe, 0xff, 0xff]);
+        assert_eq!(
+            chs,
+            Chs {
+                cylinder: 1023,
+                head: 254,
+                sector: 63
+            }
+        );
+        assert_eq!(chs.to_bytes(), [0xfe, 0xff, 0xff]);
+        assert_eq!(
+            Chs::from_bytes([1, 0x82, 3]),
+            Chs {
+                cylinder: 0x203,
+                head: 1,
+                sector: 2
+            }
+        );
+    }
+
+    #[test]
+    fn test_read() {
+        let mut disk = MemoryDisk::new(make_disk(), BLOCK_SIZE as u32);
+        let mbr = Mbr::read(&mut disk).unwrap();
+        assert_eq!(mbr.disk_signature(), 0x1234_5678);
+        assert!(!mbr.is_protective());
+
+        let primary = mbr.primary_partitions();
+        assert_eq!(primary.len(), 2);
+        assert_eq!(primary[0].number(), 1);
+        assert!(primary[0].is_bootable());
+        assert_eq!(primary[0].os_type(), MbrOsType(0x0c));
+        assert_eq!(primary[0].starting_chs(), Chs::from_bytes([1, 2, 3]));
+        assert_eq!(primary[1].number(), 3);
+        assert!(primary[1].is_extended());
+
+        let numbers_and_ranges = mbr
+            .partitions()
+            .map(|p| (p.number(), p.starting_lba(), p.size_in_lba()))
+            .collect::<Vec<_>>();
+        assert_eq!(numbers_and_ranges, [(1, 1, 99), (5, 101, 49), (6, 152, 48)]);
+        assert!(mbr.logical_partitions().iter().all(|p| p.is_logical()));
+    }
+
+    #[test]
+    fn test_parse_primary_only() {
+        let data = make_disk();
+        let mbr = Mbr::parse(&data[..BLOCK_SIZE]).unwrap();
+        assert_eq!(mbr.primary_partitions().len(), 2);
+        assert!(mbr.logical_partitions().is_empty());
+
+        assert_eq!(
+            Mbr::parse(&[0; BLOCK_SIZE]).unwrap_err(),
+            MbrError::InvalidSignature
+        );
+    }
+
+    #[test]
+    fn test_ebr_loop() {
+        let mut data = make_disk();
+        // An offset of zero ends the chain.
+        write_record(block(&mut data, 150), 1, 0x05, 0, 50);
+        assert_eq!(
+            Mbr::read(&mut MemoryDisk::new(data.clone(), BLOCK_SIZE as u32))
+                .unwrap()
+                .logical_partitions()
+                .len(),
+            2
+        );
+
+        // Pointing back to an earlier EBR is rejected.
+        write_record(block(&mut data, 150), 1, 0x05, 20, 50);
+        assert_eq!(
+            Mbr::read(&mut MemoryDisk::new(data, BLOCK_SIZE as u32)).unwrap_err(),
+            MbrError::InvalidExtendedPartition
+        );
+    }
+}
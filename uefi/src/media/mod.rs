@@ -0,0 +1,29 @@
+// SPDX-License-Identifier: MIT OR Apache-2.0
+
+//! Direct access to storage media, independent of the file system drivers
+//! of the firmware.
+//!
+//! The firmware only exposes the partitions and file systems it recognizes.
+//! The modules in here parse on-disk structures themselves, which allows
+//! inspecting any disk that provides a [`BlockIO`] protocol.
+//!
+//! All parsers operate on a [`BlockDevice`]. Use [`Disk`] to access a disk
+//! through the [`BlockIO`] and, optionally, the [`DiskIo`] protocol, or
+//! [`MemoryDisk`] for disk images held in memory.
+//!
+//! [`BlockIO`]: crate::proto::media::block::BlockIO
+//! [`DiskIo`]: crate::proto::media::disk::DiskIo
+
+#[cfg(feature = "fat")]
+pub mod fat;
+pub mod gpt;
+pub mod imaging;
+pub mod iso9660;
+pub mod mbr;
+#[cfg(feature = "embedded-storage")]
+pub mod storage;
+
+pub(crate) mod crc32;
+mod device;
+
+pub use device::*;
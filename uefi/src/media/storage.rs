@@ -0,0 +1,231 @@
+// SPDX-License-Identifier: MIT OR Apache-2.0
+
+//! Implementations of the [`embedded-storage`] traits for the block devices
+//! of this module, so that `no_std` storage and file system crates written
+//! against these traits work on UEFI disks.
+//!
+//! [`Disk`], [`Partition`] and [`MemoryDisk`] implement [`ReadStorage`],
+//! [`Storage`], [`ReadNorFlash`], [`NorFlash`] and [`MultiwriteNorFlash`].
+//! As the traits use 32-bit offsets, only the first 4 GiB of a device are
+//! accessible, and [`ReadStorage::capacity`] is limited accordingly.
+//!
+//! Disks have no erase operation. [`NorFlash::erase`] fills the range with
+//! `0xff` bytes to provide the semantics of NOR flash, with an erase size of
+//! 512 bytes.
+//!
+//! [`embedded-storage`]: https://docs.rs/embedded-storage
+
+use super::{BlockDevice, Disk, MemoryDisk, Partition};
+use core::fmt::{self, Display, Formatter};
+use embedded_storage::nor_flash::{
+    ErrorType, MultiwriteNorFlash, NorFlash, NorFlashError, NorFlashErrorKind, ReadNorFlash,
+};
+use embedded_storage::{ReadStorage, Storage};
+
+/// Erase size reported by the [`NorFlash`] implementations.
+const ERASE_SIZE: usize = 512;
+
+/// Largest number of bytes addressable with 32-bit offsets.
+const MAX_CAPACITY: u64 = 1 << 32;
+
+/// Errors of the [`embedded-storage`] implementations.
+///
+/// [`embedded-storage`]: https://docs.rs/embedded-storage
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum StorageError {
+    /// Accessing the device failed.
+    Io(crate::Error),
+    /// The access is not within the device.
+    OutOfBounds,
+    /// The erased range is not aligned to the erase size.
+    NotAligned,
+}
+
+impl Display for StorageError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Io(err) => write!(f, "failed to access device: {err}"),
+            Self::OutOfBounds => write!(f, "access out of bounds"),
+            Self::NotAligned => write!(f, "erase range not aligned"),
+        }
+    }
+}
+
+impl core::error::Error for StorageError {
+    fn source(&self) -> Option<&(dyn core::error::Error + 'static)> {
+        match self {
+            Self::Io(err) => Some(err),
+            _ => None,
+        }
+    }
+}
+
+impl From<crate::Error> for StorageError {
+    fn from(err: crate::Error) -> Self {
+        Self::Io(err)
+    }
+}
+
+impl NorFlashError for StorageError {
+    fn kind(&self) -> NorFlashErrorKind {
+        match self {
+            Self::Io(_) => NorFlashErrorKind::Other,
+            Self::OutOfBounds => NorFlashErrorKind::OutOfBounds,
+            Self::NotAligned => NorFlashErrorKind::NotAligned,
+        }
+    }
+}
+
+fn capacity<D: BlockDevice + ?Sized>(device: &D) -> usize {
+    usize::try_from(device.size().min(MAX_CAPACITY)).unwrap_or(usize::MAX)
+}
+
+fn check_bounds<D: BlockDevice + ?Sized>(
+    device: &D,
+    offset: u32,
+    len: usize,
+) -> Result<(), StorageError> {
+    if u64::from(offset) + len as u64 > device.size() {
+        return Err(StorageError::OutOfBounds);
+    }
+    Ok(())
+}
+
+fn read<D: BlockDevice + ?Sized>(
+    device: &mut D,
+    offset: u32,
+    bytes: &mut [u8],
+) -> Result<(), StorageError> {
+    check_bounds(device, offset, bytes.len())?;
+    device.read_at(u64::from(offset), bytes)?;
+    Ok(())
+}
+
+fn write<D: BlockDevice + ?Sized>(
+    device: &mut D,
+    offset: u32,
+    bytes: &[u8],
+) -> Result<(), StorageError> {
+    check_bounds(device, offset, bytes.len())?;
+    device.write_at(u64::from(offset), bytes)?;
+    Ok(())
+}
+
+fn erase<D: BlockDevice + ?Sized>(device: &mut D, from: u32, to: u32) -> Result<(), StorageError> {
+    if from > to {
+        return Err(StorageError::OutOfBounds);
+    }
+    check_bounds(device, from, (to - from) as usize)?;
+    if from as usize % ERASE_SIZE != 0 || to as usize % ERASE_SIZE != 0 {
+        return Err(StorageError::NotAligned);
+    }
+    let erased = [0xff; ERASE_SIZE];
+    for offset in (from..to).step_by(ERASE_SIZE) {
+        device.write_at(u64::from(offset), &erased)?;
+    }
+    Ok(())
+}
+
+macro_rules! impl_embedded_storage {
+    ([$($generic:ident: $bound:ident)?] $ty:ty) => {
+        impl<$($generic: $bound)?> ErrorType for $ty {
+            type Error = StorageError;
+        }
+
+        impl<$($generic: $bound)?> ReadNorFlash for $ty {
+            const READ_SIZE: usize = 1;
+
+            fn read(&mut self, offset: u32, bytes: &mut [u8]) -> Result<(), StorageError> {
+                read(self, offset, bytes)
+            }
+
+            fn capacity(&self) -> usize {
+                capacity(self)
+            }
+        }
+
+        impl<$($generic: $bound)?> NorFlash for $ty {
+            const WRITE_SIZE: usize = 1;
+            const ERASE_SIZE: usize = ERASE_SIZE;
+
+            fn erase(&mut self, from: u32, to: u32) -> Result<(), StorageError> {
+                erase(self, from, to)
+            }
+
+            fn write(&mut self, offset: u32, bytes: &[u8]) -> Result<(), StorageError> {
+                write(self, offset, bytes)
+            }
+        }
+
+        impl<$($generic: $bound)?> MultiwriteNorFlash for $ty {}
+
+        impl<$($generic: $bound)?> ReadStorage for $ty {
+            type Error = StorageError;
+
+            fn read(&mut self, offset: u32, bytes: &mut [u8]) -> Result<(), StorageError> {
+                read(self, offset, bytes)
+            }
+
+            fn capacity(&self) -> usize {
+                capacity(self)
+            }
+        }
+
+        impl<$($generic: $bound)?> Storage for $ty {
+            fn write(&mut self, offset: u32, bytes: &[u8]) -> Result<(), StorageError> {
+                write(self, offset, bytes)
+            }
+        }
+    };
+}
+
+impl_embedded_storage!([] Disk<'_>);
+impl_embedded_storage!([D: BlockDevice] Partition<D>);
+impl_embedded_storage!([] MemoryDisk);
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloc::vec;
+
+    #[test]
+    fn test_storage() {
+        let mut disk = MemoryDisk::new(vec![0; 4096], 512);
+        assert_eq!(ReadStorage::capacity(&disk), 4096);
+
+        Storage::write(&mut disk, 510, b"hello").unwrap();
+        let mut buffer = [0; 5];
+        ReadStorage::read(&mut disk, 510, &mut buffer).unwrap();
+        assert_eq!(&buffer, b"hello");
+
+        assert_eq!(
+            ReadStorage::read(&mut disk, 4094, &mut buffer),
+            Err(StorageError::OutOfBounds)
+        );
+    }
+
+    #[test]
+    fn test_nor_flash() {
+        let mut partition = Partition::new(MemoryDisk::new(vec![0; 4096], 512), 2, 4);
+        assert_eq!(ReadNorFlash::capacity(&partition), 2048);
+
+        NorFlash::erase(&mut partition, 512, 1024).unwrap();
+        NorFlash::write(&mut partition, 600, &[1, 2, 3]).unwrap();
+        let mut buffer = [0; 4];
+        ReadNorFlash::read(&mut partition, 599, &mut buffer).unwrap();
+        assert_eq!(buffer, [0xff, 1, 2, 3]);
+        assert_eq!(partition.into_inner().data()[1535], 0);
+
+        let mut partition = Partition::new(MemoryDisk::new(vec![0; 4096], 512), 2, 4);
+        assert_eq!(
+            NorFlash::erase(&mut partition, 100, 512)
+                .unwrap_err()
+                .kind(),
+            NorFlashErrorKind::NotAligned
+        );
+        assert_eq!(
+            NorFlash::erase(&mut partition, 0, 2560).unwrap_err().kind(),
+            NorFlashErrorKind::OutOfBounds
+        );
+    }
+}
@@ -0,0 +1,173 @@
+// SPDX-License-Identifier: MIT OR Apache-2.0
+
+//! Bump allocation in pages.
+
+use super::pages::Pages;
+use crate::boot::{AllocateType, MemoryType};
+use crate::{Result, Status};
+use core::alloc::Layout;
+use core::cell::Cell;
+use core::fmt::{self, Debug, Formatter};
+use core::ptr::{self, NonNull};
+use core::slice;
+
+/// A bump allocator in a single allocation of pages.
+///
+/// Allocations are very fast: they only move an offset in the pages forward.
+/// Memory is never freed individually; instead, [`reset`] frees all
+/// allocations at once in constant time. The pages themselves are freed on
+/// drop. This is useful for short-lived work like parsing, where the
+/// overhead of allocating and freeing from the pool dominates.
+///
+/// Values placed in the arena are never dropped.
+///
+/// With the `unstable` feature, `&Arena` implements
+/// [`core::alloc::Allocator`], so collections can be created in the arena,
+/// e.g. with `Vec::new_in(&arena)`. Growing such collections wastes the
+/// space of their previous allocation.
+///
+/// [`reset`]: Self::reset
+pub struct Arena {
+    pages: Pages,
+    /// Offset of the free space in the pages.
+    used: Cell<usize>,
+}
+
+// Allocations never overlap, so they can be mutable although the arena is
+// only borrowed immutably.
+#[allow(clippy::mut_from_ref)]
+impl Arena {
+    /// Creates an arena of at least `size` bytes in pages of type
+    /// [`MemoryType::LOADER_DATA`].
+    ///
+    /// # Errors
+    ///
+    /// See [`new_in`](Self::new_in).
+    pub fn new(size: usize) -> Result<Self> {
+        Self::new_in(size, MemoryType::LOADER_DATA)
+    }
+
+    /// Creates an arena of at least `size` bytes in pages of `memory_type`.
+    ///
+    /// # Errors
+    ///
+    /// * Errors of [`boot::allocate_pages`].
+    ///
+    /// [`boot::allocate_pages`]: crate::boot::allocate_pages
+    pub fn new_in(size: usize, memory_type: MemoryType) -> Result<Self> {
+        let pages = Pages::allocate(size, 1, AllocateType::AnyPages, memory_type)?;
+        Ok(Self {
+            pages,
+            used: Cell::new(0),
+        })
+    }
+
+    /// Returns the size of the arena in bytes.
+    #[must_use]
+    pub const fn capacity(&self) -> usize {
+        self.pages.size()
+    }
+
+    /// Returns the number of bytes in use, including padding for alignment.
+    #[must_use]
+    pub fn used(&self) -> usize {
+        self.used.get()
+    }
+
+    /// Returns the number of free bytes.
+    #[must_use]
+    pub fn remaining(&self) -> usize {
+        self.capacity() - self.used()
+    }
+
+    /// Allocates memory for `layout`, or returns `None` if the arena is full.
+    /// The memory is uninitialized.
+    #[must_use]
+    pub fn alloc_layout(&self, layout: Layout) -> Option<NonNull<u8>> {
+        let base = self.pages.ptr;
+        let start = base
+            .as_ptr()
+            .wrapping_add(self.used())
+            .align_offset(layout.align());
+        let start = self.used().checked_add(start)?;
+        let end = start.checked_add(layout.size())?;
+        if end > self.capacity() {
+            return None;
+        }
+        self.used.set(end);
+        Some(unsafe { base.add(start) })
+    }
+
+    /// Moves `value` into the arena, or returns it if the arena is full.
+    pub fn alloc<T>(&self, value: T) -> core::result::Result<&mut T, T> {
+        match self.alloc_layout(Layout::new::<T>()) {
+            Some(ptr) => {
+                let ptr = ptr.cast::<T>().as_ptr();
+                unsafe {
+                    ptr.write(value);
+                    Ok(&mut *ptr)
+                }
+            }
+            None => Err(value),
+        }
+    }
+
+    /// Copies `values` into the arena.
+    ///
+    /// # Errors
+    ///
+    /// * [`Status::OUT_OF_RESOURCES`]: the arena is full.
+    pub fn alloc_slice_copy<T: Copy>(&self, values: &[T]) -> Result<&mut [T]> {
+        let layout = Layout::for_value(values);
+        let ptr = self
+            .alloc_layout(layout)
+            .ok_or(Status::OUT_OF_RESOURCES)?
+            .cast::<T>()
+            .as_ptr();
+        unsafe {
+            ptr::copy_nonoverlapping(values.as_ptr(), ptr, values.len());
+            Ok(slice::from_raw_parts_mut(ptr, values.len()))
+        }
+    }
+
+    /// Copies `s` into the arena.
+    ///
+    /// # Errors
+    ///
+    /// * [`Status::OUT_OF_RESOURCES`]: the arena is full.
+    pub fn alloc_str(&self, s: &str) -> Result<&mut str> {
+        let bytes = self.alloc_slice_copy(s.as_bytes())?;
+        Ok(unsafe { core::str::from_utf8_unchecked_mut(bytes) })
+    }
+
+    /// Frees all allocations, without dropping the values in them.
+    pub fn reset(&mut self) {
+        *self.used.get_mut() = 0;
+    }
+}
+
+impl Debug for Arena {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Arena")
+            .field("address", &self.pages.ptr)
+            .field("capacity", &self.capacity())
+            .field("used", &self.used())
+            .finish()
+    }
+}
+
+#[cfg(all(feature = "unstable", feature = "alloc"))]
+unsafe impl core::alloc::Allocator for &Arena {
+    fn allocate(
+        &self,
+        layout: Layout,
+    ) -> core::result::Result<NonNull<[u8]>, core::alloc::AllocError> {
+        self.alloc_layout(layout)
+            .map(|ptr| NonNull::slice_from_raw_parts(ptr, layout.size()))
+            .ok_or(core::alloc::AllocError)
+    }
+
+    unsafe fn deallocate(&self, _ptr: NonNull<u8>, _layout: Layout) {
+        // Memory is only freed by `Arena::reset`.
+    }
+}
@@ -94,9 +94,9 @@ impl Index<usize> for MemoryMapRef<'_> {
 /// Implementation of [`MemoryMapMut`] for the given buffer.
 #[derive(Debug)]
 pub struct MemoryMapRefMut<'a> {
-    buf: &'a mut [u8],
-    meta: MemoryMapMeta,
-    len: usize,
+    pub(crate) buf: &'a mut [u8],
+    pub(crate) meta: MemoryMapMeta,
+    pub(crate) len: usize,
 }
 
 impl<'a> MemoryMapRefMut<'a> {
@@ -270,7 +270,7 @@ impl IndexMut<usize> for MemoryMapRefMut<'_> {
 /// [`boot::get_memory_map`]: crate::boot::get_memory_map
 #[derive(Debug)]
 #[allow(clippy::len_without_is_empty)] // this type is never empty
-pub(crate) struct MemoryMapBackingMemory(NonNull<[u8]>);
+pub(crate) struct MemoryMapBackingMemory(NonNull<[u8]>, MemoryType);
 
 impl MemoryMapBackingMemory {
     /// Constructs a new [`MemoryMapBackingMemory`].
@@ -290,16 +290,16 @@ impl MemoryMapBackingMemory {
         // If this panics, the UEFI implementation is broken.
         assert_eq!(memory_map_meta.map_size % memory_map_meta.desc_size, 0);
 
-        unsafe { Ok(Self::from_raw(ptr, len)) }
+        unsafe { Ok(Self::from_raw(ptr, len, memory_type)) }
     }
 
-    unsafe fn from_raw(ptr: *mut u8, len: usize) -> Self {
+    unsafe fn from_raw(ptr: *mut u8, len: usize, memory_type: MemoryType) -> Self {
         assert_eq!(ptr.align_offset(align_of::<MemoryDescriptor>()), 0);
 
         let ptr = NonNull::new(ptr).expect("UEFI should never return a null ptr. An error should have been reflected via an Err earlier.");
         let slice = NonNull::slice_from_raw_parts(ptr, len);
 
-        Self(slice)
+        Self(slice, memory_type)
     }
 
     /// INTERNAL, for unit tests.
@@ -309,7 +309,7 @@ impl MemoryMapBackingMemory {
     #[cfg(test)]
     pub(crate) fn from_slice(buffer: &mut [u8]) -> Self {
         let len = buffer.len();
-        unsafe { Self::from_raw(buffer.as_mut_ptr(), len) }
+        unsafe { Self::from_raw(buffer.as_mut_ptr(), len, MemoryType::LOADER_DATA) }
     }
 
     /// Returns a "safe" best-effort size hint for the memory map size with
@@ -338,6 +338,12 @@ impl MemoryMapBackingMemory {
     pub const fn as_mut_slice(&mut self) -> &mut [u8] {
         unsafe { self.0.as_mut() }
     }
+
+    /// Returns the memory type of the allocation.
+    #[must_use]
+    pub const fn memory_type(&self) -> MemoryType {
+        self.1
+    }
 }
 
 // Don't drop when we use this in unit tests.
@@ -374,6 +380,59 @@ impl MemoryMapOwned {
         let len = meta.entry_count();
         Self { buf, meta, len }
     }
+
+    /// Replaces the memory map with the current one of the firmware, reusing
+    /// the buffer. The buffer is only reallocated if the map has grown beyond
+    /// its size.
+    ///
+    /// This is useful to get the latest [`MemoryMapKey`] right before
+    /// exiting boot services.
+    ///
+    /// If an error is returned, the memory map is empty.
+    ///
+    /// # Errors
+    ///
+    /// * [`Status::OUT_OF_RESOURCES`]: reallocating the buffer failed.
+    ///
+    /// # Panics
+    ///
+    /// Panics if boot services have been exited.
+    ///
+    /// [`Status::OUT_OF_RESOURCES`]: crate::Status::OUT_OF_RESOURCES
+    pub fn refresh(&mut self) -> crate::Result {
+        self.meta.map_size = 0;
+        self.len = 0;
+        let meta = boot::get_memory_map_reusing(&mut self.buf)?;
+        self.meta = meta;
+        self.len = meta.entry_count();
+        Ok(())
+    }
+}
+
+/// Caller-owned buffer on the UEFI heap to retrieve the memory map into with
+/// [`boot::memory_map_into`].
+///
+/// The buffer is allocated on the first use and only reallocated if the
+/// memory map has grown beyond its size, so retrieving the map repeatedly,
+/// e.g. right before exiting boot services, doesn't allocate each time.
+///
+/// [`boot::memory_map_into`]: crate::boot::memory_map_into
+#[derive(Debug)]
+pub struct MemoryMapBuffer {
+    pub(crate) buf: Option<MemoryMapBackingMemory>,
+    pub(crate) memory_type: MemoryType,
+}
+
+impl MemoryMapBuffer {
+    /// Creates an empty buffer, which is allocated with `memory_type` when it
+    /// is first used. Usually, this is [`MemoryType::LOADER_DATA`].
+    #[must_use]
+    pub const fn new(memory_type: MemoryType) -> Self {
+        Self {
+            buf: None,
+            memory_type,
+        }
+    }
 }
 
 impl MemoryMap for MemoryMapOwned {
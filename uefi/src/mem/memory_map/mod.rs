@@ -12,7 +12,9 @@
 //!
 //! You can use [`boot::exit_boot_services`] or
 //! [`boot::memory_map`], which returns an properly initialized
-//! [`MemoryMapOwned`].
+//! [`MemoryMapOwned`]. To query the memory map repeatedly without allocating
+//! each time, use [`MemoryMapOwned::refresh`] or [`boot::memory_map_into`]
+//! with a [`MemoryMapBuffer`].
 //!
 //! # Usecase: Parse Memory Slice as UEFI Memory Map
 //!
@@ -32,6 +34,7 @@
 //!
 //! [`boot::exit_boot_services`]: crate::boot::exit_boot_services
 //! [`boot::memory_map`]: crate::boot::memory_map
+//! [`boot::memory_map_into`]: crate::boot::memory_map_into
 
 mod api;
 mod impl_;
@@ -8,6 +8,18 @@ use core::ptr::NonNull;
 
 pub mod memory_map;
 
+mod arena;
+mod pages;
+pub use arena::Arena;
+pub use pages::{PageBox, PageVec};
+
+#[cfg(feature = "alloc_tracking")]
+mod tracking;
+#[cfg(feature = "alloc_tracking")]
+pub use tracking::{AllocStats, AllocTagStats, alloc_stats, alloc_tags, report, with_alloc_tag};
+#[cfg(feature = "alloc_tracking")]
+pub(crate) use tracking::{record_alloc, record_dealloc};
+
 #[cfg(feature = "alloc")]
 pub(crate) mod util;
 
@@ -0,0 +1,374 @@
+// SPDX-License-Identifier: MIT OR Apache-2.0
+
+//! Owned allocations of whole pages.
+
+use crate::boot::{self, AllocateType, MemoryType, PAGE_SIZE};
+use crate::data_types::PhysicalAddress;
+use crate::{Result, Status};
+use core::fmt::{self, Debug, Formatter};
+use core::marker::PhantomData;
+use core::mem::{self, ManuallyDrop};
+use core::ops::{Deref, DerefMut};
+use core::ptr::{self, NonNull};
+use core::slice;
+
+/// Pages allocated with [`boot::allocate_pages`], which are freed on drop.
+#[derive(Debug)]
+pub(super) struct Pages {
+    pub(super) ptr: NonNull<u8>,
+    count: usize,
+}
+
+impl Pages {
+    /// Allocates enough pages for `size` bytes, but at least one page.
+    /// Returns [`Status::INVALID_PARAMETER`] if `align` is larger than a
+    /// page.
+    pub(super) fn allocate(
+        size: usize,
+        align: usize,
+        allocation_type: AllocateType,
+        memory_type: MemoryType,
+    ) -> Result<Self> {
+        if align > PAGE_SIZE {
+            return Err(Status::INVALID_PARAMETER.into());
+        }
+        let count = size.div_ceil(PAGE_SIZE).max(1);
+        let ptr = boot::allocate_pages(allocation_type, memory_type, count)?;
+        Ok(Self { ptr, count })
+    }
+
+    /// Size of the allocation in bytes.
+    pub(super) const fn size(&self) -> usize {
+        self.count * PAGE_SIZE
+    }
+}
+
+impl Drop for Pages {
+    fn drop(&mut self) {
+        // Ignore errors returned by `free_pages` since we can't propagate
+        // them from `drop`.
+        let _ = unsafe { boot::free_pages(self.ptr, self.count) };
+    }
+}
+
+/// A value in its own allocation of whole pages, like a `Box` whose memory
+/// comes from [`boot::allocate_pages`] instead of the pool allocator.
+///
+/// Page allocations have a chosen [`MemoryType`] and [`AllocateType`], e.g.
+/// to place a DMA buffer below 4 GiB or to stage a kernel at a fixed address.
+/// The pages are freed on drop, unless the box is [leaked].
+///
+/// [leaked]: Self::leak
+pub struct PageBox<T> {
+    pages: Pages,
+    _marker: PhantomData<T>,
+}
+
+impl<T> PageBox<T> {
+    /// Moves `value` into new pages of type [`MemoryType::LOADER_DATA`] at
+    /// any address.
+    ///
+    /// # Errors
+    ///
+    /// See [`new_in`](Self::new_in).
+    pub fn new(value: T) -> Result<Self> {
+        Self::new_in(value, AllocateType::AnyPages, MemoryType::LOADER_DATA)
+    }
+
+    /// Moves `value` into new pages of `memory_type`, allocated with the
+    /// strategy `allocation_type`.
+    ///
+    /// # Errors
+    ///
+    /// * [`Status::INVALID_PARAMETER`]: `T` must be aligned to more than a
+    ///   page.
+    /// * Errors of [`boot::allocate_pages`].
+    pub fn new_in(
+        value: T,
+        allocation_type: AllocateType,
+        memory_type: MemoryType,
+    ) -> Result<Self> {
+        let pages = Pages::allocate(
+            mem::size_of::<T>(),
+            mem::align_of::<T>(),
+            allocation_type,
+            memory_type,
+        )?;
+        unsafe { pages.ptr.cast::<T>().write(value) };
+        Ok(Self {
+            pages,
+            _marker: PhantomData,
+        })
+    }
+
+    /// Returns the physical address of the value. Memory is identity-mapped
+    /// while boot services are active, so this is also its virtual address.
+    #[must_use]
+    pub fn physical_address(&self) -> PhysicalAddress {
+        self.pages.ptr.as_ptr() as PhysicalAddress
+    }
+
+    /// Returns the number of allocated pages.
+    #[must_use]
+    pub const fn page_count(&self) -> usize {
+        self.pages.count
+    }
+
+    /// Consumes the box without freeing the pages, and returns a reference
+    /// to the value. This is useful for memory that is handed over to the
+    /// operating system.
+    #[must_use]
+    pub fn leak(this: Self) -> &'static mut T {
+        let this = ManuallyDrop::new(this);
+        unsafe { &mut *this.pages.ptr.cast::<T>().as_ptr() }
+    }
+}
+
+impl<T> Deref for PageBox<T> {
+    type Target = T;
+
+    fn deref(&self) -> &T {
+        unsafe { self.pages.ptr.cast::<T>().as_ref() }
+    }
+}
+
+impl<T> DerefMut for PageBox<T> {
+    fn deref_mut(&mut self) -> &mut T {
+        unsafe { self.pages.ptr.cast::<T>().as_mut() }
+    }
+}
+
+impl<T> Drop for PageBox<T> {
+    fn drop(&mut self) {
+        // The pages are freed afterwards by `Pages::drop`.
+        unsafe { ptr::drop_in_place(self.pages.ptr.cast::<T>().as_ptr()) };
+    }
+}
+
+impl<T: Debug> Debug for PageBox<T> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.debug_struct("PageBox")
+            .field("address", &self.pages.ptr)
+            .field("page_count", &self.pages.count)
+            .field("value", &**self)
+            .finish()
+    }
+}
+
+/// A vector of elements in an allocation of whole pages.
+///
+/// Unlike `Vec`, a `PageVec` never reallocates, so its elements keep their
+/// [physical address]. It can hold as many elements as fit into the pages
+/// allocated when it was created, see [`capacity`]. The pages are freed on
+/// drop, unless the vector is [leaked].
+///
+/// This is the right tool for DMA buffers and for staging data like a kernel
+/// or initrd, which should not come from the pool allocator.
+///
+/// [`capacity`]: Self::capacity
+/// [physical address]: Self::physical_address
+/// [leaked]: Self::leak
+pub struct PageVec<T> {
+    pages: Pages,
+    len: usize,
+    _marker: PhantomData<T>,
+}
+
+impl<T> PageVec<T> {
+    /// Creates an empty vector with space for at least `capacity` elements,
+    /// in pages of type [`MemoryType::LOADER_DATA`] at any address.
+    ///
+    /// # Errors
+    ///
+    /// See [`with_capacity_in`](Self::with_capacity_in).
+    pub fn with_capacity(capacity: usize) -> Result<Self> {
+        Self::with_capacity_in(capacity, AllocateType::AnyPages, MemoryType::LOADER_DATA)
+    }
+
+    /// Creates an empty vector with space for at least `capacity` elements,
+    /// in pages of `memory_type` allocated with the strategy
+    /// `allocation_type`.
+    ///
+    /// # Errors
+    ///
+    /// * [`Status::INVALID_PARAMETER`]: `T` must be aligned to more than a
+    ///   page, or the size of `capacity` elements overflows.
+    /// * Errors of [`boot::allocate_pages`].
+    pub fn with_capacity_in(
+        capacity: usize,
+        allocation_type: AllocateType,
+        memory_type: MemoryType,
+    ) -> Result<Self> {
+        let size = mem::size_of::<T>()
+            .checked_mul(capacity)
+            .ok_or(Status::INVALID_PARAMETER)?;
+        let pages = Pages::allocate(size, mem::align_of::<T>(), allocation_type, memory_type)?;
+        Ok(Self {
+            pages,
+            len: 0,
+            _marker: PhantomData,
+        })
+    }
+
+    /// Returns the number of elements the vector can hold.
+    #[must_use]
+    pub const fn capacity(&self) -> usize {
+        match mem::size_of::<T>() {
+            0 => usize::MAX,
+            size => self.pages.size() / size,
+        }
+    }
+
+    /// Returns the number of elements in the vector.
+    #[must_use]
+    pub const fn len(&self) -> usize {
+        self.len
+    }
+
+    /// Returns whether the vector is empty.
+    #[must_use]
+    pub const fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Returns the physical address of the first element. Memory is
+    /// identity-mapped while boot services are active, so this is also its
+    /// virtual address.
+    #[must_use]
+    pub fn physical_address(&self) -> PhysicalAddress {
+        self.pages.ptr.as_ptr() as PhysicalAddress
+    }
+
+    /// Returns the number of allocated pages.
+    #[must_use]
+    pub const fn page_count(&self) -> usize {
+        self.pages.count
+    }
+
+    /// Appends `value`, or returns it if the vector is full.
+    pub const fn push(&mut self, value: T) -> core::result::Result<(), T> {
+        if self.len == self.capacity() {
+            return Err(value);
+        }
+        unsafe { self.as_mut_ptr().add(self.len).write(value) };
+        self.len += 1;
+        Ok(())
+    }
+
+    /// Removes the last element and returns it.
+    pub const fn pop(&mut self) -> Option<T> {
+        if self.len == 0 {
+            return None;
+        }
+        self.len -= 1;
+        Some(unsafe { self.as_mut_ptr().add(self.len).read() })
+    }
+
+    /// Shortens the vector to `len` elements, dropping the others. This has
+    /// no effect if the vector is not longer than `len`.
+    pub fn truncate(&mut self, len: usize) {
+        if len < self.len {
+            let tail = ptr::slice_from_raw_parts_mut(
+                unsafe { self.as_mut_ptr().add(len) },
+                self.len - len,
+            );
+            self.len = len;
+            unsafe { ptr::drop_in_place(tail) };
+        }
+    }
+
+    /// Removes all elements.
+    pub fn clear(&mut self) {
+        self.truncate(0);
+    }
+
+    /// Returns a pointer to the first element.
+    #[must_use]
+    pub const fn as_ptr(&self) -> *const T {
+        self.pages.ptr.as_ptr().cast()
+    }
+
+    /// Returns a mutable pointer to the first element.
+    #[must_use]
+    pub const fn as_mut_ptr(&mut self) -> *mut T {
+        self.pages.ptr.as_ptr().cast()
+    }
+
+    /// Consumes the vector without freeing the pages, and returns its
+    /// elements. This is useful for memory that is handed over to the
+    /// operating system.
+    #[must_use]
+    pub fn leak(this: Self) -> &'static mut [T] {
+        let mut this = ManuallyDrop::new(this);
+        unsafe { slice::from_raw_parts_mut(this.as_mut_ptr(), this.len) }
+    }
+}
+
+impl<T: Clone> PageVec<T> {
+    /// Appends clones of the elements of `values`.
+    ///
+    /// # Errors
+    ///
+    /// * [`Status::BUFFER_TOO_SMALL`]: the elements don't fit into the
+    ///   vector. Nothing is appended in that case.
+    pub fn extend_from_slice(&mut self, values: &[T]) -> Result {
+        if values.len() > self.capacity() - self.len {
+            return Err(Status::BUFFER_TOO_SMALL.into());
+        }
+        for value in values {
+            unsafe { self.as_mut_ptr().add(self.len).write(value.clone()) };
+            self.len += 1;
+        }
+        Ok(())
+    }
+
+    /// Resizes the vector to `len` elements, appending clones of `value` or
+    /// dropping elements as needed.
+    ///
+    /// # Errors
+    ///
+    /// * [`Status::BUFFER_TOO_SMALL`]: `len` is larger than the capacity.
+    ///   The vector is unchanged in that case.
+    pub fn resize(&mut self, len: usize, value: T) -> Result {
+        if len > self.capacity() {
+            return Err(Status::BUFFER_TOO_SMALL.into());
+        }
+        self.truncate(len);
+        while self.len < len {
+            unsafe { self.as_mut_ptr().add(self.len).write(value.clone()) };
+            self.len += 1;
+        }
+        Ok(())
+    }
+}
+
+impl<T> Deref for PageVec<T> {
+    type Target = [T];
+
+    fn deref(&self) -> &[T] {
+        unsafe { slice::from_raw_parts(self.as_ptr(), self.len) }
+    }
+}
+
+impl<T> DerefMut for PageVec<T> {
+    fn deref_mut(&mut self) -> &mut [T] {
+        unsafe { slice::from_raw_parts_mut(self.as_mut_ptr(), self.len) }
+    }
+}
+
+impl<T> Drop for PageVec<T> {
+    fn drop(&mut self) {
+        // The pages are freed afterwards by `Pages::drop`.
+        self.clear();
+    }
+}
+
+impl<T: Debug> Debug for PageVec<T> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.debug_struct("PageVec")
+            .field("address", &self.pages.ptr)
+            .field("page_count", &self.pages.count)
+            .field("elements", &&**self)
+            .finish()
+    }
+}
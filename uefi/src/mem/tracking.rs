@@ -0,0 +1,200 @@
+// SPDX-License-Identifier: MIT OR Apache-2.0
+
+//! Statistics of the allocations of [`allocator`] (feature `alloc_tracking`).
+//!
+//! [`allocator`]: crate::allocator
+
+use core::cell::UnsafeCell;
+use core::sync::atomic::{AtomicUsize, Ordering};
+
+/// Maximum number of tags of [`with_alloc_tag`] that are tracked.
+const MAX_TAGS: usize = 16;
+
+/// Statistics of the allocations of [`Allocator`] and [`TypedAllocator`],
+/// returned by [`alloc_stats`].
+///
+/// [`Allocator`]: crate::allocator::Allocator
+/// [`TypedAllocator`]: crate::allocator::TypedAllocator
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub struct AllocStats {
+    /// Number of bytes currently allocated.
+    pub current_bytes: usize,
+    /// Highest number of bytes allocated at the same time.
+    pub peak_bytes: usize,
+    /// Number of allocations that haven't been freed.
+    pub live_allocations: usize,
+    /// Number of allocations made so far.
+    pub total_allocations: usize,
+}
+
+/// Allocations made while a tag was active, see [`with_alloc_tag`].
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct AllocTagStats {
+    /// Tag passed to [`with_alloc_tag`].
+    pub tag: &'static str,
+    /// Number of allocations made with the tag.
+    pub allocations: usize,
+    /// Number of bytes allocated with the tag, including bytes that have
+    /// been freed since.
+    pub bytes: usize,
+}
+
+struct Tracker {
+    current_bytes: AtomicUsize,
+    peak_bytes: AtomicUsize,
+    live_allocations: AtomicUsize,
+    total_allocations: AtomicUsize,
+    tag: UnsafeCell<Option<&'static str>>,
+    tags: UnsafeCell<[Option<AllocTagStats>; MAX_TAGS]>,
+}
+
+// The tags are not thread-safe, but the UEFI boot environment only uses one
+// processor.
+unsafe impl Sync for Tracker {}
+
+static TRACKER: Tracker = Tracker {
+    current_bytes: AtomicUsize::new(0),
+    peak_bytes: AtomicUsize::new(0),
+    live_allocations: AtomicUsize::new(0),
+    total_allocations: AtomicUsize::new(0),
+    tag: UnsafeCell::new(None),
+    tags: UnsafeCell::new([None; MAX_TAGS]),
+};
+
+/// Records an allocation of `size` bytes.
+pub(crate) fn record_alloc(size: usize) {
+    let current = TRACKER.current_bytes.fetch_add(size, Ordering::Relaxed) + size;
+    TRACKER.peak_bytes.fetch_max(current, Ordering::Relaxed);
+    TRACKER.live_allocations.fetch_add(1, Ordering::Relaxed);
+    TRACKER.total_allocations.fetch_add(1, Ordering::Relaxed);
+
+    let Some(tag) = (unsafe { *TRACKER.tag.get() }) else {
+        return;
+    };
+    let tags = unsafe { &mut *TRACKER.tags.get() };
+    let slot = tags
+        .iter_mut()
+        .find(|slot| slot.is_none_or(|stats| stats.tag == tag));
+    if let Some(slot) = slot {
+        let stats = slot.get_or_insert(AllocTagStats {
+            tag,
+            allocations: 0,
+            bytes: 0,
+        });
+        stats.allocations += 1;
+        stats.bytes += size;
+    }
+}
+
+/// Records that an allocation of `size` bytes has been freed.
+pub(crate) fn record_dealloc(size: usize) {
+    TRACKER.current_bytes.fetch_sub(size, Ordering::Relaxed);
+    TRACKER.live_allocations.fetch_sub(1, Ordering::Relaxed);
+}
+
+/// Returns the statistics of the allocations of [`Allocator`] and
+/// [`TypedAllocator`].
+///
+/// Comparing the statistics before and after some code runs shows whether
+/// the code leaks memory.
+///
+/// [`Allocator`]: crate::allocator::Allocator
+/// [`TypedAllocator`]: crate::allocator::TypedAllocator
+#[must_use]
+pub fn alloc_stats() -> AllocStats {
+    AllocStats {
+        current_bytes: TRACKER.current_bytes.load(Ordering::Relaxed),
+        peak_bytes: TRACKER.peak_bytes.load(Ordering::Relaxed),
+        live_allocations: TRACKER.live_allocations.load(Ordering::Relaxed),
+        total_allocations: TRACKER.total_allocations.load(Ordering::Relaxed),
+    }
+}
+
+/// Runs `f` and attributes the allocations it makes to `tag`. Tags can be
+/// nested; allocations are attributed to the innermost tag.
+///
+/// The first 16 tags are tracked, see [`alloc_tags`].
+pub fn with_alloc_tag<R>(tag: &'static str, f: impl FnOnce() -> R) -> R {
+    // Restores the outer tag, also if `f` panics.
+    struct Restore(Option<&'static str>);
+
+    impl Drop for Restore {
+        fn drop(&mut self) {
+            unsafe { *TRACKER.tag.get() = self.0 };
+        }
+    }
+
+    let _restore = Restore(unsafe { TRACKER.tag.get().replace(Some(tag)) });
+    f()
+}
+
+/// Returns the statistics of the tags of [`with_alloc_tag`], in the order
+/// in which the tags were first used.
+pub fn alloc_tags() -> impl Iterator<Item = AllocTagStats> {
+    let tags = unsafe { *TRACKER.tags.get() };
+    tags.into_iter().flatten()
+}
+
+/// Logs the statistics of [`alloc_stats`] and [`alloc_tags`] at the info
+/// level.
+pub fn report() {
+    let stats = alloc_stats();
+    log::info!(
+        "Allocations: {} bytes in {} allocations, peak {} bytes, {} allocations in total",
+        stats.current_bytes,
+        stats.live_allocations,
+        stats.peak_bytes,
+        stats.total_allocations
+    );
+    for tag in alloc_tags() {
+        log::info!(
+            "- {}: {} bytes in {} allocations",
+            tag.tag,
+            tag.bytes,
+            tag.allocations
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_tracking() {
+        let before = alloc_stats();
+        with_alloc_tag("outer", || {
+            record_alloc(100);
+            with_alloc_tag("inner", || record_alloc(20));
+            record_alloc(3);
+        });
+        let stats = alloc_stats();
+        assert_eq!(stats.current_bytes, before.current_bytes + 123);
+        assert_eq!(stats.live_allocations, before.live_allocations + 3);
+        assert!(stats.peak_bytes >= stats.current_bytes);
+
+        record_dealloc(100);
+        record_dealloc(20);
+        record_dealloc(3);
+        assert_eq!(alloc_stats().current_bytes, before.current_bytes);
+
+        let mut tags = alloc_tags();
+        assert_eq!(
+            tags.next(),
+            Some(AllocTagStats {
+                tag: "outer",
+                allocations: 2,
+                bytes: 103
+            })
+        );
+        assert_eq!(
+            tags.next(),
+            Some(AllocTagStats {
+                tag: "inner",
+                allocations: 1,
+                bytes: 20
+            })
+        );
+        assert_eq!(tags.next(), None);
+    }
+}
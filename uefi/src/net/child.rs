@@ -0,0 +1,127 @@
+// SPDX-License-Identifier: MIT OR Apache-2.0
+
+//! Children of the service binding protocols of the network stack.
+
+use crate::boot::{self, OpenProtocolAttributes, OpenProtocolParams, ScopedProtocol};
+use crate::proto::ProtocolPointer;
+use crate::proto::network::tcp4::{Tcp4, Tcp4Binding};
+use crate::proto::network::tcp6::{Tcp6, Tcp6Binding};
+use crate::proto::network::udp4::{Udp4, Udp4Binding};
+use crate::proto::network::udp6::{Udp6, Udp6Binding};
+use crate::{Handle, Result};
+use core::fmt::Debug;
+use core::ops::{Deref, DerefMut};
+
+/// A service binding protocol.
+pub(super) trait Binding: ProtocolPointer + Debug {
+    /// The protocol of the children.
+    type Child: ProtocolPointer + Debug;
+
+    fn create_child(&mut self) -> Result<Handle>;
+
+    fn destroy_child(&mut self, handle: Handle) -> Result;
+}
+
+macro_rules! impl_binding {
+    ($binding:ty => $child:ty) => {
+        impl Binding for $binding {
+            type Child = $child;
+
+            fn create_child(&mut self) -> Result<Handle> {
+                <$binding>::create_child(self)
+            }
+
+            fn destroy_child(&mut self, handle: Handle) -> Result {
+                <$binding>::destroy_child(self, handle)
+            }
+        }
+    };
+}
+
+impl_binding!(Tcp4Binding => Tcp4);
+impl_binding!(Tcp6Binding => Tcp6);
+impl_binding!(Udp4Binding => Udp4);
+impl_binding!(Udp6Binding => Udp6);
+
+/// A child of the service binding protocol `B` on a network device, with
+/// its protocol opened. The child is destroyed on drop.
+#[derive(Debug)]
+pub(super) struct Child<B: Binding> {
+    device: Handle,
+    binding: ScopedProtocol<B>,
+    handle: Handle,
+    protocol: Option<ScopedProtocol<B::Child>>,
+}
+
+impl<B: Binding> Child<B> {
+    /// Creates a child on `device`, or on the first device with the
+    /// service binding protocol if `device` is `None`.
+    pub(super) fn create(device: Option<Handle>) -> Result<Self> {
+        let device = match device {
+            Some(device) => device,
+            None => boot::get_handle_for_protocol::<B>()?,
+        };
+        let mut binding = open::<B>(device)?;
+        let handle = binding.create_child()?;
+        Self::open(device, binding, handle)
+    }
+
+    /// Takes ownership of the child `handle`, which the firmware created on
+    /// the same device as `self`.
+    pub(super) fn adopt(&self, handle: Handle) -> Result<Self> {
+        let binding = open::<B>(self.device)?;
+        Self::open(self.device, binding, handle)
+    }
+
+    fn open(device: Handle, mut binding: ScopedProtocol<B>, handle: Handle) -> Result<Self> {
+        match open::<B::Child>(handle) {
+            Ok(protocol) => Ok(Self {
+                device,
+                binding,
+                handle,
+                protocol: Some(protocol),
+            }),
+            Err(err) => {
+                let _ = binding.destroy_child(handle);
+                Err(err)
+            }
+        }
+    }
+}
+
+impl<B: Binding> Deref for Child<B> {
+    type Target = B::Child;
+
+    fn deref(&self) -> &B::Child {
+        // OK to unwrap: the protocol is only taken on drop.
+        self.protocol.as_ref().unwrap()
+    }
+}
+
+impl<B: Binding> DerefMut for Child<B> {
+    fn deref_mut(&mut self) -> &mut B::Child {
+        // OK to unwrap: the protocol is only taken on drop.
+        self.protocol.as_mut().unwrap()
+    }
+}
+
+impl<B: Binding> Drop for Child<B> {
+    fn drop(&mut self) {
+        // The protocol must be closed before the child is destroyed.
+        self.protocol = None;
+        let _ = self.binding.destroy_child(self.handle);
+    }
+}
+
+fn open<P: ProtocolPointer + ?Sized>(handle: Handle) -> Result<ScopedProtocol<P>> {
+    unsafe {
+        boot::open_protocol::<P>(
+            OpenProtocolParams {
+                handle,
+                agent: boot::image_handle(),
+                controller: None,
+            },
+            OpenProtocolAttributes::GetProtocol,
+        )
+    }
+}
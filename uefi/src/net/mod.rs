@@ -0,0 +1,100 @@
+// SPDX-License-Identifier: MIT OR Apache-2.0
+
+//! Async TCP and UDP sockets.
+//!
+//! [`TcpStream`], [`TcpListener`] and [`UdpSocket`] use the TCP4/TCP6 and
+//! UDP4/UDP6 protocols of the firmware's network stack, depending on the IP
+//! version of the address. Their functions are `async`, so that the
+//! executor of [`task`] can run other work, e.g. a second transfer, while a
+//! request is in progress.
+//!
+//! The sockets use the first network device that supports the protocol,
+//! unless a device is given with the `_on` variants of the constructors.
+//! IPv4 sockets always use the default address of the device, which is
+//! usually configured by DHCP.
+//!
+//! On firmware without these protocols, the `smoltcp` feature provides
+//! [`SnpDevice`], which runs the TCP/IP stack of the [`smoltcp`] crate over
+//! the Simple Network protocol of the network device.
+//!
+//! # Timeouts
+//!
+//! [`set_timeout`] limits how long each request of a socket may take.
+//! Requests fail with [`Status::TIMEOUT`] when the limit is exceeded. Any
+//! future of this module can also be wrapped with [`task::timeout`]: when
+//! it is dropped, the request in progress is canceled.
+//!
+//! # Example
+//!
+//! ```no_run
+//! use core::time::Duration;
+//! use uefi::net::TcpStream;
+//! use uefi::task;
+//!
+//! # fn main() -> uefi::Result {
+//! task::block_on(async {
+//!     let mut stream = TcpStream::connect("192.168.1.10:8000".parse().unwrap()).await?;
+//!     stream.set_timeout(Some(Duration::from_secs(5)));
+//!     stream.write_all(b"hello").await?;
+//!     let mut reply = [0; 64];
+//!     let len = stream.read(&mut reply).await?;
+//!     log::info!("reply: {:?}", &reply[..len]);
+//!     stream.close().await
+//! })
+//! # }
+//! ```
+//!
+//! [`task`]: crate::task
+//! [`task::timeout`]: crate::task::timeout
+//! [`set_timeout`]: TcpStream::set_timeout
+//! [`Status::TIMEOUT`]: crate::Status::TIMEOUT
+//! [`smoltcp`]: https://docs.rs/smoltcp
+
+mod child;
+#[cfg(feature = "smoltcp")]
+mod snp_device;
+mod tcp;
+mod udp;
+
+#[cfg(feature = "smoltcp")]
+pub use snp_device::{SnpDevice, SnpRxToken, SnpTxToken};
+pub use tcp::{TcpListener, TcpStream};
+pub use udp::UdpSocket;
+
+use crate::Result;
+use crate::task::{self, Completion};
+use core::future::Future;
+use core::time::Duration;
+
+/// Runs `future`, failing with [`Status::TIMEOUT`] after `timeout`.
+///
+/// [`Status::TIMEOUT`]: crate::Status::TIMEOUT
+async fn with_timeout<T>(
+    timeout: Option<Duration>,
+    future: impl Future<Output = Result<T>>,
+) -> Result<T> {
+    match timeout {
+        Some(timeout) => task::timeout(timeout, future).await?,
+        None => future.await,
+    }
+}
+
+/// Cancels a request when dropped, unless it has been disarmed.
+struct CancelOnDrop<F: FnOnce()>(Option<F>);
+
+impl<F: FnOnce()> Drop for CancelOnDrop<F> {
+    fn drop(&mut self) {
+        if let Some(cancel) = self.0.take() {
+            cancel();
+        }
+    }
+}
+
+/// Waits for the `completion` of a request. If the returned future is
+/// dropped before, the request is canceled with `cancel`, so that the
+/// firmware no longer uses the token and the buffers of the request.
+async fn wait_or_cancel(completion: &mut Completion, cancel: impl FnOnce()) {
+    let mut guard = CancelOnDrop(Some(cancel));
+    completion.await;
+    guard.0 = None;
+}
@@ -0,0 +1,185 @@
+// SPDX-License-Identifier: MIT OR Apache-2.0
+
+//! A [`smoltcp`] device over the Simple Network protocol.
+//!
+//! [`smoltcp`]: https://docs.rs/smoltcp
+
+use crate::Result;
+use crate::boot::ScopedProtocol;
+use crate::proto::network::snp::{NetworkState, ReceiveFlags, SimpleNetwork};
+use alloc::vec;
+use alloc::vec::Vec;
+use smoltcp::phy::{Device, DeviceCapabilities, Medium, RxToken, TxToken};
+use smoltcp::time::Instant;
+use smoltcp::wire::EthernetAddress;
+
+/// A [`smoltcp`] [`Device`] that sends and receives Ethernet frames with the
+/// [`SimpleNetwork`] protocol.
+///
+/// This provides a complete TCP/IP stack on firmware without the TCP, UDP or
+/// HTTP drivers. The [`SimpleNetwork`] protocol should be opened
+/// exclusively, so that the network stack of the firmware doesn't receive
+/// the frames instead.
+///
+/// Frames are sent synchronously: a transmission waits until the network
+/// interface has recycled the buffer. Frames that can't be sent or received
+/// because of an error are dropped, and left to the protocols of [`smoltcp`]
+/// to retransmit.
+///
+/// # Example
+///
+/// ```no_run
+/// use smoltcp::iface::{Config, Interface};
+/// use smoltcp::time::Instant;
+/// use uefi::boot;
+/// use uefi::net::SnpDevice;
+/// use uefi::proto::network::snp::SimpleNetwork;
+///
+/// # fn example() -> uefi::Result {
+/// let handle = boot::get_handle_for_protocol::<SimpleNetwork>()?;
+/// let snp = boot::open_protocol_exclusive::<SimpleNetwork>(handle)?;
+/// let mut device = SnpDevice::new(snp)?;
+/// let config = Config::new(device.hardware_address().into());
+/// let mut iface = Interface::new(config, &mut device, Instant::ZERO);
+/// # Ok(())
+/// # }
+/// ```
+///
+/// [`smoltcp`]: https://docs.rs/smoltcp
+#[derive(Debug)]
+pub struct SnpDevice {
+    snp: ScopedProtocol<SimpleNetwork>,
+    rx_buffer: Vec<u8>,
+    tx_buffer: Vec<u8>,
+}
+
+impl SnpDevice {
+    /// Creates a device for the network interface of `snp`.
+    ///
+    /// The interface is started and initialized if necessary, and set up to
+    /// receive unicast, broadcast and multicast frames, as far as it
+    /// supports them.
+    ///
+    /// # Errors
+    ///
+    /// Errors of [`SimpleNetwork::start`], [`SimpleNetwork::initialize`] and
+    /// [`SimpleNetwork::receive_filters`].
+    pub fn new(snp: ScopedProtocol<SimpleNetwork>) -> Result<Self> {
+        if snp.mode().state == NetworkState::STOPPED {
+            snp.start()?;
+        }
+        if snp.mode().state == NetworkState::STARTED {
+            snp.initialize(0, 0)?;
+        }
+
+        // Multicast is needed for IPv6 neighbor discovery and mDNS, and
+        // only available on some interfaces.
+        let supported = ReceiveFlags::from_bits_truncate(snp.mode().receive_filter_mask);
+        let wanted =
+            ReceiveFlags::UNICAST | ReceiveFlags::BROADCAST | ReceiveFlags::PROMISCUOUS_MULTICAST;
+        snp.receive_filters(wanted & supported, ReceiveFlags::empty(), false, None)?;
+
+        let frame_size = (snp.mode().media_header_size + snp.mode().max_packet_size) as usize;
+        Ok(Self {
+            snp,
+            rx_buffer: vec![0; frame_size],
+            tx_buffer: vec![0; frame_size],
+        })
+    }
+
+    /// Returns the current MAC address of the network interface, to
+    /// configure the [`smoltcp`] interface with.
+    ///
+    /// [`smoltcp`]: https://docs.rs/smoltcp
+    #[must_use]
+    pub fn hardware_address(&self) -> EthernetAddress {
+        EthernetAddress::from_bytes(&self.snp.mode().current_address.0[..6])
+    }
+
+    /// Returns the [`SimpleNetwork`] protocol.
+    #[must_use]
+    pub fn into_inner(self) -> ScopedProtocol<SimpleNetwork> {
+        self.snp
+    }
+}
+
+impl Device for SnpDevice {
+    type RxToken<'a> = SnpRxToken<'a>;
+    type TxToken<'a> = SnpTxToken<'a>;
+
+    fn receive(&mut self, _timestamp: Instant) -> Option<(Self::RxToken<'_>, Self::TxToken<'_>)> {
+        let len = self
+            .snp
+            .receive(&mut self.rx_buffer, None, None, None, None)
+            .ok()?;
+        let rx = SnpRxToken {
+            frame: &self.rx_buffer[..len],
+        };
+        let tx = SnpTxToken {
+            snp: &self.snp,
+            buffer: &mut self.tx_buffer,
+        };
+        Some((rx, tx))
+    }
+
+    fn transmit(&mut self, _timestamp: Instant) -> Option<Self::TxToken<'_>> {
+        Some(SnpTxToken {
+            snp: &self.snp,
+            buffer: &mut self.tx_buffer,
+        })
+    }
+
+    fn capabilities(&self) -> DeviceCapabilities {
+        let mut capabilities = DeviceCapabilities::default();
+        capabilities.medium = Medium::Ethernet;
+        // smoltcp includes the Ethernet header in the MTU.
+        capabilities.max_transmission_unit = self.tx_buffer.len();
+        capabilities.max_burst_size = Some(1);
+        capabilities
+    }
+}
+
+/// A received frame of a [`SnpDevice`].
+#[derive(Debug)]
+pub struct SnpRxToken<'a> {
+    frame: &'a [u8],
+}
+
+impl RxToken for SnpRxToken<'_> {
+    fn consume<R, F>(self, f: F) -> R
+    where
+        F: FnOnce(&[u8]) -> R,
+    {
+        f(self.frame)
+    }
+}
+
+/// A frame to be sent by a [`SnpDevice`].
+#[derive(Debug)]
+pub struct SnpTxToken<'a> {
+    snp: &'a SimpleNetwork,
+    buffer: &'a mut Vec<u8>,
+}
+
+impl TxToken for SnpTxToken<'_> {
+    fn consume<R, F>(self, len: usize, f: F) -> R
+    where
+        F: FnOnce(&mut [u8]) -> R,
+    {
+        if self.buffer.len() < len {
+            self.buffer.resize(len, 0);
+        }
+        let frame = &mut self.buffer[..len];
+        let result = f(frame);
+
+        // The frame includes the Ethernet header, so the interface doesn't
+        // need to fill it in.
+        match self.snp.transmit(0, frame, None, None, None) {
+            // The buffer must not be reused until the interface is done
+            // with it.
+            Ok(()) => while let Ok(None) = self.snp.get_recycled_transmit_buffer_status() {},
+            Err(err) => log::debug!("Dropping frame that failed to transmit: {err}"),
+        }
+        result
+    }
+}
@@ -0,0 +1,487 @@
+// SPDX-License-Identifier: MIT OR Apache-2.0
+
+//! TCP sockets.
+
+use super::child::Child;
+use super::{wait_or_cancel, with_timeout};
+use crate::proto::network::tcp4::{
+    Tcp4AccessPoint, Tcp4Binding, Tcp4CloseToken, Tcp4CompletionToken, Tcp4ConfigData,
+    Tcp4ConnectionToken, Tcp4FragmentData, Tcp4IoToken, Tcp4ListenToken, Tcp4Packet,
+    Tcp4ReceiveData, Tcp4TransmitData,
+};
+use crate::proto::network::tcp6::{Tcp6AccessPoint, Tcp6Binding, Tcp6ConfigData};
+use crate::task::Completion;
+use crate::{Handle, Result, Status, StatusExt};
+use alloc::vec;
+use core::net::{Ipv4Addr, Ipv6Addr, SocketAddr, SocketAddrV4, SocketAddrV6};
+use core::ptr;
+use core::time::Duration;
+use uefi_raw::Boolean;
+
+/// Type of service of IPv4 packets, as used by the TCP clients of EDK2.
+const TYPE_OF_SERVICE: u8 = 8;
+
+/// Time to live or hop limit of IP packets.
+const TIME_TO_LIVE: u8 = 255;
+
+/// Maximum length of the data of a read or write request.
+///
+/// The firmware accesses a buffer owned by the request rather than the
+/// caller's, so that leaking the future can't leave it with a dangling
+/// pointer. Requests are capped to bound the copy.
+const MAX_REQUEST_LEN: usize = 0x10000;
+
+/// A TCP4 or TCP6 child.
+///
+/// Requests use the TCP4 token types; the TCP6 tokens have the same layout.
+#[derive(Debug)]
+enum Socket {
+    V4(Child<Tcp4Binding>),
+    V6(Child<Tcp6Binding>),
+}
+
+impl Socket {
+    /// Creates a socket for `local` and `remote`. Passive sockets listen
+    /// for connections.
+    fn open(
+        device: Option<Handle>,
+        local: SocketAddr,
+        remote: SocketAddr,
+        active: bool,
+    ) -> Result<Self> {
+        match (local, remote) {
+            (SocketAddr::V4(local), SocketAddr::V4(remote)) => {
+                if !local.ip().is_unspecified() {
+                    return Err(Status::UNSUPPORTED.into());
+                }
+                let mut child = Child::<Tcp4Binding>::create(device)?;
+                child.configure(Some(&Tcp4ConfigData {
+                    type_of_service: TYPE_OF_SERVICE,
+                    time_to_live: TIME_TO_LIVE,
+                    access_point: Tcp4AccessPoint {
+                        use_default_address: Boolean::TRUE,
+                        station_port: local.port(),
+                        remote_address: (*remote.ip()).into(),
+                        remote_port: remote.port(),
+                        active_flag: active.into(),
+                        ..Default::default()
+                    },
+                    control_option: ptr::null_mut(),
+                }))?;
+                Ok(Self::V4(child))
+            }
+            (SocketAddr::V6(local), SocketAddr::V6(remote)) => {
+                let mut child = Child::<Tcp6Binding>::create(device)?;
+                child.configure(Some(&Tcp6ConfigData {
+                    traffic_class: 0,
+                    hop_limit: TIME_TO_LIVE,
+                    access_point: Tcp6AccessPoint {
+                        station_address: (*local.ip()).into(),
+                        station_port: local.port(),
+                        remote_address: (*remote.ip()).into(),
+                        remote_port: remote.port(),
+                        active_flag: active.into(),
+                    },
+                    control_option: ptr::null_mut(),
+                }))?;
+                Ok(Self::V6(child))
+            }
+            _ => Err(Status::INVALID_PARAMETER.into()),
+        }
+    }
+
+    /// Returns the local and the remote address.
+    fn addresses(&self) -> Result<(SocketAddr, SocketAddr)> {
+        match self {
+            Self::V4(child) => {
+                let ap = child.config_data()?.access_point;
+                Ok((
+                    SocketAddrV4::new(ap.station_address.into(), ap.station_port).into(),
+                    SocketAddrV4::new(ap.remote_address.into(), ap.remote_port).into(),
+                ))
+            }
+            Self::V6(child) => {
+                let ap = child.config_data()?.access_point;
+                Ok((
+                    SocketAddrV6::new(ap.station_address.into(), ap.station_port, 0, 0).into(),
+                    SocketAddrV6::new(ap.remote_address.into(), ap.remote_port, 0, 0).into(),
+                ))
+            }
+        }
+    }
+
+    /// Runs the request of `token`, which `start` queues, until it
+    /// completes. The request is canceled if the future is dropped before.
+    ///
+    /// All TCP tokens start with a completion token.
+    async fn run<T>(
+        &mut self,
+        token: *mut T,
+        start: unsafe fn(&mut Self, *mut T) -> Result,
+    ) -> Result {
+        let completion = token.cast::<Tcp4CompletionToken>();
+        let mut done = Completion::new()?;
+        unsafe {
+            (*completion).event = done.event().as_ptr();
+            (*completion).status = Status::NOT_READY;
+            start(self, token)?;
+        }
+        let this: *mut Self = self;
+        wait_or_cancel(&mut done, || {
+            let _ = unsafe { (*this).cancel(completion) };
+        })
+        .await;
+        unsafe { (*completion).status }.to_result()
+    }
+
+    unsafe fn cancel(&mut self, token: *mut Tcp4CompletionToken) -> Result {
+        match self {
+            Self::V4(child) => unsafe { child.cancel(token) },
+            Self::V6(child) => unsafe { child.cancel(token.cast()) },
+        }
+    }
+
+    unsafe fn start_connect(&mut self, token: *mut Tcp4ConnectionToken) -> Result {
+        match self {
+            Self::V4(child) => unsafe { child.connect(token) },
+            Self::V6(child) => unsafe { child.connect(token.cast()) },
+        }
+    }
+
+    unsafe fn start_accept(&mut self, token: *mut Tcp4ListenToken) -> Result {
+        match self {
+            Self::V4(child) => unsafe { child.accept(token) },
+            Self::V6(child) => unsafe { child.accept(token.cast()) },
+        }
+    }
+
+    unsafe fn start_transmit(&mut self, token: *mut Tcp4IoToken) -> Result {
+        match self {
+            Self::V4(child) => unsafe { child.transmit(token) },
+            Self::V6(child) => unsafe { child.transmit(token.cast()) },
+        }
+    }
+
+    unsafe fn start_receive(&mut self, token: *mut Tcp4IoToken) -> Result {
+        match self {
+            Self::V4(child) => unsafe { child.receive(token) },
+            Self::V6(child) => unsafe { child.receive(token.cast()) },
+        }
+    }
+
+    unsafe fn start_close(&mut self, token: *mut Tcp4CloseToken) -> Result {
+        match self {
+            Self::V4(child) => unsafe { child.close(token) },
+            Self::V6(child) => unsafe { child.close(token.cast()) },
+        }
+    }
+
+    /// Takes ownership of the child for an accepted connection.
+    fn adopt(&self, handle: Handle) -> Result<Self> {
+        match self {
+            Self::V4(child) => child.adopt(handle).map(Self::V4),
+            Self::V6(child) => child.adopt(handle).map(Self::V6),
+        }
+    }
+}
+
+const fn completion_token() -> Tcp4CompletionToken {
+    Tcp4CompletionToken {
+        event: ptr::null_mut(),
+        status: Status::NOT_READY,
+    }
+}
+
+/// Unspecified local address of the IP version of `addr`.
+const fn unspecified(addr: &SocketAddr) -> SocketAddr {
+    match addr {
+        SocketAddr::V4(_) => SocketAddr::V4(SocketAddrV4::new(Ipv4Addr::UNSPECIFIED, 0)),
+        SocketAddr::V6(_) => SocketAddr::V6(SocketAddrV6::new(Ipv6Addr::UNSPECIFIED, 0, 0, 0)),
+    }
+}
+
+/// A TCP connection.
+///
+/// Dropping the stream resets the connection; use [`close`] to close it
+/// gracefully.
+///
+/// [`close`]: Self::close
+#[derive(Debug)]
+pub struct TcpStream {
+    socket: Socket,
+    timeout: Option<Duration>,
+}
+
+impl TcpStream {
+    /// Opens a connection to `addr` on the first network device with the
+    /// TCP protocol of the IP version of `addr`.
+    ///
+    /// # Errors
+    ///
+    /// See [`connect_on`](Self::connect_on).
+    pub async fn connect(addr: SocketAddr) -> Result<Self> {
+        Self::connect_with(None, addr).await
+    }
+
+    /// Opens a connection to `addr` on the network device `device`.
+    ///
+    /// # Errors
+    ///
+    /// * [`Status::NOT_FOUND`]: no device has the TCP protocol.
+    /// * [`Status::NO_MAPPING`]: the device has no IP address yet.
+    /// * [`Status::CONNECTION_REFUSED`]: the remote host refused the
+    ///   connection.
+    /// * [`Status::TIMEOUT`]: the remote host didn't respond.
+    pub async fn connect_on(device: Handle, addr: SocketAddr) -> Result<Self> {
+        Self::connect_with(Some(device), addr).await
+    }
+
+    async fn connect_with(device: Option<Handle>, addr: SocketAddr) -> Result<Self> {
+        let mut socket = Socket::open(device, unspecified(&addr), addr, true)?;
+        let mut token = Tcp4ConnectionToken {
+            completion_token: completion_token(),
+        };
+        socket.run(&raw mut token, Socket::start_connect).await?;
+        Ok(Self {
+            socket,
+            timeout: None,
+        })
+    }
+
+    /// Returns the local address of the connection.
+    pub fn local_addr(&self) -> Result<SocketAddr> {
+        Ok(self.socket.addresses()?.0)
+    }
+
+    /// Returns the address of the remote host.
+    pub fn peer_addr(&self) -> Result<SocketAddr> {
+        Ok(self.socket.addresses()?.1)
+    }
+
+    /// Returns the time limit of requests, see [`set_timeout`].
+    ///
+    /// [`set_timeout`]: Self::set_timeout
+    #[must_use]
+    pub const fn timeout(&self) -> Option<Duration> {
+        self.timeout
+    }
+
+    /// Sets how long [`read`], [`write`] and [`close`] may take, or removes
+    /// the limit if `timeout` is `None`.
+    ///
+    /// [`read`]: Self::read
+    /// [`write`]: Self::write
+    /// [`close`]: Self::close
+    pub const fn set_timeout(&mut self, timeout: Option<Duration>) {
+        self.timeout = timeout;
+    }
+
+    /// Receives data into `buf` and returns its length. Returns 0 once the
+    /// remote host has closed the connection.
+    ///
+    /// # Errors
+    ///
+    /// * [`Status::TIMEOUT`]: the [time limit](Self::set_timeout) has been
+    ///   exceeded.
+    /// * [`Status::CONNECTION_RESET`]: the remote host reset the
+    ///   connection.
+    pub async fn read(&mut self, buf: &mut [u8]) -> Result<usize> {
+        if buf.is_empty() {
+            return Ok(0);
+        }
+        let mut data = vec![0; buf.len().min(MAX_REQUEST_LEN)];
+        let len = data.len() as u32;
+        let mut rx = Tcp4ReceiveData {
+            urgent_flag: Boolean::FALSE,
+            data_length: len,
+            fragment_count: 1,
+            fragment_table: [Tcp4FragmentData {
+                fragment_length: len,
+                fragment_buffer: data.as_mut_ptr().cast(),
+            }],
+        };
+        let mut token = Tcp4IoToken {
+            completion_token: completion_token(),
+            packet: Tcp4Packet { rx_data: &mut rx },
+        };
+        let run = self.socket.run(&raw mut token, Socket::start_receive);
+        match with_timeout(self.timeout, run).await {
+            Ok(()) => {
+                let len = (rx.data_length as usize).min(data.len());
+                buf[..len].copy_from_slice(&data[..len]);
+                Ok(len)
+            }
+            Err(err) if err.status() == Status::CONNECTION_FIN => Ok(0),
+            Err(err) => Err(err),
+        }
+    }
+
+    /// Sends data from `buf` and returns how much was sent.
+    ///
+    /// # Errors
+    ///
+    /// * [`Status::TIMEOUT`]: the [time limit](Self::set_timeout) has been
+    ///   exceeded.
+    /// * [`Status::CONNECTION_FIN`]: the connection has been closed.
+    /// * [`Status::CONNECTION_RESET`]: the remote host reset the
+    ///   connection.
+    pub async fn write(&mut self, buf: &[u8]) -> Result<usize> {
+        if buf.is_empty() {
+            return Ok(0);
+        }
+        let mut data = buf[..buf.len().min(MAX_REQUEST_LEN)].to_vec();
+        let len = data.len() as u32;
+        let mut tx = Tcp4TransmitData {
+            push: Boolean::TRUE,
+            urgent: Boolean::FALSE,
+            data_length: len,
+            fragment_count: 1,
+            fragment_table: [Tcp4FragmentData {
+                fragment_length: len,
+                fragment_buffer: data.as_mut_ptr().cast(),
+            }],
+        };
+        let mut token = Tcp4IoToken {
+            completion_token: completion_token(),
+            packet: Tcp4Packet { tx_data: &mut tx },
+        };
+        let run = self.socket.run(&raw mut token, Socket::start_transmit);
+        with_timeout(self.timeout, run).await?;
+        Ok(data.len())
+    }
+
+    /// Sends all data of `buf`.
+    ///
+    /// # Errors
+    ///
+    /// See [`write`](Self::write).
+    pub async fn write_all(&mut self, mut buf: &[u8]) -> Result {
+        while !buf.is_empty() {
+            let len = self.write(buf).await?;
+            buf = &buf[len..];
+        }
+        Ok(())
+    }
+
+    /// Closes the connection gracefully, after the data that has been
+    /// written is sent.
+    ///
+    /// # Errors
+    ///
+    /// * [`Status::TIMEOUT`]: the [time limit](Self::set_timeout) has been
+    ///   exceeded. The connection is reset.
+    pub async fn close(mut self) -> Result {
+        let mut token = Tcp4CloseToken {
+            completion_token: completion_token(),
+            abort_on_close: Boolean::FALSE,
+        };
+        let run = self.socket.run(&raw mut token, Socket::start_close);
+        with_timeout(self.timeout, run).await
+    }
+}
+
+#[cfg(feature = "embedded-io")]
+impl embedded_io_async::ErrorType for TcpStream {
+    type Error = crate::Error;
+}
+
+#[cfg(feature = "embedded-io")]
+impl embedded_io_async::Read for TcpStream {
+    async fn read(&mut self, buf: &mut [u8]) -> Result<usize> {
+        Self::read(self, buf).await
+    }
+}
+
+#[cfg(feature = "embedded-io")]
+impl embedded_io_async::Write for TcpStream {
+    async fn write(&mut self, buf: &[u8]) -> Result<usize> {
+        Self::write(self, buf).await
+    }
+
+    /// Does nothing, written data is pushed to the remote host right away.
+    async fn flush(&mut self) -> Result {
+        Ok(())
+    }
+}
+
+/// A TCP socket that accepts connections.
+#[derive(Debug)]
+pub struct TcpListener {
+    socket: Socket,
+    timeout: Option<Duration>,
+}
+
+impl TcpListener {
+    /// Listens for connections to `addr` on the first network device with
+    /// the TCP protocol of the IP version of `addr`.
+    ///
+    /// # Errors
+    ///
+    /// See [`bind_on`](Self::bind_on).
+    pub fn bind(addr: SocketAddr) -> Result<Self> {
+        Self::bind_with(None, addr)
+    }
+
+    /// Listens for connections to `addr` on the network device `device`.
+    ///
+    /// # Errors
+    ///
+    /// * [`Status::NOT_FOUND`]: no device has the TCP protocol.
+    /// * [`Status::NO_MAPPING`]: the device has no IP address yet.
+    /// * [`Status::UNSUPPORTED`]: `addr` is an IPv4 address other than
+    ///   `0.0.0.0`.
+    /// * [`Status::ACCESS_DENIED`]: the port is in use.
+    pub fn bind_on(device: Handle, addr: SocketAddr) -> Result<Self> {
+        Self::bind_with(Some(device), addr)
+    }
+
+    fn bind_with(device: Option<Handle>, addr: SocketAddr) -> Result<Self> {
+        let socket = Socket::open(device, addr, unspecified(&addr), false)?;
+        Ok(Self {
+            socket,
+            timeout: None,
+        })
+    }
+
+    /// Returns the local address of the listener.
+    pub fn local_addr(&self) -> Result<SocketAddr> {
+        Ok(self.socket.addresses()?.0)
+    }
+
+    /// Returns the time limit of [`accept`](Self::accept).
+    #[must_use]
+    pub const fn timeout(&self) -> Option<Duration> {
+        self.timeout
+    }
+
+    /// Sets how long [`accept`](Self::accept) may wait, or removes the limit
+    /// if `timeout` is `None`.
+    pub const fn set_timeout(&mut self, timeout: Option<Duration>) {
+        self.timeout = timeout;
+    }
+
+    /// Waits for a connection, and returns it together with the address of
+    /// the remote host.
+    ///
+    /// # Errors
+    ///
+    /// * [`Status::TIMEOUT`]: the [time limit](Self::set_timeout) has been
+    ///   exceeded.
+    pub async fn accept(&mut self) -> Result<(TcpStream, SocketAddr)> {
+        let mut token = Tcp4ListenToken {
+            completion_token: completion_token(),
+            new_child_handle: ptr::null_mut(),
+        };
+        let run = self.socket.run(&raw mut token, Socket::start_accept);
+        with_timeout(self.timeout, run).await?;
+
+        let handle =
+            unsafe { Handle::from_ptr(token.new_child_handle) }.ok_or(Status::PROTOCOL_ERROR)?;
+        let stream = TcpStream {
+            socket: self.socket.adopt(handle)?,
+            timeout: None,
+        };
+        let peer = stream.peer_addr()?;
+        Ok((stream, peer))
+    }
+}
@@ -0,0 +1,332 @@
+// SPDX-License-Identifier: MIT OR Apache-2.0
+
+//! UDP sockets.
+
+use super::child::Child;
+use super::{wait_or_cancel, with_timeout};
+use crate::boot;
+use crate::proto::network::udp4::{
+    Udp4Binding, Udp4CompletionToken, Udp4ConfigData, Udp4FragmentData, Udp4Packet,
+    Udp4ReceiveData, Udp4SessionData, Udp4TransmitData,
+};
+use crate::proto::network::udp6::{
+    Udp6Binding, Udp6ConfigData, Udp6FragmentData, Udp6ReceiveData, Udp6SessionData,
+    Udp6TransmitData,
+};
+use crate::task::Completion;
+use crate::{Event, Handle, Result, Status, StatusExt};
+use core::net::{SocketAddr, SocketAddrV4, SocketAddrV6};
+use core::time::Duration;
+use core::{ptr, slice};
+use uefi_raw::Boolean;
+
+/// Time to live or hop limit of IP packets.
+const TIME_TO_LIVE: u8 = 64;
+
+/// A UDP4 or UDP6 child.
+///
+/// Requests use the UDP4 token type; the UDP6 token has the same layout.
+#[derive(Debug)]
+enum Socket {
+    V4(Child<Udp4Binding>),
+    V6(Child<Udp6Binding>),
+}
+
+impl Socket {
+    fn open(device: Option<Handle>, local: SocketAddr) -> Result<Self> {
+        match local {
+            SocketAddr::V4(local) => {
+                if !local.ip().is_unspecified() {
+                    return Err(Status::UNSUPPORTED.into());
+                }
+                let mut child = Child::<Udp4Binding>::create(device)?;
+                child.configure(Some(&Udp4ConfigData {
+                    time_to_live: TIME_TO_LIVE,
+                    use_default_address: Boolean::TRUE,
+                    station_port: local.port(),
+                    ..Default::default()
+                }))?;
+                Ok(Self::V4(child))
+            }
+            SocketAddr::V6(local) => {
+                let mut child = Child::<Udp6Binding>::create(device)?;
+                child.configure(Some(&Udp6ConfigData {
+                    hop_limit: TIME_TO_LIVE,
+                    station_address: (*local.ip()).into(),
+                    station_port: local.port(),
+                    ..Default::default()
+                }))?;
+                Ok(Self::V6(child))
+            }
+        }
+    }
+
+    fn local_addr(&self) -> Result<SocketAddr> {
+        Ok(match self {
+            Self::V4(child) => {
+                let config = child.config_data()?;
+                SocketAddrV4::new(config.station_address.into(), config.station_port).into()
+            }
+            Self::V6(child) => {
+                let config = child.config_data()?;
+                SocketAddrV6::new(config.station_address.into(), config.station_port, 0, 0).into()
+            }
+        })
+    }
+
+    /// Runs the request of `token`, which `start` queues, until it
+    /// completes. The request is canceled if the future is dropped before.
+    async fn run(
+        &mut self,
+        token: *mut Udp4CompletionToken,
+        start: unsafe fn(&mut Self, *mut Udp4CompletionToken) -> Result,
+    ) -> Result {
+        let mut done = Completion::new()?;
+        unsafe {
+            (*token).event = done.event().as_ptr();
+            (*token).status = Status::NOT_READY;
+            start(self, token)?;
+        }
+        let this: *mut Self = self;
+        wait_or_cancel(&mut done, || {
+            let _ = unsafe { (*this).cancel(token) };
+        })
+        .await;
+        unsafe { (*token).status }.to_result()
+    }
+
+    unsafe fn cancel(&mut self, token: *mut Udp4CompletionToken) -> Result {
+        match self {
+            Self::V4(child) => unsafe { child.cancel(token) },
+            Self::V6(child) => unsafe { child.cancel(token.cast()) },
+        }
+    }
+
+    unsafe fn start_transmit(&mut self, token: *mut Udp4CompletionToken) -> Result {
+        match self {
+            Self::V4(child) => unsafe { child.transmit(token) },
+            Self::V6(child) => unsafe { child.transmit(token.cast()) },
+        }
+    }
+
+    unsafe fn start_receive(&mut self, token: *mut Udp4CompletionToken) -> Result {
+        match self {
+            Self::V4(child) => unsafe { child.receive(token) },
+            Self::V6(child) => unsafe { child.receive(token.cast()) },
+        }
+    }
+
+    const fn is_ipv6(&self) -> bool {
+        matches!(self, Self::V6(_))
+    }
+}
+
+const fn token(packet: Udp4Packet) -> Udp4CompletionToken {
+    Udp4CompletionToken {
+        event: ptr::null_mut(),
+        status: Status::NOT_READY,
+        packet,
+    }
+}
+
+/// Copies the fragments of a received datagram into `buf`, and returns the
+/// number of bytes copied.
+///
+/// # Safety
+///
+/// `fragments` must point to `count` valid fragments.
+unsafe fn copy_fragments(fragments: *const Udp4FragmentData, count: u32, buf: &mut [u8]) -> usize {
+    let fragments = unsafe { slice::from_raw_parts(fragments, count as usize) };
+    let mut len = 0;
+    for fragment in fragments {
+        let n = (fragment.fragment_length as usize).min(buf.len() - len);
+        unsafe {
+            ptr::copy_nonoverlapping(
+                fragment.fragment_buffer.cast::<u8>(),
+                buf[len..].as_mut_ptr(),
+                n,
+            );
+        }
+        len += n;
+    }
+    len
+}
+
+/// Frees received data by signaling its recycle event.
+fn recycle(event: uefi_raw::Event) {
+    if let Some(event) = unsafe { Event::from_ptr(event) } {
+        let _ = boot::signal_event(&event);
+    }
+}
+
+/// A UDP socket.
+#[derive(Debug)]
+pub struct UdpSocket {
+    socket: Socket,
+    timeout: Option<Duration>,
+}
+
+impl UdpSocket {
+    /// Creates a socket bound to `addr` on the first network device with
+    /// the UDP protocol of the IP version of `addr`. Port 0 selects a free
+    /// port.
+    ///
+    /// # Errors
+    ///
+    /// See [`bind_on`](Self::bind_on).
+    pub fn bind(addr: SocketAddr) -> Result<Self> {
+        Self::bind_with(None, addr)
+    }
+
+    /// Creates a socket bound to `addr` on the network device `device`.
+    /// Port 0 selects a free port.
+    ///
+    /// # Errors
+    ///
+    /// * [`Status::NOT_FOUND`]: no device has the UDP protocol.
+    /// * [`Status::NO_MAPPING`]: the device has no IP address yet.
+    /// * [`Status::UNSUPPORTED`]: `addr` is an IPv4 address other than
+    ///   `0.0.0.0`.
+    /// * [`Status::ACCESS_DENIED`]: the port is in use.
+    pub fn bind_on(device: Handle, addr: SocketAddr) -> Result<Self> {
+        Self::bind_with(Some(device), addr)
+    }
+
+    fn bind_with(device: Option<Handle>, addr: SocketAddr) -> Result<Self> {
+        Ok(Self {
+            socket: Socket::open(device, addr)?,
+            timeout: None,
+        })
+    }
+
+    /// Returns the local address of the socket.
+    pub fn local_addr(&self) -> Result<SocketAddr> {
+        self.socket.local_addr()
+    }
+
+    /// Returns the time limit of requests, see [`set_timeout`].
+    ///
+    /// [`set_timeout`]: Self::set_timeout
+    #[must_use]
+    pub const fn timeout(&self) -> Option<Duration> {
+        self.timeout
+    }
+
+    /// Sets how long [`send_to`] and [`recv_from`] may take, or removes the
+    /// limit if `timeout` is `None`.
+    ///
+    /// [`send_to`]: Self::send_to
+    /// [`recv_from`]: Self::recv_from
+    pub const fn set_timeout(&mut self, timeout: Option<Duration>) {
+        self.timeout = timeout;
+    }
+
+    /// Sends `buf` as a datagram to `addr`, and returns its length.
+    ///
+    /// # Errors
+    ///
+    /// * [`Status::INVALID_PARAMETER`]: `addr` has another IP version than
+    ///   the socket.
+    /// * [`Status::BAD_BUFFER_SIZE`]: `buf` is too large for a datagram.
+    /// * [`Status::TIMEOUT`]: the [time limit](Self::set_timeout) has been
+    ///   exceeded.
+    pub async fn send_to(&mut self, buf: &[u8], addr: SocketAddr) -> Result<usize> {
+        let len = u32::try_from(buf.len()).map_err(|_| Status::BAD_BUFFER_SIZE)?;
+        // The firmware reads from a copy owned by the request, which stays
+        // valid even if the future is leaked.
+        let mut data = buf.to_vec();
+        let fragment_buffer = data.as_mut_ptr().cast();
+        match addr {
+            SocketAddr::V4(addr) if !self.socket.is_ipv6() => {
+                let mut session = Udp4SessionData {
+                    destination_address: (*addr.ip()).into(),
+                    destination_port: addr.port(),
+                    ..Default::default()
+                };
+                let mut tx = Udp4TransmitData {
+                    udp_session_data: &mut session,
+                    gateway_address: ptr::null_mut(),
+                    data_length: len,
+                    fragment_count: 1,
+                    fragment_table: [Udp4FragmentData {
+                        fragment_length: len,
+                        fragment_buffer,
+                    }],
+                };
+                let mut token = token(Udp4Packet { tx_data: &mut tx });
+                let run = self.socket.run(&raw mut token, Socket::start_transmit);
+                with_timeout(self.timeout, run).await?;
+            }
+            SocketAddr::V6(addr) if self.socket.is_ipv6() => {
+                let mut session = Udp6SessionData {
+                    destination_address: (*addr.ip()).into(),
+                    destination_port: addr.port(),
+                    ..Default::default()
+                };
+                let mut tx = Udp6TransmitData {
+                    udp_session_data: &mut session,
+                    data_length: len,
+                    fragment_count: 1,
+                    fragment_table: [Udp6FragmentData {
+                        fragment_length: len,
+                        fragment_buffer,
+                    }],
+                };
+                let mut token = token(Udp4Packet {
+                    tx_data: (&raw mut tx).cast(),
+                });
+                let run = self.socket.run(&raw mut token, Socket::start_transmit);
+                with_timeout(self.timeout, run).await?;
+            }
+            _ => return Err(Status::INVALID_PARAMETER.into()),
+        }
+        Ok(buf.len())
+    }
+
+    /// Receives a datagram into `buf`, and returns its length and the
+    /// address of the sender. The part of the datagram that doesn't fit into
+    /// `buf` is discarded.
+    ///
+    /// # Errors
+    ///
+    /// * [`Status::TIMEOUT`]: the [time limit](Self::set_timeout) has been
+    ///   exceeded.
+    pub async fn recv_from(&mut self, buf: &mut [u8]) -> Result<(usize, SocketAddr)> {
+        let mut token = token(Udp4Packet {
+            rx_data: ptr::null_mut(),
+        });
+        let run = self.socket.run(&raw mut token, Socket::start_receive);
+        with_timeout(self.timeout, run).await?;
+
+        let rx_data = unsafe { token.packet.rx_data };
+        if self.socket.is_ipv6() {
+            let rx = rx_data.cast::<Udp6ReceiveData>();
+            let (len, session, recycle_signal) = unsafe {
+                let fragments = (&raw const (*rx).fragment_table).cast::<Udp6FragmentData>();
+                (
+                    // The UDP6 fragments have the same layout.
+                    copy_fragments(fragments.cast(), (*rx).fragment_count, buf),
+                    (*rx).udp_session,
+                    (*rx).recycle_signal,
+                )
+            };
+            recycle(recycle_signal);
+            let source =
+                SocketAddrV6::new(session.source_address.into(), session.source_port, 0, 0);
+            Ok((len, source.into()))
+        } else {
+            let rx: *mut Udp4ReceiveData = rx_data;
+            let (len, session, recycle_signal) = unsafe {
+                let fragments = (&raw const (*rx).fragment_table).cast::<Udp4FragmentData>();
+                (
+                    copy_fragments(fragments, (*rx).fragment_count, buf),
+                    (*rx).udp_session,
+                    (*rx).recycle_signal,
+                )
+            };
+            recycle(recycle_signal);
+            let source = SocketAddrV4::new(session.source_address.into(), session.source_port);
+            Ok((len, source.into()))
+        }
+    }
+}
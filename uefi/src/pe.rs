@@ -0,0 +1,789 @@
+// SPDX-License-Identifier: MIT OR Apache-2.0
+
+//! Parsing and loading of PE/COFF images.
+//!
+//! [`PeImage`] parses the headers and the section table of an image file,
+//! so that it can be inspected before it is started. [`PeImage::load`] maps
+//! the image into freshly allocated pages and applies its base relocations
+//! without going through [`boot::load_image`]. This allows images to be
+//! started that the firmware would refuse to load, e.g. unsigned images
+//! that are verified with a custom policy instead of Secure Boot.
+//!
+//! The format is defined in the [PE Format][spec] specification.
+//!
+//! [spec]: https://learn.microsoft.com/en-us/windows/win32/debug/pe-format
+
+use crate::boot::{self, AllocateType, MemoryType, PAGE_SIZE};
+use crate::util::usize_from_u32;
+use crate::{Handle, Status, table};
+use core::fmt::{self, Display, Formatter};
+use core::ptr::NonNull;
+use core::{mem, slice};
+
+/// Magic of the optional header of a PE32 image.
+const PE32_MAGIC: u16 = 0x10b;
+/// Magic of the optional header of a PE32+ image.
+const PE32_PLUS_MAGIC: u16 = 0x20b;
+
+/// Size of a section header.
+const SECTION_HEADER_SIZE: usize = 40;
+
+/// `IMAGE_FILE_RELOCS_STRIPPED`: the image must be loaded at its preferred
+/// base address.
+const RELOCS_STRIPPED: u16 = 0x0001;
+
+/// Relocation that is skipped (`IMAGE_REL_BASED_ABSOLUTE`).
+const REL_BASED_ABSOLUTE: u16 = 0;
+/// Relocation of a 32-bit address (`IMAGE_REL_BASED_HIGHLOW`).
+const REL_BASED_HIGHLOW: u16 = 3;
+/// Relocation of a 64-bit address (`IMAGE_REL_BASED_DIR64`).
+const REL_BASED_DIR64: u16 = 10;
+
+newtype_enum! {
+    /// Target machine of an image (`IMAGE_FILE_MACHINE_*`).
+    pub enum Machine: u16 => {
+        /// Intel 386 or later.
+        I386 = 0x014c,
+        /// ARM Thumb-2.
+        ARM_THUMB = 0x01c2,
+        /// Intel Itanium.
+        IA64 = 0x0200,
+        /// RISC-V 64-bit.
+        RISCV64 = 0x5064,
+        /// LoongArch 64-bit.
+        LOONGARCH64 = 0x6264,
+        /// x64.
+        X86_64 = 0x8664,
+        /// ARM64.
+        AARCH64 = 0xaa64,
+    }
+}
+
+impl Machine {
+    /// Machine of the images that can run on the current target.
+    #[cfg(target_arch = "x86")]
+    pub const NATIVE: Self = Self::I386;
+    /// Machine of the images that can run on the current target.
+    #[cfg(target_arch = "x86_64")]
+    pub const NATIVE: Self = Self::X86_64;
+    /// Machine of the images that can run on the current target.
+    #[cfg(target_arch = "arm")]
+    pub const NATIVE: Self = Self::ARM_THUMB;
+    /// Machine of the images that can run on the current target.
+    #[cfg(target_arch = "aarch64")]
+    pub const NATIVE: Self = Self::AARCH64;
+    /// Machine of the images that can run on the current target.
+    #[cfg(target_arch = "riscv64")]
+    pub const NATIVE: Self = Self::RISCV64;
+    /// Machine of the images that can run on the current target.
+    #[cfg(target_arch = "loongarch64")]
+    pub const NATIVE: Self = Self::LOONGARCH64;
+    /// Machine of the images that can run on the current target.
+    #[cfg(not(any(
+        target_arch = "x86",
+        target_arch = "x86_64",
+        target_arch = "arm",
+        target_arch = "aarch64",
+        target_arch = "riscv64",
+        target_arch = "loongarch64"
+    )))]
+    pub const NATIVE: Self = Self(0);
+}
+
+newtype_enum! {
+    /// Subsystem of an image (`IMAGE_SUBSYSTEM_*`).
+    pub enum Subsystem: u16 => {
+        /// UEFI application.
+        EFI_APPLICATION = 10,
+        /// UEFI boot service driver.
+        EFI_BOOT_SERVICE_DRIVER = 11,
+        /// UEFI runtime driver.
+        EFI_RUNTIME_DRIVER = 12,
+        /// UEFI option ROM.
+        EFI_ROM = 13,
+    }
+}
+
+/// Error of [`PeImage::parse`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ParseError {
+    /// The image doesn't start with a DOS header that points to a PE
+    /// signature.
+    NotPe,
+    /// The magic of the optional header is neither PE32 nor PE32+.
+    UnsupportedFormat(u16),
+    /// A header or a section extends beyond the end of the image.
+    OutOfBounds,
+}
+
+impl Display for ParseError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::NotPe => write!(f, "not a PE image"),
+            Self::UnsupportedFormat(magic) => {
+                write!(f, "unsupported optional header magic {magic:#x}")
+            }
+            Self::OutOfBounds => write!(f, "image is truncated"),
+        }
+    }
+}
+
+impl core::error::Error for ParseError {}
+
+/// Error of [`PeImage::load`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum LoadError {
+    /// The pages for the image could not be allocated.
+    Uefi(crate::Error),
+    /// The image is built for another machine, see [`Machine::NATIVE`].
+    UnsupportedMachine(Machine),
+    /// The image has no base relocations, but could not be loaded at its
+    /// preferred base address.
+    NotRelocatable,
+    /// The image has a base relocation of an unsupported type.
+    UnsupportedRelocation(u16),
+    /// A section, a relocation or the entry point is outside of the image.
+    OutOfBounds,
+}
+
+impl Display for LoadError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Uefi(err) => write!(f, "UEFI error: {err}"),
+            Self::UnsupportedMachine(machine) => {
+                write!(f, "unsupported machine {:#x}", machine.0)
+            }
+            Self::NotRelocatable => write!(f, "image is not relocatable"),
+            Self::UnsupportedRelocation(ty) => write!(f, "unsupported relocation type {ty}"),
+            Self::OutOfBounds => write!(f, "image layout is out of bounds"),
+        }
+    }
+}
+
+impl core::error::Error for LoadError {
+    fn source(&self) -> Option<&(dyn core::error::Error + 'static)> {
+        match self {
+            Self::Uefi(err) => Some(err),
+            _ => None,
+        }
+    }
+}
+
+impl From<crate::Error> for LoadError {
+    fn from(err: crate::Error) -> Self {
+        Self::Uefi(err)
+    }
+}
+
+/// Entry of the data directories of an image.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct DataDirectory {
+    /// Address of the data relative to the image base. For the certificate
+    /// table, this is an offset in the file instead.
+    pub virtual_address: u32,
+    /// Size of the data in bytes.
+    pub size: u32,
+}
+
+impl DataDirectory {
+    /// Index of the certificate table.
+    pub const CERTIFICATE_TABLE: usize = 4;
+    /// Index of the base relocation table.
+    pub const BASE_RELOCATION_TABLE: usize = 5;
+}
+
+/// Section of an image.
+#[derive(Clone, Copy, Debug)]
+pub struct Section<'a> {
+    name: [u8; 8],
+    virtual_size: u32,
+    virtual_address: u32,
+    file_offset: u32,
+    characteristics: u32,
+    data: &'a [u8],
+}
+
+impl<'a> Section<'a> {
+    /// Name of the section, without null padding. Names longer than eight
+    /// bytes are truncated.
+    #[must_use]
+    pub fn name(&self) -> &[u8] {
+        let len = self.name.iter().position(|&b| b == 0).unwrap_or(8);
+        &self.name[..len]
+    }
+
+    /// Size of the section when it is loaded into memory.
+    #[must_use]
+    pub const fn virtual_size(&self) -> u32 {
+        self.virtual_size
+    }
+
+    /// Address of the section relative to the image base.
+    #[must_use]
+    pub const fn virtual_address(&self) -> u32 {
+        self.virtual_address
+    }
+
+    /// Offset of the data of the section in the file.
+    #[must_use]
+    pub const fn file_offset(&self) -> u32 {
+        self.file_offset
+    }
+
+    /// Characteristics of the section (`IMAGE_SCN_*` flags).
+    #[must_use]
+    pub const fn characteristics(&self) -> u32 {
+        self.characteristics
+    }
+
+    /// Data of the section in the file. It is padded with zeros up to the
+    /// [`virtual_size`](Self::virtual_size) when it is loaded.
+    #[must_use]
+    pub const fn data(&self) -> &'a [u8] {
+        self.data
+    }
+}
+
+/// Parsed headers of a PE/COFF image file.
+#[derive(Clone, Copy, Debug)]
+pub struct PeImage<'a> {
+    data: &'a [u8],
+    machine: Machine,
+    characteristics: u16,
+    pe32_plus: bool,
+    optional: usize,
+    entry_point: u32,
+    image_base: u64,
+    size_of_image: u32,
+    size_of_headers: u32,
+    subsystem: Subsystem,
+    directories: usize,
+    directory_count: usize,
+    sections: usize,
+    section_count: usize,
+}
+
+impl<'a> PeImage<'a> {
+    /// Parses the headers of the image file `data`.
+    ///
+    /// # Errors
+    ///
+    /// See [`ParseError`].
+    pub fn parse(data: &'a [u8]) -> Result<Self, ParseError> {
+        if data.get(..2) != Some(b"MZ") {
+            return Err(ParseError::NotPe);
+        }
+        let pe = usize_from_u32(u32_at(data, 0x3c).ok_or(ParseError::NotPe)?);
+        if data.get(pe..pe.checked_add(4).ok_or(ParseError::NotPe)?) != Some(b"PE\0\0") {
+            return Err(ParseError::NotPe);
+        }
+
+        // COFF file header.
+        let coff = pe + 4;
+        let field = |offset| u16_at(data, offset).ok_or(ParseError::OutOfBounds);
+        let machine = Machine(field(coff)?);
+        let section_count = usize::from(field(coff + 2)?);
+        let optional_size = usize::from(field(coff + 16)?);
+        let characteristics = field(coff + 18)?;
+
+        // Optional header.
+        let optional = coff + 20;
+        let magic = field(optional)?;
+        let (pe32_plus, directory_count) = match magic {
+            PE32_MAGIC => (false, optional + 92),
+            PE32_PLUS_MAGIC => (true, optional + 108),
+            _ => return Err(ParseError::UnsupportedFormat(magic)),
+        };
+        let directories = directory_count + 4;
+        let directory_count =
+            usize_from_u32(u32_at(data, directory_count).ok_or(ParseError::OutOfBounds)?);
+        let sections = optional + optional_size;
+        let directories_end = directory_count
+            .checked_mul(8)
+            .and_then(|size| directories.checked_add(size))
+            .ok_or(ParseError::OutOfBounds)?;
+        if directories_end > sections || data.len() < sections + section_count * SECTION_HEADER_SIZE
+        {
+            return Err(ParseError::OutOfBounds);
+        }
+
+        // The fixed fields precede the data directories, which are in
+        // bounds.
+        let image_base = if pe32_plus {
+            u64_at(data, optional + 24).unwrap()
+        } else {
+            u64::from(u32_at(data, optional + 28).unwrap())
+        };
+        let image = Self {
+            data,
+            machine,
+            characteristics,
+            pe32_plus,
+            optional,
+            entry_point: u32_at(data, optional + 16).unwrap(),
+            image_base,
+            size_of_image: u32_at(data, optional + 56).unwrap(),
+            size_of_headers: u32_at(data, optional + 60).unwrap(),
+            subsystem: Subsystem(u16_at(data, optional + 68).unwrap()),
+            directories,
+            directory_count,
+            sections,
+            section_count,
+        };
+        if usize_from_u32(image.size_of_headers) > data.len() {
+            return Err(ParseError::OutOfBounds);
+        }
+        for index in 0..section_count {
+            let header = image.section_header(index);
+            let offset = usize_from_u32(u32_at(header, 20).unwrap());
+            let size = usize_from_u32(u32_at(header, 16).unwrap());
+            if !offset
+                .checked_add(size)
+                .is_some_and(|end| end <= data.len())
+            {
+                return Err(ParseError::OutOfBounds);
+            }
+        }
+        Ok(image)
+    }
+
+    /// Returns the bytes of the image file.
+    #[must_use]
+    pub const fn data(&self) -> &'a [u8] {
+        self.data
+    }
+
+    /// Returns the machine that the image is built for.
+    #[must_use]
+    pub const fn machine(&self) -> Machine {
+        self.machine
+    }
+
+    /// Returns whether the image has the PE32+ format of 64-bit images.
+    #[must_use]
+    pub const fn is_pe32_plus(&self) -> bool {
+        self.pe32_plus
+    }
+
+    /// Returns the subsystem of the image.
+    #[must_use]
+    pub const fn subsystem(&self) -> Subsystem {
+        self.subsystem
+    }
+
+    /// Returns the preferred base address of the image.
+    #[must_use]
+    pub const fn image_base(&self) -> u64 {
+        self.image_base
+    }
+
+    /// Returns the size of the image when it is loaded into memory.
+    #[must_use]
+    pub const fn size_of_image(&self) -> u32 {
+        self.size_of_image
+    }
+
+    /// Returns the size of the headers in the file.
+    #[must_use]
+    pub const fn size_of_headers(&self) -> u32 {
+        self.size_of_headers
+    }
+
+    /// Returns the address of the entry point relative to the image base.
+    #[must_use]
+    pub const fn entry_point(&self) -> u32 {
+        self.entry_point
+    }
+
+    /// Returns the data directory `index`, or `None` if the image doesn't
+    /// have it. See the constants of [`DataDirectory`] for the indices.
+    #[must_use]
+    pub fn data_directory(&self, index: usize) -> Option<DataDirectory> {
+        let offset = self.data_directory_offset(index)?;
+        Some(DataDirectory {
+            virtual_address: u32_at(self.data, offset)?,
+            size: u32_at(self.data, offset + 4)?,
+        })
+    }
+
+    /// Returns the sections of the image, in the order of the section table.
+    pub fn sections(&self) -> impl Iterator<Item = Section<'a>> + '_ {
+        (0..self.section_count).map(|index| {
+            let header = self.section_header(index);
+            let field = |offset| u32_at(header, offset).unwrap();
+            let file_offset = field(20);
+            let start = usize_from_u32(file_offset);
+            Section {
+                name: header[..8].try_into().unwrap(),
+                virtual_size: field(8),
+                virtual_address: field(12),
+                file_offset,
+                characteristics: field(36),
+                data: &self.data[start..start + usize_from_u32(field(16))],
+            }
+        })
+    }
+
+    /// Returns the file offset of the checksum in the optional header.
+    pub(crate) const fn checksum_offset(&self) -> usize {
+        self.optional + 64
+    }
+
+    /// Returns the file offset of the data directory `index`.
+    pub(crate) const fn data_directory_offset(&self, index: usize) -> Option<usize> {
+        if index < self.directory_count {
+            Some(self.directories + index * 8)
+        } else {
+            None
+        }
+    }
+
+    /// Returns the header of section `index`, which is in bounds.
+    fn section_header(&self, index: usize) -> &'a [u8] {
+        let start = self.sections + index * SECTION_HEADER_SIZE;
+        &self.data[start..start + SECTION_HEADER_SIZE]
+    }
+
+    /// Maps the image into freshly allocated pages, and applies its base
+    /// relocations.
+    ///
+    /// The pages are of type [`MemoryType::LOADER_CODE`], or of the code
+    /// type of boot service or runtime drivers. They are freed when the
+    /// returned [`MappedImage`] is dropped.
+    ///
+    /// The image is not verified in any way, e.g. it's not checked against
+    /// the Secure Boot databases.
+    ///
+    /// # Errors
+    ///
+    /// See [`LoadError`].
+    pub fn load(&self) -> Result<MappedImage, LoadError> {
+        if self.machine != Machine::NATIVE {
+            return Err(LoadError::UnsupportedMachine(self.machine));
+        }
+        let size = usize_from_u32(self.size_of_image);
+        let headers = usize_from_u32(self.size_of_headers);
+        let entry_point = usize_from_u32(self.entry_point);
+        if headers > size || entry_point >= size {
+            return Err(LoadError::OutOfBounds);
+        }
+
+        let memory_type = match self.subsystem {
+            Subsystem::EFI_BOOT_SERVICE_DRIVER => MemoryType::BOOT_SERVICES_CODE,
+            Subsystem::EFI_RUNTIME_DRIVER => MemoryType::RUNTIME_SERVICES_CODE,
+            _ => MemoryType::LOADER_CODE,
+        };
+        let page_count = size.div_ceil(PAGE_SIZE);
+        let base = boot::allocate_pages(AllocateType::AnyPages, memory_type, page_count)?;
+        // Frees the pages if loading fails.
+        let image = MappedImage {
+            base,
+            page_count,
+            size,
+            entry_point,
+        };
+
+        // Safety: the pages are allocated for the image, and not used
+        // elsewhere.
+        let memory = unsafe { slice::from_raw_parts_mut(base.as_ptr(), size) };
+        memory.fill(0);
+        memory[..headers].copy_from_slice(&self.data[..headers]);
+        for section in self.sections() {
+            let data = section.data();
+            let len = match section.virtual_size() {
+                0 => data.len(),
+                virtual_size => data.len().min(usize_from_u32(virtual_size)),
+            };
+            let start = usize_from_u32(section.virtual_address());
+            memory
+                .get_mut(start..)
+                .and_then(|memory| memory.get_mut(..len))
+                .ok_or(LoadError::OutOfBounds)?
+                .copy_from_slice(&data[..len]);
+        }
+
+        let delta = (base.as_ptr() as u64).wrapping_sub(self.image_base);
+        if delta != 0 {
+            match self.data_directory(DataDirectory::BASE_RELOCATION_TABLE) {
+                Some(table) if table.size != 0 && self.characteristics & RELOCS_STRIPPED == 0 => {
+                    apply_relocations(memory, table, delta)?;
+                }
+                _ => return Err(LoadError::NotRelocatable),
+            }
+        }
+        Ok(image)
+    }
+}
+
+/// Adds `delta` to the addresses that the base relocation `table` of the
+/// loaded image `memory` refers to.
+fn apply_relocations(memory: &mut [u8], table: DataDirectory, delta: u64) -> Result<(), LoadError> {
+    let mut offset = usize_from_u32(table.virtual_address);
+    let end = offset
+        .checked_add(usize_from_u32(table.size))
+        .filter(|&end| end <= memory.len())
+        .ok_or(LoadError::OutOfBounds)?;
+
+    // Each block relocates the addresses in one page.
+    while offset + 8 <= end {
+        let page = usize_from_u32(u32_at(memory, offset).unwrap());
+        let block_size = usize_from_u32(u32_at(memory, offset + 4).unwrap());
+        if block_size < 8 || block_size > end - offset {
+            return Err(LoadError::OutOfBounds);
+        }
+        for index in 0..(block_size - 8) / 2 {
+            let entry = u16_at(memory, offset + 8 + index * 2).unwrap();
+            let target = page + usize::from(entry & 0xfff);
+            match entry >> 12 {
+                REL_BASED_ABSOLUTE => {}
+                REL_BASED_HIGHLOW => {
+                    let value = u32_at(memory, target).ok_or(LoadError::OutOfBounds)?;
+                    let value = value.wrapping_add(delta as u32);
+                    memory[target..target + 4].copy_from_slice(&value.to_le_bytes());
+                }
+                REL_BASED_DIR64 => {
+                    let value = u64_at(memory, target).ok_or(LoadError::OutOfBounds)?;
+                    let value = value.wrapping_add(delta);
+                    memory[target..target + 8].copy_from_slice(&value.to_le_bytes());
+                }
+                ty => return Err(LoadError::UnsupportedRelocation(ty)),
+            }
+        }
+        offset += block_size;
+    }
+    Ok(())
+}
+
+/// Image that has been loaded into memory by [`PeImage::load`].
+///
+/// The pages of the image are freed when it is dropped. To keep a driver
+/// resident after it has been started, use [`mem::forget`].
+#[derive(Debug)]
+pub struct MappedImage {
+    base: NonNull<u8>,
+    page_count: usize,
+    size: usize,
+    entry_point: usize,
+}
+
+impl MappedImage {
+    /// Returns the address at which the image has been loaded.
+    #[must_use]
+    pub const fn as_ptr(&self) -> *const u8 {
+        self.base.as_ptr()
+    }
+
+    /// Returns the bytes of the loaded image.
+    #[must_use]
+    pub const fn as_slice(&self) -> &[u8] {
+        unsafe { slice::from_raw_parts(self.base.as_ptr(), self.size) }
+    }
+
+    /// Returns the address of the entry point.
+    #[must_use]
+    pub const fn entry_point(&self) -> *const u8 {
+        unsafe { self.base.as_ptr().add(self.entry_point) }
+    }
+
+    /// Calls the entry point of the image with `image_handle` and the system
+    /// table, and returns the status that it returns.
+    ///
+    /// # Safety
+    ///
+    /// The image must be trusted, since it runs with the privileges of the
+    /// caller. It must be a UEFI image, and `image_handle` must be suitable
+    /// for it: the image typically opens the [`LoadedImage`] protocol on its
+    /// handle, which describes the caller when the handle of the caller is
+    /// passed.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the system table has not been set.
+    ///
+    /// [`LoadedImage`]: crate::proto::loaded_image::LoadedImage
+    pub unsafe fn start(&self, image_handle: Handle) -> Status {
+        let system_table = table::system_table_raw().expect("system table is not set");
+        // Safety: the entry point of a UEFI image has this signature.
+        let entry: extern "efiapi" fn(
+            uefi_raw::Handle,
+            *mut uefi_raw::table::system::SystemTable,
+        ) -> Status = unsafe { mem::transmute(self.entry_point()) };
+        entry(image_handle.as_ptr(), system_table.as_ptr())
+    }
+}
+
+impl Drop for MappedImage {
+    fn drop(&mut self) {
+        // Ignore errors, since we can't propagate them from `drop`.
+        let _ = unsafe { boot::free_pages(self.base, self.page_count) };
+    }
+}
+
+fn u16_at(bytes: &[u8], offset: usize) -> Option<u16> {
+    let bytes = bytes.get(offset..offset.checked_add(2)?)?;
+    Some(u16::from_le_bytes(bytes.try_into().ok()?))
+}
+
+fn u32_at(bytes: &[u8], offset: usize) -> Option<u32> {
+    let bytes = bytes.get(offset..offset.checked_add(4)?)?;
+    Some(u32::from_le_bytes(bytes.try_into().ok()?))
+}
+
+fn u64_at(bytes: &[u8], offset: usize) -> Option<u64> {
+    let bytes = bytes.get(offset..offset.checked_add(8)?)?;
+    Some(u64::from_le_bytes(bytes.try_into().ok()?))
+}
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use super::*;
+    use alloc::vec::Vec;
+
+    pub(crate) const OPTIONAL: usize = 0x40 + 24;
+    pub(crate) const CHECKSUM: usize = OPTIONAL + 64;
+    pub(crate) const CERTIFICATE_ENTRY: usize = OPTIONAL + 112 + 32;
+
+    /// Builds a PE32+ image with the headers in `0..0x200`, two sections in
+    /// `0x200..0x400` and `0x400..0x500` that are listed in reverse order,
+    /// extra data in `0x500..0x510`, and a certificate table in
+    /// `0x510..0x530`.
+    pub(crate) fn image() -> Vec<u8> {
+        let mut image: Vec<u8> = (0..0x530u32).map(|i| (i * 7 % 251) as u8).collect();
+        let mut put = |offset: usize, bytes: &[u8]| {
+            image[offset..offset + bytes.len()].copy_from_slice(bytes);
+        };
+        put(0, b"MZ");
+        put(0x3c, &0x40u32.to_le_bytes());
+        put(0x40, b"PE\0\0");
+        // Machine, NumberOfSections, SizeOfOptionalHeader and
+        // Characteristics.
+        put(0x44, &Machine::X86_64.0.to_le_bytes());
+        put(0x44 + 2, &2u16.to_le_bytes());
+        put(0x44 + 16, &240u16.to_le_bytes());
+        put(0x44 + 18, &0x22u16.to_le_bytes());
+        put(OPTIONAL, &PE32_PLUS_MAGIC.to_le_bytes());
+        // AddressOfEntryPoint, ImageBase, SizeOfImage, SizeOfHeaders and
+        // Subsystem.
+        put(OPTIONAL + 16, &0x1010u32.to_le_bytes());
+        put(OPTIONAL + 24, &0x1_4000_0000u64.to_le_bytes());
+        put(OPTIONAL + 56, &0x2000u32.to_le_bytes());
+        put(OPTIONAL + 60, &0x200u32.to_le_bytes());
+        put(OPTIONAL + 68, &Subsystem::EFI_APPLICATION.0.to_le_bytes());
+        // NumberOfRvaAndSizes, the certificate table and the base
+        // relocation table.
+        put(OPTIONAL + 108, &16u32.to_le_bytes());
+        put(CERTIFICATE_ENTRY, &0x510u32.to_le_bytes());
+        put(CERTIFICATE_ENTRY + 4, &0x20u32.to_le_bytes());
+        put(CERTIFICATE_ENTRY + 8, &0x1400u32.to_le_bytes());
+        put(CERTIFICATE_ENTRY + 12, &0x10u32.to_le_bytes());
+        // Section headers.
+        let sections = OPTIONAL + 240;
+        put(sections, b".reloc\0\0");
+        put(sections + 8, &0x10u32.to_le_bytes());
+        put(sections + 12, &0x1400u32.to_le_bytes());
+        put(sections + 16, &0x100u32.to_le_bytes());
+        put(sections + 20, &0x400u32.to_le_bytes());
+        put(sections + 36, &0x4200_0040u32.to_le_bytes());
+        put(sections + 40, b".text\0\0\0");
+        put(sections + 40 + 8, &0x200u32.to_le_bytes());
+        put(sections + 40 + 12, &0x1000u32.to_le_bytes());
+        put(sections + 40 + 16, &0x200u32.to_le_bytes());
+        put(sections + 40 + 20, &0x200u32.to_le_bytes());
+        put(sections + 40 + 36, &0x6000_0020u32.to_le_bytes());
+        image
+    }
+
+    #[test]
+    fn test_parse() {
+        let data = image();
+        let image = PeImage::parse(&data).unwrap();
+        assert_eq!(image.machine(), Machine::X86_64);
+        assert!(image.is_pe32_plus());
+        assert_eq!(image.subsystem(), Subsystem::EFI_APPLICATION);
+        assert_eq!(image.image_base(), 0x1_4000_0000);
+        assert_eq!(image.size_of_image(), 0x2000);
+        assert_eq!(image.size_of_headers(), 0x200);
+        assert_eq!(image.entry_point(), 0x1010);
+        assert_eq!(
+            image.data_directory(DataDirectory::BASE_RELOCATION_TABLE),
+            Some(DataDirectory {
+                virtual_address: 0x1400,
+                size: 0x10
+            })
+        );
+        assert_eq!(image.data_directory(16), None);
+
+        let sections: Vec<Section<'_>> = image.sections().collect();
+        assert_eq!(sections.len(), 2);
+        assert_eq!(sections[0].name(), b".reloc");
+        assert_eq!(sections[0].virtual_address(), 0x1400);
+        assert_eq!(sections[0].data(), &data[0x400..0x500]);
+        assert_eq!(sections[1].name(), b".text");
+        assert_eq!(sections[1].file_offset(), 0x200);
+        assert_eq!(sections[1].characteristics(), 0x6000_0020);
+    }
+
+    #[test]
+    fn test_parse_errors() {
+        let data = image();
+        assert_eq!(PeImage::parse(&data[1..]).err(), Some(ParseError::NotPe));
+
+        let mut pe32 = data.clone();
+        pe32[OPTIONAL] = 0x07;
+        pe32[OPTIONAL + 1] = 0x01;
+        assert_eq!(
+            PeImage::parse(&pe32).err(),
+            Some(ParseError::UnsupportedFormat(0x107))
+        );
+
+        // The first section extends beyond the end of the image.
+        assert_eq!(
+            PeImage::parse(&data[..0x480]).err(),
+            Some(ParseError::OutOfBounds)
+        );
+        // The section table is truncated.
+        assert_eq!(
+            PeImage::parse(&data[..0x150]).err(),
+            Some(ParseError::OutOfBounds)
+        );
+    }
+
+    #[test]
+    fn test_apply_relocations() {
+        let mut memory = [0u8; 0x40];
+        // One block for page 0, with a 64-bit relocation at 0x10, a 32-bit
+        // relocation at 0x18, and padding.
+        memory[0x20..0x24].copy_from_slice(&0u32.to_le_bytes());
+        memory[0x24..0x28].copy_from_slice(&14u32.to_le_bytes());
+        memory[0x28..0x2a].copy_from_slice(&0xa010u16.to_le_bytes());
+        memory[0x2a..0x2c].copy_from_slice(&0x3018u16.to_le_bytes());
+        memory[0x2c..0x2e].copy_from_slice(&0u16.to_le_bytes());
+        memory[0x10..0x18].copy_from_slice(&0x1_4000_1000u64.to_le_bytes());
+        memory[0x18..0x1c].copy_from_slice(&0x4000_2000u32.to_le_bytes());
+
+        let table = DataDirectory {
+            virtual_address: 0x20,
+            size: 14,
+        };
+        apply_relocations(&mut memory, table, 0x10_0000).unwrap();
+        assert_eq!(u64_at(&memory, 0x10), Some(0x1_4010_1000));
+        assert_eq!(u32_at(&memory, 0x18), Some(0x4010_2000));
+
+        // Unsupported relocation type.
+        memory[0x2c..0x2e].copy_from_slice(&0x5000u16.to_le_bytes());
+        assert_eq!(
+            apply_relocations(&mut memory, table, 0x10_0000),
+            Err(LoadError::UnsupportedRelocation(5))
+        );
+
+        // The table extends beyond the image.
+        let table = DataDirectory {
+            virtual_address: 0x30,
+            size: 0x20,
+        };
+        assert_eq!(
+            apply_relocations(&mut memory, table, 0x10_0000),
+            Err(LoadError::OutOfBounds)
+        );
+    }
+}
@@ -0,0 +1,545 @@
+// SPDX-License-Identifier: MIT OR Apache-2.0
+
+//! Typed helpers for the IDENTIFY DEVICE and SMART commands.
+//!
+//! The commands are available as methods of [`AtaDevice`], and return the
+//! decoded [`IdentifyData`] and [`SmartData`].
+//!
+//! # Example
+//!
+//! ```no_run
+//! use uefi::proto::ata::pass_thru::AtaDevice;
+//!
+//! fn print_health(device: &mut AtaDevice) -> uefi::Result {
+//!     let identify = device.identify()?;
+//!     log::info!("{} ({})", identify.model_number(), identify.serial_number());
+//!     if identify.features().contains(uefi::proto::ata::command::AtaFeatures::SMART_ENABLED) {
+//!         let smart = device.smart_read_data()?;
+//!         for attribute in smart.attributes() {
+//!             log::info!("{:?}: {}", attribute.name(), attribute.raw);
+//!         }
+//!     }
+//!     Ok(())
+//! }
+//! ```
+
+use super::AtaRequestBuilder;
+use super::pass_thru::AtaDevice;
+use crate::{Result, Status};
+use alloc::string::String;
+use alloc::vec::Vec;
+
+/// Size of the data returned by IDENTIFY DEVICE and the SMART commands.
+const SECTOR_SIZE: usize = 512;
+
+/// Opcode of IDENTIFY DEVICE.
+const IDENTIFY_DEVICE: u8 = 0xec;
+/// Opcode of the SMART commands.
+const SMART: u8 = 0xb0;
+/// Feature of SMART READ DATA.
+const SMART_READ_DATA: u8 = 0xd0;
+/// Feature of SMART READ ATTRIBUTE THRESHOLDS.
+const SMART_READ_THRESHOLDS: u8 = 0xd1;
+/// Feature of SMART READ LOG.
+const SMART_READ_LOG: u8 = 0xd5;
+/// Values of the cylinder registers that are required by all SMART commands.
+const SMART_CYLINDER: (u8, u8) = (0x4f, 0xc2);
+
+bitflags::bitflags! {
+    /// Features reported by [`IdentifyData`].
+    #[derive(Clone, Copy, Debug, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+    #[repr(transparent)]
+    pub struct AtaFeatures: u32 {
+        /// LBA addressing is supported.
+        const LBA = 1 << 0;
+        /// DMA transfers are supported.
+        const DMA = 1 << 1;
+        /// 48-bit LBA addressing is supported.
+        const LBA48 = 1 << 2;
+        /// The SMART feature set is supported.
+        const SMART = 1 << 3;
+        /// The SMART feature set is enabled.
+        const SMART_ENABLED = 1 << 4;
+        /// The Security feature set is supported.
+        const SECURITY = 1 << 5;
+        /// A volatile write cache is supported.
+        const WRITE_CACHE = 1 << 6;
+        /// The volatile write cache is enabled.
+        const WRITE_CACHE_ENABLED = 1 << 7;
+        /// Native command queuing is supported.
+        const NCQ = 1 << 8;
+        /// DATA SET MANAGEMENT with the TRIM bit is supported.
+        const TRIM = 1 << 9;
+        /// The device has a world wide name.
+        const WORLD_WIDE_NAME = 1 << 10;
+    }
+}
+
+/// Data returned by IDENTIFY DEVICE.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct IdentifyData {
+    words: [u16; 256],
+}
+
+impl IdentifyData {
+    /// Parses the data returned by IDENTIFY DEVICE. Returns `None` if `data`
+    /// is shorter than 512 bytes.
+    #[must_use]
+    pub fn parse(data: &[u8]) -> Option<Self> {
+        let data = data.get(..SECTOR_SIZE)?;
+        let mut words = [0; 256];
+        for (word, bytes) in words.iter_mut().zip(data.chunks_exact(2)) {
+            *word = u16::from_le_bytes([bytes[0], bytes[1]]);
+        }
+        Some(Self { words })
+    }
+
+    /// The raw data as 256 words.
+    #[must_use]
+    pub const fn words(&self) -> &[u16; 256] {
+        &self.words
+    }
+
+    /// Decodes an ATA string, which stores two characters per word with the
+    /// first one in the high byte.
+    fn string(&self, words: core::ops::Range<usize>) -> String {
+        let bytes = self.words[words]
+            .iter()
+            .flat_map(|word| word.to_be_bytes())
+            .collect::<Vec<_>>();
+        String::from_utf8_lossy(&bytes).trim().into()
+    }
+
+    const fn bit(&self, word: usize, bit: u32) -> bool {
+        self.words[word] & (1 << bit) != 0
+    }
+
+    /// Returns whether a word that reports supported or enabled features
+    /// is valid, which is the case if bit 14 is set and bit 15 is clear.
+    const fn is_valid(&self, word: usize) -> bool {
+        self.words[word] & 0xc000 == 0x4000
+    }
+
+    /// Serial number.
+    #[must_use]
+    pub fn serial_number(&self) -> String {
+        self.string(10..20)
+    }
+
+    /// Firmware revision.
+    #[must_use]
+    pub fn firmware_revision(&self) -> String {
+        self.string(23..27)
+    }
+
+    /// Model number.
+    #[must_use]
+    pub fn model_number(&self) -> String {
+        self.string(27..47)
+    }
+
+    /// Supported features.
+    #[must_use]
+    pub fn features(&self) -> AtaFeatures {
+        let mut features = AtaFeatures::empty();
+        let mut set = |flag, value| features.set(flag, value);
+        set(AtaFeatures::LBA, self.bit(49, 9));
+        set(AtaFeatures::DMA, self.bit(49, 8));
+        if self.is_valid(83) {
+            set(AtaFeatures::LBA48, self.bit(83, 10));
+        }
+        if self.is_valid(82) {
+            set(AtaFeatures::SMART, self.bit(82, 0));
+            set(AtaFeatures::SECURITY, self.bit(82, 1));
+            set(AtaFeatures::WRITE_CACHE, self.bit(82, 5));
+        }
+        if self.is_valid(87) {
+            set(AtaFeatures::SMART_ENABLED, self.bit(85, 0));
+            set(AtaFeatures::WRITE_CACHE_ENABLED, self.bit(85, 5));
+            set(AtaFeatures::WORLD_WIDE_NAME, self.bit(87, 8));
+        }
+        // Word 76 is only valid for SATA devices, which never report 0xffff.
+        if self.words[76] != 0xffff {
+            set(AtaFeatures::NCQ, self.bit(76, 8));
+        }
+        set(AtaFeatures::TRIM, self.bit(169, 0));
+        features
+    }
+
+    /// Number of addressable logical sectors.
+    #[must_use]
+    pub fn sector_count(&self) -> u64 {
+        if self.features().contains(AtaFeatures::LBA48) {
+            self.words[100] as u64
+                | (self.words[101] as u64) << 16
+                | (self.words[102] as u64) << 32
+                | (self.words[103] as u64) << 48
+        } else {
+            self.words[60] as u64 | (self.words[61] as u64) << 16
+        }
+    }
+
+    /// Size of a logical sector in bytes.
+    #[must_use]
+    pub const fn logical_sector_size(&self) -> u32 {
+        if self.is_valid(106) && self.bit(106, 12) {
+            // Size in words.
+            (self.words[117] as u32 | (self.words[118] as u32) << 16) * 2
+        } else {
+            512
+        }
+    }
+
+    /// Size of a physical sector in bytes.
+    #[must_use]
+    pub const fn physical_sector_size(&self) -> u32 {
+        let exponent = if self.is_valid(106) && self.bit(106, 13) {
+            self.words[106] & 0x0f
+        } else {
+            0
+        };
+        self.logical_sector_size() << exponent
+    }
+
+    /// Capacity of the device in bytes.
+    #[must_use]
+    pub fn capacity(&self) -> u64 {
+        self.sector_count() * self.logical_sector_size() as u64
+    }
+
+    /// Nominal media rotation rate in RPM. Returns `Some(0)` for non-rotating
+    /// media like SSDs, and `None` if not reported.
+    #[must_use]
+    pub const fn rotation_rate(&self) -> Option<u16> {
+        match self.words[217] {
+            1 => Some(0),
+            rpm @ 0x0401..=0xfffe => Some(rpm),
+            _ => None,
+        }
+    }
+
+    /// World wide name, if reported.
+    #[must_use]
+    pub fn world_wide_name(&self) -> Option<u64> {
+        if !self.features().contains(AtaFeatures::WORLD_WIDE_NAME) {
+            return None;
+        }
+        Some(
+            (self.words[108] as u64) << 48
+                | (self.words[109] as u64) << 32
+                | (self.words[110] as u64) << 16
+                | self.words[111] as u64,
+        )
+    }
+}
+
+/// A SMART attribute of [`SmartData`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct SmartAttribute {
+    /// Attribute ID.
+    pub id: u8,
+    /// Status flags.
+    pub flags: u16,
+    /// Current normalized value, usually from 1 to 253. Lower is worse.
+    pub current: u8,
+    /// Worst normalized value ever recorded.
+    pub worst: u8,
+    /// Raw 48-bit value, whose interpretation is vendor specific.
+    pub raw: u64,
+    /// Threshold below which the attribute is failing, if known. See
+    /// [`SmartData::apply_thresholds`].
+    pub threshold: Option<u8>,
+}
+
+impl SmartAttribute {
+    /// Returns whether a failing attribute predicts an imminent failure of
+    /// the device, rather than being an indicator of age.
+    #[must_use]
+    pub const fn is_prefailure(&self) -> bool {
+        self.flags & 0x01 != 0
+    }
+
+    /// Returns whether the attribute is failing, i.e. its current value is
+    /// at or below a nonzero threshold.
+    #[must_use]
+    pub const fn is_failing(&self) -> bool {
+        match self.threshold {
+            Some(threshold) => threshold != 0 && self.current <= threshold,
+            None => false,
+        }
+    }
+
+    /// Conventional name of well-known attributes.
+    #[must_use]
+    pub const fn name(&self) -> Option<&'static str> {
+        Some(match self.id {
+            0x01 => "Raw Read Error Rate",
+            0x03 => "Spin-Up Time",
+            0x04 => "Start/Stop Count",
+            0x05 => "Reallocated Sectors Count",
+            0x07 => "Seek Error Rate",
+            0x09 => "Power-On Hours",
+            0x0a => "Spin Retry Count",
+            0x0c => "Power Cycle Count",
+            0xbb => "Reported Uncorrectable Errors",
+            0xbe => "Airflow Temperature",
+            0xc2 => "Temperature",
+            0xc4 => "Reallocation Event Count",
+            0xc5 => "Current Pending Sector Count",
+            0xc6 => "Offline Uncorrectable Sector Count",
+            0xc7 => "UDMA CRC Error Count",
+            _ => return None,
+        })
+    }
+}
+
+/// Data returned by SMART READ DATA.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct SmartData {
+    /// Revision of the data structure.
+    pub revision: u16,
+    /// Status of the offline data collection.
+    pub offline_collection_status: u8,
+    /// Status of the last self-test. The upper four bits are `0` if the
+    /// test passed and `0xf` if it is still running.
+    pub self_test_status: u8,
+    attributes: Vec<SmartAttribute>,
+}
+
+impl SmartData {
+    /// Parses the data returned by SMART READ DATA. Returns `None` if `data`
+    /// is shorter than 512 bytes or its checksum is invalid.
+    #[must_use]
+    pub fn parse(data: &[u8]) -> Option<Self> {
+        let data = data.get(..SECTOR_SIZE)?;
+        if !checksum_valid(data) {
+            return None;
+        }
+        let attributes = data[2..362]
+            .chunks_exact(12)
+            .filter(|raw| raw[0] != 0)
+            .map(|raw| {
+                let mut value = [0; 8];
+                value[..6].copy_from_slice(&raw[5..11]);
+                SmartAttribute {
+                    id: raw[0],
+                    flags: u16::from_le_bytes([raw[1], raw[2]]),
+                    current: raw[3],
+                    worst: raw[4],
+                    raw: u64::from_le_bytes(value),
+                    threshold: None,
+                }
+            })
+            .collect();
+        Some(Self {
+            revision: u16::from_le_bytes([data[0], data[1]]),
+            offline_collection_status: data[362],
+            self_test_status: data[363],
+            attributes,
+        })
+    }
+
+    /// Sets the thresholds of the attributes from the data returned by
+    /// SMART READ ATTRIBUTE THRESHOLDS. Does nothing if `data` is shorter
+    /// than 512 bytes or its checksum is invalid.
+    pub fn apply_thresholds(&mut self, data: &[u8]) {
+        let Some(data) = data.get(..SECTOR_SIZE) else {
+            return;
+        };
+        if !checksum_valid(data) {
+            return;
+        }
+        for raw in data[2..362].chunks_exact(12).filter(|raw| raw[0] != 0) {
+            if let Some(attribute) = self.attributes.iter_mut().find(|a| a.id == raw[0]) {
+                attribute.threshold = Some(raw[1]);
+            }
+        }
+    }
+
+    /// All attributes reported by the device.
+    #[must_use]
+    pub fn attributes(&self) -> &[SmartAttribute] {
+        &self.attributes
+    }
+
+    /// Returns the attribute with the given ID.
+    #[must_use]
+    pub fn attribute(&self, id: u8) -> Option<&SmartAttribute> {
+        self.attributes.iter().find(|attribute| attribute.id == id)
+    }
+
+    /// Current temperature in degrees Celsius, taken from the attribute
+    /// `0xc2` or `0xbe`.
+    #[must_use]
+    pub fn temperature(&self) -> Option<u8> {
+        self.attribute(0xc2)
+            .or_else(|| self.attribute(0xbe))
+            .map(|attribute| attribute.raw as u8)
+    }
+}
+
+/// Returns whether the checksum in the last byte of a SMART data structure
+/// is valid. A checksum of zero means that it is not implemented.
+fn checksum_valid(data: &[u8]) -> bool {
+    data[511] == 0 || data.iter().fold(0u8, |sum, b| sum.wrapping_add(*b)) == 0
+}
+
+impl AtaDevice<'_> {
+    /// Sends IDENTIFY DEVICE.
+    ///
+    /// # Errors
+    /// See [`execute_command`](Self::execute_command).
+    pub fn identify(&mut self) -> Result<IdentifyData> {
+        let request = AtaRequestBuilder::read_pio(self.io_align(), IDENTIFY_DEVICE)
+            .and_then(|builder| builder.with_read_buffer(SECTOR_SIZE))
+            .map_err(|_| Status::OUT_OF_RESOURCES)?
+            .build();
+        let response = self.execute_command(request)?;
+        response
+            .read_buffer()
+            .and_then(IdentifyData::parse)
+            .ok_or_else(|| Status::DEVICE_ERROR.into())
+    }
+
+    /// Sends a SMART command that reads `sectors` sectors.
+    fn smart_read(&mut self, feature: u8, address: u8, sectors: u8) -> Result<Vec<u8>> {
+        let request = AtaRequestBuilder::read_pio(self.io_align(), SMART)
+            .and_then(|builder| {
+                builder
+                    .with_features(feature)
+                    .with_sector_count(sectors)
+                    .with_sector_number(address)
+                    .with_cylinder(SMART_CYLINDER.0, SMART_CYLINDER.1)
+                    .with_read_buffer(usize::from(sectors) * SECTOR_SIZE)
+            })
+            .map_err(|_| Status::OUT_OF_RESOURCES)?
+            .build();
+        let response = self.execute_command(request)?;
+        Ok(response.read_buffer().unwrap_or_default().to_vec())
+    }
+
+    /// Sends SMART READ DATA and SMART READ ATTRIBUTE THRESHOLDS, and
+    /// returns the attributes with their thresholds. The thresholds are
+    /// left empty if the device doesn't support reading them.
+    ///
+    /// # Errors
+    /// * [`Status::DEVICE_ERROR`] if the returned data is invalid.
+    /// * See [`execute_command`](Self::execute_command) for the other
+    ///   errors.
+    pub fn smart_read_data(&mut self) -> Result<SmartData> {
+        let data = self.smart_read(SMART_READ_DATA, 0, 1)?;
+        let mut smart = SmartData::parse(&data).ok_or(Status::DEVICE_ERROR)?;
+        if let Ok(thresholds) = self.smart_read(SMART_READ_THRESHOLDS, 0, 1) {
+            smart.apply_thresholds(&thresholds);
+        }
+        Ok(smart)
+    }
+
+    /// Sends SMART READ LOG to read `sectors` sectors of the log at
+    /// `address`, e.g. `0x00` for the log directory or `0x06` for the
+    /// self-test log.
+    ///
+    /// # Errors
+    /// See [`execute_command`](Self::execute_command).
+    pub fn smart_read_log(&mut self, address: u8, sectors: u8) -> Result<Vec<u8>> {
+        if sectors == 0 {
+            return Err(Status::INVALID_PARAMETER.into());
+        }
+        self.smart_read(SMART_READ_LOG, address, sectors)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloc::vec;
+
+    fn set_string(data: &mut [u8], offset: usize, s: &[u8]) {
+        for (i, pair) in s.chunks(2).enumerate() {
+            data[offset + 2 * i] = *pair.get(1).unwrap_or(&b' ');
+            data[offset + 2 * i + 1] = pair[0];
+        }
+    }
+
+    fn set_word(data: &mut [u8], word: usize, value: u16) {
+        data[2 * word..2 * word + 2].copy_from_slice(&value.to_le_bytes());
+    }
+
+    #[test]
+    fn test_identify() {
+        let mut data = vec![0; 512];
+        data[20..60].fill(b' ');
+        data[46..94].fill(b' ');
+        set_string(&mut data, 20, b"AtaPassThru");
+        set_string(&mut data, 46, b"2.5+");
+        set_string(&mut data, 54, b"QEMU HARDDISK");
+        set_word(&mut data, 49, 0x0300);
+        set_word(&mut data, 82, 0x4021);
+        set_word(&mut data, 83, 0x4400);
+        set_word(&mut data, 85, 0x0001);
+        set_word(&mut data, 87, 0x4000);
+        set_word(&mut data, 100, 0x5000);
+        set_word(&mut data, 106, 0x6003);
+        set_word(&mut data, 217, 1);
+
+        let identify = IdentifyData::parse(&data).unwrap();
+        assert_eq!(identify.serial_number(), "AtaPassThru");
+        assert_eq!(identify.firmware_revision(), "2.5+");
+        assert_eq!(identify.model_number(), "QEMU HARDDISK");
+        assert_eq!(
+            identify.features(),
+            AtaFeatures::LBA
+                | AtaFeatures::DMA
+                | AtaFeatures::LBA48
+                | AtaFeatures::SMART
+                | AtaFeatures::SMART_ENABLED
+                | AtaFeatures::WRITE_CACHE
+        );
+        assert_eq!(identify.sector_count(), 0x5000);
+        assert_eq!(identify.logical_sector_size(), 512);
+        assert_eq!(identify.physical_sector_size(), 4096);
+        assert_eq!(identify.capacity(), 0x5000 * 512);
+        assert_eq!(identify.rotation_rate(), Some(0));
+        assert_eq!(identify.world_wide_name(), None);
+    }
+
+    fn smart_sector(entries: &[[u8; 12]]) -> Vec<u8> {
+        let mut data = vec![0; 512];
+        data[0] = 0x10;
+        for (i, entry) in entries.iter().enumerate() {
+            data[2 + 12 * i..14 + 12 * i].copy_from_slice(entry);
+        }
+        let sum = data.iter().fold(0u8, |sum, b| sum.wrapping_add(*b));
+        data[511] = 0u8.wrapping_sub(sum);
+        data
+    }
+
+    #[test]
+    fn test_smart_data() {
+        let data = smart_sector(&[
+            [0x05, 0x33, 0, 100, 100, 2, 0, 0, 0, 0, 0, 0],
+            [0x09, 0x32, 0, 99, 99, 0x10, 0x27, 0, 0, 0, 0, 0],
+            [0xc2, 0x22, 0, 35, 40, 35, 0, 0, 0, 0, 0, 0],
+        ]);
+        let mut smart = SmartData::parse(&data).unwrap();
+        assert_eq!(smart.revision, 0x10);
+        assert_eq!(smart.attributes().len(), 3);
+        let reallocated = smart.attribute(0x05).unwrap();
+        assert!(reallocated.is_prefailure());
+        assert_eq!(reallocated.raw, 2);
+        assert_eq!(reallocated.name(), Some("Reallocated Sectors Count"));
+        assert_eq!(smart.attribute(0x09).unwrap().raw, 10000);
+        assert_eq!(smart.temperature(), Some(35));
+
+        let thresholds = smart_sector(&[[0x05, 100, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0]]);
+        smart.apply_thresholds(&thresholds);
+        let reallocated = smart.attribute(0x05).unwrap();
+        assert_eq!(reallocated.threshold, Some(100));
+        assert!(reallocated.is_failing());
+        assert!(!smart.attribute(0x09).unwrap().is_failing());
+
+        let mut corrupted = data;
+        corrupted[10] ^= 1;
+        assert!(SmartData::parse(&corrupted).is_none());
+    }
+}
@@ -12,6 +12,7 @@ use uefi_raw::protocol::ata::{
     AtaCommandBlock, AtaPassThruCommandPacket, AtaPassThruLength, AtaStatusBlock,
 };
 
+pub mod command;
 pub mod pass_thru;
 
 /// Represents the protocol for ATA Pass Thru command handling.
@@ -117,6 +118,24 @@ impl<'a> AtaRequestBuilder<'a> {
         Self::new(io_align, command, AtaPassThruCommandProtocol::UDMA_DATA_OUT)
     }
 
+    /// Creates a builder for a PIO read operation.
+    ///
+    /// Most commands that return device information, like IDENTIFY DEVICE
+    /// or SMART READ DATA, use this protocol.
+    ///
+    /// # Parameters
+    /// - `io_align`: The I/O buffer alignment required for the ATA controller.
+    /// - `command`: The ATA command byte specifying the read operation.
+    ///
+    /// # Returns
+    /// `Result<Self, LayoutError>` indicating success or memory allocation failure.
+    ///
+    /// # Errors
+    /// This method can fail due to alignment or memory allocation issues.
+    pub fn read_pio(io_align: u32, command: u8) -> Result<Self, LayoutError> {
+        Self::new(io_align, command, AtaPassThruCommandProtocol::PIO_DATA_IN)
+    }
+
     // ########################################################################
 
     /// Configure the given timeout for this request.
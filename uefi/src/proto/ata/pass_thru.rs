@@ -109,6 +109,11 @@ impl AtaDevice<'_> {
         ptr::from_ref(self.proto).cast_mut()
     }
 
+    /// Alignment requirement of the controller for I/O buffers.
+    pub(super) const fn io_align(&self) -> u32 {
+        unsafe { (*self.proto.mode).io_align }
+    }
+
     /// Returns the port number of the device.
     ///
     /// # Details
@@ -0,0 +1,87 @@
+// SPDX-License-Identifier: MIT OR Apache-2.0
+
+//! Legacy Console Control protocol.
+
+use crate::proto::unsafe_protocol;
+use crate::{CStr16, Result, StatusExt};
+use uefi_raw::Boolean;
+use uefi_raw::protocol::console::ConsoleControlProtocol;
+
+pub use uefi_raw::protocol::console::ConsoleControlScreenMode as ScreenMode;
+
+/// Legacy protocol to switch the screen between text and graphics mode.
+///
+/// This protocol predates the UEFI specification and is only provided by
+/// older firmware, notably on Apple machines. There, the firmware keeps
+/// drawing the text console over the framebuffer until the screen is
+/// switched to [`ScreenMode::GRAPHICS`], so graphical applications should
+/// do that before drawing with the [`GraphicsOutput`] protocol. Its
+/// absence is not an error: other firmware doesn't need it.
+///
+/// [`GraphicsOutput`]: super::gop::GraphicsOutput
+#[derive(Debug)]
+#[repr(transparent)]
+#[unsafe_protocol(ConsoleControlProtocol::GUID)]
+pub struct ConsoleControl(ConsoleControlProtocol);
+
+/// State of the screen reported by [`ConsoleControl::mode`].
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct ScreenModeInfo {
+    /// Current mode of the screen.
+    pub mode: ScreenMode,
+    /// Whether a graphics protocol (GOP or UGA) is available.
+    pub graphics_available: bool,
+    /// Whether the text input is locked with [`ConsoleControl::lock_std_in`].
+    pub std_in_locked: bool,
+}
+
+impl ConsoleControl {
+    /// Returns the current mode of the screen.
+    ///
+    /// # Errors
+    ///
+    /// Errors are not specified for this protocol, but firmware may return
+    /// any error status.
+    pub fn mode(&mut self) -> Result<ScreenModeInfo> {
+        let mut mode = ScreenMode::TEXT;
+        let mut graphics_available = Boolean::FALSE;
+        let mut std_in_locked = Boolean::FALSE;
+        unsafe {
+            (self.0.get_mode)(
+                &mut self.0,
+                &mut mode,
+                &mut graphics_available,
+                &mut std_in_locked,
+            )
+        }
+        .to_result_with_val(|| ScreenModeInfo {
+            mode,
+            graphics_available: graphics_available.into(),
+            std_in_locked: std_in_locked.into(),
+        })
+    }
+
+    /// Switches the screen to `mode`.
+    ///
+    /// # Errors
+    ///
+    /// * [`Status::UNSUPPORTED`]: the mode is not supported.
+    ///
+    /// [`Status::UNSUPPORTED`]: crate::Status::UNSUPPORTED
+    pub fn set_mode(&mut self, mode: ScreenMode) -> Result {
+        unsafe { (self.0.set_mode)(&mut self.0, mode) }.to_result()
+    }
+
+    /// Locks the text input until `password` is entered on the console.
+    ///
+    /// Many implementations don't support this.
+    ///
+    /// # Errors
+    ///
+    /// * [`Status::UNSUPPORTED`]: locking is not supported.
+    ///
+    /// [`Status::UNSUPPORTED`]: crate::Status::UNSUPPORTED
+    pub fn lock_std_in(&mut self, password: &CStr16) -> Result {
+        unsafe { (self.0.lock_std_in)(&mut self.0, password.as_ptr().cast()) }.to_result()
+    }
+}
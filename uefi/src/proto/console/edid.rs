@@ -0,0 +1,123 @@
+// SPDX-License-Identifier: MIT OR Apache-2.0
+
+//! EDID (Extended Display Identification Data) protocols.
+//!
+//! These protocols are installed on the handles of [`GraphicsOutput`]
+//! instances that are connected to a display, and provide the EDID of that
+//! display.
+//!
+//! [`GraphicsOutput`]: super::gop::GraphicsOutput
+
+use crate::proto::unsafe_protocol;
+use core::slice;
+use uefi_raw::protocol::console::{EdidActiveProtocol, EdidDiscoveredProtocol};
+
+/// Size of an EDID base block.
+const BASE_BLOCK_SIZE: usize = 128;
+
+/// Fixed header at the start of an EDID.
+const HEADER: [u8; 8] = [0x00, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0x00];
+
+/// Offset of the first detailed timing descriptor, which contains the
+/// preferred timing of the display.
+const PREFERRED_TIMING: usize = 54;
+
+/// Provides the EDID read from the display.
+#[derive(Debug)]
+#[repr(transparent)]
+#[unsafe_protocol(EdidDiscoveredProtocol::GUID)]
+pub struct EdidDiscovered(EdidDiscoveredProtocol);
+
+impl EdidDiscovered {
+    /// Returns the EDID, or an empty slice if the display provides none.
+    #[must_use]
+    pub const fn edid(&self) -> &[u8] {
+        edid_slice(self.0.edid, self.0.size_of_edid)
+    }
+
+    /// Returns the preferred resolution of the display, see
+    /// [`preferred_resolution`].
+    #[must_use]
+    pub fn preferred_resolution(&self) -> Option<(usize, usize)> {
+        preferred_resolution(self.edid())
+    }
+}
+
+/// Provides the EDID in use for the display, which is either the
+/// [`EdidDiscovered`] one or an override provided by the platform.
+#[derive(Debug)]
+#[repr(transparent)]
+#[unsafe_protocol(EdidActiveProtocol::GUID)]
+pub struct EdidActive(EdidActiveProtocol);
+
+impl EdidActive {
+    /// Returns the EDID, or an empty slice if there is none.
+    #[must_use]
+    pub const fn edid(&self) -> &[u8] {
+        edid_slice(self.0.edid, self.0.size_of_edid)
+    }
+
+    /// Returns the preferred resolution of the display, see
+    /// [`preferred_resolution`].
+    #[must_use]
+    pub fn preferred_resolution(&self) -> Option<(usize, usize)> {
+        preferred_resolution(self.edid())
+    }
+}
+
+const fn edid_slice<'a>(edid: *const u8, size: u32) -> &'a [u8] {
+    if edid.is_null() {
+        &[]
+    } else {
+        unsafe { slice::from_raw_parts(edid, size as usize) }
+    }
+}
+
+/// Returns the preferred resolution given by the first detailed timing
+/// descriptor of an EDID, which is usually the native resolution of the
+/// display.
+///
+/// Returns `None` if the EDID is invalid or has no preferred timing.
+#[must_use]
+pub fn preferred_resolution(edid: &[u8]) -> Option<(usize, usize)> {
+    let block = edid.get(..BASE_BLOCK_SIZE)?;
+    let checksum = block.iter().fold(0u8, |sum, byte| sum.wrapping_add(*byte));
+    if !block.starts_with(&HEADER) || checksum != 0 {
+        return None;
+    }
+
+    let timing = &block[PREFERRED_TIMING..PREFERRED_TIMING + 18];
+    // A pixel clock of zero marks a display descriptor instead of a timing.
+    if timing[0] == 0 && timing[1] == 0 {
+        return None;
+    }
+    let width = usize::from(timing[2]) | (usize::from(timing[4] & 0xf0) << 4);
+    let height = usize::from(timing[5]) | (usize::from(timing[7] & 0xf0) << 4);
+    (width > 0 && height > 0).then_some((width, height))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Returns a base block with a preferred timing of 1920x1080.
+    fn make_edid() -> [u8; BASE_BLOCK_SIZE] {
+        let mut edid = [0; BASE_BLOCK_SIZE];
+        edid[..8].copy_from_slice(&HEADER);
+        edid[PREFERRED_TIMING..PREFERRED_TIMING + 8]
+            .copy_from_slice(&[0x02, 0x3a, 0x80, 0x18, 0x71, 0x38, 0x2d, 0x40]);
+        let sum = edid.iter().fold(0u8, |sum, byte| sum.wrapping_add(*byte));
+        edid[127] = 0u8.wrapping_sub(sum);
+        edid
+    }
+
+    #[test]
+    fn test_preferred_resolution() {
+        let mut edid = make_edid();
+        assert_eq!(preferred_resolution(&edid), Some((1920, 1080)));
+        assert_eq!(preferred_resolution(&edid[..100]), None);
+
+        edid[127] ^= 1;
+        assert_eq!(preferred_resolution(&edid), None);
+    }
+}
@@ -52,9 +52,13 @@
 //! You will have to implement your own double buffering if you want to
 //! avoid tearing with animations.
 
+use crate::proto::console::edid;
 use crate::proto::unsafe_protocol;
 use crate::util::usize_from_u32;
-use crate::{Result, StatusExt, boot};
+use crate::{Error, Result, StatusExt, boot};
+#[cfg(feature = "alloc")]
+use alloc::{vec, vec::Vec};
+use core::cmp::Ordering;
 use core::fmt::{Debug, Formatter};
 use core::marker::PhantomData;
 use core::ptr::{self, NonNull};
@@ -112,6 +116,30 @@ impl GraphicsOutput {
         }
     }
 
+    /// Returns the mode that best matches `criteria`, or `None` if no mode
+    /// matches.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use uefi::proto::console::gop::{GraphicsOutput, ModeCriteria};
+    ///
+    /// fn set_best_mode(gop: &mut GraphicsOutput, edid: &[u8]) -> uefi::Result {
+    ///     let mode = gop
+    ///         .choose_mode(ModeCriteria::Native(edid))
+    ///         .or_else(|| gop.choose_mode(ModeCriteria::AspectRatio(16, 9)))
+    ///         .or_else(|| gop.choose_mode(ModeCriteria::LargestArea));
+    ///     if let Some(mode) = mode {
+    ///         gop.set_mode(&mode)?;
+    ///     }
+    ///     Ok(())
+    /// }
+    /// ```
+    #[must_use]
+    pub fn choose_mode(&self, criteria: ModeCriteria) -> Option<Mode> {
+        select_mode(self.modes(), criteria)
+    }
+
     /// Sets the video device into the specified mode, clearing visible portions
     /// of the output display to black.
     ///
@@ -123,131 +151,187 @@ impl GraphicsOutput {
     /// Performs a blt (block transfer) operation on the frame buffer.
     ///
     /// Every operation requires different parameters.
-    pub fn blt(&mut self, op: BltOp) -> Result {
+    ///
+    /// # Panics
+    ///
+    /// Panics if a rectangle of the operation is out of bounds.
+    pub fn blt(&mut self, mut op: BltOp) -> Result {
+        self.check_blt_op(&op);
+        unsafe { self.blt_unchecked(&mut op) }
+    }
+
+    /// Performs several blt (block transfer) operations back-to-back, in
+    /// order.
+    ///
+    /// All operations are validated before the first one is issued, so the
+    /// bounds of each operation aren't checked between the firmware calls.
+    /// Adjacent fills can be merged beforehand with
+    /// [`BltOp::coalesce_fills`].
+    ///
+    /// # Errors
+    ///
+    /// Stops at the first operation that fails, and returns the error of
+    /// the firmware with the index of the operation as error data. The
+    /// operations before it have been performed.
+    ///
+    /// # Panics
+    ///
+    /// Panics if a rectangle of any operation is out of bounds, before any
+    /// operation is performed.
+    pub fn blt_batch(&mut self, ops: &mut [BltOp]) -> Result<(), usize> {
+        for op in ops.iter() {
+            self.check_blt_op(op);
+        }
+        for (index, op) in ops.iter_mut().enumerate() {
+            unsafe { self.blt_unchecked(op) }.map_err(|err| Error::new(err.status(), index))?;
+        }
+        Ok(())
+    }
+
+    /// Memory-safety check of all rectangles of a blt operation.
+    fn check_blt_op(&self, op: &BltOp) {
+        match *op {
+            BltOp::VideoFill { dest, dims, .. } => self.check_framebuffer_region(dest, dims),
+            BltOp::VideoToBltBuffer {
+                ref buffer,
+                src,
+                dest,
+                dims,
+            } => {
+                self.check_framebuffer_region(src, dims);
+                self.check_blt_buffer_region(dest, dims, buffer.len());
+            }
+            BltOp::BufferToVideo {
+                buffer,
+                src,
+                dest,
+                dims,
+            } => {
+                self.check_blt_buffer_region(src, dims, buffer.len());
+                self.check_framebuffer_region(dest, dims);
+            }
+            BltOp::VideoToVideo { src, dest, dims } => {
+                self.check_framebuffer_region(src, dims);
+                self.check_framebuffer_region(dest, dims);
+            }
+        }
+    }
+
+    /// Performs a blt operation without checking its bounds.
+    ///
+    /// # Safety
+    ///
+    /// The operation must have passed [`Self::check_blt_op`].
+    unsafe fn blt_unchecked(&mut self, op: &mut BltOp) -> Result {
         // Demultiplex the operation type.
         unsafe {
-            match op {
+            match *op {
                 BltOp::VideoFill {
                     color,
                     dest: (dest_x, dest_y),
                     dims: (width, height),
-                } => {
-                    self.check_framebuffer_region((dest_x, dest_y), (width, height));
-                    (self.0.blt)(
+                } => (self.0.blt)(
+                    &mut self.0,
+                    ptr::from_ref(&color) as *mut _,
+                    GraphicsOutputBltOperation::BLT_VIDEO_FILL,
+                    0,
+                    0,
+                    dest_x,
+                    dest_y,
+                    width,
+                    height,
+                    0,
+                )
+                .to_result(),
+                BltOp::VideoToBltBuffer {
+                    ref mut buffer,
+                    src: (src_x, src_y),
+                    dest: dest_region,
+                    dims: (width, height),
+                } => match dest_region {
+                    BltRegion::Full => (self.0.blt)(
                         &mut self.0,
-                        ptr::from_ref(&color) as *mut _,
-                        GraphicsOutputBltOperation::BLT_VIDEO_FILL,
+                        buffer.as_mut_ptr().cast(),
+                        GraphicsOutputBltOperation::BLT_VIDEO_TO_BLT_BUFFER,
+                        src_x,
+                        src_y,
+                        0,
                         0,
+                        width,
+                        height,
                         0,
+                    )
+                    .to_result(),
+                    BltRegion::SubRectangle {
+                        coords: (dest_x, dest_y),
+                        px_stride,
+                    } => (self.0.blt)(
+                        &mut self.0,
+                        buffer.as_mut_ptr().cast(),
+                        GraphicsOutputBltOperation::BLT_VIDEO_TO_BLT_BUFFER,
+                        src_x,
+                        src_y,
                         dest_x,
                         dest_y,
                         width,
                         height,
-                        0,
+                        px_stride * size_of::<BltPixel>(),
                     )
-                    .to_result()
-                }
-                BltOp::VideoToBltBuffer {
-                    buffer,
-                    src: (src_x, src_y),
-                    dest: dest_region,
-                    dims: (width, height),
-                } => {
-                    self.check_framebuffer_region((src_x, src_y), (width, height));
-                    self.check_blt_buffer_region(dest_region, (width, height), buffer.len());
-                    match dest_region {
-                        BltRegion::Full => (self.0.blt)(
-                            &mut self.0,
-                            buffer.as_mut_ptr().cast(),
-                            GraphicsOutputBltOperation::BLT_VIDEO_TO_BLT_BUFFER,
-                            src_x,
-                            src_y,
-                            0,
-                            0,
-                            width,
-                            height,
-                            0,
-                        )
-                        .to_result(),
-                        BltRegion::SubRectangle {
-                            coords: (dest_x, dest_y),
-                            px_stride,
-                        } => (self.0.blt)(
-                            &mut self.0,
-                            buffer.as_mut_ptr().cast(),
-                            GraphicsOutputBltOperation::BLT_VIDEO_TO_BLT_BUFFER,
-                            src_x,
-                            src_y,
-                            dest_x,
-                            dest_y,
-                            width,
-                            height,
-                            px_stride * size_of::<BltPixel>(),
-                        )
-                        .to_result(),
-                    }
-                }
+                    .to_result(),
+                },
                 BltOp::BufferToVideo {
                     buffer,
                     src: src_region,
                     dest: (dest_x, dest_y),
                     dims: (width, height),
-                } => {
-                    self.check_blt_buffer_region(src_region, (width, height), buffer.len());
-                    self.check_framebuffer_region((dest_x, dest_y), (width, height));
-                    match src_region {
-                        BltRegion::Full => (self.0.blt)(
-                            &mut self.0,
-                            buffer.as_ptr() as *mut _,
-                            GraphicsOutputBltOperation::BLT_BUFFER_TO_VIDEO,
-                            0,
-                            0,
-                            dest_x,
-                            dest_y,
-                            width,
-                            height,
-                            0,
-                        )
-                        .to_result(),
-                        BltRegion::SubRectangle {
-                            coords: (src_x, src_y),
-                            px_stride,
-                        } => (self.0.blt)(
-                            &mut self.0,
-                            buffer.as_ptr() as *mut _,
-                            GraphicsOutputBltOperation::BLT_BUFFER_TO_VIDEO,
-                            src_x,
-                            src_y,
-                            dest_x,
-                            dest_y,
-                            width,
-                            height,
-                            px_stride * size_of::<BltPixel>(),
-                        )
-                        .to_result(),
-                    }
-                }
-                BltOp::VideoToVideo {
-                    src: (src_x, src_y),
-                    dest: (dest_x, dest_y),
-                    dims: (width, height),
-                } => {
-                    self.check_framebuffer_region((src_x, src_y), (width, height));
-                    self.check_framebuffer_region((dest_x, dest_y), (width, height));
-                    (self.0.blt)(
+                } => match src_region {
+                    BltRegion::Full => (self.0.blt)(
+                        &mut self.0,
+                        buffer.as_ptr() as *mut _,
+                        GraphicsOutputBltOperation::BLT_BUFFER_TO_VIDEO,
+                        0,
+                        0,
+                        dest_x,
+                        dest_y,
+                        width,
+                        height,
+                        0,
+                    )
+                    .to_result(),
+                    BltRegion::SubRectangle {
+                        coords: (src_x, src_y),
+                        px_stride,
+                    } => (self.0.blt)(
                         &mut self.0,
-                        ptr::null_mut(),
-                        GraphicsOutputBltOperation::BLT_VIDEO_TO_VIDEO,
+                        buffer.as_ptr() as *mut _,
+                        GraphicsOutputBltOperation::BLT_BUFFER_TO_VIDEO,
                         src_x,
                         src_y,
                         dest_x,
                         dest_y,
                         width,
                         height,
-                        0,
+                        px_stride * size_of::<BltPixel>(),
                     )
-                    .to_result()
-                }
+                    .to_result(),
+                },
+                BltOp::VideoToVideo {
+                    src: (src_x, src_y),
+                    dest: (dest_x, dest_y),
+                    dims: (width, height),
+                } => (self.0.blt)(
+                    &mut self.0,
+                    ptr::null_mut(),
+                    GraphicsOutputBltOperation::BLT_VIDEO_TO_VIDEO,
+                    src_x,
+                    src_y,
+                    dest_x,
+                    dest_y,
+                    width,
+                    height,
+                    0,
+                )
+                .to_result(),
             }
         }
     }
@@ -310,6 +394,44 @@ impl GraphicsOutput {
         }
     }
 
+    /// Creates a [`Surface`] of the size of the current mode, initialized
+    /// with the current content of the screen.
+    ///
+    /// Drawing on the surface happens in system memory, and the changed
+    /// parts are copied to the screen with [`Surface::flush`]. This is much
+    /// faster than writing individual pixels to the frame buffer, which is
+    /// often uncached memory behind a PCIe bus.
+    #[cfg(feature = "alloc")]
+    pub fn create_backbuffer(&mut self) -> Result<Surface<'_>> {
+        let image = self.capture()?;
+        Ok(Surface {
+            gop: self,
+            width: image.width(),
+            height: image.height(),
+            pixels: image.into_pixels(),
+            dirty: None,
+        })
+    }
+
+    /// Copies the content of the screen into an [`Image`].
+    ///
+    /// The image can be saved with [`Image::write_bmp`].
+    ///
+    /// [`Image`]: crate::graphics::Image
+    /// [`Image::write_bmp`]: crate::graphics::Image::write_bmp
+    #[cfg(feature = "alloc")]
+    pub fn capture(&mut self) -> Result<crate::graphics::Image> {
+        let (width, height) = self.current_mode_info().resolution();
+        let mut pixels = vec![BltPixel::new(0, 0, 0); width * height];
+        self.blt(BltOp::VideoToBltBuffer {
+            buffer: &mut pixels,
+            src: (0, 0),
+            dest: BltRegion::Full,
+            dims: (width, height),
+        })?;
+        Ok(crate::graphics::Image::new(width, height, pixels))
+    }
+
     const fn mode(&self) -> &GraphicsOutputProtocolMode {
         unsafe { &*self.0.mode.cast_const() }
     }
@@ -336,6 +458,43 @@ pub enum PixelFormat {
     //         is safe to model this C enum as a Rust enum.
 }
 
+/// Criteria for selecting a mode with [`GraphicsOutput::choose_mode`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ModeCriteria<'a> {
+    /// A mode with exactly the given (horizontal, vertical) resolution.
+    Resolution(usize, usize),
+    /// The native resolution of the display, given by its EDID as returned
+    /// by [`EdidActive::edid`]. If the native resolution is not available,
+    /// the largest mode that fits within it is chosen.
+    ///
+    /// [`EdidActive::edid`]: super::edid::EdidActive::edid
+    Native(&'a [u8]),
+    /// The mode with the most pixels.
+    LargestArea,
+    /// The largest mode with the given (horizontal, vertical) aspect ratio,
+    /// e.g. `AspectRatio(16, 9)`.
+    AspectRatio(usize, usize),
+}
+
+/// Selects the mode matching `criteria` from `modes`.
+fn select_mode(mut modes: impl Iterator<Item = Mode>, criteria: ModeCriteria) -> Option<Mode> {
+    let largest =
+        |modes: &mut dyn Iterator<Item = Mode>| modes.max_by(|a, b| a.info.cmp_by_size(&b.info));
+    match criteria {
+        ModeCriteria::Resolution(width, height) => {
+            modes.find(|mode| mode.info.resolution() == (width, height))
+        }
+        ModeCriteria::Native(edid) => {
+            let (width, height) = edid::preferred_resolution(edid)?;
+            largest(&mut modes.filter(|mode| mode.info.fits_within(width, height)))
+        }
+        ModeCriteria::LargestArea => largest(&mut modes),
+        ModeCriteria::AspectRatio(width, height) => {
+            largest(&mut modes.filter(|mode| mode.info.has_aspect_ratio(width, height)))
+        }
+    }
+}
+
 /// Represents a graphics mode compatible with a given graphics device.
 #[derive(Copy, Clone, Debug)]
 pub struct Mode {
@@ -398,6 +557,51 @@ impl ModeInfo {
         }
     }
 
+    /// Returns the number of visible pixels.
+    #[must_use]
+    pub const fn area(&self) -> usize {
+        let (width, height) = self.resolution();
+        width * height
+    }
+
+    /// Returns the (horizontal, vertical) aspect ratio in lowest terms, e.g.
+    /// `(16, 9)` for a resolution of 1920x1080.
+    #[must_use]
+    pub const fn aspect_ratio(&self) -> (usize, usize) {
+        let (width, height) = self.resolution();
+        let (mut a, mut b) = (width, height);
+        while b != 0 {
+            (a, b) = (b, a % b);
+        }
+        match (width.checked_div(a), height.checked_div(a)) {
+            (Some(width), Some(height)) => (width, height),
+            _ => (width, height),
+        }
+    }
+
+    /// Returns whether the aspect ratio of the mode is `width:height`.
+    #[must_use]
+    pub const fn has_aspect_ratio(&self, width: usize, height: usize) -> bool {
+        let (mode_width, mode_height) = self.resolution();
+        mode_width * height == mode_height * width
+    }
+
+    /// Returns whether the resolution is at most `width` x `height`.
+    #[must_use]
+    pub const fn fits_within(&self, width: usize, height: usize) -> bool {
+        let (mode_width, mode_height) = self.resolution();
+        mode_width <= width && mode_height <= height
+    }
+
+    /// Compares the sizes of two modes by their area, and by their
+    /// horizontal resolution if the areas are equal.
+    #[must_use]
+    pub fn cmp_by_size(&self, other: &Self) -> Ordering {
+        self.area()
+            .cmp(&other.area())
+            .then_with(|| self.resolution().0.cmp(&other.resolution().0))
+    }
+
     /// Returns the number of pixels per scanline.
     ///
     /// Due to performance reasons, the stride might not be equal to the width,
@@ -550,6 +754,71 @@ pub enum BltOp<'buf> {
     },
 }
 
+impl BltOp<'_> {
+    /// Merges consecutive [`BltOp::VideoFill`] operations with the same
+    /// color whose rectangles are adjacent into one, to reduce the number of
+    /// firmware calls of [`GraphicsOutput::blt_batch`].
+    ///
+    /// Rectangles are merged if they have the same rows and touch
+    /// horizontally, or the same columns and touch vertically. Only
+    /// consecutive operations are merged, so the result of drawing the
+    /// operations in order doesn't change.
+    ///
+    /// The merged operations are moved to the start of `ops`, and their
+    /// number is returned.
+    pub fn coalesce_fills(ops: &mut [Self]) -> usize {
+        let mut len = 0;
+        for i in 0..ops.len() {
+            if len > 0 {
+                if let Some(merged) = merge_fills(&ops[len - 1], &ops[i]) {
+                    ops[len - 1] = merged;
+                    continue;
+                }
+            }
+            ops.swap(len, i);
+            len += 1;
+        }
+        len
+    }
+}
+
+/// Returns the fill covering the rectangles of `a` and `b` if both are fills
+/// of the same color and the rectangles are adjacent.
+fn merge_fills<'buf>(a: &BltOp<'buf>, b: &BltOp<'buf>) -> Option<BltOp<'buf>> {
+    let (
+        &BltOp::VideoFill {
+            color: a_color,
+            dest: (ax, ay),
+            dims: (aw, ah),
+        },
+        &BltOp::VideoFill {
+            color: b_color,
+            dest: (bx, by),
+            dims: (bw, bh),
+        },
+    ) = (a, b)
+    else {
+        return None;
+    };
+    let rgb = |color: BltPixel| (color.red, color.green, color.blue);
+    if rgb(a_color) != rgb(b_color) {
+        return None;
+    }
+
+    let (dest, dims) = if (ay, ah) == (by, bh) && (ax + aw == bx || bx + bw == ax) {
+        ((ax.min(bx), ay), (aw + bw, ah))
+    } else if (ax, aw) == (bx, bw) && (ay + ah == by || by + bh == ay) {
+        ((ax, ay.min(by)), (aw, ah + bh))
+    } else {
+        return None;
+    };
+    Some(BltOp::VideoFill {
+        color: a_color,
+        dest,
+        dims,
+    })
+}
+
 /// Direct access to a memory-mapped frame buffer
 #[derive(Debug)]
 pub struct FrameBuffer<'gop> {
@@ -652,3 +921,355 @@ impl FrameBuffer<'_> {
         unsafe { (self.base.add(index) as *const T).read_volatile() }
     }
 }
+
+/// Rectangle given by its top-left corner and its exclusive bottom-right
+/// corner.
+#[cfg(feature = "alloc")]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+struct Region {
+    x0: usize,
+    y0: usize,
+    x1: usize,
+    y1: usize,
+}
+
+#[cfg(feature = "alloc")]
+impl Region {
+    /// Returns the part of the rectangle at `dest` with size `dims` that is
+    /// within `(width, height)`, or `None` if it is empty.
+    fn clip(
+        dest: (usize, usize),
+        dims: (usize, usize),
+        (width, height): (usize, usize),
+    ) -> Option<Self> {
+        let region = Self {
+            x0: dest.0.min(width),
+            y0: dest.1.min(height),
+            x1: dest.0.saturating_add(dims.0).min(width),
+            y1: dest.1.saturating_add(dims.1).min(height),
+        };
+        (region.x0 < region.x1 && region.y0 < region.y1).then_some(region)
+    }
+
+    /// Returns the smallest rectangle containing both rectangles.
+    fn union(self, other: Self) -> Self {
+        Self {
+            x0: self.x0.min(other.x0),
+            y0: self.y0.min(other.y0),
+            x1: self.x1.max(other.x1),
+            y1: self.y1.max(other.y1),
+        }
+    }
+
+    const fn contains(&self, other: &Self) -> bool {
+        self.x0 <= other.x0 && self.y0 <= other.y0 && self.x1 >= other.x1 && self.y1 >= other.y1
+    }
+
+    const fn dest(&self) -> (usize, usize) {
+        (self.x0, self.y0)
+    }
+
+    const fn dims(&self) -> (usize, usize) {
+        (self.x1 - self.x0, self.y1 - self.y0)
+    }
+}
+
+/// Back buffer for a [`GraphicsOutput`], created with
+/// [`GraphicsOutput::create_backbuffer`].
+///
+/// All drawing operations of the surface only change the pixels in system
+/// memory and record the changed area. [`flush`] copies the changed area to
+/// the screen with a single blit. The changed area is tracked as one
+/// bounding rectangle, so drawing in two opposite corners causes everything
+/// in between to be copied as well.
+///
+/// Rectangles passed to the drawing operations are clipped to the surface.
+/// The surface must not be used after the mode has been changed.
+///
+/// [`flush`]: Self::flush
+#[cfg(feature = "alloc")]
+#[derive(Debug)]
+pub struct Surface<'gop> {
+    gop: &'gop mut GraphicsOutput,
+    width: usize,
+    height: usize,
+    pixels: Vec<BltPixel>,
+    dirty: Option<Region>,
+}
+
+#[cfg(feature = "alloc")]
+impl Surface<'_> {
+    /// Returns the (width, height) of the surface.
+    #[must_use]
+    pub const fn resolution(&self) -> (usize, usize) {
+        (self.width, self.height)
+    }
+
+    /// Returns the pixels of the surface, row by row without padding.
+    #[must_use]
+    pub fn pixels(&self) -> &[BltPixel] {
+        &self.pixels
+    }
+
+    /// Returns the pixels of the surface for modification, row by row
+    /// without padding. The whole surface is marked as changed.
+    #[must_use]
+    pub fn pixels_mut(&mut self) -> &mut [BltPixel] {
+        self.mark_dirty((0, 0), (self.width, self.height));
+        &mut self.pixels
+    }
+
+    /// Returns the pixel at `(x, y)`, or `None` if it is outside the
+    /// surface.
+    #[must_use]
+    pub fn pixel(&self, x: usize, y: usize) -> Option<BltPixel> {
+        (x < self.width && y < self.height).then(|| self.pixels[y * self.width + x])
+    }
+
+    /// Sets the pixel at `(x, y)`. Pixels outside the surface are ignored.
+    pub fn set_pixel(&mut self, x: usize, y: usize, color: BltPixel) {
+        if x < self.width && y < self.height {
+            self.pixels[y * self.width + x] = color;
+            self.mark_dirty((x, y), (1, 1));
+        }
+    }
+
+    /// Fills a rectangle with `color`.
+    pub fn fill_rect(&mut self, dest: (usize, usize), dims: (usize, usize), color: BltPixel) {
+        let Some(region) = self.clip(dest, dims) else {
+            return;
+        };
+        for y in region.y0..region.y1 {
+            self.pixels[y * self.width..][region.x0..region.x1].fill(color);
+        }
+        self.add_dirty(region);
+    }
+
+    /// Copies `pixels`, which are stored row by row without padding, into
+    /// the rectangle at `dest` with size `dims`.
+    ///
+    /// # Panics
+    /// Panics if `pixels` has less than `dims.0 * dims.1` elements.
+    pub fn write_rect(&mut self, dest: (usize, usize), dims: (usize, usize), pixels: &[BltPixel]) {
+        assert!(
+            pixels.len() >= dims.0.saturating_mul(dims.1),
+            "pixel buffer too small"
+        );
+        let Some(region) = self.clip(dest, dims) else {
+            return;
+        };
+        let (width, height) = region.dims();
+        for (y, src) in pixels.chunks_exact(dims.0).take(height).enumerate() {
+            self.pixels[(region.y0 + y) * self.width..][region.x0..region.x1]
+                .copy_from_slice(&src[..width]);
+        }
+        self.add_dirty(region);
+    }
+
+    /// Copies the rectangle at `src` with size `dims` to `dest`. The
+    /// rectangles may overlap.
+    pub fn copy_rect(&mut self, src: (usize, usize), dest: (usize, usize), dims: (usize, usize)) {
+        // Clip the size so that both rectangles are within the surface.
+        let (Some(from), Some(to)) = (self.clip(src, dims), self.clip(dest, dims)) else {
+            return;
+        };
+        let (width, height) = (
+            from.dims().0.min(to.dims().0),
+            from.dims().1.min(to.dims().1),
+        );
+        let copy_row = |pixels: &mut [BltPixel], y: usize| {
+            let start = (src.1 + y) * self.width + src.0;
+            pixels.copy_within(start..start + width, (dest.1 + y) * self.width + dest.0);
+        };
+        // Copy in the right order if the rectangles overlap vertically.
+        if dest.1 <= src.1 {
+            (0..height).for_each(|y| copy_row(&mut self.pixels, y));
+        } else {
+            (0..height)
+                .rev()
+                .for_each(|y| copy_row(&mut self.pixels, y));
+        }
+        self.mark_dirty(dest, (width, height));
+    }
+
+    /// Marks a rectangle as changed, so that it is copied to the screen by
+    /// the next [`flush`]. This is only needed after modifying the pixels
+    /// by other means than the methods of the surface.
+    ///
+    /// [`flush`]: Self::flush
+    pub fn mark_dirty(&mut self, dest: (usize, usize), dims: (usize, usize)) {
+        if let Some(region) = self.clip(dest, dims) {
+            self.add_dirty(region);
+        }
+    }
+
+    /// Returns the position and size of the changed area that has not been
+    /// copied to the screen yet, or `None` if nothing has changed.
+    #[must_use]
+    pub fn dirty_rect(&self) -> Option<((usize, usize), (usize, usize))> {
+        self.dirty.map(|region| (region.dest(), region.dims()))
+    }
+
+    /// Copies the changed area to the screen.
+    pub fn flush(&mut self) -> Result {
+        if let Some(region) = self.dirty {
+            self.blit(region)?;
+            self.dirty = None;
+        }
+        Ok(())
+    }
+
+    /// Copies a rectangle to the screen, regardless of whether it has
+    /// changed. The changed area is reset if the rectangle covers all of it.
+    pub fn flush_rect(&mut self, dest: (usize, usize), dims: (usize, usize)) -> Result {
+        let Some(region) = self.clip(dest, dims) else {
+            return Ok(());
+        };
+        self.blit(region)?;
+        if self.dirty.is_some_and(|dirty| region.contains(&dirty)) {
+            self.dirty = None;
+        }
+        Ok(())
+    }
+
+    fn clip(&self, dest: (usize, usize), dims: (usize, usize)) -> Option<Region> {
+        Region::clip(dest, dims, (self.width, self.height))
+    }
+
+    fn add_dirty(&mut self, region: Region) {
+        self.dirty = Some(match self.dirty {
+            Some(dirty) => dirty.union(region),
+            None => region,
+        });
+    }
+
+    fn blit(&mut self, region: Region) -> Result {
+        self.gop.blt(BltOp::BufferToVideo {
+            buffer: &self.pixels,
+            src: BltRegion::SubRectangle {
+                coords: region.dest(),
+                px_stride: self.width,
+            },
+            dest: region.dest(),
+            dims: region.dims(),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn mode(index: u32, width: u32, height: u32) -> Mode {
+        Mode {
+            index,
+            info_sz: size_of::<GraphicsOutputModeInformation>(),
+            info: ModeInfo(GraphicsOutputModeInformation {
+                horizontal_resolution: width,
+                vertical_resolution: height,
+                pixels_per_scan_line: width,
+                ..Default::default()
+            }),
+        }
+    }
+
+    #[test]
+    fn test_mode_info() {
+        let info = mode(0, 1920, 1080).info;
+        assert_eq!(info.area(), 1920 * 1080);
+        assert_eq!(info.aspect_ratio(), (16, 9));
+        assert!(info.has_aspect_ratio(32, 18));
+        assert!(!info.has_aspect_ratio(4, 3));
+        assert!(info.fits_within(1920, 1200));
+        assert!(!info.fits_within(1280, 1080));
+        assert_eq!(
+            info.cmp_by_size(&mode(1, 1080, 1920).info),
+            Ordering::Greater
+        );
+    }
+
+    #[test]
+    fn test_select_mode() {
+        let modes = [
+            mode(0, 640, 480),
+            mode(1, 1024, 768),
+            mode(2, 1280, 720),
+            mode(3, 1920, 1080),
+            mode(4, 2048, 1536),
+        ];
+        let select = |criteria| select_mode(modes.into_iter(), criteria).map(|mode| mode.index);
+
+        assert_eq!(select(ModeCriteria::Resolution(1280, 720)), Some(2));
+        assert_eq!(select(ModeCriteria::Resolution(800, 600)), None);
+        assert_eq!(select(ModeCriteria::LargestArea), Some(4));
+        assert_eq!(select(ModeCriteria::AspectRatio(16, 9)), Some(3));
+        assert_eq!(select(ModeCriteria::AspectRatio(16, 10)), None);
+
+        // EDID with a preferred timing of 1600x900, which is not available.
+        let mut edid = [0; 128];
+        edid[..8].copy_from_slice(&[0x00, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0x00]);
+        edid[54..62].copy_from_slice(&[0x30, 0x2a, 0x40, 0x20, 0x60, 0x84, 0x20, 0x30]);
+        let sum = edid.iter().fold(0u8, |sum, byte| sum.wrapping_add(*byte));
+        edid[127] = 0u8.wrapping_sub(sum);
+        assert_eq!(select(ModeCriteria::Native(&edid)), Some(2));
+        assert_eq!(select(ModeCriteria::Native(&[])), None);
+    }
+
+    #[cfg(feature = "alloc")]
+    #[test]
+    fn test_region() {
+        let screen = (100, 50);
+        assert_eq!(Region::clip((10, 20), (0, 5), screen), None);
+        assert_eq!(Region::clip((100, 20), (5, 5), screen), None);
+
+        let a = Region::clip((90, 40), (20, 20), screen).unwrap();
+        assert_eq!((a.dest(), a.dims()), ((90, 40), (10, 10)));
+        let b = Region::clip((10, 5), (1, 1), screen).unwrap();
+        let union = a.union(b);
+        assert_eq!((union.dest(), union.dims()), ((10, 5), (90, 45)));
+        assert!(union.contains(&a));
+        assert!(!a.contains(&union));
+    }
+
+    #[test]
+    fn test_coalesce_fills() {
+        let fill = |color: u32, dest, dims| BltOp::VideoFill {
+            color: BltPixel::from(color),
+            dest,
+            dims,
+        };
+        let mut ops = [
+            fill(0xff0000, (0, 0), (10, 5)),
+            fill(0xff0000, (10, 0), (20, 5)),
+            fill(0xff0000, (0, 5), (30, 5)),
+            // Different color.
+            fill(0x00ff00, (0, 10), (30, 5)),
+            // Not adjacent.
+            fill(0x00ff00, (0, 20), (30, 5)),
+            BltOp::VideoToVideo {
+                src: (0, 0),
+                dest: (0, 25),
+                dims: (30, 5),
+            },
+            fill(0x00ff00, (5, 30), (5, 5)),
+            fill(0x00ff00, (0, 30), (5, 5)),
+        ];
+        let len = BltOp::coalesce_fills(&mut ops);
+        assert_eq!(len, 5);
+
+        let rects: [_; 5] = core::array::from_fn(|i| match ops[i] {
+            BltOp::VideoFill { dest, dims, .. } => Some((dest, dims)),
+            _ => None,
+        });
+        assert_eq!(
+            rects,
+            [
+                Some(((0, 0), (30, 10))),
+                Some(((0, 10), (30, 5))),
+                Some(((0, 20), (30, 5))),
+                None,
+                Some(((0, 30), (10, 5))),
+            ]
+        );
+    }
+}
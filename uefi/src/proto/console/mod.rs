@@ -5,6 +5,8 @@
 //! The console represents the various input and output methods
 //! used by the user to interact with the early boot platform.
 
+pub mod control;
+pub mod edid;
 pub mod gop;
 pub mod pointer;
 pub mod serial;
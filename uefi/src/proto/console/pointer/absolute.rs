@@ -0,0 +1,72 @@
+// SPDX-License-Identifier: MIT OR Apache-2.0
+
+use crate::proto::unsafe_protocol;
+use crate::{Event, Result, Status, StatusExt};
+use uefi_raw::protocol::console::AbsolutePointerProtocol;
+
+pub use uefi_raw::protocol::console::{
+    AbsolutePointerMode, AbsolutePointerModeAttributes, AbsolutePointerState,
+};
+
+/// Provides information about a pointer device with absolute coordinates,
+/// such as a touchscreen or a digitizer.
+#[derive(Debug)]
+#[repr(transparent)]
+#[unsafe_protocol(AbsolutePointerProtocol::GUID)]
+pub struct AbsolutePointer(AbsolutePointerProtocol);
+
+impl AbsolutePointer {
+    /// Bit of [`AbsolutePointerState::active_buttons`] that is set while the
+    /// device is touched or its primary button is pressed.
+    pub const TOUCH_ACTIVE: u32 = 0x01;
+
+    /// Bit of [`AbsolutePointerState::active_buttons`] that is set while the
+    /// alternate button is pressed.
+    pub const ALT_ACTIVE: u32 = 0x02;
+
+    /// Resets the pointer device hardware.
+    ///
+    /// # Arguments
+    /// The `extended_verification` parameter is used to request that UEFI
+    /// performs an extended check and reset of the input device.
+    ///
+    /// # Errors
+    /// - `DeviceError` if the device is malfunctioning and cannot be reset.
+    pub fn reset(&mut self, extended_verification: bool) -> Result {
+        unsafe { (self.0.reset)(&mut self.0, extended_verification.into()) }.to_result()
+    }
+
+    /// Retrieves the pointer device's current state, if a state change occurred
+    /// since the last time this function was called.
+    ///
+    /// Use `wait_for_input_event()` with the [`boot::wait_for_event`]
+    /// interface in order to wait for input from the pointer device.
+    ///
+    /// # Errors
+    /// - `DeviceError` if there was an issue with the pointer device.
+    ///
+    /// [`boot::wait_for_event`]: crate::boot::wait_for_event
+    pub fn read_state(&mut self) -> Result<Option<AbsolutePointerState>> {
+        let mut state = AbsolutePointerState::default();
+
+        match unsafe { (self.0.get_state)(&self.0, &mut state) } {
+            Status::NOT_READY => Ok(None),
+            other => other.to_result_with_val(|| Some(state)),
+        }
+    }
+
+    /// Event to be used with [`boot::wait_for_event`] in order to wait
+    /// for input from the pointer device
+    ///
+    /// [`boot::wait_for_event`]: crate::boot::wait_for_event
+    #[must_use]
+    pub fn wait_for_input_event(&self) -> Option<Event> {
+        unsafe { Event::from_ptr(self.0.wait_for_input) }
+    }
+
+    /// Returns a reference to the pointer device information.
+    #[must_use]
+    pub const fn mode(&self) -> &AbsolutePointerMode {
+        unsafe { &*self.0.mode }
+    }
+}
@@ -2,6 +2,12 @@
 
 //! Pointer device access.
 
+mod absolute;
+
+pub use absolute::{
+    AbsolutePointer, AbsolutePointerMode, AbsolutePointerModeAttributes, AbsolutePointerState,
+};
+
 use crate::proto::unsafe_protocol;
 use crate::{Event, Result, Status, StatusExt};
 use uefi_raw::protocol::console::SimplePointerProtocol;
@@ -2,6 +2,8 @@
 
 //! Abstraction over byte stream devices, also known as serial I/O devices.
 
+#[cfg(feature = "embedded-io")]
+use crate::Status;
 use crate::proto::unsafe_protocol;
 use crate::{Result, StatusExt};
 use core::fmt::Write;
@@ -109,3 +111,50 @@ impl Write for Serial {
         self.write(s.as_bytes()).map_err(|_| core::fmt::Error)
     }
 }
+
+#[cfg(feature = "embedded-io")]
+impl embedded_io::ErrorType for Serial {
+    type Error = crate::Error;
+}
+
+#[cfg(feature = "embedded-io")]
+impl embedded_io::Read for Serial {
+    /// Reads at least one byte into `buf`. Timeouts of the device without
+    /// data are retried, so this blocks until data is available.
+    fn read(&mut self, buf: &mut [u8]) -> Result<usize> {
+        if buf.is_empty() {
+            return Ok(0);
+        }
+        loop {
+            match Self::read(self, buf) {
+                Ok(()) => return Ok(buf.len()),
+                Err(err) if *err.data() > 0 => return Ok(*err.data()),
+                Err(err) if err.status() == Status::TIMEOUT => continue,
+                Err(err) => return Err(err.to_err_without_payload()),
+            }
+        }
+    }
+}
+
+#[cfg(feature = "embedded-io")]
+impl embedded_io::ReadReady for Serial {
+    fn read_ready(&mut self) -> Result<bool> {
+        let bits = self.get_control_bits()?;
+        Ok(!bits.contains(ControlBits::INPUT_BUFFER_EMPTY))
+    }
+}
+
+#[cfg(feature = "embedded-io")]
+impl embedded_io::Write for Serial {
+    fn write(&mut self, buf: &[u8]) -> Result<usize> {
+        match Self::write(self, buf) {
+            Ok(()) => Ok(buf.len()),
+            Err(err) if *err.data() > 0 => Ok(*err.data()),
+            Err(err) => Err(err.to_err_without_payload()),
+        }
+    }
+
+    fn flush(&mut self) -> Result {
+        Ok(())
+    }
+}
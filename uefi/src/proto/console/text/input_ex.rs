@@ -0,0 +1,133 @@
+// SPDX-License-Identifier: MIT OR Apache-2.0
+
+use super::Key;
+use crate::proto::unsafe_protocol;
+use crate::{Event, Result, Status, StatusExt};
+use uefi_raw::protocol::console::{KeyData, SimpleTextInputExProtocol};
+
+pub use uefi_raw::protocol::console::{KeyShiftState, KeyToggleState};
+
+/// Interface for text-based input devices that also reports the state of
+/// the modifier and toggle keys.
+#[derive(Debug)]
+#[repr(transparent)]
+#[unsafe_protocol(SimpleTextInputExProtocol::GUID)]
+pub struct InputEx(SimpleTextInputExProtocol);
+
+impl InputEx {
+    /// Resets the input device hardware.
+    ///
+    /// The `extended_verification` parameter is used to request that UEFI
+    /// performs an extended check and reset of the input device.
+    ///
+    /// # Errors
+    ///
+    /// - `DeviceError` if the device is malfunctioning and cannot be reset.
+    pub fn reset(&mut self, extended_verification: bool) -> Result {
+        unsafe { (self.0.reset)(&mut self.0, extended_verification.into()) }.to_result()
+    }
+
+    /// Reads the next keystroke from the input device, if any.
+    ///
+    /// Use [`wait_for_key_event`] with the [`boot::wait_for_event`]
+    /// interface in order to wait for a key to be pressed.
+    ///
+    /// [`boot::wait_for_event`]: crate::boot::wait_for_event
+    /// [`wait_for_key_event`]: Self::wait_for_key_event
+    ///
+    /// # Errors
+    ///
+    /// - [`Status::DEVICE_ERROR`] if there was an issue with the input device
+    pub fn read_key(&mut self) -> Result<Option<KeyStroke>> {
+        let mut key_data = KeyData::default();
+
+        match unsafe { (self.0.read_key_stroke_ex)(&mut self.0, &mut key_data) } {
+            Status::NOT_READY => Ok(None),
+            other => other.to_result_with_val(|| Some(key_data.into())),
+        }
+    }
+
+    /// Event to be used with [`boot::wait_for_event`] in order to wait
+    /// for a key to be available
+    ///
+    /// [`boot::wait_for_event`]: crate::boot::wait_for_event
+    #[must_use]
+    pub fn wait_for_key_event(&self) -> Option<Event> {
+        unsafe { Event::from_ptr(self.0.wait_for_key_ex) }
+    }
+
+    /// Sets the state of the toggle keys, e.g. to turn on Num Lock.
+    ///
+    /// # Errors
+    ///
+    /// - [`Status::DEVICE_ERROR`] if the state could not be set.
+    /// - [`Status::UNSUPPORTED`] if the device doesn't support changing the
+    ///   state.
+    pub fn set_state(&mut self, state: KeyToggleState) -> Result {
+        unsafe { (self.0.set_state)(&mut self.0, &state) }.to_result()
+    }
+}
+
+/// A keystroke together with the state of the modifier and toggle keys.
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub struct KeyStroke {
+    /// The key that was pressed. Partial keystrokes, which only change the
+    /// modifier or toggle state, are reported as a printable NUL character.
+    pub key: Key,
+    /// The state of the shift, control, alt and logo keys. Only valid if
+    /// [`KeyShiftState::SHIFT_STATE_VALID`] is set.
+    pub shift_state: KeyShiftState,
+    /// The state of the toggle keys. Only valid if
+    /// [`KeyToggleState::TOGGLE_STATE_VALID`] is set.
+    pub toggle_state: KeyToggleState,
+}
+
+impl KeyStroke {
+    /// Returns whether this keystroke only changes the modifier or toggle
+    /// state.
+    #[must_use]
+    pub fn is_partial(&self) -> bool {
+        matches!(self.key, Key::Printable(c) if u16::from(c) == 0)
+    }
+
+    /// Returns whether either shift key is pressed.
+    #[must_use]
+    pub const fn shift(&self) -> bool {
+        self.is_pressed(KeyShiftState::LEFT_SHIFT_PRESSED.union(KeyShiftState::RIGHT_SHIFT_PRESSED))
+    }
+
+    /// Returns whether either control key is pressed.
+    #[must_use]
+    pub const fn ctrl(&self) -> bool {
+        self.is_pressed(
+            KeyShiftState::LEFT_CONTROL_PRESSED.union(KeyShiftState::RIGHT_CONTROL_PRESSED),
+        )
+    }
+
+    /// Returns whether either alt key is pressed.
+    #[must_use]
+    pub const fn alt(&self) -> bool {
+        self.is_pressed(KeyShiftState::LEFT_ALT_PRESSED.union(KeyShiftState::RIGHT_ALT_PRESSED))
+    }
+
+    /// Returns whether either logo key is pressed.
+    #[must_use]
+    pub const fn logo(&self) -> bool {
+        self.is_pressed(KeyShiftState::LEFT_LOGO_PRESSED.union(KeyShiftState::RIGHT_LOGO_PRESSED))
+    }
+
+    const fn is_pressed(&self, keys: KeyShiftState) -> bool {
+        self.shift_state.contains(KeyShiftState::SHIFT_STATE_VALID)
+            && self.shift_state.intersects(keys)
+    }
+}
+
+impl From<KeyData> for KeyStroke {
+    fn from(data: KeyData) -> Self {
+        Self {
+            key: Key::from(data.key),
+            shift_state: data.key_state.key_shift_state,
+            toggle_state: data.key_state.key_toggle_state,
+        }
+    }
+}
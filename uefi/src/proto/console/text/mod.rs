@@ -5,5 +5,13 @@
 mod input;
 pub use input::{Input, Key, ScanCode};
 
+mod input_ex;
+pub use input_ex::{InputEx, KeyShiftState, KeyStroke, KeyToggleState};
+
 mod output;
-pub use output::{Color, Output, OutputMode};
+pub use output::{Color, CursorState, Output, OutputMode};
+
+#[cfg(feature = "alloc")]
+mod scroll;
+#[cfg(feature = "alloc")]
+pub use scroll::ScrollRegion;
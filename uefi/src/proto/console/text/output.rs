@@ -145,6 +145,74 @@ impl Output {
         unsafe { (self.0.set_cursor_position)(&mut self.0, column, row) }.to_result()
     }
 
+    /// Returns the number of columns and rows of the current text mode.
+    ///
+    /// Returns [`Status::UNSUPPORTED`] if no text mode is set.
+    pub fn size(&self) -> Result<(usize, usize)> {
+        let mode = self.current_mode()?.ok_or(Status::UNSUPPORTED)?;
+        Ok((mode.columns(), mode.rows()))
+    }
+
+    /// Moves the cursor by `columns` and `rows` from its current position.
+    /// The new position is limited to the screen.
+    pub fn move_cursor_by(&mut self, columns: isize, rows: isize) -> Result {
+        let (width, height) = self.size()?;
+        let (column, row) = self.cursor_position();
+        let column = column
+            .saturating_add_signed(columns)
+            .min(width.saturating_sub(1));
+        let row = row
+            .saturating_add_signed(rows)
+            .min(height.saturating_sub(1));
+        self.set_cursor_position(column, row)
+    }
+
+    /// Returns the position and visibility of the cursor, to be restored
+    /// later with [`restore_cursor`].
+    ///
+    /// [`restore_cursor`]: Self::restore_cursor
+    #[must_use]
+    pub fn save_cursor(&self) -> CursorState {
+        let (column, row) = self.cursor_position();
+        CursorState {
+            column,
+            row,
+            visible: self.cursor_visible(),
+        }
+    }
+
+    /// Restores the cursor position and visibility saved with
+    /// [`save_cursor`].
+    ///
+    /// Errors of consoles that can't change the cursor visibility are
+    /// ignored.
+    ///
+    /// [`save_cursor`]: Self::save_cursor
+    pub fn restore_cursor(&mut self, state: CursorState) -> Result {
+        self.set_cursor_position(state.column, state.row)?;
+        if self.cursor_visible() != state.visible {
+            match self.enable_cursor(state.visible) {
+                Err(err) if err.status() == Status::UNSUPPORTED => {}
+                result => result?,
+            }
+        }
+        Ok(())
+    }
+
+    /// Runs `f` with the text and background colors set to `foreground` and
+    /// `background`. The previous colors are restored afterwards.
+    pub fn with_color<R>(
+        &mut self,
+        foreground: Color,
+        background: Color,
+        f: impl FnOnce(&mut Self) -> R,
+    ) -> Result<R> {
+        let attribute = self.data().attribute as usize;
+        self.set_color(foreground, background)?;
+        let value = f(self);
+        unsafe { (self.0.set_attribute)(&mut self.0, attribute) }.to_result_with_val(|| value)
+    }
+
     /// Sets the text and background colors for the console.
     ///
     /// Note that for the foreground color you can choose any color.
@@ -185,7 +253,9 @@ impl fmt::Write for Output {
 
             let text = CStr16::from_u16_with_nul(codes).map_err(|_| fmt::Error)?;
 
-            self.output_string(text).map_err(|_| fmt::Error)
+            // Skip characters the console can't render rather than failing
+            // the whole write.
+            self.output_string_lossy(text).map_err(|_| fmt::Error)
         };
 
         // This closure converts a character to UCS-2 and adds it to the buffer,
@@ -219,6 +289,17 @@ impl fmt::Write for Output {
     }
 }
 
+/// The cursor position and visibility, as saved by [`Output::save_cursor`].
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub struct CursorState {
+    /// Column of the cursor.
+    pub column: usize,
+    /// Row of the cursor.
+    pub row: usize,
+    /// Whether the cursor is shown.
+    pub visible: bool,
+}
+
 /// The text mode (resolution) of the output device.
 #[derive(Debug, Copy, Clone, Eq, PartialEq, Ord, PartialOrd)]
 pub struct OutputMode {
@@ -0,0 +1,167 @@
+// SPDX-License-Identifier: MIT OR Apache-2.0
+
+use super::Output;
+use crate::{Result, Status};
+use alloc::collections::VecDeque;
+use alloc::string::String;
+use alloc::vec::Vec;
+use core::fmt::{self, Write};
+
+/// A range of rows of an [`Output`] that scrolls independently of the rest
+/// of the screen.
+///
+/// The text output protocol can neither scroll part of the screen nor read
+/// back what is shown, so the region keeps a copy of its text and redraws
+/// it when it scrolls. All text is redrawn with the colors that are current
+/// at the time of the redraw.
+///
+/// Text is written with [`write_str`] or the [`fmt::Write`] implementation.
+/// Lines longer than the screen width wrap, `\n` starts a new line and `\r`
+/// returns to the start of the line.
+///
+/// [`write_str`]: Self::write_str
+#[derive(Debug)]
+pub struct ScrollRegion<'a> {
+    output: &'a mut Output,
+    top: usize,
+    width: usize,
+    lines: VecDeque<Vec<char>>,
+    column: usize,
+    row: usize,
+}
+
+impl<'a> ScrollRegion<'a> {
+    /// Creates a region of the rows `top..bottom`, which are cleared.
+    ///
+    /// Returns [`Status::INVALID_PARAMETER`] if the region is empty or
+    /// doesn't fit on the screen.
+    pub fn new(output: &'a mut Output, top: usize, bottom: usize) -> Result<Self> {
+        let (width, height) = output.size()?;
+        if top >= bottom || bottom > height {
+            return Err(Status::INVALID_PARAMETER.into());
+        }
+        let mut region = Self {
+            output,
+            top,
+            width,
+            lines: (top..bottom).map(|_| Vec::new()).collect(),
+            column: 0,
+            row: 0,
+        };
+        region.redraw()?;
+        Ok(region)
+    }
+
+    /// Returns the number of rows of the region.
+    #[must_use]
+    pub fn rows(&self) -> usize {
+        self.lines.len()
+    }
+
+    /// Returns the text of row `row` of the region.
+    #[must_use]
+    pub fn line(&self, row: usize) -> Option<String> {
+        self.lines.get(row).map(|line| line.iter().collect())
+    }
+
+    /// Scrolls the region up by `rows` rows, adding empty rows at the
+    /// bottom.
+    pub fn scroll_up(&mut self, rows: usize) -> Result {
+        let rows = rows.min(self.lines.len());
+        self.lines.rotate_left(rows);
+        for line in self.lines.iter_mut().rev().take(rows) {
+            line.clear();
+        }
+        self.row = self.row.saturating_sub(rows);
+        self.redraw()
+    }
+
+    /// Clears the region and moves the cursor to its first row.
+    pub fn clear(&mut self) -> Result {
+        self.lines.iter_mut().for_each(Vec::clear);
+        self.column = 0;
+        self.row = 0;
+        self.redraw()
+    }
+
+    /// Writes `s` at the end of the region, scrolling as needed.
+    pub fn write_str(&mut self, s: &str) -> Result {
+        let start = self.row;
+        let mut scrolled = false;
+        for c in s.chars() {
+            match c {
+                '\n' => self.new_line(&mut scrolled),
+                '\r' => self.column = 0,
+                c => {
+                    if self.column == self.width {
+                        self.new_line(&mut scrolled);
+                    }
+                    let line = &mut self.lines[self.row];
+                    if line.len() <= self.column {
+                        line.resize(self.column + 1, ' ');
+                    }
+                    line[self.column] = c;
+                    self.column += 1;
+                }
+            }
+        }
+
+        if scrolled {
+            self.redraw()
+        } else {
+            (start..=self.row).try_for_each(|row| self.draw_row(row))?;
+            self.place_cursor()
+        }
+    }
+
+    /// Moves to the start of the next line, scrolling by one row if the
+    /// cursor is on the last row.
+    fn new_line(&mut self, scrolled: &mut bool) {
+        self.column = 0;
+        if self.row + 1 < self.lines.len() {
+            self.row += 1;
+        } else {
+            self.lines.rotate_left(1);
+            self.lines[self.row].clear();
+            *scrolled = true;
+        }
+    }
+
+    fn redraw(&mut self) -> Result {
+        (0..self.lines.len()).try_for_each(|row| self.draw_row(row))?;
+        self.place_cursor()
+    }
+
+    /// Writes row `row` of the region, padded with spaces to the screen
+    /// width.
+    fn draw_row(&mut self, row: usize) -> Result {
+        let (_, height) = self.output.size()?;
+        let screen_row = self.top + row;
+        // Writing the last cell of the screen would scroll the whole screen.
+        let width = if screen_row + 1 == height {
+            self.width - 1
+        } else {
+            self.width
+        };
+        let line = &self.lines[row];
+        let text: String = line
+            .iter()
+            .copied()
+            .chain(core::iter::repeat(' '))
+            .take(width)
+            .collect();
+        self.output.set_cursor_position(0, screen_row)?;
+        Write::write_str(self.output, &text).map_err(|_| Status::DEVICE_ERROR.into())
+    }
+
+    fn place_cursor(&mut self) -> Result {
+        let column = self.column.min(self.width - 1);
+        self.output.set_cursor_position(column, self.top + self.row)
+    }
+}
+
+impl fmt::Write for ScrollRegion<'_> {
+    fn write_str(&mut self, s: &str) -> fmt::Result {
+        Self::write_str(self, s).map_err(|_| fmt::Error)
+    }
+}
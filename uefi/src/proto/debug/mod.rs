@@ -247,3 +247,58 @@ impl DebugPort {
         (self.poll)(self).to_result()
     }
 }
+
+/// Timeout in microseconds of the reads and writes of the `embedded-io`
+/// implementations of [`DebugPort`].
+#[cfg(feature = "embedded-io")]
+const EMBEDDED_IO_TIMEOUT: u32 = 100_000;
+
+#[cfg(feature = "embedded-io")]
+impl embedded_io::ErrorType for DebugPort {
+    type Error = crate::Error;
+}
+
+#[cfg(feature = "embedded-io")]
+impl embedded_io::Read for DebugPort {
+    /// Reads at least one byte into `buf`. Timeouts of the device without
+    /// data are retried, so this blocks until data is available.
+    fn read(&mut self, buf: &mut [u8]) -> Result<usize> {
+        if buf.is_empty() {
+            return Ok(0);
+        }
+        loop {
+            match Self::read(self, EMBEDDED_IO_TIMEOUT, buf) {
+                Ok(()) => return Ok(buf.len()),
+                Err(err) if *err.data() > 0 => return Ok(*err.data()),
+                Err(err) if err.status() == Status::TIMEOUT => continue,
+                Err(err) => return Err(err.to_err_without_payload()),
+            }
+        }
+    }
+}
+
+#[cfg(feature = "embedded-io")]
+impl embedded_io::ReadReady for DebugPort {
+    fn read_ready(&mut self) -> Result<bool> {
+        match self.poll() {
+            Ok(()) => Ok(true),
+            Err(err) if err.status() == Status::NOT_READY => Ok(false),
+            Err(err) => Err(err),
+        }
+    }
+}
+
+#[cfg(feature = "embedded-io")]
+impl embedded_io::Write for DebugPort {
+    fn write(&mut self, buf: &[u8]) -> Result<usize> {
+        match Self::write(self, EMBEDDED_IO_TIMEOUT, buf) {
+            Ok(()) => Ok(buf.len()),
+            Err(err) if *err.data() > 0 => Ok(*err.data()),
+            Err(err) => Err(err.to_err_without_payload()),
+        }
+    }
+
+    fn flush(&mut self) -> Result {
+        Ok(())
+    }
+}
@@ -0,0 +1,698 @@
+// SPDX-License-Identifier: MIT OR Apache-2.0
+
+//! Conversion of device paths to text without the [`DevicePathToText`]
+//! protocol.
+//!
+//! The text representation follows the UEFI specification and matches the
+//! output of the EDK2 implementation of the protocol with
+//! [`DisplayOnly(false)`] and [`AllowShortcuts(false)`], for the nodes that
+//! are covered here. Other nodes are shown in the generic `Path(...)` form
+//! of the specification.
+//!
+//! [`DevicePathToText`]: super::text::DevicePathToText
+//! [`DisplayOnly(false)`]: super::text::DisplayOnly
+//! [`AllowShortcuts(false)`]: super::text::AllowShortcuts
+
+use super::{
+    DevicePath, DevicePathNode, DevicePathNodeEnum, DeviceSubType, DeviceType, acpi,
+    bios_boot_spec, hardware, media, messaging,
+};
+use crate::Guid;
+use core::fmt::{self, Display, Formatter, Write};
+
+/// Helper to display a [`DevicePath`] as text, returned by
+/// [`DevicePath::display`].
+#[derive(Clone, Copy, Debug)]
+pub struct DevicePathDisplay<'a>(pub(super) &'a DevicePath);
+
+impl Display for DevicePathDisplay<'_> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        let mut first = true;
+        for node in self.0.node_iter() {
+            if node.full_type() == (DeviceType::END, DeviceSubType::END_INSTANCE) {
+                f.write_char(',')?;
+                first = true;
+                continue;
+            }
+            if !first {
+                f.write_char('/')?;
+            }
+            first = false;
+            write_node(f, node)?;
+        }
+        Ok(())
+    }
+}
+
+/// Helper to display a [`DevicePathNode`] as text, returned by
+/// [`DevicePathNode::display`].
+#[derive(Clone, Copy, Debug)]
+pub struct DevicePathNodeDisplay<'a>(pub(super) &'a DevicePathNode);
+
+impl Display for DevicePathNodeDisplay<'_> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write_node(f, self.0)
+    }
+}
+
+fn write_node(f: &mut Formatter<'_>, node: &DevicePathNode) -> fmt::Result {
+    use DevicePathNodeEnum as N;
+
+    let Ok(node_enum) = node.as_enum() else {
+        return write_generic(f, node);
+    };
+    match node_enum {
+        N::EndInstance(_) => f.write_char(','),
+        N::EndEntire(_) => Ok(()),
+        N::HardwarePci(n) => write!(f, "Pci({:#X},{:#X})", n.device(), n.function()),
+        N::HardwarePccard(n) => write!(f, "PcCard({:#X})", n.function()),
+        N::HardwareMemoryMapped(n) => write!(
+            f,
+            "MemoryMapped({:#X},{:#X},{:#X})",
+            n.memory_type().0,
+            n.start_address(),
+            n.end_address()
+        ),
+        N::HardwareVendor(n) => write_vendor(f, "VenHw", n.vendor_guid(), n.vendor_defined_data()),
+        N::HardwareController(n) => write!(f, "Ctrl({:#X})", n.controller_number()),
+        N::HardwareBmc(n) => write_bmc(f, n),
+        N::AcpiAcpi(n) => write_acpi(f, n),
+        N::AcpiExpanded(n) => write_acpi_expanded(f, n),
+        N::AcpiAdr(n) => {
+            f.write_str("AcpiAdr(")?;
+            for (i, adr) in n.adr().iter().enumerate() {
+                if i != 0 {
+                    f.write_char(',')?;
+                }
+                write!(f, "{adr:#X}")?;
+            }
+            f.write_char(')')
+        }
+        N::MessagingAtapi(n) => {
+            let channel = if n.primary_secondary() == messaging::PrimarySecondary::PRIMARY {
+                "Primary"
+            } else {
+                "Secondary"
+            };
+            let device = if n.master_slave() == messaging::MasterSlave::MASTER {
+                "Master"
+            } else {
+                "Slave"
+            };
+            write!(f, "Ata({channel},{device},{:#X})", n.logical_unit_number())
+        }
+        N::MessagingScsi(n) => write!(
+            f,
+            "Scsi({:#X},{:#X})",
+            n.target_id(),
+            n.logical_unit_number()
+        ),
+        N::MessagingFibreChannel(n) => write!(
+            f,
+            "Fibre({:#X},{:#X})",
+            n.world_wide_name(),
+            n.logical_unit_number()
+        ),
+        N::MessagingFibreChannelEx(n) => {
+            f.write_str("FibreEx(0x")?;
+            write_hex(f, &n.world_wide_name())?;
+            f.write_str(",0x")?;
+            write_hex(f, &n.logical_unit_number())?;
+            f.write_char(')')
+        }
+        N::MessagingIeee1394(n) => write!(f, "I1394({:016X})", u64::from_le_bytes(n.guid())),
+        N::MessagingUsb(n) => write!(f, "USB({:#X},{:#X})", n.parent_port_number(), n.interface()),
+        N::MessagingSata(n) => write!(
+            f,
+            "Sata({:#X},{:#X},{:#X})",
+            n.hba_port_number(),
+            n.port_multiplier_port_number(),
+            n.logical_unit_number()
+        ),
+        N::MessagingUsbWwid(n) => {
+            write!(
+                f,
+                "UsbWwid({:#X},{:#X},{:#X},\"",
+                n.device_vendor_id(),
+                n.device_product_id(),
+                n.interface_number()
+            )?;
+            write_ucs2(f, n.serial_number())?;
+            f.write_str("\")")
+        }
+        N::MessagingDeviceLogicalUnit(n) => write!(f, "Unit({:#X})", n.logical_unit_number()),
+        N::MessagingUsbClass(n) => write!(
+            f,
+            "UsbClass({:#X},{:#X},{:#X},{:#X},{:#X})",
+            n.vendor_id(),
+            n.product_id(),
+            n.device_class(),
+            n.device_subclass(),
+            n.device_protocol()
+        ),
+        N::MessagingI2o(n) => write!(f, "I2O({:#X})", n.target_id()),
+        N::MessagingMacAddress(n) => {
+            // Ethernet and IEEE 802.3 addresses have six bytes, the size of
+            // other addresses is unknown.
+            let address = n.mac_address();
+            let len = if n.interface_type() <= 1 { 6 } else { 32 };
+            f.write_str("MAC(")?;
+            write_hex(f, &address[..len])?;
+            write!(f, ",{:#X})", n.interface_type())
+        }
+        N::MessagingIpv4(n) => write_ipv4(f, n),
+        N::MessagingIpv6(n) => write_ipv6(f, n),
+        N::MessagingVlan(n) => write!(f, "Vlan({})", n.vlan_id()),
+        N::MessagingInfiniband(n) => write!(
+            f,
+            "Infiniband({:#X},{},{:#X},{:#X},{:#X})",
+            n.resource_flags().bits(),
+            GuidText(Guid::from_bytes(n.port_gid())),
+            n.ioc_guid_or_service_id(),
+            n.target_port_id(),
+            n.device_id()
+        ),
+        N::MessagingUart(n) => write_uart(f, n),
+        N::MessagingVendor(n) => {
+            write_vendor(f, "VenMsg", n.vendor_guid(), n.vendor_defined_data())
+        }
+        N::MessagingNvmeNamespace(n) => {
+            write!(f, "NVMe({:#X},", n.namespace_identifier())?;
+            let eui = n.ieee_extended_unique_identifier().to_le_bytes();
+            for (i, byte) in eui.iter().rev().enumerate() {
+                if i != 0 {
+                    f.write_char('-')?;
+                }
+                write!(f, "{byte:02X}")?;
+            }
+            f.write_char(')')
+        }
+        N::MessagingUri(n) => {
+            f.write_str("Uri(")?;
+            write_ascii(f, n.value())?;
+            f.write_char(')')
+        }
+        N::MessagingUfs(n) => write!(
+            f,
+            "UFS({:#X},{:#X})",
+            n.target_id(),
+            n.logical_unit_number()
+        ),
+        N::MessagingSd(n) => write!(f, "SD({:#X})", n.slot_number()),
+        N::MessagingEmmc(n) => write!(f, "eMMC({:#X})", n.slot_number()),
+        N::MessagingBluetooth(n) => {
+            let mut address = n.device_address();
+            address.reverse();
+            f.write_str("Bluetooth(")?;
+            write_hex(f, &address)?;
+            f.write_char(')')
+        }
+        N::MessagingBluetoothLe(n) => {
+            let mut address = n.device_address();
+            address.reverse();
+            f.write_str("BluetoothLE(")?;
+            write_hex(f, &address)?;
+            write!(f, ",{:#X})", n.address_type().0)
+        }
+        N::MessagingWifi(n) => {
+            f.write_str("Wi-Fi(")?;
+            write_ascii(f, &n.ssid())?;
+            f.write_char(')')
+        }
+        N::MediaHardDrive(n) => write_hard_drive(f, n),
+        N::MediaCdRom(n) => write!(
+            f,
+            "CDROM({:#X},{:#X},{:#X})",
+            n.boot_entry(),
+            n.partition_start(),
+            n.partition_size()
+        ),
+        N::MediaVendor(n) => write_vendor(f, "VenMedia", n.vendor_guid(), n.vendor_defined_data()),
+        N::MediaFilePath(n) => write_ucs2(f, n.path_name()),
+        N::MediaProtocol(n) => write!(f, "Media({})", GuidText(n.protocol_guid())),
+        N::MediaPiwgFirmwareFile(n) => match n.data().get(..16) {
+            Some(guid) => write!(f, "FvFile({})", GuidText::from_slice(guid)),
+            None => write_generic(f, node),
+        },
+        N::MediaPiwgFirmwareVolume(n) => match n.data().get(..16) {
+            Some(guid) => write!(f, "Fv({})", GuidText::from_slice(guid)),
+            None => write_generic(f, node),
+        },
+        N::MediaRelativeOffsetRange(n) => write!(
+            f,
+            "Offset({:#X},{:#X})",
+            n.starting_offset(),
+            n.ending_offset()
+        ),
+        N::MediaRamDisk(n) => write_ram_disk(f, n),
+        N::BiosBootSpecBootSpecification(n) => write_bbs(f, n),
+        _ => write_generic(f, node),
+    }
+}
+
+/// Writes a node in the generic form, e.g. `Msg(30,0102)`.
+fn write_generic(f: &mut Formatter<'_>, node: &DevicePathNode) -> fmt::Result {
+    let name = match node.device_type() {
+        DeviceType::HARDWARE => "HardwarePath",
+        DeviceType::ACPI => "AcpiPath",
+        DeviceType::MESSAGING => "Msg",
+        DeviceType::MEDIA => "MediaPath",
+        DeviceType::BIOS_BOOT_SPEC => "BbsPath",
+        _ => "",
+    };
+    if name.is_empty() {
+        write!(f, "Path({},{}", node.device_type().0, node.sub_type().0)?;
+    } else {
+        write!(f, "{name}({}", node.sub_type().0)?;
+    }
+    if !node.data().is_empty() {
+        f.write_char(',')?;
+        write_hex(f, node.data())?;
+    }
+    f.write_char(')')
+}
+
+fn write_vendor(f: &mut Formatter<'_>, name: &str, guid: Guid, data: &[u8]) -> fmt::Result {
+    write!(f, "{name}({}", GuidText(guid))?;
+    if !data.is_empty() {
+        f.write_char(',')?;
+        write_hex(f, data)?;
+    }
+    f.write_char(')')
+}
+
+fn write_bmc(f: &mut Formatter<'_>, node: &hardware::Bmc) -> fmt::Result {
+    write!(
+        f,
+        "BMC({},{:#X})",
+        node.interface_type().0,
+        node.base_address()
+    )
+}
+
+/// Formats a GUID in upper case, like EDK2 does.
+struct GuidText(Guid);
+
+impl GuidText {
+    fn from_slice(bytes: &[u8]) -> Self {
+        Self(Guid::from_bytes(bytes.try_into().unwrap()))
+    }
+}
+
+impl Display for GuidText {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        self.0
+            .to_ascii_hex_lower()
+            .iter()
+            .try_for_each(|c| f.write_char(char::from(c.to_ascii_uppercase())))
+    }
+}
+
+/// Returns whether `id` is a compressed EISA ID with the `PNP` prefix.
+const fn is_pnp_id(id: u32) -> bool {
+    id & 0xffff == 0x41d0
+}
+
+/// Writes a compressed EISA ID in the form `PNP0A03`.
+fn write_eisa_id(f: &mut Formatter<'_>, id: u32) -> fmt::Result {
+    for shift in [10, 5, 0] {
+        let c = ((id >> shift) & 0x1f) as u8 + b'A' - 1;
+        f.write_char(char::from(c))?;
+    }
+    write!(f, "{:04X}", id >> 16)
+}
+
+fn write_acpi(f: &mut Formatter<'_>, node: &acpi::Acpi) -> fmt::Result {
+    let (hid, uid) = (node.hid(), node.uid());
+    if !is_pnp_id(hid) {
+        return write!(f, "Acpi({hid:#010X},{uid:#X})");
+    }
+    let name = match hid >> 16 {
+        0x0a03 => "PciRoot",
+        0x0a08 => "PcieRoot",
+        0x0604 => "Floppy",
+        0x0301 => "Keyboard",
+        0x0501 => "Serial",
+        0x0401 => "ParallelPort",
+        num => return write!(f, "Acpi(PNP{num:04X},{uid:#X})"),
+    };
+    write!(f, "{name}({uid:#X})")
+}
+
+fn write_acpi_expanded(f: &mut Formatter<'_>, node: &acpi::Expanded) -> fmt::Result {
+    f.write_str("AcpiEx(")?;
+    write_eisa_id(f, node.hid())?;
+    f.write_char(',')?;
+    write_eisa_id(f, node.cid())?;
+    write!(f, ",{:#X},", node.uid())?;
+    write_ascii(f, node.hid_str())?;
+    f.write_char(',')?;
+    write_ascii(f, node.cid_str())?;
+    f.write_char(',')?;
+    write_ascii(f, node.uid_str())?;
+    f.write_char(')')
+}
+
+struct Ipv4Address([u8; 4]);
+
+impl Display for Ipv4Address {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        let [a, b, c, d] = self.0;
+        write!(f, "{a}.{b}.{c}.{d}")
+    }
+}
+
+struct Ipv6Address([u8; 16]);
+
+impl Display for Ipv6Address {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        for (i, group) in self.0.chunks_exact(2).enumerate() {
+            if i != 0 {
+                f.write_char(':')?;
+            }
+            write!(f, "{:X}", u16::from_be_bytes([group[0], group[1]]))?;
+        }
+        Ok(())
+    }
+}
+
+fn write_protocol(f: &mut Formatter<'_>, protocol: u16) -> fmt::Result {
+    match protocol {
+        6 => f.write_str("TCP"),
+        17 => f.write_str("UDP"),
+        _ => write!(f, "{protocol:#X}"),
+    }
+}
+
+fn write_ipv4(f: &mut Formatter<'_>, node: &messaging::Ipv4) -> fmt::Result {
+    write!(f, "IPv4({},", Ipv4Address(node.remote_ip_address()))?;
+    write_protocol(f, node.protocol())?;
+    let origin = if node.ip_address_origin() == messaging::Ipv4AddressOrigin::STATIC {
+        "Static"
+    } else {
+        "DHCP"
+    };
+    write!(
+        f,
+        ",{origin},{},{},{})",
+        Ipv4Address(node.local_ip_address()),
+        Ipv4Address(node.gateway_ip_address()),
+        Ipv4Address(node.subnet_mask())
+    )
+}
+
+fn write_ipv6(f: &mut Formatter<'_>, node: &messaging::Ipv6) -> fmt::Result {
+    write!(f, "IPv6({},", Ipv6Address(node.remote_ip_address()))?;
+    write_protocol(f, node.protocol())?;
+    let origin = match node.ip_address_origin() {
+        messaging::Ipv6AddressOrigin::MANUAL => "Static",
+        messaging::Ipv6AddressOrigin::STATELESS_AUTO_CONFIGURATION => "StatelessAutoConfigure",
+        _ => "StatefulAutoConfigure",
+    };
+    write!(
+        f,
+        ",{origin},{},{},{:#X})",
+        Ipv6Address(node.local_ip_address()),
+        Ipv6Address(node.gateway_ip_address()),
+        node.prefix_length()
+    )
+}
+
+fn write_uart(f: &mut Formatter<'_>, node: &messaging::Uart) -> fmt::Result {
+    f.write_str("Uart(")?;
+    match node.baud_rate() {
+        0 => f.write_str("DEFAULT,")?,
+        baud_rate => write!(f, "{baud_rate},")?,
+    }
+    match node.data_bits() {
+        0 => f.write_str("DEFAULT,")?,
+        data_bits => write!(f, "{data_bits},")?,
+    }
+    let parity = match node.parity() {
+        messaging::Parity::DEFAULT => 'D',
+        messaging::Parity::NO => 'N',
+        messaging::Parity::EVEN => 'E',
+        messaging::Parity::ODD => 'O',
+        messaging::Parity::MARK => 'M',
+        messaging::Parity::SPACE => 'S',
+        _ => 'x',
+    };
+    let stop_bits = match node.stop_bits() {
+        messaging::StopBits::DEFAULT => "D",
+        messaging::StopBits::ONE => "1",
+        messaging::StopBits::ONE_POINT_FIVE => "1.5",
+        messaging::StopBits::TWO => "2",
+        _ => "x",
+    };
+    write!(f, "{parity},{stop_bits})")
+}
+
+fn write_hard_drive(f: &mut Formatter<'_>, node: &media::HardDrive) -> fmt::Result {
+    write!(f, "HD({},", node.partition_number())?;
+    match node.partition_signature() {
+        media::PartitionSignature::Mbr(signature) => {
+            write!(f, "MBR,{:#010X}", u32::from_le_bytes(signature))?;
+        }
+        media::PartitionSignature::Guid(guid) => write!(f, "GPT,{}", GuidText(guid))?,
+        media::PartitionSignature::None => f.write_str("0,0")?,
+        media::PartitionSignature::Unknown { signature_type, .. } => {
+            write!(f, "{signature_type},0")?;
+        }
+    }
+    write!(
+        f,
+        ",{:#X},{:#X})",
+        node.partition_start(),
+        node.partition_size()
+    )
+}
+
+fn write_ram_disk(f: &mut Formatter<'_>, node: &media::RamDisk) -> fmt::Result {
+    let name = match node.disk_type() {
+        media::RamDiskType::VIRTUAL_DISK => "VirtualDisk",
+        media::RamDiskType::VIRTUAL_CD => "VirtualCD",
+        media::RamDiskType::PERSISTENT_VIRTUAL_DISK => "PersistentVirtualDisk",
+        media::RamDiskType::PERSISTENT_VIRTUAL_CD => "PersistentVirtualCD",
+        disk_type => {
+            return write!(
+                f,
+                "RamDisk({:#X},{:#X},{},{})",
+                node.starting_address(),
+                node.ending_address(),
+                node.disk_instance(),
+                GuidText(disk_type.0)
+            );
+        }
+    };
+    write!(
+        f,
+        "{name}({:#X},{:#X},{})",
+        node.starting_address(),
+        node.ending_address(),
+        node.disk_instance()
+    )
+}
+
+fn write_bbs(f: &mut Formatter<'_>, node: &bios_boot_spec::BootSpecification) -> fmt::Result {
+    f.write_str("BBS(")?;
+    match node.device_type() {
+        1 => f.write_str("Floppy")?,
+        2 => f.write_str("HD")?,
+        3 => f.write_str("CDROM")?,
+        4 => f.write_str("PCMCIA")?,
+        5 => f.write_str("USB")?,
+        6 => f.write_str("Network")?,
+        device_type => write!(f, "{device_type:#X}")?,
+    }
+    f.write_char(',')?;
+    write_ascii(f, node.description_string())?;
+    write!(f, ",{:#X})", node.status_flag())
+}
+
+/// Writes bytes as hexadecimal digits.
+fn write_hex(f: &mut Formatter<'_>, bytes: &[u8]) -> fmt::Result {
+    bytes.iter().try_for_each(|byte| write!(f, "{byte:02X}"))
+}
+
+/// Writes a string of ASCII characters, which ends at the first null
+/// character, if any. Other characters are replaced with `?`.
+fn write_ascii(f: &mut Formatter<'_>, bytes: &[u8]) -> fmt::Result {
+    bytes
+        .iter()
+        .take_while(|b| **b != 0)
+        .map(|b| if b.is_ascii() { char::from(*b) } else { '?' })
+        .try_for_each(|c| f.write_char(c))
+}
+
+/// Writes a UCS-2 string, which ends at the first null character, if any.
+/// Invalid characters are replaced with U+FFFD.
+fn write_ucs2(f: &mut Formatter<'_>, chars: impl IntoIterator<Item = u16>) -> fmt::Result {
+    char::decode_utf16(chars.into_iter().take_while(|c| *c != 0))
+        .map(|c| c.unwrap_or(char::REPLACEMENT_CHARACTER))
+        .try_for_each(|c| f.write_char(c))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::proto::device_path::build::{self, DevicePathBuilder};
+    use crate::{cstr16, guid};
+    use alloc::string::ToString;
+    use alloc::vec::Vec;
+
+    #[test]
+    fn test_display_path() {
+        let mut buf = Vec::new();
+        let path = DevicePathBuilder::with_vec(&mut buf)
+            .push(&build::acpi::Acpi {
+                hid: 0x0a03_41d0,
+                uid: 0,
+            })
+            .unwrap()
+            .push(&build::hardware::Pci {
+                function: 1,
+                device: 0x1f,
+            })
+            .unwrap()
+            .push(&build::messaging::Sata {
+                hba_port_number: 0,
+                port_multiplier_port_number: 0xffff,
+                logical_unit_number: 0,
+            })
+            .unwrap()
+            .push(&build::media::HardDrive {
+                partition_number: 1,
+                partition_start: 0x800,
+                partition_size: 0x10_0000,
+                partition_format: media::PartitionFormat::GPT,
+                partition_signature: media::PartitionSignature::Guid(guid!(
+                    "15e39a00-1dd2-1000-8d7f-00a0c92408fc"
+                )),
+            })
+            .unwrap()
+            .push(&build::media::FilePath {
+                path_name: cstr16!("\\EFI\\BOOT\\BOOTX64.EFI"),
+            })
+            .unwrap()
+            .push(&build::end::Instance)
+            .unwrap()
+            .push(&build::messaging::MacAddress {
+                mac_address: [
+                    0x52, 0x54, 0x00, 0x12, 0x34, 0x56, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0,
+                    0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0,
+                ],
+                interface_type: 1,
+            })
+            .unwrap()
+            .push(&build::messaging::Ipv4 {
+                local_ip_address: [192, 168, 0, 2],
+                remote_ip_address: [192, 168, 0, 1],
+                local_port: 0,
+                remote_port: 0,
+                protocol: 6,
+                ip_address_origin: messaging::Ipv4AddressOrigin::DHCP,
+                gateway_ip_address: [0; 4],
+                subnet_mask: [0; 4],
+            })
+            .unwrap()
+            .finalize()
+            .unwrap();
+
+        assert_eq!(
+            path.display().to_string(),
+            "PciRoot(0x0)/Pci(0x1F,0x1)/Sata(0x0,0xFFFF,0x0)/\
+             HD(1,GPT,15E39A00-1DD2-1000-8D7F-00A0C92408FC,0x800,0x100000)/\
+             \\EFI\\BOOT\\BOOTX64.EFI,\
+             MAC(525400123456,0x1)/IPv4(192.168.0.1,TCP,DHCP,192.168.0.2,0.0.0.0,0.0.0.0)"
+        );
+    }
+
+    #[test]
+    fn test_display_nodes() {
+        let display = |node: &dyn build::BuildNode| {
+            let mut buf = Vec::new();
+            let path = DevicePathBuilder::with_vec(&mut buf)
+                .push(node)
+                .unwrap()
+                .finalize()
+                .unwrap();
+            path.node_iter().next().unwrap().display().to_string()
+        };
+
+        assert_eq!(
+            display(&build::acpi::Acpi {
+                hid: 0x0501_41d0,
+                uid: 1
+            }),
+            "Serial(0x1)"
+        );
+        assert_eq!(
+            display(&build::acpi::Acpi {
+                hid: 0x0b00_41d0,
+                uid: 0
+            }),
+            "Acpi(PNP0B00,0x0)"
+        );
+        assert_eq!(
+            display(&build::acpi::Expanded {
+                hid: 0x0a08_41d0,
+                uid: 0,
+                cid: 0x0a03_41d0,
+                hid_str: b"\0",
+                uid_str: b"\0",
+                cid_str: b"\0",
+            }),
+            "AcpiEx(PNP0A08,PNP0A03,0x0,,,)"
+        );
+        assert_eq!(
+            display(&build::messaging::Usb {
+                parent_port_number: 2,
+                interface: 0
+            }),
+            "USB(0x2,0x0)"
+        );
+        assert_eq!(
+            display(&build::messaging::Uart {
+                baud_rate: 115_200,
+                data_bits: 8,
+                parity: messaging::Parity::NO,
+                stop_bits: messaging::StopBits::ONE,
+            }),
+            "Uart(115200,8,N,1)"
+        );
+        assert_eq!(
+            display(&build::messaging::NvmeNamespace {
+                namespace_identifier: 1,
+                ieee_extended_unique_identifier: 0x0102_0304_0506_0708,
+            }),
+            "NVMe(0x1,01-02-03-04-05-06-07-08)"
+        );
+        assert_eq!(
+            display(&build::media::HardDrive {
+                partition_number: 2,
+                partition_start: 0x3f,
+                partition_size: 0x1000,
+                partition_format: media::PartitionFormat::MBR,
+                partition_signature: media::PartitionSignature::Mbr([0x78, 0x56, 0x34, 0x12]),
+            }),
+            "HD(2,MBR,0x12345678,0x3F,0x1000)"
+        );
+        assert_eq!(
+            display(&build::hardware::Vendor {
+                vendor_guid: guid!("15e39a00-1dd2-1000-8d7f-00a0c92408fc"),
+                vendor_defined_data: &[0xab, 0x01],
+            }),
+            "VenHw(15E39A00-1DD2-1000-8D7F-00A0C92408FC,AB01)"
+        );
+    }
+
+    #[test]
+    fn test_display_generic() {
+        let mut raw = Vec::<u8>::new();
+        raw.extend_from_slice(&[0x03, 0x7e, 0x06, 0x00, 0x01, 0x02]);
+        raw.extend_from_slice(&[0x42, 0x01, 0x04, 0x00]);
+        raw.extend_from_slice(&[0x7f, 0xff, 0x04, 0x00]);
+        let path = unsafe { DevicePath::from_ffi_ptr(raw.as_ptr().cast()) };
+        assert_eq!(path.display().to_string(), "Msg(126,0102)/Path(66,1)");
+    }
+}
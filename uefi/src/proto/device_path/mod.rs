@@ -92,10 +92,12 @@ pub mod text;
 pub mod util;
 
 mod device_path_gen;
+mod display;
 
 pub use device_path_gen::{
     DevicePathNodeEnum, acpi, bios_boot_spec, end, hardware, media, messaging,
 };
+pub use display::{DevicePathDisplay, DevicePathNodeDisplay};
 pub use uefi_raw::protocol::device_path::{DeviceSubType, DeviceType};
 
 use crate::mem::PoolAllocation;
@@ -293,6 +295,18 @@ impl DevicePathNode {
         DevicePathNodeEnum::try_from(self)
     }
 
+    /// Returns an object that implements [`Display`] to print the node in
+    /// the text representation of the UEFI specification, e.g.
+    /// `Pci(0x1F,0x2)`.
+    ///
+    /// Unlike [`to_string`](Self::to_string), this doesn't need the
+    /// [`DevicePathToText`] protocol, which some firmware doesn't provide.
+    /// Only the common node types are converted to their specific form.
+    #[must_use]
+    pub const fn display(&self) -> DevicePathNodeDisplay<'_> {
+        DevicePathNodeDisplay(self)
+    }
+
     /// Transforms the device path node to its string representation using the
     /// [`DevicePathToText`] protocol.
     #[cfg(feature = "alloc")]
@@ -335,7 +349,9 @@ impl<'a> TryFrom<&'a [u8]> for &'a DevicePathNode {
 
     fn try_from(bytes: &[u8]) -> Result<Self, Self::Error> {
         let dp = <&DevicePathHeader>::try_from(bytes)?;
-        if usize::from(dp.length()) <= bytes.len() {
+        let len = usize::from(dp.length());
+        // A node can't be shorter than its header.
+        if (size_of::<DevicePathHeader>()..=bytes.len()).contains(&len) {
             unsafe { Ok(DevicePathNode::from_ffi_ptr(bytes.as_ptr().cast())) }
         } else {
             Err(ByteConversionError::InvalidLength)
@@ -497,7 +513,8 @@ impl DevicePath {
     ///
     /// The [`ByteConversionError::InvalidLength`] error will be returned
     /// when the length of the given bytes slice cannot contain the full
-    /// [`DevicePath`] represented by the slice.
+    /// [`DevicePath`] represented by the slice, or when a node is shorter
+    /// than its header.
     fn size_in_bytes_from_slice(mut bytes: &[u8]) -> Result<usize, ByteConversionError> {
         let max_size_in_bytes = bytes.len();
         let mut total_size_in_bytes: usize = 0;
@@ -572,6 +589,28 @@ impl DevicePath {
         unsafe { mem::transmute(data) }
     }
 
+    /// Returns an object that implements [`Display`] to print the device
+    /// path in the text representation of the UEFI specification, e.g.
+    /// `PciRoot(0x0)/Pci(0x1F,0x2)/Sata(0x0,0xFFFF,0x0)`.
+    ///
+    /// Unlike [`to_string`](Self::to_string), this doesn't need the
+    /// [`DevicePathToText`] protocol, which some firmware doesn't provide.
+    /// Only the common node types are converted to their specific form.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use uefi::proto::device_path::DevicePath;
+    ///
+    /// fn log_path(path: &DevicePath) {
+    ///     log::info!("{}", path.display());
+    /// }
+    /// ```
+    #[must_use]
+    pub const fn display(&self) -> DevicePathDisplay<'_> {
+        DevicePathDisplay(self)
+    }
+
     /// Transforms the device path to its string representation using the
     /// [`DevicePathToText`] protocol.
     #[cfg(feature = "alloc")]
@@ -991,6 +1030,14 @@ mod tests {
         check_node(nodes[4], 0xa3, 0xb3, &[40, 41, 42, 43]);
         // The end-entire node is not returned by the iterator.
         assert_eq!(nodes.len(), 5);
+
+        // A zero-length node is rejected rather than looped over.
+        let mut raw_data = raw_data;
+        raw_data[8..10].copy_from_slice(&[0, 0]);
+        assert_eq!(
+            <&DevicePath>::try_from(raw_data.as_slice()),
+            Err(ByteConversionError::InvalidLength)
+        );
     }
 
     #[test]
@@ -1067,6 +1114,12 @@ mod tests {
         // [`DevicePathNode`] data length exceeds the raw_data slice.
         raw_data[2] += 1;
         assert!(<&DevicePathNode>::try_from(raw_data.as_slice()).is_err());
+
+        // [`DevicePathNode`] length is smaller than its header.
+        for len in 0..4 {
+            raw_data[2] = len;
+            assert!(<&DevicePathNode>::try_from(raw_data.as_slice()).is_err());
+        }
     }
 
     #[test]
@@ -0,0 +1,637 @@
+// SPDX-License-Identifier: MIT OR Apache-2.0
+
+//! Key Management Service (KMS) protocol.
+
+use crate::proto::unsafe_protocol;
+use crate::{CStr16, Guid, Result, Status, StatusExt};
+use core::ffi::c_void;
+use core::marker::PhantomData;
+use core::{ptr, slice};
+use uefi_raw::protocol::kms::{KmsClientInfo, KmsKeyAttribute, KmsKeyDescriptor, KmsProtocol};
+
+pub use uefi_raw::protocol::kms::{KmsAttributeType, KmsDataType, KmsKeyFormat};
+
+/// Key Management Service protocol, which provides access to the keys of a
+/// key server, e.g. for unlocking storage.
+///
+/// Each instance of the protocol represents one service, identified by
+/// [`service_id`]. Before other functions are used, a client may have to
+/// register with [`register_client`].
+///
+/// Keys are identified by a binary key identifier, and are passed in
+/// [`KeyDescriptor`]s. Functions that operate on several keys report the
+/// status of each key in its descriptor. Keys may also have attributes,
+/// which are passed in [`KeyAttribute`]s.
+///
+/// Client data, which is specific to a service, is not supported.
+///
+/// # Example
+///
+/// ```no_run
+/// use uefi::boot;
+/// use uefi::proto::kms::{KeyDescriptor, Kms, KmsClient, KmsKeyFormat};
+///
+/// # fn example() -> uefi::Result {
+/// let handle = boot::get_handle_for_protocol::<Kms>()?;
+/// let mut kms = boot::open_protocol_exclusive::<Kms>(handle)?;
+/// kms.service_status()?;
+///
+/// let client = KmsClient::new(b"disk-unlock")?;
+/// kms.register_client(&client)?;
+///
+/// let mut key = [0; 32];
+/// let mut keys = [KeyDescriptor::new(b"volume-0", KmsKeyFormat::GENERIC_256)?
+///     .with_value(&mut key)?];
+/// kms.get_keys(&client, &mut keys)?;
+/// # Ok(())
+/// # }
+/// ```
+///
+/// [`service_id`]: Self::service_id
+/// [`register_client`]: Self::register_client
+#[derive(Debug)]
+#[repr(transparent)]
+#[unsafe_protocol(KmsProtocol::GUID)]
+pub struct Kms(KmsProtocol);
+
+impl Kms {
+    /// Returns the GUID of the service.
+    #[must_use]
+    pub const fn service_id(&self) -> Guid {
+        self.0.service_id
+    }
+
+    /// Returns the name of the service, if it has one.
+    #[must_use]
+    pub fn service_name(&self) -> Option<&CStr16> {
+        let name = self.0.service_name;
+        (!name.is_null()).then(|| unsafe { CStr16::from_ptr(name.cast()) })
+    }
+
+    /// Returns the version of the service.
+    #[must_use]
+    pub const fn service_version(&self) -> u32 {
+        self.0.service_version
+    }
+
+    /// Returns whether clients must register with an identifier.
+    #[must_use]
+    pub fn client_id_required(&self) -> bool {
+        self.0.client_id_required.into()
+    }
+
+    /// Returns the maximum size of a key identifier.
+    #[must_use]
+    pub const fn key_id_max_size(&self) -> usize {
+        self.0.key_id_max_size
+    }
+
+    /// Returns the formats of the keys supported by the service.
+    #[must_use]
+    pub const fn key_formats(&self) -> &[KmsKeyFormat] {
+        if self.0.key_formats.is_null() {
+            return &[];
+        }
+        unsafe { slice::from_raw_parts(self.0.key_formats, self.0.key_formats_count) }
+    }
+
+    /// Returns whether the service supports key attributes.
+    #[must_use]
+    pub fn key_attributes_supported(&self) -> bool {
+        self.0.key_attributes_supported.into()
+    }
+
+    /// Checks whether the service is available. This may try to connect to
+    /// the key server.
+    ///
+    /// # Errors
+    ///
+    /// * [`Status::NOT_READY`]: the service is not available.
+    /// * [`Status::NO_MAPPING`]: the network is not configured.
+    /// * [`Status::NO_RESPONSE`]: the key server did not respond.
+    pub fn service_status(&mut self) -> Result {
+        unsafe { (self.0.get_service_status)(&mut self.0) }.to_result()
+    }
+
+    /// Registers `client` with the service.
+    ///
+    /// # Errors
+    ///
+    /// * [`Status::INVALID_PARAMETER`]: the identity of `client` is not valid
+    ///   for the service.
+    /// * [`Status::NOT_READY`]: the service is not available.
+    /// * [`Status::ACCESS_DENIED`]: the client was rejected.
+    /// * [`Status::OUT_OF_RESOURCES`]: there is not enough memory.
+    pub fn register_client(&mut self, client: &KmsClient) -> Result {
+        let mut info = client.info();
+        unsafe {
+            (self.0.register_client)(&mut self.0, &mut info, ptr::null_mut(), ptr::null_mut())
+        }
+        .to_result()
+    }
+
+    /// Creates keys on the key server. The identifier and the value of each
+    /// new key are written to its descriptor, which should be created with
+    /// [`KeyDescriptor::new_generated`].
+    ///
+    /// # Errors
+    ///
+    /// See [`get_keys`](Self::get_keys).
+    pub fn create_keys(&mut self, client: &KmsClient, keys: &mut [KeyDescriptor]) -> Result {
+        self.call_keys(self.0.create_key, client, keys)
+    }
+
+    /// Reads the value of the keys with the identifiers in `keys`.
+    ///
+    /// # Errors
+    ///
+    /// * [`Status::INVALID_PARAMETER`]: a descriptor is not valid for the
+    ///   operation, or there are more than [`u16::MAX`] keys.
+    /// * [`Status::NOT_READY`]: the service is not available.
+    /// * [`Status::ACCESS_DENIED`]: the client is not authorized.
+    /// * [`Status::NOT_FOUND`]: a key was not found.
+    /// * [`Status::DEVICE_ERROR`]: the request failed for some keys. The
+    ///   status of each key is returned by [`KeyDescriptor::status`].
+    pub fn get_keys(&mut self, client: &KmsClient, keys: &mut [KeyDescriptor]) -> Result {
+        self.call_keys(self.0.get_key, client, keys)
+    }
+
+    /// Adds the keys in `keys` to the key server.
+    ///
+    /// # Errors
+    ///
+    /// See [`get_keys`](Self::get_keys).
+    pub fn add_keys(&mut self, client: &KmsClient, keys: &mut [KeyDescriptor]) -> Result {
+        self.call_keys(self.0.add_key, client, keys)
+    }
+
+    /// Deletes the keys with the identifiers in `keys` from the key server.
+    /// The descriptors don't need a value buffer.
+    ///
+    /// # Errors
+    ///
+    /// See [`get_keys`](Self::get_keys).
+    pub fn delete_keys(&mut self, client: &KmsClient, keys: &mut [KeyDescriptor]) -> Result {
+        self.call_keys(self.0.delete_key, client, keys)
+    }
+
+    fn call_keys(
+        &mut self,
+        f: unsafe extern "efiapi" fn(
+            *mut KmsProtocol,
+            *mut KmsClientInfo,
+            *mut u16,
+            *mut KmsKeyDescriptor,
+            *mut usize,
+            *mut *mut c_void,
+        ) -> Status,
+        client: &KmsClient,
+        keys: &mut [KeyDescriptor],
+    ) -> Result {
+        let mut count = u16::try_from(keys.len()).map_err(|_| Status::INVALID_PARAMETER)?;
+        let mut info = client.info();
+        unsafe {
+            (f)(
+                &mut self.0,
+                &mut info,
+                &mut count,
+                keys.as_mut_ptr().cast(),
+                ptr::null_mut(),
+                ptr::null_mut(),
+            )
+        }
+        .to_result()
+    }
+
+    /// Reads the attributes in `attributes` of the key `key_id`, and returns
+    /// the number of attributes read.
+    ///
+    /// # Errors
+    ///
+    /// * [`Status::INVALID_PARAMETER`]: `key_id` or `attributes` is too long.
+    /// * [`Status::NOT_READY`]: the service is not available.
+    /// * [`Status::ACCESS_DENIED`]: the client is not authorized.
+    /// * [`Status::NOT_FOUND`]: the key or an attribute was not found.
+    /// * [`Status::UNSUPPORTED`]: the service doesn't support attributes.
+    pub fn get_key_attributes(
+        &mut self,
+        client: &KmsClient,
+        key_id: &[u8],
+        attributes: &mut [KeyAttribute],
+    ) -> Result<usize> {
+        self.call_attributes(self.0.get_key_attributes, client, key_id, attributes)
+    }
+
+    /// Adds the attributes in `attributes` to the key `key_id`.
+    ///
+    /// # Errors
+    ///
+    /// See [`get_key_attributes`](Self::get_key_attributes).
+    pub fn add_key_attributes(
+        &mut self,
+        client: &KmsClient,
+        key_id: &[u8],
+        attributes: &mut [KeyAttribute],
+    ) -> Result {
+        self.call_attributes(self.0.add_key_attributes, client, key_id, attributes)
+            .map(|_| ())
+    }
+
+    /// Deletes the attributes in `attributes` from the key `key_id`.
+    ///
+    /// # Errors
+    ///
+    /// See [`get_key_attributes`](Self::get_key_attributes).
+    pub fn delete_key_attributes(
+        &mut self,
+        client: &KmsClient,
+        key_id: &[u8],
+        attributes: &mut [KeyAttribute],
+    ) -> Result {
+        self.call_attributes(self.0.delete_key_attributes, client, key_id, attributes)
+            .map(|_| ())
+    }
+
+    fn call_attributes(
+        &mut self,
+        f: unsafe extern "efiapi" fn(
+            *mut KmsProtocol,
+            *mut KmsClientInfo,
+            *mut u8,
+            *const c_void,
+            *mut u16,
+            *mut KmsKeyAttribute,
+            *mut usize,
+            *mut *mut c_void,
+        ) -> Status,
+        client: &KmsClient,
+        key_id: &[u8],
+        attributes: &mut [KeyAttribute],
+    ) -> Result<usize> {
+        let mut id_size = u8::try_from(key_id.len()).map_err(|_| Status::INVALID_PARAMETER)?;
+        let mut count = u16::try_from(attributes.len()).map_err(|_| Status::INVALID_PARAMETER)?;
+        let mut info = client.info();
+        unsafe {
+            (f)(
+                &mut self.0,
+                &mut info,
+                &mut id_size,
+                key_id.as_ptr().cast(),
+                &mut count,
+                attributes.as_mut_ptr().cast(),
+                ptr::null_mut(),
+                ptr::null_mut(),
+            )
+        }
+        .to_result_with_val(|| usize::from(count))
+    }
+
+    /// Finds the keys that have all attributes in `attributes`. The keys
+    /// are written to `keys`, and their number is returned.
+    ///
+    /// # Errors
+    ///
+    /// * [`Status::BUFFER_TOO_SMALL`]: `keys` is too small. The required
+    ///   number of descriptors is returned in the error data.
+    /// * [`Status::INVALID_PARAMETER`]: a descriptor or attribute is not
+    ///   valid.
+    /// * [`Status::NOT_READY`]: the service is not available.
+    /// * [`Status::ACCESS_DENIED`]: the client is not authorized.
+    /// * [`Status::NOT_FOUND`]: no key has the attributes.
+    pub fn get_keys_by_attributes(
+        &mut self,
+        client: &KmsClient,
+        attributes: &mut [KeyAttribute],
+        keys: &mut [KeyDescriptor],
+    ) -> Result<usize, Option<usize>> {
+        let mut attribute_count = attributes.len();
+        let mut count = keys.len();
+        let mut info = client.info();
+        unsafe {
+            (self.0.get_key_by_attributes)(
+                &mut self.0,
+                &mut info,
+                &mut attribute_count,
+                attributes.as_mut_ptr().cast(),
+                &mut count,
+                keys.as_mut_ptr().cast(),
+                ptr::null_mut(),
+                ptr::null_mut(),
+            )
+        }
+        .to_result_with(
+            || count,
+            |status| (status == Status::BUFFER_TOO_SMALL).then_some(count),
+        )
+    }
+}
+
+/// Identity of a client of a [`Kms`] service.
+///
+/// The client name is passed as a UCS-2 string.
+#[derive(Debug)]
+pub struct KmsClient<'a> {
+    id: &'a [u8],
+    name: Option<&'a CStr16>,
+}
+
+impl<'a> KmsClient<'a> {
+    /// Creates a client with the identifier `id`. An empty identifier is
+    /// passed as no identifier.
+    ///
+    /// # Errors
+    ///
+    /// * [`Status::INVALID_PARAMETER`]: `id` is longer than [`u16::MAX`]
+    ///   bytes.
+    pub fn new(id: &'a [u8]) -> Result<Self> {
+        u16::try_from(id.len()).map_err(|_| Status::INVALID_PARAMETER)?;
+        Ok(Self { id, name: None })
+    }
+
+    /// Sets the name of the client.
+    ///
+    /// # Errors
+    ///
+    /// * [`Status::INVALID_PARAMETER`]: `name` is longer than [`u8::MAX`]
+    ///   characters.
+    pub fn with_name(self, name: &'a CStr16) -> Result<Self> {
+        u8::try_from(name.num_chars()).map_err(|_| Status::INVALID_PARAMETER)?;
+        Ok(Self {
+            name: Some(name),
+            ..self
+        })
+    }
+
+    const fn info(&self) -> KmsClientInfo {
+        let (name_type, name_count, name) = match self.name {
+            Some(name) => (
+                KmsDataType::UNICODE,
+                name.num_chars() as u8,
+                name.as_ptr().cast_mut().cast(),
+            ),
+            None => (KmsDataType::NONE, 0, ptr::null_mut()),
+        };
+        KmsClientInfo {
+            client_id_size: self.id.len() as u16,
+            client_id: if self.id.is_empty() {
+                ptr::null_mut()
+            } else {
+                self.id.as_ptr().cast_mut().cast()
+            },
+            client_name_type: name_type,
+            client_name_count: name_count,
+            client_name: name,
+        }
+    }
+}
+
+/// Description of a key passed to a [`Kms`] service.
+///
+/// The buffer of the key value must be at least as large as the key size of
+/// its format, so only the generic formats can be used with a value buffer.
+#[derive(Debug)]
+#[repr(transparent)]
+pub struct KeyDescriptor<'a> {
+    raw: KmsKeyDescriptor,
+    _lifetime: PhantomData<&'a mut [u8]>,
+}
+
+impl<'a> KeyDescriptor<'a> {
+    /// Creates a descriptor of the key `key_id` in `format`, without a value
+    /// buffer.
+    ///
+    /// # Errors
+    ///
+    /// * [`Status::INVALID_PARAMETER`]: `key_id` is longer than [`u8::MAX`]
+    ///   bytes.
+    pub fn new(key_id: &'a [u8], format: KmsKeyFormat) -> Result<Self> {
+        let size = u8::try_from(key_id.len()).map_err(|_| Status::INVALID_PARAMETER)?;
+        Ok(Self::from_parts(size, key_id.as_ptr().cast_mut(), format))
+    }
+
+    /// Creates a descriptor of a key in `format` whose identifier is
+    /// generated by the service and written to `key_id`. The buffer should
+    /// be at least [`Kms::key_id_max_size`] bytes long.
+    #[must_use]
+    pub const fn new_generated(key_id: &'a mut [u8], format: KmsKeyFormat) -> Self {
+        Self::from_parts(0, key_id.as_mut_ptr(), format)
+    }
+
+    const fn from_parts(size: u8, key_id: *mut u8, format: KmsKeyFormat) -> Self {
+        Self {
+            raw: KmsKeyDescriptor {
+                key_identifier_size: size,
+                key_identifier: key_id.cast(),
+                key_format: format,
+                key_value: ptr::null_mut(),
+                key_status: Status::SUCCESS,
+            },
+            _lifetime: PhantomData,
+        }
+    }
+
+    /// Sets the buffer of the key value. The value is read from it when
+    /// keys are added, and written to it otherwise.
+    ///
+    /// # Errors
+    ///
+    /// * [`Status::UNSUPPORTED`]: the format is not a generic format, so its
+    ///   key size is not known.
+    /// * [`Status::BAD_BUFFER_SIZE`]: `value` is smaller than the key size.
+    pub fn with_value(mut self, value: &'a mut [u8]) -> Result<Self> {
+        let size = key_size(self.raw.key_format).ok_or(Status::UNSUPPORTED)?;
+        if value.len() < size {
+            return Err(Status::BAD_BUFFER_SIZE.into());
+        }
+        self.raw.key_value = value.as_mut_ptr().cast();
+        Ok(self)
+    }
+
+    /// Returns the identifier of the key.
+    #[must_use]
+    pub fn key_id(&self) -> &[u8] {
+        let size = usize::from(self.raw.key_identifier_size);
+        if size == 0 {
+            return &[];
+        }
+        unsafe { slice::from_raw_parts(self.raw.key_identifier.cast(), size) }
+    }
+
+    /// Returns the format of the key.
+    #[must_use]
+    pub const fn format(&self) -> KmsKeyFormat {
+        self.raw.key_format
+    }
+
+    /// Returns the status of the last operation on the key.
+    pub const fn status(&self) -> Status {
+        self.raw.key_status
+    }
+}
+
+/// Returns the size in bytes of a key in `format`.
+const fn key_size(format: KmsKeyFormat) -> Option<usize> {
+    let bits = match format {
+        KmsKeyFormat::GENERIC_128 => 128,
+        KmsKeyFormat::GENERIC_160 => 160,
+        KmsKeyFormat::GENERIC_256 => 256,
+        KmsKeyFormat::GENERIC_512 => 512,
+        KmsKeyFormat::GENERIC_1024 => 1024,
+        KmsKeyFormat::GENERIC_2048 => 2048,
+        KmsKeyFormat::GENERIC_3072 => 3072,
+        _ => return None,
+    };
+    Some(bits / 8)
+}
+
+/// Attribute of a key passed to a [`Kms`] service.
+///
+/// The attribute is identified by a UCS-2 name and an instance number,
+/// which starts at 1.
+#[derive(Debug)]
+#[repr(transparent)]
+pub struct KeyAttribute<'a> {
+    raw: KmsKeyAttribute,
+    _lifetime: PhantomData<&'a mut [u8]>,
+}
+
+impl<'a> KeyAttribute<'a> {
+    /// Creates the first instance of the attribute `name`, without a value.
+    ///
+    /// # Errors
+    ///
+    /// * [`Status::INVALID_PARAMETER`]: `name` is longer than [`u16::MAX`]
+    ///   characters.
+    pub fn new(name: &'a CStr16) -> Result<Self> {
+        let count = u16::try_from(name.num_chars()).map_err(|_| Status::INVALID_PARAMETER)?;
+        Ok(Self {
+            raw: KmsKeyAttribute {
+                key_attribute_identifier_type: KmsDataType::UNICODE,
+                key_attribute_identifier_count: count,
+                key_attribute_identifier: name.as_ptr().cast_mut().cast(),
+                key_attribute_instance: 1,
+                key_attribute_type: KmsAttributeType::NONE,
+                key_attribute_value_size: 0,
+                key_attribute_value: ptr::null_mut(),
+                key_attribute_status: Status::SUCCESS,
+            },
+            _lifetime: PhantomData,
+        })
+    }
+
+    /// Sets the instance of the attribute.
+    #[must_use]
+    pub const fn with_instance(mut self, instance: u16) -> Self {
+        self.raw.key_attribute_instance = instance;
+        self
+    }
+
+    /// Sets the type and the value buffer of the attribute. The value is
+    /// read from it when attributes are added or searched for, and written
+    /// to it when they are read.
+    ///
+    /// # Errors
+    ///
+    /// * [`Status::INVALID_PARAMETER`]: `value` is longer than [`u16::MAX`]
+    ///   bytes.
+    pub fn with_value(
+        mut self,
+        attribute_type: KmsAttributeType,
+        value: &'a mut [u8],
+    ) -> Result<Self> {
+        self.raw.key_attribute_value_size =
+            u16::try_from(value.len()).map_err(|_| Status::INVALID_PARAMETER)?;
+        self.raw.key_attribute_type = attribute_type;
+        self.raw.key_attribute_value = value.as_mut_ptr().cast();
+        Ok(self)
+    }
+
+    /// Returns the type of the attribute.
+    #[must_use]
+    pub const fn attribute_type(&self) -> KmsAttributeType {
+        self.raw.key_attribute_type
+    }
+
+    /// Returns the size of the value of the attribute.
+    #[must_use]
+    pub fn value_size(&self) -> usize {
+        usize::from(self.raw.key_attribute_value_size)
+    }
+
+    /// Returns the status of the last operation on the attribute.
+    pub const fn status(&self) -> Status {
+        self.raw.key_attribute_status
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cstr16;
+    use alloc::vec;
+    use alloc::vec::Vec;
+
+    #[test]
+    fn test_oversized_client() {
+        let id = vec![0; usize::from(u16::MAX) + 1];
+        assert_eq!(
+            KmsClient::new(&id).unwrap_err().status(),
+            Status::INVALID_PARAMETER
+        );
+        assert!(KmsClient::new(&id[1..]).is_ok());
+
+        let name: Vec<u16> = vec![u16::from(b'a'); 256].into_iter().chain([0]).collect();
+        let name = CStr16::from_u16_with_nul(&name).unwrap();
+        let client = KmsClient::new(b"client").unwrap();
+        assert_eq!(
+            client.with_name(name).unwrap_err().status(),
+            Status::INVALID_PARAMETER
+        );
+    }
+
+    #[test]
+    fn test_oversized_key() {
+        let key_id = [0; 256];
+        assert_eq!(
+            KeyDescriptor::new(&key_id, KmsKeyFormat::GENERIC_128)
+                .unwrap_err()
+                .status(),
+            Status::INVALID_PARAMETER
+        );
+
+        let mut value = [0; 15];
+        let key = KeyDescriptor::new(&key_id[..255], KmsKeyFormat::GENERIC_128).unwrap();
+        assert_eq!(
+            key.with_value(&mut value).unwrap_err().status(),
+            Status::BAD_BUFFER_SIZE
+        );
+
+        let mut value = [0; 16];
+        let key = KeyDescriptor::new(b"key", KmsKeyFormat(Guid::ZERO)).unwrap();
+        assert_eq!(
+            key.with_value(&mut value).unwrap_err().status(),
+            Status::UNSUPPORTED
+        );
+        let key = KeyDescriptor::new(b"key", KmsKeyFormat::GENERIC_128).unwrap();
+        assert_eq!(key.with_value(&mut value).unwrap().key_id(), b"key");
+    }
+
+    #[test]
+    fn test_oversized_attribute() {
+        let mut value = vec![0; usize::from(u16::MAX) + 1];
+        let attribute = KeyAttribute::new(cstr16!("attr")).unwrap();
+        assert_eq!(
+            attribute
+                .with_value(KmsAttributeType::BYTE_STRING, &mut value)
+                .unwrap_err()
+                .status(),
+            Status::INVALID_PARAMETER
+        );
+
+        let attribute = KeyAttribute::new(cstr16!("attr")).unwrap();
+        let attribute = attribute
+            .with_value(KmsAttributeType::BYTE_STRING, &mut value[1..])
+            .unwrap();
+        assert_eq!(attribute.value_size(), usize::from(u16::MAX));
+    }
+}
@@ -12,6 +12,14 @@ use core::ffi::c_void;
 use core::{mem, slice};
 use uefi_raw::protocol::loaded_image::LoadedImageProtocol;
 
+#[cfg(feature = "alloc")]
+use {
+    crate::proto::shell_params::{push_arg, split_command_line},
+    crate::{CString16, Char16},
+    alloc::vec::Vec,
+    core::ops::Deref,
+};
+
 /// The LoadedImage protocol. This can be opened on any image handle using the `HandleProtocol` boot service.
 #[derive(Debug)]
 #[repr(transparent)]
@@ -38,6 +46,16 @@ impl LoadedImage {
         unsafe { Handle::from_ptr(self.0.device_handle) }
     }
 
+    /// Sets the handle of the device on which the image is located. This
+    /// can be used prior to calling [`boot::start_image`] for images that
+    /// were loaded from a buffer, so that they can load files relative to
+    /// the device of their parent.
+    ///
+    /// [`boot::start_image`]: crate::boot::start_image
+    pub const fn set_device(&mut self, device: Handle) {
+        self.0.device_handle = device.as_ptr();
+    }
+
     /// Get a reference to the `file_path` portion of the DeviceHandle that the
     /// EFI image was loaded from.
     ///
@@ -84,6 +102,19 @@ impl LoadedImage {
         }
     }
 
+    /// Splits the load options of the image into arguments, with the quoting
+    /// rules of [`split_command_line`].
+    ///
+    /// Images that are started by the UEFI Shell can also get the arguments
+    /// from the [`ShellParameters`] protocol.
+    ///
+    /// [`ShellParameters`]: crate::proto::shell_params::ShellParameters
+    /// [`split_command_line`]: crate::proto::shell_params::split_command_line
+    #[cfg(feature = "alloc")]
+    pub fn load_options_args(&self) -> Result<Vec<CString16>, LoadOptionsError> {
+        self.load_options_as_cstr16().map(split_command_line)
+    }
+
     /// Get the load options of the image as raw bytes.
     ///
     /// UEFI allows arbitrary binary data in load options, but typically
@@ -172,6 +203,29 @@ impl LoadedImage {
         self.0.load_options_size = size;
     }
 
+    /// Sets the load options of the image to `options`, which the returned
+    /// guard keeps alive. This is the safe variant of
+    /// [`set_load_options`](Self::set_load_options).
+    ///
+    /// The previous load options are restored when the guard is dropped, so
+    /// it must be kept until the image has exited, i.e. until
+    /// [`boot::start_image`] returns.
+    ///
+    /// [`boot::start_image`]: crate::boot::start_image
+    #[cfg(feature = "alloc")]
+    pub fn set_owned_load_options(&mut self, options: LoadOptions) -> LoadOptionsGuard<'_> {
+        let previous = (self.0.load_options, self.0.load_options_size);
+        // The size fits into `u32`, see `LoadOptions::arg`.
+        let size = u32::try_from(options.0.num_bytes()).unwrap();
+        self.0.load_options = options.0.as_ptr().cast();
+        self.0.load_options_size = size;
+        LoadOptionsGuard {
+            image: self,
+            options,
+            previous,
+        }
+    }
+
     /// Returns the base address and the size in bytes of the loaded image.
     #[must_use]
     pub const fn info(&self) -> (*const c_void, u64) {
@@ -190,3 +244,128 @@ impl LoadedImage {
         self.0.image_data_type
     }
 }
+
+/// Builder of the load options of an image, see
+/// [`LoadedImage::set_owned_load_options`].
+///
+/// The load options are a command line of arguments that are quoted for
+/// [`split_command_line`].
+///
+/// # Example
+///
+/// ```
+/// use uefi::cstr16;
+/// use uefi::proto::loaded_image::LoadOptions;
+///
+/// let options = LoadOptions::new()
+///     .arg(cstr16!("kernel.efi"))
+///     .arg(cstr16!("root=LABEL=My Disk"));
+/// assert_eq!(*options, *cstr16!(r#"kernel.efi "root=LABEL=My Disk""#));
+/// ```
+///
+/// [`split_command_line`]: crate::proto::shell_params::split_command_line
+#[cfg(feature = "alloc")]
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct LoadOptions(CString16);
+
+#[cfg(feature = "alloc")]
+impl LoadOptions {
+    /// Creates empty load options.
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Appends the argument `arg`, quoted if needed.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the load options become larger than 4 GiB.
+    #[must_use]
+    pub fn arg(mut self, arg: &CStr16) -> Self {
+        if !self.0.is_empty() {
+            self.0.push(Char16::try_from(' ').unwrap());
+        }
+        push_arg(&mut self.0, arg);
+        assert!(
+            u32::try_from(self.0.num_bytes()).is_ok(),
+            "load options are too large"
+        );
+        self
+    }
+
+    /// Appends the arguments `args`, quoted if needed.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the load options become larger than 4 GiB.
+    #[must_use]
+    pub fn args<'a>(self, args: impl IntoIterator<Item = &'a CStr16>) -> Self {
+        args.into_iter().fold(self, Self::arg)
+    }
+}
+
+#[cfg(feature = "alloc")]
+impl From<CString16> for LoadOptions {
+    /// Uses `options` as the load options as is, without quoting.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the load options are larger than 4 GiB.
+    fn from(options: CString16) -> Self {
+        assert!(
+            u32::try_from(options.num_bytes()).is_ok(),
+            "load options are too large"
+        );
+        Self(options)
+    }
+}
+
+#[cfg(feature = "alloc")]
+impl Deref for LoadOptions {
+    type Target = CStr16;
+
+    fn deref(&self) -> &CStr16 {
+        &self.0
+    }
+}
+
+/// Guard of load options that have been set with
+/// [`LoadedImage::set_owned_load_options`]. The previous load options are
+/// restored when it is dropped.
+///
+/// The guard dereferences to the [`LoadedImage`].
+#[cfg(feature = "alloc")]
+#[derive(Debug)]
+pub struct LoadOptionsGuard<'a> {
+    image: &'a mut LoadedImage,
+    options: LoadOptions,
+    previous: (*const c_void, u32),
+}
+
+#[cfg(feature = "alloc")]
+impl LoadOptionsGuard<'_> {
+    /// Returns the load options that have been set.
+    #[must_use]
+    pub const fn options(&self) -> &LoadOptions {
+        &self.options
+    }
+}
+
+#[cfg(feature = "alloc")]
+impl Deref for LoadOptionsGuard<'_> {
+    type Target = LoadedImage;
+
+    fn deref(&self) -> &LoadedImage {
+        self.image
+    }
+}
+
+#[cfg(feature = "alloc")]
+impl Drop for LoadOptionsGuard<'_> {
+    fn drop(&mut self) {
+        let (options, size) = self.previous;
+        self.image.0.load_options = options;
+        self.image.0.load_options_size = size;
+    }
+}
@@ -3,9 +3,13 @@
 //! Block I/O protocols.
 
 use crate::proto::unsafe_protocol;
+use crate::util::opt_nonnull_to_ptr;
 use crate::{Result, StatusExt};
+use core::ptr::NonNull;
+#[cfg(feature = "alloc")]
+use {crate::Status, crate::mem::AlignedBuffer, crate::task, core::slice};
 
-pub use uefi_raw::protocol::block::{BlockIoProtocol, Lba};
+pub use uefi_raw::protocol::block::{BlockIo2Protocol, BlockIo2Token, BlockIoProtocol, Lba};
 
 /// The Block I/O protocol.
 #[derive(Debug)]
@@ -104,6 +108,226 @@ impl BlockIO {
     }
 }
 
+/// The Block I/O 2 protocol.
+///
+/// This protocol extends the [`BlockIO`] protocol with non-blocking
+/// requests, which signal the event of their token when done.
+#[derive(Debug)]
+#[repr(transparent)]
+#[unsafe_protocol(BlockIo2Protocol::GUID)]
+pub struct BlockIO2(BlockIo2Protocol);
+
+impl BlockIO2 {
+    /// Pointer for block IO media.
+    #[must_use]
+    pub const fn media(&self) -> &BlockIOMedia {
+        unsafe { &*self.0.media.cast::<BlockIOMedia>() }
+    }
+
+    /// Resets the block device hardware, and aborts all requests in
+    /// progress.
+    ///
+    /// # Arguments
+    /// * `extended_verification` Indicates that the driver may perform a more
+    ///   exhaustive verification operation of the device during reset.
+    ///
+    /// # Errors
+    /// * `uefi::Status::DEVICE_ERROR`  The block device is not functioning
+    ///   correctly and could not be reset.
+    pub fn reset(&mut self, extended_verification: bool) -> Result {
+        unsafe { (self.0.reset)(&mut self.0, extended_verification.into()) }.to_result()
+    }
+
+    /// Reads the requested number of blocks from the device.
+    ///
+    /// # Arguments
+    /// * `media_id` - The media ID that the read request is for.
+    /// * `lba` - The starting logical block address to read from on the device.
+    /// * `token` - Transaction token for the asynchronous read, or `None` for
+    ///   a blocking read.
+    /// * `len` - Buffer size.
+    /// * `buffer` - Buffer to read into.
+    ///
+    /// # Safety
+    ///
+    /// Because of the asynchronous nature of the transaction, the token and
+    /// the buffer must stay valid until the event of the token is signaled.
+    ///
+    /// # Errors
+    /// * `uefi::Status::DEVICE_ERROR`       The device reported an error while attempting to perform the read
+    ///   operation.
+    /// * `uefi::Status::NO_MEDIA`           There is no media in the device.
+    /// * `uefi::Status::MEDIA_CHANGED`      The `media_id` is not for the current media.
+    /// * `uefi::Status::BAD_BUFFER_SIZE`    The buffer size parameter is not a multiple of the intrinsic block size of
+    ///   the device.
+    /// * `uefi::Status::INVALID_PARAMETER`  The read request contains LBAs that are not valid, or the buffer is not on
+    ///   proper alignment.
+    /// * `uefi::Status::OUT_OF_RESOURCES`   The request could not be completed due to a lack of resources.
+    pub unsafe fn read_blocks_raw(
+        &self,
+        media_id: u32,
+        lba: Lba,
+        token: Option<NonNull<BlockIo2Token>>,
+        len: usize,
+        buffer: *mut u8,
+    ) -> Result {
+        let token = opt_nonnull_to_ptr(token);
+        unsafe { (self.0.read_blocks_ex)(&self.0, media_id, lba, token, len, buffer.cast()) }
+            .to_result()
+    }
+
+    /// Writes the requested number of blocks to the device.
+    ///
+    /// # Arguments
+    /// * `media_id` - The media ID that the write request is for.
+    /// * `lba` - The starting logical block address to be written.
+    /// * `token` - Transaction token for the asynchronous write, or `None` for
+    ///   a blocking write.
+    /// * `len` - Buffer size.
+    /// * `buffer` - Buffer to write from.
+    ///
+    /// # Safety
+    ///
+    /// Because of the asynchronous nature of the transaction, the token and
+    /// the buffer must stay valid until the event of the token is signaled.
+    ///
+    /// # Errors
+    /// * `uefi::Status::WRITE_PROTECTED`       The device cannot be written to.
+    /// * `uefi::Status::NO_MEDIA`              There is no media in the device.
+    /// * `uefi::Status::MEDIA_CHANGED`         The `media_id` is not for the current media.
+    /// * `uefi::Status::DEVICE_ERROR`          The device reported an error while attempting to perform the write
+    ///   operation.
+    /// * `uefi::Status::BAD_BUFFER_SIZE`       The buffer size parameter is not a multiple of the intrinsic block size
+    ///   of the device.
+    /// * `uefi::Status::INVALID_PARAMETER`     The write request contains LBAs that are not valid, or the buffer is not
+    ///   on proper alignment.
+    /// * `uefi::Status::OUT_OF_RESOURCES`      The request could not be completed due to a lack of resources.
+    pub unsafe fn write_blocks_raw(
+        &mut self,
+        media_id: u32,
+        lba: Lba,
+        token: Option<NonNull<BlockIo2Token>>,
+        len: usize,
+        buffer: *const u8,
+    ) -> Result {
+        let token = opt_nonnull_to_ptr(token);
+        unsafe { (self.0.write_blocks_ex)(&mut self.0, media_id, lba, token, len, buffer.cast()) }
+            .to_result()
+    }
+
+    /// Flushes all modified data to a physical block device.
+    ///
+    /// # Arguments
+    /// * `token` - Transaction token for the asynchronous flush, or `None` for
+    ///   a blocking flush.
+    ///
+    /// # Errors
+    /// * `uefi::Status::DEVICE_ERROR`          The device reported an error while attempting to write data.
+    /// * `uefi::Status::WRITE_PROTECTED`       The device cannot be written to.
+    /// * `uefi::Status::NO_MEDIA`              There is no media in the device.
+    /// * `uefi::Status::MEDIA_CHANGED`         The media in the device has changed since the last access.
+    /// * `uefi::Status::OUT_OF_RESOURCES`      The request could not be completed due to a lack of resources.
+    pub fn flush_blocks(&mut self, token: Option<NonNull<BlockIo2Token>>) -> Result {
+        let token = opt_nonnull_to_ptr(token);
+        unsafe { (self.0.flush_blocks_ex)(&mut self.0, token) }.to_result()
+    }
+}
+
+/// Async variants of the requests, run by the executor of [`task`].
+///
+/// Block I/O 2 requests can't be canceled individually, so if a future is
+/// dropped before its request is done, the drop blocks until the request
+/// is done.
+///
+/// The requests go through a buffer owned by the future, aligned as the
+/// device requires, rather than the caller's buffer. A leaked future thus
+/// can't leave the firmware with a dangling pointer.
+#[cfg(feature = "alloc")]
+impl BlockIO2 {
+    /// Reads blocks from the device into `buffer`, without blocking the
+    /// other tasks.
+    ///
+    /// # Errors
+    ///
+    /// See [`read_blocks_raw`](Self::read_blocks_raw), as well as the
+    /// errors of [`task::Completion::new`].
+    pub async fn read_blocks_async(
+        &mut self,
+        media_id: u32,
+        lba: Lba,
+        buffer: &mut [u8],
+    ) -> Result {
+        if buffer.is_empty() {
+            return Ok(());
+        }
+        let mut data = self.request_buffer(buffer.len())?;
+        let len = data.size();
+        let ptr = data.ptr_mut();
+        self.run(|this, token| unsafe {
+            this.read_blocks_raw(media_id, lba, Some(token), len, ptr)
+        })
+        .await?;
+        buffer.copy_from_slice(unsafe { slice::from_raw_parts(data.ptr(), len) });
+        Ok(())
+    }
+
+    /// Writes `buffer` to blocks of the device, without blocking the other
+    /// tasks.
+    ///
+    /// # Errors
+    ///
+    /// See [`write_blocks_raw`](Self::write_blocks_raw), as well as the
+    /// errors of [`task::Completion::new`].
+    pub async fn write_blocks_async(&mut self, media_id: u32, lba: Lba, buffer: &[u8]) -> Result {
+        if buffer.is_empty() {
+            return Ok(());
+        }
+        let mut data = self.request_buffer(buffer.len())?;
+        data.copy_from_slice(buffer);
+        let len = data.size();
+        let ptr = data.ptr();
+        self.run(|this, token| unsafe {
+            this.write_blocks_raw(media_id, lba, Some(token), len, ptr)
+        })
+        .await
+    }
+
+    /// Flushes all modified data to the physical device, without blocking
+    /// the other tasks.
+    ///
+    /// # Errors
+    ///
+    /// See [`flush_blocks`](Self::flush_blocks), as well as the errors of
+    /// [`task::Completion::new`].
+    pub async fn flush_blocks_async(&mut self) -> Result {
+        self.run(|this, token| this.flush_blocks(Some(token))).await
+    }
+
+    /// Allocates a request buffer of `len` bytes with the alignment of the
+    /// medium.
+    fn request_buffer(&self, len: usize) -> Result<AlignedBuffer> {
+        let align = (self.media().io_align() as usize).max(1);
+        AlignedBuffer::from_size_align(len, align).map_err(|_| Status::INVALID_PARAMETER.into())
+    }
+
+    /// Runs the request that `start` queues with the given token until it
+    /// completes.
+    async fn run(
+        &mut self,
+        start: impl FnOnce(&mut Self, NonNull<BlockIo2Token>) -> Result,
+    ) -> Result {
+        let mut done = task::Completion::new()?;
+        let mut token = BlockIo2Token {
+            event: done.event().as_ptr(),
+            transaction_status: Status::NOT_READY,
+        };
+        let token_ptr = NonNull::from(&mut token);
+        start(self, token_ptr)?;
+        task::request(&mut done, || {}).await;
+        unsafe { token_ptr.as_ref() }.transaction_status.to_result()
+    }
+}
+
 /// Media information structure
 #[repr(transparent)]
 #[derive(Debug)]
@@ -7,6 +7,8 @@ use crate::util::opt_nonnull_to_ptr;
 use crate::{Event, Result, Status, StatusExt};
 use core::ptr::NonNull;
 use uefi_raw::protocol::disk::{DiskIo2Protocol, DiskIoProtocol};
+#[cfg(feature = "alloc")]
+use {crate::task, alloc::vec, core::ptr};
 
 /// The disk I/O protocol.
 ///
@@ -194,3 +196,87 @@ impl DiskIo2 {
         unsafe { (self.0.flush_disk_ex)(&mut self.0, token.cast()) }.to_result()
     }
 }
+
+/// Async variants of the requests, run by the executor of [`task`].
+///
+/// If a future is dropped before its request is done, all requests to the
+/// device are canceled with [`DiskIo2::cancel`], and the drop blocks until
+/// the firmware has aborted the request.
+///
+/// The firmware accesses a copy of the data owned by the future, never the
+/// caller's buffer, so leaking a future can't leave the firmware with a
+/// dangling pointer.
+#[cfg(feature = "alloc")]
+impl DiskIo2 {
+    /// Reads bytes from the disk device into `buffer`, without blocking the
+    /// other tasks.
+    ///
+    /// # Errors
+    ///
+    /// See [`read_disk_raw`](Self::read_disk_raw), as well as the errors of
+    /// [`task::Completion::new`].
+    pub async fn read_disk_async(
+        &mut self,
+        media_id: u32,
+        offset: u64,
+        buffer: &mut [u8],
+    ) -> Result {
+        let mut data = vec![0; buffer.len()];
+        let len = data.len();
+        let ptr = data.as_mut_ptr();
+        self.run(|this, token| unsafe {
+            this.read_disk_raw(media_id, offset, Some(token), len, ptr)
+        })
+        .await?;
+        buffer.copy_from_slice(&data);
+        Ok(())
+    }
+
+    /// Writes `buffer` to the disk device, without blocking the other tasks.
+    ///
+    /// # Errors
+    ///
+    /// See [`write_disk_raw`](Self::write_disk_raw), as well as the errors
+    /// of [`task::Completion::new`].
+    pub async fn write_disk_async(&mut self, media_id: u32, offset: u64, buffer: &[u8]) -> Result {
+        let data = buffer.to_vec();
+        let len = data.len();
+        let ptr = data.as_ptr();
+        self.run(|this, token| unsafe {
+            this.write_disk_raw(media_id, offset, Some(token), len, ptr)
+        })
+        .await
+    }
+
+    /// Flushes all modified data to the physical device, without blocking
+    /// the other tasks.
+    ///
+    /// # Errors
+    ///
+    /// See [`flush_disk`](Self::flush_disk), as well as the errors of
+    /// [`task::Completion::new`].
+    pub async fn flush_disk_async(&mut self) -> Result {
+        self.run(|this, token| this.flush_disk(Some(token))).await
+    }
+
+    /// Runs the request that `start` queues with the given token until it
+    /// completes.
+    async fn run(
+        &mut self,
+        start: impl FnOnce(&mut Self, NonNull<DiskIo2Token>) -> Result,
+    ) -> Result {
+        let mut done = task::Completion::new()?;
+        let mut token = DiskIo2Token {
+            event: Some(unsafe { done.event().unsafe_clone() }),
+            transaction_status: Status::NOT_READY,
+        };
+        let token_ptr = NonNull::from(&mut token);
+        start(self, token_ptr)?;
+        let this = ptr::from_mut(self);
+        task::request(&mut done, || {
+            let _ = unsafe { (*this).cancel() };
+        })
+        .await;
+        unsafe { token_ptr.as_ref() }.transaction_status.to_result()
+    }
+}
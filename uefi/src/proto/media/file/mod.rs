@@ -22,7 +22,13 @@ use uefi_raw::protocol::file_system::FileProtocolV1;
 use {alloc::alloc::Global, core::alloc::Allocator};
 
 #[cfg(feature = "alloc")]
-use {crate::mem::make_boxed, alloc::boxed::Box};
+use {
+    crate::Error,
+    crate::mem::make_boxed,
+    alloc::boxed::Box,
+    core::alloc::Layout,
+    uefi_raw::protocol::file_system::{FileProtocolRevision, FileProtocolV2},
+};
 
 pub use dir::Directory;
 pub use info::{
@@ -193,9 +199,27 @@ pub trait File: Sized {
     }
 
     /// Read the dynamically allocated info for a file.
+    ///
+    /// The info is first read into a buffer on the stack, which is large
+    /// enough for most infos. Only if it is too small, another call is
+    /// needed to read the info into a heap buffer of the required size.
     #[cfg(feature = "alloc")]
     fn get_boxed_info<Info: FileProtocolInfo + ?Sized + Debug>(&mut self) -> Result<Box<Info>> {
-        let fetch_data_fn = |buf| self.get_info::<Info>(buf);
+        let mut stack_buf = InfoStackBuffer([0; INFO_STACK_BUFFER_SIZE]);
+        let mut required_size = match self.get_info::<Info>(&mut stack_buf.0) {
+            Ok(info) => return Ok(copy_info_to_box(info)),
+            Err(err) if err.status() == Status::BUFFER_TOO_SMALL => *err.data(),
+            Err(err) => return Err(err.to_err_without_payload()),
+        };
+
+        let fetch_data_fn = |buf| {
+            // The first call queries the required size, which is already
+            // known, so don't query it again.
+            match required_size.take() {
+                Some(size) => Err(Error::new(Status::BUFFER_TOO_SMALL, Some(size))),
+                None => self.get_info::<Info>(buf),
+            }
+        };
         #[cfg(not(feature = "unstable"))]
         let file_info = make_boxed::<Info, _>(fetch_data_fn)?;
         #[cfg(feature = "unstable")]
@@ -227,6 +251,32 @@ pub trait File: Sized {
     fn is_directory(&self) -> Result<bool>;
 }
 
+/// Size of the stack buffer that [`File::get_boxed_info`] tries first.
+#[cfg(feature = "alloc")]
+const INFO_STACK_BUFFER_SIZE: usize = 256;
+
+/// Stack buffer with the alignment of the info types.
+#[cfg(feature = "alloc")]
+#[repr(C, align(8))]
+struct InfoStackBuffer([u8; INFO_STACK_BUFFER_SIZE]);
+
+/// Copies an info from a temporary buffer to the heap.
+#[cfg(feature = "alloc")]
+fn copy_info_to_box<Info: FileProtocolInfo + ?Sized>(info: &Info) -> Box<Info> {
+    let size = size_of_val(info);
+    let layout = Layout::from_size_align(size, Info::alignment())
+        .unwrap()
+        .pad_to_align();
+    unsafe {
+        let heap_buf = alloc::alloc::alloc(layout);
+        if heap_buf.is_null() {
+            alloc::alloc::handle_alloc_error(layout);
+        }
+        ptr::copy_nonoverlapping(ptr::from_ref(info).cast::<u8>(), heap_buf, size);
+        Box::from_raw(Info::from_uefi(heap_buf.cast()))
+    }
+}
+
 // Internal File helper methods to access the function pointer table.
 trait FileInternal: File {
     fn imp(&mut self) -> &mut FileProtocolV1 {
@@ -266,6 +316,14 @@ impl FileHandle {
         })
     }
 
+    /// Returns the function table of revision 2 of the file protocol, or
+    /// `None` if the file system only supports revision 1.
+    #[cfg(feature = "alloc")]
+    pub(crate) fn protocol_v2(&mut self) -> Option<*mut FileProtocolV2> {
+        let imp = self.imp();
+        (imp.revision >= FileProtocolRevision::REVISION_2).then(|| ptr::from_mut(imp).cast())
+    }
+
     /// If the handle represents a directory, convert it into a
     /// [`Directory`]. Otherwise returns `None`.
     #[must_use]
@@ -360,7 +418,37 @@ mod tests {
     // get_info is actually implemented to return useful data.
     #[test]
     fn test_get_boxed_info() {
-        let mut file_impl = FileProtocolV1 {
+        let mut file_impl = fake_file_protocol(stub_get_info);
+        let mut file = unsafe { RegularFile::new(FileHandle(&mut file_impl)) };
+        let info = file.get_boxed_info::<FileInfo>().unwrap();
+        assert_eq!(info.file_size(), 123);
+        assert_eq!(info.file_name(), CString16::try_from("test_file").unwrap());
+    }
+
+    // Test `get_boxed_info` with an info that doesn't fit in the stack
+    // buffer.
+    #[test]
+    fn test_get_boxed_info_large() {
+        let mut file_impl = fake_file_protocol(stub_get_info_large);
+        let mut file = unsafe { RegularFile::new(FileHandle(&mut file_impl)) };
+        let info = file.get_boxed_info::<FileInfo>().unwrap();
+        assert_eq!(info.file_size(), 123);
+        assert_eq!(info.file_name(), CString16::try_from(LARGE_NAME).unwrap());
+    }
+
+    const LARGE_NAME: &str = "a_file_name_that_is_long_enough_to_make_the_file_info_larger_\
+                              than_the_stack_buffer_of_get_boxed_info_which_has_256_bytes_so_\
+                              it_needs_more_than_one_hundred_characters";
+
+    fn fake_file_protocol(
+        get_info: unsafe extern "efiapi" fn(
+            *mut FileProtocolV1,
+            *const Guid,
+            *mut usize,
+            *mut c_void,
+        ) -> Status,
+    ) -> FileProtocolV1 {
+        FileProtocolV1 {
             revision: FileProtocolRevision::REVISION_1,
             open: stub_open,
             close: stub_close,
@@ -369,16 +457,10 @@ mod tests {
             write: stub_write,
             get_position: stub_get_position,
             set_position: stub_set_position,
-            get_info: stub_get_info,
+            get_info,
             set_info: stub_set_info,
             flush: stub_flush,
-        };
-        let file_handle = FileHandle(&mut file_impl);
-
-        let mut file = unsafe { RegularFile::new(file_handle) };
-        let info = file.get_boxed_info::<FileInfo>().unwrap();
-        assert_eq!(info.file_size(), 123);
-        assert_eq!(info.file_name(), CString16::try_from("test_file").unwrap());
+        }
     }
 
     unsafe extern "efiapi" fn stub_get_info(
@@ -386,12 +468,30 @@ mod tests {
         information_type: *const Guid,
         buffer_size: *mut usize,
         buffer: *mut c_void,
+    ) -> Status {
+        unsafe { get_file_info(information_type, buffer_size, buffer, "test_file") }
+    }
+
+    unsafe extern "efiapi" fn stub_get_info_large(
+        _this: *mut FileProtocolV1,
+        information_type: *const Guid,
+        buffer_size: *mut usize,
+        buffer: *mut c_void,
+    ) -> Status {
+        unsafe { get_file_info(information_type, buffer_size, buffer, LARGE_NAME) }
+    }
+
+    unsafe fn get_file_info(
+        information_type: *const Guid,
+        buffer_size: *mut usize,
+        buffer: *mut c_void,
+        name: &str,
     ) -> Status {
         assert_eq!(unsafe { *information_type }, FileInfo::GUID);
 
         // Use a temporary buffer to get some file info, then copy that
         // data to the output buffer.
-        let mut tmp = vec![0; 128];
+        let mut tmp = vec![0; 512];
         let file_size = 123;
         let physical_size = 456;
         let time = Time::invalid();
@@ -403,7 +503,7 @@ mod tests {
             time,
             time,
             FileAttribute::empty(),
-            &CString16::try_from("test_file").unwrap(),
+            &CString16::try_from(name).unwrap(),
         )
         .unwrap();
         let required_size = size_of_val(info);
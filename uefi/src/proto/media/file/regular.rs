@@ -133,6 +133,25 @@ impl RegularFile {
     pub fn set_position(&mut self, position: u64) -> Result {
         unsafe { (self.imp().set_position)(self.imp(), position) }.to_result()
     }
+
+    /// Reads exactly `buffer.len()` bytes, starting at `offset`.
+    ///
+    /// This combines [`Self::set_position`] and [`Self::read`]. The position
+    /// of the file is left after the read data.
+    ///
+    /// # Errors
+    ///
+    /// * [`uefi::Status::END_OF_FILE`]: the file ends before the buffer is
+    ///   filled. The contents of the buffer are unspecified.
+    /// * Errors of [`Self::set_position`] and [`Self::read`].
+    pub fn read_exact_at(&mut self, offset: u64, buffer: &mut [u8]) -> Result {
+        self.set_position(offset)?;
+        if self.read(buffer)? == buffer.len() {
+            Ok(())
+        } else {
+            Err(Status::END_OF_FILE.into())
+        }
+    }
 }
 
 impl File for RegularFile {
@@ -0,0 +1,170 @@
+// SPDX-License-Identifier: MIT OR Apache-2.0
+
+//! Generic Memory Test protocol.
+
+use crate::proto::unsafe_protocol;
+use crate::{Result, Status, StatusExt};
+use uefi_raw::protocol::memory_test::GenericMemoryTestProtocol;
+use uefi_raw::{Boolean, PhysicalAddress};
+
+pub use uefi_raw::protocol::memory_test::ExtendedMemoryCoverageLevel as CoverageLevel;
+
+/// Progress of a memory test.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct MemoryTestProgress {
+    /// Amount of memory tested so far, in bytes.
+    pub tested: u64,
+    /// Total amount of memory to test, in bytes.
+    pub total: u64,
+}
+
+impl MemoryTestProgress {
+    /// Progress in percent.
+    #[must_use]
+    pub const fn percent(&self) -> u8 {
+        if self.total == 0 {
+            100
+        } else {
+            (self.tested as u128 * 100 / self.total as u128) as u8
+        }
+    }
+}
+
+/// Generic Memory Test protocol, which runs the memory test of the platform.
+///
+/// EDK2-based firmware uses this protocol to test the memory that was not
+/// tested during early initialization, and to add it to the memory map
+/// afterwards. The memory test can be run by an application with
+/// [`run`](Self::run), or step by step with the other methods.
+///
+/// # Example
+///
+/// ```no_run
+/// use uefi::boot;
+/// use uefi::proto::memory_test::{CoverageLevel, GenericMemoryTest};
+///
+/// # fn example() -> uefi::Result {
+/// let handle = boot::get_handle_for_protocol::<GenericMemoryTest>()?;
+/// let mut memory_test = boot::open_protocol_exclusive::<GenericMemoryTest>(handle)?;
+/// memory_test.run(CoverageLevel::SPARSE, |progress| {
+///     uefi::println!("Tested {}%", progress.percent());
+///     true
+/// })?;
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Debug)]
+#[repr(transparent)]
+#[unsafe_protocol(GenericMemoryTestProtocol::GUID)]
+pub struct GenericMemoryTest(GenericMemoryTestProtocol);
+
+impl GenericMemoryTest {
+    /// Prepares the memory test with the given coverage `level`. Returns
+    /// whether the memory needs a software ECC initialization.
+    ///
+    /// # Errors
+    ///
+    /// * [`Status::NO_MEDIA`]: there is no untested memory.
+    /// * [`Status::INVALID_PARAMETER`]: the level is invalid.
+    pub fn init(&mut self, level: CoverageLevel) -> Result<bool> {
+        let mut require_soft_ecc_init = Boolean::FALSE;
+        unsafe { (self.0.memory_test_init)(&mut self.0, level, &mut require_soft_ecc_init) }
+            .to_result_with_val(|| require_soft_ecc_init.into())
+    }
+
+    /// Tests the next range of memory and returns the progress, or `None`
+    /// if all memory has been tested.
+    ///
+    /// If `abort` is true, the remaining memory is not tested, but still
+    /// added to the memory map.
+    ///
+    /// # Errors
+    ///
+    /// * [`Status::DEVICE_ERROR`]: a memory error was found.
+    pub fn perform_test(&mut self, abort: bool) -> Result<Option<MemoryTestProgress>> {
+        let mut progress = MemoryTestProgress::default();
+        let mut error = Boolean::FALSE;
+        let status = unsafe {
+            (self.0.perform_memory_test)(
+                &mut self.0,
+                &mut progress.tested,
+                &mut progress.total,
+                &mut error,
+                abort.into(),
+            )
+        };
+        if bool::from(error) {
+            return Err(Status::DEVICE_ERROR.into());
+        }
+        match status {
+            Status::NOT_FOUND => Ok(None),
+            status => status.to_result_with_val(|| Some(progress)),
+        }
+    }
+
+    /// Finishes the memory test and adds the tested memory to the memory
+    /// map.
+    ///
+    /// # Errors
+    ///
+    /// Errors are not specified for this function, but firmware may return
+    /// any error status.
+    pub fn finish(&mut self) -> Result {
+        unsafe { (self.0.finished)(&mut self.0) }.to_result()
+    }
+
+    /// Tests the range of `length` bytes at `start`, and adds it to the
+    /// memory map if it passes. This is meant for ranges that must be
+    /// available early, such as legacy BIOS memory.
+    ///
+    /// # Errors
+    ///
+    /// * [`Status::INVALID_PARAMETER`]: the range is invalid.
+    /// * [`Status::DEVICE_ERROR`]: a memory error was found.
+    pub fn compatible_range_test(&mut self, start: PhysicalAddress, length: u64) -> Result {
+        unsafe { (self.0.compatible_range_test)(&mut self.0, start, length) }.to_result()
+    }
+
+    /// Runs the complete memory test with the given coverage `level`,
+    /// calling `progress` after each tested range. If `progress` returns
+    /// false, the test is aborted and the remaining memory is added to the
+    /// memory map untested. Returns the last progress.
+    ///
+    /// [`finish`](Self::finish) is called even if the test fails.
+    ///
+    /// # Errors
+    ///
+    /// * [`Status::DEVICE_ERROR`]: a memory error was found.
+    /// * Errors of [`init`](Self::init), except [`Status::NO_MEDIA`], which
+    ///   means that there is nothing to test.
+    pub fn run(
+        &mut self,
+        level: CoverageLevel,
+        mut progress: impl FnMut(&MemoryTestProgress) -> bool,
+    ) -> Result<MemoryTestProgress> {
+        match self.init(level) {
+            Ok(_) => {}
+            Err(err) if err.status() == Status::NO_MEDIA => {
+                return Ok(MemoryTestProgress::default());
+            }
+            Err(err) => return Err(err),
+        }
+
+        let mut last = MemoryTestProgress::default();
+        let result = loop {
+            match self.perform_test(false) {
+                Ok(Some(current)) => {
+                    last = current;
+                    if !progress(&current) {
+                        break self.perform_test(true).map(|_| last);
+                    }
+                }
+                Ok(None) => break Ok(last),
+                Err(err) => break Err(err),
+            }
+        };
+        let finished = self.finish();
+        let last = result?;
+        finished.map(|()| last)
+    }
+}
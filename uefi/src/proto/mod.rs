@@ -16,8 +16,10 @@ pub mod console;
 pub mod debug;
 pub mod device_path;
 pub mod driver;
+pub mod kms;
 pub mod loaded_image;
 pub mod media;
+pub mod memory_test;
 pub mod misc;
 pub mod network;
 #[cfg(feature = "alloc")]
@@ -38,7 +40,7 @@ pub mod usb;
 mod boot_policy;
 
 pub use boot_policy::BootPolicy;
-pub use uefi_macros::unsafe_protocol;
+pub use uefi_macros::{uefi_protocol, unsafe_protocol};
 
 use crate::Identify;
 use core::ffi::c_void;
@@ -0,0 +1,267 @@
+// SPDX-License-Identifier: MIT OR Apache-2.0
+
+//! EAP Configuration protocol.
+
+use crate::proto::unsafe_protocol;
+use crate::{CStr16, Result, Status, StatusExt};
+use core::ffi::c_void;
+use uefi_raw::protocol::network::eap::EapConfigurationProtocol;
+
+#[cfg(feature = "alloc")]
+use alloc::{vec, vec::Vec};
+
+pub use uefi_raw::protocol::network::eap::{EapConfigDataType, EapType};
+
+/// EAP Configuration protocol, which configures the 802.1X authentication
+/// of a network device.
+///
+/// The protocol is installed on the handle of each network device that
+/// supports EAP, so the configuration applies to the networks reached
+/// through that device. Credentials can be provisioned before connecting,
+/// e.g. with the Wireless MAC Connection protocol.
+///
+/// The typed methods cover the common configuration. The other data can be
+/// accessed with [`set_data`] and [`get_data`].
+///
+/// # Example
+///
+/// ```no_run
+/// use uefi::proto::network::eap::{EapConfiguration, EapType};
+/// use uefi::{boot, cstr16};
+///
+/// # fn example(ca_cert: &[u8]) -> uefi::Result {
+/// let handle = boot::get_handle_for_protocol::<EapConfiguration>()?;
+/// let mut eap = boot::open_protocol_exclusive::<EapConfiguration>(handle)?;
+/// eap.set_auth_method(EapType::PEAP)?;
+/// eap.set_second_auth_method(EapType::PEAP, EapType::MSCHAPV2)?;
+/// eap.set_identity("user@example.com")?;
+/// eap.set_mschapv2_password(cstr16!("secret"))?;
+/// eap.set_ca_cert(ca_cert)?;
+/// # Ok(())
+/// # }
+/// ```
+///
+/// [`set_data`]: Self::set_data
+/// [`get_data`]: Self::get_data
+#[derive(Debug)]
+#[repr(transparent)]
+#[unsafe_protocol(EapConfigurationProtocol::GUID)]
+pub struct EapConfiguration(EapConfigurationProtocol);
+
+impl EapConfiguration {
+    /// Sets the data of `data_type` for the method `eap_type`.
+    ///
+    /// # Errors
+    ///
+    /// * [`Status::INVALID_PARAMETER`]: `data` is not valid for `data_type`.
+    /// * [`Status::UNSUPPORTED`]: `eap_type` or `data_type` is not supported.
+    /// * [`Status::OUT_OF_RESOURCES`]: there is not enough memory.
+    pub fn set_data(
+        &mut self,
+        eap_type: EapType,
+        data_type: EapConfigDataType,
+        data: &[u8],
+    ) -> Result {
+        unsafe {
+            (self.0.set_data)(
+                &mut self.0,
+                eap_type,
+                data_type,
+                data.as_ptr().cast(),
+                data.len(),
+            )
+        }
+        .to_result()
+    }
+
+    /// Reads the data of `data_type` for the method `eap_type` into
+    /// `buffer`, and returns its size.
+    ///
+    /// # Errors
+    ///
+    /// * [`Status::BUFFER_TOO_SMALL`]: `buffer` is too small. The required
+    ///   size is returned in the error data.
+    /// * [`Status::NOT_FOUND`]: the data is not configured.
+    /// * [`Status::UNSUPPORTED`]: `eap_type` or `data_type` is not supported.
+    pub fn get_data(
+        &mut self,
+        eap_type: EapType,
+        data_type: EapConfigDataType,
+        buffer: &mut [u8],
+    ) -> Result<usize, Option<usize>> {
+        let mut size = buffer.len();
+        unsafe {
+            (self.0.get_data)(
+                &mut self.0,
+                eap_type,
+                data_type,
+                buffer.as_mut_ptr().cast::<c_void>(),
+                &mut size,
+            )
+        }
+        .to_result_with(
+            || size,
+            |status| (status == Status::BUFFER_TOO_SMALL).then_some(size),
+        )
+    }
+
+    /// Returns the data of `data_type` for the method `eap_type`.
+    ///
+    /// # Errors
+    ///
+    /// See [`get_data`](Self::get_data).
+    #[cfg(feature = "alloc")]
+    pub fn get_data_vec(
+        &mut self,
+        eap_type: EapType,
+        data_type: EapConfigDataType,
+    ) -> Result<Vec<u8>> {
+        let mut data = Vec::new();
+        loop {
+            match self.get_data(eap_type, data_type, &mut data) {
+                Ok(size) => {
+                    data.truncate(size);
+                    return Ok(data);
+                }
+                Err(err) => match *err.data() {
+                    Some(size) if size > data.len() => data = vec![0; size],
+                    _ => return Err(err.to_err_without_payload()),
+                },
+            }
+        }
+    }
+
+    /// Sets the EAP method used for authentication.
+    ///
+    /// # Errors
+    ///
+    /// See [`set_data`](Self::set_data).
+    pub fn set_auth_method(&mut self, method: EapType) -> Result {
+        self.set_data(
+            EapType::ATTRIBUTE,
+            EapConfigDataType::EAP_AUTH_METHOD,
+            &[method.0],
+        )
+    }
+
+    /// Returns the EAP method used for authentication.
+    ///
+    /// # Errors
+    ///
+    /// See [`get_data`](Self::get_data).
+    pub fn auth_method(&mut self) -> Result<EapType> {
+        let mut method = [0];
+        self.get_data(
+            EapType::ATTRIBUTE,
+            EapConfigDataType::EAP_AUTH_METHOD,
+            &mut method,
+        )
+        .map_err(|err| err.to_err_without_payload())?;
+        Ok(EapType(method[0]))
+    }
+
+    /// Returns the EAP methods supported by the device.
+    ///
+    /// # Errors
+    ///
+    /// See [`get_data`](Self::get_data).
+    #[cfg(feature = "alloc")]
+    pub fn supported_auth_methods(&mut self) -> Result<Vec<EapType>> {
+        let methods = self.get_data_vec(
+            EapType::ATTRIBUTE,
+            EapConfigDataType::EAP_SUPPORTED_AUTH_METHOD,
+        )?;
+        Ok(methods.into_iter().map(EapType).collect())
+    }
+
+    /// Sets the EAP method of the inner authentication of the tunneled
+    /// method `outer`, i.e. [`EapType::PEAP`] or [`EapType::TTLS`].
+    ///
+    /// # Errors
+    ///
+    /// See [`set_data`](Self::set_data).
+    pub fn set_second_auth_method(&mut self, outer: EapType, inner: EapType) -> Result {
+        self.set_data(outer, EapConfigDataType::EAP_2ND_AUTH_METHOD, &[inner.0])
+    }
+
+    /// Sets the identity sent to the authentication server. It must be
+    /// ASCII.
+    ///
+    /// # Errors
+    ///
+    /// * [`Status::INVALID_PARAMETER`]: the identity is not ASCII.
+    /// * Errors of [`set_data`](Self::set_data).
+    pub fn set_identity(&mut self, identity: &str) -> Result {
+        if !identity.is_ascii() {
+            return Err(Status::INVALID_PARAMETER.into());
+        }
+        self.set_data(
+            EapType::IDENTITY,
+            EapConfigDataType::IDENTITY_STRING,
+            identity.as_bytes(),
+        )
+    }
+
+    /// Sets the CA certificate that the certificate of the authentication
+    /// server must be signed with. The certificate is used by all TLS-based
+    /// methods.
+    ///
+    /// # Errors
+    ///
+    /// See [`set_data`](Self::set_data).
+    pub fn set_ca_cert(&mut self, cert: &[u8]) -> Result {
+        self.set_data(EapType::EAP_TLS, EapConfigDataType::EAP_TLS_CA_CERT, cert)
+    }
+
+    /// Sets the client certificate for EAP-TLS.
+    ///
+    /// # Errors
+    ///
+    /// See [`set_data`](Self::set_data).
+    pub fn set_client_cert(&mut self, cert: &[u8]) -> Result {
+        self.set_data(
+            EapType::EAP_TLS,
+            EapConfigDataType::EAP_TLS_CLIENT_CERT,
+            cert,
+        )
+    }
+
+    /// Sets the private key of the client certificate, and the password
+    /// of the key if it is encrypted. The password must be ASCII.
+    ///
+    /// # Errors
+    ///
+    /// * [`Status::INVALID_PARAMETER`]: the password is not ASCII.
+    /// * Errors of [`set_data`](Self::set_data).
+    pub fn set_client_private_key(&mut self, key: &[u8], password: Option<&str>) -> Result {
+        if password.is_some_and(|password| !password.is_ascii()) {
+            return Err(Status::INVALID_PARAMETER.into());
+        }
+        self.set_data(
+            EapType::EAP_TLS,
+            EapConfigDataType::EAP_TLS_CLIENT_PRIVATE_KEY_FILE,
+            key,
+        )?;
+        if let Some(password) = password {
+            self.set_data(
+                EapType::EAP_TLS,
+                EapConfigDataType::EAP_TLS_CLIENT_PRIVATE_KEY_FILE_PASSWORD,
+                password.as_bytes(),
+            )?;
+        }
+        Ok(())
+    }
+
+    /// Sets the password for MSCHAPv2.
+    ///
+    /// # Errors
+    ///
+    /// See [`set_data`](Self::set_data).
+    pub fn set_mschapv2_password(&mut self, password: &CStr16) -> Result {
+        self.set_data(
+            EapType::MSCHAPV2,
+            EapConfigDataType::EAP_MSCHAPV2_PASSWORD,
+            password.as_bytes(),
+        )
+    }
+}
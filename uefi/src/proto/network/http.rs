@@ -6,11 +6,13 @@
 //!
 //! See [`Http`].
 
+use alloc::ffi::CString;
 use alloc::string::String;
 use alloc::vec;
 use alloc::vec::Vec;
 use core::ffi::{CStr, c_char, c_void};
-use core::ptr;
+use core::ptr::{self, NonNull};
+use core::slice;
 use log::debug;
 
 use uefi::boot::ScopedProtocol;
@@ -19,7 +21,8 @@ use uefi::proto::unsafe_protocol;
 use uefi_raw::protocol::driver::ServiceBindingProtocol;
 use uefi_raw::protocol::network::http::{
     HttpAccessPoint, HttpConfigData, HttpHeader, HttpMessage, HttpMethod, HttpProtocol,
-    HttpRequestData, HttpResponseData, HttpStatusCode, HttpToken, HttpV4AccessPoint, HttpVersion,
+    HttpRequestData, HttpResponseData, HttpStatusCode, HttpToken, HttpUtilitiesProtocol,
+    HttpV4AccessPoint, HttpVersion,
 };
 
 /// HTTP [`Protocol`]. Send HTTP Requests.
@@ -116,6 +119,123 @@ impl HttpBinding {
     }
 }
 
+/// HTTP Utilities [`Protocol`]. Build and parse HTTP header blocks.
+///
+/// A header block is a sequence of `Name: Value` lines, each terminated
+/// with CRLF, as in an HTTP message or in the buffers of the REST EX
+/// protocol.
+///
+/// [`Protocol`]: uefi::proto::Protocol
+#[derive(Debug)]
+#[repr(transparent)]
+#[unsafe_protocol(HttpUtilitiesProtocol::GUID)]
+pub struct HttpUtilities(HttpUtilitiesProtocol);
+
+impl HttpUtilities {
+    /// Builds a header block from the header block `seed`: the fields named
+    /// in `delete` are removed, and the fields in `append` are added, or
+    /// replace the value of an existing field with the same name.
+    ///
+    /// # Errors
+    ///
+    /// * [`Status::INVALID_PARAMETER`]: `seed` is not a valid header block.
+    /// * [`Status::OUT_OF_RESOURCES`]: there is not enough memory.
+    pub fn build(
+        &self,
+        seed: &[u8],
+        delete: &[&CStr],
+        append: &[(&CStr, &CStr)],
+    ) -> uefi::Result<Vec<u8>> {
+        let delete: Vec<_> = delete
+            .iter()
+            .map(|name| name.as_ptr().cast::<u8>())
+            .collect();
+        let append: Vec<_> = append
+            .iter()
+            .map(|(name, value)| HttpHeader {
+                field_name: name.as_ptr().cast(),
+                field_value: value.as_ptr().cast(),
+            })
+            .collect();
+        let append_ptrs: Vec<_> = append.iter().map(ptr::from_ref).collect();
+
+        let mut size = 0;
+        let mut message = ptr::null_mut();
+        unsafe {
+            (self.0.build)(
+                &self.0,
+                seed.len(),
+                if seed.is_empty() {
+                    ptr::null()
+                } else {
+                    seed.as_ptr().cast()
+                },
+                delete.len(),
+                delete.as_ptr(),
+                append_ptrs.len(),
+                append_ptrs.as_ptr(),
+                &mut size,
+                &mut message,
+            )
+        }
+        .to_result()?;
+
+        let Some(message) = NonNull::new(message.cast::<u8>()) else {
+            return Ok(Vec::new());
+        };
+        let block = unsafe { slice::from_raw_parts(message.as_ptr(), size) }.to_vec();
+        unsafe { boot::free_pool(message) }?;
+        Ok(block)
+    }
+
+    /// Parses the header block `message` into its fields, as pairs of name
+    /// and value.
+    ///
+    /// # Errors
+    ///
+    /// * [`Status::INVALID_PARAMETER`]: `message` is empty.
+    /// * [`Status::NOT_FOUND`]: `message` has no header fields.
+    /// * [`Status::OUT_OF_RESOURCES`]: there is not enough memory.
+    pub fn parse(&self, message: &[u8]) -> uefi::Result<Vec<(CString, CString)>> {
+        let mut fields = ptr::null_mut();
+        let mut count = 0;
+        unsafe {
+            (self.0.parse)(
+                &self.0,
+                message.as_ptr(),
+                message.len(),
+                &mut fields,
+                &mut count,
+            )
+        }
+        .to_result()?;
+
+        let Some(fields) = NonNull::new(fields) else {
+            return Ok(Vec::new());
+        };
+        // The array and the strings of its fields are allocated from pool
+        // memory, and owned by the caller.
+        let mut headers = Vec::with_capacity(count);
+        for field in unsafe { slice::from_raw_parts(fields.as_ptr(), count) } {
+            let name = take_pool_str(field.field_name);
+            let value = take_pool_str(field.field_value);
+            headers.push((name, value));
+        }
+        unsafe { boot::free_pool(fields.cast()) }?;
+        Ok(headers)
+    }
+}
+
+/// Copies the pool-allocated string `s`, and frees it.
+fn take_pool_str(s: *const u8) -> CString {
+    let Some(ptr) = NonNull::new(s.cast_mut()) else {
+        return CString::default();
+    };
+    let string = CString::from(unsafe { CStr::from_ptr(ptr.as_ptr().cast::<c_char>()) });
+    let _ = unsafe { boot::free_pool(ptr) };
+    string
+}
+
 /// HTTP Response data
 #[derive(Debug)]
 pub struct HttpHelperResponse {
@@ -133,6 +253,7 @@ pub struct HttpHelper {
     child_handle: Handle,
     binding: ScopedProtocol<HttpBinding>,
     protocol: Option<ScopedProtocol<Http>>,
+    utilities: ScopedProtocol<HttpUtilities>,
 }
 
 impl HttpHelper {
@@ -150,6 +271,19 @@ impl HttpHelper {
         };
         debug!("http: binding proto ok");
 
+        // The HTTP driver depends on the utilities, so they are available
+        // if the binding is.
+        let utilities = unsafe {
+            boot::open_protocol::<HttpUtilities>(
+                boot::OpenProtocolParams {
+                    handle: boot::get_handle_for_protocol::<HttpUtilities>()?,
+                    agent: boot::image_handle(),
+                    controller: None,
+                },
+                boot::OpenProtocolAttributes::GetProtocol,
+            )?
+        };
+
         let child_handle = binding.create_child()?;
         debug!("http: child handle ok");
 
@@ -173,6 +307,7 @@ impl HttpHelper {
             child_handle,
             binding,
             protocol: Some(protocol_res.unwrap()),
+            utilities,
         })
     }
 
@@ -202,14 +337,25 @@ impl HttpHelper {
         method: HttpMethod,
         url: &str,
         body: Option<&mut [u8]>,
+    ) -> uefi::Result<()> {
+        self.request_with_headers(method, url, &[], body)
+    }
+
+    /// Send HTTP request with additional header fields. A `Host` field
+    /// replaces the one derived from the URL.
+    pub fn request_with_headers(
+        &mut self,
+        method: HttpMethod,
+        url: &str,
+        headers: &[(&CStr, &CStr)],
+        body: Option<&mut [u8]>,
     ) -> uefi::Result<()> {
         let url16 = uefi::CString16::try_from(url).unwrap();
 
         let Some(hostname) = url.split('/').nth(2) else {
             return Err(Status::INVALID_PARAMETER.into());
         };
-        let mut c_hostname = String::from(hostname);
-        c_hostname.push('\0');
+        let c_hostname = CString::new(hostname).map_err(|_| Status::INVALID_PARAMETER)?;
         debug!("http: host: {hostname}");
 
         let mut tx_req = HttpRequestData {
@@ -217,11 +363,19 @@ impl HttpHelper {
             url: url16.as_ptr().cast::<u16>(),
         };
 
-        let mut tx_hdr = Vec::new();
-        tx_hdr.push(HttpHeader {
-            field_name: c"Host".as_ptr().cast::<u8>(),
-            field_value: c_hostname.as_ptr(),
-        });
+        // Merge the fields into a header block, and split it again into the
+        // fields of the message.
+        let mut fields = vec![(c"Host", c_hostname.as_c_str())];
+        fields.extend_from_slice(headers);
+        let block = self.utilities.build(&[], &[], &fields)?;
+        let fields = self.utilities.parse(&block)?;
+        let mut tx_hdr: Vec<_> = fields
+            .iter()
+            .map(|(name, value)| HttpHeader {
+                field_name: name.as_ptr().cast::<u8>(),
+                field_value: value.as_ptr().cast::<u8>(),
+            })
+            .collect();
 
         let mut tx_msg = HttpMessage::default();
         tx_msg.data.request = &mut tx_req;
@@ -0,0 +1,702 @@
+// SPDX-License-Identifier: MIT OR Apache-2.0
+
+//! IPsec configuration protocol.
+
+use crate::proto::unsafe_protocol;
+use crate::{Event, Result, Status, StatusExt};
+use core::ffi::c_void;
+use core::marker::PhantomData;
+use core::net::IpAddr;
+use core::{mem, ptr};
+use uefi_raw::protocol::network::ipsec::{
+    IpsecAhAlgoInfo, IpsecAlgoInfo, IpsecConfigProtocol, IpsecEspAlgoInfo, IpsecPadData,
+    IpsecProcessPolicy, IpsecSaData2, IpsecSpdData, IpsecSpdSelector,
+};
+
+#[cfg(feature = "alloc")]
+use alloc::{vec, vec::Vec};
+
+pub use uefi_raw::protocol::network::ipsec::{
+    IpAddressInfo, IpsecAction, IpsecAuthAlgorithm, IpsecAuthMethod, IpsecAuthProtocolType,
+    IpsecConfigDataType, IpsecEncryptAlgorithm, IpsecMode, IpsecPadId, IpsecPadIdValue,
+    IpsecProtocolType, IpsecSaId, IpsecSaLifetime, IpsecTrafficDirection, IpsecTunnelDfOption,
+    IpsecTunnelOption, MAX_PEERID_LEN,
+};
+
+/// IPsec configuration protocol, which manages the databases of the IPsec
+/// driver.
+///
+/// There are three databases:
+/// * The Security Policy Database (SPD) decides which traffic is
+///   discarded, bypasses IPsec, or is protected. Its entries are set with
+///   [`set_spd_entry`].
+/// * The Security Association Database (SAD) holds the algorithms and keys
+///   of the manually keyed security associations. Its entries are set with
+///   [`set_sad_entry`].
+/// * The Peer Authorization Database (PAD) holds the credentials used to
+///   authenticate peers with IKE. Its entries are set with
+///   [`set_pad_entry`].
+///
+/// The policies must be installed before the network stack uses the
+/// affected connections, e.g. before booting from iSCSI or HTTPS.
+///
+/// # Example
+///
+/// ```no_run
+/// use core::net::{IpAddr, Ipv4Addr};
+/// use uefi::boot;
+/// use uefi::proto::network::ipsec::{
+///     IpAddressInfo, IpsecAction, IpsecConfig, IpsecTrafficDirection, SpdData, SpdSelector,
+/// };
+///
+/// # fn example() -> uefi::Result {
+/// let handle = boot::get_handle_for_protocol::<IpsecConfig>()?;
+/// let mut ipsec = boot::open_protocol_exclusive::<IpsecConfig>(handle)?;
+///
+/// // Bypass IPsec for all outbound traffic to 192.168.0.0/24.
+/// let remote = [IpAddressInfo {
+///     address: IpAddr::V4(Ipv4Addr::new(192, 168, 0, 0)).into(),
+///     prefix_length: 24,
+/// }];
+/// let selector = SpdSelector::new(&[], &remote)?;
+/// let data = SpdData::new(b"bypass-lan", IpsecTrafficDirection::OUTBOUND, IpsecAction::BYPASS)?;
+/// ipsec.set_spd_entry(&selector, Some(&data), None)?;
+/// # Ok(())
+/// # }
+/// ```
+///
+/// [`set_spd_entry`]: Self::set_spd_entry
+/// [`set_sad_entry`]: Self::set_sad_entry
+/// [`set_pad_entry`]: Self::set_pad_entry
+#[derive(Debug)]
+#[repr(transparent)]
+#[unsafe_protocol(IpsecConfigProtocol::GUID)]
+pub struct IpsecConfig(IpsecConfigProtocol);
+
+impl IpsecConfig {
+    /// Sets the SPD entry of `selector` to `data`, or deletes it if `data`
+    /// is `None`. A new entry is inserted before the entry of
+    /// `insert_before`, or at the end.
+    ///
+    /// # Errors
+    ///
+    /// See [`set_pad_entry`](Self::set_pad_entry).
+    pub fn set_spd_entry(
+        &mut self,
+        selector: &SpdSelector,
+        data: Option<&SpdData>,
+        insert_before: Option<&SpdSelector>,
+    ) -> Result {
+        self.set_data(
+            IpsecConfigDataType::SPD,
+            ptr::from_ref(selector).cast(),
+            opt_ptr(data),
+            opt_ptr(insert_before),
+        )
+    }
+
+    /// Sets the SAD entry of the security association `sa_id` to `data`,
+    /// or deletes it if `data` is `None`. A new entry is inserted before the
+    /// entry of `insert_before`, or at the end.
+    ///
+    /// # Errors
+    ///
+    /// See [`set_pad_entry`](Self::set_pad_entry).
+    pub fn set_sad_entry(
+        &mut self,
+        sa_id: &IpsecSaId,
+        data: Option<&SaData>,
+        insert_before: Option<&IpsecSaId>,
+    ) -> Result {
+        self.set_data(
+            IpsecConfigDataType::SAD,
+            ptr::from_ref(sa_id).cast(),
+            opt_ptr(data),
+            opt_ptr(insert_before),
+        )
+    }
+
+    /// Sets the PAD entry of the peer `pad_id` to `data`, or deletes it if
+    /// `data` is `None`. A new entry is inserted before the entry of
+    /// `insert_before`, or at the end.
+    ///
+    /// # Errors
+    ///
+    /// * [`Status::INVALID_PARAMETER`]: the selector or the data is not
+    ///   valid.
+    /// * [`Status::NOT_FOUND`]: the entry of `insert_before`, or the entry
+    ///   to delete, doesn't exist.
+    /// * [`Status::OUT_OF_RESOURCES`]: there is not enough memory.
+    pub fn set_pad_entry(
+        &mut self,
+        pad_id: &IpsecPadId,
+        data: Option<&PadData>,
+        insert_before: Option<&IpsecPadId>,
+    ) -> Result {
+        self.set_data(
+            IpsecConfigDataType::PAD,
+            ptr::from_ref(pad_id).cast(),
+            opt_ptr(data),
+            opt_ptr(insert_before),
+        )
+    }
+
+    fn set_data(
+        &mut self,
+        data_type: IpsecConfigDataType,
+        selector: *const c_void,
+        data: *const c_void,
+        insert_before: *const c_void,
+    ) -> Result {
+        unsafe { (self.0.set_data)(&self.0, data_type, selector, data, insert_before) }.to_result()
+    }
+
+    /// Reads the data of the entry of `selector` into `buffer`, and returns
+    /// its size.
+    ///
+    /// The data is an `EFI_IPSEC_SPD_DATA`, `EFI_IPSEC_SA_DATA2` or
+    /// `EFI_IPSEC_PAD_DATA`, followed by the buffers it points to.
+    ///
+    /// # Errors
+    ///
+    /// * [`Status::BUFFER_TOO_SMALL`]: `buffer` is too small. The required
+    ///   size is returned in the error data.
+    /// * [`Status::NOT_FOUND`]: the entry doesn't exist.
+    pub fn get_data(
+        &mut self,
+        selector: ConfigSelector,
+        buffer: &mut [u8],
+    ) -> Result<usize, Option<usize>> {
+        let (data_type, selector) = selector.to_raw();
+        let mut size = buffer.len();
+        unsafe {
+            (self.0.get_data)(
+                &self.0,
+                data_type,
+                selector,
+                &mut size,
+                buffer.as_mut_ptr().cast(),
+            )
+        }
+        .to_result_with(
+            || size,
+            |status| (status == Status::BUFFER_TOO_SMALL).then_some(size),
+        )
+    }
+
+    /// Returns the data of the entry of `selector`.
+    ///
+    /// # Errors
+    ///
+    /// See [`get_data`](Self::get_data).
+    #[cfg(feature = "alloc")]
+    pub fn get_data_vec(&mut self, selector: ConfigSelector) -> Result<Vec<u8>> {
+        let mut data = Vec::new();
+        loop {
+            match self.get_data(selector, &mut data) {
+                Ok(size) => {
+                    data.truncate(size);
+                    return Ok(data);
+                }
+                Err(err) => match *err.data() {
+                    Some(size) if size > data.len() => data = vec![0; size],
+                    _ => return Err(err.to_err_without_payload()),
+                },
+            }
+        }
+    }
+
+    /// Replaces the selector in `buffer` with the selector of the next entry
+    /// of the database `data_type`, and returns its size. A zeroed selector
+    /// is followed by the first entry.
+    ///
+    /// This is needed for SPD selectors, whose addresses are stored after
+    /// them. The other selectors can be read with [`next_sa_id`] and
+    /// [`next_pad_id`].
+    ///
+    /// # Errors
+    ///
+    /// * [`Status::BUFFER_TOO_SMALL`]: `buffer` is too small. The required
+    ///   size is returned in the error data.
+    /// * [`Status::NOT_FOUND`]: there are no more entries.
+    ///
+    /// [`next_sa_id`]: Self::next_sa_id
+    /// [`next_pad_id`]: Self::next_pad_id
+    pub fn get_next_selector(
+        &mut self,
+        data_type: IpsecConfigDataType,
+        buffer: &mut [u8],
+    ) -> Result<usize, Option<usize>> {
+        let mut size = buffer.len();
+        unsafe {
+            (self.0.get_next_selector)(&self.0, data_type, &mut size, buffer.as_mut_ptr().cast())
+        }
+        .to_result_with(
+            || size,
+            |status| (status == Status::BUFFER_TOO_SMALL).then_some(size),
+        )
+    }
+
+    /// Returns the security association of the SAD entry after the entry of
+    /// `previous`, or the first one. Returns `None` at the end.
+    ///
+    /// # Errors
+    ///
+    /// See [`get_next_selector`](Self::get_next_selector).
+    pub fn next_sa_id(&mut self, previous: Option<&IpsecSaId>) -> Result<Option<IpsecSaId>> {
+        let mut sa_id = previous.copied().unwrap_or(IpsecSaId {
+            spi: 0,
+            proto: IpsecProtocolType::AH,
+            dest_address: Default::default(),
+        });
+        self.next_selector(IpsecConfigDataType::SAD, &mut sa_id)
+            .map(|found| found.then_some(sa_id))
+    }
+
+    /// Returns the peer of the PAD entry after the entry of `previous`, or
+    /// the first one. Returns `None` at the end.
+    ///
+    /// # Errors
+    ///
+    /// See [`get_next_selector`](Self::get_next_selector).
+    pub fn next_pad_id(&mut self, previous: Option<&IpsecPadId>) -> Result<Option<IpsecPadId>> {
+        let mut pad_id = previous.copied().unwrap_or(IpsecPadId {
+            peer_id_valid: false.into(),
+            id: IpsecPadIdValue {
+                peer_id: [0; MAX_PEERID_LEN],
+            },
+        });
+        self.next_selector(IpsecConfigDataType::PAD, &mut pad_id)
+            .map(|found| found.then_some(pad_id))
+    }
+
+    fn next_selector<T>(
+        &mut self,
+        data_type: IpsecConfigDataType,
+        selector: &mut T,
+    ) -> Result<bool> {
+        let mut size = mem::size_of::<T>();
+        let status = unsafe {
+            (self.0.get_next_selector)(
+                &self.0,
+                data_type,
+                &mut size,
+                ptr::from_mut(selector).cast(),
+            )
+        };
+        match status {
+            Status::NOT_FOUND => Ok(false),
+            status => status.to_result_with_val(|| true),
+        }
+    }
+
+    /// Registers `event` to be signaled when the database `data_type`
+    /// changes.
+    ///
+    /// # Errors
+    ///
+    /// * [`Status::ACCESS_DENIED`]: `event` is already registered.
+    /// * [`Status::OUT_OF_RESOURCES`]: there is not enough memory.
+    pub fn register_data_notify(
+        &mut self,
+        data_type: IpsecConfigDataType,
+        event: &Event,
+    ) -> Result {
+        unsafe { (self.0.register_data_notify)(&self.0, data_type, event.as_ptr()) }.to_result()
+    }
+
+    /// Unregisters `event` registered with [`register_data_notify`].
+    ///
+    /// # Errors
+    ///
+    /// * [`Status::NOT_FOUND`]: `event` is not registered.
+    ///
+    /// [`register_data_notify`]: Self::register_data_notify
+    pub fn unregister_data_notify(
+        &mut self,
+        data_type: IpsecConfigDataType,
+        event: &Event,
+    ) -> Result {
+        unsafe { (self.0.unregister_data_notify)(&self.0, data_type, event.as_ptr()) }.to_result()
+    }
+}
+
+fn opt_ptr<T>(value: Option<&T>) -> *const c_void {
+    value.map_or(ptr::null(), |value| ptr::from_ref(value).cast())
+}
+
+/// Selector of an entry of one of the databases of [`IpsecConfig`].
+#[derive(Clone, Copy, Debug)]
+pub enum ConfigSelector<'a> {
+    /// SPD entry.
+    Spd(&'a SpdSelector<'a>),
+    /// SAD entry.
+    Sad(&'a IpsecSaId),
+    /// PAD entry.
+    Pad(&'a IpsecPadId),
+}
+
+impl ConfigSelector<'_> {
+    const fn to_raw(self) -> (IpsecConfigDataType, *const c_void) {
+        match self {
+            Self::Spd(selector) => (IpsecConfigDataType::SPD, ptr::from_ref(selector).cast()),
+            Self::Sad(sa_id) => (IpsecConfigDataType::SAD, ptr::from_ref(sa_id).cast()),
+            Self::Pad(pad_id) => (IpsecConfigDataType::PAD, ptr::from_ref(pad_id).cast()),
+        }
+    }
+}
+
+/// Selector of an SPD entry, which matches traffic by address, next layer
+/// protocol and port.
+///
+/// An empty address list and the default protocol and ports match all
+/// traffic.
+#[derive(Debug)]
+#[repr(transparent)]
+pub struct SpdSelector<'a> {
+    raw: IpsecSpdSelector,
+    _lifetime: PhantomData<&'a [IpAddressInfo]>,
+}
+
+impl<'a> SpdSelector<'a> {
+    /// Next layer protocol that matches all protocols.
+    pub const ANY_PROTOCOL: u16 = 0xffff;
+
+    /// Port that matches all ports.
+    pub const ANY_PORT: u16 = 0;
+
+    /// Creates a selector of the traffic between the `local` and `remote`
+    /// addresses, with any protocol and port.
+    ///
+    /// # Errors
+    ///
+    /// * [`Status::INVALID_PARAMETER`]: an address list has more than
+    ///   [`u32::MAX`] entries.
+    pub fn new(local: &'a [IpAddressInfo], remote: &'a [IpAddressInfo]) -> Result<Self> {
+        let count = |addresses: &[IpAddressInfo]| {
+            u32::try_from(addresses.len()).map_err(|_| Status::INVALID_PARAMETER)
+        };
+        Ok(Self {
+            raw: IpsecSpdSelector {
+                local_address_count: count(local)?,
+                local_address: local.as_ptr().cast_mut(),
+                remote_address_count: count(remote)?,
+                remote_address: remote.as_ptr().cast_mut(),
+                next_layer_protocol: Self::ANY_PROTOCOL,
+                local_port: Self::ANY_PORT,
+                local_port_range: 0,
+                remote_port: Self::ANY_PORT,
+                remote_port_range: 0,
+            },
+            _lifetime: PhantomData,
+        })
+    }
+
+    /// Sets the next layer protocol, e.g. 6 for TCP.
+    #[must_use]
+    pub const fn with_next_layer_protocol(mut self, protocol: u16) -> Self {
+        self.raw.next_layer_protocol = protocol;
+        self
+    }
+
+    /// Sets the local ports, which are `port` up to `port + range`.
+    #[must_use]
+    pub const fn with_local_ports(mut self, port: u16, range: u16) -> Self {
+        self.raw.local_port = port;
+        self.raw.local_port_range = range;
+        self
+    }
+
+    /// Sets the remote ports, which are `port` up to `port + range`.
+    #[must_use]
+    pub const fn with_remote_ports(mut self, port: u16, range: u16) -> Self {
+        self.raw.remote_port = port;
+        self.raw.remote_port_range = range;
+        self
+    }
+}
+
+/// Processing policy of an SPD entry with the [`IpsecAction::PROTECT`]
+/// action.
+#[derive(Debug)]
+#[repr(transparent)]
+pub struct ProcessPolicy<'a> {
+    raw: IpsecProcessPolicy,
+    _lifetime: PhantomData<&'a IpsecTunnelOption>,
+}
+
+impl<'a> ProcessPolicy<'a> {
+    /// Creates a policy that protects the traffic with `proto` in `mode`,
+    /// using the given algorithms.
+    #[must_use]
+    pub const fn new(
+        mode: IpsecMode,
+        proto: IpsecProtocolType,
+        auth_algorithm: IpsecAuthAlgorithm,
+        encrypt_algorithm: IpsecEncryptAlgorithm,
+    ) -> Self {
+        Self {
+            raw: IpsecProcessPolicy {
+                ext_seq_num: uefi_raw::Boolean::FALSE,
+                seq_overflow: uefi_raw::Boolean::FALSE,
+                frag_check: uefi_raw::Boolean::FALSE,
+                mode,
+                tunnel_option: ptr::null_mut(),
+                proto,
+                auth_algo_id: auth_algorithm,
+                enc_algo_id: encrypt_algorithm,
+            },
+            _lifetime: PhantomData,
+        }
+    }
+
+    /// Sets the tunnel endpoints, which are required in tunnel mode.
+    #[must_use]
+    pub const fn with_tunnel(mut self, tunnel: &'a IpsecTunnelOption) -> Self {
+        self.raw.tunnel_option = ptr::from_ref(tunnel).cast_mut();
+        self
+    }
+
+    /// Sets whether 64-bit extended sequence numbers are used.
+    #[must_use]
+    pub const fn with_extended_sequence_numbers(mut self, enabled: bool) -> Self {
+        self.raw.ext_seq_num = uefi_raw::Boolean(enabled as u8);
+        self
+    }
+
+    /// Sets whether the sequence number may overflow instead of requiring
+    /// a new security association.
+    #[must_use]
+    pub const fn with_sequence_overflow(mut self, enabled: bool) -> Self {
+        self.raw.seq_overflow = uefi_raw::Boolean(enabled as u8);
+        self
+    }
+
+    /// Sets whether fragments are checked in tunnel mode.
+    #[must_use]
+    pub const fn with_fragment_check(mut self, enabled: bool) -> Self {
+        self.raw.frag_check = uefi_raw::Boolean(enabled as u8);
+        self
+    }
+}
+
+/// Data of an SPD entry.
+#[derive(Debug)]
+#[repr(transparent)]
+pub struct SpdData<'a> {
+    raw: IpsecSpdData,
+    _lifetime: PhantomData<&'a ProcessPolicy<'a>>,
+}
+
+impl<'a> SpdData<'a> {
+    /// Creates the policy `name` with `action` for the traffic in
+    /// `direction`.
+    ///
+    /// # Errors
+    ///
+    /// * [`Status::INVALID_PARAMETER`]: `name` is not shorter than
+    ///   [`MAX_PEERID_LEN`].
+    pub fn new(name: &[u8], direction: IpsecTrafficDirection, action: IpsecAction) -> Result<Self> {
+        if name.len() >= MAX_PEERID_LEN {
+            return Err(Status::INVALID_PARAMETER.into());
+        }
+        let mut raw_name = [0; MAX_PEERID_LEN];
+        raw_name[..name.len()].copy_from_slice(name);
+        Ok(Self {
+            raw: IpsecSpdData {
+                name: raw_name,
+                package_flag: 0,
+                traffic_direction: direction,
+                action,
+                processing_policy: ptr::null_mut(),
+                sa_id_count: 0,
+                sa_id: [IpsecSaId {
+                    spi: 0,
+                    proto: IpsecProtocolType::AH,
+                    dest_address: uefi_raw::IpAddress { addr: [0; 4] },
+                }],
+            },
+            _lifetime: PhantomData,
+        })
+    }
+
+    /// Sets the processing policy, which is required for the
+    /// [`IpsecAction::PROTECT`] action.
+    #[must_use]
+    pub const fn with_policy(mut self, policy: &'a ProcessPolicy<'a>) -> Self {
+        self.raw.processing_policy = ptr::from_ref(&policy.raw).cast_mut();
+        self
+    }
+}
+
+/// Data of an SAD entry, i.e. a manually keyed security association.
+#[derive(Debug)]
+#[repr(transparent)]
+pub struct SaData<'a> {
+    raw: IpsecSaData2,
+    _lifetime: PhantomData<&'a [u8]>,
+}
+
+impl<'a> SaData<'a> {
+    /// Creates an Encapsulating Security Payload association with the given
+    /// algorithms and keys.
+    #[must_use]
+    pub const fn esp(
+        mode: IpsecMode,
+        encrypt_algorithm: IpsecEncryptAlgorithm,
+        encrypt_key: &'a [u8],
+        auth_algorithm: IpsecAuthAlgorithm,
+        auth_key: &'a [u8],
+    ) -> Self {
+        Self::new(
+            mode,
+            IpsecAlgoInfo {
+                esp_algo_info: IpsecEspAlgoInfo {
+                    enc_algo_id: encrypt_algorithm,
+                    enc_key_length: encrypt_key.len(),
+                    enc_key: encrypt_key.as_ptr().cast_mut().cast(),
+                    auth_algo_id: auth_algorithm,
+                    auth_key_length: auth_key.len(),
+                    auth_key: auth_key.as_ptr().cast_mut().cast(),
+                },
+            },
+        )
+    }
+
+    /// Creates an Authentication Header association with the given
+    /// algorithm and key.
+    #[must_use]
+    pub const fn ah(
+        mode: IpsecMode,
+        auth_algorithm: IpsecAuthAlgorithm,
+        auth_key: &'a [u8],
+    ) -> Self {
+        Self::new(
+            mode,
+            IpsecAlgoInfo {
+                ah_algo_info: IpsecAhAlgoInfo {
+                    auth_algo_id: auth_algorithm,
+                    auth_key_length: auth_key.len(),
+                    auth_key: auth_key.as_ptr().cast_mut().cast(),
+                },
+            },
+        )
+    }
+
+    const fn new(mode: IpsecMode, algo_info: IpsecAlgoInfo) -> Self {
+        Self {
+            raw: IpsecSaData2 {
+                mode,
+                sn_count: 0,
+                anti_replay_window: 0,
+                algo_info,
+                sa_lifetime: IpsecSaLifetime {
+                    byte_count: 0,
+                    soft_lifetime: 0,
+                    hard_lifetime: 0,
+                },
+                path_mtu: 0,
+                spd_selector: ptr::null_mut(),
+                manual_set: uefi_raw::Boolean::TRUE,
+                tunnel_source_address: uefi_raw::IpAddress { addr: [0; 4] },
+                tunnel_destination_address: uefi_raw::IpAddress { addr: [0; 4] },
+            },
+            _lifetime: PhantomData,
+        }
+    }
+
+    /// Sets the SPD selector of the traffic the association applies to.
+    #[must_use]
+    pub const fn with_selector(mut self, selector: &'a SpdSelector<'a>) -> Self {
+        self.raw.spd_selector = ptr::from_ref(&selector.raw).cast_mut();
+        self
+    }
+
+    /// Sets the lifetime of the association. Zero values mean no limit.
+    #[must_use]
+    pub const fn with_lifetime(mut self, lifetime: IpsecSaLifetime) -> Self {
+        self.raw.sa_lifetime = lifetime;
+        self
+    }
+
+    /// Sets the size of the anti-replay window, in multiples of 32 packets.
+    /// Zero disables the anti-replay check.
+    #[must_use]
+    pub const fn with_anti_replay_window(mut self, window: u8) -> Self {
+        self.raw.anti_replay_window = window;
+        self
+    }
+
+    /// Sets the tunnel endpoints, which are required in tunnel mode.
+    #[must_use]
+    pub fn with_tunnel(mut self, source: IpAddr, destination: IpAddr) -> Self {
+        self.raw.tunnel_source_address = source.into();
+        self.raw.tunnel_destination_address = destination.into();
+        self
+    }
+}
+
+/// Data of a PAD entry, i.e. the credentials of a peer.
+#[derive(Debug)]
+#[repr(transparent)]
+pub struct PadData<'a> {
+    raw: IpsecPadData,
+    _lifetime: PhantomData<&'a [u8]>,
+}
+
+impl<'a> PadData<'a> {
+    /// Creates the credentials `auth_data` for `method` with `protocol`,
+    /// e.g. a pre-shared secret or a certificate.
+    #[must_use]
+    pub const fn new(
+        protocol: IpsecAuthProtocolType,
+        method: IpsecAuthMethod,
+        auth_data: &'a [u8],
+    ) -> Self {
+        Self {
+            raw: IpsecPadData {
+                auth_protocol: protocol,
+                auth_method: method,
+                ike_id_flag: uefi_raw::Boolean::FALSE,
+                auth_data_size: auth_data.len(),
+                auth_data: auth_data.as_ptr().cast_mut().cast(),
+                revocation_data_size: 0,
+                revocation_data: ptr::null_mut(),
+            },
+            _lifetime: PhantomData,
+        }
+    }
+
+    /// Sets the certificate revocation list.
+    #[must_use]
+    pub const fn with_revocation_data(mut self, revocation_data: &'a [u8]) -> Self {
+        self.raw.revocation_data_size = revocation_data.len();
+        self.raw.revocation_data = revocation_data.as_ptr().cast_mut().cast();
+        self
+    }
+
+    /// Sets whether the IKE identity of the peer is checked against the
+    /// PAD selector.
+    #[must_use]
+    pub const fn with_ike_id_check(mut self, enabled: bool) -> Self {
+        self.raw.ike_id_flag = uefi_raw::Boolean(enabled as u8);
+        self
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_spd_data_name() {
+        let name = [b'a'; MAX_PEERID_LEN];
+        let new = |name| SpdData::new(name, IpsecTrafficDirection::OUTBOUND, IpsecAction::BYPASS);
+        assert_eq!(new(&name).unwrap_err().status(), Status::INVALID_PARAMETER);
+
+        // The name must leave room for the null terminator.
+        let data = new(&name[1..]).unwrap();
+        assert_eq!(data.raw.name[..MAX_PEERID_LEN - 1], name[1..]);
+        assert_eq!(data.raw.name[MAX_PEERID_LEN - 1], 0);
+    }
+}
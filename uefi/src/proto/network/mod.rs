@@ -4,13 +4,23 @@
 //!
 //! These protocols can be used to interact with network resources.
 
+pub mod eap;
 pub mod http;
 pub mod ip4config2;
+pub mod ipsec;
 pub mod pxe;
 pub mod snp;
+pub mod tcp4;
+pub mod tcp6;
+pub mod udp4;
+pub mod udp6;
 
 pub use uefi_raw::MacAddress;
 
+use crate::{Handle, Result, StatusExt};
+use core::ptr;
+use uefi_raw::protocol::driver::ServiceBindingProtocol;
+
 /// Represents an IPv4/v6 address.
 ///
 /// Corresponds to the `EFI_IP_ADDRESS` type in the C API.
@@ -101,3 +111,16 @@ impl From<core::net::IpAddr> for IpAddress {
 // NOTE: We cannot impl From<IpAddress> for core::net::IpAddr
 // because IpAddress is a raw union, with nothing indicating
 // whether it should be considered v4 or v6.
+
+/// Creates a child of a service binding protocol.
+fn create_child(binding: &mut ServiceBindingProtocol) -> Result<Handle> {
+    let mut handle = ptr::null_mut();
+    unsafe { (binding.create_child)(binding, &mut handle) }
+        // OK to unwrap: the handle is non-null for Status::SUCCESS.
+        .to_result_with_val(|| unsafe { Handle::from_ptr(handle) }.unwrap())
+}
+
+/// Destroys a child of a service binding protocol.
+fn destroy_child(binding: &mut ServiceBindingProtocol, handle: Handle) -> Result {
+    unsafe { (binding.destroy_child)(binding, handle.as_ptr()) }.to_result()
+}
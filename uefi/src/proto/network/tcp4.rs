@@ -0,0 +1,188 @@
+// SPDX-License-Identifier: MIT OR Apache-2.0
+
+//! TCPv4 protocol.
+//!
+//! See [`Tcp4`]. The async [`TcpStream`] and [`TcpListener`] are easier to
+//! use.
+//!
+//! [`TcpStream`]: crate::net::TcpStream
+//! [`TcpListener`]: crate::net::TcpListener
+
+use crate::proto::unsafe_protocol;
+use crate::{Handle, Result, StatusExt};
+use core::ptr;
+use uefi_raw::protocol::driver::ServiceBindingProtocol;
+use uefi_raw::protocol::network::tcp4::Tcp4Protocol;
+
+pub use uefi_raw::protocol::network::tcp4::{
+    Tcp4AccessPoint, Tcp4CloseToken, Tcp4CompletionToken, Tcp4ConfigData, Tcp4ConnectionState,
+    Tcp4ConnectionToken, Tcp4FragmentData, Tcp4IoToken, Tcp4ListenToken, Tcp4Option, Tcp4Packet,
+    Tcp4ReceiveData, Tcp4TransmitData,
+};
+
+/// TCPv4 [`Protocol`]: a TCP connection over IPv4.
+///
+/// An instance is a child of [`Tcp4Binding`] on a network device. The
+/// requests of the protocol are asynchronous: they complete by signaling the
+/// event in their token.
+///
+/// [`Protocol`]: crate::proto::Protocol
+#[derive(Debug)]
+#[repr(transparent)]
+#[unsafe_protocol(Tcp4Protocol::GUID)]
+pub struct Tcp4(Tcp4Protocol);
+
+impl Tcp4 {
+    /// Returns the state of the connection.
+    pub fn state(&self) -> Result<Tcp4ConnectionState> {
+        let mut state = Tcp4ConnectionState::CLOSED;
+        unsafe {
+            (self.0.get_mode_data)(
+                &self.0,
+                &mut state,
+                ptr::null_mut(),
+                ptr::null_mut(),
+                ptr::null_mut(),
+                ptr::null_mut(),
+            )
+        }
+        .to_result_with_val(|| state)
+    }
+
+    /// Returns the configuration of the instance, without the control
+    /// options.
+    ///
+    /// # Errors
+    ///
+    /// * [`Status::NOT_STARTED`]: the instance has not been configured.
+    ///
+    /// [`Status::NOT_STARTED`]: crate::Status::NOT_STARTED
+    pub fn config_data(&self) -> Result<Tcp4ConfigData> {
+        let mut config = Tcp4ConfigData {
+            type_of_service: 0,
+            time_to_live: 0,
+            access_point: Tcp4AccessPoint::default(),
+            control_option: ptr::null_mut(),
+        };
+        unsafe {
+            (self.0.get_mode_data)(
+                &self.0,
+                ptr::null_mut(),
+                &mut config,
+                ptr::null_mut(),
+                ptr::null_mut(),
+                ptr::null_mut(),
+            )
+        }
+        .to_result_with_val(|| config)
+    }
+
+    /// Configures the instance, or resets it to the unconfigured state if
+    /// `config` is `None`.
+    ///
+    /// # Errors
+    ///
+    /// * [`Status::NO_MAPPING`]: the default address of the device has not
+    ///   been configured yet, e.g. by DHCP.
+    /// * [`Status::ACCESS_DENIED`]: the instance is already configured.
+    /// * [`Status::INVALID_PARAMETER`]: `config` is invalid.
+    ///
+    /// [`Status::NO_MAPPING`]: crate::Status::NO_MAPPING
+    /// [`Status::ACCESS_DENIED`]: crate::Status::ACCESS_DENIED
+    /// [`Status::INVALID_PARAMETER`]: crate::Status::INVALID_PARAMETER
+    pub fn configure(&mut self, config: Option<&Tcp4ConfigData>) -> Result {
+        let config = config.map_or(ptr::null(), ptr::from_ref);
+        unsafe { (self.0.configure)(&mut self.0, config) }.to_result()
+    }
+
+    /// Starts the active open of a connection.
+    ///
+    /// # Safety
+    ///
+    /// `token` must stay valid and must not move until its event is
+    /// signaled or the request is [canceled](Self::cancel).
+    pub unsafe fn connect(&mut self, token: *mut Tcp4ConnectionToken) -> Result {
+        unsafe { (self.0.connect)(&mut self.0, token) }.to_result()
+    }
+
+    /// Starts to wait for an incoming connection. When the token's event is
+    /// signaled, the token contains the handle of a new [`Tcp4`] child for
+    /// the connection.
+    ///
+    /// # Safety
+    ///
+    /// `token` must stay valid and must not move until its event is
+    /// signaled or the request is [canceled](Self::cancel).
+    pub unsafe fn accept(&mut self, token: *mut Tcp4ListenToken) -> Result {
+        unsafe { (self.0.accept)(&mut self.0, token) }.to_result()
+    }
+
+    /// Queues data for transmission.
+    ///
+    /// # Safety
+    ///
+    /// `token`, its transmit data and the fragment buffers must stay valid
+    /// and must not move until its event is signaled or the request is
+    /// [canceled](Self::cancel).
+    pub unsafe fn transmit(&mut self, token: *mut Tcp4IoToken) -> Result {
+        unsafe { (self.0.transmit)(&mut self.0, token) }.to_result()
+    }
+
+    /// Queues a buffer for received data.
+    ///
+    /// # Safety
+    ///
+    /// `token`, its receive data and the fragment buffers must stay valid
+    /// and must not move until its event is signaled or the request is
+    /// [canceled](Self::cancel).
+    pub unsafe fn receive(&mut self, token: *mut Tcp4IoToken) -> Result {
+        unsafe { (self.0.receive)(&mut self.0, token) }.to_result()
+    }
+
+    /// Starts to close the connection, gracefully or by a reset.
+    ///
+    /// # Safety
+    ///
+    /// `token` must stay valid and must not move until its event is
+    /// signaled.
+    pub unsafe fn close(&mut self, token: *mut Tcp4CloseToken) -> Result {
+        unsafe { (self.0.close)(&mut self.0, token) }.to_result()
+    }
+
+    /// Cancels the request of `token`, or all requests if `token` is null.
+    /// The event of a canceled request is signaled with the status
+    /// [`Status::ABORTED`], and the firmware no longer uses the token.
+    ///
+    /// # Safety
+    ///
+    /// `token` must be null or point to the completion token of a request.
+    ///
+    /// [`Status::ABORTED`]: crate::Status::ABORTED
+    pub unsafe fn cancel(&mut self, token: *mut Tcp4CompletionToken) -> Result {
+        unsafe { (self.0.cancel)(&mut self.0, token) }.to_result()
+    }
+
+    /// Polls the network device for packets, which can speed up requests.
+    pub fn poll(&mut self) -> Result {
+        unsafe { (self.0.poll)(&mut self.0) }.to_result()
+    }
+}
+
+/// TCPv4 Service Binding Protocol, which creates [`Tcp4`] children.
+#[derive(Debug)]
+#[repr(transparent)]
+#[unsafe_protocol(Tcp4Protocol::SERVICE_BINDING_GUID)]
+pub struct Tcp4Binding(ServiceBindingProtocol);
+
+impl Tcp4Binding {
+    /// Creates a child handle with a [`Tcp4`] protocol.
+    pub fn create_child(&mut self) -> Result<Handle> {
+        super::create_child(&mut self.0)
+    }
+
+    /// Destroys a child created by [`create_child`](Self::create_child) or
+    /// by [`Tcp4::accept`].
+    pub fn destroy_child(&mut self, handle: Handle) -> Result {
+        super::destroy_child(&mut self.0, handle)
+    }
+}
@@ -0,0 +1,137 @@
+// SPDX-License-Identifier: MIT OR Apache-2.0
+
+//! UDPv4 protocol.
+//!
+//! See [`Udp4`]. The async [`UdpSocket`] is easier to use.
+//!
+//! [`UdpSocket`]: crate::net::UdpSocket
+
+use crate::proto::unsafe_protocol;
+use crate::{Handle, Result, StatusExt};
+use core::ptr;
+use uefi_raw::protocol::driver::ServiceBindingProtocol;
+use uefi_raw::protocol::network::udp4::Udp4Protocol;
+use uefi_raw::{Boolean, Ipv4Address};
+
+pub use uefi_raw::protocol::network::udp4::{
+    Udp4CompletionToken, Udp4ConfigData, Udp4FragmentData, Udp4Packet, Udp4ReceiveData,
+    Udp4SessionData, Udp4TransmitData,
+};
+
+/// UDPv4 [`Protocol`]: a UDP socket over IPv4.
+///
+/// An instance is a child of [`Udp4Binding`] on a network device. The
+/// requests of the protocol are asynchronous: they complete by signaling the
+/// event in their token.
+///
+/// [`Protocol`]: crate::proto::Protocol
+#[derive(Debug)]
+#[repr(transparent)]
+#[unsafe_protocol(Udp4Protocol::GUID)]
+pub struct Udp4(Udp4Protocol);
+
+impl Udp4 {
+    /// Returns the configuration of the instance.
+    ///
+    /// # Errors
+    ///
+    /// * [`Status::NOT_STARTED`]: the instance has not been configured.
+    ///
+    /// [`Status::NOT_STARTED`]: crate::Status::NOT_STARTED
+    pub fn config_data(&self) -> Result<Udp4ConfigData> {
+        let mut config = Udp4ConfigData::default();
+        unsafe {
+            (self.0.get_mode_data)(
+                &self.0,
+                &mut config,
+                ptr::null_mut(),
+                ptr::null_mut(),
+                ptr::null_mut(),
+            )
+        }
+        .to_result_with_val(|| config)
+    }
+
+    /// Configures the instance, or resets it to the unconfigured state if
+    /// `config` is `None`.
+    ///
+    /// # Errors
+    ///
+    /// * [`Status::NO_MAPPING`]: the default address of the device has not
+    ///   been configured yet, e.g. by DHCP.
+    /// * [`Status::ACCESS_DENIED`]: the port is in use, or the instance is
+    ///   already configured.
+    /// * [`Status::INVALID_PARAMETER`]: `config` is invalid.
+    ///
+    /// [`Status::NO_MAPPING`]: crate::Status::NO_MAPPING
+    /// [`Status::ACCESS_DENIED`]: crate::Status::ACCESS_DENIED
+    /// [`Status::INVALID_PARAMETER`]: crate::Status::INVALID_PARAMETER
+    pub fn configure(&mut self, config: Option<&Udp4ConfigData>) -> Result {
+        let config = config.map_or(ptr::null(), ptr::from_ref);
+        unsafe { (self.0.configure)(&mut self.0, config) }.to_result()
+    }
+
+    /// Joins the multicast group `address`, or leaves it if `join` is false.
+    pub fn groups(&mut self, join: bool, address: Ipv4Address) -> Result {
+        unsafe { (self.0.groups)(&mut self.0, Boolean::from(join), &address) }.to_result()
+    }
+
+    /// Queues a datagram for transmission.
+    ///
+    /// # Safety
+    ///
+    /// `token`, its transmit data, the session data and the fragment
+    /// buffers must stay valid and must not move until its event is
+    /// signaled or the request is [canceled](Self::cancel).
+    pub unsafe fn transmit(&mut self, token: *mut Udp4CompletionToken) -> Result {
+        unsafe { (self.0.transmit)(&mut self.0, token) }.to_result()
+    }
+
+    /// Starts to wait for a datagram. When the token's event is signaled,
+    /// the token points to receive data allocated by the firmware, which
+    /// must be freed by signaling its recycle event.
+    ///
+    /// # Safety
+    ///
+    /// `token` must stay valid and must not move until its event is
+    /// signaled or the request is [canceled](Self::cancel).
+    pub unsafe fn receive(&mut self, token: *mut Udp4CompletionToken) -> Result {
+        unsafe { (self.0.receive)(&mut self.0, token) }.to_result()
+    }
+
+    /// Cancels the request of `token`, or all requests if `token` is null.
+    /// The event of a canceled request is signaled with the status
+    /// [`Status::ABORTED`], and the firmware no longer uses the token.
+    ///
+    /// # Safety
+    ///
+    /// `token` must be null or point to the token of a request.
+    ///
+    /// [`Status::ABORTED`]: crate::Status::ABORTED
+    pub unsafe fn cancel(&mut self, token: *mut Udp4CompletionToken) -> Result {
+        unsafe { (self.0.cancel)(&mut self.0, token) }.to_result()
+    }
+
+    /// Polls the network device for packets, which can speed up requests.
+    pub fn poll(&mut self) -> Result {
+        unsafe { (self.0.poll)(&mut self.0) }.to_result()
+    }
+}
+
+/// UDPv4 Service Binding Protocol, which creates [`Udp4`] children.
+#[derive(Debug)]
+#[repr(transparent)]
+#[unsafe_protocol(Udp4Protocol::SERVICE_BINDING_GUID)]
+pub struct Udp4Binding(ServiceBindingProtocol);
+
+impl Udp4Binding {
+    /// Creates a child handle with a [`Udp4`] protocol.
+    pub fn create_child(&mut self) -> Result<Handle> {
+        super::create_child(&mut self.0)
+    }
+
+    /// Destroys a child created by [`create_child`](Self::create_child).
+    pub fn destroy_child(&mut self, handle: Handle) -> Result {
+        super::destroy_child(&mut self.0, handle)
+    }
+}
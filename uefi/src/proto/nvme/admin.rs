@@ -0,0 +1,990 @@
+// SPDX-License-Identifier: MIT OR Apache-2.0
+
+//! Typed wrappers for common NVMe admin commands.
+//!
+//! The commands are available as methods of [`NvmeNamespace`]. Commands
+//! that concern the whole controller, like [`identify_controller`] or the
+//! firmware commands, should be sent to [`NvmePassThru::controller`].
+//!
+//! [`format_nvm`] and [`sanitize`] irrecoverably erase data, so they require
+//! an [`EraseConfirmation`].
+//!
+//! [`format_nvm`]: NvmeNamespace::format_nvm
+//! [`sanitize`]: NvmeNamespace::sanitize
+//! [`identify_controller`]: NvmeNamespace::identify_controller
+//! [`NvmePassThru::controller`]: super::pass_thru::NvmePassThru::controller
+
+use super::pass_thru::NvmeNamespace;
+use super::{NvmeQueueType, NvmeRequestBuilder, NvmeResponse};
+use crate::{Result, Status, boot};
+use alloc::vec::Vec;
+use core::fmt::{self, Debug, Formatter};
+use core::time::Duration;
+
+/// Size of the data returned by the Identify command.
+const IDENTIFY_SIZE: usize = 4096;
+
+/// Size of the SMART / Health Information log page.
+const SMART_LOG_SIZE: usize = 512;
+
+/// Size of the defined part of the Sanitize Status log page.
+const SANITIZE_LOG_SIZE: usize = 20;
+
+/// Interval of polling the Sanitize Status log page.
+const SANITIZE_POLL_INTERVAL: Duration = Duration::from_secs(1);
+
+newtype_enum! {
+    /// Opcodes of the NVMe admin commands with typed wrappers.
+    pub enum NvmeAdminOpcode: u8 => {
+        /// Get Log Page.
+        GET_LOG_PAGE = 0x02,
+        /// Identify.
+        IDENTIFY = 0x06,
+        /// Firmware Commit.
+        FIRMWARE_COMMIT = 0x10,
+        /// Firmware Image Download.
+        FIRMWARE_IMAGE_DOWNLOAD = 0x11,
+        /// Format NVM.
+        FORMAT_NVM = 0x80,
+        /// Sanitize.
+        SANITIZE = 0x84,
+    }
+}
+
+newtype_enum! {
+    /// Identifiers of log pages for [`NvmeNamespace::get_log_page`].
+    pub enum NvmeLogPage: u8 => {
+        /// Error Information.
+        ERROR_INFORMATION = 0x01,
+        /// SMART / Health Information.
+        SMART_HEALTH = 0x02,
+        /// Firmware Slot Information.
+        FIRMWARE_SLOT = 0x03,
+        /// Sanitize Status.
+        SANITIZE_STATUS = 0x81,
+    }
+}
+
+newtype_enum! {
+    /// What [`NvmeNamespace::firmware_commit`] does with the downloaded
+    /// firmware image.
+    pub enum FirmwareCommitAction: u8 => {
+        /// Replace the image in the slot without activating it.
+        REPLACE = 0x00,
+        /// Replace the image in the slot and activate it at the next reset.
+        REPLACE_AND_ACTIVATE = 0x01,
+        /// Activate the existing image in the slot at the next reset.
+        ACTIVATE = 0x02,
+        /// Replace the image in the slot and activate it immediately.
+        REPLACE_AND_ACTIVATE_IMMEDIATELY = 0x03,
+    }
+}
+
+newtype_enum! {
+    /// Secure erase that [`NvmeNamespace::format_nvm`] performs.
+    pub enum FormatSecureErase: u8 => {
+        /// No secure erase. The data may still be readable from the media.
+        NONE = 0x00,
+        /// Erase all user data.
+        USER_DATA = 0x01,
+        /// Erase the key that all user data is encrypted with.
+        CRYPTOGRAPHIC = 0x02,
+    }
+}
+
+newtype_enum! {
+    /// Operations of [`NvmeNamespace::sanitize`].
+    pub enum SanitizeAction: u8 => {
+        /// Leave the failure mode after a failed sanitize operation, without
+        /// erasing data.
+        EXIT_FAILURE_MODE = 0x01,
+        /// Erase all user data by a low-level block erase of the media.
+        BLOCK_ERASE = 0x02,
+        /// Erase the key that all user data is encrypted with.
+        CRYPTO_ERASE = 0x04,
+    }
+}
+
+newtype_enum! {
+    /// State of the most recent sanitize operation, see [`SanitizeStatus`].
+    pub enum SanitizeState: u8 => {
+        /// The controller has never been sanitized.
+        NEVER_SANITIZED = 0x00,
+        /// The last sanitize operation completed successfully.
+        COMPLETED = 0x01,
+        /// A sanitize operation is in progress.
+        IN_PROGRESS = 0x02,
+        /// The last sanitize operation failed.
+        FAILED = 0x03,
+        /// The last sanitize operation completed successfully, without
+        /// deallocating the media.
+        COMPLETED_NO_DEALLOCATE = 0x04,
+    }
+}
+
+/// Confirmation that a command irrecoverably erases data.
+///
+/// This is required by [`NvmeNamespace::format_nvm`] and
+/// [`NvmeNamespace::sanitize`], to make the destructive nature of these
+/// commands obvious at the call site.
+#[derive(Clone, Copy, Debug)]
+pub struct EraseConfirmation(());
+
+impl EraseConfirmation {
+    /// Confirms that all data of the affected namespaces may be erased.
+    #[must_use]
+    pub const fn all_data_will_be_lost() -> Self {
+        Self(())
+    }
+}
+
+/// A temperature reported by an NVMe controller, which uses Kelvin.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Temperature(u16);
+
+impl Temperature {
+    /// Creates a temperature from a value in Kelvin.
+    #[must_use]
+    pub const fn from_kelvin(kelvin: u16) -> Self {
+        Self(kelvin)
+    }
+
+    /// Temperature in Kelvin.
+    #[must_use]
+    pub const fn kelvin(self) -> u16 {
+        self.0
+    }
+
+    /// Temperature in degrees Celsius, rounded down.
+    #[must_use]
+    pub const fn celsius(self) -> i32 {
+        self.0 as i32 - 273
+    }
+}
+
+/// Data returned by the Identify Controller command.
+#[derive(Clone, PartialEq, Eq)]
+pub struct IdentifyController {
+    pci_vendor_id: u16,
+    pci_subsystem_vendor_id: u16,
+    serial_number: [u8; 20],
+    model_number: [u8; 40],
+    firmware_revision: [u8; 8],
+    ieee_oui: [u8; 3],
+    controller_id: u16,
+    version: u32,
+    optional_admin_commands: u16,
+    firmware_updates: u8,
+    warning_temperature: Temperature,
+    critical_temperature: Temperature,
+    firmware_update_granularity: u8,
+    total_capacity: u128,
+    unallocated_capacity: u128,
+    sanitize_capabilities: u32,
+    namespace_count: u32,
+    format_nvm_attributes: u8,
+}
+
+impl IdentifyController {
+    /// Parses the data returned by the Identify Controller command. Returns
+    /// `None` if `data` is shorter than 4096 bytes.
+    #[must_use]
+    pub fn parse(data: &[u8]) -> Option<Self> {
+        let data = data.get(..IDENTIFY_SIZE)?;
+        Some(Self {
+            pci_vendor_id: read_u16(data, 0),
+            pci_subsystem_vendor_id: read_u16(data, 2),
+            serial_number: data[4..24].try_into().unwrap(),
+            model_number: data[24..64].try_into().unwrap(),
+            firmware_revision: data[64..72].try_into().unwrap(),
+            ieee_oui: data[73..76].try_into().unwrap(),
+            controller_id: read_u16(data, 78),
+            version: read_u32(data, 80),
+            optional_admin_commands: read_u16(data, 256),
+            firmware_updates: data[260],
+            warning_temperature: Temperature(read_u16(data, 266)),
+            critical_temperature: Temperature(read_u16(data, 268)),
+            total_capacity: read_u128(data, 280),
+            unallocated_capacity: read_u128(data, 296),
+            firmware_update_granularity: data[319],
+            sanitize_capabilities: read_u32(data, 328),
+            namespace_count: read_u32(data, 516),
+            format_nvm_attributes: data[524],
+        })
+    }
+
+    /// PCI vendor ID.
+    #[must_use]
+    pub const fn pci_vendor_id(&self) -> u16 {
+        self.pci_vendor_id
+    }
+
+    /// PCI subsystem vendor ID.
+    #[must_use]
+    pub const fn pci_subsystem_vendor_id(&self) -> u16 {
+        self.pci_subsystem_vendor_id
+    }
+
+    /// Serial number, without padding.
+    #[must_use]
+    pub fn serial_number(&self) -> &str {
+        ascii_field(&self.serial_number)
+    }
+
+    /// Model number, without padding.
+    #[must_use]
+    pub fn model_number(&self) -> &str {
+        ascii_field(&self.model_number)
+    }
+
+    /// Revision of the active firmware, without padding.
+    #[must_use]
+    pub fn firmware_revision(&self) -> &str {
+        ascii_field(&self.firmware_revision)
+    }
+
+    /// IEEE OUI identifier of the vendor, least significant byte first.
+    #[must_use]
+    pub const fn ieee_oui(&self) -> [u8; 3] {
+        self.ieee_oui
+    }
+
+    /// Controller ID.
+    #[must_use]
+    pub const fn controller_id(&self) -> u16 {
+        self.controller_id
+    }
+
+    /// Supported NVMe version as `(major, minor, tertiary)`, or `(0, 0, 0)`
+    /// for controllers older than version 1.2.
+    #[must_use]
+    pub const fn version(&self) -> (u16, u8, u8) {
+        (
+            (self.version >> 16) as u16,
+            (self.version >> 8) as u8,
+            self.version as u8,
+        )
+    }
+
+    /// Bitmask of the supported optional admin commands (OACS).
+    #[must_use]
+    pub const fn optional_admin_commands(&self) -> u16 {
+        self.optional_admin_commands
+    }
+
+    /// Returns whether the Firmware Commit and Firmware Image Download
+    /// commands are supported.
+    #[must_use]
+    pub const fn supports_firmware_download(&self) -> bool {
+        self.optional_admin_commands & 0x04 != 0
+    }
+
+    /// Returns whether the Format NVM command is supported.
+    #[must_use]
+    pub const fn supports_format_nvm(&self) -> bool {
+        self.optional_admin_commands & 0x02 != 0
+    }
+
+    /// Returns whether [`FormatSecureErase::CRYPTOGRAPHIC`] is supported.
+    #[must_use]
+    pub const fn supports_format_crypto_erase(&self) -> bool {
+        self.format_nvm_attributes & 0x04 != 0
+    }
+
+    /// Returns whether the Format NVM command formats all namespaces of the
+    /// controller, rather than only the one it is sent to.
+    #[must_use]
+    pub const fn format_applies_to_all_namespaces(&self) -> bool {
+        self.format_nvm_attributes & 0x01 != 0
+    }
+
+    /// Returns whether the Sanitize command supports `action`.
+    #[must_use]
+    pub const fn supports_sanitize(&self, action: SanitizeAction) -> bool {
+        let bit = match action {
+            SanitizeAction::EXIT_FAILURE_MODE => return self.sanitize_capabilities & 0x07 != 0,
+            SanitizeAction::CRYPTO_ERASE => 0x01,
+            SanitizeAction::BLOCK_ERASE => 0x02,
+            _ => return false,
+        };
+        self.sanitize_capabilities & bit != 0
+    }
+
+    /// Number of firmware slots.
+    #[must_use]
+    pub const fn firmware_slots(&self) -> u8 {
+        (self.firmware_updates >> 1) & 0x07
+    }
+
+    /// Returns whether the first firmware slot is read only.
+    #[must_use]
+    pub const fn first_firmware_slot_read_only(&self) -> bool {
+        self.firmware_updates & 0x01 != 0
+    }
+
+    /// Granularity of firmware image downloads in bytes, or `None` if there
+    /// is no restriction.
+    #[must_use]
+    pub const fn firmware_update_granularity(&self) -> Option<u32> {
+        match self.firmware_update_granularity {
+            // 0 means that no information is provided, in which case the
+            // spec recommends 4 KiB.
+            0 => Some(4096),
+            0xff => None,
+            n => Some(n as u32 * 4096),
+        }
+    }
+
+    /// Composite temperature above which the controller reports a warning,
+    /// or `None` if not reported.
+    #[must_use]
+    pub const fn warning_temperature(&self) -> Option<Temperature> {
+        nonzero_temperature(self.warning_temperature)
+    }
+
+    /// Composite temperature above which the controller may stop working,
+    /// or `None` if not reported.
+    #[must_use]
+    pub const fn critical_temperature(&self) -> Option<Temperature> {
+        nonzero_temperature(self.critical_temperature)
+    }
+
+    /// Total NVM capacity in bytes, or zero if not reported.
+    #[must_use]
+    pub const fn total_capacity(&self) -> u128 {
+        self.total_capacity
+    }
+
+    /// Unallocated NVM capacity in bytes, or zero if not reported.
+    #[must_use]
+    pub const fn unallocated_capacity(&self) -> u128 {
+        self.unallocated_capacity
+    }
+
+    /// Maximum namespace ID supported by the controller.
+    #[must_use]
+    pub const fn namespace_count(&self) -> u32 {
+        self.namespace_count
+    }
+}
+
+impl Debug for IdentifyController {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.debug_struct("IdentifyController")
+            .field("pci_vendor_id", &self.pci_vendor_id)
+            .field("serial_number", &self.serial_number())
+            .field("model_number", &self.model_number())
+            .field("firmware_revision", &self.firmware_revision())
+            .field("controller_id", &self.controller_id)
+            .field("version", &self.version())
+            .field("total_capacity", &self.total_capacity)
+            .field("namespace_count", &self.namespace_count)
+            .finish_non_exhaustive()
+    }
+}
+
+/// Format of the logical blocks of a namespace.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct LbaFormat {
+    /// Size of the metadata of each block in bytes.
+    pub metadata_size: u16,
+    /// Size of each block in bytes.
+    pub block_size: u32,
+    /// Relative performance, from 0 (best) to 3 (degraded).
+    pub relative_performance: u8,
+}
+
+/// Data returned by the Identify Namespace command.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct IdentifyNamespace {
+    size: u64,
+    capacity: u64,
+    utilization: u64,
+    lba_format_count: u8,
+    formatted_lba_size: u8,
+    lba_formats: [LbaFormat; 64],
+}
+
+impl IdentifyNamespace {
+    /// Parses the data returned by the Identify Namespace command. Returns
+    /// `None` if `data` is shorter than 4096 bytes.
+    #[must_use]
+    pub fn parse(data: &[u8]) -> Option<Self> {
+        let data = data.get(..IDENTIFY_SIZE)?;
+        let mut lba_formats = [LbaFormat::default(); 64];
+        for (format, raw) in lba_formats.iter_mut().zip(data[128..384].chunks_exact(4)) {
+            *format = LbaFormat {
+                metadata_size: read_u16(raw, 0),
+                // Values below 9 are not supported by the spec.
+                block_size: 1u32.checked_shl(u32::from(raw[2])).unwrap_or(0),
+                relative_performance: raw[3] & 0x03,
+            };
+        }
+        Some(Self {
+            size: read_u64(data, 0),
+            capacity: read_u64(data, 8),
+            utilization: read_u64(data, 16),
+            lba_format_count: data[25].saturating_add(1).min(64),
+            formatted_lba_size: data[26],
+            lba_formats,
+        })
+    }
+
+    /// Size of the namespace in logical blocks.
+    #[must_use]
+    pub const fn size(&self) -> u64 {
+        self.size
+    }
+
+    /// Number of logical blocks that may be allocated at a time.
+    #[must_use]
+    pub const fn capacity(&self) -> u64 {
+        self.capacity
+    }
+
+    /// Number of logical blocks currently allocated.
+    #[must_use]
+    pub const fn utilization(&self) -> u64 {
+        self.utilization
+    }
+
+    /// LBA formats supported by the namespace.
+    #[must_use]
+    pub fn lba_formats(&self) -> &[LbaFormat] {
+        &self.lba_formats[..usize::from(self.lba_format_count)]
+    }
+
+    /// Index of the LBA format the namespace is formatted with.
+    #[must_use]
+    pub const fn formatted_lba_index(&self) -> usize {
+        ((self.formatted_lba_size & 0x0f) | ((self.formatted_lba_size >> 1) & 0x30)) as usize
+    }
+
+    /// LBA format the namespace is formatted with.
+    #[must_use]
+    pub const fn formatted_lba_format(&self) -> LbaFormat {
+        self.lba_formats[self.formatted_lba_index()]
+    }
+
+    /// Size of the namespace in bytes.
+    #[must_use]
+    pub const fn size_in_bytes(&self) -> u64 {
+        self.size * self.formatted_lba_format().block_size as u64
+    }
+}
+
+bitflags::bitflags! {
+    /// Critical warnings of the [`SmartLog`].
+    #[derive(Clone, Copy, Debug, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+    #[repr(transparent)]
+    pub struct SmartCriticalWarning: u8 {
+        /// The available spare capacity fell below the threshold.
+        const AVAILABLE_SPARE = 0x01;
+        /// A temperature is outside of the allowed range.
+        const TEMPERATURE = 0x02;
+        /// Reliability is degraded due to media errors.
+        const RELIABILITY = 0x04;
+        /// The media has been made read only.
+        const READ_ONLY = 0x08;
+        /// The volatile memory backup device failed.
+        const VOLATILE_BACKUP_FAILED = 0x10;
+        /// The persistent memory region has been made read only.
+        const PERSISTENT_MEMORY_READ_ONLY = 0x20;
+    }
+}
+
+/// The SMART / Health Information log page.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct SmartLog {
+    /// Critical warnings.
+    pub critical_warning: SmartCriticalWarning,
+    /// Current composite temperature of the controller.
+    pub composite_temperature: Temperature,
+    /// Remaining spare capacity in percent.
+    pub available_spare: u8,
+    /// Threshold of [`available_spare`](Self::available_spare) below which a
+    /// critical warning is reported, in percent.
+    pub available_spare_threshold: u8,
+    /// Estimate of the used up endurance in percent. Can exceed 100.
+    pub percentage_used: u8,
+    /// Number of 512-byte units read by the host, in thousands.
+    pub data_units_read: u128,
+    /// Number of 512-byte units written by the host, in thousands.
+    pub data_units_written: u128,
+    /// Number of read commands completed.
+    pub host_read_commands: u128,
+    /// Number of write commands completed.
+    pub host_write_commands: u128,
+    /// Time the controller was busy with I/O commands, in minutes.
+    pub controller_busy_time: u128,
+    /// Number of power cycles.
+    pub power_cycles: u128,
+    /// Number of power-on hours.
+    pub power_on_hours: u128,
+    /// Number of unsafe shutdowns.
+    pub unsafe_shutdowns: u128,
+    /// Number of unrecovered data integrity errors.
+    pub media_errors: u128,
+    /// Number of entries in the Error Information log page over the
+    /// lifetime of the controller.
+    pub error_log_entries: u128,
+    /// Temperatures of the individual sensors, `None` for sensors that are
+    /// not implemented.
+    pub temperature_sensors: [Option<Temperature>; 8],
+}
+
+impl SmartLog {
+    /// Parses the SMART / Health Information log page. Returns `None` if
+    /// `data` is shorter than 512 bytes.
+    #[must_use]
+    pub fn parse(data: &[u8]) -> Option<Self> {
+        let data = data.get(..SMART_LOG_SIZE)?;
+        let mut temperature_sensors = [None; 8];
+        for (i, sensor) in temperature_sensors.iter_mut().enumerate() {
+            *sensor = nonzero_temperature(Temperature(read_u16(data, 200 + 2 * i)));
+        }
+        Some(Self {
+            critical_warning: SmartCriticalWarning::from_bits_retain(data[0]),
+            composite_temperature: Temperature(read_u16(data, 1)),
+            available_spare: data[3],
+            available_spare_threshold: data[4],
+            percentage_used: data[5],
+            data_units_read: read_u128(data, 32),
+            data_units_written: read_u128(data, 48),
+            host_read_commands: read_u128(data, 64),
+            host_write_commands: read_u128(data, 80),
+            controller_busy_time: read_u128(data, 96),
+            power_cycles: read_u128(data, 112),
+            power_on_hours: read_u128(data, 128),
+            unsafe_shutdowns: read_u128(data, 144),
+            media_errors: read_u128(data, 160),
+            error_log_entries: read_u128(data, 176),
+            temperature_sensors,
+        })
+    }
+
+    /// Number of bytes read by the host, derived from
+    /// [`data_units_read`](Self::data_units_read).
+    #[must_use]
+    pub const fn bytes_read(&self) -> u128 {
+        self.data_units_read.saturating_mul(512_000)
+    }
+
+    /// Number of bytes written by the host, derived from
+    /// [`data_units_written`](Self::data_units_written).
+    #[must_use]
+    pub const fn bytes_written(&self) -> u128 {
+        self.data_units_written.saturating_mul(512_000)
+    }
+}
+
+/// The Sanitize Status log page, which reports the progress of a sanitize
+/// operation.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct SanitizeStatus {
+    /// Progress of the current sanitize operation, in units of 1/65536.
+    /// Only valid while [`state`](Self::state) is
+    /// [`SanitizeState::IN_PROGRESS`].
+    pub progress: u16,
+    /// State of the most recent sanitize operation.
+    pub state: SanitizeState,
+    /// Whether no user data has been written since the media was
+    /// sanitized or formatted with a secure erase.
+    pub global_data_erased: bool,
+    /// Estimated duration of a block erase, or `None` if not reported.
+    pub estimated_block_erase_time: Option<Duration>,
+    /// Estimated duration of a crypto erase, or `None` if not reported.
+    pub estimated_crypto_erase_time: Option<Duration>,
+}
+
+impl SanitizeStatus {
+    /// Parses the Sanitize Status log page. Returns `None` if `data` is
+    /// shorter than 20 bytes.
+    #[must_use]
+    pub fn parse(data: &[u8]) -> Option<Self> {
+        let data = data.get(..SANITIZE_LOG_SIZE)?;
+        let status = read_u16(data, 2);
+        Some(Self {
+            progress: read_u16(data, 0),
+            state: SanitizeState((status & 0x07) as u8),
+            global_data_erased: status & 0x100 != 0,
+            estimated_block_erase_time: estimated_time(read_u32(data, 12)),
+            estimated_crypto_erase_time: estimated_time(read_u32(data, 16)),
+        })
+    }
+
+    /// Progress of the current sanitize operation in percent.
+    #[must_use]
+    pub const fn progress_percent(&self) -> u8 {
+        (self.progress as u32 * 100 / 65536) as u8
+    }
+}
+
+impl NvmeNamespace<'_> {
+    /// Sends an admin command, with a newly allocated transfer buffer of
+    /// `transfer_len` bytes if it is not zero.
+    fn admin_command(
+        &mut self,
+        opcode: NvmeAdminOpcode,
+        cdw10: u32,
+        cdw11: u32,
+        transfer_len: usize,
+    ) -> Result<NvmeResponse<'static>> {
+        let mut builder = NvmeRequestBuilder::new(self.io_align(), opcode.0, NvmeQueueType::ADMIN)
+            .with_cdw10(cdw10)
+            .with_cdw11(cdw11);
+        if transfer_len != 0 {
+            builder = builder
+                .with_transfer_buffer(transfer_len)
+                .map_err(|_| Status::OUT_OF_RESOURCES)?;
+        }
+        self.execute_command(builder.build())
+    }
+
+    /// Sends the Identify Controller command.
+    ///
+    /// # Errors
+    /// See [`execute_command`](Self::execute_command).
+    pub fn identify_controller(&mut self) -> Result<IdentifyController> {
+        let response = self.admin_command(NvmeAdminOpcode::IDENTIFY, 0x01, 0, IDENTIFY_SIZE)?;
+        response
+            .transfer_buffer()
+            .and_then(IdentifyController::parse)
+            .ok_or_else(|| Status::DEVICE_ERROR.into())
+    }
+
+    /// Sends the Identify Namespace command for this namespace.
+    ///
+    /// # Errors
+    /// See [`execute_command`](Self::execute_command).
+    pub fn identify_namespace(&mut self) -> Result<IdentifyNamespace> {
+        let response = self.admin_command(NvmeAdminOpcode::IDENTIFY, 0x00, 0, IDENTIFY_SIZE)?;
+        response
+            .transfer_buffer()
+            .and_then(IdentifyNamespace::parse)
+            .ok_or_else(|| Status::DEVICE_ERROR.into())
+    }
+
+    /// Reads the first `len` bytes of a log page. `len` must be a nonzero
+    /// multiple of four.
+    ///
+    /// # Errors
+    /// * [`Status::INVALID_PARAMETER`] if `len` is invalid.
+    /// * See [`execute_command`](Self::execute_command) for the other
+    ///   errors.
+    pub fn get_log_page(&mut self, log_page: NvmeLogPage, len: usize) -> Result<Vec<u8>> {
+        if len == 0 || len % 4 != 0 {
+            return Err(Status::INVALID_PARAMETER.into());
+        }
+        // Number of dwords minus one, split over CDW10 and CDW11.
+        let dwords = u32::try_from(len / 4 - 1).map_err(|_| Status::INVALID_PARAMETER)?;
+        let cdw10 = u32::from(log_page.0) | (dwords << 16);
+        let response =
+            self.admin_command(NvmeAdminOpcode::GET_LOG_PAGE, cdw10, dwords >> 16, len)?;
+        let data = response.transfer_buffer().ok_or(Status::DEVICE_ERROR)?;
+        Ok(data[..len.min(data.len())].to_vec())
+    }
+
+    /// Reads the SMART / Health Information log page. Sent to the
+    /// controller, it returns the information for the whole controller.
+    ///
+    /// # Errors
+    /// See [`execute_command`](Self::execute_command).
+    pub fn smart_log(&mut self) -> Result<SmartLog> {
+        let data = self.get_log_page(NvmeLogPage::SMART_HEALTH, SMART_LOG_SIZE)?;
+        SmartLog::parse(&data).ok_or_else(|| Status::DEVICE_ERROR.into())
+    }
+
+    /// Downloads a part of a firmware image to the controller, starting at
+    /// byte `offset` of the image. The image is activated with
+    /// [`firmware_commit`](Self::firmware_commit) once all parts have been
+    /// downloaded.
+    ///
+    /// `offset` and the length of `data` must be multiples of four, and
+    /// should be multiples of
+    /// [`IdentifyController::firmware_update_granularity`].
+    ///
+    /// # Errors
+    /// * [`Status::INVALID_PARAMETER`] if `offset` or `data` are not
+    ///   multiples of four.
+    /// * See [`execute_command`](Self::execute_command) for the other
+    ///   errors.
+    pub fn firmware_download(&mut self, offset: u32, data: &[u8]) -> Result {
+        if offset % 4 != 0 || data.is_empty() || data.len() % 4 != 0 {
+            return Err(Status::INVALID_PARAMETER.into());
+        }
+        let dwords = u32::try_from(data.len() / 4 - 1).map_err(|_| Status::INVALID_PARAMETER)?;
+        let mut buffer =
+            crate::mem::AlignedBuffer::from_size_align(data.len(), self.io_align() as usize)
+                .map_err(|_| Status::OUT_OF_RESOURCES)?;
+        buffer.copy_from_slice(data);
+        let request = NvmeRequestBuilder::new(
+            self.io_align(),
+            NvmeAdminOpcode::FIRMWARE_IMAGE_DOWNLOAD.0,
+            NvmeQueueType::ADMIN,
+        )
+        .with_cdw10(dwords)
+        .with_cdw11(offset / 4)
+        .use_transfer_buffer(&mut buffer)
+        .map_err(|_| Status::INVALID_PARAMETER)?
+        .build();
+        self.execute_command(request).map(|_| ())
+    }
+
+    /// Commits the downloaded firmware image to `slot` (1 to 7), or
+    /// activates the image in `slot`, depending on `action`. Slot 0 lets
+    /// the controller choose a slot.
+    ///
+    /// # Errors
+    /// * [`Status::INVALID_PARAMETER`] if `slot` is larger than 7.
+    /// * See [`execute_command`](Self::execute_command) for the other
+    ///   errors.
+    pub fn firmware_commit(&mut self, slot: u8, action: FirmwareCommitAction) -> Result {
+        if slot > 7 {
+            return Err(Status::INVALID_PARAMETER.into());
+        }
+        let cdw10 = u32::from(slot) | (u32::from(action.0 & 0x07) << 3);
+        self.admin_command(NvmeAdminOpcode::FIRMWARE_COMMIT, cdw10, 0, 0)
+            .map(|_| ())
+    }
+
+    /// Formats this namespace with the LBA format at index `lba_format` of
+    /// [`IdentifyNamespace::lba_formats`], optionally erasing the data
+    /// securely. Depending on
+    /// [`IdentifyController::format_applies_to_all_namespaces`], all
+    /// namespaces of the controller are formatted.
+    ///
+    /// All data of the namespace is lost. The command returns when the format
+    /// is complete, which may take minutes with a secure erase.
+    ///
+    /// # Errors
+    /// * [`Status::INVALID_PARAMETER`] if `lba_format` is larger than 63.
+    /// * See [`execute_command`](Self::execute_command) for the other
+    ///   errors.
+    pub fn format_nvm(
+        &mut self,
+        lba_format: usize,
+        erase: FormatSecureErase,
+        _confirmation: EraseConfirmation,
+    ) -> Result {
+        if lba_format >= 64 {
+            return Err(Status::INVALID_PARAMETER.into());
+        }
+        let lba_format = lba_format as u32;
+        let cdw10 =
+            (lba_format & 0x0f) | (u32::from(erase.0 & 0x07) << 9) | ((lba_format & 0x30) << 8);
+        self.admin_command(NvmeAdminOpcode::FORMAT_NVM, cdw10, 0, 0)
+            .map(|_| ())
+    }
+
+    /// Starts a sanitize operation, which erases all user data of the
+    /// controller, including caches and unallocated blocks. Must be sent to
+    /// [`NvmePassThru::controller`].
+    ///
+    /// The command returns immediately and the operation continues in the
+    /// background, even across resets. Its progress is reported by
+    /// [`sanitize_status`], and [`wait_for_sanitize`] waits for it.
+    ///
+    /// # Errors
+    /// See [`execute_command`](Self::execute_command).
+    ///
+    /// [`NvmePassThru::controller`]: super::pass_thru::NvmePassThru::controller
+    /// [`sanitize_status`]: Self::sanitize_status
+    /// [`wait_for_sanitize`]: Self::wait_for_sanitize
+    pub fn sanitize(&mut self, action: SanitizeAction, _confirmation: EraseConfirmation) -> Result {
+        let cdw10 = u32::from(action.0 & 0x07);
+        self.admin_command(NvmeAdminOpcode::SANITIZE, cdw10, 0, 0)
+            .map(|_| ())
+    }
+
+    /// Reads the Sanitize Status log page.
+    ///
+    /// # Errors
+    /// See [`execute_command`](Self::execute_command).
+    pub fn sanitize_status(&mut self) -> Result<SanitizeStatus> {
+        let data = self.get_log_page(NvmeLogPage::SANITIZE_STATUS, SANITIZE_LOG_SIZE)?;
+        SanitizeStatus::parse(&data).ok_or_else(|| Status::DEVICE_ERROR.into())
+    }
+
+    /// Waits until the current sanitize operation is finished, calling
+    /// `progress` with the status about once per second. Returns the final
+    /// status.
+    ///
+    /// # Errors
+    /// * [`Status::DEVICE_ERROR`] if the sanitize operation failed.
+    /// * See [`execute_command`](Self::execute_command) for the other
+    ///   errors.
+    pub fn wait_for_sanitize(
+        &mut self,
+        mut progress: impl FnMut(&SanitizeStatus),
+    ) -> Result<SanitizeStatus> {
+        loop {
+            let status = self.sanitize_status()?;
+            match status.state {
+                SanitizeState::IN_PROGRESS => {
+                    progress(&status);
+                    boot::stall(SANITIZE_POLL_INTERVAL);
+                }
+                SanitizeState::FAILED => return Err(Status::DEVICE_ERROR.into()),
+                _ => return Ok(status),
+            }
+        }
+    }
+}
+
+/// Converts an estimated time in seconds, `u32::MAX` meaning not reported.
+const fn estimated_time(seconds: u32) -> Option<Duration> {
+    if seconds == u32::MAX {
+        None
+    } else {
+        Some(Duration::from_secs(seconds as u64))
+    }
+}
+
+/// Returns `None` for the temperature 0, which means "not reported".
+const fn nonzero_temperature(temperature: Temperature) -> Option<Temperature> {
+    if temperature.0 == 0 {
+        None
+    } else {
+        Some(temperature)
+    }
+}
+
+/// Converts a space-padded ASCII field to a string.
+fn ascii_field(bytes: &[u8]) -> &str {
+    core::str::from_utf8(bytes)
+        .unwrap_or("")
+        .trim_end_matches([' ', '\0'])
+}
+
+fn read_u16(bytes: &[u8], offset: usize) -> u16 {
+    u16::from_le_bytes(bytes[offset..offset + 2].try_into().unwrap())
+}
+
+fn read_u32(bytes: &[u8], offset: usize) -> u32 {
+    u32::from_le_bytes(bytes[offset..offset + 4].try_into().unwrap())
+}
+
+fn read_u64(bytes: &[u8], offset: usize) -> u64 {
+    u64::from_le_bytes(bytes[offset..offset + 8].try_into().unwrap())
+}
+
+fn read_u128(bytes: &[u8], offset: usize) -> u128 {
+    u128::from_le_bytes(bytes[offset..offset + 16].try_into().unwrap())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloc::vec;
+
+    #[test]
+    fn test_identify_controller() {
+        let mut data = vec![0; IDENTIFY_SIZE];
+        data[0..2].copy_from_slice(&0x1b36u16.to_le_bytes());
+        data[4..24].copy_from_slice(b"uefi-rsNvmePassThru ");
+        data[24..64].fill(b' ');
+        data[24..34].copy_from_slice(b"QEMU NVMe ");
+        data[64..72].copy_from_slice(b"9.0     ");
+        data[80..84].copy_from_slice(&0x0001_0400u32.to_le_bytes());
+        data[256] = 0x04;
+        data[260] = 0x07;
+        data[266..268].copy_from_slice(&343u16.to_le_bytes());
+        data[516..520].copy_from_slice(&256u32.to_le_bytes());
+
+        let ctrl = IdentifyController::parse(&data).unwrap();
+        assert_eq!(ctrl.pci_vendor_id(), 0x1b36);
+        assert_eq!(ctrl.serial_number(), "uefi-rsNvmePassThru");
+        assert_eq!(ctrl.model_number(), "QEMU NVMe");
+        assert_eq!(ctrl.firmware_revision(), "9.0");
+        assert_eq!(ctrl.version(), (1, 4, 0));
+        assert!(ctrl.supports_firmware_download());
+        assert_eq!(ctrl.firmware_slots(), 3);
+        assert!(ctrl.first_firmware_slot_read_only());
+        assert_eq!(ctrl.firmware_update_granularity(), Some(4096));
+        assert_eq!(ctrl.warning_temperature().unwrap().celsius(), 70);
+        assert_eq!(ctrl.critical_temperature(), None);
+        assert_eq!(ctrl.namespace_count(), 256);
+
+        assert!(IdentifyController::parse(&data[..100]).is_none());
+
+        data[256] = 0x06;
+        data[328] = 0x01;
+        data[524] = 0x04;
+        let ctrl = IdentifyController::parse(&data).unwrap();
+        assert!(ctrl.supports_format_nvm());
+        assert!(ctrl.supports_format_crypto_erase());
+        assert!(!ctrl.format_applies_to_all_namespaces());
+        assert!(ctrl.supports_sanitize(SanitizeAction::CRYPTO_ERASE));
+        assert!(!ctrl.supports_sanitize(SanitizeAction::BLOCK_ERASE));
+        assert!(ctrl.supports_sanitize(SanitizeAction::EXIT_FAILURE_MODE));
+    }
+
+    #[test]
+    fn test_identify_namespace() {
+        let mut data = vec![0; IDENTIFY_SIZE];
+        data[0..8].copy_from_slice(&1000u64.to_le_bytes());
+        data[25] = 1;
+        data[26] = 1;
+        data[128..132].copy_from_slice(&[0, 0, 9, 0]);
+        data[132..136].copy_from_slice(&[8, 0, 12, 2]);
+
+        let ns = IdentifyNamespace::parse(&data).unwrap();
+        assert_eq!(ns.size(), 1000);
+        assert_eq!(ns.lba_formats().len(), 2);
+        assert_eq!(ns.lba_formats()[0].block_size, 512);
+        assert_eq!(
+            ns.formatted_lba_format(),
+            LbaFormat {
+                metadata_size: 8,
+                block_size: 4096,
+                relative_performance: 2,
+            }
+        );
+        assert_eq!(ns.size_in_bytes(), 4_096_000);
+    }
+
+    #[test]
+    fn test_smart_log() {
+        let mut data = vec![0; SMART_LOG_SIZE];
+        data[0] = 0x02;
+        data[1..3].copy_from_slice(&323u16.to_le_bytes());
+        data[3] = 100;
+        data[4] = 10;
+        data[32..48].copy_from_slice(&3u128.to_le_bytes());
+        data[128..144].copy_from_slice(&42u128.to_le_bytes());
+        data[202..204].copy_from_slice(&300u16.to_le_bytes());
+
+        let log = SmartLog::parse(&data).unwrap();
+        assert_eq!(log.critical_warning, SmartCriticalWarning::TEMPERATURE);
+        assert_eq!(log.composite_temperature.celsius(), 50);
+        assert_eq!(log.available_spare, 100);
+        assert_eq!(log.bytes_read(), 1_536_000);
+        assert_eq!(log.power_on_hours, 42);
+        assert_eq!(
+            log.temperature_sensors[..2],
+            [None, Some(Temperature::from_kelvin(300))]
+        );
+    }
+
+    #[test]
+    fn test_sanitize_status() {
+        let mut data = vec![0; 512];
+        data[0..2].copy_from_slice(&0x8000u16.to_le_bytes());
+        data[2..4].copy_from_slice(&0x0102u16.to_le_bytes());
+        data[8..16].fill(0xff);
+        data[16..20].copy_from_slice(&30u32.to_le_bytes());
+
+        let status = SanitizeStatus::parse(&data).unwrap();
+        assert_eq!(status.state, SanitizeState::IN_PROGRESS);
+        assert_eq!(status.progress_percent(), 50);
+        assert!(status.global_data_erased);
+        assert_eq!(status.estimated_block_erase_time, None);
+        assert_eq!(
+            status.estimated_crypto_erase_time,
+            Some(Duration::from_secs(30))
+        );
+
+        assert!(SanitizeStatus::parse(&data[..19]).is_none());
+    }
+}
@@ -11,6 +11,7 @@ use uefi_raw::protocol::nvme::{
     NvmExpressCommand, NvmExpressCommandCdwValidity, NvmExpressPassThruCommandPacket,
 };
 
+pub mod admin;
 pub mod pass_thru;
 
 /// Represents the completion status of an NVMe command.
@@ -123,6 +123,11 @@ impl NvmeNamespace<'_> {
         ptr::from_ref(self.proto).cast_mut()
     }
 
+    /// Alignment requirement of the controller for I/O buffers.
+    pub(super) const fn io_align(&self) -> u32 {
+        unsafe { (*self.proto.mode).io_align }
+    }
+
     /// Retrieves the namespace identifier (NSID) associated with this NVMe namespace.
     #[must_use]
     pub const fn namespace_id(&self) -> NvmeNamespaceId {
@@ -6,3 +6,4 @@
 //! Platform Initialization (PI) Specification.
 
 pub mod mp;
+pub mod status_code;
@@ -0,0 +1,334 @@
+// SPDX-License-Identifier: MIT OR Apache-2.0
+
+//! Status Code protocol.
+//!
+//! Status codes report the progress of the boot and the errors that occur
+//! to the platform, which routes them to handlers such as a POST code
+//! display, the event log of a BMC or a serial port. A status code consists
+//! of a [`CodeType`] and a [`StatusCodeValue`], which is made of the
+//! [`SubClass`] of the component that reports it and an operation.
+//!
+//! # Example
+//!
+//! ```no_run
+//! use uefi::boot;
+//! use uefi::proto::pi::status_code::{CodeType, ProgressOperation, StatusCode, SubClass};
+//!
+//! # fn example() -> uefi::Result {
+//! let handle = boot::get_handle_for_protocol::<StatusCode>()?;
+//! let status_code = boot::open_protocol_exclusive::<StatusCode>(handle)?;
+//! status_code.report(
+//!     CodeType::Progress,
+//!     SubClass::EFI_APPLICATION.progress(ProgressOperation::INIT_BEGIN),
+//!     0,
+//!     None,
+//! )?;
+//! # Ok(())
+//! # }
+//! ```
+
+use crate::proto::unsafe_protocol;
+use crate::{CStr8, Guid, Result, StatusExt};
+use core::{mem, ptr};
+use uefi_raw::protocol::status_code::{
+    StatusCodeData, StatusCodeProtocol, StatusCodeStringData, StringType,
+};
+
+pub use uefi_raw::protocol::status_code::{StatusCodeType, StatusCodeValue};
+
+newtype_enum! {
+    /// Severity of an error code.
+    pub enum Severity: u32 => {
+        /// The operation failed, but the system can continue.
+        MINOR = 0x4000_0000,
+        /// The operation failed, and the system is degraded.
+        MAJOR = 0x8000_0000,
+        /// The error can't be recovered from.
+        UNRECOVERED = 0x9000_0000,
+        /// The error may have corrupted other parts of the system.
+        UNCONTAINED = 0xa000_0000,
+    }
+}
+
+/// Type of a status code.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum CodeType {
+    /// Progress of an operation.
+    Progress,
+    /// Error of the given severity.
+    Error(Severity),
+    /// Debug information, usually with a message.
+    Debug,
+}
+
+impl From<CodeType> for StatusCodeType {
+    fn from(code_type: CodeType) -> Self {
+        match code_type {
+            CodeType::Progress => Self(1),
+            CodeType::Error(severity) => Self(2 | severity.0),
+            CodeType::Debug => Self(3),
+        }
+    }
+}
+
+newtype_enum! {
+    /// Class of a status code, the top byte of a [`StatusCodeValue`].
+    pub enum Class: u32 => {
+        /// Processors, caches, memory and chipsets.
+        COMPUTING_UNIT = 0x0000_0000,
+        /// Input and output devices, e.g. keyboards and displays.
+        PERIPHERAL = 0x0100_0000,
+        /// Buses such as PCI and USB.
+        IO_BUS = 0x0200_0000,
+        /// Firmware and software, including UEFI applications.
+        SOFTWARE = 0x0300_0000,
+    }
+}
+
+newtype_enum! {
+    /// Subclass of a status code, including its [`Class`].
+    ///
+    /// The constants are the subclasses of [`Class::SOFTWARE`]. Subclasses of
+    /// the other classes can be created from their values.
+    pub enum SubClass: u32 => {
+        /// Software of no specific kind.
+        SOFTWARE_UNSPECIFIED = 0x0300_0000,
+        /// The SEC phase of the firmware.
+        SEC = 0x0301_0000,
+        /// The PEI core.
+        PEI_CORE = 0x0302_0000,
+        /// A PEI module.
+        PEI_MODULE = 0x0303_0000,
+        /// The DXE core.
+        DXE_CORE = 0x0304_0000,
+        /// A DXE boot services driver.
+        DXE_BS_DRIVER = 0x0305_0000,
+        /// A DXE runtime services driver.
+        DXE_RT_DRIVER = 0x0306_0000,
+        /// An SMM driver.
+        SMM_DRIVER = 0x0307_0000,
+        /// A UEFI application.
+        EFI_APPLICATION = 0x0308_0000,
+        /// An OS loader.
+        OS_LOADER = 0x0309_0000,
+        /// The OS at runtime.
+        RUNTIME = 0x030a_0000,
+        /// The OS before it has exited boot services.
+        AL = 0x030b_0000,
+        /// An EBC exception.
+        EBC_EXCEPTION = 0x030c_0000,
+        /// An IA-32 exception.
+        IA32_EXCEPTION = 0x030d_0000,
+        /// An Itanium exception.
+        IPF_EXCEPTION = 0x030e_0000,
+        /// A PEI service.
+        PEI_SERVICE = 0x030f_0000,
+        /// A boot service.
+        EFI_BOOT_SERVICE = 0x0310_0000,
+        /// A runtime service.
+        EFI_RUNTIME_SERVICE = 0x0311_0000,
+        /// A DXE service.
+        DXE_SERVICE = 0x0312_0000,
+        /// An x64 exception.
+        X64_EXCEPTION = 0x0313_0000,
+        /// An ARM exception.
+        ARM_EXCEPTION = 0x0314_0000,
+    }
+}
+
+impl SubClass {
+    /// Returns the class of the subclass.
+    #[must_use]
+    pub const fn class(self) -> Class {
+        Class(self.0 & StatusCodeValue::CLASS_MASK)
+    }
+
+    /// Returns the value of a progress code of `operation` in this subclass.
+    #[must_use]
+    pub const fn progress(self, operation: ProgressOperation) -> StatusCodeValue {
+        self.with_operation(operation.0)
+    }
+
+    /// Returns the value of an error code of `operation` in this subclass.
+    #[must_use]
+    pub const fn error(self, operation: ErrorOperation) -> StatusCodeValue {
+        self.with_operation(operation.0)
+    }
+
+    /// Returns the value of a status code with a subclass specific or OEM
+    /// `operation`.
+    #[must_use]
+    pub const fn with_operation(self, operation: u16) -> StatusCodeValue {
+        StatusCodeValue(self.0 | operation as u32)
+    }
+}
+
+newtype_enum! {
+    /// Operation of a progress code that applies to all software subclasses.
+    ///
+    /// Subclass specific operations start at `0x1000`, OEM specific ones at
+    /// `0x8000`, see [`SubClass::with_operation`].
+    pub enum ProgressOperation: u16 => {
+        /// Initialization.
+        INIT = 0x0000,
+        /// Loading.
+        LOAD = 0x0001,
+        /// Start of the initialization.
+        INIT_BEGIN = 0x0002,
+        /// End of the initialization.
+        INIT_END = 0x0003,
+        /// Start of an authentication.
+        AUTHENTICATE_BEGIN = 0x0004,
+        /// End of an authentication.
+        AUTHENTICATE_END = 0x0005,
+        /// Waiting for input.
+        INPUT_WAIT = 0x0006,
+        /// Running the setup of the user.
+        USER_SETUP = 0x0007,
+    }
+}
+
+newtype_enum! {
+    /// Operation of an error code that applies to all software subclasses.
+    ///
+    /// Subclass specific operations start at `0x1000`, OEM specific ones at
+    /// `0x8000`, see [`SubClass::with_operation`].
+    pub enum ErrorOperation: u16 => {
+        /// An error of no specific kind.
+        NON_SPECIFIC = 0x0000,
+        /// Loading failed.
+        LOAD_ERROR = 0x0001,
+        /// A parameter was invalid.
+        INVALID_PARAMETER = 0x0002,
+        /// The operation is not supported.
+        UNSUPPORTED = 0x0003,
+        /// A buffer was invalid.
+        INVALID_BUFFER = 0x0004,
+        /// Resources are exhausted.
+        OUT_OF_RESOURCES = 0x0005,
+        /// The operation was aborted.
+        ABORTED = 0x0006,
+        /// The software is in an illegal state.
+        ILLEGAL_SOFTWARE_STATE = 0x0007,
+        /// The hardware is in an illegal state.
+        ILLEGAL_HARDWARE_STATE = 0x0008,
+        /// Starting failed.
+        START_ERROR = 0x0009,
+        /// The date or time is invalid.
+        BAD_DATE_TIME = 0x000a,
+        /// The configuration is invalid.
+        CFG_INVALID = 0x000b,
+        /// Clearing the configuration was requested.
+        CFG_CLR_REQUEST = 0x000c,
+        /// The default configuration is used.
+        CFG_DEFAULT = 0x000d,
+        /// A password is invalid.
+        PWD_INVALID = 0x000e,
+        /// Clearing a password was requested.
+        PWD_CLR_REQUEST = 0x000f,
+        /// A password was cleared.
+        PWD_CLEARED = 0x0010,
+        /// The event log is full.
+        EVENT_LOG_FULL = 0x0011,
+    }
+}
+
+/// Status Code [`Protocol`], also known as the Status Code Runtime
+/// protocol. It reports status codes to the handlers of the platform.
+///
+/// [`Protocol`]: uefi::proto::Protocol
+#[derive(Debug)]
+#[repr(transparent)]
+#[unsafe_protocol(StatusCodeProtocol::GUID)]
+pub struct StatusCode(StatusCodeProtocol);
+
+impl StatusCode {
+    /// Reports a status code of `code_type` and `value`.
+    ///
+    /// `instance` distinguishes multiple instances of the reporting
+    /// component, starting at 1; 0 means that the instance is unknown.
+    /// `caller_id` identifies the caller, e.g. by the GUID of its file.
+    ///
+    /// # Errors
+    ///
+    /// * [`Status::DEVICE_ERROR`]: the status code could not be reported.
+    ///
+    /// [`Status::DEVICE_ERROR`]: crate::Status::DEVICE_ERROR
+    pub fn report(
+        &self,
+        code_type: CodeType,
+        value: StatusCodeValue,
+        instance: u32,
+        caller_id: Option<&Guid>,
+    ) -> Result {
+        unsafe { self.report_raw(code_type, value, instance, caller_id, ptr::null()) }
+    }
+
+    /// Reports a status code of `code_type` and `value` like [`report`],
+    /// with `message` as its description.
+    ///
+    /// # Errors
+    ///
+    /// See [`report`].
+    ///
+    /// [`report`]: Self::report
+    pub fn report_with_message(
+        &self,
+        code_type: CodeType,
+        value: StatusCodeValue,
+        instance: u32,
+        caller_id: Option<&Guid>,
+        message: &CStr8,
+    ) -> Result {
+        let data = StatusCodeStringData {
+            header: StatusCodeData {
+                header_size: mem::size_of::<StatusCodeData>() as u16,
+                size: (mem::size_of::<StatusCodeStringData>() - mem::size_of::<StatusCodeData>())
+                    as u16,
+                data_type: StatusCodeData::STRING_GUID,
+            },
+            string_type: StringType::ASCII,
+            string: message.as_ptr().cast(),
+        };
+        unsafe {
+            self.report_raw(
+                code_type,
+                value,
+                instance,
+                caller_id,
+                ptr::from_ref(&data.header),
+            )
+        }
+    }
+
+    /// Calls `ReportStatusCode` with the extended data `data`, which may be
+    /// null.
+    unsafe fn report_raw(
+        &self,
+        code_type: CodeType,
+        value: StatusCodeValue,
+        instance: u32,
+        caller_id: Option<&Guid>,
+        data: *const StatusCodeData,
+    ) -> Result {
+        let caller_id = caller_id.map_or(ptr::null(), ptr::from_ref);
+        unsafe { (self.0.report_status_code)(code_type.into(), value, instance, caller_id, data) }
+            .to_result()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_status_code_value() {
+        let value = SubClass::EFI_APPLICATION.error(ErrorOperation::LOAD_ERROR);
+        assert_eq!(value, StatusCodeValue(0x0308_0001));
+        assert_eq!(SubClass::EFI_APPLICATION.class(), Class::SOFTWARE);
+        assert_eq!(
+            StatusCodeType::from(CodeType::Error(Severity::MAJOR)),
+            StatusCodeType(0x8000_0002)
+        );
+    }
+}
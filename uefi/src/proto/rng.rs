@@ -57,4 +57,107 @@ impl Rng {
             (self.0.get_rng)(&mut self.0, algo, buffer_length, buffer.as_mut_ptr()).to_result()
         }
     }
+
+    /// Fills `buffer` with random data, using the default algorithm if it
+    /// works and otherwise the first supported algorithm that does.
+    ///
+    /// Some implementations fail requests for the default algorithm, e.g.
+    /// because the hardware source behind it is unavailable, while other
+    /// algorithms work.
+    ///
+    /// # Errors
+    ///
+    /// Returns the error of the default algorithm if no algorithm works.
+    pub fn get_rng_with_fallback(&mut self, buffer: &mut [u8]) -> Result {
+        let Err(err) = self.get_rng(None, buffer) else {
+            return Ok(());
+        };
+
+        let mut algorithms = [RngAlgorithmType::EMPTY_ALGORITHM; MAX_ALGORITHMS];
+        let algorithms = self.get_info(&mut algorithms).map_err(|_| err.clone())?;
+        for algorithm in algorithms {
+            if self.get_rng(Some(*algorithm), buffer).is_ok() {
+                return Ok(());
+            }
+        }
+        Err(err)
+    }
+}
+
+/// Number of algorithms that [`Rng::get_rng_with_fallback`] considers.
+const MAX_ALGORITHMS: usize = 16;
+
+/// Random numbers for the [`rand`] ecosystem.
+///
+/// The data is generated with [`Rng::get_rng_with_fallback`].
+///
+/// # Panics
+///
+/// The methods panic if no algorithm of the protocol works, as the
+/// [`RngCore`] trait has no way to report errors.
+///
+/// [`rand`]: https://docs.rs/rand
+/// [`RngCore`]: rand_core::RngCore
+#[cfg(feature = "rand_core")]
+impl rand_core::RngCore for Rng {
+    fn next_u32(&mut self) -> u32 {
+        let mut bytes = [0; 4];
+        self.fill_bytes(&mut bytes);
+        u32::from_le_bytes(bytes)
+    }
+
+    fn next_u64(&mut self) -> u64 {
+        let mut bytes = [0; 8];
+        self.fill_bytes(&mut bytes);
+        u64::from_le_bytes(bytes)
+    }
+
+    fn fill_bytes(&mut self, dst: &mut [u8]) {
+        if dst.is_empty() {
+            return;
+        }
+        self.get_rng_with_fallback(dst)
+            .expect("failed to get random data");
+    }
+}
+
+/// The algorithms of the protocol are all required to be suitable for
+/// cryptography.
+#[cfg(feature = "rand_core")]
+impl rand_core::CryptoRng for Rng {}
+
+/// Custom backend of [`getrandom`], which fills the buffer using the first
+/// [`Rng`] protocol. Fails with [`getrandom::Error::UNSUPPORTED`] after boot
+/// services have been exited or if there is no such protocol.
+///
+/// The backend is only used if `getrandom_backend="custom"` is configured,
+/// e.g. with `RUSTFLAGS='--cfg getrandom_backend="custom"'`.
+///
+/// [`getrandom`]: https://docs.rs/getrandom
+#[cfg(feature = "getrandom")]
+#[unsafe(no_mangle)]
+unsafe extern "Rust" fn __getrandom_v03_custom(
+    dest: *mut u8,
+    len: usize,
+) -> core::result::Result<(), getrandom::Error> {
+    use crate::boot;
+
+    if len == 0 {
+        return Ok(());
+    }
+    if !boot::are_boot_services_active() {
+        return Err(getrandom::Error::UNSUPPORTED);
+    }
+    let mut rng = boot::get_handle_for_protocol::<Rng>()
+        .and_then(boot::open_protocol_exclusive::<Rng>)
+        .map_err(|_| getrandom::Error::UNSUPPORTED)?;
+
+    // SAFETY: `getrandom` passes a valid buffer, which may be uninitialized,
+    // so it's zeroed before creating a slice of it.
+    let buffer = unsafe {
+        ptr::write_bytes(dest, 0, len);
+        core::slice::from_raw_parts_mut(dest, len)
+    };
+    rng.get_rng_with_fallback(buffer)
+        .map_err(|err| getrandom::Error::new_custom(err.status().0 as u16))
 }
@@ -0,0 +1,648 @@
+// SPDX-License-Identifier: MIT OR Apache-2.0
+
+//! Builders for common SCSI command descriptor blocks (CDBs) and parsers for
+//! their responses.
+//!
+//! Each command type can return its raw CDB with `cdb()`, or a
+//! [`ScsiRequestBuilder`] that is already set up with the CDB and a data
+//! buffer of the right size with `builder()`.
+//!
+//! # Example
+//!
+//! ```no_run
+//! use uefi::proto::scsi::command::{Inquiry, InquiryData};
+//! use uefi::proto::scsi::pass_thru::{ExtScsiPassThru, ScsiDevice};
+//!
+//! fn print_vendor(scsi_pt: &ExtScsiPassThru, device: &mut ScsiDevice) -> uefi::Result {
+//!     let request = Inquiry::standard().builder(scsi_pt.io_align()).unwrap().build();
+//!     let response = device.execute_command(request)?;
+//!     if let Some(data) = response.read_buffer().and_then(InquiryData::parse) {
+//!         log::info!("{} {}", data.vendor_id(), data.product_id());
+//!     }
+//!     Ok(())
+//! }
+//! ```
+
+use super::ScsiRequestBuilder;
+use alloc::string::String;
+use alloc::vec::Vec;
+use core::alloc::LayoutError;
+
+/// Opcode of TEST UNIT READY.
+const TEST_UNIT_READY: u8 = 0x00;
+/// Opcode of REQUEST SENSE.
+const REQUEST_SENSE: u8 = 0x03;
+/// Opcode of INQUIRY.
+const INQUIRY: u8 = 0x12;
+/// Opcode of READ CAPACITY (10).
+const READ_CAPACITY_10: u8 = 0x25;
+/// Opcode of READ (16).
+const READ_16: u8 = 0x88;
+/// Opcode of WRITE (16).
+const WRITE_16: u8 = 0x8a;
+/// Opcode of SERVICE ACTION IN (16), which carries READ CAPACITY (16).
+const SERVICE_ACTION_IN_16: u8 = 0x9e;
+/// Service action of READ CAPACITY (16).
+const READ_CAPACITY_16_ACTION: u8 = 0x10;
+
+/// The TEST UNIT READY command, which checks whether the device is ready.
+/// The device is ready if the command succeeds with a GOOD target status.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct TestUnitReady;
+
+impl TestUnitReady {
+    /// Returns the CDB of the command.
+    #[must_use]
+    pub const fn cdb(&self) -> [u8; 6] {
+        [TEST_UNIT_READY, 0, 0, 0, 0, 0]
+    }
+
+    /// Returns a request builder for the command.
+    pub fn builder<'a>(&self, io_align: u32) -> Result<ScsiRequestBuilder<'a>, LayoutError> {
+        ScsiRequestBuilder::read(io_align).with_command_data(&self.cdb())
+    }
+}
+
+/// The REQUEST SENSE command, which returns [`SenseData`] about the last
+/// error.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct RequestSense {
+    /// Maximum number of bytes to return.
+    pub allocation_length: u8,
+}
+
+impl RequestSense {
+    /// Creates the command with the maximum allocation length of 252 bytes.
+    #[must_use]
+    pub const fn new() -> Self {
+        Self {
+            allocation_length: 252,
+        }
+    }
+
+    /// Returns the CDB of the command.
+    #[must_use]
+    pub const fn cdb(&self) -> [u8; 6] {
+        [REQUEST_SENSE, 0, 0, 0, self.allocation_length, 0]
+    }
+
+    /// Returns a request builder for the command.
+    pub fn builder<'a>(&self, io_align: u32) -> Result<ScsiRequestBuilder<'a>, LayoutError> {
+        ScsiRequestBuilder::read(io_align)
+            .with_command_data(&self.cdb())?
+            .with_read_buffer(usize::from(self.allocation_length))
+    }
+}
+
+impl Default for RequestSense {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// The INQUIRY command, which returns either the standard [`InquiryData`],
+/// or a vital product data (VPD) page.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Inquiry {
+    /// VPD page to return, or `None` for the standard inquiry data.
+    pub vpd_page: Option<u8>,
+    /// Maximum number of bytes to return.
+    pub allocation_length: u16,
+}
+
+impl Inquiry {
+    /// VPD page with the unit serial number, see [`parse_unit_serial_number`].
+    pub const VPD_UNIT_SERIAL_NUMBER: u8 = 0x80;
+    /// VPD page with the device identification, see [`Designator`].
+    pub const VPD_DEVICE_IDENTIFICATION: u8 = 0x83;
+
+    /// Creates the command for the standard inquiry data.
+    #[must_use]
+    pub const fn standard() -> Self {
+        Self {
+            vpd_page: None,
+            allocation_length: 255,
+        }
+    }
+
+    /// Creates the command for the VPD page `page`.
+    #[must_use]
+    pub const fn vpd(page: u8) -> Self {
+        Self {
+            vpd_page: Some(page),
+            allocation_length: 255,
+        }
+    }
+
+    /// Returns the CDB of the command.
+    #[must_use]
+    pub const fn cdb(&self) -> [u8; 6] {
+        let (evpd, page) = match self.vpd_page {
+            Some(page) => (1, page),
+            None => (0, 0),
+        };
+        let [len_hi, len_lo] = self.allocation_length.to_be_bytes();
+        [INQUIRY, evpd, page, len_hi, len_lo, 0]
+    }
+
+    /// Returns a request builder for the command.
+    pub fn builder<'a>(&self, io_align: u32) -> Result<ScsiRequestBuilder<'a>, LayoutError> {
+        ScsiRequestBuilder::read(io_align)
+            .with_command_data(&self.cdb())?
+            .with_read_buffer(usize::from(self.allocation_length))
+    }
+}
+
+/// The READ CAPACITY (10) command, which returns [`Capacity`] for devices
+/// with up to 2^32 blocks.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct ReadCapacity10;
+
+impl ReadCapacity10 {
+    /// Returns the CDB of the command.
+    #[must_use]
+    pub const fn cdb(&self) -> [u8; 10] {
+        [READ_CAPACITY_10, 0, 0, 0, 0, 0, 0, 0, 0, 0]
+    }
+
+    /// Returns a request builder for the command.
+    pub fn builder<'a>(&self, io_align: u32) -> Result<ScsiRequestBuilder<'a>, LayoutError> {
+        ScsiRequestBuilder::read(io_align)
+            .with_command_data(&self.cdb())?
+            .with_read_buffer(8)
+    }
+}
+
+/// The READ CAPACITY (16) command, which returns [`Capacity`] for devices
+/// of any size.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct ReadCapacity16;
+
+impl ReadCapacity16 {
+    /// Size of the returned data.
+    const DATA_LENGTH: u32 = 32;
+
+    /// Returns the CDB of the command.
+    #[must_use]
+    pub const fn cdb(&self) -> [u8; 16] {
+        let mut cdb = [0; 16];
+        cdb[0] = SERVICE_ACTION_IN_16;
+        cdb[1] = READ_CAPACITY_16_ACTION;
+        let len = Self::DATA_LENGTH.to_be_bytes();
+        cdb[10] = len[0];
+        cdb[11] = len[1];
+        cdb[12] = len[2];
+        cdb[13] = len[3];
+        cdb
+    }
+
+    /// Returns a request builder for the command.
+    pub fn builder<'a>(&self, io_align: u32) -> Result<ScsiRequestBuilder<'a>, LayoutError> {
+        ScsiRequestBuilder::read(io_align)
+            .with_command_data(&self.cdb())?
+            .with_read_buffer(Self::DATA_LENGTH as usize)
+    }
+}
+
+/// Builds a READ (16) or WRITE (16) CDB.
+const fn rw16_cdb(opcode: u8, lba: u64, blocks: u32) -> [u8; 16] {
+    let mut cdb = [0; 16];
+    cdb[0] = opcode;
+    let lba = lba.to_be_bytes();
+    let blocks = blocks.to_be_bytes();
+    let mut i = 0;
+    while i < 8 {
+        cdb[2 + i] = lba[i];
+        i += 1;
+    }
+    let mut i = 0;
+    while i < 4 {
+        cdb[10 + i] = blocks[i];
+        i += 1;
+    }
+    cdb
+}
+
+/// The READ (16) command, which reads blocks from the device.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Read16 {
+    /// First block to read.
+    pub lba: u64,
+    /// Number of blocks to read.
+    pub blocks: u32,
+}
+
+impl Read16 {
+    /// Creates the command to read `blocks` blocks starting at `lba`.
+    #[must_use]
+    pub const fn new(lba: u64, blocks: u32) -> Self {
+        Self { lba, blocks }
+    }
+
+    /// Returns the CDB of the command.
+    #[must_use]
+    pub const fn cdb(&self) -> [u8; 16] {
+        rw16_cdb(READ_16, self.lba, self.blocks)
+    }
+
+    /// Returns a request builder for the command, with a read buffer for
+    /// blocks of `block_size` bytes.
+    pub fn builder<'a>(
+        &self,
+        io_align: u32,
+        block_size: u32,
+    ) -> Result<ScsiRequestBuilder<'a>, LayoutError> {
+        ScsiRequestBuilder::read(io_align)
+            .with_command_data(&self.cdb())?
+            .with_read_buffer(self.blocks as usize * block_size as usize)
+    }
+}
+
+/// The WRITE (16) command, which writes blocks to the device.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Write16 {
+    /// First block to write.
+    pub lba: u64,
+    /// Number of blocks to write.
+    pub blocks: u32,
+}
+
+impl Write16 {
+    /// Creates the command to write `blocks` blocks starting at `lba`.
+    #[must_use]
+    pub const fn new(lba: u64, blocks: u32) -> Self {
+        Self { lba, blocks }
+    }
+
+    /// Returns the CDB of the command.
+    #[must_use]
+    pub const fn cdb(&self) -> [u8; 16] {
+        rw16_cdb(WRITE_16, self.lba, self.blocks)
+    }
+
+    /// Returns a request builder for the command that writes `data`, whose
+    /// length must be the number of blocks times the block size.
+    pub fn builder<'a>(
+        &self,
+        io_align: u32,
+        data: &[u8],
+    ) -> Result<ScsiRequestBuilder<'a>, LayoutError> {
+        ScsiRequestBuilder::write(io_align)
+            .with_command_data(&self.cdb())?
+            .with_write_data(data)
+    }
+}
+
+/// Standard data returned by [`Inquiry`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct InquiryData {
+    /// Peripheral qualifier, `0` if a device is connected to the LUN.
+    pub peripheral_qualifier: u8,
+    /// Peripheral device type, e.g. `0x00` for disks and `0x05` for CD/DVD
+    /// drives. `0x1f` means that no device is connected.
+    pub device_type: u8,
+    /// Whether the medium is removable.
+    pub removable: bool,
+    /// Supported version of the SCSI standard.
+    pub version: u8,
+    vendor_id: [u8; 8],
+    product_id: [u8; 16],
+    product_revision: [u8; 4],
+}
+
+impl InquiryData {
+    /// Parses the standard inquiry data. Returns `None` if `data` is shorter
+    /// than 36 bytes.
+    #[must_use]
+    pub fn parse(data: &[u8]) -> Option<Self> {
+        let data = data.get(..36)?;
+        Some(Self {
+            peripheral_qualifier: data[0] >> 5,
+            device_type: data[0] & 0x1f,
+            removable: data[1] & 0x80 != 0,
+            version: data[2],
+            vendor_id: data[8..16].try_into().unwrap(),
+            product_id: data[16..32].try_into().unwrap(),
+            product_revision: data[32..36].try_into().unwrap(),
+        })
+    }
+
+    /// Returns whether a device is connected to the LUN.
+    #[must_use]
+    pub const fn is_connected(&self) -> bool {
+        self.peripheral_qualifier == 0 && self.device_type != 0x1f
+    }
+
+    /// Vendor identification, without padding.
+    #[must_use]
+    pub fn vendor_id(&self) -> &str {
+        ascii_field(&self.vendor_id)
+    }
+
+    /// Product identification, without padding.
+    #[must_use]
+    pub fn product_id(&self) -> &str {
+        ascii_field(&self.product_id)
+    }
+
+    /// Product revision level, without padding.
+    #[must_use]
+    pub fn product_revision(&self) -> &str {
+        ascii_field(&self.product_revision)
+    }
+}
+
+/// Parses the unit serial number VPD page (`0x80`). Returns `None` if the
+/// data is no such page.
+#[must_use]
+pub fn parse_unit_serial_number(data: &[u8]) -> Option<String> {
+    let payload = vpd_payload(data, Inquiry::VPD_UNIT_SERIAL_NUMBER)?;
+    Some(String::from_utf8_lossy(payload).trim().into())
+}
+
+/// A designator of the device identification VPD page (`0x83`).
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Designator {
+    /// Code set of the identifier: `1` binary, `2` ASCII, `3` UTF-8.
+    pub code_set: u8,
+    /// Entity the designator belongs to: `0` the LUN, `1` the target port,
+    /// `2` the target device.
+    pub association: u8,
+    /// Type of the designator, e.g. `2` for EUI-64, `3` for NAA or `8` for
+    /// SCSI name strings.
+    pub designator_type: u8,
+    /// The identifier.
+    pub identifier: Vec<u8>,
+}
+
+impl Designator {
+    /// Parses all designators of the device identification VPD page.
+    /// Returns `None` if the data is no such page or truncated.
+    #[must_use]
+    pub fn parse_all(data: &[u8]) -> Option<Vec<Self>> {
+        let mut payload = vpd_payload(data, Inquiry::VPD_DEVICE_IDENTIFICATION)?;
+        let mut designators = Vec::new();
+        while !payload.is_empty() {
+            let header = payload.get(..4)?;
+            let len = usize::from(header[3]);
+            designators.push(Self {
+                code_set: header[0] & 0x0f,
+                association: (header[1] >> 4) & 0x03,
+                designator_type: header[1] & 0x0f,
+                identifier: payload.get(4..4 + len)?.to_vec(),
+            });
+            payload = &payload[4 + len..];
+        }
+        Some(designators)
+    }
+}
+
+/// Returns the payload of a VPD page, after checking its page code.
+fn vpd_payload(data: &[u8], page: u8) -> Option<&[u8]> {
+    let header = data.get(..4)?;
+    if header[1] != page {
+        return None;
+    }
+    let len = usize::from(u16::from_be_bytes([header[2], header[3]]));
+    // The payload may be truncated if the allocation length was too small.
+    Some(&data[4..data.len().min(4 + len)])
+}
+
+/// Capacity returned by [`ReadCapacity10`] and [`ReadCapacity16`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Capacity {
+    /// Address of the last block.
+    pub last_lba: u64,
+    /// Size of a block in bytes.
+    pub block_size: u32,
+    /// Number of logical blocks per physical block, as a power of two. Only
+    /// returned by [`ReadCapacity16`].
+    pub logical_blocks_per_physical_block_exponent: u8,
+    /// First logical block that is aligned to a physical block. Only
+    /// returned by [`ReadCapacity16`].
+    pub lowest_aligned_lba: u16,
+}
+
+impl Capacity {
+    /// Parses the data returned by [`ReadCapacity10`]. Returns `None` if
+    /// `data` is shorter than 8 bytes.
+    ///
+    /// A last LBA of `0xffff_ffff` means that the device is too large and
+    /// [`ReadCapacity16`] must be used.
+    #[must_use]
+    pub fn parse_10(data: &[u8]) -> Option<Self> {
+        let data = data.get(..8)?;
+        Some(Self {
+            last_lba: u64::from(u32::from_be_bytes(data[0..4].try_into().unwrap())),
+            block_size: u32::from_be_bytes(data[4..8].try_into().unwrap()),
+            logical_blocks_per_physical_block_exponent: 0,
+            lowest_aligned_lba: 0,
+        })
+    }
+
+    /// Parses the data returned by [`ReadCapacity16`]. Returns `None` if
+    /// `data` is shorter than 16 bytes.
+    #[must_use]
+    pub fn parse_16(data: &[u8]) -> Option<Self> {
+        let data = data.get(..16)?;
+        Some(Self {
+            last_lba: u64::from_be_bytes(data[0..8].try_into().unwrap()),
+            block_size: u32::from_be_bytes(data[8..12].try_into().unwrap()),
+            logical_blocks_per_physical_block_exponent: data[13] & 0x0f,
+            lowest_aligned_lba: u16::from_be_bytes([data[14] & 0x3f, data[15]]),
+        })
+    }
+
+    /// Number of blocks of the device.
+    #[must_use]
+    pub const fn num_blocks(&self) -> u64 {
+        self.last_lba + 1
+    }
+
+    /// Size of the device in bytes.
+    #[must_use]
+    pub const fn size(&self) -> u64 {
+        self.num_blocks() * self.block_size as u64
+    }
+}
+
+newtype_enum! {
+    /// Sense key of [`SenseData`], the general category of an error.
+    pub enum SenseKey: u8 => {
+        /// No error.
+        NO_SENSE = 0x00,
+        /// The command succeeded after recovery.
+        RECOVERED_ERROR = 0x01,
+        /// The device is not ready, e.g. because no medium is inserted.
+        NOT_READY = 0x02,
+        /// Unrecoverable error of the medium.
+        MEDIUM_ERROR = 0x03,
+        /// Hardware failure.
+        HARDWARE_ERROR = 0x04,
+        /// Invalid command or parameter.
+        ILLEGAL_REQUEST = 0x05,
+        /// The device has been reset or the medium has been changed.
+        UNIT_ATTENTION = 0x06,
+        /// The medium is write protected.
+        DATA_PROTECT = 0x07,
+        /// Blank or non-blank medium encountered.
+        BLANK_CHECK = 0x08,
+        /// Vendor specific condition.
+        VENDOR_SPECIFIC = 0x09,
+        /// A copy command was aborted.
+        COPY_ABORTED = 0x0a,
+        /// The device aborted the command.
+        ABORTED_COMMAND = 0x0b,
+        /// The end of the medium was reached.
+        VOLUME_OVERFLOW = 0x0d,
+        /// Source data did not match the medium.
+        MISCOMPARE = 0x0e,
+    }
+}
+
+/// Sense data, returned by [`RequestSense`] or in the sense buffer of a
+/// failed request.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct SenseData {
+    /// Whether the data uses the descriptor format rather than the fixed
+    /// format.
+    pub descriptor_format: bool,
+    /// Whether the error is about the current command, rather than an
+    /// earlier one.
+    pub current: bool,
+    /// General category of the error.
+    pub sense_key: SenseKey,
+    /// Additional sense code.
+    pub asc: u8,
+    /// Additional sense code qualifier.
+    pub ascq: u8,
+}
+
+impl SenseData {
+    /// Parses sense data in fixed or descriptor format. Returns `None` if
+    /// the response code is unknown or the data is truncated.
+    #[must_use]
+    pub fn parse(data: &[u8]) -> Option<Self> {
+        let response_code = data.first()? & 0x7f;
+        let (descriptor_format, key, asc, ascq) = match response_code {
+            0x70 | 0x71 => (false, *data.get(2)?, *data.get(12)?, *data.get(13)?),
+            0x72 | 0x73 => (true, *data.get(1)?, *data.get(2)?, *data.get(3)?),
+            _ => return None,
+        };
+        Some(Self {
+            descriptor_format,
+            current: response_code & 0x01 == 0,
+            sense_key: SenseKey(key & 0x0f),
+            asc,
+            ascq,
+        })
+    }
+}
+
+/// Converts a space-padded ASCII field to a string.
+fn ascii_field(bytes: &[u8]) -> &str {
+    core::str::from_utf8(bytes)
+        .unwrap_or("")
+        .trim_end_matches([' ', '\0'])
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloc::vec;
+
+    #[test]
+    fn test_cdbs() {
+        assert_eq!(TestUnitReady.cdb(), [0; 6]);
+        assert_eq!(RequestSense::new().cdb(), [0x03, 0, 0, 0, 252, 0]);
+        assert_eq!(Inquiry::standard().cdb(), [0x12, 0, 0, 0, 255, 0]);
+        assert_eq!(
+            Inquiry::vpd(Inquiry::VPD_DEVICE_IDENTIFICATION).cdb(),
+            [0x12, 1, 0x83, 0, 255, 0]
+        );
+        assert_eq!(ReadCapacity10.cdb(), [0x25, 0, 0, 0, 0, 0, 0, 0, 0, 0]);
+        assert_eq!(
+            ReadCapacity16.cdb(),
+            [0x9e, 0x10, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 32, 0, 0]
+        );
+        assert_eq!(
+            Read16::new(0x0102_0304_0506_0708, 0x1234).cdb(),
+            [0x88, 0, 1, 2, 3, 4, 5, 6, 7, 8, 0, 0, 0x12, 0x34, 0, 0]
+        );
+        assert_eq!(Write16::new(1, 2).cdb()[..2], [0x8a, 0]);
+    }
+
+    #[test]
+    fn test_inquiry_data() {
+        let mut data = vec![0; 36];
+        data[1] = 0x80;
+        data[2] = 5;
+        data[8..16].copy_from_slice(b"uefi-rs ");
+        data[16..32].copy_from_slice(b"ExtScsiPassThru ");
+        data[32..36].copy_from_slice(b"2.5+");
+        let inquiry = InquiryData::parse(&data).unwrap();
+        assert!(inquiry.is_connected());
+        assert!(inquiry.removable);
+        assert_eq!(inquiry.vendor_id(), "uefi-rs");
+        assert_eq!(inquiry.product_id(), "ExtScsiPassThru");
+        assert_eq!(inquiry.product_revision(), "2.5+");
+        assert!(InquiryData::parse(&data[..20]).is_none());
+    }
+
+    #[test]
+    fn test_vpd_pages() {
+        let serial = [0, 0x80, 0, 6, b'A', b'B', b'C', b'1', b' ', b' '];
+        assert_eq!(parse_unit_serial_number(&serial).unwrap(), "ABC1");
+        assert!(parse_unit_serial_number(&[0, 0x83, 0, 0]).is_none());
+
+        let ids = [
+            0, 0x83, 0, 20, //
+            0x01, 0x03, 0, 8, 1, 2, 3, 4, 5, 6, 7, 8, //
+            0x02, 0x18, 0, 4, b'a', b'b', b'c', b'd',
+        ];
+        let designators = Designator::parse_all(&ids).unwrap();
+        assert_eq!(designators.len(), 2);
+        assert_eq!(designators[0].designator_type, 3);
+        assert_eq!(designators[0].identifier, [1, 2, 3, 4, 5, 6, 7, 8]);
+        assert_eq!(designators[1].code_set, 2);
+        assert_eq!(designators[1].association, 1);
+        assert_eq!(designators[1].designator_type, 8);
+    }
+
+    #[test]
+    fn test_capacity() {
+        let capacity = Capacity::parse_10(&[0, 0, 0x4f, 0xff, 0, 0, 2, 0]).unwrap();
+        assert_eq!(capacity.num_blocks(), 20480);
+        assert_eq!(capacity.size(), 10 * 1024 * 1024);
+
+        let mut data = [0; 32];
+        data[0..8].copy_from_slice(&0x1_0000_0000u64.to_be_bytes());
+        data[8..12].copy_from_slice(&4096u32.to_be_bytes());
+        data[13] = 3;
+        let capacity = Capacity::parse_16(&data).unwrap();
+        assert_eq!(capacity.last_lba, 0x1_0000_0000);
+        assert_eq!(capacity.block_size, 4096);
+        assert_eq!(capacity.logical_blocks_per_physical_block_exponent, 3);
+    }
+
+    #[test]
+    fn test_sense_data() {
+        let mut fixed = [0; 18];
+        fixed[0] = 0x70;
+        fixed[2] = 0x05;
+        fixed[12] = 0x24;
+        let sense = SenseData::parse(&fixed).unwrap();
+        assert!(!sense.descriptor_format);
+        assert!(sense.current);
+        assert_eq!(sense.sense_key, SenseKey::ILLEGAL_REQUEST);
+        assert_eq!((sense.asc, sense.ascq), (0x24, 0));
+
+        let sense = SenseData::parse(&[0x73, 0x02, 0x3a, 0x01]).unwrap();
+        assert!(sense.descriptor_format);
+        assert!(!sense.current);
+        assert_eq!(sense.sense_key, SenseKey::NOT_READY);
+        assert_eq!((sense.asc, sense.ascq), (0x3a, 0x01));
+
+        assert!(SenseData::parse(&[0x00; 18]).is_none());
+    }
+}
@@ -11,6 +11,7 @@ use uefi_raw::protocol::scsi::{
     ScsiIoDataDirection, ScsiIoHostAdapterStatus, ScsiIoScsiRequestPacket, ScsiIoTargetStatus,
 };
 
+pub mod command;
 pub mod pass_thru;
 
 /// Represents the data direction for a SCSI request.
@@ -2,7 +2,9 @@
 
 //! EFI Shell Protocol v2.2
 
+use crate::CStr16;
 use crate::proto::unsafe_protocol;
+use core::ptr;
 
 pub use uefi_raw::protocol::shell::ShellProtocol;
 
@@ -11,3 +13,27 @@ pub use uefi_raw::protocol::shell::ShellProtocol;
 #[repr(transparent)]
 #[unsafe_protocol(uefi_raw::protocol::shell::ShellProtocol::GUID)]
 pub struct Shell(uefi_raw::protocol::shell::ShellProtocol);
+
+impl Shell {
+    /// Returns the current directory on the specified device.
+    ///
+    /// # Arguments
+    /// * `file_system_mapping` - The file system mapping for which to get the
+    ///   current directory, e.g. `FS0:`. If `None`, the current directory of
+    ///   the current file system mapping is returned.
+    ///
+    /// # Returns
+    /// * `Some(cwd)` - The current directory, prefixed by the file system
+    ///   mapping, e.g. `FS0:\EFI\BOOT`.
+    /// * `None` - The mapping does not exist or has no current directory.
+    #[must_use]
+    pub fn current_dir(&self, file_system_mapping: Option<&CStr16>) -> Option<&CStr16> {
+        let mapping_ptr = file_system_mapping.map_or(ptr::null(), |x| x.as_ptr().cast());
+        let cur_dir = unsafe { (self.0.get_cur_dir)(mapping_ptr) };
+        if cur_dir.is_null() {
+            None
+        } else {
+            unsafe { Some(CStr16::from_ptr(cur_dir.cast())) }
+        }
+    }
+}
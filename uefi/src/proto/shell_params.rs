@@ -1,6 +1,13 @@
 // SPDX-License-Identifier: MIT OR Apache-2.0
 
 //! `ShellParams` protocol
+//!
+//! The shell splits the command line of an image into arguments. Images that
+//! are started by other means only have the command line in their
+//! [load options]; [`split_command_line`] splits it with the same quoting
+//! rules.
+//!
+//! [load options]: crate::proto::loaded_image::LoadedImage::load_options_as_cstr16
 
 use crate::proto::unsafe_protocol;
 use crate::{Char16, data_types};
@@ -8,6 +15,21 @@ use core::slice::from_raw_parts;
 use uefi_raw::protocol::shell_params::ShellParametersProtocol;
 
 use crate::CStr16;
+#[cfg(feature = "alloc")]
+use {crate::CString16, alloc::vec::Vec};
+
+/// Character that quotes arguments with whitespace.
+#[cfg(feature = "alloc")]
+const QUOTE: Char16 = unsafe { Char16::from_u16_unchecked(b'"' as u16) };
+/// Character that makes the following character literal.
+#[cfg(feature = "alloc")]
+const ESCAPE: Char16 = unsafe { Char16::from_u16_unchecked(b'^' as u16) };
+/// Character that separates arguments.
+#[cfg(feature = "alloc")]
+const SPACE: Char16 = unsafe { Char16::from_u16_unchecked(b' ' as u16) };
+/// Character that separates arguments.
+#[cfg(feature = "alloc")]
+const TAB: Char16 = unsafe { Char16::from_u16_unchecked(b'\t' as u16) };
 
 /// The ShellParameters protocol.
 #[derive(Debug)]
@@ -40,3 +62,129 @@ impl ShellParameters {
         }
     }
 }
+
+/// Splits `command_line` into arguments, with the quoting rules of the UEFI
+/// Shell:
+///
+/// * Arguments are separated by spaces and tabs.
+/// * Double quotes group characters, including whitespace, into one
+///   argument. They are removed from the argument.
+/// * A caret (`^`) makes the following character literal, e.g. `^"` is a
+///   double quote. The caret is removed from the argument.
+///
+/// See [`join_command_line`] for the reverse.
+#[cfg(feature = "alloc")]
+#[must_use]
+pub fn split_command_line(command_line: &CStr16) -> Vec<CString16> {
+    let mut args = Vec::new();
+    let mut arg = CString16::new();
+    // Whether an argument has been started, which may be empty if it
+    // consists of quotes only.
+    let mut in_arg = false;
+    let mut quoted = false;
+    let mut escaped = false;
+    for &c in command_line.as_slice() {
+        if escaped {
+            arg.push(c);
+            escaped = false;
+        } else if c == ESCAPE {
+            escaped = true;
+        } else if c == QUOTE {
+            quoted = !quoted;
+        } else if (c == SPACE || c == TAB) && !quoted {
+            if in_arg {
+                args.push(core::mem::take(&mut arg));
+                in_arg = false;
+            }
+            continue;
+        } else {
+            arg.push(c);
+        }
+        in_arg = true;
+    }
+    if in_arg {
+        args.push(arg);
+    }
+    args
+}
+
+/// Joins `args` into a command line that [`split_command_line`] splits into
+/// the same arguments. Arguments with whitespace, double quotes or carets
+/// are quoted.
+#[cfg(feature = "alloc")]
+#[must_use]
+pub fn join_command_line<'a>(args: impl IntoIterator<Item = &'a CStr16>) -> CString16 {
+    let mut command_line = CString16::new();
+    for (index, arg) in args.into_iter().enumerate() {
+        if index > 0 {
+            command_line.push(SPACE);
+        }
+        push_arg(&mut command_line, arg);
+    }
+    command_line
+}
+
+/// Appends `arg` to `command_line`, quoted if needed.
+#[cfg(feature = "alloc")]
+pub(crate) fn push_arg(command_line: &mut CString16, arg: &CStr16) {
+    let needs_quotes = arg.is_empty()
+        || arg
+            .as_slice()
+            .iter()
+            .any(|&c| [QUOTE, ESCAPE, SPACE, TAB].contains(&c));
+    if !needs_quotes {
+        command_line.push_str(arg);
+        return;
+    }
+    command_line.push(QUOTE);
+    for &c in arg.as_slice() {
+        if c == QUOTE || c == ESCAPE {
+            command_line.push(ESCAPE);
+        }
+        command_line.push(c);
+    }
+    command_line.push(QUOTE);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cstr16;
+
+    #[test]
+    fn test_split_command_line() {
+        assert_eq!(
+            split_command_line(cstr16!(r#"  shell.efi  -a "b c"	d^"e ^^ "" "#)),
+            [
+                cstr16!("shell.efi"),
+                cstr16!("-a"),
+                cstr16!("b c"),
+                cstr16!(r#"d"e"#),
+                cstr16!("^"),
+                cstr16!(""),
+            ]
+        );
+        assert!(split_command_line(cstr16!("  ")).is_empty());
+        // Quotes in the middle of an argument.
+        assert_eq!(
+            split_command_line(cstr16!(r#"a"b c"d"#)),
+            [cstr16!("ab cd")]
+        );
+    }
+
+    #[test]
+    fn test_join_command_line() {
+        let args = [
+            cstr16!("test.efi"),
+            cstr16!("with space"),
+            cstr16!(""),
+            cstr16!(r#"q"u^"#),
+        ];
+        let command_line = join_command_line(args);
+        assert_eq!(
+            command_line,
+            cstr16!(r#"test.efi "with space" "" "q^"u^^""#)
+        );
+        assert_eq!(split_command_line(&command_line), args);
+    }
+}
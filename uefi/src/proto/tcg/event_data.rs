@@ -0,0 +1,275 @@
+// SPDX-License-Identifier: MIT OR Apache-2.0
+
+//! Typed event data of the TPM event log.
+
+use super::EventType;
+use crate::data_types::{PhysicalAddress, UnalignedSlice};
+use crate::proto::device_path::DevicePath;
+use crate::{Char16, Guid};
+use core::str;
+
+/// Signature of the [`EventType::NO_ACTION`] event that records the
+/// locality from which the TPM was started.
+const STARTUP_LOCALITY_SIGNATURE: &[u8; 16] = b"StartupLocality\0";
+
+/// Event data of a PCR event, decoded according to its [`EventType`].
+///
+/// The layouts are defined in the [TCG PC Client Platform Firmware Profile
+/// Specification][spec]. Event types that are not decoded, and event data
+/// that doesn't match the layout of its type, are returned as
+/// [`EventData::Other`].
+///
+/// [spec]: https://trustedcomputinggroup.org/resource/pc-client-specific-platform-firmware-profile-specification/
+#[derive(Clone, Debug)]
+pub enum EventData<'a> {
+    /// [`EventType::SEPARATOR`]: the end of the pre-OS measurements of a
+    /// PCR. If `error` is true, an error occurred during the measurements.
+    Separator {
+        /// Whether the separator marks an error.
+        error: bool,
+    },
+
+    /// [`EventType::ACTION`] or [`EventType::EFI_ACTION`]: a string that
+    /// describes an action of the firmware.
+    Action(&'a str),
+
+    /// [`EventType::NO_ACTION`] with the startup locality of the TPM. It is
+    /// the initial value of the last byte of PCR 0.
+    StartupLocality(u8),
+
+    /// [`EventType::EFI_VARIABLE_DRIVER_CONFIG`],
+    /// [`EventType::EFI_VARIABLE_BOOT`], [`EventType::EFI_VARIABLE_BOOT2`]
+    /// or [`EventType::EFI_VARIABLE_AUTHORITY`]: a measured UEFI variable.
+    Variable {
+        /// Vendor GUID of the variable.
+        vendor: Guid,
+        /// Name of the variable, without null terminator.
+        name: UnalignedSlice<'a, Char16>,
+        /// Data of the variable.
+        data: &'a [u8],
+    },
+
+    /// [`EventType::EFI_BOOT_SERVICES_APPLICATION`],
+    /// [`EventType::EFI_BOOT_SERVICES_DRIVER`] or
+    /// [`EventType::EFI_RUNTIME_SERVICES_DRIVER`]: a measured PE/COFF image.
+    ImageLoad {
+        /// Address of the image in memory.
+        location: PhysicalAddress,
+        /// Size of the image in bytes.
+        length: u64,
+        /// Link time address of the image.
+        link_time_address: u64,
+        /// Device path of the image, if the firmware recorded a valid one.
+        device_path: Option<&'a DevicePath>,
+    },
+
+    /// [`EventType::EFI_PLATFORM_FIRMWARE_BLOB`]: a measured region of
+    /// firmware memory.
+    FirmwareBlob {
+        /// Address of the region.
+        base: PhysicalAddress,
+        /// Size of the region in bytes.
+        length: u64,
+    },
+
+    /// Any other event data.
+    Other(&'a [u8]),
+}
+
+impl<'a> EventData<'a> {
+    /// Decodes the `data` of an event of type `event_type`.
+    #[must_use]
+    pub fn parse(event_type: EventType, data: &'a [u8]) -> Self {
+        Self::try_parse(event_type, data).unwrap_or(Self::Other(data))
+    }
+
+    fn try_parse(event_type: EventType, data: &'a [u8]) -> Option<Self> {
+        match event_type {
+            EventType::SEPARATOR => match u32_at(data, 0)? {
+                0 => Some(Self::Separator { error: false }),
+                1 => Some(Self::Separator { error: true }),
+                _ => None,
+            },
+            EventType::ACTION | EventType::EFI_ACTION => {
+                str::from_utf8(data).ok().map(Self::Action)
+            }
+            EventType::NO_ACTION => {
+                if data.get(..16)? != STARTUP_LOCALITY_SIGNATURE {
+                    return None;
+                }
+                Some(Self::StartupLocality(*data.get(16)?))
+            }
+            EventType::EFI_VARIABLE_DRIVER_CONFIG
+            | EventType::EFI_VARIABLE_BOOT
+            | EventType::EFI_VARIABLE_BOOT2
+            | EventType::EFI_VARIABLE_AUTHORITY => {
+                // Layout of `UEFI_VARIABLE_DATA`.
+                let vendor = Guid::from_bytes(data.get(..16)?.try_into().ok()?);
+                let name_len = usize::try_from(u64_at(data, 16)?).ok()?;
+                let data_len = usize::try_from(u64_at(data, 24)?).ok()?;
+                let name_size = name_len.checked_mul(2)?;
+                let name = data.get(32..32usize.checked_add(name_size)?)?;
+                let data = data.get(32 + name_size..)?.get(..data_len)?;
+                // Safety: `name` has room for `name_len` characters.
+                let name = unsafe { UnalignedSlice::new(name.as_ptr().cast(), name_len) };
+                Some(Self::Variable { vendor, name, data })
+            }
+            EventType::EFI_BOOT_SERVICES_APPLICATION
+            | EventType::EFI_BOOT_SERVICES_DRIVER
+            | EventType::EFI_RUNTIME_SERVICES_DRIVER => {
+                // Layout of `UEFI_IMAGE_LOAD_EVENT`.
+                let location = u64_at(data, 0)?;
+                let length = u64_at(data, 8)?;
+                let link_time_address = u64_at(data, 16)?;
+                let device_path_len = usize::try_from(u64_at(data, 24)?).ok()?;
+                let device_path = data.get(32..)?.get(..device_path_len)?;
+                Some(Self::ImageLoad {
+                    location,
+                    length,
+                    link_time_address,
+                    device_path: <&DevicePath>::try_from(device_path).ok(),
+                })
+            }
+            EventType::EFI_PLATFORM_FIRMWARE_BLOB => Some(Self::FirmwareBlob {
+                base: u64_at(data, 0)?,
+                length: u64_at(data, 8)?,
+            }),
+            _ => None,
+        }
+    }
+}
+
+fn u32_at(bytes: &[u8], offset: usize) -> Option<u32> {
+    let bytes = bytes.get(offset..offset + 4)?;
+    Some(u32::from_le_bytes(bytes.try_into().ok()?))
+}
+
+fn u64_at(bytes: &[u8], offset: usize) -> Option<u64> {
+    let bytes = bytes.get(offset..offset + 8)?;
+    Some(u64::from_le_bytes(bytes.try_into().ok()?))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::guid;
+    use alloc::vec::Vec;
+
+    #[test]
+    fn test_parse_separator() {
+        assert!(matches!(
+            EventData::parse(EventType::SEPARATOR, &[0, 0, 0, 0]),
+            EventData::Separator { error: false }
+        ));
+        assert!(matches!(
+            EventData::parse(EventType::SEPARATOR, &[1, 0, 0, 0]),
+            EventData::Separator { error: true }
+        ));
+        assert!(matches!(
+            EventData::parse(EventType::SEPARATOR, &[1, 0]),
+            EventData::Other(&[1, 0])
+        ));
+    }
+
+    #[test]
+    fn test_parse_action() {
+        assert!(matches!(
+            EventData::parse(
+                EventType::EFI_ACTION,
+                b"Calling EFI Application from Boot Option"
+            ),
+            EventData::Action("Calling EFI Application from Boot Option")
+        ));
+    }
+
+    #[test]
+    fn test_parse_startup_locality() {
+        let mut data = STARTUP_LOCALITY_SIGNATURE.to_vec();
+        data.push(3);
+        assert!(matches!(
+            EventData::parse(EventType::NO_ACTION, &data),
+            EventData::StartupLocality(3)
+        ));
+    }
+
+    #[test]
+    fn test_parse_variable() {
+        let vendor = guid!("8be4df61-93ca-11d2-aa0d-00e098032b8c");
+        let mut bytes = vendor.to_bytes().to_vec();
+        bytes.extend(2u64.to_le_bytes());
+        bytes.extend(3u64.to_le_bytes());
+        bytes.extend([b'S', 0, b'B', 0]);
+        bytes.extend([1, 2, 3]);
+
+        let EventData::Variable {
+            vendor: parsed_vendor,
+            name,
+            data,
+        } = EventData::parse(EventType::EFI_VARIABLE_DRIVER_CONFIG, &bytes)
+        else {
+            panic!("not a variable");
+        };
+        assert_eq!(parsed_vendor, vendor);
+        let name: Vec<u16> = name.iter().map(u16::from).collect();
+        assert_eq!(name, [u16::from(b'S'), u16::from(b'B')]);
+        assert_eq!(data, [1, 2, 3]);
+
+        // The data is truncated.
+        assert!(matches!(
+            EventData::parse(EventType::EFI_VARIABLE_BOOT, &bytes[..bytes.len() - 1]),
+            EventData::Other(_)
+        ));
+    }
+
+    #[test]
+    fn test_parse_image_load() {
+        let image_load = |device_path: &[u8]| {
+            let mut data = 0x1000u64.to_le_bytes().to_vec();
+            data.extend(0x2000u64.to_le_bytes());
+            data.extend(0x3000u64.to_le_bytes());
+            data.extend((device_path.len() as u64).to_le_bytes());
+            data.extend(device_path);
+            data
+        };
+
+        // A device path with only the end node.
+        let data = image_load(&[0x7f, 0xff, 4, 0]);
+        let EventData::ImageLoad {
+            location,
+            length,
+            link_time_address,
+            device_path,
+        } = EventData::parse(EventType::EFI_BOOT_SERVICES_APPLICATION, &data)
+        else {
+            panic!("not an image load");
+        };
+        assert_eq!(
+            (location, length, link_time_address),
+            (0x1000, 0x2000, 0x3000)
+        );
+        assert_eq!(device_path.unwrap().node_iter().count(), 0);
+
+        // A corrupt device path with a zero-length node.
+        let data = image_load(&[0x54, 0x5d, 0, 0, 0x7f, 0xff, 4, 0]);
+        assert!(matches!(
+            EventData::parse(EventType::EFI_BOOT_SERVICES_DRIVER, &data),
+            EventData::ImageLoad {
+                device_path: None,
+                ..
+            }
+        ));
+    }
+
+    #[test]
+    fn test_parse_firmware_blob() {
+        let mut data = 0x1000u64.to_le_bytes().to_vec();
+        data.extend(0x2000u64.to_le_bytes());
+        assert!(matches!(
+            EventData::parse(EventType::EFI_PLATFORM_FIRMWARE_BLOB, &data),
+            EventData::FirmwareBlob {
+                base: 0x1000,
+                length: 0x2000
+            }
+        ));
+    }
+}
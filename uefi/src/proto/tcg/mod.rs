@@ -8,14 +8,19 @@
 //! the [`v1`] module. It is used with TPM 1.1 and 1.2 devices. The
 //! newer protocol in the [`v2`] module is generally provided for TPM
 //! 2.0 devices, although the spec indicates it can be used for older
-//! TPM versions as well.
+//! TPM versions as well. The [`tpm2`] module builds and parses the TPM 2.0
+//! commands that are sent with [`v2::Tcg::submit_command`].
 //!
 //! [TCG]: https://trustedcomputinggroup.org/
 //! [TPM]: https://en.wikipedia.org/wiki/Trusted_Platform_Module
 
+pub mod tpm2;
 pub mod v1;
 pub mod v2;
 
+mod event_data;
+
+pub use event_data::EventData;
 pub use uefi_raw::protocol::tcg::{AlgorithmId, EventType};
 
 use bitflags::bitflags;
@@ -48,3 +53,51 @@ bitflags! {
         const SM3_256 = 0x0000_0010;
     }
 }
+
+/// Size in bytes of the largest digest of the [`HashAlgorithm`]s.
+const MAX_DIGEST_SIZE: usize = 64;
+
+/// Returns the value that a PCR has after being extended with `digest`,
+/// i.e. `hash(value || digest)`. Returns `None` if `digest` doesn't have
+/// the size of the PCR.
+fn extend_pcr<const N: usize>(
+    value: &[u8; N],
+    digest: &[u8],
+    hash: &mut impl FnMut(&[u8]) -> [u8; N],
+) -> Option<[u8; N]> {
+    if N > MAX_DIGEST_SIZE || digest.len() != N {
+        return None;
+    }
+    let mut data = [0; 2 * MAX_DIGEST_SIZE];
+    data[..N].copy_from_slice(value);
+    data[N..2 * N].copy_from_slice(digest);
+    Some(hash(&data[..2 * N]))
+}
+
+/// Computes the value of PCR `pcr` after the measurements of `events`,
+/// which yields the PCR index, event type, event data and digest of each
+/// event.
+fn replay_pcr<'a, const N: usize>(
+    pcr: PcrIndex,
+    events: impl Iterator<Item = (PcrIndex, EventType, &'a [u8], Option<&'a [u8]>)>,
+    mut hash: impl FnMut(&[u8]) -> [u8; N],
+) -> Option<[u8; N]> {
+    let mut value = [0; N];
+    for (index, event_type, event_data, digest) in events {
+        if index != pcr {
+            continue;
+        }
+        if event_type == EventType::NO_ACTION {
+            // Events of this type are not measured, but the startup
+            // locality is the initial value of PCR 0.
+            if let EventData::StartupLocality(locality) = EventData::parse(event_type, event_data) {
+                if pcr == PcrIndex(0) && N > 0 {
+                    value[N - 1] = locality;
+                }
+            }
+            continue;
+        }
+        value = extend_pcr(&value, digest?, &mut hash)?;
+    }
+    Some(value)
+}
@@ -0,0 +1,827 @@
+// SPDX-License-Identifier: MIT OR Apache-2.0
+
+//! Marshaling of TPM 2.0 commands and responses.
+//!
+//! The functions of this module build a command, send it to the TPM with
+//! [`Tcg::submit_command`], and parse the response into typed values. Only
+//! commands that don't need an authorization session are supported, plus
+//! [`nv_read`], which authorizes with the empty password of the NV index.
+//!
+//! The structures are defined in the [TPM 2.0 Library Specification][spec],
+//! Part 2 (Structures) and Part 3 (Commands).
+//!
+//! [spec]: https://trustedcomputinggroup.org/resource/tpm-library-specification/
+
+use super::v2::{PcrValue, Tcg};
+use super::{AlgorithmId, PcrIndex};
+use crate::Status;
+use crate::util::usize_from_u32;
+use bitflags::bitflags;
+use core::fmt::{self, Display, Formatter};
+
+/// Tag of a command without authorization sessions (`TPM_ST_NO_SESSIONS`).
+const ST_NO_SESSIONS: u16 = 0x8001;
+/// Tag of a command with authorization sessions (`TPM_ST_SESSIONS`).
+const ST_SESSIONS: u16 = 0x8002;
+
+const CC_NV_READ: u32 = 0x14e;
+const CC_NV_READ_PUBLIC: u32 = 0x169;
+const CC_GET_CAPABILITY: u32 = 0x17a;
+const CC_GET_RANDOM: u32 = 0x17b;
+const CC_PCR_READ: u32 = 0x17e;
+
+/// Handle of the password authorization session (`TPM_RS_PW`).
+const RS_PW: u32 = 0x4000_0009;
+
+/// Capability of the TPM properties (`TPM_CAP_TPM_PROPERTIES`).
+const CAP_TPM_PROPERTIES: u32 = 6;
+
+/// Number of PCRs that can be selected in a `TPM2_PCR_Read` command.
+const PCR_COUNT: u32 = 24;
+
+/// Size of the buffer for responses.
+const RESPONSE_SIZE: usize = 1024;
+
+/// Largest number of bytes read from an NV index with one command.
+const NV_READ_CHUNK_SIZE: u16 = 512;
+
+/// Largest number of random bytes requested with one command.
+const GET_RANDOM_CHUNK_SIZE: u16 = 32;
+
+newtype_enum! {
+    /// Response code of a TPM command (`TPM_RC`).
+    ///
+    /// Codes of format one also encode the parameter, handle or session
+    /// that caused the error; [`base`] removes that information so that
+    /// the code can be compared with the constants of this type.
+    ///
+    /// [`base`]: Self::base
+    pub enum ResponseCode: u32 => {
+        /// The command succeeded.
+        SUCCESS = 0x000,
+
+        /// The TPM is not initialized.
+        INITIALIZE = 0x100,
+        /// The TPM is in failure mode.
+        FAILURE = 0x101,
+        /// The command is disabled.
+        DISABLED = 0x120,
+        /// The authorization of a handle is missing.
+        AUTH_MISSING = 0x125,
+        /// The authorization of the command is not available.
+        AUTH_UNAVAILABLE = 0x12f,
+        /// The command size is wrong.
+        COMMAND_SIZE = 0x142,
+        /// The command code is not supported.
+        COMMAND_CODE = 0x143,
+        /// The NV offset and size are out of the range of the NV index.
+        NV_RANGE = 0x146,
+        /// The NV index is locked.
+        NV_LOCKED = 0x148,
+        /// The NV index can't be accessed with the given authorization.
+        NV_AUTHORIZATION = 0x149,
+        /// The NV index has not been written yet.
+        NV_UNINITIALIZED = 0x14a,
+
+        /// A parameter has an unsupported value.
+        VALUE = 0x084,
+        /// The hash algorithm is not supported.
+        HASH = 0x083,
+        /// The handle is not correct for its use, e.g. it doesn't exist.
+        HANDLE = 0x08b,
+        /// The authorization failed.
+        AUTH_FAIL = 0x08e,
+        /// A size is out of range.
+        SIZE = 0x095,
+        /// The authorization HMAC check failed.
+        BAD_AUTH = 0x0a2,
+
+        /// The TPM is in dictionary attack lockout.
+        LOCKOUT = 0x921,
+        /// The TPM is busy, the command should be retried.
+        RETRY = 0x922,
+        /// The TPM is testing itself.
+        TESTING = 0x90a,
+    }
+}
+
+impl ResponseCode {
+    /// Bit of codes of format one.
+    const FORMAT_ONE: u32 = 0x080;
+
+    /// Returns whether the command succeeded.
+    #[must_use]
+    pub const fn is_success(self) -> bool {
+        self.0 == 0
+    }
+
+    /// Returns the code without the number of the parameter, handle or
+    /// session that caused the error.
+    #[must_use]
+    pub const fn base(self) -> Self {
+        if self.0 & Self::FORMAT_ONE != 0 {
+            Self(self.0 & 0xbf)
+        } else {
+            self
+        }
+    }
+}
+
+impl Display for ResponseCode {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(f, "TPM_RC {:#x}", self.0)
+    }
+}
+
+/// Error of the functions of this module.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Error {
+    /// The firmware failed to send the command, or the parameters are
+    /// invalid.
+    Uefi(crate::Error),
+    /// The TPM failed the command.
+    Tpm(ResponseCode),
+    /// The response of the TPM can't be parsed.
+    MalformedResponse,
+}
+
+impl Display for Error {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Uefi(err) => write!(f, "UEFI error: {err}"),
+            Self::Tpm(code) => write!(f, "TPM error: {code}"),
+            Self::MalformedResponse => write!(f, "malformed TPM response"),
+        }
+    }
+}
+
+impl core::error::Error for Error {
+    fn source(&self) -> Option<&(dyn core::error::Error + 'static)> {
+        match self {
+            Self::Uefi(err) => Some(err),
+            _ => None,
+        }
+    }
+}
+
+impl From<crate::Error> for Error {
+    fn from(err: crate::Error) -> Self {
+        Self::Uefi(err)
+    }
+}
+
+impl From<Status> for Error {
+    fn from(status: Status) -> Self {
+        Self::Uefi(status.into())
+    }
+}
+
+/// Result of the functions of this module.
+pub type Tpm2Result<T> = core::result::Result<T, Error>;
+
+newtype_enum! {
+    /// Property of the TPM (`TPM_PT`), see [`get_tpm_property`].
+    pub enum TpmProperty: u32 => {
+        /// Family of the specification, e.g. `"2.0\0"` as big endian integer.
+        FAMILY_INDICATOR = 0x100,
+        /// Level of the specification.
+        LEVEL = 0x101,
+        /// Revision of the specification, multiplied by 100.
+        REVISION = 0x102,
+        /// Day of the year of the specification.
+        DAY_OF_YEAR = 0x103,
+        /// Year of the specification.
+        YEAR = 0x104,
+        /// Vendor ID of the manufacturer, e.g. `"IBM\0"` as big endian
+        /// integer.
+        MANUFACTURER = 0x105,
+        /// First four characters of the vendor string.
+        VENDOR_STRING_1 = 0x106,
+        /// Second four characters of the vendor string.
+        VENDOR_STRING_2 = 0x107,
+        /// Third four characters of the vendor string.
+        VENDOR_STRING_3 = 0x108,
+        /// Last four characters of the vendor string.
+        VENDOR_STRING_4 = 0x109,
+        /// Vendor-defined TPM type.
+        VENDOR_TPM_TYPE = 0x10a,
+        /// Most significant 32 bits of the firmware version.
+        FIRMWARE_VERSION_1 = 0x10b,
+        /// Least significant 32 bits of the firmware version.
+        FIRMWARE_VERSION_2 = 0x10c,
+        /// Number of PCRs.
+        PCR_COUNT = 0x112,
+        /// Largest size of a command.
+        MAX_COMMAND_SIZE = 0x11e,
+        /// Largest size of a response.
+        MAX_RESPONSE_SIZE = 0x11f,
+        /// Largest size of a digest.
+        MAX_DIGEST = 0x120,
+        /// Largest number of bytes that can be read or written to an NV
+        /// index with one command.
+        NV_BUFFER_MAX = 0x12c,
+    }
+}
+
+bitflags! {
+    /// Attributes of an NV index (`TPMA_NV`).
+    #[derive(Clone, Copy, Debug, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+    #[repr(transparent)]
+    pub struct NvAttributes: u32 {
+        /// Platform authorization allows writing.
+        const PPWRITE = 1 << 0;
+        /// Owner authorization allows writing.
+        const OWNERWRITE = 1 << 1;
+        /// The authorization of the index allows writing.
+        const AUTHWRITE = 1 << 2;
+        /// The policy of the index allows writing.
+        const POLICYWRITE = 1 << 3;
+        /// The index can only be deleted with its policy.
+        const POLICY_DELETE = 1 << 10;
+        /// The index is write-locked.
+        const WRITELOCKED = 1 << 11;
+        /// Partial writes are not allowed.
+        const WRITEALL = 1 << 12;
+        /// The index can be permanently write-locked.
+        const WRITEDEFINE = 1 << 13;
+        /// The index can be write-locked until the next TPM reset.
+        const WRITE_STCLEAR = 1 << 14;
+        /// The index is write-locked by a global lock.
+        const GLOBALLOCK = 1 << 15;
+        /// Platform authorization allows reading.
+        const PPREAD = 1 << 16;
+        /// Owner authorization allows reading.
+        const OWNERREAD = 1 << 17;
+        /// The authorization of the index allows reading.
+        const AUTHREAD = 1 << 18;
+        /// The policy of the index allows reading.
+        const POLICYREAD = 1 << 19;
+        /// Failed authorizations don't count for dictionary attacks.
+        const NO_DA = 1 << 25;
+        /// The index is only written to NV memory on orderly shutdown.
+        const ORDERLY = 1 << 26;
+        /// The written state is cleared on TPM reset.
+        const CLEAR_STCLEAR = 1 << 27;
+        /// The index is read-locked.
+        const READLOCKED = 1 << 28;
+        /// The index has been written.
+        const WRITTEN = 1 << 29;
+        /// The index was defined with platform authorization.
+        const PLATFORMCREATE = 1 << 30;
+        /// The index can be read-locked until the next TPM reset.
+        const READ_STCLEAR = 1 << 31;
+    }
+}
+
+/// Public area of an NV index, see [`nv_read_public`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct NvPublic {
+    /// Handle of the index.
+    pub index: u32,
+    /// Algorithm used to compute the name of the index.
+    pub name_algorithm: AlgorithmId,
+    /// Attributes of the index.
+    pub attributes: NvAttributes,
+    /// Size of the data of the index in bytes.
+    pub data_size: u16,
+}
+
+/// Builder of a command.
+struct Command {
+    bytes: [u8; 64],
+    len: usize,
+}
+
+impl Command {
+    fn new(tag: u16, code: u32) -> Self {
+        let mut command = Self {
+            bytes: [0; 64],
+            len: 0,
+        };
+        command.u16(tag);
+        // The size is filled in by `finish`.
+        command.u32(0);
+        command.u32(code);
+        command
+    }
+
+    fn bytes(&mut self, bytes: &[u8]) {
+        self.bytes[self.len..self.len + bytes.len()].copy_from_slice(bytes);
+        self.len += bytes.len();
+    }
+
+    fn u8(&mut self, value: u8) {
+        self.bytes(&[value]);
+    }
+
+    fn u16(&mut self, value: u16) {
+        self.bytes(&value.to_be_bytes());
+    }
+
+    fn u32(&mut self, value: u32) {
+        self.bytes(&value.to_be_bytes());
+    }
+
+    /// Appends an empty password authorization session (`TPMS_AUTH_COMMAND`
+    /// with `TPM_RS_PW`), preceded by the size of the authorization area.
+    fn password_session(&mut self) {
+        self.u32(9);
+        self.u32(RS_PW);
+        // Empty nonce.
+        self.u16(0);
+        // Session attributes.
+        self.u8(0);
+        // Empty password.
+        self.u16(0);
+    }
+
+    /// Fills in the size, and returns the bytes of the command.
+    fn finish(&mut self) -> &[u8] {
+        let size = u32::try_from(self.len).unwrap();
+        self.bytes[2..6].copy_from_slice(&size.to_be_bytes());
+        &self.bytes[..self.len]
+    }
+}
+
+/// Parser of the parameters of a response.
+struct Response<'a> {
+    bytes: &'a [u8],
+}
+
+impl<'a> Response<'a> {
+    /// Checks the header of `bytes`, and returns a parser of the parameters
+    /// that follow it.
+    fn new(bytes: &'a [u8]) -> Tpm2Result<Self> {
+        let mut response = Self { bytes };
+        let _tag = response.u16()?;
+        let size = usize_from_u32(response.u32()?);
+        let code = ResponseCode(response.u32()?);
+        if !code.is_success() {
+            return Err(Error::Tpm(code));
+        }
+        let parameters = bytes.get(10..size).ok_or(Error::MalformedResponse)?;
+        Ok(Self { bytes: parameters })
+    }
+
+    const fn bytes(&mut self, len: usize) -> Tpm2Result<&'a [u8]> {
+        if len > self.bytes.len() {
+            return Err(Error::MalformedResponse);
+        }
+        let (bytes, rest) = self.bytes.split_at(len);
+        self.bytes = rest;
+        Ok(bytes)
+    }
+
+    fn u8(&mut self) -> Tpm2Result<u8> {
+        Ok(self.bytes(1)?[0])
+    }
+
+    fn u16(&mut self) -> Tpm2Result<u16> {
+        Ok(u16::from_be_bytes(self.bytes(2)?.try_into().unwrap()))
+    }
+
+    fn u32(&mut self) -> Tpm2Result<u32> {
+        Ok(u32::from_be_bytes(self.bytes(4)?.try_into().unwrap()))
+    }
+
+    /// Parses a sized buffer (`TPM2B_*`).
+    fn sized(&mut self) -> Tpm2Result<&'a [u8]> {
+        let size = self.u16()?;
+        self.bytes(usize::from(size))
+    }
+}
+
+/// Sends `command` to the TPM, and returns a parser of the parameters of
+/// the response, which is stored in `buffer`.
+fn submit<'a>(
+    tcg: &mut Tcg,
+    command: &mut Command,
+    buffer: &'a mut [u8; RESPONSE_SIZE],
+) -> Tpm2Result<Response<'a>> {
+    tcg.submit_command(command.finish(), buffer)?;
+    Response::new(buffer)
+}
+
+/// Fills `buffer` with random bytes from the TPM (`TPM2_GetRandom`).
+///
+/// # Errors
+///
+/// * [`Error::MalformedResponse`]: the TPM returned no random bytes.
+/// * Errors of [`Tcg::submit_command`] and of the TPM.
+pub fn get_random(tcg: &mut Tcg, buffer: &mut [u8]) -> Tpm2Result<()> {
+    let mut filled = 0;
+    while filled < buffer.len() {
+        let requested =
+            GET_RANDOM_CHUNK_SIZE.min(u16::try_from(buffer.len() - filled).unwrap_or(u16::MAX));
+        let mut response = [0; RESPONSE_SIZE];
+        let random = submit(tcg, &mut get_random_command(requested), &mut response)?.sized()?;
+        if random.is_empty() || random.len() > buffer.len() - filled {
+            return Err(Error::MalformedResponse);
+        }
+        buffer[filled..filled + random.len()].copy_from_slice(random);
+        filled += random.len();
+    }
+    Ok(())
+}
+
+fn get_random_command(bytes_requested: u16) -> Command {
+    let mut command = Command::new(ST_NO_SESSIONS, CC_GET_RANDOM);
+    command.u16(bytes_requested);
+    command
+}
+
+/// Reads the value of PCR `pcr` in the bank `algorithm` (`TPM2_PCR_Read`).
+/// Returns `None` if the bank is not active.
+///
+/// [`Tcg::read_pcr`] is a shorthand that returns UEFI errors.
+///
+/// # Errors
+///
+/// * [`Error::Uefi`] with [`Status::INVALID_PARAMETER`]: `pcr` is greater
+///   than 23.
+/// * Errors of [`Tcg::submit_command`] and of the TPM.
+pub fn pcr_read(
+    tcg: &mut Tcg,
+    pcr: PcrIndex,
+    algorithm: AlgorithmId,
+) -> Tpm2Result<Option<PcrValue>> {
+    if pcr.0 >= PCR_COUNT {
+        return Err(Status::INVALID_PARAMETER.into());
+    }
+    let mut response = [0; RESPONSE_SIZE];
+    let response = submit(tcg, &mut pcr_read_command(pcr, algorithm), &mut response)?;
+    parse_pcr_read(response, algorithm)
+}
+
+fn pcr_read_command(pcr: PcrIndex, algorithm: AlgorithmId) -> Command {
+    let mut select = [0; 3];
+    select[usize_from_u32(pcr.0 / 8)] = 1 << (pcr.0 % 8);
+
+    let mut command = Command::new(ST_NO_SESSIONS, CC_PCR_READ);
+    // pcrSelectionIn: one selection of size 3.
+    command.u32(1);
+    command.u16(algorithm.0);
+    command.u8(3);
+    command.bytes(&select);
+    command
+}
+
+fn parse_pcr_read(
+    mut response: Response<'_>,
+    algorithm: AlgorithmId,
+) -> Tpm2Result<Option<PcrValue>> {
+    let _update_counter = response.u32()?;
+    // Skip pcrSelectionOut.
+    for _ in 0..response.u32()? {
+        let _hash = response.u16()?;
+        let select_size = response.u8()?;
+        response.bytes(usize::from(select_size))?;
+    }
+    if response.u32()? == 0 {
+        return Ok(None);
+    }
+    let digest = response.sized()?;
+    PcrValue::new(algorithm, digest)
+        .map(Some)
+        .ok_or(Error::MalformedResponse)
+}
+
+/// Reads the TPM property `property` (`TPM2_GetCapability` with
+/// `TPM_CAP_TPM_PROPERTIES`). Returns `None` if the TPM doesn't have the
+/// property.
+///
+/// # Errors
+///
+/// * Errors of [`Tcg::submit_command`] and of the TPM.
+pub fn get_tpm_property(tcg: &mut Tcg, property: TpmProperty) -> Tpm2Result<Option<u32>> {
+    let mut response = [0; RESPONSE_SIZE];
+    let response = submit(tcg, &mut get_tpm_property_command(property), &mut response)?;
+    parse_get_tpm_property(response, property)
+}
+
+fn get_tpm_property_command(property: TpmProperty) -> Command {
+    let mut command = Command::new(ST_NO_SESSIONS, CC_GET_CAPABILITY);
+    command.u32(CAP_TPM_PROPERTIES);
+    command.u32(property.0);
+    // propertyCount
+    command.u32(1);
+    command
+}
+
+fn parse_get_tpm_property(
+    mut response: Response<'_>,
+    property: TpmProperty,
+) -> Tpm2Result<Option<u32>> {
+    let _more_data = response.u8()?;
+    if response.u32()? != CAP_TPM_PROPERTIES {
+        return Err(Error::MalformedResponse);
+    }
+    for _ in 0..response.u32()? {
+        let tag = response.u32()?;
+        let value = response.u32()?;
+        // The TPM returns the next property if it doesn't have the
+        // requested one.
+        if tag == property.0 {
+            return Ok(Some(value));
+        }
+    }
+    Ok(None)
+}
+
+/// Reads the public area of the NV index `index` (`TPM2_NV_ReadPublic`).
+///
+/// # Errors
+///
+/// * [`Error::Tpm`] with a [`ResponseCode::HANDLE`] [base](ResponseCode::base):
+///   the index doesn't exist.
+/// * Errors of [`Tcg::submit_command`] and of the TPM.
+pub fn nv_read_public(tcg: &mut Tcg, index: u32) -> Tpm2Result<NvPublic> {
+    let mut response = [0; RESPONSE_SIZE];
+    let response = submit(tcg, &mut nv_read_public_command(index), &mut response)?;
+    parse_nv_read_public(response)
+}
+
+fn nv_read_public_command(index: u32) -> Command {
+    let mut command = Command::new(ST_NO_SESSIONS, CC_NV_READ_PUBLIC);
+    command.u32(index);
+    command
+}
+
+fn parse_nv_read_public(mut response: Response<'_>) -> Tpm2Result<NvPublic> {
+    let mut public = Response {
+        bytes: response.sized()?,
+    };
+    let index = public.u32()?;
+    let name_algorithm = AlgorithmId(public.u16()?);
+    let attributes = NvAttributes::from_bits_retain(public.u32()?);
+    let _auth_policy = public.sized()?;
+    let data_size = public.u16()?;
+    Ok(NvPublic {
+        index,
+        name_algorithm,
+        attributes,
+        data_size,
+    })
+}
+
+/// Reads data at `offset` of the NV index `index` into `buffer`
+/// (`TPM2_NV_Read`).
+///
+/// The index authorizes the read with its password, which must be empty,
+/// so the index must have the [`NvAttributes::AUTHREAD`] attribute.
+///
+/// # Errors
+///
+/// * [`Error::Uefi`] with [`Status::INVALID_PARAMETER`]: the end of the
+///   data is beyond the largest possible offset.
+/// * [`Error::Tpm`] with [`ResponseCode::NV_RANGE`]: the data is out of the
+///   range of the index.
+/// * Errors of [`Tcg::submit_command`] and of the TPM.
+pub fn nv_read(tcg: &mut Tcg, index: u32, offset: u16, buffer: &mut [u8]) -> Tpm2Result<()> {
+    let end = u16::try_from(buffer.len())
+        .ok()
+        .and_then(|len| offset.checked_add(len))
+        .ok_or(Status::INVALID_PARAMETER)?;
+
+    let mut position = offset;
+    for chunk in buffer.chunks_mut(usize::from(NV_READ_CHUNK_SIZE)) {
+        // The chunk size fits into `u16`, since the end does.
+        let size = u16::try_from(chunk.len()).unwrap();
+        let mut response = [0; RESPONSE_SIZE];
+        let response = submit(
+            tcg,
+            &mut nv_read_command(index, position, size),
+            &mut response,
+        )?;
+        let data = parse_nv_read(response)?;
+        if data.len() != chunk.len() {
+            return Err(Error::MalformedResponse);
+        }
+        chunk.copy_from_slice(data);
+        position += size;
+    }
+    debug_assert_eq!(position, end);
+    Ok(())
+}
+
+fn nv_read_command(index: u32, offset: u16, size: u16) -> Command {
+    let mut command = Command::new(ST_SESSIONS, CC_NV_READ);
+    // authHandle and nvIndex
+    command.u32(index);
+    command.u32(index);
+    command.password_session();
+    command.u16(size);
+    command.u16(offset);
+    command
+}
+
+fn parse_nv_read(mut response: Response<'_>) -> Tpm2Result<&[u8]> {
+    let _parameter_size = response.u32()?;
+    response.sized()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_response_code() {
+        assert!(ResponseCode::SUCCESS.is_success());
+        assert!(!ResponseCode::RETRY.is_success());
+        // TPM_RC_HANDLE for handle 1.
+        assert_eq!(ResponseCode(0x18b).base(), ResponseCode::HANDLE);
+        // TPM_RC_VALUE for parameter 2.
+        assert_eq!(ResponseCode(0x2c4).base(), ResponseCode::VALUE);
+        assert_eq!(ResponseCode::NV_RANGE.base(), ResponseCode::NV_RANGE);
+    }
+
+    #[test]
+    fn test_response_header() {
+        // TPM_RC_HANDLE for handle 1.
+        let bytes = [0x80, 0x01, 0x00, 0x00, 0x00, 0x0a, 0x00, 0x00, 0x01, 0x8b];
+        assert_eq!(
+            Response::new(&bytes).err(),
+            Some(Error::Tpm(ResponseCode(0x18b)))
+        );
+
+        // The size is larger than the response.
+        let bytes = [0x80, 0x01, 0x00, 0x00, 0x00, 0x10, 0x00, 0x00, 0x00, 0x00];
+        assert_eq!(Response::new(&bytes).err(), Some(Error::MalformedResponse));
+    }
+
+    #[test]
+    fn test_get_random() {
+        #[rustfmt::skip]
+        assert_eq!(get_random_command(16).finish(), [
+            0x80, 0x01,
+            0x00, 0x00, 0x00, 0x0c,
+            0x00, 0x00, 0x01, 0x7b,
+            0x00, 0x10,
+        ]);
+    }
+
+    #[test]
+    fn test_pcr_read() {
+        #[rustfmt::skip]
+        assert_eq!(pcr_read_command(PcrIndex(9), AlgorithmId::SHA256).finish(), [
+            0x80, 0x01,
+            0x00, 0x00, 0x00, 0x14,
+            0x00, 0x00, 0x01, 0x7e,
+            0x00, 0x00, 0x00, 0x01,
+            0x00, 0x0b,
+            0x03,
+            0x00, 0x02, 0x00,
+        ]);
+
+        #[rustfmt::skip]
+        let mut bytes = [
+            // Header
+            0x80, 0x01,
+            0x00, 0x00, 0x00, 0x22,
+            0x00, 0x00, 0x00, 0x00,
+            // Update counter
+            0x00, 0x00, 0x00, 0x2a,
+            // pcrSelectionOut
+            0x00, 0x00, 0x00, 0x01,
+            0x00, 0x04,
+            0x03,
+            0x00, 0x02, 0x00,
+            // pcrValues
+            0x00, 0x00, 0x00, 0x01,
+            0x00, 0x04,
+            0x01, 0x02, 0x03, 0x04,
+        ];
+        let value = parse_pcr_read(Response::new(&bytes).unwrap(), AlgorithmId::SHA1)
+            .unwrap()
+            .unwrap();
+        assert_eq!(value.algorithm(), AlgorithmId::SHA1);
+        assert_eq!(*value, [1, 2, 3, 4]);
+
+        // The bank is not active, so no digest is returned.
+        bytes[24..28].copy_from_slice(&[0; 4]);
+        assert_eq!(
+            parse_pcr_read(Response::new(&bytes).unwrap(), AlgorithmId::SHA1),
+            Ok(None)
+        );
+
+        // Truncated response.
+        bytes[5] = 20;
+        assert_eq!(
+            parse_pcr_read(Response::new(&bytes[..20]).unwrap(), AlgorithmId::SHA1),
+            Err(Error::MalformedResponse)
+        );
+    }
+
+    #[test]
+    fn test_get_tpm_property() {
+        #[rustfmt::skip]
+        assert_eq!(get_tpm_property_command(TpmProperty::MANUFACTURER).finish(), [
+            0x80, 0x01,
+            0x00, 0x00, 0x00, 0x16,
+            0x00, 0x00, 0x01, 0x7a,
+            0x00, 0x00, 0x00, 0x06,
+            0x00, 0x00, 0x01, 0x05,
+            0x00, 0x00, 0x00, 0x01,
+        ]);
+
+        #[rustfmt::skip]
+        let bytes = [
+            // Header
+            0x80, 0x01,
+            0x00, 0x00, 0x00, 0x1b,
+            0x00, 0x00, 0x00, 0x00,
+            // moreData
+            0x01,
+            // capability
+            0x00, 0x00, 0x00, 0x06,
+            // count
+            0x00, 0x00, 0x00, 0x01,
+            // TPM_PT_MANUFACTURER: "IBM\0"
+            0x00, 0x00, 0x01, 0x05,
+            0x49, 0x42, 0x4d, 0x00,
+        ];
+        let response = Response::new(&bytes).unwrap();
+        assert_eq!(
+            parse_get_tpm_property(response, TpmProperty::MANUFACTURER),
+            Ok(Some(0x4942_4d00))
+        );
+        let response = Response::new(&bytes).unwrap();
+        assert_eq!(
+            parse_get_tpm_property(response, TpmProperty::VENDOR_STRING_1),
+            Ok(None)
+        );
+    }
+
+    #[test]
+    fn test_nv_read_public() {
+        #[rustfmt::skip]
+        let bytes = [
+            // Header
+            0x80, 0x01,
+            0x00, 0x00, 0x00, 0x26,
+            0x00, 0x00, 0x00, 0x00,
+            // nvPublic
+            0x00, 0x0e,
+            0x01, 0xc0, 0x00, 0x02,
+            0x00, 0x0b,
+            0x20, 0x04, 0x00, 0x02,
+            0x00, 0x00,
+            0x03, 0x00,
+            // nvName
+            0x00, 0x0a,
+            0x00, 0x0b, 0x01, 0x02, 0x03, 0x04, 0x05, 0x06, 0x07, 0x08,
+        ];
+        assert_eq!(
+            parse_nv_read_public(Response::new(&bytes).unwrap()),
+            Ok(NvPublic {
+                index: 0x01c0_0002,
+                name_algorithm: AlgorithmId::SHA256,
+                attributes: NvAttributes::OWNERWRITE
+                    | NvAttributes::AUTHREAD
+                    | NvAttributes::WRITTEN,
+                data_size: 0x300,
+            })
+        );
+    }
+
+    #[test]
+    fn test_nv_read() {
+        #[rustfmt::skip]
+        assert_eq!(nv_read_command(0x01c0_0002, 0x10, 0x20).finish(), [
+            0x80, 0x02,
+            0x00, 0x00, 0x00, 0x23,
+            0x00, 0x00, 0x01, 0x4e,
+            // authHandle, nvIndex
+            0x01, 0xc0, 0x00, 0x02,
+            0x01, 0xc0, 0x00, 0x02,
+            // Authorization area
+            0x00, 0x00, 0x00, 0x09,
+            0x40, 0x00, 0x00, 0x09,
+            0x00, 0x00,
+            0x00,
+            0x00, 0x00,
+            // size, offset
+            0x00, 0x20,
+            0x00, 0x10,
+        ]);
+
+        #[rustfmt::skip]
+        let bytes = [
+            // Header
+            0x80, 0x02,
+            0x00, 0x00, 0x00, 0x18,
+            0x00, 0x00, 0x00, 0x00,
+            // parameterSize
+            0x00, 0x00, 0x00, 0x05,
+            // data
+            0x00, 0x03,
+            0x01, 0x02, 0x03,
+            // Authorization area
+            0x00, 0x00, 0x01, 0x00, 0x00,
+        ];
+        assert_eq!(
+            parse_nv_read(Response::new(&bytes).unwrap()),
+            Ok([1, 2, 3].as_slice())
+        );
+    }
+}
@@ -10,7 +10,7 @@
 //! [TCG]: https://trustedcomputinggroup.org/
 //! [TPM]: https://en.wikipedia.org/wiki/Trusted_Platform_Module
 
-use super::{AlgorithmId, EventType, HashAlgorithm, PcrIndex};
+use super::{AlgorithmId, EventData, EventType, HashAlgorithm, PcrIndex};
 use crate::data_types::{Align, PhysicalAddress};
 use crate::proto::unsafe_protocol;
 use crate::util::{ptr_write_unaligned_and_add, usize_from_u32};
@@ -196,6 +196,14 @@ impl PcrEvent {
         &self.event_data
     }
 
+    /// Event data decoded according to the [`event_type`].
+    ///
+    /// [`event_type`]: Self::event_type
+    #[must_use]
+    pub fn data(&self) -> EventData<'_> {
+        EventData::parse(self.event_type(), self.event_data())
+    }
+
     /// SHA-1 digest of the data hashed for this event.
     #[must_use]
     pub const fn digest(&self) -> Sha1Digest {
@@ -283,6 +291,26 @@ impl EventLog<'_> {
         }
     }
 
+    /// Computes the value that PCR `pcr` has after the measurements of the
+    /// events in the log, so it can be compared with the value read from
+    /// the TPM. `hash` must compute the SHA-1 digest of its input.
+    ///
+    /// The value is only meaningful if the log is not
+    /// [truncated](Self::is_truncated).
+    #[must_use]
+    pub fn replay_pcr(&self, pcr: PcrIndex, hash: impl FnMut(&[u8]) -> Sha1Digest) -> Sha1Digest {
+        let events = self.iter().map(|event| {
+            (
+                event.pcr_index(),
+                event.event_type(),
+                event.event_data(),
+                Some(event.digest.as_slice()),
+            )
+        });
+        // Every event has a digest of the right size.
+        super::replay_pcr(pcr, events, hash).unwrap()
+    }
+
     /// If true, the event log is missing one or more entries because
     /// additional events would have exceeded the space allocated for
     /// the log.
@@ -12,13 +12,16 @@
 //! [TCG]: https://trustedcomputinggroup.org/
 //! [TPM]: https://en.wikipedia.org/wiki/Trusted_Platform_Module
 
-use super::{AlgorithmId, EventType, HashAlgorithm, PcrIndex, v1};
+use super::{
+    AlgorithmId, EventData, EventType, HashAlgorithm, MAX_DIGEST_SIZE, PcrIndex, tpm2, v1,
+};
 use crate::data_types::{Align, PhysicalAddress, UnalignedSlice};
 use crate::proto::unsafe_protocol;
 use crate::util::{ptr_write_unaligned_and_add, usize_from_u32};
 use crate::{Error, Result, Status, StatusExt};
 use core::fmt::{self, Debug, Formatter};
 use core::marker::PhantomData;
+use core::ops::Deref;
 use core::{ptr, slice};
 use ptr_meta::Pointee;
 use uefi_raw::protocol::tcg::v2::{Tcg2EventHeader as EventHeader, Tcg2Protocol};
@@ -370,6 +373,45 @@ impl EventLog<'_> {
         }
     }
 
+    /// Computes the value that PCR `pcr` of the bank `algorithm` has after
+    /// the measurements of the events in the log, so it can be compared
+    /// with the value read from the TPM. `hash` must compute the digest of
+    /// its input with `algorithm`; `N` is the size of the digest.
+    ///
+    /// Returns `None` if an event of the PCR has no digest of size `N` for
+    /// `algorithm`. The value is only meaningful if the log is not
+    /// [truncated](Self::is_truncated).
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use uefi::proto::tcg::v2::EventLog;
+    /// use uefi::proto::tcg::{AlgorithmId, PcrIndex};
+    ///
+    /// // `sha256` could be provided by the `sha2` crate, for example.
+    /// fn expected_pcr7(log: &EventLog, sha256: fn(&[u8]) -> [u8; 32]) -> Option<[u8; 32]> {
+    ///     log.replay_pcr(PcrIndex(7), AlgorithmId::SHA256, sha256)
+    /// }
+    /// ```
+    pub fn replay_pcr<const N: usize>(
+        &self,
+        pcr: PcrIndex,
+        algorithm: AlgorithmId,
+        hash: impl FnMut(&[u8]) -> [u8; N],
+    ) -> Option<[u8; N]> {
+        let events = self.iter().map(|event| {
+            let digests = PcrEventDigests {
+                data: event.digests,
+                algorithm_digest_sizes: event.algorithm_digest_sizes,
+            };
+            let digest = digests
+                .into_iter()
+                .find_map(|(alg, digest)| (alg == algorithm).then_some(digest));
+            (event.pcr_index, event.event_type, event.event_data, digest)
+        });
+        super::replay_pcr(pcr, events, hash)
+    }
+
     /// Whether the event log is truncated due to not enough space in the log to
     /// contain some events.
     #[must_use]
@@ -511,6 +553,14 @@ impl<'a> PcrEvent<'a> {
         self.event_data
     }
 
+    /// Event data decoded according to the [`event_type`].
+    ///
+    /// [`event_type`]: Self::event_type
+    #[must_use]
+    pub fn data(&self) -> EventData<'_> {
+        EventData::parse(self.event_type, self.event_data)
+    }
+
     /// Digests of the data hashed for this event.
     #[must_use]
     pub fn digests(&self) -> PcrEventDigests<'_> {
@@ -556,6 +606,60 @@ impl<'a> Iterator for EventLogIter<'a> {
     }
 }
 
+/// Value of a PCR, as returned by [`Tcg::read_pcr`].
+///
+/// Dereferences to the bytes of the value, whose size is the digest size
+/// of the [`algorithm`](Self::algorithm).
+#[derive(Clone, Copy, Eq, PartialEq)]
+pub struct PcrValue {
+    algorithm: AlgorithmId,
+    len: usize,
+    bytes: [u8; MAX_DIGEST_SIZE],
+}
+
+impl PcrValue {
+    /// Hash algorithm of the PCR bank.
+    #[must_use]
+    pub const fn algorithm(&self) -> AlgorithmId {
+        self.algorithm
+    }
+
+    /// Creates a value from its `digest`. Returns `None` if the digest is
+    /// too large.
+    pub(super) fn new(algorithm: AlgorithmId, digest: &[u8]) -> Option<Self> {
+        let mut bytes = [0; MAX_DIGEST_SIZE];
+        bytes.get_mut(..digest.len())?.copy_from_slice(digest);
+        Some(Self {
+            algorithm,
+            len: digest.len(),
+            bytes,
+        })
+    }
+
+    /// Bytes of the value.
+    #[must_use]
+    pub fn as_bytes(&self) -> &[u8] {
+        &self.bytes[..self.len]
+    }
+}
+
+impl Deref for PcrValue {
+    type Target = [u8];
+
+    fn deref(&self) -> &[u8] {
+        self.as_bytes()
+    }
+}
+
+impl Debug for PcrValue {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.debug_struct("PcrValue")
+            .field("algorithm", &self.algorithm)
+            .field("bytes", &self.as_bytes())
+            .finish()
+    }
+}
+
 /// Protocol for interacting with TPM devices.
 ///
 /// This protocol can be used for interacting with older TPM 1.1/1.2
@@ -692,6 +796,48 @@ impl Tcg {
         }
     }
 
+    /// Reads the value of PCR `pcr` in the bank `algorithm`, using the
+    /// `TPM2_PCR_Read` command.
+    ///
+    /// # Errors
+    ///
+    /// * [`Status::INVALID_PARAMETER`]: `pcr` is greater than 23.
+    /// * [`Status::UNSUPPORTED`]: the bank `algorithm` is not active.
+    /// * [`Status::DEVICE_ERROR`]: the TPM failed the command.
+    /// * Errors of [`submit_command`](Self::submit_command).
+    pub fn read_pcr(&mut self, pcr: PcrIndex, algorithm: AlgorithmId) -> Result<PcrValue> {
+        match tpm2::pcr_read(self, pcr, algorithm) {
+            Ok(Some(value)) => Ok(value),
+            Ok(None) => Err(Status::UNSUPPORTED.into()),
+            Err(tpm2::Error::Uefi(err)) => Err(err),
+            Err(_) => Err(Status::DEVICE_ERROR.into()),
+        }
+    }
+
+    /// Measures `data` into PCR `pcr`: the data is hashed with the
+    /// algorithms of all active banks, the PCR is extended with the
+    /// digests, and an event is added to the log.
+    ///
+    /// The `description` is stored as the data of the event, e.g. an ASCII
+    /// string for [`EventType::IPL`] or [`EventType::EFI_ACTION`]. It is not
+    /// measured itself.
+    ///
+    /// # Errors
+    ///
+    /// * [`Status::INVALID_PARAMETER`]: `description` is too large.
+    /// * Errors of [`hash_log_extend_event`](Self::hash_log_extend_event).
+    #[cfg(feature = "alloc")]
+    pub fn measure(
+        &mut self,
+        pcr: PcrIndex,
+        event_type: EventType,
+        description: &[u8],
+        data: &[u8],
+    ) -> Result {
+        let event = PcrEventInputs::new_in_box(pcr, event_type, description)?;
+        self.hash_log_extend_event(HashLogExtendEventFlags::empty(), data, &event)
+    }
+
     /// Get a bitmap of the active PCR banks. Each bank corresponds to a hash
     /// algorithm.
     pub fn get_active_pcr_banks(&mut self) -> Result<HashAlgorithm> {
@@ -1045,6 +1191,44 @@ mod tests {
             0x00, 0x00, 0x0e, 0x00, 0x00, 0x00, 0x00, 0x00,
         ]);
 
+        assert!(matches!(
+            entry.data(),
+            EventData::FirmwareBlob {
+                base: 0x820000,
+                length: 0xe0000
+            }
+        ));
+
         assert!(iter.next().is_none());
+
+        // Replay PCR 0 with a fake hash that records its inputs.
+        let mut inputs = Vec::new();
+        let value = log
+            .replay_pcr(PcrIndex(0), AlgorithmId::SHA1, |data| {
+                inputs.push(data.to_vec());
+                [u8::try_from(inputs.len()).unwrap(); 20]
+            })
+            .unwrap();
+        assert_eq!(value, [2; 20]);
+        let mut iter = log.iter();
+        let digest = |event: PcrEvent| event.digests().into_iter().next().unwrap().1.to_vec();
+        assert_eq!(
+            inputs[0],
+            [[0; 20].to_vec(), digest(iter.next().unwrap())].concat()
+        );
+        assert_eq!(
+            inputs[1],
+            [[1; 20].to_vec(), digest(iter.next().unwrap())].concat()
+        );
+
+        // No events are measured into PCR 1, and there are no SM3 digests.
+        assert_eq!(
+            log.replay_pcr(PcrIndex(1), AlgorithmId::SHA1, |_| [1; 20]),
+            Some([0; 20])
+        );
+        assert_eq!(
+            log.replay_pcr(PcrIndex(0), AlgorithmId::SM3_256, |_| [1; 32]),
+            None
+        );
     }
 }
@@ -4,16 +4,31 @@
 
 use super::Status;
 use core::fmt::{Debug, Display};
+#[cfg(feature = "alloc")]
+use {alloc::borrow::Cow, alloc::boxed::Box, alloc::vec::Vec};
 
 /// An UEFI-related error with optionally additional payload data. The error
 /// kind is encoded in the `status` field (see [`Status`]). Additional payload
 /// may be inside the `data` field.
-#[derive(Clone, Debug, PartialEq, Eq)]
+///
+/// With the `alloc` feature, an error can also carry a chain of messages
+/// that describe what was being done when it occurred, see
+/// [`Error::context`]. They are printed before the status when the error is
+/// displayed.
+#[derive(Clone, PartialEq, Eq)]
 pub struct Error<Data: Debug = ()> {
     status: Status,
     data: Data,
+    #[cfg(feature = "alloc")]
+    context: Option<Box<Context>>,
 }
 
+/// Context messages of an [`Error`], innermost first. Boxed, so that errors
+/// without context stay small.
+#[cfg(feature = "alloc")]
+#[derive(Clone, Debug, PartialEq, Eq)]
+struct Context(Vec<Cow<'static, str>>);
+
 impl<Data: Debug> Error<Data> {
     /// Create an `Error`.
     ///
@@ -22,7 +37,12 @@ impl<Data: Debug> Error<Data> {
     /// Panics if `status` is [`Status::SUCCESS`].
     pub const fn new(status: Status, data: Data) -> Self {
         assert!(!matches!(status, Status::SUCCESS));
-        Self { status, data }
+        Self {
+            status,
+            data,
+            #[cfg(feature = "alloc")]
+            context: None,
+        }
     }
 
     /// Get error `Status`.
@@ -40,6 +60,56 @@ impl<Data: Debug> Error<Data> {
     pub fn split(self) -> (Status, Data) {
         (self.status, self.data)
     }
+
+    /// Adds a message that describes what was being done when the error
+    /// occurred, e.g. `"opening kernel image"`. The status and data are
+    /// kept.
+    ///
+    /// Messages added later describe the outer operations; they are printed
+    /// first when the error is displayed:
+    ///
+    /// ```
+    /// use uefi::{Error, Status};
+    ///
+    /// let err = Error::from(Status::NOT_FOUND)
+    ///     .context("reading \\EFI\\linux\\vmlinuz")
+    ///     .context("opening kernel image");
+    /// assert_eq!(err.status(), Status::NOT_FOUND);
+    /// assert_eq!(
+    ///     err.to_string(),
+    ///     "opening kernel image: reading \\EFI\\linux\\vmlinuz: UEFI Error NOT_FOUND: ()"
+    /// );
+    /// ```
+    #[cfg(feature = "alloc")]
+    #[must_use]
+    pub fn context(mut self, message: impl Into<Cow<'static, str>>) -> Self {
+        self.context
+            .get_or_insert_with(|| Box::new(Context(Vec::new())))
+            .0
+            .push(message.into());
+        self
+    }
+
+    /// Returns the messages added with [`context`], outermost first.
+    ///
+    /// [`context`]: Self::context
+    #[cfg(feature = "alloc")]
+    pub fn context_messages(&self) -> impl DoubleEndedIterator<Item = &str> {
+        self.context
+            .iter()
+            .flat_map(|context| context.0.iter().rev())
+            .map(|message| &**message)
+    }
+
+    /// Replaces the data of the error with `()`, keeping the context.
+    pub(crate) fn into_err_without_payload(self) -> Error<()> {
+        Error {
+            status: self.status,
+            data: (),
+            #[cfg(feature = "alloc")]
+            context: self.context,
+        }
+    }
 }
 
 // Errors without error data can be autogenerated from statuses
@@ -50,8 +120,26 @@ impl From<Status> for Error<()> {
     }
 }
 
+impl<Data: Debug> Debug for Error<Data> {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        let mut debug = f.debug_struct("Error");
+        debug
+            .field("status", &self.status)
+            .field("data", &self.data);
+        #[cfg(feature = "alloc")]
+        if self.context.is_some() {
+            debug.field("context", &self.context_messages().collect::<Vec<_>>());
+        }
+        debug.finish()
+    }
+}
+
 impl<Data: Debug> Display for Error<Data> {
     fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        #[cfg(feature = "alloc")]
+        for message in self.context_messages() {
+            write!(f, "{message}: ")?;
+        }
         write!(f, "UEFI Error {}: {:?}", self.status(), self.data())
     }
 }
@@ -62,12 +150,143 @@ impl<Data: Debug> Error<Data> {
     /// - to retain the erroneous status code,
     /// - do not care about the payload, and
     /// - refrain from generic type complexity in a higher API level.
-    pub const fn to_err_without_payload(&self) -> Error<()> {
+    ///
+    /// The context messages are kept.
+    #[allow(clippy::missing_const_for_fn)]
+    #[must_use]
+    pub fn to_err_without_payload(&self) -> Error<()> {
         Error {
             status: self.status,
             data: (),
+            #[cfg(feature = "alloc")]
+            context: self.context.clone(),
         }
     }
 }
 
 impl<Data: Debug> core::error::Error for Error<Data> {}
+
+#[cfg(feature = "embedded-io")]
+impl<Data: Debug> embedded_io::Error for Error<Data> {
+    fn kind(&self) -> embedded_io::ErrorKind {
+        use embedded_io::ErrorKind;
+
+        match self.status() {
+            Status::BAD_BUFFER_SIZE | Status::INVALID_LANGUAGE => ErrorKind::InvalidData,
+            Status::ABORTED => ErrorKind::ConnectionAborted,
+            Status::ACCESS_DENIED | Status::SECURITY_VIOLATION => ErrorKind::PermissionDenied,
+            Status::CONNECTION_FIN => ErrorKind::NotConnected,
+            Status::CONNECTION_REFUSED => ErrorKind::ConnectionRefused,
+            Status::CONNECTION_RESET => ErrorKind::ConnectionReset,
+            Status::INVALID_PARAMETER => ErrorKind::InvalidInput,
+            Status::IP_ADDRESS_CONFLICT => ErrorKind::AddrInUse,
+            Status::NOT_FOUND => ErrorKind::NotFound,
+            Status::OUT_OF_RESOURCES => ErrorKind::OutOfMemory,
+            Status::TIMEOUT => ErrorKind::TimedOut,
+            Status::UNSUPPORTED => ErrorKind::Unsupported,
+            _ => ErrorKind::Other,
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl<Data: Debug + Send + Sync + 'static> From<Error<Data>> for std::io::Error {
+    /// Converts the error to an I/O error whose kind corresponds to the
+    /// status, keeping the original error as its source.
+    fn from(err: Error<Data>) -> Self {
+        Self::new(io_error_kind(err.status()), err)
+    }
+}
+
+/// Returns the kind of I/O error that corresponds to `status`. The mapping is
+/// the same as the one of the `std` implementation for UEFI, so that errors
+/// look the same no matter which API returned them.
+#[cfg(feature = "std")]
+const fn io_error_kind(status: Status) -> std::io::ErrorKind {
+    use std::io::ErrorKind;
+
+    match status {
+        Status::BAD_BUFFER_SIZE | Status::INVALID_LANGUAGE => ErrorKind::InvalidData,
+        Status::ABORTED => ErrorKind::ConnectionAborted,
+        Status::ACCESS_DENIED | Status::SECURITY_VIOLATION => ErrorKind::PermissionDenied,
+        Status::BUFFER_TOO_SMALL => ErrorKind::FileTooLarge,
+        Status::CONNECTION_REFUSED => ErrorKind::ConnectionRefused,
+        Status::CONNECTION_RESET => ErrorKind::ConnectionReset,
+        Status::END_OF_FILE => ErrorKind::UnexpectedEof,
+        Status::INVALID_PARAMETER => ErrorKind::InvalidInput,
+        Status::IP_ADDRESS_CONFLICT => ErrorKind::AddrInUse,
+        Status::NO_RESPONSE => ErrorKind::HostUnreachable,
+        Status::NOT_FOUND => ErrorKind::NotFound,
+        Status::NOT_READY => ErrorKind::ResourceBusy,
+        Status::OUT_OF_RESOURCES => ErrorKind::OutOfMemory,
+        Status::TIMEOUT => ErrorKind::TimedOut,
+        Status::UNSUPPORTED => ErrorKind::Unsupported,
+        Status::VOLUME_FULL => ErrorKind::StorageFull,
+        Status::WRITE_PROTECTED => ErrorKind::ReadOnlyFilesystem,
+        _ => ErrorKind::Other,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    #[cfg(feature = "embedded-io")]
+    fn test_embedded_io_error_kind() {
+        use embedded_io::{Error as _, ErrorKind};
+
+        assert_eq!(Error::from(Status::TIMEOUT).kind(), ErrorKind::TimedOut);
+        assert_eq!(
+            Error::new(Status::CONNECTION_RESET, 1).kind(),
+            ErrorKind::ConnectionReset
+        );
+        assert_eq!(Error::from(Status::DEVICE_ERROR).kind(), ErrorKind::Other);
+    }
+
+    #[test]
+    #[cfg(feature = "alloc")]
+    fn test_context() {
+        use crate::ResultExt;
+        use alloc::format;
+        use alloc::string::ToString;
+
+        let result: crate::Result<(), u8> = Err(Error::new(Status::NOT_FOUND, 3));
+        let err = result
+            .context("inner")
+            .with_context(|| format!("outer {}", 1))
+            .unwrap_err();
+        assert_eq!(err.status(), Status::NOT_FOUND);
+        assert_eq!(*err.data(), 3);
+        assert!(err.context_messages().eq(["outer 1", "inner"]));
+        assert_eq!(err.to_string(), "outer 1: inner: UEFI Error NOT_FOUND: 3");
+        assert_eq!(
+            format!("{err:?}"),
+            r#"Error { status: NOT_FOUND, data: 3, context: ["outer 1", "inner"] }"#
+        );
+
+        // The context is kept when the data is discarded.
+        let err = Err::<(), _>(err).discard_errdata().unwrap_err();
+        assert!(err.context_messages().eq(["outer 1", "inner"]));
+
+        let err = Error::from(Status::ABORTED);
+        assert_eq!(format!("{err:?}"), "Error { status: ABORTED, data: () }");
+    }
+
+    #[test]
+    #[cfg(feature = "std")]
+    fn test_into_io_error() {
+        use std::io::{self, ErrorKind};
+
+        let err = io::Error::from(Error::from(Status::NOT_FOUND));
+        assert_eq!(err.kind(), ErrorKind::NotFound);
+        let source = err.into_inner().unwrap().downcast::<Error>().unwrap();
+        assert_eq!(source.status(), Status::NOT_FOUND);
+
+        let err = io::Error::from(Error::new(Status::WRITE_PROTECTED, 5_u32));
+        assert_eq!(err.kind(), ErrorKind::ReadOnlyFilesystem);
+
+        let err = io::Error::from(Error::from(Status::DEVICE_ERROR));
+        assert_eq!(err.kind(), ErrorKind::Other);
+    }
+}
@@ -2,6 +2,8 @@
 
 //! Facilities for dealing with UEFI operation results.
 
+#[cfg(feature = "alloc")]
+use alloc::borrow::Cow;
 use core::fmt::Debug;
 
 /// The error type that we use, essentially a status code + optional additional data
@@ -12,6 +14,10 @@ pub use error::Error;
 mod status;
 pub use status::{Status, StatusExt};
 
+/// Configurable handling of warning statuses
+mod warning;
+pub use warning::{Warned, WarningPolicy};
+
 /// Return type of most UEFI functions. Both success and error payloads are optional.
 ///
 /// Almost all UEFI operations provide a status code as an output which
@@ -65,6 +71,65 @@ pub trait ResultExt<Output, ErrData: Debug> {
     fn handle_warning<O>(self, op: O) -> Result<Output, ErrData>
     where
         O: FnOnce(Error<ErrData>) -> Result<Output, ErrData>;
+
+    /// Handles a warning status according to `policy`.
+    ///
+    /// With [`WarningPolicy::Error`], a warning stays an error. With the
+    /// other policies, it's returned as `Ok(Warned::Warning(status))`; the
+    /// data of the error is dropped. Errors are returned unchanged, and
+    /// success as `Ok(Warned::Success(output))`.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use uefi::{cstr16, system, ResultExt, Status, Warned, WarningPolicy};
+    ///
+    /// # fn example() -> uefi::Result {
+    /// let mut missing_glyphs = false;
+    /// let mut on_warning = |status| missing_glyphs |= status == Status::WARN_UNKNOWN_GLYPH;
+    /// system::with_stdout(|stdout| {
+    ///     stdout
+    ///         .output_string(cstr16!("✓ done\r\n"))
+    ///         .with_warning_policy(WarningPolicy::Callback(&mut on_warning))
+    /// })?;
+    /// if missing_glyphs {
+    ///     system::with_stdout(|stdout| stdout.output_string(cstr16!("OK\r\n")))?;
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    fn with_warning_policy(self, policy: WarningPolicy<'_>) -> Result<Warned<Output>, ErrData>;
+
+    /// Adds a message to the error that describes what was being done, see
+    /// [`Error::context`].
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use uefi::{boot, cstr16, ResultExt};
+    /// use uefi::proto::media::file::{File, FileAttribute, FileMode};
+    ///
+    /// # fn example() -> uefi::Result {
+    /// let mut fs = boot::get_image_file_system(boot::image_handle())
+    ///     .context("opening the boot volume")?;
+    /// let mut root = fs.open_volume().context("opening the root directory")?;
+    /// root.open(cstr16!("kernel.efi"), FileMode::Read, FileAttribute::empty())
+    ///     .context("opening kernel image")?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    #[cfg(feature = "alloc")]
+    fn context(self, message: impl Into<Cow<'static, str>>) -> Result<Output, ErrData>;
+
+    /// Like [`context`], but the message is only created if the result is
+    /// an error.
+    ///
+    /// [`context`]: Self::context
+    #[cfg(feature = "alloc")]
+    fn with_context<C, F>(self, f: F) -> Result<Output, ErrData>
+    where
+        C: Into<Cow<'static, str>>,
+        F: FnOnce() -> C;
 }
 
 impl<Output, ErrData: Debug> ResultExt<Output, ErrData> for Result<Output, ErrData> {
@@ -78,7 +143,7 @@ impl<Output, ErrData: Debug> ResultExt<Output, ErrData> for Result<Output, ErrDa
     fn discard_errdata(self) -> Result<Output> {
         match self {
             Ok(o) => Ok(o),
-            Err(e) => Err(e.status().into()),
+            Err(e) => Err(e.into_err_without_payload()),
         }
     }
 
@@ -97,4 +162,68 @@ impl<Output, ErrData: Debug> ResultExt<Output, ErrData> for Result<Output, ErrDa
             }
         }
     }
+
+    fn with_warning_policy(self, policy: WarningPolicy<'_>) -> Result<Warned<Output>, ErrData> {
+        match self {
+            Ok(output) => Ok(Warned::Success(output)),
+            Err(err) if err.status().is_warning() => match policy {
+                WarningPolicy::Error => Err(err),
+                WarningPolicy::Allow => Ok(Warned::Warning(err.status())),
+                WarningPolicy::Callback(callback) => {
+                    callback(err.status());
+                    Ok(Warned::Warning(err.status()))
+                }
+            },
+            Err(err) => Err(err),
+        }
+    }
+
+    #[cfg(feature = "alloc")]
+    fn context(self, message: impl Into<Cow<'static, str>>) -> Self {
+        self.map_err(|err| err.context(message))
+    }
+
+    #[cfg(feature = "alloc")]
+    fn with_context<C, F>(self, f: F) -> Self
+    where
+        C: Into<Cow<'static, str>>,
+        F: FnOnce() -> C,
+    {
+        self.map_err(|err| err.context(f()))
+    }
+}
+
+/// Return type of an entry point marked with [`entry`].
+///
+/// The entry point can return either a [`Status`] or a [`Result`], which is
+/// converted to the status returned to the firmware.
+///
+/// [`entry`]: crate::entry
+#[diagnostic::on_unimplemented(
+    message = "entry function has invalid return type `{Self}`",
+    label = "the entry function must return `Status` or `uefi::Result`"
+)]
+pub trait Termination {
+    /// Converts the return value of the entry point to a [`Status`].
+    fn report(self) -> Status;
+}
+
+impl Termination for Status {
+    fn report(self) -> Status {
+        self
+    }
+}
+
+impl<ErrData: Debug> Termination for Result<(), ErrData> {
+    /// Returns [`Status::SUCCESS`] for `Ok`, and logs the error and returns
+    /// its status for `Err`.
+    fn report(self) -> Status {
+        match self {
+            Ok(()) => Status::SUCCESS,
+            Err(err) => {
+                log::error!("Entry point failed: {err}");
+                err.status()
+            }
+        }
+    }
 }
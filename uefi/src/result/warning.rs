@@ -0,0 +1,111 @@
+// SPDX-License-Identifier: MIT OR Apache-2.0
+
+use super::Status;
+use core::fmt::{self, Debug, Formatter};
+
+/// How a warning status is handled by [`ResultExt::with_warning_policy`].
+///
+/// All functions of this crate treat warnings as errors, which is what
+/// [`WarningPolicy::Error`] does too. The other policies let the caller
+/// accept the warnings of a specific call, e.g. [`Status::WARN_UNKNOWN_GLYPH`]
+/// when writing text that the console might not be able to render.
+///
+/// [`ResultExt::with_warning_policy`]: super::ResultExt::with_warning_policy
+#[derive(Default)]
+pub enum WarningPolicy<'a> {
+    /// Warnings are returned as errors.
+    #[default]
+    Error,
+    /// Warnings are returned as `Ok` with [`Warned::Warning`].
+    Allow,
+    /// Warnings are passed to the callback, e.g. to log or collect them,
+    /// and then returned as `Ok` with [`Warned::Warning`].
+    Callback(&'a mut dyn FnMut(Status)),
+}
+
+impl Debug for WarningPolicy<'_> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Error => f.write_str("Error"),
+            Self::Allow => f.write_str("Allow"),
+            Self::Callback(_) => f.write_str("Callback(..)"),
+        }
+    }
+}
+
+/// Output of an operation that succeeded, possibly with a warning.
+///
+/// Operations that complete with a warning don't return their output, so
+/// only the warning is available in that case.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+#[must_use]
+pub enum Warned<T> {
+    /// The operation succeeded without a warning.
+    Success(T),
+    /// The operation succeeded with the warning.
+    Warning(Status),
+}
+
+impl<T> Warned<T> {
+    /// Returns the output of the operation, or `None` if it completed with
+    /// a warning.
+    #[allow(clippy::missing_const_for_fn)]
+    pub fn success(self) -> Option<T> {
+        match self {
+            Self::Success(output) => Some(output),
+            Self::Warning(_) => None,
+        }
+    }
+
+    /// Returns the warning, or `None` if the operation succeeded without
+    /// one.
+    #[must_use]
+    pub const fn warning(&self) -> Option<Status> {
+        match self {
+            Self::Success(_) => None,
+            Self::Warning(status) => Some(*status),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Error, ResultExt, StatusExt};
+
+    #[test]
+    fn test_warning_policy() {
+        let warning = || Status::WARN_UNKNOWN_GLYPH.to_result_with_val(|| 1);
+        let error = || Status::NOT_FOUND.to_result_with_val(|| 1);
+
+        assert_eq!(
+            Status::SUCCESS
+                .to_result_with_val(|| 1)
+                .with_warning_policy(WarningPolicy::Error),
+            Ok(Warned::Success(1))
+        );
+        assert_eq!(
+            warning().with_warning_policy(WarningPolicy::Error),
+            Err(Error::from(Status::WARN_UNKNOWN_GLYPH))
+        );
+        assert_eq!(
+            warning().with_warning_policy(WarningPolicy::Allow),
+            Ok(Warned::Warning(Status::WARN_UNKNOWN_GLYPH))
+        );
+        assert_eq!(
+            error().with_warning_policy(WarningPolicy::Allow),
+            Err(Error::from(Status::NOT_FOUND))
+        );
+
+        let mut warnings = 0;
+        let mut count = |status: Status| {
+            assert_eq!(status, Status::WARN_UNKNOWN_GLYPH);
+            warnings += 1;
+        };
+        let result = warning().with_warning_policy(WarningPolicy::Callback(&mut count));
+        assert_eq!(result.unwrap().warning(), Some(Status::WARN_UNKNOWN_GLYPH));
+        let result = error().with_warning_policy(WarningPolicy::Callback(&mut count));
+        assert!(result.is_err());
+        assert_eq!(warnings, 1);
+    }
+}
@@ -162,8 +162,60 @@ pub fn get_variable<'buf>(
     }
 }
 
+/// Gets the contents and attributes of a variable whose size is exactly `N`
+/// bytes, such as `SecureBoot` or `Timeout`, without allocating.
+///
+/// # Example
+///
+/// ```no_run
+/// use uefi::cstr16;
+/// use uefi::runtime::{self, VariableVendor};
+///
+/// # fn example() -> uefi::Result<(), Option<usize>> {
+/// let (timeout, _) =
+///     runtime::get_variable_array::<2>(cstr16!("Timeout"), &VariableVendor::GLOBAL_VARIABLE)?;
+/// let timeout = u16::from_le_bytes(timeout);
+/// # Ok(())
+/// # }
+/// ```
+///
+/// # Errors
+///
+/// * [`Status::NOT_FOUND`]: variable was not found.
+/// * [`Status::BUFFER_TOO_SMALL`]: the variable is larger than `N` bytes.
+///   Its size will be returned in the error data.
+/// * [`Status::BAD_BUFFER_SIZE`]: the variable is smaller than `N` bytes.
+///   Its size will be returned in the error data.
+/// * [`Status::DEVICE_ERROR`]: variable could not be read due to a hardware error.
+/// * [`Status::SECURITY_VIOLATION`]: variable could not be read due to an
+///   authentication error.
+/// * [`Status::UNSUPPORTED`]: this platform does not support variable storage
+///   after exiting boot services.
+pub fn get_variable_array<const N: usize>(
+    name: &CStr16,
+    vendor: &VariableVendor,
+) -> Result<([u8; N], VariableAttributes), Option<usize>> {
+    let mut buf = [0; N];
+    let (data, attributes) = get_variable(name, vendor, &mut buf)?;
+    let size = data.len();
+    if size == N {
+        Ok((buf, attributes))
+    } else {
+        Err(Error::new(Status::BAD_BUFFER_SIZE, Some(size)))
+    }
+}
+
+/// Size of the stack buffer that [`get_variable_boxed`] tries first. Most
+/// variables, such as `BootOrder`, are much smaller.
+#[cfg(feature = "alloc")]
+const VARIABLE_STACK_BUFFER_SIZE: usize = 256;
+
 /// Gets the contents and attributes of a variable.
 ///
+/// The variable is first read into a buffer on the stack, which is large
+/// enough for most variables. Only if it is too small, another call is
+/// needed to read the variable into a heap buffer of the required size.
+///
 /// # Errors
 ///
 /// * [`Status::NOT_FOUND`]: variable was not found.
@@ -177,8 +229,20 @@ pub fn get_variable_boxed(
     name: &CStr16,
     vendor: &VariableVendor,
 ) -> Result<(Box<[u8]>, VariableAttributes)> {
+    let mut stack_buf = [0; VARIABLE_STACK_BUFFER_SIZE];
+    let mut required_size = match get_variable(name, vendor, &mut stack_buf) {
+        Ok((val, attr)) => return Ok((Box::from(&*val), attr)),
+        Err(err) if err.status() == Status::BUFFER_TOO_SMALL => *err.data(),
+        Err(err) => return Err(err.to_err_without_payload()),
+    };
+
     let mut out_attr = VariableAttributes::empty();
     let get_var = |buf| {
+        // The first call queries the required size, which is already known,
+        // so don't query it again.
+        if let Some(size) = required_size.take() {
+            return Err(Error::new(Status::BUFFER_TOO_SMALL, Some(size)));
+        }
         get_variable(name, vendor, buf).map(|(val, attr)| {
             // `make_boxed` expects only a DST value to be returned (`val` in
             // this case), so smuggle the `attr` value out via a separate
@@ -489,6 +553,95 @@ pub fn query_capsule_capabilities(capsule_header_array: &[&CapsuleHeader]) -> Re
     }
 }
 
+/// Increments the high 32 bits of the platform's monotonic counter and
+/// returns the new value.
+///
+/// The high count is stored in non-volatile storage, so it increases across
+/// resets. See [`MonotonicId`] for a helper that combines it with
+/// [`boot::get_next_monotonic_count`].
+///
+/// # Errors
+///
+/// * [`Status::DEVICE_ERROR`]: the device is not functioning properly.
+///
+/// [`boot::get_next_monotonic_count`]: crate::boot::get_next_monotonic_count
+pub fn get_next_high_monotonic_count() -> Result<u32> {
+    let rt = runtime_services_raw_panicking();
+    let rt = unsafe { rt.as_ref() };
+
+    let mut high_count = 0;
+    unsafe { (rt.get_next_high_monotonic_count)(&mut high_count) }.to_result_with_val(|| high_count)
+}
+
+/// Unique 64-bit ID taken from the platform's monotonic counter.
+///
+/// IDs only ever increase, including across resets, so they can be used to
+/// number log records or to order writes to a journal kept in variables.
+///
+/// While boot services are active, [`next`] returns the value of
+/// [`boot::get_next_monotonic_count`]. After exiting boot services, that
+/// counter is no longer available, so [`next`] increments the high count
+/// with [`get_next_high_monotonic_count`] instead and returns an ID whose
+/// lower 32 bits are zero. This ID is larger than all IDs returned before.
+///
+/// # Example
+///
+/// ```no_run
+/// use uefi::runtime::MonotonicId;
+///
+/// # fn example() -> uefi::Result {
+/// let first = MonotonicId::next()?;
+/// let second = MonotonicId::next()?;
+/// assert!(first < second);
+/// # Ok(())
+/// # }
+/// ```
+///
+/// [`boot::get_next_monotonic_count`]: crate::boot::get_next_monotonic_count
+/// [`next`]: Self::next
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[repr(transparent)]
+pub struct MonotonicId(pub u64);
+
+impl MonotonicId {
+    /// Returns the next ID.
+    ///
+    /// # Errors
+    ///
+    /// * [`Status::DEVICE_ERROR`]: the device is not functioning properly.
+    pub fn next() -> Result<Self> {
+        if crate::boot::are_boot_services_active() {
+            crate::boot::get_next_monotonic_count().map(Self)
+        } else {
+            get_next_high_monotonic_count().map(|high| Self(u64::from(high) << 32))
+        }
+    }
+
+    /// Returns the high count of the ID, which identifies the boot.
+    #[must_use]
+    pub const fn high(self) -> u32 {
+        (self.0 >> 32) as u32
+    }
+
+    /// Returns the low count of the ID, which increases within a boot.
+    #[must_use]
+    pub const fn low(self) -> u32 {
+        self.0 as u32
+    }
+}
+
+impl From<MonotonicId> for u64 {
+    fn from(id: MonotonicId) -> Self {
+        id.0
+    }
+}
+
+impl Display for MonotonicId {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        write!(f, "{:08x}:{:08x}", self.high(), self.low())
+    }
+}
+
 /// Resets the computer.
 ///
 /// See [`ResetType`] for details of the various reset types.
@@ -869,6 +1022,274 @@ impl TryFrom<&[u8]> for Time {
     }
 }
 
+/// Error returned from converting between [`Time`] and the date and time
+/// types of the `chrono` and `time` crates.
+#[cfg(any(feature = "chrono", feature = "time"))]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum TimeConversionError {
+    /// A field is outside its valid range, or the fields don't form a
+    /// valid date, e.g. February 30.
+    InvalidFields(TimeError),
+    /// The [`Time`] is a local time without time zone, which can't be
+    /// converted to a date and time with an offset.
+    UnspecifiedTimeZone,
+}
+
+#[cfg(any(feature = "chrono", feature = "time"))]
+impl Display for TimeConversionError {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        match self {
+            Self::InvalidFields(error) => write!(f, "{error}"),
+            Self::UnspecifiedTimeZone => write!(f, "the time has no time zone"),
+        }
+    }
+}
+
+#[cfg(any(feature = "chrono", feature = "time"))]
+impl core::error::Error for TimeConversionError {}
+
+#[cfg(any(feature = "chrono", feature = "time"))]
+impl Time {
+    /// Returns the offset from UTC in seconds, including the hour of
+    /// daylight saving time if [`Daylight::IN_DAYLIGHT`] is set, or `None`
+    /// for local time.
+    fn utc_offset_seconds(&self) -> Option<i32> {
+        let mut minutes = i32::from(self.time_zone()?);
+        if self.daylight().contains(Daylight::IN_DAYLIGHT) {
+            minutes += 60;
+        }
+        Some(minutes * 60)
+    }
+
+    /// Creates a `Time` from the fields of another date and time type. The
+    /// offset from UTC must be whole minutes.
+    #[allow(clippy::too_many_arguments)]
+    fn from_fields(
+        year: i32,
+        month: u8,
+        day: u8,
+        hour: u8,
+        minute: u8,
+        second: u8,
+        nanosecond: u32,
+        utc_offset_seconds: Option<i32>,
+    ) -> core::result::Result<Self, TimeConversionError> {
+        let year = u16::try_from(year).map_err(|_| {
+            TimeConversionError::InvalidFields(TimeError {
+                year: true,
+                ..Default::default()
+            })
+        })?;
+        let time_zone = match utc_offset_seconds {
+            None => None,
+            Some(seconds) if seconds % 60 == 0 => {
+                Some(i16::try_from(seconds / 60).map_err(|_| invalid_offset())?)
+            }
+            Some(_) => return Err(invalid_offset()),
+        };
+        Self::new(TimeParams {
+            year,
+            month,
+            day,
+            hour,
+            minute,
+            second,
+            nanosecond,
+            time_zone,
+            daylight: Daylight::empty(),
+        })
+        .map_err(TimeConversionError::InvalidFields)
+    }
+}
+
+/// Error of a `Time` whose fields are each valid, but don't form a valid
+/// date.
+#[cfg(any(feature = "chrono", feature = "time"))]
+fn invalid_date() -> TimeConversionError {
+    TimeConversionError::InvalidFields(TimeError {
+        day: true,
+        ..Default::default()
+    })
+}
+
+/// Error of an offset from UTC that the target type doesn't support.
+#[cfg(any(feature = "chrono", feature = "time"))]
+fn invalid_offset() -> TimeConversionError {
+    TimeConversionError::InvalidFields(TimeError {
+        timezone: true,
+        ..Default::default()
+    })
+}
+
+/// Converts the date and time, ignoring the time zone.
+#[cfg(feature = "chrono")]
+impl TryFrom<Time> for chrono::NaiveDateTime {
+    type Error = TimeConversionError;
+
+    fn try_from(time: Time) -> core::result::Result<Self, Self::Error> {
+        time.is_valid()
+            .map_err(TimeConversionError::InvalidFields)?;
+        let date = chrono::NaiveDate::from_ymd_opt(
+            time.year().into(),
+            time.month().into(),
+            time.day().into(),
+        )
+        .ok_or_else(invalid_date)?;
+        let clock = chrono::NaiveTime::from_hms_nano_opt(
+            time.hour().into(),
+            time.minute().into(),
+            time.second().into(),
+            time.nanosecond(),
+        )
+        .ok_or_else(invalid_date)?;
+        Ok(Self::new(date, clock))
+    }
+}
+
+/// Converts the date and time with its offset from UTC, which includes the
+/// hour of daylight saving time if [`Daylight::IN_DAYLIGHT`] is set.
+#[cfg(feature = "chrono")]
+impl TryFrom<Time> for chrono::DateTime<chrono::FixedOffset> {
+    type Error = TimeConversionError;
+
+    fn try_from(time: Time) -> core::result::Result<Self, Self::Error> {
+        use chrono::TimeZone;
+
+        let offset = time
+            .utc_offset_seconds()
+            .ok_or(TimeConversionError::UnspecifiedTimeZone)?;
+        let offset = chrono::FixedOffset::east_opt(offset).ok_or_else(invalid_offset)?;
+        let local = chrono::NaiveDateTime::try_from(time)?;
+        offset
+            .from_local_datetime(&local)
+            .single()
+            .ok_or_else(invalid_date)
+    }
+}
+
+/// Creates a [`Time`] from a local date and time of `chrono` and its offset
+/// from UTC in seconds.
+#[cfg(feature = "chrono")]
+fn from_chrono(
+    local: &chrono::NaiveDateTime,
+    utc_offset_seconds: Option<i32>,
+) -> core::result::Result<Time, TimeConversionError> {
+    use chrono::{Datelike, Timelike};
+
+    Time::from_fields(
+        local.year(),
+        local.month() as u8,
+        local.day() as u8,
+        local.hour() as u8,
+        local.minute() as u8,
+        local.second() as u8,
+        local.nanosecond(),
+        utc_offset_seconds,
+    )
+}
+
+/// Converts to a local time without time zone.
+#[cfg(feature = "chrono")]
+impl TryFrom<chrono::NaiveDateTime> for Time {
+    type Error = TimeConversionError;
+
+    fn try_from(local: chrono::NaiveDateTime) -> core::result::Result<Self, Self::Error> {
+        from_chrono(&local, None)
+    }
+}
+
+/// Converts to a time with the same local date and time and offset from UTC.
+/// The offset must be whole minutes. [`Daylight`] is left empty, as the
+/// offset already includes daylight saving time.
+#[cfg(feature = "chrono")]
+impl<Tz: chrono::TimeZone> TryFrom<chrono::DateTime<Tz>> for Time {
+    type Error = TimeConversionError;
+
+    fn try_from(date_time: chrono::DateTime<Tz>) -> core::result::Result<Self, Self::Error> {
+        use chrono::Offset;
+
+        let offset = date_time.offset().fix().local_minus_utc();
+        from_chrono(&date_time.naive_local(), Some(offset))
+    }
+}
+
+/// Converts the date and time, ignoring the time zone.
+#[cfg(feature = "time")]
+impl TryFrom<Time> for ::time::PrimitiveDateTime {
+    type Error = TimeConversionError;
+
+    fn try_from(time: Time) -> core::result::Result<Self, Self::Error> {
+        time.is_valid()
+            .map_err(TimeConversionError::InvalidFields)?;
+        let month = ::time::Month::try_from(time.month()).map_err(|_| invalid_date())?;
+        let date = ::time::Date::from_calendar_date(time.year().into(), month, time.day())
+            .map_err(|_| invalid_date())?;
+        let clock = ::time::Time::from_hms_nano(
+            time.hour(),
+            time.minute(),
+            time.second(),
+            time.nanosecond(),
+        )
+        .map_err(|_| invalid_date())?;
+        Ok(Self::new(date, clock))
+    }
+}
+
+/// Converts the date and time with its offset from UTC, which includes the
+/// hour of daylight saving time if [`Daylight::IN_DAYLIGHT`] is set.
+#[cfg(feature = "time")]
+impl TryFrom<Time> for ::time::OffsetDateTime {
+    type Error = TimeConversionError;
+
+    fn try_from(time: Time) -> core::result::Result<Self, Self::Error> {
+        let offset = time
+            .utc_offset_seconds()
+            .ok_or(TimeConversionError::UnspecifiedTimeZone)?;
+        let offset = ::time::UtcOffset::from_whole_seconds(offset).map_err(|_| invalid_offset())?;
+        Ok(::time::PrimitiveDateTime::try_from(time)?.assume_offset(offset))
+    }
+}
+
+/// Converts to a local time without time zone.
+#[cfg(feature = "time")]
+impl TryFrom<::time::PrimitiveDateTime> for Time {
+    type Error = TimeConversionError;
+
+    fn try_from(local: ::time::PrimitiveDateTime) -> core::result::Result<Self, Self::Error> {
+        Self::from_fields(
+            local.year(),
+            local.month().into(),
+            local.day(),
+            local.hour(),
+            local.minute(),
+            local.second(),
+            local.nanosecond(),
+            None,
+        )
+    }
+}
+
+/// Converts to a time with the same local date and time and offset from UTC.
+/// The offset must be whole minutes. [`Daylight`] is left empty, as the
+/// offset already includes daylight saving time.
+#[cfg(feature = "time")]
+impl TryFrom<::time::OffsetDateTime> for Time {
+    type Error = TimeConversionError;
+
+    fn try_from(date_time: ::time::OffsetDateTime) -> core::result::Result<Self, Self::Error> {
+        Self::from_fields(
+            date_time.year(),
+            date_time.month().into(),
+            date_time.day(),
+            date_time.hour(),
+            date_time.minute(),
+            date_time.second(),
+            date_time.nanosecond(),
+            Some(date_time.offset().whole_seconds()),
+        )
+    }
+}
+
 /// Unique key for a variable.
 #[cfg(feature = "alloc")]
 #[derive(Clone, Debug, Eq, PartialEq, Ord, PartialOrd, Hash)]
@@ -935,3 +1356,107 @@ pub struct CapsuleInfo {
     /// The type of reset required for the capsule update.
     pub reset_type: ResetType,
 }
+
+#[cfg(all(test, any(feature = "chrono", feature = "time")))]
+mod tests {
+    use super::*;
+
+    fn time(time_zone: Option<i16>, daylight: Daylight) -> Time {
+        Time::new(TimeParams {
+            year: 2024,
+            month: 2,
+            day: 29,
+            hour: 13,
+            minute: 45,
+            second: 30,
+            nanosecond: 123_456_789,
+            time_zone,
+            daylight,
+        })
+        .unwrap()
+    }
+
+    #[test]
+    #[cfg(feature = "chrono")]
+    fn test_chrono_conversions() {
+        use chrono::{DateTime, FixedOffset, NaiveDate, NaiveDateTime, TimeZone, Utc};
+
+        let expected = NaiveDate::from_ymd_opt(2024, 2, 29)
+            .unwrap()
+            .and_hms_nano_opt(13, 45, 30, 123_456_789)
+            .unwrap();
+        let local = NaiveDateTime::try_from(time(None, Daylight::empty())).unwrap();
+        assert_eq!(local, expected);
+        assert_eq!(Time::try_from(local), Ok(time(None, Daylight::empty())));
+        assert_eq!(
+            DateTime::<FixedOffset>::try_from(time(None, Daylight::empty())),
+            Err(TimeConversionError::UnspecifiedTimeZone)
+        );
+
+        // The offset includes daylight saving time.
+        let date_time =
+            DateTime::<FixedOffset>::try_from(time(Some(60), Daylight::IN_DAYLIGHT)).unwrap();
+        assert_eq!(date_time.naive_local(), expected);
+        assert_eq!(date_time.offset().local_minus_utc(), 2 * 3600);
+        assert_eq!(
+            Time::try_from(date_time),
+            Ok(time(Some(120), Daylight::empty()))
+        );
+
+        let utc = Utc.with_ymd_and_hms(2024, 2, 29, 13, 45, 30).unwrap();
+        assert_eq!(Time::try_from(utc).unwrap().time_zone(), Some(0));
+
+        let offset = FixedOffset::east_opt(30).unwrap();
+        let date_time = offset.with_ymd_and_hms(2024, 2, 29, 13, 45, 30).unwrap();
+        assert!(matches!(
+            Time::try_from(date_time),
+            Err(TimeConversionError::InvalidFields(TimeError {
+                timezone: true,
+                ..
+            }))
+        ));
+
+        // 2023 is not a leap year.
+        let invalid = Time::new(TimeParams {
+            year: 2023,
+            month: 2,
+            day: 29,
+            hour: 0,
+            minute: 0,
+            second: 0,
+            nanosecond: 0,
+            time_zone: None,
+            daylight: Daylight::empty(),
+        })
+        .unwrap();
+        assert!(matches!(
+            NaiveDateTime::try_from(invalid),
+            Err(TimeConversionError::InvalidFields(TimeError {
+                day: true,
+                ..
+            }))
+        ));
+    }
+
+    #[test]
+    #[cfg(feature = "time")]
+    fn test_time_conversions() {
+        use ::time::{OffsetDateTime, PrimitiveDateTime, UtcOffset};
+
+        let local = PrimitiveDateTime::try_from(time(None, Daylight::empty())).unwrap();
+        assert_eq!(local.nanosecond(), 123_456_789);
+        assert_eq!(Time::try_from(local), Ok(time(None, Daylight::empty())));
+        assert_eq!(
+            OffsetDateTime::try_from(time(None, Daylight::empty())),
+            Err(TimeConversionError::UnspecifiedTimeZone)
+        );
+
+        let date_time = OffsetDateTime::try_from(time(Some(-300), Daylight::IN_DAYLIGHT)).unwrap();
+        assert_eq!(date_time.offset(), UtcOffset::from_hms(-4, 0, 0).unwrap());
+        assert_eq!(date_time.hour(), 13);
+        assert_eq!(
+            Time::try_from(date_time),
+            Ok(time(Some(-240), Daylight::empty()))
+        );
+    }
+}
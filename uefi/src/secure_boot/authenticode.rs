@@ -0,0 +1,124 @@
+// SPDX-License-Identifier: MIT OR Apache-2.0
+
+//! Authenticode digests of PE/COFF images.
+//!
+//! The Authenticode digest identifies an image independently of its
+//! signatures. It is what the firmware compares with the SHA-256 entries of
+//! the `db` and `dbx` signature databases, and what it measures into PCR 4
+//! before starting a boot application.
+//!
+//! The algorithm is defined in the [Windows Authenticode Portable Executable
+//! Signature Format][spec]: the headers without the checksum and the
+//! certificate table entry, the sections in the order of their file offset,
+//! and any data after the sections except the certificate table are hashed.
+//!
+//! [spec]: https://learn.microsoft.com/en-us/windows-hardware/drivers/install/authenticode
+
+use super::sha256::{DIGEST_SIZE, Sha256};
+use crate::pe::{DataDirectory, ParseError, PeImage};
+use crate::util::usize_from_u32;
+
+/// Computes the SHA-256 Authenticode digest of a PE/COFF image.
+///
+/// `image` must contain the image file as it is stored on disk, not an
+/// image that has been loaded into memory, since loading moves the sections
+/// to their virtual addresses.
+///
+/// # Errors
+///
+/// * [`ParseError::OutOfBounds`]: the certificate table extends beyond the
+///   end of the image.
+/// * Errors of [`PeImage::parse`].
+pub fn digest(image: &[u8]) -> Result<[u8; DIGEST_SIZE], ParseError> {
+    let pe = PeImage::parse(image)?;
+    let checksum = pe.checksum_offset();
+    let certificate_entry = pe.data_directory_offset(DataDirectory::CERTIFICATE_TABLE);
+    let certificate_size = pe
+        .data_directory(DataDirectory::CERTIFICATE_TABLE)
+        .map_or(0, |table| usize_from_u32(table.size));
+    let size_of_headers = usize_from_u32(pe.size_of_headers());
+    if certificate_size > image.len() || size_of_headers < certificate_entry.unwrap_or(checksum) + 8
+    {
+        return Err(ParseError::OutOfBounds);
+    }
+
+    let mut hasher = Sha256::new();
+
+    // Headers, without the checksum and the certificate table entry.
+    let headers = &image[..size_of_headers];
+    hasher.update(&headers[..checksum]);
+    match certificate_entry {
+        Some(entry) => {
+            hasher.update(&headers[checksum + 4..entry]);
+            hasher.update(&headers[entry + 8..]);
+        }
+        None => hasher.update(&headers[checksum + 4..]),
+    }
+
+    // Sections, in the order of their file offset and then their index.
+    // Sorting them would require an allocation, so instead the next section
+    // is searched for on each iteration.
+    let mut hashed = size_of_headers;
+    let mut previous = None;
+    loop {
+        let next = pe
+            .sections()
+            .enumerate()
+            .map(|(index, section)| ((section.file_offset(), index), section))
+            .filter(|(key, _)| previous.is_none_or(|previous| *key > previous))
+            .min_by_key(|(key, _)| *key);
+        let Some((key, section)) = next else {
+            break;
+        };
+        previous = Some(key);
+        hasher.update(section.data());
+        hashed += section.data().len();
+    }
+
+    // Data after the sections, without the certificate table.
+    if let Some(end) = image.len().checked_sub(certificate_size) {
+        if end > hashed {
+            hasher.update(&image[hashed..end]);
+        }
+    }
+
+    Ok(hasher.finalize())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::pe::tests::{CERTIFICATE_ENTRY, CHECKSUM, image};
+
+    #[test]
+    fn test_digest() {
+        let image = image();
+        let mut hasher = Sha256::new();
+        hasher.update(&image[..CHECKSUM]);
+        hasher.update(&image[CHECKSUM + 4..CERTIFICATE_ENTRY]);
+        hasher.update(&image[CERTIFICATE_ENTRY + 8..0x510]);
+        assert_eq!(digest(&image), Ok(hasher.finalize()));
+
+        // The checksum, the certificate table entry and the certificate
+        // table are not hashed.
+        let mut signed = image.clone();
+        signed[CHECKSUM] ^= 0xff;
+        signed[CERTIFICATE_ENTRY] ^= 0x01;
+        signed[0x520] ^= 0xff;
+        assert_eq!(digest(&signed), digest(&image));
+
+        // Everything else is.
+        let mut modified = image.clone();
+        modified[0x4ff] ^= 0xff;
+        assert_ne!(digest(&modified), digest(&image));
+    }
+
+    #[test]
+    fn test_digest_errors() {
+        let image = image();
+        assert_eq!(digest(&image[1..]), Err(ParseError::NotPe));
+
+        // The last section extends beyond the end of the image.
+        assert_eq!(digest(&image[..0x480]), Err(ParseError::OutOfBounds));
+    }
+}
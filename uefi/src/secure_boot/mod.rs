@@ -0,0 +1,158 @@
+// SPDX-License-Identifier: MIT OR Apache-2.0
+
+//! Secure Boot state.
+//!
+//! Use [`state`] to find out whether the firmware verifies the signatures of
+//! images before loading them, e.g. to decide whether an unsigned payload may
+//! be loaded. The [`authenticode`] module computes the digests of images
+//! that the firmware compares with the signature databases.
+
+pub mod authenticode;
+
+mod sha256;
+
+use crate::runtime::{self, VariableVendor};
+use crate::{CStr16, Result, Status, cstr16};
+
+/// Mode of the Secure Boot key management, see section 32.3 of the UEFI
+/// specification.
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Hash)]
+pub enum Mode {
+    /// No Platform Key (PK) is enrolled, so the keys can be changed without
+    /// authentication, and images are not verified.
+    Setup,
+    /// A Platform Key is enrolled, and images are verified if Secure Boot is
+    /// enabled.
+    User,
+    /// No Platform Key is enrolled, and images are verified, but the
+    /// results are only recorded in the image execution information table.
+    Audit,
+    /// Like [`Mode::User`], but the mode can only be left by platform
+    /// specific means.
+    Deployed,
+}
+
+/// Secure Boot state, as returned by [`state`].
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Hash)]
+pub struct State {
+    /// Whether the firmware verifies images (`SecureBoot` variable).
+    pub secure_boot: bool,
+    /// Whether the platform is in setup mode (`SetupMode` variable).
+    pub setup_mode: bool,
+    /// Whether the platform is in audit mode (`AuditMode` variable).
+    pub audit_mode: bool,
+    /// Whether the platform is in deployed mode (`DeployedMode` variable).
+    pub deployed_mode: bool,
+    /// Whether the shim boot loader was told to not verify the images that
+    /// it loads (`MokSBState` variable). `None` if the variable doesn't
+    /// exist, e.g. because the image was not loaded by shim.
+    pub shim_validation_disabled: Option<bool>,
+}
+
+impl State {
+    /// Returns the key management mode. Firmware that predates audit and
+    /// deployed mode only reports [`Mode::Setup`] or [`Mode::User`].
+    #[must_use]
+    pub const fn mode(&self) -> Mode {
+        if self.audit_mode {
+            Mode::Audit
+        } else if self.deployed_mode {
+            Mode::Deployed
+        } else if self.setup_mode {
+            Mode::Setup
+        } else {
+            Mode::User
+        }
+    }
+
+    /// Returns whether images are verified before they are loaded, by the
+    /// firmware and, if present, by shim.
+    ///
+    /// Audit mode doesn't count as enforcing, since failed verifications are
+    /// only recorded.
+    #[must_use]
+    pub const fn is_enforcing(&self) -> bool {
+        let shim_disabled = matches!(self.shim_validation_disabled, Some(true));
+        self.secure_boot && !self.audit_mode && !shim_disabled
+    }
+}
+
+/// Reads the Secure Boot state from the global variables of the firmware and
+/// the variables of shim.
+///
+/// Variables that don't exist, e.g. on firmware without Secure Boot support,
+/// are read as `false`.
+///
+/// # Errors
+///
+/// * [`Status::DEVICE_ERROR`]: a variable could not be read due to a hardware
+///   error.
+/// * [`Status::SECURITY_VIOLATION`]: a variable could not be read due to an
+///   authentication error.
+/// * [`Status::UNSUPPORTED`]: the variables can't be read after exiting boot
+///   services.
+pub fn state() -> Result<State> {
+    let global = &VariableVendor::GLOBAL_VARIABLE;
+    Ok(State {
+        secure_boot: read_bool(cstr16!("SecureBoot"), global)?.unwrap_or(false),
+        setup_mode: read_bool(cstr16!("SetupMode"), global)?.unwrap_or(false),
+        audit_mode: read_bool(cstr16!("AuditMode"), global)?.unwrap_or(false),
+        deployed_mode: read_bool(cstr16!("DeployedMode"), global)?.unwrap_or(false),
+        shim_validation_disabled: read_bool(cstr16!("MokSBState"), &VariableVendor::SHIM_LOCK)?,
+    })
+}
+
+/// Reads a one-byte boolean variable. Returns `None` if it doesn't exist.
+fn read_bool(name: &CStr16, vendor: &VariableVendor) -> Result<Option<bool>> {
+    match runtime::get_variable_array::<1>(name, vendor) {
+        Ok(([value], _)) => Ok(Some(value != 0)),
+        Err(err) if err.status() == Status::NOT_FOUND => Ok(None),
+        // The variable is not a boolean.
+        Err(err)
+            if matches!(
+                err.status(),
+                Status::BUFFER_TOO_SMALL | Status::BAD_BUFFER_SIZE
+            ) =>
+        {
+            Err(Status::VOLUME_CORRUPTED.into())
+        }
+        Err(err) => Err(err.to_err_without_payload()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_mode() {
+        let mut state = State {
+            secure_boot: false,
+            setup_mode: true,
+            audit_mode: false,
+            deployed_mode: false,
+            shim_validation_disabled: None,
+        };
+        assert_eq!(state.mode(), Mode::Setup);
+        assert!(!state.is_enforcing());
+
+        state.setup_mode = false;
+        state.secure_boot = true;
+        assert_eq!(state.mode(), Mode::User);
+        assert!(state.is_enforcing());
+
+        state.shim_validation_disabled = Some(true);
+        assert!(!state.is_enforcing());
+
+        state.shim_validation_disabled = Some(false);
+        state.deployed_mode = true;
+        assert_eq!(state.mode(), Mode::Deployed);
+        assert!(state.is_enforcing());
+
+        state.deployed_mode = false;
+        state.setup_mode = true;
+        state.audit_mode = true;
+        assert_eq!(state.mode(), Mode::Audit);
+        assert!(!state.is_enforcing());
+    }
+}
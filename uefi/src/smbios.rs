@@ -0,0 +1,539 @@
+// SPDX-License-Identifier: MIT OR Apache-2.0
+
+//! Construction and publishing of SMBIOS tables.
+//!
+//! SMBIOS tables describe the system to the operating system: the firmware,
+//! the system, the boards, the memory and so on. Firmware usually installs
+//! them itself, but payloads that run on virtual or bare hardware sometimes
+//! have to fabricate them. [`TableBuilder`] builds a structure table from
+//! [`Structure`]s and installs it with a 64-bit ([SMBIOS 3.x][spec]) entry
+//! point.
+//!
+//! # Example
+//!
+//! ```no_run
+//! use uefi::Guid;
+//! use uefi::smbios::{BiosInformation, SystemInformation, TableBuilder};
+//!
+//! # fn example() -> Result<(), Box<dyn core::error::Error>> {
+//! let mut table = TableBuilder::new(3, 7, 0);
+//! table.push(&BiosInformation {
+//!     vendor: "Example",
+//!     version: "1.0",
+//!     release_date: "01/01/2025",
+//!     ..Default::default()
+//! }.to_structure()?)?;
+//! table.push(&SystemInformation {
+//!     manufacturer: "Example",
+//!     product_name: "Virtual Machine",
+//!     uuid: Guid::ZERO,
+//!     ..Default::default()
+//! }.to_structure()?)?;
+//! table.install()?;
+//! # Ok(())
+//! # }
+//! ```
+//!
+//! [spec]: https://www.dmtf.org/standards/smbios
+
+use crate::boot::{self, MemoryType};
+use crate::table::cfg::ConfigTableEntry;
+use crate::{Guid, Result};
+use alloc::vec::Vec;
+use core::fmt::{self, Display, Formatter};
+use core::ptr;
+
+/// Errors of building SMBIOS structures and tables.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum SmbiosError {
+    /// A string contains a null character.
+    InvalidString,
+    /// A structure has more than 255 strings.
+    TooManyStrings,
+    /// The formatted area of a structure is larger than 251 bytes.
+    StructureTooLarge,
+    /// The table is larger than 4 GiB or has run out of handles.
+    TableTooLarge,
+}
+
+impl Display for SmbiosError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        let s = match self {
+            Self::InvalidString => "SMBIOS string contains a null character",
+            Self::TooManyStrings => "too many strings in SMBIOS structure",
+            Self::StructureTooLarge => "SMBIOS structure is too large",
+            Self::TableTooLarge => "SMBIOS table is too large",
+        };
+        f.write_str(s)
+    }
+}
+
+impl core::error::Error for SmbiosError {}
+
+/// Size of the header of a structure: type, length and handle.
+const HEADER_SIZE: usize = 4;
+
+/// An SMBIOS structure: a header, a formatted area and a set of strings.
+///
+/// The fields of the formatted area are appended in order with the `push_*`
+/// methods. String fields hold the number of a string in the string set,
+/// which [`push_string`](Self::push_string) takes care of. The handle is
+/// assigned by [`TableBuilder::push`].
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Structure {
+    ty: u8,
+    formatted: Vec<u8>,
+    strings: Vec<u8>,
+    string_count: u8,
+}
+
+impl Structure {
+    /// Type of the end-of-table structure.
+    pub const END_OF_TABLE: u8 = 127;
+
+    /// Creates an empty structure of type `ty`.
+    #[must_use]
+    pub const fn new(ty: u8) -> Self {
+        Self {
+            ty,
+            formatted: Vec::new(),
+            strings: Vec::new(),
+            string_count: 0,
+        }
+    }
+
+    /// Returns the type of the structure.
+    #[must_use]
+    pub const fn ty(&self) -> u8 {
+        self.ty
+    }
+
+    /// Appends a byte field.
+    pub fn push_u8(&mut self, value: u8) -> &mut Self {
+        self.formatted.push(value);
+        self
+    }
+
+    /// Appends a word field.
+    pub fn push_u16(&mut self, value: u16) -> &mut Self {
+        self.push_bytes(&value.to_le_bytes())
+    }
+
+    /// Appends a dword field.
+    pub fn push_u32(&mut self, value: u32) -> &mut Self {
+        self.push_bytes(&value.to_le_bytes())
+    }
+
+    /// Appends a qword field.
+    pub fn push_u64(&mut self, value: u64) -> &mut Self {
+        self.push_bytes(&value.to_le_bytes())
+    }
+
+    /// Appends raw bytes to the formatted area.
+    pub fn push_bytes(&mut self, bytes: &[u8]) -> &mut Self {
+        self.formatted.extend_from_slice(bytes);
+        self
+    }
+
+    /// Adds `s` to the string set, and appends its number as a byte field.
+    /// An empty string is not added, and its number is zero.
+    ///
+    /// # Errors
+    ///
+    /// See [`add_string`](Self::add_string).
+    pub fn push_string(&mut self, s: &str) -> core::result::Result<&mut Self, SmbiosError> {
+        let number = self.add_string(s)?;
+        Ok(self.push_u8(number))
+    }
+
+    /// Adds `s` to the string set and returns its number, without appending
+    /// a field. An empty string is not added, and its number is zero.
+    ///
+    /// # Errors
+    ///
+    /// * [`SmbiosError::InvalidString`]: `s` contains a null character.
+    /// * [`SmbiosError::TooManyStrings`]: the structure already has 255
+    ///   strings.
+    pub fn add_string(&mut self, s: &str) -> core::result::Result<u8, SmbiosError> {
+        if s.is_empty() {
+            return Ok(0);
+        }
+        if s.contains('\0') {
+            return Err(SmbiosError::InvalidString);
+        }
+        self.string_count = self
+            .string_count
+            .checked_add(1)
+            .ok_or(SmbiosError::TooManyStrings)?;
+        self.strings.extend_from_slice(s.as_bytes());
+        self.strings.push(0);
+        Ok(self.string_count)
+    }
+
+    /// Appends the encoded structure with the given `handle` to `out`.
+    fn encode(&self, handle: u16, out: &mut Vec<u8>) -> core::result::Result<(), SmbiosError> {
+        let length = u8::try_from(HEADER_SIZE + self.formatted.len())
+            .map_err(|_| SmbiosError::StructureTooLarge)?;
+        out.push(self.ty);
+        out.push(length);
+        out.extend_from_slice(&handle.to_le_bytes());
+        out.extend_from_slice(&self.formatted);
+        if self.strings.is_empty() {
+            // A structure without strings ends with two null bytes.
+            out.push(0);
+        } else {
+            out.extend_from_slice(&self.strings);
+        }
+        out.push(0);
+        Ok(())
+    }
+}
+
+/// Contents of a BIOS Information (type 0) structure.
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub struct BiosInformation<'a> {
+    /// Vendor of the firmware.
+    pub vendor: &'a str,
+    /// Version of the firmware.
+    pub version: &'a str,
+    /// Release date of the firmware, in the format `mm/dd/yyyy`.
+    pub release_date: &'a str,
+    /// BIOS characteristics bit field.
+    pub characteristics: u64,
+    /// BIOS characteristics extension bytes. Bit 3 of the second byte
+    /// indicates that UEFI is supported.
+    pub characteristics_ext: [u8; 2],
+    /// Major release of the firmware.
+    pub major_release: u8,
+    /// Minor release of the firmware.
+    pub minor_release: u8,
+}
+
+impl BiosInformation<'_> {
+    /// Type of the structure.
+    pub const TYPE: u8 = 0;
+
+    /// Builds the structure. The firmware is reported without an embedded
+    /// controller and with the minimal ROM size.
+    ///
+    /// # Errors
+    ///
+    /// See [`Structure::add_string`].
+    pub fn to_structure(&self) -> core::result::Result<Structure, SmbiosError> {
+        let mut structure = Structure::new(Self::TYPE);
+        structure
+            .push_string(self.vendor)?
+            .push_string(self.version)?
+            .push_u16(0) // Starting address segment, zero for UEFI.
+            .push_string(self.release_date)?
+            .push_u8(0) // ROM size.
+            .push_u64(self.characteristics)
+            .push_bytes(&self.characteristics_ext)
+            .push_u8(self.major_release)
+            .push_u8(self.minor_release)
+            .push_u8(0xff) // Embedded controller major release.
+            .push_u8(0xff) // Embedded controller minor release.
+            .push_u16(0); // Extended ROM size.
+        Ok(structure)
+    }
+}
+
+/// Contents of a System Information (type 1) structure.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct SystemInformation<'a> {
+    /// Manufacturer of the system.
+    pub manufacturer: &'a str,
+    /// Product name of the system.
+    pub product_name: &'a str,
+    /// Version of the system.
+    pub version: &'a str,
+    /// Serial number of the system.
+    pub serial_number: &'a str,
+    /// Universally unique identifier of the system.
+    pub uuid: Guid,
+    /// Event that caused the system to power up, e.g. 6 for the power
+    /// switch.
+    pub wake_up_type: u8,
+    /// SKU number of the system.
+    pub sku_number: &'a str,
+    /// Family of the system.
+    pub family: &'a str,
+}
+
+impl Default for SystemInformation<'_> {
+    fn default() -> Self {
+        Self {
+            manufacturer: "",
+            product_name: "",
+            version: "",
+            serial_number: "",
+            uuid: Guid::ZERO,
+            wake_up_type: 6,
+            sku_number: "",
+            family: "",
+        }
+    }
+}
+
+impl SystemInformation<'_> {
+    /// Type of the structure.
+    pub const TYPE: u8 = 1;
+
+    /// Builds the structure.
+    ///
+    /// # Errors
+    ///
+    /// See [`Structure::add_string`].
+    pub fn to_structure(&self) -> core::result::Result<Structure, SmbiosError> {
+        let mut structure = Structure::new(Self::TYPE);
+        structure
+            .push_string(self.manufacturer)?
+            .push_string(self.product_name)?
+            .push_string(self.version)?
+            .push_string(self.serial_number)?
+            // SMBIOS uses the same mixed-endian encoding as UEFI GUIDs.
+            .push_bytes(&self.uuid.to_bytes())
+            .push_u8(self.wake_up_type)
+            .push_string(self.sku_number)?
+            .push_string(self.family)?;
+        Ok(structure)
+    }
+}
+
+/// Size of the SMBIOS 3.x entry point structure.
+pub const ENTRY_POINT_SIZE: usize = 24;
+
+/// Builder of an SMBIOS structure table.
+///
+/// Handles are assigned to the structures in the order they are pushed,
+/// starting at zero. The end-of-table structure is appended automatically.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct TableBuilder {
+    version: (u8, u8, u8),
+    data: Vec<u8>,
+    next_handle: u16,
+}
+
+impl TableBuilder {
+    /// Creates an empty table that conforms to SMBIOS version
+    /// `major`.`minor`.`docrev`.
+    #[must_use]
+    pub const fn new(major: u8, minor: u8, docrev: u8) -> Self {
+        Self {
+            version: (major, minor, docrev),
+            data: Vec::new(),
+            next_handle: 0,
+        }
+    }
+
+    /// Returns the handle that the next pushed structure gets, e.g. to
+    /// reference it from another structure.
+    #[must_use]
+    pub const fn next_handle(&self) -> u16 {
+        self.next_handle
+    }
+
+    /// Appends `structure` to the table and returns its handle.
+    ///
+    /// # Errors
+    ///
+    /// * [`SmbiosError::StructureTooLarge`]: the formatted area of the
+    ///   structure is too large.
+    /// * [`SmbiosError::TableTooLarge`]: the table is full.
+    pub fn push(&mut self, structure: &Structure) -> core::result::Result<u16, SmbiosError> {
+        // Handles from 0xFEFF on are reserved, and one is kept for the
+        // end-of-table structure.
+        if self.next_handle >= 0xfefe {
+            return Err(SmbiosError::TableTooLarge);
+        }
+        let len = self.data.len();
+        structure.encode(self.next_handle, &mut self.data)?;
+        if u32::try_from(self.data.len() + HEADER_SIZE + 2).is_err() {
+            self.data.truncate(len);
+            return Err(SmbiosError::TableTooLarge);
+        }
+        let handle = self.next_handle;
+        self.next_handle += 1;
+        Ok(handle)
+    }
+
+    /// Returns the structure table, terminated by an end-of-table structure.
+    #[must_use]
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut bytes = self.data.clone();
+        Structure::new(Structure::END_OF_TABLE)
+            .encode(self.next_handle, &mut bytes)
+            .expect("end-of-table structure is valid");
+        bytes
+    }
+
+    /// Returns the 64-bit entry point of the table, for a structure table
+    /// of `table_size` bytes at `table_address`.
+    #[must_use]
+    pub fn entry_point(&self, table_size: u32, table_address: u64) -> [u8; ENTRY_POINT_SIZE] {
+        let (major, minor, docrev) = self.version;
+        let mut entry_point = [0; ENTRY_POINT_SIZE];
+        entry_point[..5].copy_from_slice(b"_SM3_");
+        entry_point[6] = ENTRY_POINT_SIZE as u8;
+        entry_point[7] = major;
+        entry_point[8] = minor;
+        entry_point[9] = docrev;
+        // Entry point revision.
+        entry_point[10] = 1;
+        entry_point[12..16].copy_from_slice(&table_size.to_le_bytes());
+        entry_point[16..24].copy_from_slice(&table_address.to_le_bytes());
+        let sum = entry_point.iter().fold(0u8, |sum, b| sum.wrapping_add(*b));
+        entry_point[5] = sum.wrapping_neg();
+        entry_point
+    }
+
+    /// Installs the table as the SMBIOS 3.x configuration table, replacing
+    /// the one of the firmware, if any.
+    ///
+    /// The entry point and the structure table are copied to a pool
+    /// allocation of type [`MemoryType::RUNTIME_SERVICES_DATA`], which is
+    /// never freed.
+    ///
+    /// # Errors
+    ///
+    /// * [`Status::OUT_OF_RESOURCES`]: out of memory.
+    ///
+    /// [`Status::OUT_OF_RESOURCES`]: crate::Status::OUT_OF_RESOURCES
+    pub fn install(&self) -> Result {
+        let table = self.to_bytes();
+        // The size is checked by `push`.
+        let table_size = u32::try_from(table.len()).unwrap();
+        let ptr = boot::allocate_pool(
+            MemoryType::RUNTIME_SERVICES_DATA,
+            ENTRY_POINT_SIZE + table.len(),
+        )?;
+        // Pool allocations are 8-byte aligned, and so is the table.
+        let table_ptr = unsafe { ptr.add(ENTRY_POINT_SIZE) };
+        let entry_point = self.entry_point(table_size, table_ptr.as_ptr() as u64);
+        unsafe {
+            ptr::copy_nonoverlapping(entry_point.as_ptr(), ptr.as_ptr(), ENTRY_POINT_SIZE);
+            ptr::copy_nonoverlapping(table.as_ptr(), table_ptr.as_ptr(), table.len());
+        }
+
+        let result = unsafe {
+            boot::install_configuration_table(
+                &ConfigTableEntry::SMBIOS3_GUID,
+                ptr.as_ptr().cast_const().cast(),
+            )
+        };
+        if result.is_err() {
+            // Safety: the allocation has not been published.
+            let _ = unsafe { boot::free_pool(ptr) };
+        }
+        result
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::guid;
+
+    #[test]
+    fn test_structure_encoding() {
+        let mut structure = Structure::new(0x80);
+        structure
+            .push_u8(1)
+            .push_string("ab")
+            .unwrap()
+            .push_string("")
+            .unwrap()
+            .push_u16(0x0302)
+            .push_string("c")
+            .unwrap();
+        let mut bytes = Vec::new();
+        structure.encode(0x1234, &mut bytes).unwrap();
+        #[rustfmt::skip]
+        assert_eq!(bytes, [
+            // Header
+            0x80, 0x0a, 0x34, 0x12,
+            // Formatted area
+            0x01, 0x01, 0x00, 0x02, 0x03, 0x02,
+            // Strings
+            b'a', b'b', 0x00, b'c', 0x00, 0x00,
+        ]);
+
+        let mut bytes = Vec::new();
+        Structure::new(Structure::END_OF_TABLE)
+            .encode(7, &mut bytes)
+            .unwrap();
+        assert_eq!(bytes, [0x7f, 0x04, 0x07, 0x00, 0x00, 0x00]);
+
+        assert_eq!(
+            Structure::new(0).add_string("a\0b"),
+            Err(SmbiosError::InvalidString)
+        );
+        let mut structure = Structure::new(0);
+        for _ in 0..255 {
+            structure.add_string("s").unwrap();
+        }
+        assert_eq!(structure.add_string("s"), Err(SmbiosError::TooManyStrings));
+
+        let mut structure = Structure::new(0);
+        structure.push_bytes(&[0; 252]);
+        assert_eq!(
+            TableBuilder::new(3, 0, 0).push(&structure),
+            Err(SmbiosError::StructureTooLarge)
+        );
+    }
+
+    #[test]
+    fn test_system_information() {
+        let structure = SystemInformation {
+            manufacturer: "M",
+            product_name: "P",
+            uuid: guid!("01020304-0506-0708-090a-0b0c0d0e0f10"),
+            family: "F",
+            ..Default::default()
+        }
+        .to_structure()
+        .unwrap();
+        let mut bytes = Vec::new();
+        structure.encode(1, &mut bytes).unwrap();
+        #[rustfmt::skip]
+        assert_eq!(bytes, [
+            0x01, 0x1b, 0x01, 0x00,
+            0x01, 0x02, 0x00, 0x00,
+            0x04, 0x03, 0x02, 0x01, 0x06, 0x05, 0x08, 0x07,
+            0x09, 0x0a, 0x0b, 0x0c, 0x0d, 0x0e, 0x0f, 0x10,
+            0x06, 0x00, 0x03,
+            b'M', 0x00, b'P', 0x00, b'F', 0x00, 0x00,
+        ]);
+
+        let structure = BiosInformation::default().to_structure().unwrap();
+        let mut bytes = Vec::new();
+        structure.encode(0, &mut bytes).unwrap();
+        assert_eq!(bytes[1], 0x1a);
+        assert_eq!(bytes.len(), 0x1a + 2);
+    }
+
+    #[test]
+    fn test_table() {
+        let mut table = TableBuilder::new(3, 7, 0);
+        assert_eq!(table.push(&Structure::new(0x80)), Ok(0));
+        assert_eq!(table.next_handle(), 1);
+        assert_eq!(table.push(&Structure::new(0x81)), Ok(1));
+        let bytes = table.to_bytes();
+        #[rustfmt::skip]
+        assert_eq!(bytes, [
+            0x80, 0x04, 0x00, 0x00, 0x00, 0x00,
+            0x81, 0x04, 0x01, 0x00, 0x00, 0x00,
+            0x7f, 0x04, 0x02, 0x00, 0x00, 0x00,
+        ]);
+
+        let entry_point = table.entry_point(bytes.len() as u32, 0x1122_3344_5566_7788);
+        assert_eq!(&entry_point[..5], b"_SM3_");
+        assert_eq!(entry_point.iter().fold(0u8, |s, b| s.wrapping_add(*b)), 0);
+        assert_eq!(entry_point[6..12], [24, 3, 7, 0, 1, 0]);
+        assert_eq!(entry_point[12..16], [18, 0, 0, 0]);
+        assert_eq!(
+            entry_point[16..],
+            [0x88, 0x77, 0x66, 0x55, 0x44, 0x33, 0x22, 0x11]
+        );
+    }
+}
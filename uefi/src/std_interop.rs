@@ -0,0 +1,64 @@
+// SPDX-License-Identifier: MIT OR Apache-2.0
+
+//! Interoperability with the [`std` implementation] for UEFI.
+//!
+//! Programs built with `std` for a `*-unknown-uefi` target don't use
+//! [`uefi::entry`], so the global image handle and system table of this
+//! crate are not set. [`init`] sets them from `std::os::uefi::env`, after
+//! which the protocol wrappers of this crate can be used alongside the file
+//! system and allocation support of `std`:
+//!
+//! ```ignore
+//! #![feature(uefi_std)]
+//!
+//! fn main() {
+//!     uefi::std_interop::init();
+//!     println!("UEFI-Version is {}", uefi::system::uefi_revision());
+//! }
+//! ```
+//!
+//! Accessing `std::os::uefi::env` currently requires the unstable
+//! `uefi_std` feature, so this module needs a nightly toolchain.
+//!
+//! [`std` implementation]: https://doc.rust-lang.org/nightly/rustc/platform-support/unknown-uefi.html
+
+use crate::{Handle, boot, table};
+use core::ptr::NonNull;
+use std::os::uefi::env;
+use uefi_raw::table::system::SystemTable;
+
+/// Sets the global image handle and system table of this crate to the ones
+/// of `std`.
+///
+/// This must be called before any other API of this crate is used. Calling
+/// it again is harmless.
+pub fn init() {
+    // SAFETY: `std` initializes these pointers with the arguments of the
+    // entry point, so they are valid.
+    unsafe {
+        table::set_system_table(system_table().as_ptr());
+        boot::set_image_handle(image_handle());
+    }
+}
+
+/// Returns the handle of the running image, as passed to the entry point of
+/// `std`.
+///
+/// # Panics
+///
+/// Panics if `std` hasn't been initialized, which can't happen in `main`.
+#[must_use]
+pub fn image_handle() -> Handle {
+    // SAFETY: `std` got the handle from the firmware.
+    unsafe { Handle::new(env::image_handle()) }
+}
+
+/// Returns the system table, as passed to the entry point of `std`.
+///
+/// # Panics
+///
+/// Panics if `std` hasn't been initialized, which can't happen in `main`.
+#[must_use]
+pub fn system_table() -> NonNull<SystemTable> {
+    env::system_table().cast()
+}
@@ -13,6 +13,9 @@ use crate::proto::console::text::{Input, Output};
 use crate::table::cfg::ConfigTableEntry;
 use crate::table::{self, Revision};
 use crate::{CStr16, Char16};
+use core::ffi::c_void;
+#[cfg(feature = "acpi")]
+use core::ptr::NonNull;
 use core::slice;
 
 /// Get the firmware vendor string.
@@ -87,6 +90,66 @@ where
     f(slice)
 }
 
+/// Get the physical address of the ACPI Root System Description Pointer
+/// (RSDP) from the configuration table.
+///
+/// The ACPI 2.0 RSDP, which also provides the 64-bit XSDT, is preferred over
+/// the ACPI 1.0 one. The address can be passed to ACPI table parsers; UEFI
+/// identity-maps memory, so the tables can be read at their physical
+/// addresses while boot services are active.
+///
+/// Returns `None` if the firmware doesn't provide ACPI tables.
+#[must_use]
+pub fn acpi_rsdp() -> Option<*const c_void> {
+    with_config_table(|entries| {
+        let find = |guid| {
+            entries
+                .iter()
+                .find(|entry| entry.guid == guid)
+                .map(|entry| entry.address)
+        };
+        find(ConfigTableEntry::ACPI2_GUID).or_else(|| find(ConfigTableEntry::ACPI_GUID))
+    })
+}
+
+/// Handler for the `acpi` crate that accesses the ACPI tables at their
+/// physical addresses, which UEFI identity-maps.
+///
+/// The handler is only valid while memory is identity-mapped, i.e. before
+/// the OS sets up its own page tables.
+#[cfg(feature = "acpi")]
+#[derive(Clone, Copy, Debug, Default)]
+pub struct IdentityMappedAcpiHandler;
+
+#[cfg(feature = "acpi")]
+impl acpi::AcpiHandler for IdentityMappedAcpiHandler {
+    unsafe fn map_physical_region<T>(
+        &self,
+        physical_address: usize,
+        size: usize,
+    ) -> acpi::PhysicalMapping<Self, T> {
+        let virtual_start =
+            NonNull::new(physical_address as *mut T).expect("ACPI table at address 0");
+        unsafe { acpi::PhysicalMapping::new(physical_address, virtual_start, size, size, *self) }
+    }
+
+    fn unmap_physical_region<T>(_region: &acpi::PhysicalMapping<Self, T>) {}
+}
+
+/// Parses the ACPI tables of the firmware, found with [`acpi_rsdp`].
+///
+/// # Errors
+///
+/// * [`acpi::AcpiError::NoValidRsdp`]: the firmware doesn't provide ACPI
+///   tables.
+/// * Errors of [`acpi::AcpiTables::from_rsdp`].
+#[cfg(feature = "acpi")]
+pub fn acpi_tables() -> acpi::AcpiResult<acpi::AcpiTables<IdentityMappedAcpiHandler>> {
+    let rsdp = acpi_rsdp().ok_or(acpi::AcpiError::NoValidRsdp)?;
+    // Safety: the configuration table points to a valid RSDP.
+    unsafe { acpi::AcpiTables::from_rsdp(IdentityMappedAcpiHandler, rsdp as usize) }
+}
+
 /// Call `f` with the [`Input`] protocol attached to stdin.
 ///
 /// # Panics
@@ -185,4 +248,61 @@ mod tests {
             }
         });
     }
+
+    #[cfg(all(feature = "acpi", feature = "alloc"))]
+    mod acpi_tables {
+        use super::*;
+        use acpi::sdt::Signature;
+        use alloc::vec::Vec;
+
+        fn checksum(bytes: &[u8]) -> u8 {
+            0u8.wrapping_sub(bytes.iter().fold(0u8, |sum, b| sum.wrapping_add(*b)))
+        }
+
+        fn sdt(signature: &[u8; 4], body: &[u8]) -> Vec<u8> {
+            let mut bytes = signature.to_vec();
+            bytes.extend((36 + body.len() as u32).to_le_bytes());
+            bytes.extend([1, 0]);
+            bytes.extend(b"UEFIRS");
+            bytes.extend(b"TESTTABL");
+            bytes.extend([0; 12]);
+            bytes.extend(body);
+            bytes[9] = checksum(&bytes);
+            bytes
+        }
+
+        #[test]
+        fn test_acpi_tables_from_rsdp() {
+            let bgrt = sdt(b"BGRT", &[]);
+            let xsdt = sdt(b"XSDT", &(bgrt.as_ptr() as u64).to_le_bytes());
+
+            let mut rsdp = b"RSD PTR ".to_vec();
+            rsdp.push(0);
+            rsdp.extend(b"UEFIRS");
+            rsdp.push(2);
+            rsdp.extend(0u32.to_le_bytes());
+            rsdp.extend(36u32.to_le_bytes());
+            rsdp.extend((xsdt.as_ptr() as u64).to_le_bytes());
+            rsdp.extend([0; 4]);
+            rsdp[8] = checksum(&rsdp[..20]);
+            rsdp[32] = checksum(&rsdp);
+
+            let tables = unsafe {
+                acpi::AcpiTables::from_rsdp(IdentityMappedAcpiHandler, rsdp.as_ptr() as usize)
+            }
+            .unwrap();
+            assert_eq!(tables.revision(), 2);
+            let signatures: Vec<Signature> = tables.headers().map(|h| h.signature).collect();
+            assert!(signatures == [Signature::BGRT]);
+
+            // The RSDP checksum is invalid.
+            rsdp[32] ^= 1;
+            assert!(matches!(
+                unsafe {
+                    acpi::AcpiTables::from_rsdp(IdentityMappedAcpiHandler, rsdp.as_ptr() as usize)
+                },
+                Err(acpi::AcpiError::RsdpInvalidChecksum)
+            ));
+        }
+    }
 }
@@ -0,0 +1,265 @@
+// SPDX-License-Identifier: MIT OR Apache-2.0
+
+//! Futures that complete when UEFI events are signaled.
+
+use super::executor;
+use crate::boot::{self, EventType, Tpl};
+use crate::{Event, Result};
+use alloc::boxed::Box;
+use core::cell::UnsafeCell;
+use core::ffi::c_void;
+use core::fmt::{self, Debug, Formatter};
+use core::future::Future;
+use core::pin::Pin;
+use core::ptr::NonNull;
+use core::sync::atomic::{AtomicBool, Ordering};
+use core::task::{Context, Poll, Waker};
+use core::time::Duration;
+
+/// Waits for an event that can be checked, i.e. one that is not of type
+/// [`EventType::NOTIFY_SIGNAL`]. Shared by the futures of this module.
+#[derive(Debug, Default)]
+pub(super) struct EventWait {
+    /// Registration with the executor, see [`executor::register_wait`].
+    id: Option<u64>,
+}
+
+impl EventWait {
+    /// Completes once `event` is signaled. `event` must be the same in all
+    /// polls.
+    pub(super) fn poll(&mut self, event: &Event, cx: &mut Context<'_>) -> Poll<Result> {
+        if self.id.is_some_and(executor::has_fired) {
+            self.unregister();
+            return Poll::Ready(Ok(()));
+        }
+        match boot::check_event(unsafe { event.unsafe_clone() }) {
+            Ok(false) => {
+                self.id = Some(executor::register_wait(self.id, event, cx.waker()));
+                Poll::Pending
+            }
+            result => {
+                self.unregister();
+                Poll::Ready(result.map(|_| ()))
+            }
+        }
+    }
+
+    /// Removes the registration with the executor. This must happen before
+    /// the event is closed.
+    pub(super) fn unregister(&mut self) {
+        if let Some(id) = self.id.take() {
+            executor::unregister_wait(id);
+        }
+    }
+}
+
+impl Drop for EventWait {
+    fn drop(&mut self) {
+        self.unregister();
+    }
+}
+
+/// Future of [`wait_for_event`].
+#[derive(Debug)]
+#[must_use = "futures do nothing unless awaited"]
+pub struct WaitForEvent<'a> {
+    event: &'a Event,
+    wait: EventWait,
+}
+
+impl Future for WaitForEvent<'_> {
+    type Output = Result;
+
+    fn poll(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Result> {
+        let event = self.event;
+        self.wait.poll(event, cx)
+    }
+}
+
+/// Returns a future that completes when `event` is signaled, and clears its
+/// signaled state.
+///
+/// The event must not be of type [`EventType::NOTIFY_SIGNAL`]; use
+/// [`Completion`] for the events of non-blocking protocol functions instead.
+///
+/// # Errors
+///
+/// * [`Status::INVALID_PARAMETER`]: `event` is of type
+///   [`EventType::NOTIFY_SIGNAL`].
+///
+/// [`Status::INVALID_PARAMETER`]: crate::Status::INVALID_PARAMETER
+pub const fn wait_for_event(event: &Event) -> WaitForEvent<'_> {
+    WaitForEvent {
+        event,
+        wait: EventWait { id: None },
+    }
+}
+
+/// State of a [`Completion`], shared with its notification function.
+struct CompletionState {
+    signaled: AtomicBool,
+    /// Only accessed at [`Tpl::CALLBACK`], the TPL of the notification
+    /// function.
+    waker: UnsafeCell<Option<Waker>>,
+}
+
+/// An event of type [`EventType::NOTIFY_SIGNAL`] that can be awaited.
+///
+/// This connects non-blocking protocol functions to async code: pass the
+/// [`event`] in the token of a request, then await the completion (by
+/// value or as `&mut completion`). It completes once the firmware signals
+/// the event, i.e. when the request is done.
+///
+/// The completion must not be dropped while a request that uses its event
+/// is in progress.
+///
+/// [`event`]: Self::event
+pub struct Completion {
+    event: Event,
+    state: Box<CompletionState>,
+}
+
+impl Completion {
+    /// Creates a completion that has not been signaled.
+    ///
+    /// # Errors
+    ///
+    /// * Errors of [`boot::create_event`].
+    pub fn new() -> Result<Self> {
+        let state = Box::new(CompletionState {
+            signaled: AtomicBool::new(false),
+            waker: UnsafeCell::new(None),
+        });
+        let context = NonNull::from(&*state).cast::<c_void>();
+        let event = unsafe {
+            boot::create_event(
+                EventType::NOTIFY_SIGNAL,
+                Tpl::CALLBACK,
+                Some(notify),
+                Some(context),
+            )
+        }?;
+        Ok(Self { event, state })
+    }
+
+    /// Returns the event to pass in the token of a request. Use
+    /// [`Event::unsafe_clone`] to copy it into the token; the event is
+    /// closed when the completion is dropped.
+    #[must_use]
+    pub const fn event(&self) -> &Event {
+        &self.event
+    }
+
+    /// Returns whether the event has been signaled.
+    #[must_use]
+    pub fn is_signaled(&self) -> bool {
+        self.state.signaled.load(Ordering::Acquire)
+    }
+
+    /// Sets the completion back to the state before its event was
+    /// signaled, so it can be used for another request.
+    pub fn reset(&mut self) {
+        self.state.signaled.store(false, Ordering::Release);
+    }
+}
+
+impl Future for Completion {
+    type Output = ();
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<()> {
+        if self.is_signaled() {
+            return Poll::Ready(());
+        }
+        // Prevent the notification function from running while the waker is
+        // replaced.
+        let _tpl = unsafe { boot::raise_tpl(Tpl::CALLBACK) };
+        if self.is_signaled() {
+            return Poll::Ready(());
+        }
+        unsafe { *self.state.waker.get() = Some(cx.waker().clone()) };
+        Poll::Pending
+    }
+}
+
+impl Debug for Completion {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Completion")
+            .field("event", &self.event)
+            .field("signaled", &self.is_signaled())
+            .finish()
+    }
+}
+
+impl Drop for Completion {
+    fn drop(&mut self) {
+        // Ignore errors, since we can't propagate them from `drop`.
+        let _ = boot::close_event(unsafe { self.event.unsafe_clone() });
+    }
+}
+
+/// Future of a request that signals a [`Completion`], see [`request`].
+pub(crate) struct Request<'a, F: FnOnce()> {
+    completion: &'a mut Completion,
+    cancel: Option<F>,
+}
+
+// Nothing is structurally pinned.
+impl<F: FnOnce()> Unpin for Request<'_, F> {}
+
+impl<F: FnOnce()> Future for Request<'_, F> {
+    type Output = ();
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<()> {
+        let this = self.get_mut();
+        let poll = Pin::new(&mut *this.completion).poll(cx);
+        if poll.is_ready() {
+            this.cancel = None;
+        }
+        poll
+    }
+}
+
+impl<F: FnOnce()> Drop for Request<'_, F> {
+    fn drop(&mut self) {
+        let Some(cancel) = self.cancel.take() else {
+            return;
+        };
+        if self.completion.is_signaled() {
+            return;
+        }
+        cancel();
+        // The firmware may use the token and buffers of the request until
+        // it signals the event.
+        while !self.completion.is_signaled() {
+            boot::stall(Duration::from_micros(10));
+        }
+    }
+}
+
+/// Returns a future that waits for the `completion` of a request that is in
+/// progress.
+///
+/// If the future is dropped before, e.g. by [`timeout`], it calls `cancel`
+/// and then blocks until the firmware signals the completion, so that the
+/// token and buffers of the request can be freed. `cancel` may do nothing
+/// if the protocol can't cancel requests.
+///
+/// [`timeout`]: super::timeout
+pub(crate) const fn request<F: FnOnce()>(completion: &mut Completion, cancel: F) -> Request<'_, F> {
+    Request {
+        completion,
+        cancel: Some(cancel),
+    }
+}
+
+/// Marks the [`Completion`] in `context` as signaled and wakes its future.
+unsafe extern "efiapi" fn notify(_event: Event, context: Option<NonNull<c_void>>) {
+    let Some(context) = context else {
+        return;
+    };
+    let state = unsafe { context.cast::<CompletionState>().as_ref() };
+    state.signaled.store(true, Ordering::Release);
+    if let Some(waker) = unsafe { (*state.waker.get()).take() } {
+        waker.wake();
+    }
+}
@@ -0,0 +1,337 @@
+// SPDX-License-Identifier: MIT OR Apache-2.0
+
+//! The executor of [`block_on`] and [`spawn`].
+
+use crate::Event;
+use crate::boot::{self, EventType, Tpl};
+use alloc::boxed::Box;
+use alloc::rc::Rc;
+use alloc::sync::Arc;
+use alloc::task::Wake;
+use alloc::vec::Vec;
+use core::cell::{Cell, RefCell, UnsafeCell};
+use core::ffi::c_void;
+use core::fmt::{self, Debug, Formatter};
+use core::future::Future;
+use core::pin::{Pin, pin};
+use core::ptr;
+use core::sync::atomic::{AtomicBool, AtomicPtr, Ordering};
+use core::task::{Context, Poll, Waker};
+
+/// Event that wakers signal to end the wait of the executor. It is created
+/// by the first call of [`block_on`] and never closed, so that wakers which
+/// outlive the executor stay valid.
+static WAKE_EVENT: AtomicPtr<c_void> = AtomicPtr::new(ptr::null_mut());
+
+/// State shared by the executor, [`spawn`] and the futures waiting for events.
+struct Runtime {
+    running: Cell<bool>,
+    /// Spawned tasks that are not being run by [`block_on`].
+    tasks: UnsafeCell<Vec<Task>>,
+    /// Events that futures wait for, see [`register_wait`].
+    waits: UnsafeCell<Vec<Wait>>,
+    next_wait_id: Cell<u64>,
+}
+
+// The runtime is not thread-safe, but the UEFI boot environment only uses
+// one processor. Event notification functions don't access it.
+unsafe impl Sync for Runtime {}
+
+static RUNTIME: Runtime = Runtime {
+    running: Cell::new(false),
+    tasks: UnsafeCell::new(Vec::new()),
+    waits: UnsafeCell::new(Vec::new()),
+    next_wait_id: Cell::new(0),
+};
+
+impl Runtime {
+    #[allow(clippy::mut_from_ref)]
+    fn tasks(&self) -> &mut Vec<Task> {
+        // The reference never lives across a poll, so it is unique.
+        unsafe { &mut *self.tasks.get() }
+    }
+
+    #[allow(clippy::mut_from_ref)]
+    fn waits(&self) -> &mut Vec<Wait> {
+        // The reference never lives across a poll, so it is unique.
+        unsafe { &mut *self.waits.get() }
+    }
+}
+
+/// An event that a future waits for.
+struct Wait {
+    id: u64,
+    event: Event,
+    waker: Waker,
+    /// Whether the executor has seen the event signaled. Waiting for the
+    /// event clears its signaled state, so the future can't observe it.
+    fired: bool,
+}
+
+/// Wakes a task by setting its flag and signaling [`WAKE_EVENT`]. This is
+/// safe in event notification functions.
+struct Flag(AtomicBool);
+
+impl Flag {
+    fn take(&self) -> bool {
+        self.0.swap(false, Ordering::Acquire)
+    }
+
+    fn is_set(&self) -> bool {
+        self.0.load(Ordering::Acquire)
+    }
+}
+
+impl Wake for Flag {
+    fn wake(self: Arc<Self>) {
+        self.wake_by_ref();
+    }
+
+    fn wake_by_ref(self: &Arc<Self>) {
+        self.0.store(true, Ordering::Release);
+        if let Some(event) = wake_event() {
+            if boot::are_boot_services_active() {
+                let _ = boot::signal_event(&event);
+            }
+        }
+    }
+}
+
+fn wake_event() -> Option<Event> {
+    unsafe { Event::from_ptr(WAKE_EVENT.load(Ordering::Acquire)) }
+}
+
+/// A spawned future, together with its waker.
+struct Task {
+    future: Pin<Box<dyn Future<Output = ()>>>,
+    flag: Arc<Flag>,
+    waker: Waker,
+}
+
+impl Task {
+    fn new(future: Pin<Box<dyn Future<Output = ()>>>) -> Self {
+        // Poll new tasks at least once.
+        let flag = Arc::new(Flag(AtomicBool::new(true)));
+        Self {
+            future,
+            waker: Waker::from(flag.clone()),
+            flag,
+        }
+    }
+
+    /// Polls the task if it has been woken. Returns whether it is done.
+    fn poll(&mut self) -> bool {
+        if !self.flag.take() {
+            return false;
+        }
+        let mut cx = Context::from_waker(&self.waker);
+        self.future.as_mut().poll(&mut cx).is_ready()
+    }
+}
+
+/// Runs `future` to completion, together with the tasks of [`spawn`].
+///
+/// Tasks that haven't finished when `future` completes are kept, and run
+/// again in the next call of `block_on`.
+///
+/// While no task can make progress, the executor waits for the events of
+/// the futures with [`boot::wait_for_event`]. If that is not possible, e.g.
+/// because the TPL is raised, the futures are polled instead.
+///
+/// # Panics
+///
+/// Panics if called from within a future run by `block_on`, or if the
+/// event that wakes the executor can't be created.
+pub fn block_on<F: Future>(future: F) -> F::Output {
+    assert!(
+        !RUNTIME.running.replace(true),
+        "`block_on` can't be called from within a task"
+    );
+    let wake_event = match wake_event() {
+        Some(event) => event,
+        None => {
+            let event =
+                unsafe { boot::create_event(EventType::empty(), Tpl::APPLICATION, None, None) }
+                    .expect("failed to create the wake event of the executor");
+            WAKE_EVENT.store(event.as_ptr(), Ordering::Release);
+            event
+        }
+    };
+
+    let mut future = pin!(future);
+    let flag = Arc::new(Flag(AtomicBool::new(true)));
+    let waker = Waker::from(flag.clone());
+    let mut tasks = core::mem::take(RUNTIME.tasks());
+
+    let output = loop {
+        // Clear the wake event before looking at the flags, so that wakes
+        // from now on end the next wait.
+        let _ = boot::check_event(unsafe { wake_event.unsafe_clone() });
+
+        if flag.take() {
+            if let Poll::Ready(output) = future.as_mut().poll(&mut Context::from_waker(&waker)) {
+                break output;
+            }
+        }
+        tasks.append(RUNTIME.tasks());
+        tasks.retain_mut(|task| !task.poll());
+
+        let woken = flag.is_set()
+            || tasks.iter().any(|task| task.flag.is_set())
+            || !RUNTIME.tasks().is_empty();
+        if !woken {
+            wait(&wake_event);
+        }
+    };
+
+    tasks.append(RUNTIME.tasks());
+    *RUNTIME.tasks() = tasks;
+    RUNTIME.running.set(false);
+    output
+}
+
+/// Waits until the wake event or one of the registered events is signaled.
+fn wait(wake_event: &Event) {
+    let waits = RUNTIME.waits();
+    let pending = waits.iter().filter(|wait| !wait.fired);
+    let mut events: Vec<Event> = core::iter::once(wake_event)
+        .chain(pending.clone().map(|wait| &wait.event))
+        .map(|event| unsafe { event.unsafe_clone() })
+        .collect();
+    let ids: Vec<u64> = pending.map(|wait| wait.id).collect();
+
+    match boot::wait_for_event(&mut events) {
+        Ok(0) => {}
+        Ok(index) => fire(|id| id == ids[index - 1]),
+        // Let the futures check their events themselves.
+        Err(_) => fire(|id| ids.contains(&id)),
+    }
+}
+
+/// Marks the waits selected by `select` as fired and wakes their futures.
+fn fire(select: impl Fn(u64) -> bool) {
+    let wakers: Vec<Waker> = RUNTIME
+        .waits()
+        .iter_mut()
+        .filter(|wait| select(wait.id))
+        .map(|wait| {
+            wait.fired = true;
+            wait.waker.clone()
+        })
+        .collect();
+    // The reference to the waits has ended, so wakers may register waits.
+    for waker in wakers {
+        waker.wake();
+    }
+}
+
+/// Registers that the future with `waker` waits for `event`, or updates the
+/// registration `id`. Returns the ID of the registration.
+///
+/// The event must stay valid until the registration is removed with
+/// [`unregister_wait`].
+pub(super) fn register_wait(id: Option<u64>, event: &Event, waker: &Waker) -> u64 {
+    let waits = RUNTIME.waits();
+    if let Some(wait) = id.and_then(|id| waits.iter_mut().find(|wait| wait.id == id)) {
+        wait.waker.clone_from(waker);
+        return wait.id;
+    }
+    let id = RUNTIME.next_wait_id.get();
+    RUNTIME.next_wait_id.set(id + 1);
+    waits.push(Wait {
+        id,
+        event: unsafe { event.unsafe_clone() },
+        waker: waker.clone(),
+        fired: false,
+    });
+    id
+}
+
+/// Removes the registration `id`. Returns whether the executor has seen its
+/// event signaled.
+pub(super) fn unregister_wait(id: u64) -> bool {
+    let waits = RUNTIME.waits();
+    match waits.iter().position(|wait| wait.id == id) {
+        Some(index) => waits.swap_remove(index).fired,
+        None => false,
+    }
+}
+
+/// Returns whether the executor has seen the event of registration `id`
+/// signaled.
+pub(super) fn has_fired(id: u64) -> bool {
+    RUNTIME
+        .waits()
+        .iter()
+        .any(|wait| wait.id == id && wait.fired)
+}
+
+struct JoinState<T> {
+    output: Option<T>,
+    waker: Option<Waker>,
+}
+
+/// Handle to a task created with [`spawn`]. Awaiting it returns the output
+/// of the task.
+///
+/// Dropping the handle detaches the task: it keeps running, but its output
+/// is dropped.
+pub struct JoinHandle<T> {
+    state: Rc<RefCell<JoinState<T>>>,
+}
+
+impl<T> JoinHandle<T> {
+    /// Returns whether the task has finished.
+    #[must_use]
+    pub fn is_finished(&self) -> bool {
+        self.state.borrow().output.is_some()
+    }
+}
+
+impl<T> Future for JoinHandle<T> {
+    type Output = T;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<T> {
+        let mut state = self.state.borrow_mut();
+        match state.output.take() {
+            Some(output) => Poll::Ready(output),
+            None => {
+                state.waker = Some(cx.waker().clone());
+                Poll::Pending
+            }
+        }
+    }
+}
+
+impl<T> Debug for JoinHandle<T> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.debug_struct("JoinHandle")
+            .field("finished", &self.is_finished())
+            .finish()
+    }
+}
+
+/// Creates a task that runs `future` concurrently with the other tasks.
+///
+/// The task runs while [`block_on`] runs; a task created outside of
+/// `block_on` starts with its next call.
+pub fn spawn<F>(future: F) -> JoinHandle<F::Output>
+where
+    F: Future + 'static,
+    F::Output: 'static,
+{
+    let state = Rc::new(RefCell::new(JoinState {
+        output: None,
+        waker: None,
+    }));
+    let task_state = state.clone();
+    RUNTIME.tasks().push(Task::new(Box::pin(async move {
+        let output = future.await;
+        let mut state = task_state.borrow_mut();
+        state.output = Some(output);
+        if let Some(waker) = state.waker.take() {
+            waker.wake();
+        }
+    })));
+    JoinHandle { state }
+}
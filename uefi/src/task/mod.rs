@@ -0,0 +1,59 @@
+// SPDX-License-Identifier: MIT OR Apache-2.0
+
+//! Cooperative async tasks driven by UEFI events.
+//!
+//! [`block_on`] runs a future to completion. Tasks created with [`spawn`]
+//! run concurrently with it, interleaved at their `.await` points. While no
+//! task can make progress, the executor waits with [`boot::wait_for_event`],
+//! so the processor idles until a timer expires or a device signals an event.
+//!
+//! # Integration with protocols
+//!
+//! Futures of this module are woken by UEFI events, which is how protocol
+//! wrappers can offer `async fn` variants of their functions:
+//!
+//! * [`wait_for_event`] completes when an event of type [`NOTIFY_WAIT`] or
+//!   [`TIMER`] is signaled, e.g. the event of [`Input::wait_for_key_event`].
+//! * [`Completion`] is an event of type [`NOTIFY_SIGNAL`] for the token of a
+//!   non-blocking protocol function, e.g. [`DiskIo2::read_disk_raw`]. It
+//!   completes when the firmware signals that the request is done.
+//! * [`sleep`] and [`timeout`] limit how long a future may take.
+//! * [`select`] races two futures, e.g. a key press against a timer.
+//!
+//! # Example
+//!
+//! ```no_run
+//! use core::time::Duration;
+//! use uefi::task;
+//!
+//! # fn main() -> uefi::Result {
+//! let answer = task::block_on(async {
+//!     let background = task::spawn(async {
+//!         task::sleep(Duration::from_millis(10)).await?;
+//!         uefi::Result::Ok(40)
+//!     });
+//!     task::sleep(Duration::from_millis(5)).await?;
+//!     uefi::Result::Ok(background.await? + 2)
+//! })?;
+//! assert_eq!(answer, 42);
+//! # Ok(())
+//! # }
+//! ```
+//!
+//! [`boot::wait_for_event`]: crate::boot::wait_for_event
+//! [`NOTIFY_WAIT`]: crate::boot::EventType::NOTIFY_WAIT
+//! [`NOTIFY_SIGNAL`]: crate::boot::EventType::NOTIFY_SIGNAL
+//! [`TIMER`]: crate::boot::EventType::TIMER
+//! [`Input::wait_for_key_event`]: crate::proto::console::text::Input::wait_for_key_event
+//! [`DiskIo2::read_disk_raw`]: crate::proto::media::disk::DiskIo2::read_disk_raw
+
+mod event;
+mod executor;
+mod select;
+mod time;
+
+pub(crate) use event::request;
+pub use event::{Completion, WaitForEvent, wait_for_event};
+pub use executor::{JoinHandle, block_on, spawn};
+pub use select::{Either, Select, select};
+pub use time::{Sleep, Timeout, YieldNow, sleep, timeout, yield_now};
@@ -0,0 +1,75 @@
+// SPDX-License-Identifier: MIT OR Apache-2.0
+
+//! Racing of futures.
+
+use core::future::Future;
+use core::pin::Pin;
+use core::task::{Context, Poll};
+
+/// Output of [`select`]: the output of the future that completed first.
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Hash)]
+pub enum Either<A, B> {
+    /// The first future completed first.
+    Left(A),
+    /// The second future completed first.
+    Right(B),
+}
+
+/// Future of [`select`].
+#[derive(Debug)]
+#[must_use = "futures do nothing unless awaited"]
+pub struct Select<A, B> {
+    a: A,
+    b: B,
+}
+
+impl<A: Future, B: Future> Future for Select<A, B> {
+    type Output = Either<A::Output, B::Output>;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        // Safety: the futures are pinned with `self` and never moved out of it.
+        let this = unsafe { self.get_unchecked_mut() };
+        if let Poll::Ready(output) = unsafe { Pin::new_unchecked(&mut this.a) }.poll(cx) {
+            return Poll::Ready(Either::Left(output));
+        }
+        if let Poll::Ready(output) = unsafe { Pin::new_unchecked(&mut this.b) }.poll(cx) {
+            return Poll::Ready(Either::Right(output));
+        }
+        Poll::Pending
+    }
+}
+
+/// Returns a future that runs `a` and `b` concurrently, and completes with
+/// the output of the one that completes first. The other future is dropped
+/// together with the returned future.
+///
+/// If both futures are ready in the same poll, `a` wins. Nest calls to race
+/// more than two futures.
+///
+/// # Example
+///
+/// Wait for a key press, but at most 5 seconds:
+///
+/// ```no_run
+/// use core::time::Duration;
+/// use uefi::proto::console::text::{Input, Key};
+/// use uefi::task::{self, Either};
+///
+/// fn wait_for_key(input: &mut Input) -> uefi::Result<Option<Key>> {
+///     let key_event = input.wait_for_key_event().unwrap();
+///     let pressed = task::block_on(task::select(
+///         task::wait_for_event(&key_event),
+///         task::sleep(Duration::from_secs(5)),
+///     ));
+///     match pressed {
+///         Either::Left(result) => {
+///             result?;
+///             input.read_key()
+///         }
+///         Either::Right(result) => result.map(|()| None),
+///     }
+/// }
+/// ```
+pub const fn select<A: Future, B: Future>(a: A, b: B) -> Select<A, B> {
+    Select { a, b }
+}
@@ -0,0 +1,147 @@
+// SPDX-License-Identifier: MIT OR Apache-2.0
+
+//! Futures for timers and yielding.
+
+use super::event::EventWait;
+use crate::boot::{self, EventType, TimerTrigger, Tpl};
+use crate::{Event, Result, Status};
+use core::future::Future;
+use core::pin::Pin;
+use core::task::{Context, Poll};
+use core::time::Duration;
+
+/// Future of [`sleep`].
+#[derive(Debug)]
+#[must_use = "futures do nothing unless awaited"]
+pub struct Sleep {
+    duration: Duration,
+    /// Timer event, created on the first poll.
+    timer: Option<Event>,
+    wait: EventWait,
+}
+
+impl Sleep {
+    fn start(&mut self) -> Result<&Event> {
+        if self.timer.is_none() {
+            let timer =
+                unsafe { boot::create_event(EventType::TIMER, Tpl::APPLICATION, None, None) }?;
+            // The timer counts in units of 100ns.
+            let ticks = u64::try_from(self.duration.as_nanos() / 100).unwrap_or(u64::MAX);
+            let result = boot::set_timer(&timer, TimerTrigger::Relative(ticks));
+            if let Err(err) = result {
+                let _ = boot::close_event(timer);
+                return Err(err);
+            }
+            self.timer = Some(timer);
+        }
+        Ok(self.timer.as_ref().unwrap())
+    }
+}
+
+impl Future for Sleep {
+    type Output = Result;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Result> {
+        let this = self.get_mut();
+        match this.start() {
+            Ok(timer) => {
+                let timer = unsafe { timer.unsafe_clone() };
+                this.wait.poll(&timer, cx)
+            }
+            Err(err) => Poll::Ready(Err(err)),
+        }
+    }
+}
+
+impl Drop for Sleep {
+    fn drop(&mut self) {
+        self.wait.unregister();
+        if let Some(timer) = self.timer.take() {
+            // Ignore errors, since we can't propagate them from `drop`.
+            let _ = boot::close_event(timer);
+        }
+    }
+}
+
+/// Returns a future that completes after `duration`.
+///
+/// The timer starts when the future is first polled. The resolution depends
+/// on the timer tick of the firmware, which is often 10ms.
+///
+/// The future fails with the errors of [`boot::create_event`] and
+/// [`boot::set_timer`].
+pub fn sleep(duration: Duration) -> Sleep {
+    Sleep {
+        duration,
+        timer: None,
+        wait: EventWait::default(),
+    }
+}
+
+/// Future of [`timeout`].
+#[derive(Debug)]
+#[must_use = "futures do nothing unless awaited"]
+pub struct Timeout<F> {
+    future: F,
+    sleep: Sleep,
+}
+
+impl<F: Future> Future for Timeout<F> {
+    type Output = Result<F::Output>;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        // Safety: `future` is pinned with `self` and never moved out of it.
+        let this = unsafe { self.get_unchecked_mut() };
+        let future = unsafe { Pin::new_unchecked(&mut this.future) };
+        if let Poll::Ready(output) = future.poll(cx) {
+            return Poll::Ready(Ok(output));
+        }
+        match Pin::new(&mut this.sleep).poll(cx) {
+            Poll::Ready(Ok(())) => Poll::Ready(Err(Status::TIMEOUT.into())),
+            Poll::Ready(Err(err)) => Poll::Ready(Err(err)),
+            Poll::Pending => Poll::Pending,
+        }
+    }
+}
+
+/// Returns a future that runs `future`, but gives up after `duration`.
+///
+/// # Errors
+///
+/// * [`Status::TIMEOUT`]: `future` didn't complete in time. It is dropped
+///   together with the returned future.
+/// * Errors of [`sleep`].
+pub fn timeout<F: Future>(duration: Duration, future: F) -> Timeout<F> {
+    Timeout {
+        future,
+        sleep: sleep(duration),
+    }
+}
+
+/// Future of [`yield_now`].
+#[derive(Debug, Default)]
+#[must_use = "futures do nothing unless awaited"]
+pub struct YieldNow {
+    yielded: bool,
+}
+
+impl Future for YieldNow {
+    type Output = ();
+
+    fn poll(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<()> {
+        if self.yielded {
+            return Poll::Ready(());
+        }
+        self.yielded = true;
+        cx.waker().wake_by_ref();
+        Poll::Pending
+    }
+}
+
+/// Returns a future that lets the other tasks run before it completes.
+///
+/// Long computations can await it now and then, so that other tasks are not
+/// starved.
+pub const fn yield_now() -> YieldNow {
+    YieldNow { yielded: false }
+}
@@ -0,0 +1,110 @@
+// SPDX-License-Identifier: MIT OR Apache-2.0
+
+//! Registration of tests that run inside UEFI.
+//!
+//! Functions marked with [`uefi_test`] are registered as a [`TestCase`] in
+//! the `.uefitst` section of the image, and [`test_cases`] returns all
+//! registered tests of the image. A test harness can then run them one by
+//! one, e.g. by starting a new instance of the image for each test, so that
+//! a panic only aborts that instance. See [`PanicAction::Exit`].
+//!
+//! The tests are collected with grouped sections of PE images, so they are
+//! only found in images built for UEFI targets.
+//!
+//! # Example
+//!
+//! ```
+//! use uefi::test_harness::{self, uefi_test};
+//!
+//! #[uefi_test]
+//! fn test_addition() {
+//!     assert_eq!(1 + 1, 2);
+//! }
+//!
+//! #[uefi_test(should_panic)]
+//! fn test_overflow() {
+//!     let _ = u8::MAX.checked_add(1).unwrap();
+//! }
+//!
+//! for test in test_harness::test_cases() {
+//!     uefi::println!("found test {}", test.name());
+//! }
+//! ```
+//!
+//! [`PanicAction::Exit`]: crate::helpers::PanicAction::Exit
+
+pub use uefi_macros::uefi_test;
+
+/// Test registered with [`uefi_test`].
+#[derive(Clone, Copy, Debug)]
+#[repr(C)]
+pub struct TestCase {
+    name: &'static str,
+    func: fn(),
+    should_panic: bool,
+}
+
+impl TestCase {
+    /// Creates a test case. This is public but hidden; it is used in the
+    /// `uefi_test` macro.
+    #[doc(hidden)]
+    #[must_use]
+    pub const fn new(name: &'static str, func: fn(), should_panic: bool) -> Self {
+        Self {
+            name,
+            func,
+            should_panic,
+        }
+    }
+
+    /// Returns the name of the test, which is the path of the test function,
+    /// including the crate name.
+    #[must_use]
+    pub const fn name(&self) -> &'static str {
+        self.name
+    }
+
+    /// Returns whether the test is expected to panic.
+    #[must_use]
+    pub const fn should_panic(&self) -> bool {
+        self.should_panic
+    }
+
+    /// Runs the test.
+    pub fn run(&self) {
+        (self.func)();
+    }
+}
+
+/// Returns the tests registered with [`uefi_test`] in the current image, in
+/// no particular order.
+///
+/// Returns an empty slice if the image isn't built for a UEFI target.
+#[must_use]
+#[allow(clippy::missing_const_for_fn)]
+pub fn test_cases() -> &'static [TestCase] {
+    #[cfg(target_os = "uefi")]
+    {
+        use core::{ptr, slice};
+
+        // The linker sorts the grouped sections `.uefitst$*` by the part
+        // after the `$` and merges them, so the registered tests in
+        // `.uefitst$b` end up between these two markers.
+        #[used]
+        #[unsafe(link_section = ".uefitst$a")]
+        static START: [TestCase; 0] = [];
+        #[used]
+        #[unsafe(link_section = ".uefitst$c")]
+        static END: [TestCase; 0] = [];
+
+        let start = ptr::addr_of!(START).cast::<TestCase>();
+        let end = ptr::addr_of!(END).cast::<TestCase>();
+        let len = (end.addr() - start.addr()) / size_of::<TestCase>();
+        // SAFETY: the section between the markers only contains test cases.
+        unsafe { slice::from_raw_parts(start, len) }
+    }
+    #[cfg(not(target_os = "uefi"))]
+    {
+        &[]
+    }
+}
@@ -0,0 +1,155 @@
+// SPDX-License-Identifier: MIT OR Apache-2.0
+
+use super::{Action, Frame, Response, Theme, Widget};
+use crate::Result;
+use crate::proto::console::text::Key;
+
+/// A list of entries to choose from.
+///
+/// The selection is moved with the arrow keys, Home, End, Page Up and
+/// Page Down. Enter confirms the selected entry and Escape cancels the menu.
+/// The menu scrolls if not all entries fit on the screen.
+#[derive(Debug)]
+pub struct Menu<'a> {
+    title: &'a str,
+    items: &'a [&'a str],
+    selected: usize,
+    top: usize,
+    page: usize,
+}
+
+impl<'a> Menu<'a> {
+    /// Creates a menu with the first entry selected.
+    #[must_use]
+    pub const fn new(title: &'a str, items: &'a [&'a str]) -> Self {
+        Self {
+            title,
+            items,
+            selected: 0,
+            top: 0,
+            page: 1,
+        }
+    }
+
+    /// Returns the index of the selected entry.
+    #[must_use]
+    pub const fn selected(&self) -> usize {
+        self.selected
+    }
+
+    /// Selects the entry at `index`, or the last one if `index` is out of
+    /// range.
+    pub const fn set_selected(&mut self, index: usize) {
+        self.selected = if index < self.items.len() {
+            index
+        } else {
+            self.items.len().saturating_sub(1)
+        };
+    }
+}
+
+impl Widget for Menu<'_> {
+    type Output = usize;
+
+    fn draw(&mut self, frame: &mut Frame<'_>, theme: &Theme) -> Result {
+        let width = self
+            .items
+            .iter()
+            .map(|item| item.chars().count())
+            .chain([self.title.chars().count() + 2])
+            .max()
+            .unwrap_or(0)
+            + 4;
+        let rect = frame.centered(width, self.items.len() + 2);
+        frame.draw_box(rect, self.title, theme)?;
+
+        let inner = rect.inner();
+        self.page = inner.height.max(1);
+        if self.selected < self.top {
+            self.top = self.selected;
+        } else if self.selected >= self.top + self.page {
+            self.top = self.selected + 1 - self.page;
+        }
+
+        for (i, item) in self
+            .items
+            .iter()
+            .enumerate()
+            .skip(self.top)
+            .take(inner.height)
+        {
+            let style = if i == self.selected {
+                theme.highlight
+            } else {
+                theme.text
+            };
+            frame.set_style(style)?;
+            frame.write_at(inner.column, inner.row + i - self.top, " ", 1)?;
+            frame.write_at(
+                inner.column + 1,
+                inner.row + i - self.top,
+                item,
+                inner.width.saturating_sub(1),
+            )?;
+        }
+        Ok(())
+    }
+
+    fn handle_key(&mut self, key: Key) -> Response<usize> {
+        let last = self.items.len().saturating_sub(1);
+        match Action::from(key) {
+            Action::Up => self.selected = self.selected.saturating_sub(1),
+            Action::Down => self.selected = (self.selected + 1).min(last),
+            Action::Home => self.selected = 0,
+            Action::End => self.selected = last,
+            Action::PageUp => self.selected = self.selected.saturating_sub(self.page),
+            Action::PageDown => self.selected = (self.selected + self.page).min(last),
+            Action::Enter if !self.items.is_empty() => return Response::Done(self.selected),
+            Action::Escape => return Response::Cancelled,
+            _ => {}
+        }
+        Response::Continue
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::proto::console::text::ScanCode;
+    use crate::tui::tests::{TestTerminal, printable, run_keys};
+
+    #[test]
+    fn test_menu() {
+        let mut term = TestTerminal::new(40, 10);
+        let items = ["One", "Two", "Three"];
+        let mut menu = Menu::new("Pick", &items);
+        let keys = [
+            Key::Special(ScanCode::DOWN),
+            Key::Special(ScanCode::DOWN),
+            Key::Special(ScanCode::DOWN),
+            Key::Special(ScanCode::UP),
+            printable('\r'),
+        ];
+        assert_eq!(run_keys(&mut menu, &mut term, &keys), Some(1));
+        assert_eq!(term.line(2), "               +-Pick---+");
+        assert_eq!(term.line(4), "               | Two    |");
+
+        let keys = [Key::Special(ScanCode::END), Key::Special(ScanCode::ESCAPE)];
+        assert_eq!(run_keys(&mut menu, &mut term, &keys), None);
+        assert_eq!(menu.selected(), 2);
+    }
+
+    #[test]
+    fn test_menu_scrolling() {
+        let mut term = TestTerminal::new(20, 6);
+        let items = ["0", "1", "2", "3", "4", "5", "6", "7", "8", "9"];
+        let mut menu = Menu::new("", &items);
+        let keys = [Key::Special(ScanCode::PAGE_DOWN), printable('\r')];
+        assert_eq!(run_keys(&mut menu, &mut term, &keys), Some(3));
+        // Three rows fit between the border, with the selection at the end.
+        assert!(term.contains("| 1"));
+        assert!(term.contains("| 3"));
+        assert!(!term.contains("| 0"));
+        assert!(!term.contains("| 4"));
+    }
+}
@@ -0,0 +1,169 @@
+// SPDX-License-Identifier: MIT OR Apache-2.0
+
+use super::{Action, Frame, Response, Theme, Widget};
+use crate::Result;
+use crate::proto::console::text::Key;
+use alloc::format;
+use alloc::vec::Vec;
+
+/// Maximum width of the message text.
+const MAX_TEXT_WIDTH: usize = 60;
+
+/// A message with a row of buttons below it.
+///
+/// The message is wrapped at word boundaries, and line breaks in it are
+/// kept. The selected button is changed with the left and right arrow keys
+/// and Tab. Enter confirms the selected button and Escape cancels the
+/// message box.
+#[derive(Debug)]
+pub struct MessageBox<'a> {
+    title: &'a str,
+    text: &'a str,
+    buttons: &'a [&'a str],
+    selected: usize,
+}
+
+impl<'a> MessageBox<'a> {
+    /// Creates a message box with a single "OK" button.
+    #[must_use]
+    pub const fn new(title: &'a str, text: &'a str) -> Self {
+        Self {
+            title,
+            text,
+            buttons: &["OK"],
+            selected: 0,
+        }
+    }
+
+    /// Replaces the buttons, selecting the first one.
+    #[must_use]
+    pub const fn with_buttons(mut self, buttons: &'a [&'a str]) -> Self {
+        self.buttons = buttons;
+        self.selected = 0;
+        self
+    }
+
+    /// Returns the index of the selected button.
+    #[must_use]
+    pub const fn selected(&self) -> usize {
+        self.selected
+    }
+}
+
+impl Widget for MessageBox<'_> {
+    type Output = usize;
+
+    fn draw(&mut self, frame: &mut Frame<'_>, theme: &Theme) -> Result {
+        let (columns, _) = frame.size();
+        let max_width = MAX_TEXT_WIDTH.min(columns.saturating_sub(4)).max(1);
+        let lines = wrap(self.text, max_width);
+        let buttons_width = self
+            .buttons
+            .iter()
+            .map(|button| button.chars().count() + 5)
+            .sum::<usize>()
+            .saturating_sub(1);
+        let width = lines
+            .iter()
+            .map(|line| line.chars().count())
+            .chain([buttons_width, self.title.chars().count() + 2])
+            .max()
+            .unwrap_or(0);
+
+        let rect = frame.centered(width + 4, lines.len() + 4);
+        frame.draw_box(rect, self.title, theme)?;
+
+        let inner = rect.inner();
+        for (row, line) in (inner.row..).zip(lines.iter().take(inner.height.saturating_sub(2))) {
+            frame.write_at(inner.column + 1, row, line, inner.width.saturating_sub(2))?;
+        }
+
+        let row = inner.row + inner.height.saturating_sub(1);
+        let mut column = inner.column + inner.width.saturating_sub(buttons_width) / 2;
+        for (i, button) in self.buttons.iter().enumerate() {
+            let label = format!("< {button} >");
+            let style = if i == self.selected {
+                theme.highlight
+            } else {
+                theme.text
+            };
+            frame.set_style(style)?;
+            frame.write_at(column, row, &label, label.chars().count())?;
+            column += label.chars().count() + 1;
+        }
+        Ok(())
+    }
+
+    fn handle_key(&mut self, key: Key) -> Response<usize> {
+        let count = self.buttons.len().max(1);
+        match Action::from(key) {
+            Action::Left => self.selected = (self.selected + count - 1) % count,
+            Action::Right | Action::Tab => self.selected = (self.selected + 1) % count,
+            Action::Enter => return Response::Done(self.selected),
+            Action::Escape => return Response::Cancelled,
+            _ => {}
+        }
+        Response::Continue
+    }
+}
+
+/// Splits `text` into lines of at most `width` characters, breaking at
+/// spaces where possible.
+fn wrap(text: &str, width: usize) -> Vec<&str> {
+    let mut lines = Vec::new();
+    for mut line in text.lines() {
+        loop {
+            let Some((end, _)) = line.char_indices().nth(width) else {
+                lines.push(line.trim_end());
+                break;
+            };
+            let space = if line[end..].starts_with(' ') {
+                Some(end)
+            } else {
+                line[..end].rfind(' ').filter(|&space| space > 0)
+            };
+            let (head, tail) = match space {
+                Some(space) => (&line[..space], &line[space + 1..]),
+                None => (&line[..end], &line[end..]),
+            };
+            lines.push(head.trim_end());
+            line = tail.trim_start();
+        }
+    }
+    lines
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::proto::console::text::ScanCode;
+    use crate::tui::tests::{TestTerminal, printable, run_keys};
+
+    #[test]
+    fn test_wrap() {
+        assert_eq!(wrap("one two three", 7), ["one two", "three"]);
+        assert_eq!(wrap("abcdefghij", 4), ["abcd", "efgh", "ij"]);
+        assert_eq!(wrap("a\n\nb", 4), ["a", "", "b"]);
+        assert_eq!(wrap("", 4), [] as [&str; 0]);
+    }
+
+    #[test]
+    fn test_message_box() {
+        let mut term = TestTerminal::new(24, 12);
+        let mut message =
+            MessageBox::new("Error", "Disk not found. Retry?").with_buttons(&["Yes", "No"]);
+        let keys = [
+            Key::Special(ScanCode::RIGHT),
+            printable('\t'),
+            Key::Special(ScanCode::LEFT),
+            printable('\r'),
+        ];
+        assert_eq!(run_keys(&mut message, &mut term, &keys), Some(1));
+        assert_eq!(term.line(2), "  +-Error-----------+");
+        assert_eq!(term.line(3), "  | Disk not found. |");
+        assert_eq!(term.line(4), "  | Retry?          |");
+        assert_eq!(term.line(5), "  |                 |");
+        assert_eq!(term.line(6), "  | < Yes > < No >  |");
+        assert_eq!(term.line(7), "  +-----------------+");
+    }
+}
@@ -0,0 +1,731 @@
+// SPDX-License-Identifier: MIT OR Apache-2.0
+
+//! Text-mode user interface widgets, e.g. for boot menus.
+//!
+//! Widgets draw themselves onto a [`Terminal`], which is implemented for the
+//! [`Output`] protocol and for the [`FramebufferConsole`]. Interactive
+//! widgets are driven by key presses with [`run`], which returns once the
+//! user confirmed or cancelled the widget. The screen is cleared and the
+//! widget redrawn whenever the size of the terminal changes, e.g. because
+//! another text mode was set.
+//!
+//! The following widgets are provided:
+//! * [`Menu`]: a list of entries to choose from.
+//! * [`TextInput`]: a single-line text field.
+//! * [`MessageBox`]: a message with a row of buttons.
+//! * [`ProgressBar`]: a non-interactive progress indicator.
+//!
+//! Other widgets can be written by implementing [`Widget`].
+//!
+//! # Example
+//!
+//! ```no_run
+//! use uefi::proto::console::text::{Input, Output};
+//! use uefi::tui::{self, Menu, Theme};
+//! use uefi::{boot, Result};
+//!
+//! fn choose_entry() -> Result<Option<usize>> {
+//!     let input = boot::get_handle_for_protocol::<Input>()?;
+//!     let mut input = boot::open_protocol_exclusive::<Input>(input)?;
+//!     let output = boot::get_handle_for_protocol::<Output>()?;
+//!     let mut output = boot::open_protocol_exclusive::<Output>(output)?;
+//!
+//!     let mut menu = Menu::new("Boot entries", &["Linux", "Windows", "Firmware setup"]);
+//!     tui::run(&mut menu, &mut *output, &mut input, &Theme::default())
+//! }
+//! ```
+//!
+//! [`FramebufferConsole`]: crate::console::fbtext::FramebufferConsole
+//! [`Output`]: crate::proto::console::text::Output
+
+mod menu;
+mod message;
+mod progress;
+mod text_input;
+
+pub use menu::Menu;
+pub use message::MessageBox;
+pub use progress::ProgressBar;
+pub use text_input::TextInput;
+
+use crate::boot::{self, EventType, TimerTrigger, Tpl};
+use crate::console::fbtext::{Canvas, FramebufferConsole};
+use crate::proto::console::text::{Color, Input, Key, Output, ScanCode};
+use crate::{Result, ResultExt, Status};
+use alloc::string::String;
+use core::fmt::Write;
+
+/// Interval in which [`run`] checks the terminal size, in units of 100ns.
+const RESIZE_POLL_INTERVAL: u64 = 5_000_000;
+
+/// A character grid that widgets are drawn on.
+///
+/// Writing to the last cell of the last row may scroll the screen, so
+/// widgets never draw there.
+pub trait Terminal {
+    /// Returns the size of the terminal as `(columns, rows)`.
+    fn size(&self) -> Result<(usize, usize)>;
+
+    /// Clears the terminal with the current background color.
+    fn clear(&mut self) -> Result;
+
+    /// Moves the cursor to `(column, row)`.
+    fn move_to(&mut self, column: usize, row: usize) -> Result;
+
+    /// Sets the colors for subsequent output.
+    fn set_color(&mut self, foreground: Color, background: Color) -> Result;
+
+    /// Writes a string at the cursor position. The string doesn't contain
+    /// control characters.
+    fn write_str(&mut self, s: &str) -> Result;
+
+    /// Shows or hides the cursor.
+    fn show_cursor(&mut self, visible: bool) -> Result;
+}
+
+impl Terminal for Output {
+    fn size(&self) -> Result<(usize, usize)> {
+        let mode = self.current_mode()?.ok_or(Status::UNSUPPORTED)?;
+        Ok((mode.columns(), mode.rows()))
+    }
+
+    fn clear(&mut self) -> Result {
+        Self::clear(self)
+    }
+
+    fn move_to(&mut self, column: usize, row: usize) -> Result {
+        self.set_cursor_position(column, row)
+    }
+
+    fn set_color(&mut self, foreground: Color, background: Color) -> Result {
+        Self::set_color(self, foreground, background)
+    }
+
+    fn write_str(&mut self, s: &str) -> Result {
+        Write::write_str(self, s).map_err(|_| Status::DEVICE_ERROR.into())
+    }
+
+    fn show_cursor(&mut self, visible: bool) -> Result {
+        // Not all consoles support hiding the cursor, which is only cosmetic.
+        match self.enable_cursor(visible) {
+            Err(err) if err.status() == Status::UNSUPPORTED => Ok(()),
+            result => result,
+        }
+    }
+}
+
+impl<C: Canvas> Terminal for FramebufferConsole<C> {
+    fn size(&self) -> Result<(usize, usize)> {
+        Ok((self.columns(), self.rows()))
+    }
+
+    fn clear(&mut self) -> Result {
+        Self::clear(self)
+    }
+
+    fn move_to(&mut self, column: usize, row: usize) -> Result {
+        self.set_cursor_position(column, row)
+    }
+
+    fn set_color(&mut self, foreground: Color, background: Color) -> Result {
+        Self::set_color(self, foreground, background);
+        Ok(())
+    }
+
+    fn write_str(&mut self, s: &str) -> Result {
+        Self::write_str(self, s)
+    }
+
+    fn show_cursor(&mut self, visible: bool) -> Result {
+        self.enable_cursor(visible)
+    }
+}
+
+/// Foreground and background color of text.
+#[derive(Clone, Copy, Debug)]
+pub struct Style {
+    /// Color of the text.
+    pub foreground: Color,
+    /// Color behind the text. Must be one of the first eight [`Color`]s on
+    /// the [`Output`] protocol.
+    ///
+    /// [`Output`]: crate::proto::console::text::Output
+    pub background: Color,
+}
+
+impl Style {
+    /// Creates a style from its colors.
+    #[must_use]
+    pub const fn new(foreground: Color, background: Color) -> Self {
+        Self {
+            foreground,
+            background,
+        }
+    }
+}
+
+/// Characters used to draw the border of a widget.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct Border {
+    /// Top and bottom edge.
+    pub horizontal: char,
+    /// Left and right edge.
+    pub vertical: char,
+    /// Top left corner.
+    pub top_left: char,
+    /// Top right corner.
+    pub top_right: char,
+    /// Bottom left corner.
+    pub bottom_left: char,
+    /// Bottom right corner.
+    pub bottom_right: char,
+}
+
+impl Border {
+    /// A border drawn with ASCII characters, which every console can show.
+    pub const ASCII: Self = Self {
+        horizontal: '-',
+        vertical: '|',
+        top_left: '+',
+        top_right: '+',
+        bottom_left: '+',
+        bottom_right: '+',
+    };
+
+    /// A border drawn with box drawing characters. These are not available
+    /// on the [`FramebufferConsole`].
+    ///
+    /// [`FramebufferConsole`]: crate::console::fbtext::FramebufferConsole
+    pub const LINE: Self = Self {
+        horizontal: '─',
+        vertical: '│',
+        top_left: '┌',
+        top_right: '┐',
+        bottom_left: '└',
+        bottom_right: '┘',
+    };
+}
+
+/// Colors and border used to draw widgets.
+#[derive(Clone, Copy, Debug)]
+pub struct Theme {
+    /// Style of the screen behind the widgets.
+    pub screen: Style,
+    /// Style of the text within widgets.
+    pub text: Style,
+    /// Style of the selected entry or button, and of text fields.
+    pub highlight: Style,
+    /// Style of the border and title of widgets.
+    pub border: Style,
+    /// Characters of the border of widgets.
+    pub border_chars: Border,
+}
+
+impl Default for Theme {
+    fn default() -> Self {
+        Self {
+            screen: Style::new(Color::LightGray, Color::Black),
+            text: Style::new(Color::Black, Color::LightGray),
+            highlight: Style::new(Color::White, Color::Blue),
+            border: Style::new(Color::Blue, Color::LightGray),
+            border_chars: Border::ASCII,
+        }
+    }
+}
+
+/// A rectangular area of the terminal, in character cells.
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub struct Rect {
+    /// Column of the left edge.
+    pub column: usize,
+    /// Row of the top edge.
+    pub row: usize,
+    /// Number of columns.
+    pub width: usize,
+    /// Number of rows.
+    pub height: usize,
+}
+
+impl Rect {
+    /// Creates a rectangle from its position and size.
+    #[must_use]
+    pub const fn new(column: usize, row: usize, width: usize, height: usize) -> Self {
+        Self {
+            column,
+            row,
+            width,
+            height,
+        }
+    }
+
+    /// Returns a rectangle of at most `width` x `height` cells centered on a
+    /// screen of `(columns, rows)`.
+    #[must_use]
+    pub fn centered(width: usize, height: usize, screen: (usize, usize)) -> Self {
+        let width = width.min(screen.0);
+        let height = height.min(screen.1);
+        Self::new(
+            (screen.0 - width) / 2,
+            (screen.1 - height) / 2,
+            width,
+            height,
+        )
+    }
+
+    /// Returns the area within a border of one cell.
+    #[must_use]
+    pub const fn inner(&self) -> Self {
+        Self::new(
+            self.column + 1,
+            self.row + 1,
+            self.width.saturating_sub(2),
+            self.height.saturating_sub(2),
+        )
+    }
+}
+
+/// Result of handling a key press in a [`Widget`].
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum Response<T> {
+    /// The widget is still active.
+    Continue,
+    /// The user confirmed the widget with the given result.
+    Done(T),
+    /// The user cancelled the widget.
+    Cancelled,
+}
+
+/// A user interface element.
+pub trait Widget {
+    /// Result of the widget once the user confirmed it.
+    type Output;
+
+    /// Draws the widget. Widgets draw over whatever is on the screen, and
+    /// have to fill all of their area.
+    fn draw(&mut self, frame: &mut Frame<'_>, theme: &Theme) -> Result;
+
+    /// Handles a key press. The widget is redrawn afterwards.
+    ///
+    /// The default implementation ignores all keys.
+    fn handle_key(&mut self, key: Key) -> Response<Self::Output> {
+        let _ = key;
+        Response::Continue
+    }
+}
+
+/// A [`Terminal`] being drawn on, which clips all output to the screen.
+pub struct Frame<'term> {
+    term: &'term mut dyn Terminal,
+    size: (usize, usize),
+    cursor: Option<(usize, usize)>,
+}
+
+impl<'term> Frame<'term> {
+    /// Creates a frame for drawing on `term`.
+    pub fn new(term: &'term mut dyn Terminal) -> Result<Self> {
+        let size = term.size()?;
+        Ok(Self {
+            term,
+            size,
+            cursor: None,
+        })
+    }
+
+    /// Returns the size of the screen as `(columns, rows)`.
+    #[must_use]
+    pub const fn size(&self) -> (usize, usize) {
+        self.size
+    }
+
+    /// Returns a rectangle of at most `width` x `height` cells centered on
+    /// the screen. The last row is left out, as it can't be drawn on
+    /// completely.
+    #[must_use]
+    pub fn centered(&self, width: usize, height: usize) -> Rect {
+        Rect::centered(width, height, (self.size.0, self.size.1.saturating_sub(1)))
+    }
+
+    /// Sets the style of subsequent output.
+    pub fn set_style(&mut self, style: Style) -> Result {
+        self.term.set_color(style.foreground, style.background)
+    }
+
+    /// Writes `text` at `(column, row)`, truncated or padded with spaces to
+    /// `width` characters.
+    pub fn write_at(&mut self, column: usize, row: usize, text: &str, width: usize) -> Result {
+        let (columns, rows) = self.size;
+        if column >= columns || row >= rows {
+            return Ok(());
+        }
+        let mut width = width.min(columns - column);
+        if row + 1 == rows && column + width == columns {
+            width -= 1;
+        }
+        if width == 0 {
+            return Ok(());
+        }
+
+        let mut line = String::with_capacity(width);
+        line.extend(
+            text.chars()
+                .map(|c| if c.is_control() { ' ' } else { c })
+                .chain(core::iter::repeat(' '))
+                .take(width),
+        );
+        self.term.move_to(column, row)?;
+        self.term.write_str(&line)
+    }
+
+    /// Fills `rect` with spaces in the current style.
+    pub fn fill(&mut self, rect: Rect) -> Result {
+        for row in rect.row..rect.row + rect.height {
+            self.write_at(rect.column, row, "", rect.width)?;
+        }
+        Ok(())
+    }
+
+    /// Draws the border of `rect` with `title` in its top edge, and fills
+    /// the inside in the text style of the theme.
+    pub fn draw_box(&mut self, rect: Rect, title: &str, theme: &Theme) -> Result {
+        if rect.width < 2 || rect.height < 2 {
+            return Ok(());
+        }
+        let chars = theme.border_chars;
+        let edge = |left, right| {
+            let mut line = String::with_capacity(rect.width);
+            line.push(left);
+            line.extend(core::iter::repeat_n(chars.horizontal, rect.width - 2));
+            line.push(right);
+            line
+        };
+        let top = edge(chars.top_left, chars.top_right);
+        let bottom = edge(chars.bottom_left, chars.bottom_right);
+        let mut vertical = [0; 4];
+        let vertical = chars.vertical.encode_utf8(&mut vertical);
+
+        self.set_style(theme.border)?;
+        self.write_at(rect.column, rect.row, &top, rect.width)?;
+        let last = rect.row + rect.height - 1;
+        for row in rect.row + 1..last {
+            self.write_at(rect.column, row, vertical, 1)?;
+            self.write_at(rect.column + rect.width - 1, row, vertical, 1)?;
+        }
+        self.write_at(rect.column, last, &bottom, rect.width)?;
+
+        if !title.is_empty() && rect.width > 4 {
+            let width = title.chars().count().min(rect.width - 4);
+            self.write_at(rect.column + 2, rect.row, title, width)?;
+        }
+
+        self.set_style(theme.text)?;
+        self.fill(rect.inner())
+    }
+
+    /// Shows the cursor at `(column, row)` once drawing is done. Otherwise,
+    /// the cursor is hidden.
+    pub const fn set_cursor(&mut self, column: usize, row: usize) {
+        self.cursor = Some((column, row));
+    }
+
+    fn finish(self) -> Result {
+        match self.cursor {
+            Some((column, row)) => {
+                self.term.move_to(column, row)?;
+                self.term.show_cursor(true)
+            }
+            None => self.term.show_cursor(false),
+        }
+    }
+}
+
+impl core::fmt::Debug for Frame<'_> {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.debug_struct("Frame")
+            .field("size", &self.size)
+            .field("cursor", &self.cursor)
+            .finish_non_exhaustive()
+    }
+}
+
+/// Draws `widget` on `term` and passes key presses from `input` to it until
+/// the user confirms or cancels it.
+///
+/// Returns the result of the widget, or `None` if it was cancelled. The
+/// screen is cleared first and whenever the size of the terminal changes.
+///
+/// # Errors
+/// * [`Status::UNSUPPORTED`]: `input` provides no event to wait for keys.
+/// * Errors of the terminal, of `input`, or of the timer used to detect a
+///   change of the terminal size.
+pub fn run<W: Widget + ?Sized>(
+    widget: &mut W,
+    term: &mut dyn Terminal,
+    input: &mut Input,
+    theme: &Theme,
+) -> Result<Option<W::Output>> {
+    let timer = unsafe { boot::create_event(EventType::TIMER, Tpl::APPLICATION, None, None) }?;
+    let result =
+        boot::set_timer(&timer, TimerTrigger::Periodic(RESIZE_POLL_INTERVAL)).and_then(|()| {
+            event_loop(widget, term, theme, || {
+                if let Some(key) = input.read_key()? {
+                    return Ok(Some(key));
+                }
+                let key_event = input.wait_for_key_event().ok_or(Status::UNSUPPORTED)?;
+                let mut events = [key_event, unsafe { timer.unsafe_clone() }];
+                boot::wait_for_event(&mut events).discard_errdata()?;
+                input.read_key()
+            })
+        });
+    boot::close_event(timer)?;
+    result
+}
+
+/// Runs `widget` with keys from `next_key`, which returns `None` if the
+/// terminal size has to be checked before the next key arrives.
+fn event_loop<W: Widget + ?Sized>(
+    widget: &mut W,
+    term: &mut dyn Terminal,
+    theme: &Theme,
+    mut next_key: impl FnMut() -> Result<Option<Key>>,
+) -> Result<Option<W::Output>> {
+    let mut size = None;
+    let mut dirty = true;
+    loop {
+        let current = term.size()?;
+        if size != Some(current) {
+            size = Some(current);
+            term.set_color(theme.screen.foreground, theme.screen.background)?;
+            term.clear()?;
+            dirty = true;
+        }
+        if dirty {
+            let mut frame = Frame::new(term)?;
+            widget.draw(&mut frame, theme)?;
+            frame.finish()?;
+            dirty = false;
+        }
+
+        if let Some(key) = next_key()? {
+            match widget.handle_key(key) {
+                Response::Continue => dirty = true,
+                Response::Done(output) => return Ok(Some(output)),
+                Response::Cancelled => return Ok(None),
+            }
+        }
+    }
+}
+
+/// Keys that widgets react to.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+enum Action {
+    Char(char),
+    Up,
+    Down,
+    Left,
+    Right,
+    Home,
+    End,
+    PageUp,
+    PageDown,
+    Backspace,
+    Delete,
+    Tab,
+    Enter,
+    Escape,
+    Other,
+}
+
+impl From<Key> for Action {
+    fn from(key: Key) -> Self {
+        match key {
+            Key::Printable(c) => match char::from(c) {
+                '\r' | '\n' => Self::Enter,
+                '\t' => Self::Tab,
+                '\x08' => Self::Backspace,
+                '\x1b' => Self::Escape,
+                c if c.is_control() => Self::Other,
+                c => Self::Char(c),
+            },
+            Key::Special(ScanCode::UP) => Self::Up,
+            Key::Special(ScanCode::DOWN) => Self::Down,
+            Key::Special(ScanCode::LEFT) => Self::Left,
+            Key::Special(ScanCode::RIGHT) => Self::Right,
+            Key::Special(ScanCode::HOME) => Self::Home,
+            Key::Special(ScanCode::END) => Self::End,
+            Key::Special(ScanCode::PAGE_UP) => Self::PageUp,
+            Key::Special(ScanCode::PAGE_DOWN) => Self::PageDown,
+            Key::Special(ScanCode::DELETE) => Self::Delete,
+            Key::Special(ScanCode::ESCAPE) => Self::Escape,
+            Key::Special(_) => Self::Other,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Char16;
+    use alloc::rc::Rc;
+    use alloc::vec;
+    use alloc::vec::Vec;
+    use core::cell::Cell;
+
+    /// A terminal that records the characters written to it.
+    pub(super) struct TestTerminal {
+        pub(super) size: Rc<Cell<(usize, usize)>>,
+        pub(super) cells: Vec<Vec<char>>,
+        pub(super) cursor: (usize, usize),
+        pub(super) cursor_visible: bool,
+        pub(super) clears: usize,
+    }
+
+    impl TestTerminal {
+        pub(super) fn new(columns: usize, rows: usize) -> Self {
+            Self {
+                size: Rc::new(Cell::new((columns, rows))),
+                cells: vec![vec![' '; columns]; rows],
+                cursor: (0, 0),
+                cursor_visible: false,
+                clears: 0,
+            }
+        }
+
+        pub(super) fn line(&self, row: usize) -> String {
+            self.cells[row].iter().collect::<String>().trim_end().into()
+        }
+
+        pub(super) fn contains(&self, text: &str) -> bool {
+            (0..self.cells.len()).any(|row| self.line(row).contains(text))
+        }
+    }
+
+    impl Terminal for TestTerminal {
+        fn size(&self) -> Result<(usize, usize)> {
+            Ok(self.size.get())
+        }
+
+        fn clear(&mut self) -> Result {
+            let (columns, rows) = self.size.get();
+            self.cells = vec![vec![' '; columns]; rows];
+            self.clears += 1;
+            Ok(())
+        }
+
+        fn move_to(&mut self, column: usize, row: usize) -> Result {
+            let (columns, rows) = self.size.get();
+            assert!(column < columns && row < rows);
+            self.cursor = (column, row);
+            Ok(())
+        }
+
+        fn set_color(&mut self, _: Color, _: Color) -> Result {
+            Ok(())
+        }
+
+        fn write_str(&mut self, s: &str) -> Result {
+            let (columns, rows) = self.size.get();
+            for c in s.chars() {
+                let (column, row) = self.cursor;
+                assert!(column < columns, "write past the end of the line");
+                assert!(
+                    (column, row) != (columns - 1, rows - 1),
+                    "write to the last cell"
+                );
+                self.cells[row][column] = c;
+                self.cursor.0 += 1;
+            }
+            Ok(())
+        }
+
+        fn show_cursor(&mut self, visible: bool) -> Result {
+            self.cursor_visible = visible;
+            Ok(())
+        }
+    }
+
+    pub(super) fn printable(c: char) -> Key {
+        Key::Printable(Char16::try_from(c).unwrap())
+    }
+
+    /// Runs `widget` with `keys`, returning `None` between keys to simulate
+    /// timer ticks.
+    pub(super) fn run_keys<W: Widget>(
+        widget: &mut W,
+        term: &mut TestTerminal,
+        keys: &[Key],
+    ) -> Option<W::Output> {
+        let mut keys = keys.iter().flat_map(|key| [None, Some(*key)]);
+        event_loop(widget, term, &Theme::default(), || {
+            Ok(keys.next().expect("widget still running after all keys"))
+        })
+        .unwrap()
+    }
+
+    #[test]
+    fn test_rect() {
+        let rect = Rect::centered(10, 4, (80, 25));
+        assert_eq!(rect, Rect::new(35, 10, 10, 4));
+        assert_eq!(rect.inner(), Rect::new(36, 11, 8, 2));
+        assert_eq!(Rect::centered(100, 4, (80, 25)), Rect::new(0, 10, 80, 4));
+    }
+
+    #[test]
+    fn test_frame_clipping() {
+        let mut term = TestTerminal::new(10, 3);
+        let mut frame = Frame::new(&mut term).unwrap();
+        frame.write_at(7, 0, "abcdef", 6).unwrap();
+        frame.write_at(5, 2, "abcdef", 6).unwrap();
+        frame.write_at(10, 1, "abc", 3).unwrap();
+        frame.write_at(0, 1, "a\tb", 5).unwrap();
+        assert_eq!(term.line(0), "       abc");
+        assert_eq!(term.line(1), "a b");
+        assert_eq!(term.line(2), "     abcd");
+    }
+
+    #[test]
+    fn test_draw_box() {
+        let mut term = TestTerminal::new(12, 5);
+        let mut frame = Frame::new(&mut term).unwrap();
+        frame
+            .draw_box(Rect::new(1, 1, 10, 3), "Title", &Theme::default())
+            .unwrap();
+        assert_eq!(term.line(0), "");
+        assert_eq!(term.line(1), " +-Title--+");
+        assert_eq!(term.line(2), " |        |");
+        assert_eq!(term.line(3), " +--------+");
+    }
+
+    #[test]
+    fn test_redraw_on_resize() {
+        struct Resizing;
+
+        impl Widget for Resizing {
+            type Output = ();
+
+            fn draw(&mut self, frame: &mut Frame<'_>, _: &Theme) -> Result {
+                let (columns, rows) = frame.size();
+                frame.write_at(0, 0, &alloc::format!("{columns}x{rows}"), columns)
+            }
+
+            fn handle_key(&mut self, _: Key) -> Response<()> {
+                Response::Done(())
+            }
+        }
+
+        let mut term = TestTerminal::new(80, 25);
+        let size = term.size.clone();
+        let mut resized = false;
+        let result = event_loop(&mut Resizing, &mut term, &Theme::default(), || {
+            if resized {
+                Ok(Some(printable('x')))
+            } else {
+                size.set((40, 10));
+                resized = true;
+                Ok(None)
+            }
+        });
+        assert_eq!(result.unwrap(), Some(()));
+        assert_eq!(term.clears, 2);
+        assert_eq!(term.line(0), "40x10");
+    }
+}
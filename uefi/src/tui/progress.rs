@@ -0,0 +1,109 @@
+// SPDX-License-Identifier: MIT OR Apache-2.0
+
+use super::{Frame, Theme, Widget};
+use crate::Result;
+use alloc::format;
+use alloc::string::String;
+
+/// Width of the bar, including the percentage.
+const WIDTH: usize = 50;
+
+/// A bar showing the progress of an operation, e.g. `[#####     ]  50%`.
+///
+/// Progress bars don't react to keys. Draw them with [`Widget::draw`]
+/// after updating the progress:
+///
+/// ```no_run
+/// use uefi::proto::console::text::Output;
+/// use uefi::tui::{Frame, ProgressBar, Theme, Widget};
+/// use uefi::Result;
+///
+/// fn load(output: &mut Output, chunks: &[&[u8]]) -> Result {
+///     let mut bar = ProgressBar::new("Loading");
+///     for (i, _chunk) in chunks.iter().enumerate() {
+///         // ...
+///         bar.set_progress(i as u64 + 1, chunks.len() as u64);
+///         bar.draw(&mut Frame::new(output)?, &Theme::default())?;
+///     }
+///     Ok(())
+/// }
+/// ```
+#[derive(Debug)]
+pub struct ProgressBar<'a> {
+    title: &'a str,
+    percent: u8,
+}
+
+impl<'a> ProgressBar<'a> {
+    /// Creates a progress bar at 0%.
+    #[must_use]
+    pub const fn new(title: &'a str) -> Self {
+        Self { title, percent: 0 }
+    }
+
+    /// Returns the progress in percent.
+    #[must_use]
+    pub const fn percent(&self) -> u8 {
+        self.percent
+    }
+
+    /// Sets the progress to `done` out of `total` steps. A `total` of zero
+    /// counts as complete.
+    pub const fn set_progress(&mut self, done: u64, total: u64) {
+        self.percent = if total == 0 || done >= total {
+            100
+        } else {
+            (done as u128 * 100 / total as u128) as u8
+        };
+    }
+
+    /// Sets the progress in percent, which is limited to 100.
+    pub fn set_percent(&mut self, percent: u8) {
+        self.percent = percent.min(100);
+    }
+}
+
+impl Widget for ProgressBar<'_> {
+    type Output = ();
+
+    fn draw(&mut self, frame: &mut Frame<'_>, theme: &Theme) -> Result {
+        let rect = frame.centered(WIDTH + 4, 3);
+        frame.draw_box(rect, self.title, theme)?;
+
+        let inner = rect.inner();
+        let label = format!(" {:>3}%", self.percent);
+        let bar_width = inner.width.saturating_sub(label.len() + 3);
+        let filled = bar_width * usize::from(self.percent) / 100;
+
+        let mut bar = String::with_capacity(inner.width);
+        bar.push_str(" [");
+        bar.extend((0..bar_width).map(|i| if i < filled { '#' } else { ' ' }));
+        bar.push(']');
+        bar.push_str(&label);
+        frame.write_at(inner.column, inner.row, &bar, inner.width)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tui::tests::TestTerminal;
+
+    #[test]
+    fn test_progress_bar() {
+        let mut bar = ProgressBar::new("Copying");
+        bar.set_progress(1, 3);
+        assert_eq!(bar.percent(), 33);
+        bar.set_progress(0, 0);
+        assert_eq!(bar.percent(), 100);
+        bar.set_percent(200);
+        assert_eq!(bar.percent(), 100);
+
+        let mut term = TestTerminal::new(30, 5);
+        bar.set_percent(50);
+        bar.draw(&mut Frame::new(&mut term).unwrap(), &Theme::default())
+            .unwrap();
+        assert_eq!(term.line(0), "+-Copying--------------------+");
+        assert_eq!(term.line(1), "| [##########          ]  50%|");
+    }
+}
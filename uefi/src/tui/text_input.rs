@@ -0,0 +1,169 @@
+// SPDX-License-Identifier: MIT OR Apache-2.0
+
+use super::{Action, Frame, Response, Theme, Widget};
+use crate::Result;
+use crate::proto::console::text::Key;
+use alloc::string::String;
+use alloc::vec::Vec;
+
+/// Width of the text field.
+const WIDTH: usize = 40;
+
+/// A single-line text field.
+///
+/// The cursor is moved with the left and right arrow keys, Home and End.
+/// Backspace and Delete remove the character before or after the cursor.
+/// Enter confirms the text and Escape cancels the input. Text longer than
+/// the field scrolls horizontally.
+#[derive(Debug)]
+pub struct TextInput<'a> {
+    title: &'a str,
+    text: Vec<char>,
+    cursor: usize,
+    scroll: usize,
+    max_len: usize,
+    masked: bool,
+}
+
+impl<'a> TextInput<'a> {
+    /// Creates an empty text field.
+    #[must_use]
+    pub const fn new(title: &'a str) -> Self {
+        Self {
+            title,
+            text: Vec::new(),
+            cursor: 0,
+            scroll: 0,
+            max_len: usize::MAX,
+            masked: false,
+        }
+    }
+
+    /// Sets the initial text and moves the cursor to its end.
+    #[must_use]
+    pub fn with_text(mut self, text: &str) -> Self {
+        self.text = text.chars().take(self.max_len).collect();
+        self.cursor = self.text.len();
+        self
+    }
+
+    /// Limits the text to `max_len` characters.
+    #[must_use]
+    pub fn with_max_len(mut self, max_len: usize) -> Self {
+        self.max_len = max_len;
+        self.text.truncate(max_len);
+        self.cursor = self.cursor.min(max_len);
+        self
+    }
+
+    /// Shows `*` instead of the text, e.g. for passwords.
+    #[must_use]
+    pub const fn masked(mut self) -> Self {
+        self.masked = true;
+        self
+    }
+
+    /// Returns the current text.
+    #[must_use]
+    pub fn text(&self) -> String {
+        self.text.iter().collect()
+    }
+}
+
+impl Widget for TextInput<'_> {
+    type Output = String;
+
+    fn draw(&mut self, frame: &mut Frame<'_>, theme: &Theme) -> Result {
+        let rect = frame.centered(WIDTH.max(self.title.chars().count() + 4) + 4, 3);
+        frame.draw_box(rect, self.title, theme)?;
+
+        let inner = rect.inner();
+        let field = inner.width.saturating_sub(2).max(1);
+        if self.cursor < self.scroll {
+            self.scroll = self.cursor;
+        } else if self.cursor >= self.scroll + field {
+            self.scroll = self.cursor + 1 - field;
+        }
+
+        let visible: String = self.text[self.scroll..]
+            .iter()
+            .map(|&c| if self.masked { '*' } else { c })
+            .collect();
+        frame.set_style(theme.highlight)?;
+        frame.write_at(inner.column + 1, inner.row, &visible, field)?;
+        frame.set_cursor(inner.column + 1 + self.cursor - self.scroll, inner.row);
+        Ok(())
+    }
+
+    fn handle_key(&mut self, key: Key) -> Response<String> {
+        match Action::from(key) {
+            Action::Char(c) if self.text.len() < self.max_len => {
+                self.text.insert(self.cursor, c);
+                self.cursor += 1;
+            }
+            Action::Backspace if self.cursor > 0 => {
+                self.cursor -= 1;
+                self.text.remove(self.cursor);
+            }
+            Action::Delete if self.cursor < self.text.len() => {
+                self.text.remove(self.cursor);
+            }
+            Action::Left => self.cursor = self.cursor.saturating_sub(1),
+            Action::Right => self.cursor = (self.cursor + 1).min(self.text.len()),
+            Action::Home => self.cursor = 0,
+            Action::End => self.cursor = self.text.len(),
+            Action::Enter => return Response::Done(self.text()),
+            Action::Escape => return Response::Cancelled,
+            _ => {}
+        }
+        Response::Continue
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::proto::console::text::ScanCode;
+    use crate::tui::tests::{TestTerminal, printable, run_keys};
+
+    #[test]
+    fn test_text_input() {
+        let mut term = TestTerminal::new(80, 25);
+        let mut input = TextInput::new("Name").with_text("ac");
+        let keys = [
+            Key::Special(ScanCode::LEFT),
+            printable('b'),
+            Key::Special(ScanCode::HOME),
+            Key::Special(ScanCode::DELETE),
+            Key::Special(ScanCode::END),
+            printable('d'),
+            printable('\x08'),
+            printable('e'),
+            printable('\r'),
+        ];
+        assert_eq!(
+            run_keys(&mut input, &mut term, &keys).as_deref(),
+            Some("bce")
+        );
+        assert!(term.contains("| bce"));
+        assert!(term.cursor_visible);
+        assert_eq!(term.cursor.0, term.line(11).find("bce").unwrap() + 3);
+    }
+
+    #[test]
+    fn test_text_input_limits() {
+        let mut term = TestTerminal::new(80, 25);
+        let mut input = TextInput::new("PIN").with_max_len(4).masked();
+        let keys: Vec<Key> = "123456\r".chars().map(printable).collect();
+        assert_eq!(
+            run_keys(&mut input, &mut term, &keys).as_deref(),
+            Some("1234")
+        );
+        assert!(term.contains("| ****"));
+        assert!(!term.contains("1234"));
+
+        let mut input = TextInput::new("");
+        let keys = [Key::Special(ScanCode::ESCAPE)];
+        assert_eq!(run_keys(&mut input, &mut term, &keys), None);
+    }
+}
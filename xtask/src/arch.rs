@@ -10,6 +10,9 @@ pub enum UefiArch {
     #[value(name = "ia32")]
     IA32,
 
+    #[value(name = "riscv64")]
+    RiscV64,
+
     #[value(name = "x86_64")]
     X86_64,
 }
@@ -19,6 +22,7 @@ impl UefiArch {
         match self {
             Self::AArch64 => "aarch64",
             Self::IA32 => "ia32",
+            Self::RiscV64 => "riscv64",
             Self::X86_64 => "x86_64",
         }
     }
@@ -27,9 +31,17 @@ impl UefiArch {
         match self {
             Self::AArch64 => "aarch64-unknown-uefi",
             Self::IA32 => "i686-unknown-uefi",
+            Self::RiscV64 => "riscv64gc-unknown-uefi",
             Self::X86_64 => "x86_64-unknown-uefi",
         }
     }
+
+    /// Whether the target has no prebuilt standard library, so `core` and
+    /// `alloc` must be built from source with `-Zbuild-std`. This requires a
+    /// nightly toolchain with the `rust-src` component.
+    pub fn needs_build_std(self) -> bool {
+        self == Self::RiscV64
+    }
 }
 
 impl Default for UefiArch {
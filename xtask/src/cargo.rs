@@ -49,18 +49,35 @@ impl Package {
 #[derive(Clone, Copy, Debug)]
 pub enum Feature {
     // `uefi` features.
+    Acpi,
     Alloc,
+    AllocTracking,
+    Chrono,
+    Defmt,
+    EmbeddedGraphics,
+    EmbeddedIo,
+    EmbeddedStorage,
+    Fat,
+    Getrandom,
     GlobalAllocator,
     LogDebugcon,
     Logger,
+    Png,
+    RandCore,
+    Smoltcp,
+    Std,
+    Time,
     Unstable,
     PanicHandler,
     Qemu,
 
     // `uefi-test-runner` features.
+    Coverage,
     DebugSupport,
     MultiProcessor,
     Pxe,
+    ScreenshotBmp,
+    SecureBoot,
     TestUnstable,
     TpmV1,
     TpmV2,
@@ -69,17 +86,34 @@ pub enum Feature {
 impl Feature {
     fn as_str(&self) -> &'static str {
         match self {
+            Self::Acpi => "acpi",
             Self::Alloc => "alloc",
+            Self::AllocTracking => "alloc_tracking",
+            Self::Chrono => "chrono",
+            Self::Defmt => "defmt",
+            Self::EmbeddedGraphics => "embedded-graphics",
+            Self::EmbeddedIo => "embedded-io",
+            Self::EmbeddedStorage => "embedded-storage",
+            Self::Fat => "fat",
+            Self::Getrandom => "getrandom",
             Self::GlobalAllocator => "global_allocator",
             Self::LogDebugcon => "log-debugcon",
             Self::Logger => "logger",
+            Self::Png => "png",
+            Self::RandCore => "rand_core",
+            Self::Smoltcp => "smoltcp",
+            Self::Std => "std",
+            Self::Time => "time",
             Self::Unstable => "unstable",
             Self::PanicHandler => "panic_handler",
             Self::Qemu => "qemu",
 
+            Self::Coverage => "uefi-test-runner/coverage",
             Self::DebugSupport => "uefi-test-runner/debug_support",
             Self::MultiProcessor => "uefi-test-runner/multi_processor",
             Self::Pxe => "uefi-test-runner/pxe",
+            Self::ScreenshotBmp => "uefi-test-runner/screenshot_bmp",
+            Self::SecureBoot => "uefi-test-runner/secure_boot",
             Self::TestUnstable => "uefi-test-runner/unstable",
             Self::TpmV1 => "uefi-test-runner/tpm_v1",
             Self::TpmV2 => "uefi-test-runner/tpm_v2",
@@ -90,19 +124,34 @@ impl Feature {
     pub fn package_features(package: Package) -> Vec<Self> {
         match package {
             Package::Uefi => vec![
+                Self::Acpi,
                 Self::Alloc,
+                Self::AllocTracking,
+                Self::Chrono,
+                Self::Defmt,
+                Self::EmbeddedGraphics,
+                Self::EmbeddedIo,
+                Self::EmbeddedStorage,
+                Self::Fat,
                 Self::GlobalAllocator,
                 Self::LogDebugcon,
                 Self::Logger,
+                Self::Png,
+                Self::RandCore,
+                Self::Smoltcp,
+                Self::Time,
                 Self::Unstable,
                 Self::PanicHandler,
                 Self::Qemu,
             ],
             Package::UefiTestRunner => {
                 vec![
+                    Self::Coverage,
                     Self::DebugSupport,
                     Self::MultiProcessor,
                     Self::Pxe,
+                    Self::ScreenshotBmp,
+                    Self::SecureBoot,
                     Self::TestUnstable,
                     Self::TpmV1,
                     Self::TpmV2,
@@ -117,12 +166,27 @@ impl Feature {
     /// - `include_unstable` - add all functionality behind the `unstable` feature
     /// - `runtime_features` - add all functionality that effect the runtime of Rust
     pub fn more_code(include_unstable: bool, runtime_features: bool) -> Vec<Self> {
-        let mut base_features = vec![Self::Alloc, Self::LogDebugcon, Self::Logger];
+        let mut base_features = vec![
+            Self::Acpi,
+            Self::Alloc,
+            Self::Chrono,
+            Self::Defmt,
+            Self::EmbeddedGraphics,
+            Self::EmbeddedIo,
+            Self::EmbeddedStorage,
+            Self::Fat,
+            Self::LogDebugcon,
+            Self::Logger,
+            Self::Png,
+            Self::RandCore,
+            Self::Smoltcp,
+            Self::Time,
+        ];
         if include_unstable {
             base_features.extend([Self::Unstable])
         }
         if runtime_features {
-            base_features.extend([Self::GlobalAllocator])
+            base_features.extend([Self::GlobalAllocator, Self::AllocTracking])
         }
         base_features
     }
@@ -339,6 +403,12 @@ impl Cargo {
 
         if let Some(target) = self.target {
             cmd.args(["--target", target.as_triple()]);
+            if target.needs_build_std() {
+                cmd.args([
+                    "-Zbuild-std=core,alloc",
+                    "-Zbuild-std-features=compiler-builtins-mem",
+                ]);
+            }
         }
 
         if self.packages.is_empty() {
@@ -377,19 +447,19 @@ mod tests {
     fn test_comma_separated_features() {
         assert_eq!(
             Feature::comma_separated_string(&Feature::more_code(false, false)),
-            "alloc,log-debugcon,logger"
+            "acpi,alloc,chrono,defmt,embedded-graphics,embedded-io,embedded-storage,fat,log-debugcon,logger,png,rand_core,smoltcp,time"
         );
         assert_eq!(
             Feature::comma_separated_string(&Feature::more_code(false, true)),
-            "alloc,log-debugcon,logger,global_allocator"
+            "acpi,alloc,chrono,defmt,embedded-graphics,embedded-io,embedded-storage,fat,log-debugcon,logger,png,rand_core,smoltcp,time,global_allocator,alloc_tracking"
         );
         assert_eq!(
             Feature::comma_separated_string(&Feature::more_code(true, false)),
-            "alloc,log-debugcon,logger,unstable"
+            "acpi,alloc,chrono,defmt,embedded-graphics,embedded-io,embedded-storage,fat,log-debugcon,logger,png,rand_core,smoltcp,time,unstable"
         );
         assert_eq!(
             Feature::comma_separated_string(&Feature::more_code(true, true)),
-            "alloc,log-debugcon,logger,unstable,global_allocator"
+            "acpi,alloc,chrono,defmt,embedded-graphics,embedded-io,embedded-storage,fat,log-debugcon,logger,png,rand_core,smoltcp,time,unstable,global_allocator,alloc_tracking"
         );
     }
 
@@ -423,4 +493,21 @@ mod tests {
             "RUSTDOCFLAGS=-Dwarnings cargo doc --no-default-features --package uefi --package xtask --features global_allocator --no-deps --document-private-items --open"
         );
     }
+
+    #[test]
+    fn test_cargo_command_build_std() {
+        let cargo = Cargo {
+            action: CargoAction::Build,
+            features: Vec::new(),
+            packages: vec![Package::UefiTestRunner],
+            release: false,
+            target: Some(UefiArch::RiscV64),
+            warnings_as_errors: false,
+            target_types: TargetTypes::BinsExamples,
+        };
+        assert_eq!(
+            command_to_string(&cargo.command().unwrap()),
+            "cargo build --no-default-features --target riscv64gc-unknown-uefi -Zbuild-std=core,alloc -Zbuild-std-features=compiler-builtins-mem --package uefi-test-runner --bins --examples"
+        );
+    }
 }
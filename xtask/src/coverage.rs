@@ -0,0 +1,154 @@
+// SPDX-License-Identifier: MIT OR Apache-2.0
+
+//! Coverage of the VM tests.
+//!
+//! With `cargo xtask run --coverage`, uefi-test-runner is built with
+//! coverage instrumentation and writes its raw profiles to the `coverage`
+//! directory of the ESP (see `uefi-test-runner/src/coverage.rs`). After the
+//! run, the profiles are merged with `llvm-profdata` and turned into an lcov
+//! report with `llvm-cov`.
+
+use crate::opt::QemuOpt;
+use crate::util::{command_to_string, run_cmd};
+use anyhow::{Context, Result, bail};
+use std::env;
+use std::ffi::OsStr;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+/// Flags to build the test runner with coverage instrumentation. The
+/// profiler runtime of LLVM isn't available for UEFI targets, the test
+/// runner provides its own.
+const RUSTFLAGS: &str = "-Cinstrument-coverage -Zno-profiler-runtime";
+
+/// Add the instrumentation flags to the `RUSTFLAGS` of `cmd`, keeping any
+/// flags that are already set.
+pub fn add_rustflags(cmd: &mut Command) {
+    let rustflags = match env::var("RUSTFLAGS") {
+        Ok(rustflags) if !rustflags.is_empty() => format!("{rustflags} {RUSTFLAGS}"),
+        _ => RUSTFLAGS.to_string(),
+    };
+    cmd.env("RUSTFLAGS", rustflags);
+}
+
+/// Directory with the build output of the test runner.
+fn build_dir(opt: &QemuOpt) -> PathBuf {
+    let build_mode = if opt.build_mode.release {
+        "release"
+    } else {
+        "debug"
+    };
+    Path::new("target")
+        .join(opt.target.as_triple())
+        .join(build_mode)
+}
+
+/// Directory on the ESP to which the test runner writes the raw profiles.
+fn profile_dir(opt: &QemuOpt) -> PathBuf {
+    build_dir(opt).join("esp").join("coverage")
+}
+
+/// Directory of the merged profile and the report.
+fn output_dir(opt: &QemuOpt) -> PathBuf {
+    Path::new("target")
+        .join("coverage")
+        .join(opt.target.to_string())
+}
+
+/// Remove the raw profiles of an earlier run.
+pub fn remove_profiles(opt: &QemuOpt) -> Result<()> {
+    let dir = profile_dir(opt);
+    if dir.exists() {
+        fs_err::remove_dir_all(dir)?;
+    }
+    Ok(())
+}
+
+/// Merge the raw profiles of the run and create the report.
+pub fn report(opt: &QemuOpt) -> Result<()> {
+    let mut profiles = Vec::new();
+    for entry in fs_err::read_dir(profile_dir(opt)).context("no coverage data was written")? {
+        let path = entry?.path();
+        if path.extension() == Some(OsStr::new("profraw")) {
+            profiles.push(path);
+        }
+    }
+    if profiles.is_empty() {
+        bail!("no coverage data was written");
+    }
+
+    let output_dir = output_dir(opt);
+    fs_err::create_dir_all(&output_dir)?;
+    let profdata = output_dir.join("uefi-test-runner.profdata");
+
+    let mut cmd = Command::new(llvm_tool("llvm-profdata")?);
+    cmd.args(["merge", "-sparse", "-o"])
+        .arg(&profdata)
+        .args(&profiles);
+    run_cmd(cmd)?;
+
+    let image = build_dir(opt).join("uefi-test-runner.efi");
+    let llvm_cov = |subcommand: &str| -> Result<Command> {
+        let mut cmd = Command::new(llvm_tool("llvm-cov")?);
+        cmd.arg(subcommand)
+            .arg("-instr-profile")
+            .arg(&profdata)
+            // Only report the code of this repo.
+            .args(["-ignore-filename-regex", r"[/\\](\.cargo|rustc)[/\\]"])
+            .arg(&image);
+        Ok(cmd)
+    };
+
+    // Print a summary.
+    run_cmd(llvm_cov("report")?)?;
+
+    // Write the lcov data, e.g. for codecov.
+    let lcov = output_dir.join("lcov.info");
+    let mut cmd = llvm_cov("export")?;
+    cmd.arg("-format=lcov");
+    println!(
+        "run_cmd: '{}' > {}",
+        command_to_string(&cmd),
+        lcov.display()
+    );
+    let output = cmd.output()?;
+    if !output.status.success() {
+        bail!(
+            "llvm-cov export failed: {}",
+            String::from_utf8_lossy(&output.stderr)
+        );
+    }
+    fs_err::write(&lcov, output.stdout)?;
+    println!("Wrote coverage report to {}", lcov.display());
+    Ok(())
+}
+
+/// Get the path of an LLVM tool from the `llvm-tools` component of the
+/// current toolchain, falling back to the `PATH`.
+fn llvm_tool(name: &str) -> Result<PathBuf> {
+    let rustc = |arg: &str| -> Result<String> {
+        let output = Command::new("rustc").arg(arg).output()?;
+        Ok(String::from_utf8(output.stdout)?)
+    };
+    let sysroot = rustc("--print=sysroot")?;
+    let host = rustc("-vV")?
+        .lines()
+        .find_map(|line| line.strip_prefix("host: "))
+        .map(str::to_string)
+        .context("failed to get the host triple")?;
+
+    let path = Path::new(sysroot.trim())
+        .join("lib")
+        .join("rustlib")
+        .join(host)
+        .join("bin")
+        .join(name)
+        .with_extension(env::consts::EXE_EXTENSION);
+    if path.exists() {
+        Ok(path)
+    } else if crate::has_cmd(name) {
+        Ok(name.into())
+    } else {
+        bail!("{name} not found, install it with `rustup component add llvm-tools`");
+    }
+}
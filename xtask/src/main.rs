@@ -5,6 +5,7 @@
 mod arch;
 mod cargo;
 mod check_raw;
+mod coverage;
 mod device_path;
 mod disk;
 mod net;
@@ -12,6 +13,7 @@ mod opt;
 mod pipe;
 mod platform;
 mod qemu;
+mod secure_boot;
 mod tpm;
 mod util;
 
@@ -150,8 +152,9 @@ fn run_vm_tests(opt: &QemuOpt) -> Result<()> {
     let mut features = vec![];
 
     // Enable the DebugSupport test on supported platforms. Not available on
-    // AARCH64 since edk2 commit f4213fed34.
-    if *opt.target != UefiArch::AArch64 {
+    // AARCH64 since edk2 commit f4213fed34, and not implemented by the edk2
+    // RISC-V firmware.
+    if !matches!(*opt.target, UefiArch::AArch64 | UefiArch::RiscV64) {
         features.push(Feature::DebugSupport);
     }
 
@@ -167,9 +170,19 @@ fn run_vm_tests(opt: &QemuOpt) -> Result<()> {
         None => {}
     }
 
-    // Enable the multi-processor test if not targeting AARCH64, and if KVM is
+    // Enable the Secure Boot tests if requested.
+    if opt.secure_boot {
+        features.push(Feature::SecureBoot);
+    }
+
+    // Save the screenshot of the graphics test if requested.
+    if opt.screenshot_bmp {
+        features.push(Feature::ScreenshotBmp);
+    }
+
+    // Enable the multi-processor test if targeting x86, and if KVM is
     // available. KVM is available on Linux generally, but not in our CI.
-    if *opt.target != UefiArch::AArch64 && platform::is_linux() && !opt.ci {
+    if matches!(*opt.target, UefiArch::IA32 | UefiArch::X86_64) && platform::is_linux() && !opt.ci {
         features.push(Feature::MultiProcessor);
     }
 
@@ -178,6 +191,11 @@ fn run_vm_tests(opt: &QemuOpt) -> Result<()> {
         features.push(Feature::TestUnstable);
     }
 
+    // Write coverage data if requested.
+    if opt.coverage {
+        features.push(Feature::Coverage);
+    }
+
     // Build uefi-test-runner.
     let cargo = Cargo {
         action: CargoAction::Build,
@@ -188,9 +206,19 @@ fn run_vm_tests(opt: &QemuOpt) -> Result<()> {
         warnings_as_errors: false,
         target_types: TargetTypes::BinsExamples,
     };
-    run_cmd(cargo.command()?)?;
+    let mut cmd = cargo.command()?;
+    if opt.coverage {
+        coverage::add_rustflags(&mut cmd);
+        coverage::remove_profiles(opt)?;
+    }
+    run_cmd(cmd)?;
+
+    qemu::run_qemu(*opt.target, opt)?;
 
-    qemu::run_qemu(*opt.target, opt)
+    if opt.coverage {
+        coverage::report(opt)?;
+    }
+    Ok(())
 }
 
 /// Run unit tests and doctests on the host. Most of uefi-rs is tested
@@ -214,6 +242,12 @@ fn run_host_tests(test_opt: &TestOpt) -> Result<()> {
         packages.push(Package::UefiMacros);
     }
 
+    // The `std` feature can only be tested on the host, the UEFI targets
+    // don't have `std`. The `getrandom` backend needs a `--cfg` to be built
+    // for the UEFI targets.
+    let mut features = Feature::more_code(*test_opt.unstable, false);
+    features.extend([Feature::Std, Feature::Getrandom]);
+
     // Run uefi-rs and uefi-macros tests.
     let cargo = Cargo {
         action: CargoAction::Test,
@@ -221,7 +255,7 @@ fn run_host_tests(test_opt: &TestOpt) -> Result<()> {
         // the unstable feature. Because of this, we need to allow to test both variants. Runtime
         // features is set to no as it is not possible as as soon a #[global_allocator] is
         // registered, the Rust runtime executing the tests uses it as well.
-        features: Feature::more_code(*test_opt.unstable, false),
+        features,
         packages,
         release: false,
         // Use the host target so that tests can run without a VM.
@@ -1,57 +1,92 @@
 // SPDX-License-Identifier: MIT OR Apache-2.0
 
-use std::net::UdpSocket;
+//! Network services on the host that the network tests in the VM talk to.
+//!
+//! QEMU's user network forwards connections to the gateway address
+//! (192.168.17.2) to the host's loopback interface, so all services listen
+//! on 127.0.0.1.
+
+use std::io::{ErrorKind, Read, Write};
+use std::net::{TcpListener, TcpStream, UdpSocket};
 use std::sync::{Arc, Mutex};
 use std::thread::{self, JoinHandle};
 use std::time::Duration;
 
-/// Run a simple echo service that listens on UDP port 21572 and
-/// reverses the incoming messages.
-pub struct EchoService {
-    stop_requested: Arc<Mutex<bool>>,
+/// UDP port of the service that reverses the incoming messages.
+const UDP_ECHO_PORT: u16 = 21572;
+
+/// TCP port of the service that echoes the incoming data.
+const TCP_ECHO_PORT: u16 = 21573;
+
+/// TCP port of the HTTP server.
+const HTTP_PORT: u16 = 21574;
+
+/// How often the services check if a stop has been requested.
+const POLL_INTERVAL: Duration = Duration::from_millis(100);
+
+/// Body of `/hello.txt` on the HTTP server.
+const HTTP_HELLO_BODY: &[u8] = b"Hello from the uefi-rs test host!\n";
 
-    // `JoinHandle::join` consumes the handle, so put it in an option so
-    // that we can call `join` in `drop`.
-    join_handle: Option<JoinHandle<()>>,
+/// Size of `/large.bin` on the HTTP server. It's larger than a single
+/// response buffer of the firmware, so it's received in multiple parts.
+const HTTP_LARGE_SIZE: usize = 64 * 1024;
+
+type StopFlag = Arc<Mutex<bool>>;
+
+/// Run the network services for the tests:
+/// * A UDP service on port 21572 that reverses the incoming messages.
+/// * A TCP service on port 21573 that echoes the incoming data.
+/// * An HTTP server on port 21574 that serves `/hello.txt` and
+///   `/large.bin`.
+pub struct NetServices {
+    stop_requested: StopFlag,
+    join_handles: Vec<JoinHandle<()>>,
 }
 
-impl Drop for EchoService {
+impl Drop for NetServices {
     fn drop(&mut self) {
         self.stop();
-        self.join_handle
-            .take()
-            .unwrap()
-            .join()
-            .expect("failed to join echo service thread");
+        for join_handle in self.join_handles.drain(..) {
+            join_handle
+                .join()
+                .expect("failed to join network service thread");
+        }
     }
 }
 
-impl EchoService {
-    /// Start the server.
+impl NetServices {
+    /// Start the services.
     pub fn start() -> Self {
-        let stop_requested = Arc::new(Mutex::new(false));
-        let stop_requested_copy = stop_requested.clone();
-        let join_handle = thread::spawn(|| reverse_echo_service(stop_requested_copy));
+        let stop_requested = StopFlag::default();
+        let services: [fn(StopFlag); 3] = [reverse_echo_service, tcp_echo_service, http_service];
+        let join_handles = services
+            .into_iter()
+            .map(|service| {
+                let stop_requested = stop_requested.clone();
+                thread::spawn(move || service(stop_requested))
+            })
+            .collect();
         Self {
             stop_requested,
-            join_handle: Some(join_handle),
+            join_handles,
         }
     }
 
-    /// Request that the server stop.
+    /// Request that the services stop.
     pub fn stop(&self) {
         let mut guard = self.stop_requested.lock().unwrap();
         *guard = true;
     }
 }
 
-fn reverse_echo_service(stop_requested: Arc<Mutex<bool>>) {
-    let socket = UdpSocket::bind(("127.0.0.1", 21572)).expect("failed to bind to UDP socket");
+fn reverse_echo_service(stop_requested: StopFlag) {
+    let socket =
+        UdpSocket::bind(("127.0.0.1", UDP_ECHO_PORT)).expect("failed to bind to UDP socket");
 
     // Set a timeout so that the service can periodically check if a
     // stop has been requested.
     socket
-        .set_read_timeout(Some(Duration::from_millis(100)))
+        .set_read_timeout(Some(POLL_INTERVAL))
         .expect("failed to set read timeout");
 
     let mut buffer = [0; 257];
@@ -78,3 +113,115 @@ fn reverse_echo_service(stop_requested: Arc<Mutex<bool>>) {
         socket.send_to(buffer, addr).expect("failed to send packet");
     }
 }
+
+fn tcp_echo_service(stop_requested: StopFlag) {
+    serve_tcp(TCP_ECHO_PORT, &stop_requested, |mut stream| {
+        let mut buffer = [0; 4096];
+        loop {
+            match stream.read(&mut buffer) {
+                // The client closed the connection.
+                Ok(0) => return Ok(()),
+                Ok(len) => stream.write_all(&buffer[..len])?,
+                Err(err) if is_timeout(&err) => {
+                    if *stop_requested.lock().unwrap() {
+                        return Ok(());
+                    }
+                }
+                Err(err) => return Err(err),
+            }
+        }
+    })
+}
+
+fn http_service(stop_requested: StopFlag) {
+    serve_tcp(HTTP_PORT, &stop_requested, |mut stream| {
+        // Read the request head. The tests only send GET requests, which
+        // don't have a body.
+        let mut request = Vec::new();
+        let mut buffer = [0; 1024];
+        while !request.windows(4).any(|w| w == b"\r\n\r\n") {
+            match stream.read(&mut buffer) {
+                Ok(0) => return Ok(()),
+                Ok(len) => request.extend_from_slice(&buffer[..len]),
+                Err(err) if is_timeout(&err) => {
+                    if *stop_requested.lock().unwrap() {
+                        return Ok(());
+                    }
+                }
+                Err(err) => return Err(err),
+            }
+        }
+
+        let request = String::from_utf8_lossy(&request);
+        let mut request_line = request.lines().next().unwrap_or_default().split(' ');
+        let (status, body) = match (request_line.next(), request_line.next()) {
+            (Some("GET"), Some("/hello.txt")) => ("200 OK", HTTP_HELLO_BODY.to_vec()),
+            (Some("GET"), Some("/large.bin")) => ("200 OK", large_body()),
+            (Some("GET"), _) => ("404 Not Found", Vec::new()),
+            _ => ("405 Method Not Allowed", Vec::new()),
+        };
+
+        let head = format!(
+            "HTTP/1.1 {status}\r\n\
+             Content-Type: application/octet-stream\r\n\
+             Content-Length: {}\r\n\
+             Connection: close\r\n\r\n",
+            body.len()
+        );
+        stream.write_all(head.as_bytes())?;
+        stream.write_all(&body)
+    })
+}
+
+/// Contents of `/large.bin` on the HTTP server. The tests in the VM check
+/// the same pattern.
+fn large_body() -> Vec<u8> {
+    (0..HTTP_LARGE_SIZE).map(|i| (i % 251) as u8).collect()
+}
+
+/// Accept connections on `port` and handle them one after another with
+/// `handle_connection` until a stop is requested. Reads of the connection
+/// time out after [`POLL_INTERVAL`], so that the handler can check for a
+/// stop too.
+///
+/// Errors of a connection are printed and otherwise ignored, the test in
+/// the VM fails if it doesn't get the expected reply.
+fn serve_tcp(
+    port: u16,
+    stop_requested: &StopFlag,
+    mut handle_connection: impl FnMut(TcpStream) -> std::io::Result<()>,
+) {
+    let listener = TcpListener::bind(("127.0.0.1", port)).expect("failed to bind to TCP socket");
+
+    // `accept` has no timeout, so poll it instead.
+    listener
+        .set_nonblocking(true)
+        .expect("failed to make TCP socket non-blocking");
+
+    loop {
+        if *stop_requested.lock().unwrap() {
+            break;
+        }
+
+        let stream = match listener.accept() {
+            Ok((stream, _)) => stream,
+            Err(err) if err.kind() == ErrorKind::WouldBlock => {
+                thread::sleep(POLL_INTERVAL);
+                continue;
+            }
+            Err(err) => panic!("failed to accept TCP connection: {err}"),
+        };
+
+        let result = stream
+            .set_nonblocking(false)
+            .and_then(|()| stream.set_read_timeout(Some(POLL_INTERVAL)))
+            .and_then(|()| handle_connection(stream));
+        if let Err(err) = result {
+            eprintln!("TCP connection on port {port} failed: {err}");
+        }
+    }
+}
+
+fn is_timeout(err: &std::io::Error) -> bool {
+    matches!(err.kind(), ErrorKind::WouldBlock | ErrorKind::TimedOut)
+}
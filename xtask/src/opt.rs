@@ -186,6 +186,27 @@ pub struct QemuOpt {
     #[clap(long, action)]
     pub tpm: Option<TpmVersion>,
 
+    /// Enroll test keys, sign the test binaries, and run with Secure Boot
+    /// enabled. Requires an OVMF code file with Secure Boot support (and SMM
+    /// on x86), as well as `openssl`, `virt-fw-vars`, `sbsign` and efitools.
+    #[clap(long, action)]
+    pub secure_boot: bool,
+
+    /// Save a BMP screenshot of the graphics test on the ESP.
+    #[clap(long, action)]
+    pub screenshot_bmp: bool,
+
+    /// Build uefi-test-runner with coverage instrumentation and write an
+    /// lcov report of the run to `target/coverage`. Requires a nightly
+    /// toolchain with the `llvm-tools` component.
+    #[clap(long, action)]
+    pub coverage: bool,
+
+    /// Overwrite the reference screenshots in `uefi-test-runner/screenshots`
+    /// with the screenshots taken during the run, instead of comparing them.
+    #[clap(long, action)]
+    pub update_screenshots: bool,
+
     /// Path of an OVMF code file.
     #[clap(long, action, env)]
     pub ovmf_code: Option<PathBuf>,
@@ -4,6 +4,7 @@ use crate::arch::UefiArch;
 use crate::disk::{check_mbr_test_disk, create_mbr_test_disk};
 use crate::opt::QemuOpt;
 use crate::pipe::Pipe;
+use crate::secure_boot::SecureBootKeys;
 use crate::tpm::Swtpm;
 use crate::util::command_to_string;
 use crate::{net, platform};
@@ -26,11 +27,20 @@ const OVMF_PREBUILT_SOURCE: Source = Source::EDK2_STABLE202502_R2;
 /// Directory into which the prebuilts will be download (relative to the repo root).
 const OVMF_PREBUILT_DIR: &str = "target/ovmf";
 
+/// Directory of the reference screenshots (relative to the repo root).
+const SCREENSHOT_REFERENCE_DIR: &str = "uefi-test-runner/screenshots";
+
+/// Directory into which screenshots that don't match their reference are
+/// saved for inspection (relative to the repo root). CI uploads it as an
+/// artifact.
+const SCREENSHOT_OUTPUT_DIR: &str = "target/screenshots";
+
 impl From<UefiArch> for ovmf_prebuilt::Arch {
     fn from(arch: UefiArch) -> Self {
         match arch {
             UefiArch::AArch64 => Self::Aarch64,
             UefiArch::IA32 => Self::Ia32,
+            UefiArch::RiscV64 => Self::Riscv64,
             UefiArch::X86_64 => Self::X64,
         }
     }
@@ -157,8 +167,52 @@ impl Io {
     }
 }
 
-fn process_qemu_io(mut monitor_io: Io, mut serial_io: Io, tmp_dir: &Path) -> Result<()> {
+/// Result of comparing a screenshot to its reference image.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+enum ScreenshotResult {
+    Match,
+    Mismatch,
+    MissingReference,
+    Updated,
+}
+
+/// Compare the screenshot at `screenshot_path` to the reference image
+/// `name`. Screenshots that don't match are copied to
+/// [`SCREENSHOT_OUTPUT_DIR`]. If `update` is true, the reference image is
+/// overwritten instead.
+fn check_screenshot(name: &str, screenshot_path: &Path, update: bool) -> Result<ScreenshotResult> {
+    let file_name = format!("{name}.ppm");
+    let reference_file = Path::new(SCREENSHOT_REFERENCE_DIR).join(&file_name);
+    let actual = fs_err::read(screenshot_path)?;
+
+    if update {
+        fs_err::write(reference_file, actual)?;
+        return Ok(ScreenshotResult::Updated);
+    }
+
+    let result = match fs_err::read(&reference_file) {
+        Ok(expected) if expected == actual => return Ok(ScreenshotResult::Match),
+        Ok(_) => ScreenshotResult::Mismatch,
+        Err(err) if err.kind() == std::io::ErrorKind::NotFound => {
+            ScreenshotResult::MissingReference
+        }
+        Err(err) => return Err(err.into()),
+    };
+
+    let output_dir = Path::new(SCREENSHOT_OUTPUT_DIR);
+    fs_err::create_dir_all(output_dir)?;
+    fs_err::write(output_dir.join(&file_name), actual)?;
+    Ok(result)
+}
+
+fn process_qemu_io(
+    mut monitor_io: Io,
+    mut serial_io: Io,
+    tmp_dir: &Path,
+    update_screenshots: bool,
+) -> Result<()> {
     let mut tests_complete = false;
+    let mut mismatched_screenshots = Vec::new();
     let mut logging_still_working_right_before_ebs = false;
 
     // This regex is used to detect and strip ANSI escape codes. These
@@ -200,18 +254,25 @@ fn process_qemu_io(mut monitor_io: Io, mut serial_io: Io, tmp_dir: &Path) -> Res
             // Tell the VM that the screenshot was taken
             reply_ok()?;
 
-            // Compare screenshot to the reference file specified by the user.
-            // TODO: Add an operating mode where the reference is created if it doesn't exist.
-            let reference_file =
-                Path::new("uefi-test-runner/screenshots").join(format!("{reference_name}.ppm"));
-            let expected = fs_err::read(reference_file)?;
-            let actual = fs_err::read(&screenshot_path)?;
-            // Use `assert` rather than `assert_eq` here to avoid
-            // dumping a huge amount of raw pixel data on failure.
-            assert!(
-                expected == actual,
-                "screenshot does not match reference image"
-            )
+            // Compare screenshot to the reference file specified by the
+            // app. Keep running on a mismatch so that all screenshots are
+            // checked, and fail at the end.
+            match check_screenshot(reference_name, &screenshot_path, update_screenshots)? {
+                ScreenshotResult::Match => {}
+                ScreenshotResult::Mismatch => {
+                    println!("screenshot {reference_name} does not match reference image");
+                    mismatched_screenshots.push(reference_name.to_string());
+                }
+                ScreenshotResult::MissingReference => {
+                    println!(
+                        "no reference image for screenshot {reference_name}, saved it to \
+                         {SCREENSHOT_OUTPUT_DIR}; run with --update-screenshots to add it"
+                    );
+                }
+                ScreenshotResult::Updated => {
+                    println!("updated reference image for screenshot {reference_name}");
+                }
+            }
         } else if line == "TESTS_COMPLETE" {
             // The app sends this command after running its tests to
             // indicate it actually got to the end. If the tests failed
@@ -238,11 +299,22 @@ fn process_qemu_io(mut monitor_io: Io, mut serial_io: Io, tmp_dir: &Path) -> Res
         bail!("logging stopped working sometime before exiting boot services");
     }
 
+    if !mismatched_screenshots.is_empty() {
+        bail!(
+            "screenshots do not match reference images: {} (saved to {SCREENSHOT_OUTPUT_DIR})",
+            mismatched_screenshots.join(", ")
+        );
+    }
+
     Ok(())
 }
 
 /// Create an EFI boot directory to pass into QEMU.
-fn build_esp_dir(opt: &QemuOpt, ovmf_paths: &OvmfPaths) -> Result<PathBuf> {
+fn build_esp_dir(
+    opt: &QemuOpt,
+    ovmf_paths: &OvmfPaths,
+    secure_boot_keys: Option<&SecureBootKeys>,
+) -> Result<PathBuf> {
     let build_mode = if opt.build_mode.release {
         "release"
     } else {
@@ -262,6 +334,7 @@ fn build_esp_dir(opt: &QemuOpt, ovmf_paths: &OvmfPaths) -> Result<PathBuf> {
     let boot_file_name = match *opt.target {
         UefiArch::AArch64 => "BootAA64.efi",
         UefiArch::IA32 => "BootIA32.efi",
+        UefiArch::RiscV64 => "BootRISCV64.efi",
         UefiArch::X86_64 => "BootX64.efi",
     };
 
@@ -283,6 +356,21 @@ fn build_esp_dir(opt: &QemuOpt, ovmf_paths: &OvmfPaths) -> Result<PathBuf> {
         fs_err::copy(test_runner, boot_dir.join("test_runner.efi"))?;
     };
 
+    // With Secure Boot, the firmware only runs images signed with the test
+    // key. The test-runner also needs an authenticated variable update.
+    if let Some(keys) = secure_boot_keys {
+        for entry in fs_err::read_dir(&boot_dir)? {
+            let path = entry?.path();
+            if path
+                .extension()
+                .is_some_and(|ext| ext.eq_ignore_ascii_case("efi"))
+            {
+                keys.sign(&path)?;
+            }
+        }
+        keys.write_db_append(&boot_dir.join("db_append.auth"))?;
+    }
+
     Ok(esp_dir)
 }
 
@@ -311,6 +399,7 @@ pub fn run_qemu(arch: UefiArch, opt: &QemuOpt) -> Result<()> {
     let qemu_exe = match arch {
         UefiArch::AArch64 => "qemu-system-aarch64",
         UefiArch::IA32 | UefiArch::X86_64 => "qemu-system-x86_64",
+        UefiArch::RiscV64 => "qemu-system-riscv64",
     };
     let mut cmd = Command::new(qemu_exe);
 
@@ -362,9 +451,26 @@ pub fn run_qemu(arch: UefiArch, opt: &QemuOpt) -> Result<()> {
             // Graphics device.
             cmd.args(["-device", "virtio-gpu-pci"]);
         }
+        UefiArch::RiscV64 => {
+            // Use the generic RISC-V environment, which is also what the
+            // edk2 RiscVVirt firmware targets.
+            cmd.args(["-machine", "virt"]);
+
+            // The default of 128M is too small for the firmware.
+            cmd.args(["-m", "256M"]);
+
+            // Graphics device.
+            cmd.args(["-device", "virtio-gpu-pci"]);
+        }
         UefiArch::IA32 | UefiArch::X86_64 => {
-            // Use a modern machine.
-            cmd.args(["-machine", "q35"]);
+            // Use a modern machine. Secure Boot builds of OVMF protect the
+            // variable store with SMM.
+            if opt.secure_boot {
+                cmd.args(["-machine", "q35,smm=on"]);
+                cmd.args(["-global", "driver=cfi.pflash01,property=secure,value=on"]);
+            } else {
+                cmd.args(["-machine", "q35"]);
+            }
 
             // Multi-processor services protocol test needs exactly 4 CPUs.
             cmd.args(["-smp", "4"]);
@@ -415,6 +521,15 @@ pub fn run_qemu(arch: UefiArch, opt: &QemuOpt) -> Result<()> {
     #[cfg(target_os = "linux")]
     fs_err::set_permissions(&ovmf_vars, Permissions::from_mode(0o666))?;
 
+    // Enroll the test keys, which enables Secure Boot.
+    let secure_boot_keys = if opt.secure_boot {
+        let keys = SecureBootKeys::generate()?;
+        keys.enroll(&ovmf_vars)?;
+        Some(keys)
+    } else {
+        None
+    };
+
     add_pflash_args(&mut cmd, &ovmf_paths.code, PflashMode::ReadOnly);
     add_pflash_args(&mut cmd, &ovmf_vars, PflashMode::ReadWrite);
 
@@ -430,7 +545,7 @@ pub fn run_qemu(arch: UefiArch, opt: &QemuOpt) -> Result<()> {
     // Mount a local directory as a FAT partition.
     cmd.arg("-drive");
     let mut drive_arg = OsString::from("format=raw,file=fat:rw:");
-    let esp_dir = build_esp_dir(opt, &ovmf_paths)?;
+    let esp_dir = build_esp_dir(opt, &ovmf_paths, secure_boot_keys.as_ref())?;
     drive_arg.push(esp_dir);
     cmd.arg(drive_arg);
 
@@ -493,7 +608,7 @@ pub fn run_qemu(arch: UefiArch, opt: &QemuOpt) -> Result<()> {
 
     // Attach network device with DHCP configured for PXE. Skip this for
     // examples since it slows down the boot some.
-    let echo_service = if !opt.disable_network && opt.example.is_none() {
+    let net_services = if !opt.disable_network && opt.example.is_none() {
         cmd.args([
             "-netdev",
             "user,id=net0,net=192.168.17.0/24,tftp=uefi-test-runner/tftp/,bootfile=fake-boot-file",
@@ -501,7 +616,7 @@ pub fn run_qemu(arch: UefiArch, opt: &QemuOpt) -> Result<()> {
             // Some integration tests depend on this specific MAC.
             "virtio-net-pci,netdev=net0,mac=52:54:00:00:00:01",
         ]);
-        Some(net::EchoService::start())
+        Some(net::NetServices::start())
     } else {
         None
     };
@@ -533,11 +648,11 @@ pub fn run_qemu(arch: UefiArch, opt: &QemuOpt) -> Result<()> {
 
     // Capture the result to check it, but first wait for the child to
     // exit.
-    let res = process_qemu_io(monitor_io, serial_io, tmp_dir);
+    let res = process_qemu_io(monitor_io, serial_io, tmp_dir, opt.update_screenshots);
     let status = child.0.wait()?;
 
-    if let Some(echo_service) = echo_service {
-        echo_service.stop();
+    if let Some(net_services) = net_services {
+        net_services.stop();
     }
 
     // Propagate earlier error if necessary.
@@ -550,7 +665,7 @@ pub fn run_qemu(arch: UefiArch, opt: &QemuOpt) -> Result<()> {
         .context(format!("qemu was terminated by a signal: {status:?}"))?;
 
     let successful_exit_code = match arch {
-        UefiArch::AArch64 | UefiArch::IA32 => 0,
+        UefiArch::AArch64 | UefiArch::IA32 | UefiArch::RiscV64 => 0,
 
         // The x86_64 version of uefi-test-runner uses exit code 3 to
         // indicate success. See the `shutdown` function in
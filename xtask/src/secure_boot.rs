@@ -0,0 +1,113 @@
+// SPDX-License-Identifier: MIT OR Apache-2.0
+
+use crate::util::run_cmd;
+use anyhow::Result;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+use tempfile::TempDir;
+
+/// Owner GUID of the test keys in the signature databases.
+const OWNER_GUID: &str = "4b5d7a4e-3c1f-4f6b-9d2a-75652d727374";
+
+/// Test keys for running with Secure Boot enabled.
+///
+/// The keys are generated with `openssl`, enrolled in the OVMF variables
+/// with `virt-fw-vars` (from [virt-firmware]), and images are signed with
+/// `sbsign` (from [sbsigntools]). Authenticated variable updates are created
+/// with `cert-to-efi-sig-list` and `sign-efi-sig-list` (from [efitools]).
+///
+/// [virt-firmware]: https://gitlab.com/kraxel/virt-firmware
+/// [sbsigntools]: https://git.kernel.org/pub/scm/linux/kernel/git/jejb/sbsigntools.git
+/// [efitools]: https://git.kernel.org/pub/scm/linux/kernel/git/jejb/efitools.git
+pub struct SecureBootKeys {
+    tmp_dir: TempDir,
+}
+
+impl SecureBootKeys {
+    /// Generate a platform key (PK), a key exchange key (KEK), a key for
+    /// signing images (db), and a second certificate to add to db at
+    /// runtime.
+    pub fn generate() -> Result<Self> {
+        let keys = Self {
+            tmp_dir: TempDir::new()?,
+        };
+        for name in ["PK", "KEK", "db", "db2"] {
+            let mut cmd = Command::new("openssl");
+            cmd.args(["req", "-new", "-x509", "-newkey", "rsa:2048", "-nodes"])
+                .args(["-sha256", "-days", "3650"])
+                .args(["-subj", &format!("/CN=uefi-rs test {name}/")])
+                .arg("-keyout")
+                .arg(keys.key(name))
+                .arg("-out")
+                .arg(keys.cert(name));
+            run_cmd(cmd)?;
+        }
+        Ok(keys)
+    }
+
+    fn key(&self, name: &str) -> PathBuf {
+        self.tmp_dir.path().join(format!("{name}.key"))
+    }
+
+    fn cert(&self, name: &str) -> PathBuf {
+        self.tmp_dir.path().join(format!("{name}.crt"))
+    }
+
+    /// Enroll the keys in the OVMF vars file at `vars`, and enable Secure
+    /// Boot.
+    pub fn enroll(&self, vars: &Path) -> Result<()> {
+        let output = self.tmp_dir.path().join("vars.fd");
+        let mut cmd = Command::new("virt-fw-vars");
+        cmd.arg("--input")
+            .arg(vars)
+            .arg("--output")
+            .arg(&output)
+            .args(["--set-pk", OWNER_GUID])
+            .arg(self.cert("PK"))
+            .args(["--add-kek", OWNER_GUID])
+            .arg(self.cert("KEK"))
+            .args(["--add-db", OWNER_GUID])
+            .arg(self.cert("db"))
+            .arg("--secure-boot");
+        run_cmd(cmd)?;
+        fs_err::copy(output, vars)?;
+        Ok(())
+    }
+
+    /// Sign the image at `path` in place with the db key.
+    pub fn sign(&self, path: &Path) -> Result<()> {
+        let output = self.tmp_dir.path().join("signed.efi");
+        let mut cmd = Command::new("sbsign");
+        cmd.arg("--key")
+            .arg(self.key("db"))
+            .arg("--cert")
+            .arg(self.cert("db"))
+            .arg("--output")
+            .arg(&output)
+            .arg(path);
+        run_cmd(cmd)?;
+        fs_err::copy(output, path)?;
+        Ok(())
+    }
+
+    /// Write an authenticated update of the db variable to `path`, which
+    /// appends the second certificate. The update is signed with the KEK.
+    pub fn write_db_append(&self, path: &Path) -> Result<()> {
+        let esl = self.tmp_dir.path().join("db2.esl");
+        let mut cmd = Command::new("cert-to-efi-sig-list");
+        cmd.args(["-g", OWNER_GUID]).arg(self.cert("db2")).arg(&esl);
+        run_cmd(cmd)?;
+
+        let mut cmd = Command::new("sign-efi-sig-list");
+        cmd.arg("-a")
+            .args(["-g", OWNER_GUID])
+            .arg("-k")
+            .arg(self.key("KEK"))
+            .arg("-c")
+            .arg(self.cert("KEK"))
+            .arg("db")
+            .arg(&esl)
+            .arg(path);
+        run_cmd(cmd)
+    }
+}